@@ -13,6 +13,7 @@ fn make_page_info(url: &str) -> PageInfo {
         description: Some("A description for benchmarking cache performance.".to_string()),
         image: Some("https://example.com/image.jpg".to_string()),
         embed_html: None,
+        failed: false,
     }
 }
 
@@ -38,6 +38,7 @@ fn bench_render(c: &mut Criterion) {
                         file,
                         &root,
                         0, // disable oembed for benchmarks
+                        Default::default(),
                         config,
                         false, // server_mode
                         false, // transcode_enabled
@@ -45,6 +46,9 @@ fn bench_render(c: &mut Criterion) {
                         false, // mark_incomplete
                         &[],
                         None, // no wikilink index in benchmarks
+                        None, // no data files in benchmarks
+                        mbr::markdown::RendererKind::Markdown,
+                        &mbr::markdown::MarkdownOptions::default(),
                     )
                     .await
                     .unwrap()
@@ -90,7 +94,7 @@ fn bench_extract_metadata(c: &mut Criterion) {
 
         group.throughput(Throughput::Bytes(content.len() as u64));
         group.bench_with_input(BenchmarkId::new("extract", name), &file, |b, path| {
-            b.iter(|| mbr::markdown::extract_metadata_from_file(path).unwrap());
+            b.iter(|| mbr::markdown::extract_metadata_from_file(path, true).unwrap());
         });
     }
 
@@ -11,6 +11,7 @@ export * from './mbr-fuzzy-nav.ts';
 export * from './mbr-live-reload.ts';
 export * from './mbr-video-extras.ts';
 export * from './mbr-media-viewer.ts';
+export * from './mbr-media-chapters.ts';
 export * from './mbr-link-enhancement.ts';
 export * from './mbr-heading-enhancer.ts';
 export * from './mbr-footnote-preview.ts';
@@ -564,7 +564,10 @@ export class MbrBrowseSingleElement extends LitElement {
       const tagCounts = new Map<string, number>();
 
       for (const file of files) {
-        const tags = this._extractTagsFromFile(file, source.field);
+        // Merge values from every field of the source, counting each page once per tag
+        const tags = new Set(
+          [source.field, ...(source.fields ?? [])]
+            .flatMap(field => this._extractTagsFromFile(file, field)));
         for (const tag of tags) {
           tagCounts.set(tag, (tagCounts.get(tag) || 0) + 1);
         }
@@ -720,6 +723,7 @@ export class MbrBrowseSingleElement extends LitElement {
         ` : nothing}
       </div>
       <nav class="sidebar-nav" aria-label="Site navigation">
+        <slot name="site-nav"></slot>
         ${this._isLoading ? this._renderLoading() :
           this._loadError ? this._renderError() : html`
             ${this._renderHomeLink()}
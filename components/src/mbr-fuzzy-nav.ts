@@ -1,6 +1,6 @@
 import { LitElement, html, css, nothing, type TemplateResult } from 'lit';
 import { customElement, state, query } from 'lit/decorators.js';
-import { resolveUrl, subscribeSiteNav, isNewTabModifier, openInNewTab } from './shared.js';
+import { fetchPageLinks, resolveUrl, subscribeSiteNav, isNewTabModifier, openInNewTab } from './shared.js';
 
 /**
  * Markdown file from site.json.
@@ -20,6 +20,8 @@ interface Heading {
   level: number;
   id: string;
   text: string;
+  /** Outline number (e.g. "1.2") when `toc_numbered` is enabled. */
+  number?: string;
 }
 
 /**
@@ -39,6 +41,8 @@ interface InboundLink {
   from: string;
   text: string;
   anchor?: string;
+  context?: string;
+  count?: number;
 }
 
 /**
@@ -201,23 +205,8 @@ export class MbrFuzzyNavElement extends LitElement {
     this._linksError = null;
 
     try {
-      const currentPath = window.location.pathname;
-      const normalizedPath = currentPath.endsWith('/') ? currentPath : currentPath + '/';
-      const linksUrl = normalizedPath + 'links.json';
-
-      const response = await fetch(linksUrl);
-
-      if (!response.ok) {
-        if (response.status === 404) {
-          // Link tracking disabled
-          this._links = { inbound: [], outbound: [] };
-          this._linksCache = this._links;
-          return;
-        }
-        throw new Error(`Failed to load links: ${response.status}`);
-      }
-
-      this._links = await response.json() as PageLinks;
+      // Link tracking disabled when there's no links.json
+      this._links = await fetchPageLinks<PageLinks>() ?? { inbound: [], outbound: [] };
       this._linksCache = this._links;
     } catch (error) {
       console.warn('Failed to load links:', error);
@@ -470,7 +459,7 @@ export class MbrFuzzyNavElement extends LitElement {
 
       return {
         id: `toc-${index}`,
-        text: heading.text,
+        text: heading.number ? `${heading.number} ${heading.text}` : heading.text,
         url: `#${heading.id}`,
         type: 'heading' as const,
         level: heading.level,
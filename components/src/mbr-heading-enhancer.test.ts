@@ -285,4 +285,17 @@ describe('MbrHeadingEnhancerElement._enhance', () => {
     const h2 = main.querySelector<HTMLElement>('#k')!
     expect(h2.classList.contains('mbr-section-collapsed')).toBe(false)
   })
+
+  it('keeps a permalink rendered by the server instead of adding another', () => {
+    main.innerHTML = `<h2 id="k">K<a class="mbr-heading-anchor" href="#k" aria-label="Permalink">#</a></h2><p id="p1">one</p>`
+    document.body.appendChild(enhancer)
+    runEnhance()
+
+    const h2 = main.querySelector<HTMLElement>('#k')!
+    expect(h2.querySelectorAll('a.mbr-heading-anchor').length).toBe(1)
+    expect(h2.classList.contains('mbr-collapsible')).toBe(true)
+
+    h2.querySelector<HTMLElement>('a.mbr-heading-anchor')!.click()
+    expect(h2.classList.contains('mbr-section-collapsed')).toBe(false)
+  })
 })
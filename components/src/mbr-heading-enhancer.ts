@@ -60,18 +60,21 @@ export class MbrHeadingEnhancerElement extends LitElement {
       if (heading.classList.contains(ENHANCED_CLASS)) return
       heading.classList.add(ENHANCED_CLASS)
 
+      // Headings may already carry a permalink (e.g. from the HeadingAnchors
+      // post-render hook), so only add one when missing.
       const id = heading.getAttribute('id')
-      if (id) {
-        const anchor = document.createElement('a')
+      let anchor = heading.querySelector<HTMLAnchorElement>(`a.${ANCHOR_CLASS}`)
+      if (id && !anchor) {
+        anchor = document.createElement('a')
         anchor.className = ANCHOR_CLASS
         anchor.href = `#${id}`
         anchor.setAttribute('aria-label', 'Permalink')
         anchor.textContent = '#'
-        // Prevent the heading's collapse handler from firing when users
-        // click the permalink to copy or navigate to the anchor.
-        anchor.addEventListener('click', (event) => event.stopPropagation())
         heading.appendChild(anchor)
       }
+      // Prevent the heading's collapse handler from firing when users
+      // click the permalink to copy or navigate to the anchor.
+      anchor?.addEventListener('click', (event) => event.stopPropagation())
 
       const hasInnerLink = heading.querySelector(`a:not(.${ANCHOR_CLASS})`) !== null
       const insideLink = heading.closest('a') !== null
@@ -1,6 +1,6 @@
 import { LitElement, html, css, nothing, type TemplateResult } from 'lit';
 import { customElement, state } from 'lit/decorators.js';
-import { getTagSources, resolveUrl, subscribeSiteNav, type TagSourceConfig } from './shared.js';
+import { fetchPageLinks, getTagSources, resolveUrl, subscribeSiteNav, type TagSourceConfig } from './shared.js';
 import {
   buildRegistry,
   capitalize,
@@ -29,6 +29,10 @@ interface InboundLink {
   from: string;
   text: string;
   anchor?: string;
+  /** Text surrounding the link in the source page */
+  context?: string;
+  /** Number of links from the source page to this one */
+  count?: number;
 }
 
 interface PageLinks {
@@ -58,6 +62,7 @@ declare global {
     frontmatter?: Frontmatter;
     headings?: Heading[];
     extendedMeta?: ExtendedMeta;
+    activeHeading?: string | null;
   }
   interface HTMLElementTagNameMap {
     'mbr-info': MbrInfoElement;
@@ -79,6 +84,10 @@ export class MbrInfoElement extends LitElement {
   @state()
   private _headings: Heading[] = [];
 
+  /** Heading of the section in view, set by the scroll spy script. */
+  @state()
+  private _activeHeading: string | null = null;
+
   @state()
   private _links: PageLinks | null = null;
 
@@ -108,10 +117,12 @@ export class MbrInfoElement extends LitElement {
   override connectedCallback() {
     super.connectedCallback();
     document.addEventListener('keydown', this._handleKeydown);
+    document.addEventListener('mbr-active-heading', this._handleActiveHeading);
 
     // Load frontmatter and headings from window
     this._frontmatter = window.frontmatter || {};
     this._headings = window.headings || [];
+    this._activeHeading = window.activeHeading ?? null;
     this._extendedMeta = window.extendedMeta || null;
 
     // Pull relationship type labels from site.json (already loaded globally).
@@ -126,9 +137,14 @@ export class MbrInfoElement extends LitElement {
   override disconnectedCallback() {
     super.disconnectedCallback();
     document.removeEventListener('keydown', this._handleKeydown);
+    document.removeEventListener('mbr-active-heading', this._handleActiveHeading);
     this._unsubscribeSiteNav?.();
   }
 
+  private _handleActiveHeading = (e: Event) => {
+    this._activeHeading = (e as CustomEvent<{ id: string | null }>).detail.id;
+  };
+
   private _handleKeydown = (e: KeyboardEvent) => {
     // Ctrl+g or Cmd+g to toggle
     if ((e.ctrlKey || e.metaKey) && e.key === 'g') {
@@ -167,24 +183,8 @@ export class MbrInfoElement extends LitElement {
     this._linksError = null;
 
     try {
-      // Get current path and construct links.json URL
-      const currentPath = window.location.pathname;
-      // Ensure path ends with / for directory-style URLs
-      const normalizedPath = currentPath.endsWith('/') ? currentPath : currentPath + '/';
-      const linksUrl = normalizedPath + 'links.json';
-
-      const response = await fetch(linksUrl);
-
-      if (!response.ok) {
-        if (response.status === 404) {
-          // Link tracking may be disabled - not an error
-          this._links = { inbound: [], outbound: [] };
-          return;
-        }
-        throw new Error(`Failed to load links: ${response.status}`);
-      }
-
-      this._links = await response.json() as PageLinks;
+      // Link tracking may be disabled (no links.json) - not an error
+      this._links = await fetchPageLinks<PageLinks>() ?? { inbound: [], outbound: [] };
     } catch (error) {
       console.warn('Failed to load links:', error);
       this._linksError = error instanceof Error ? error.message : 'Unknown error';
@@ -241,7 +241,8 @@ export class MbrInfoElement extends LitElement {
    */
   private _getTagSourceForField(field: string): TagSourceConfig | undefined {
     const lowerField = field.toLowerCase();
-    return getTagSources().find(ts => ts.field.toLowerCase() === lowerField);
+    return getTagSources().find(ts =>
+      [ts.field, ...(ts.fields ?? [])].some(f => f.toLowerCase() === lowerField));
   }
 
   /**
@@ -452,7 +453,12 @@ export class MbrInfoElement extends LitElement {
           <ul class="toc-list">
             ${this._headings.map(heading => html`
               <li class="toc-item toc-level-${heading.level}">
-                <a href="#${heading.id}" @click=${() => this._close()}>${heading.text}</a>
+                <a
+                  href="#${heading.id}"
+                  class=${heading.id === this._activeHeading ? 'active' : ''}
+                  aria-current=${heading.id === this._activeHeading ? 'location' : nothing}
+                  @click=${() => this._close()}
+                >${heading.text}</a>
               </li>
             `)}
           </ul>
@@ -653,7 +659,10 @@ export class MbrInfoElement extends LitElement {
                 <a href="${link.from}" class="link-url" @click=${() => this._close()}>
                   <span class="link-source">${link.from}</span>
                 </a>
-                ${link.text ? html`<span class="link-text">"${link.text}"</span>` : nothing}
+                ${(link.count ?? 1) > 1 ? html`<span class="link-count">×${link.count}</span>` : nothing}
+                ${link.context
+                  ? this._renderLinkContext(link.context, link.text)
+                  : link.text ? html`<span class="link-text">"${link.text}"</span>` : nothing}
                 ${link.anchor ? html`<span class="link-anchor">${link.anchor}</span>` : nothing}
               </li>
             `)}
@@ -663,6 +672,17 @@ export class MbrInfoElement extends LitElement {
     `;
   }
 
+  /**
+   * Render a backlink's surrounding text with the link text highlighted.
+   */
+  private _renderLinkContext(context: string, text: string): TemplateResult {
+    const idx = text ? context.indexOf(text) : -1;
+    if (idx < 0) {
+      return html`<span class="link-context">${context}</span>`;
+    }
+    return html`<span class="link-context">${context.slice(0, idx)}<mark>${text}</mark>${context.slice(idx + text.length)}</span>`;
+  }
+
   private _renderPageNavSection(): TemplateResult | typeof nothing {
     const meta = this._extendedMeta;
     if (!meta || (!meta.prevPage && !meta.nextPage)) {
@@ -942,6 +962,12 @@ export class MbrInfoElement extends LitElement {
       text-decoration: none;
     }
 
+    .toc-item a.active {
+      color: var(--pico-primary, #1095c1);
+      font-weight: 600;
+      box-shadow: inset 3px 0 0 var(--pico-primary, #1095c1);
+    }
+
     /* Indentation for different heading levels */
     .toc-level-1 {
       padding-left: 0;
@@ -1043,6 +1069,19 @@ export class MbrInfoElement extends LitElement {
       margin-top: 0.15rem;
     }
 
+    .link-context {
+      display: block;
+      font-size: 0.85em;
+      color: var(--pico-muted-color, #666);
+      margin-top: 0.15rem;
+    }
+
+    .link-context mark {
+      background: none;
+      color: var(--pico-color, inherit);
+      font-weight: 600;
+    }
+
     .link-anchor {
       display: inline-block;
       font-size: 0.8em;
@@ -0,0 +1,54 @@
+import { describe, it, expect, afterEach } from 'vitest'
+import './mbr-media-chapters.ts'
+import { activeChapterIndex, type MbrMediaChaptersElement } from './mbr-media-chapters.ts'
+
+const chapters = [
+  { start: 0, title: 'Intro' },
+  { start: 30, title: 'Setup' },
+  { start: 95, title: 'Questions' },
+]
+
+describe('activeChapterIndex', () => {
+  it('returns the last chapter that has started', () => {
+    expect(activeChapterIndex(chapters, 0)).toBe(0)
+    expect(activeChapterIndex(chapters, 29.9)).toBe(0)
+    expect(activeChapterIndex(chapters, 30)).toBe(1)
+    expect(activeChapterIndex(chapters, 600)).toBe(2)
+  })
+
+  it('returns -1 before the first chapter', () => {
+    expect(activeChapterIndex([{ start: 10, title: 'Late' }], 5)).toBe(-1)
+  })
+})
+
+describe('mbr-media-chapters', () => {
+  afterEach(() => {
+    document.body.innerHTML = ''
+  })
+
+  it('seeks the nearby player when a chapter is clicked', async () => {
+    document.body.innerHTML = `
+      <audio src="/song.mp3"></audio>
+      <mbr-media-chapters></mbr-media-chapters>
+    `
+    const audio = document.querySelector('audio')!
+    audio.play = () => Promise.resolve()
+    const el = document.querySelector('mbr-media-chapters') as MbrMediaChaptersElement
+    el.chapters = chapters
+    await el.updateComplete
+
+    const buttons = el.shadowRoot!.querySelectorAll('button')
+    expect(buttons).toHaveLength(3)
+    expect(buttons[2].textContent).toContain('1:35')
+
+    buttons[1].click()
+    expect(audio.currentTime).toBe(30)
+  })
+
+  it('renders nothing without chapters', async () => {
+    document.body.innerHTML = '<mbr-media-chapters></mbr-media-chapters>'
+    const el = document.querySelector('mbr-media-chapters') as MbrMediaChaptersElement
+    await el.updateComplete
+    expect(el.shadowRoot!.querySelector('nav')).toBeNull()
+  })
+})
@@ -0,0 +1,195 @@
+import { LitElement, html, css, nothing, type CSSResultGroup } from 'lit';
+import { customElement, property, state } from 'lit/decorators.js';
+
+/**
+ * A chapter marker as emitted by `src/chapters.rs` (start is in seconds).
+ */
+export interface MediaChapter {
+  start: number;
+  title: string;
+}
+
+/**
+ * Format seconds as MM:SS or HH:MM:SS.
+ */
+function formatTime(seconds: number): string {
+  const hrs = Math.floor(seconds / 3600);
+  const mins = Math.floor((seconds % 3600) / 60);
+  const secs = Math.floor(seconds % 60);
+
+  if (hrs > 0) {
+    return `${hrs}:${mins.toString().padStart(2, '0')}:${secs.toString().padStart(2, '0')}`;
+  }
+  return `${mins}:${secs.toString().padStart(2, '0')}`;
+}
+
+/**
+ * Index of the chapter playing at `time`, or -1 before the first chapter.
+ * Chapters are expected to be sorted by start time.
+ */
+export function activeChapterIndex(chapters: MediaChapter[], time: number): number {
+  let active = -1;
+  for (let i = 0; i < chapters.length; i++) {
+    if (chapters[i].start <= time) {
+      active = i;
+    } else {
+      break;
+    }
+  }
+  return active;
+}
+
+/**
+ * Clickable chapter list that seeks an audio or video player.
+ *
+ * Chapters come from the `chapters` property (set by the media viewer from a
+ * `.chapters.json` sidecar) or, on markdown pages, from the `chapters:`
+ * frontmatter key. The controlled player defaults to the first `<audio>` or
+ * `<video>` in the same document or shadow root.
+ *
+ * @example
+ * ```html
+ * <mbr-media-chapters></mbr-media-chapters>
+ * ```
+ */
+@customElement('mbr-media-chapters')
+export class MbrMediaChaptersElement extends LitElement {
+  static override styles: CSSResultGroup = css`
+    :host {
+      display: block;
+      margin: 1rem 0;
+      font-size: 0.9em;
+    }
+
+    ol {
+      list-style: none;
+      margin: 0;
+      padding: 0;
+    }
+
+    li {
+      margin: 0;
+      padding: 0;
+      list-style: none;
+    }
+
+    button {
+      display: flex;
+      gap: 0.75em;
+      width: 100%;
+      margin: 0;
+      padding: 0.3em 0.5em;
+      background: none;
+      border: none;
+      border-radius: 4px;
+      color: inherit;
+      font: inherit;
+      text-align: left;
+      cursor: pointer;
+    }
+
+    button:hover {
+      background: var(--pico-secondary-background, rgba(0, 0, 0, 0.05));
+    }
+
+    li.active button {
+      color: var(--pico-primary, #1095c1);
+      font-weight: 600;
+    }
+
+    .time {
+      font-family: ui-monospace, SFMono-Regular, "SF Mono", Menlo, Consolas, monospace;
+      color: var(--pico-muted-color, #666);
+      min-width: 4.5em;
+    }
+  `;
+
+  /**
+   * Chapter list; falls back to `window.frontmatter.chapters` when unset.
+   */
+  @property({ type: Array })
+  chapters: MediaChapter[] | null = null;
+
+  /**
+   * Player to control; defaults to the first audio/video element nearby.
+   */
+  @property({ attribute: false })
+  media: HTMLMediaElement | null = null;
+
+  @state()
+  private _activeIndex = -1;
+
+  private _boundMedia: HTMLMediaElement | null = null;
+
+  override connectedCallback(): void {
+    super.connectedCallback();
+    if (!this.chapters) {
+      const fromFrontmatter = window.frontmatter?.chapters;
+      if (Array.isArray(fromFrontmatter)) {
+        this.chapters = fromFrontmatter as MediaChapter[];
+      }
+    }
+  }
+
+  override disconnectedCallback(): void {
+    super.disconnectedCallback();
+    this._bindMedia(null);
+  }
+
+  override updated(): void {
+    this._bindMedia(this._findMedia());
+  }
+
+  private _findMedia(): HTMLMediaElement | null {
+    if (this.media) return this.media;
+    const root = this.getRootNode() as Document | ShadowRoot;
+    return root.querySelector?.('audio, video') ?? null;
+  }
+
+  private _bindMedia(media: HTMLMediaElement | null): void {
+    if (media === this._boundMedia) return;
+    this._boundMedia?.removeEventListener('timeupdate', this._handleTimeUpdate);
+    this._boundMedia = media;
+    media?.addEventListener('timeupdate', this._handleTimeUpdate);
+  }
+
+  private _handleTimeUpdate = (): void => {
+    if (!this._boundMedia || !this.chapters) return;
+    this._activeIndex = activeChapterIndex(this.chapters, this._boundMedia.currentTime);
+  };
+
+  private _seek(chapter: MediaChapter): void {
+    const media = this._findMedia();
+    if (!media) return;
+    this._bindMedia(media);
+    media.currentTime = chapter.start;
+    media.play().catch(() => {
+      // Autoplay may be blocked; the seek still happened
+    });
+  }
+
+  override render() {
+    if (!this.chapters || this.chapters.length === 0) return nothing;
+
+    return html`
+      <nav aria-label="Chapters">
+        <ol>
+          ${this.chapters.map((chapter, i) => html`
+            <li class="${i === this._activeIndex ? 'active' : ''}">
+              <button type="button" @click="${() => this._seek(chapter)}">
+                <span class="time">${formatTime(chapter.start)}</span>
+                <span class="title">${chapter.title}</span>
+              </button>
+            </li>
+          `)}
+        </ol>
+      </nav>
+    `;
+  }
+}
+
+declare global {
+  interface HTMLElementTagNameMap {
+    'mbr-media-chapters': MbrMediaChaptersElement;
+  }
+}
@@ -1,6 +1,8 @@
 import { LitElement, html, css, nothing, type CSSResultGroup, type TemplateResult } from 'lit';
 import { customElement, property, state } from 'lit/decorators.js';
 import { resolveUrl } from './shared.js';
+import type { MediaChapter } from './mbr-media-chapters.js';
+import './mbr-media-chapters.js';
 
 /**
  * Supported media types for the viewer.
@@ -15,6 +17,17 @@ export interface MediaViewerProps {
   mediaType: MediaType;
 }
 
+declare global {
+  interface Window {
+    __MBR_MEDIA_VIEWER__?: {
+      mediaType?: string;
+      title?: string;
+      /** Chapter markers keyed by absolute media path */
+      chapters?: Record<string, MediaChapter[]>;
+    };
+  }
+}
+
 /**
  * Media viewer component that renders video, PDF, or audio content.
  *
@@ -250,6 +263,12 @@ export class MbrMediaViewerElement extends LitElement {
   @state()
   private _path: string | null = null;
 
+  /**
+   * Chapter markers for the current media, if the page embedded any.
+   */
+  @state()
+  private _chapters: MediaChapter[] | null = null;
+
   /**
    * Error message if path is invalid.
    */
@@ -300,6 +319,7 @@ export class MbrMediaViewerElement extends LitElement {
       // Ensure path starts with / then resolve for static builds
       const absPath = path.startsWith('/') ? path : '/' + path;
       this._path = resolveUrl(absPath);
+      this._chapters = window.__MBR_MEDIA_VIEWER__?.chapters?.[absPath] ?? null;
       this._error = null;
       this._loading = false;
     } catch (e) {
@@ -339,6 +359,7 @@ export class MbrMediaViewerElement extends LitElement {
         <figcaption>
           <mbr-video-extras src="${this._path}"></mbr-video-extras>
         </figcaption>
+        ${this._renderChapters()}
       </figure>
     `;
   }
@@ -441,10 +462,19 @@ export class MbrMediaViewerElement extends LitElement {
         <div class="audio-info">
           <span>${filename}</span>
         </div>
+        ${this._renderChapters()}
       </div>
     `;
   }
 
+  /**
+   * Render the chapter list for the current media, if any.
+   */
+  private _renderChapters(): TemplateResult | typeof nothing {
+    if (!this._chapters || this._chapters.length === 0) return nothing;
+    return html`<mbr-media-chapters .chapters=${this._chapters}></mbr-media-chapters>`;
+  }
+
   /**
    * Render image content with native img element.
    */
@@ -10,12 +10,7 @@
 import { LitElement, nothing } from 'lit'
 import { customElement } from 'lit/decorators.js'
 import { waitForDom, loadScript, getMbrAssetBase } from './dynamic-loader.ts'
-
-/** Mermaid initialization options type */
-interface MermaidConfig {
-  startOnLoad: boolean
-  theme: string
-}
+import { getMermaidOptions } from './shared.ts'
 
 /** Options for mermaid.run() */
 interface MermaidRunOptions {
@@ -25,7 +20,7 @@ interface MermaidRunOptions {
 /** Window with mermaid global */
 interface WindowWithMermaid extends Window {
   mermaid?: {
-    initialize: (config: MermaidConfig) => void
+    initialize: (config: Record<string, unknown>) => void
     run: (options: MermaidRunOptions) => Promise<void>
   }
 }
@@ -57,13 +52,9 @@ export class MbrMermaidElement extends LitElement {
 
     // Initialize mermaid and manually trigger rendering
     // Using startOnLoad: false + explicit run() avoids race conditions
-    const prefersDark = window.matchMedia('(prefers-color-scheme: dark)').matches
     const mermaid = (window as WindowWithMermaid).mermaid
 
-    mermaid?.initialize({
-      startOnLoad: false,
-      theme: prefersDark ? 'dark' : 'default',
-    })
+    mermaid?.initialize(getMermaidOptions())
 
     // Manually render the diagrams we found
     mermaid?.run({
@@ -15,7 +15,7 @@ interface BrokenInternalLinkError {
 interface BrokenMediaReferenceError {
   type: 'broken_media_reference';
   src: string;
-  kind: 'image' | 'video' | 'audio' | 'source';
+  kind: 'image' | 'video' | 'audio' | 'source' | 'track';
 }
 
 interface UnresolvedWikilinkError {
@@ -17,7 +17,7 @@ import { LitElement, html, css, nothing, type PropertyValues } from 'lit'
 import { customElement, property, state } from 'lit/decorators.js'
 import { unsafeHTML } from 'lit/directives/unsafe-html.js'
 import { waitForDom, loadScript, getMbrAssetBase } from './dynamic-loader.ts'
-import { subscribeSiteNav, getCanonicalPath, resolveUrl } from './shared.ts'
+import { subscribeSiteNav, getCanonicalPath, resolveUrl, getMermaidOptions } from './shared.ts'
 
 // ============================================================================
 // site.json data shapes (subset we consume)
@@ -663,7 +663,7 @@ export function clientPointToSvg(
 // ============================================================================
 
 interface MermaidApi {
-  initialize: (config: { startOnLoad: boolean; theme: string }) => void
+  initialize: (config: Record<string, unknown>) => void
   render: (id: string, text: string) => Promise<{ svg: string; bindFunctions?: (el: Element) => void }>
 }
 
@@ -787,8 +787,7 @@ export class MbrRelationshipsElement extends LitElement {
       await loadScript(`${getMbrAssetBase()}mermaid.min.js`)
       const mermaid = (window as unknown as WindowWithMermaid).mermaid
       if (!mermaid) return
-      const prefersDark = window.matchMedia('(prefers-color-scheme: dark)').matches
-      mermaid.initialize({ startOnLoad: false, theme: prefersDark ? 'dark' : 'default' })
+      mermaid.initialize(getMermaidOptions())
       const renderId = `mbr-rel-${Math.random().toString(36).slice(2)}`
       const { svg } = await mermaid.render(renderId, source)
       this._svg = svg
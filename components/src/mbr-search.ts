@@ -1,5 +1,5 @@
 import { LitElement, css, html, nothing, type TemplateResult } from 'lit'
-import { customElement, state, query } from 'lit/decorators.js'
+import { customElement, property, state, query } from 'lit/decorators.js'
 import { unsafeHTML } from 'lit/directives/unsafe-html.js'
 import { getBasePath, resolveUrl, isNewTabModifier, openInNewTab } from './shared.js'
 import type { MbrMediaBrowserElement } from './mbr-media-browser.js'
@@ -127,6 +127,18 @@ function getCurrentFolder(): string {
  */
 @customElement('mbr-search')
 export class MbrSearchElement extends LitElement {
+  /** Placeholder text for the search input (`search_placeholder`). */
+  @property({ type: String })
+  placeholder = 'Search files...';
+
+  /** Maximum number of results to show (`search_result_limit`). */
+  @property({ type: Number, attribute: 'result-limit' })
+  resultLimit = 20;
+
+  /** Directory holding the Pagefind bundle in static builds. */
+  @property({ type: String, attribute: 'pagefind-path' })
+  pagefindPath = '';
+
   @state()
   private _query = '';
 
@@ -220,8 +232,8 @@ export class MbrSearchElement extends LitElement {
       try {
         // Load Pagefind from the .mbr assets location
         // Use URL() to resolve relative to page, not the component module
-        const basePath = getBasePath();
-        const pagefindUrl = new URL(basePath + '.mbr/pagefind/pagefind.js', window.location.href).href;
+        const pagefindPath = this.pagefindPath || getBasePath() + '.mbr/pagefind/';
+        const pagefindUrl = new URL(pagefindPath + 'pagefind.js', window.location.href).href;
         const pagefind = await import(/* @vite-ignore */ pagefindUrl) as Pagefind;
         // Configure baseUrl and ranking to prioritize title/filename matches
         await pagefind.options({
@@ -453,7 +465,7 @@ export class MbrSearchElement extends LitElement {
       // Build search request with folder context
       const searchBody: Record<string, any> = {
         q: this._query,
-        limit: 20,
+        limit: this.resultLimit,
         scope: this._scope,
         folder_scope: this._folderScope,
       };
@@ -519,8 +531,8 @@ export class MbrSearchElement extends LitElement {
       const searchResponse = await pagefind.search(this._query);
       this._totalMatches = searchResponse.results.length;
 
-      // Load data for the first 20 results
-      const resultPromises = searchResponse.results.slice(0, 20).map(r => r.data());
+      // Load data for the first resultLimit results
+      const resultPromises = searchResponse.results.slice(0, this.resultLimit).map(r => r.data());
       const resultData = await Promise.all(resultPromises);
 
       // Map Pagefind results to our format
@@ -612,7 +624,7 @@ export class MbrSearchElement extends LitElement {
               <input
                 id="search-input"
                 type="search"
-                placeholder="Search files..."
+                placeholder=${this.placeholder}
                 .value=${this._query}
                 @input=${this._handleInput}
                 @keydown=${this._handleKeydown}
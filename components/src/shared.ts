@@ -3,6 +3,8 @@
  */
 export interface TagSourceConfig {
   field: string;
+  /** Additional frontmatter fields merged into this source. */
+  fields?: string[];
   urlSource: string;
   label: string;
   labelPlural: string;
@@ -20,6 +22,8 @@ declare global {
       searchEndpoint?: string;
       basePath?: string;
       tagSources?: TagSourceConfig[];
+      /** `mermaid.initialize()` options from `mermaid_theme`/`mermaid_config`. */
+      mermaid?: Record<string, unknown>;
     };
   }
 }
@@ -91,6 +95,20 @@ export function getTagSources(): TagSourceConfig[] {
   return window.__MBR_CONFIG__?.tagSources ?? [];
 }
 
+/**
+ * Get the options for `mermaid.initialize()`.
+ * The theme follows light/dark mode unless `mermaid_theme` or
+ * `mermaid_config` sets one; diagrams are always rendered explicitly.
+ */
+export function getMermaidOptions(): Record<string, unknown> {
+  const prefersDark = window.matchMedia('(prefers-color-scheme: dark)').matches
+  return {
+    theme: prefersDark ? 'dark' : 'default',
+    ...window.__MBR_CONFIG__?.mermaid,
+    startOnLoad: false,
+  }
+}
+
 /**
  * Get the canonical path from window.location.pathname.
  *
@@ -152,6 +170,39 @@ export function getCanonicalPath(): string {
   return canonical.endsWith('/') || canonical === '/' ? canonical : canonical + '/';
 }
 
+let consolidatedLinks: Promise<Record<string, unknown> | null> | null = null;
+
+/**
+ * Fetch the current page's links.json data.
+ *
+ * Static builds with `link_index_mode = "consolidated"` have no per-page
+ * files, so a 404 falls back to this page's entry in the site-wide
+ * `.mbr/links.json` (fetched once). Resolves to null when neither exists,
+ * e.g. when link tracking is disabled.
+ */
+export async function fetchPageLinks<T>(): Promise<T | null> {
+  const currentPath = window.location.pathname;
+  // Ensure path ends with / for directory-style URLs
+  const normalizedPath = currentPath.endsWith('/') ? currentPath : currentPath + '/';
+  const response = await fetch(normalizedPath + 'links.json');
+
+  if (response.ok) {
+    return await response.json() as T;
+  }
+  if (response.status !== 404) {
+    throw new Error(`Failed to load links: ${response.status}`);
+  }
+  if (window.__MBR_CONFIG__?.serverMode) {
+    return null;
+  }
+
+  consolidatedLinks ??= fetch(getBasePath() + '.mbr/links.json')
+    .then((resp) => (resp.ok ? resp.json() : null))
+    .catch(() => null);
+  const allLinks = await consolidatedLinks;
+  return (allLinks?.[getCanonicalPath()] as T | undefined) ?? null;
+}
+
 /**
  * Reactive state for site navigation loading.
  * Components can subscribe to changes via the callback pattern.
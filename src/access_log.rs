@@ -0,0 +1,320 @@
+//! Per-request access logging for the server.
+//!
+//! Every request is recorded with its method, path, status, response size and
+//! duration once its body has been sent (or the client went away). The size
+//! is the body bytes actually sent, after compression. Lines go to
+//! `access_log` when that file is configured, appended by a writer thread of
+//! their own, and otherwise to the `mbr::access_log` tracing target at
+//! `info` level (visible with `-v`).
+//!
+//! Two formats are available via `access_log_format`:
+//! - `human`: `GET /docs/ 200 5123B 3.2ms`
+//! - `combined`: Apache/NCSA combined log format, for log analyzers
+
+use std::{
+    fs::File,
+    io::Write,
+    net::SocketAddr,
+    path::Path,
+    pin::Pin,
+    sync::mpsc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Local};
+use http_body::{Frame, SizeHint};
+
+/// Line format for the access log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccessLogFormat {
+    /// Short, readable lines: method, path, status, size and duration.
+    #[default]
+    Human,
+    /// Apache/NCSA combined log format.
+    Combined,
+}
+
+impl AccessLogFormat {
+    /// Parses a config value ("human" or "combined").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Self::Human),
+            "combined" => Some(Self::Combined),
+            _ => None,
+        }
+    }
+}
+
+/// What gets recorded about a single request.
+#[derive(Debug, Clone)]
+pub struct AccessLogEntry {
+    pub remote: Option<SocketAddr>,
+    pub time: DateTime<Local>,
+    pub method: String,
+    /// Path plus query string, as requested.
+    pub target: String,
+    pub version: String,
+    pub status: u16,
+    /// Response body bytes sent, when known.
+    pub bytes: Option<u64>,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+    pub duration: Duration,
+}
+
+impl AccessLogEntry {
+    /// Renders the entry as a single line (without a trailing newline).
+    pub fn format(&self, format: AccessLogFormat) -> String {
+        match format {
+            AccessLogFormat::Human => {
+                let bytes = self
+                    .bytes
+                    .map_or_else(|| "-".to_string(), |b| format!("{b}B"));
+                format!(
+                    "{} {} {} {} {:.1}ms",
+                    self.method,
+                    self.target,
+                    self.status,
+                    bytes,
+                    self.duration.as_secs_f64() * 1000.0
+                )
+            }
+            AccessLogFormat::Combined => {
+                let host = self
+                    .remote
+                    .map_or_else(|| "-".to_string(), |addr| addr.ip().to_string());
+                let bytes = self
+                    .bytes
+                    .map_or_else(|| "-".to_string(), |b| b.to_string());
+                format!(
+                    "{} - - [{}] \"{} {} {}\" {} {} \"{}\" \"{}\"",
+                    host,
+                    self.time.format("%d/%b/%Y:%H:%M:%S %z"),
+                    self.method,
+                    escape_quoted(&self.target),
+                    self.version,
+                    self.status,
+                    bytes,
+                    self.referer.as_deref().map_or("-".into(), escape_quoted),
+                    self.user_agent.as_deref().map_or("-".into(), escape_quoted),
+                )
+            }
+        }
+    }
+}
+
+/// Escapes a value for a double-quoted combined-log field.
+fn escape_quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Where access log lines go and how they are formatted.
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    format: AccessLogFormat,
+    /// Lines for the thread appending to the log file.
+    file: Option<mpsc::Sender<String>>,
+}
+
+impl AccessLog {
+    /// Logs to the tracing output.
+    pub fn new(format: AccessLogFormat) -> Self {
+        Self { format, file: None }
+    }
+
+    /// Appends to `path`, creating it if needed. Writes happen on a thread
+    /// of their own, so a slow disk never holds up a request; it exits once
+    /// every clone of the log is dropped.
+    pub fn to_file(format: AccessLogFormat, path: &Path) -> std::io::Result<Self> {
+        let mut file = File::options().create(true).append(true).open(path)?;
+        let (tx, rx) = mpsc::channel::<String>();
+        std::thread::Builder::new()
+            .name("mbr-access-log".to_string())
+            .spawn(move || {
+                for line in rx {
+                    if let Err(e) = writeln!(file, "{line}") {
+                        tracing::warn!("Failed to write access log: {e}");
+                    }
+                }
+            })?;
+        Ok(Self {
+            format,
+            file: Some(tx),
+        })
+    }
+
+    fn write(&self, entry: &AccessLogEntry) {
+        let line = entry.format(self.format);
+        match &self.file {
+            // Only fails if the writer thread is gone, which it warned about
+            Some(tx) => {
+                let _ = tx.send(line);
+            }
+            None => tracing::info!("{line}"),
+        }
+    }
+}
+
+fn header_string(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Axum middleware recording one access log line per request.
+pub async fn log_requests(State(log): State<AccessLog>, req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let time = Local::now();
+    let remote = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let method = req.method().to_string();
+    let target = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_string(), |pq| pq.to_string());
+    let version = format!("{:?}", req.version());
+    let headers = req.headers();
+    let referer = header_string(headers, header::REFERER);
+    let user_agent = header_string(headers, header::USER_AGENT);
+
+    let response = next.run(req).await;
+
+    let entry = AccessLogEntry {
+        remote,
+        time,
+        method,
+        target,
+        version,
+        status: response.status().as_u16(),
+        bytes: None,
+        referer,
+        user_agent,
+        duration: Duration::ZERO,
+    };
+    response.map(|body| {
+        Body::new(LoggedBody {
+            inner: body,
+            sent: 0,
+            pending: Some((log, entry, start)),
+        })
+    })
+}
+
+/// A response body that counts the bytes sent and writes the request's log
+/// line when it's dropped: after the last frame, or when the client leaves.
+struct LoggedBody {
+    inner: Body,
+    sent: u64,
+    pending: Option<(AccessLog, AccessLogEntry, Instant)>,
+}
+
+impl HttpBody for LoggedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll
+            && let Some(data) = frame.data_ref()
+        {
+            self.sent += data.len() as u64;
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for LoggedBody {
+    fn drop(&mut self) {
+        if let Some((log, mut entry, start)) = self.pending.take() {
+            entry.bytes = Some(self.sent);
+            entry.duration = start.elapsed();
+            log.write(&entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry() -> AccessLogEntry {
+        AccessLogEntry {
+            remote: Some("192.168.1.5:51234".parse().unwrap()),
+            time: Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap(),
+            method: "GET".to_string(),
+            target: "/docs/guide/?q=1".to_string(),
+            version: "HTTP/1.1".to_string(),
+            status: 200,
+            bytes: Some(5123),
+            referer: None,
+            user_agent: Some("curl/8.0 \"test\"".to_string()),
+            duration: Duration::from_micros(3200),
+        }
+    }
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(
+            AccessLogFormat::from_name("human"),
+            Some(AccessLogFormat::Human)
+        );
+        assert_eq!(
+            AccessLogFormat::from_name("combined"),
+            Some(AccessLogFormat::Combined)
+        );
+        assert_eq!(AccessLogFormat::from_name("json"), None);
+    }
+
+    #[test]
+    fn test_human_format() {
+        assert_eq!(
+            entry().format(AccessLogFormat::Human),
+            "GET /docs/guide/?q=1 200 5123B 3.2ms"
+        );
+
+        let unknown_size = AccessLogEntry {
+            bytes: None,
+            ..entry()
+        };
+        assert!(
+            unknown_size
+                .format(AccessLogFormat::Human)
+                .contains(" 200 - ")
+        );
+    }
+
+    #[test]
+    fn test_combined_format() {
+        let line = entry().format(AccessLogFormat::Combined);
+        let offset = entry().time.format("%z").to_string();
+        assert_eq!(
+            line,
+            format!(
+                "192.168.1.5 - - [09/Mar/2024:14:05:07 {offset}] \"GET /docs/guide/?q=1 HTTP/1.1\" 200 5123 \"-\" \"curl/8.0 \\\"test\\\"\""
+            )
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Date-based archive listings for static builds.
+//!
+//! With `build_archive_pages` enabled, every page with a publish date
+//! (frontmatter `created`, else `date`) is grouped by year and month, and the
+//! build writes a listing page for each group:
+//!
+//! - `/archive/`: every year, each listing its posts
+//! - `/archive/2024/`: one year, grouped by month
+//! - `/archive/2024/03/`: a single month
+//!
+//! Groups and the posts within them are newest first. Dates are read in UTC,
+//! matching the normalized timestamps on [`MarkdownInfo`].
+
+use chrono::{DateTime, Datelike};
+
+use crate::repo::MarkdownInfo;
+
+/// URL segment the archive pages live under.
+pub const ARCHIVE_SOURCE: &str = "archive";
+
+/// A dated page listed in the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivePage {
+    pub url_path: String,
+    pub title: String,
+    /// Frontmatter `description`, if any.
+    pub description: Option<String>,
+    /// Publish date as a Unix timestamp (UTC).
+    pub date: i64,
+    pub word_count: usize,
+}
+
+impl ArchivePage {
+    /// The archive entry for a page, or `None` when it has no publish date
+    /// or redirects elsewhere.
+    pub fn from_info(info: &MarkdownInfo) -> Option<Self> {
+        if info.is_redirect() {
+            return None;
+        }
+        let date = info.created_date?;
+        let empty = crate::markdown::SimpleMetadata::new();
+        let frontmatter = info.frontmatter.as_ref().unwrap_or(&empty);
+        Some(Self {
+            url_path: info.url_path.clone(),
+            title: crate::repo::get_page_title(frontmatter, &info.raw_path),
+            description: frontmatter
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            date,
+            word_count: info.word_count,
+        })
+    }
+}
+
+/// The pages published in one month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMonth {
+    pub year: i32,
+    /// 1-based month number.
+    pub month: u32,
+    pub pages: Vec<ArchivePage>,
+}
+
+impl ArchiveMonth {
+    /// URL of the month's listing, e.g. `/archive/2024/03/`.
+    pub fn url_path(&self) -> String {
+        format!("/{ARCHIVE_SOURCE}/{}/{:02}/", self.year, self.month)
+    }
+
+    /// Display label, e.g. "March 2024".
+    pub fn label(&self) -> String {
+        chrono::NaiveDate::from_ymd_opt(self.year, self.month, 1)
+            .map(|d| d.format("%B %Y").to_string())
+            .unwrap_or_else(|| format!("{}-{:02}", self.year, self.month))
+    }
+}
+
+/// The pages published in one year, by month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveYear {
+    pub year: i32,
+    pub months: Vec<ArchiveMonth>,
+}
+
+impl ArchiveYear {
+    /// URL of the year's listing, e.g. `/archive/2024/`.
+    pub fn url_path(&self) -> String {
+        format!("/{ARCHIVE_SOURCE}/{}/", self.year)
+    }
+
+    /// Number of pages published in the year.
+    pub fn page_count(&self) -> usize {
+        self.months.iter().map(|m| m.pages.len()).sum()
+    }
+
+    /// The year's pages, newest first.
+    pub fn pages(&self) -> impl Iterator<Item = &ArchivePage> {
+        self.months.iter().flat_map(|m| &m.pages)
+    }
+}
+
+/// One section of an archive listing: a year or month with its pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveGroup<'a> {
+    pub label: String,
+    pub url_path: String,
+    pub pages: Vec<&'a ArchivePage>,
+}
+
+impl<'a> From<&'a ArchiveYear> for ArchiveGroup<'a> {
+    fn from(year: &'a ArchiveYear) -> Self {
+        Self {
+            label: year.year.to_string(),
+            url_path: year.url_path(),
+            pages: year.pages().collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a ArchiveMonth> for ArchiveGroup<'a> {
+    fn from(month: &'a ArchiveMonth) -> Self {
+        Self {
+            label: month.label(),
+            url_path: month.url_path(),
+            pages: month.pages.iter().collect(),
+        }
+    }
+}
+
+/// Groups pages into years and months, newest first. Pages published at
+/// the same time are ordered by title, then URL.
+pub fn group_by_date(pages: impl IntoIterator<Item = ArchivePage>) -> Vec<ArchiveYear> {
+    let mut pages: Vec<(i32, u32, ArchivePage)> = pages
+        .into_iter()
+        .filter_map(|page| {
+            let dt = DateTime::from_timestamp(page.date, 0)?;
+            Some((dt.year(), dt.month(), page))
+        })
+        .collect();
+    pages.sort_by(|(_, _, a), (_, _, b)| {
+        b.date
+            .cmp(&a.date)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+            .then_with(|| a.url_path.cmp(&b.url_path))
+    });
+
+    let mut years: Vec<ArchiveYear> = Vec::new();
+    for (year, month, page) in pages {
+        if years.last().is_none_or(|y| y.year != year) {
+            years.push(ArchiveYear {
+                year,
+                months: Vec::new(),
+            });
+        }
+        let Some(current_year) = years.last_mut() else {
+            continue;
+        };
+        if current_year.months.last().is_none_or(|m| m.month != month) {
+            current_year.months.push(ArchiveMonth {
+                year,
+                month,
+                pages: Vec::new(),
+            });
+        }
+        if let Some(current_month) = current_year.months.last_mut() {
+            current_month.pages.push(page);
+        }
+    }
+    years
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(url_path: &str, title: &str, date: &str) -> ArchivePage {
+        ArchivePage {
+            url_path: url_path.to_string(),
+            title: title.to_string(),
+            description: None,
+            date: crate::dates::parse_date(date).unwrap(),
+            word_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_group_by_date_newest_first() {
+        let years = group_by_date(vec![
+            page("/a/", "A", "2023-12-31"),
+            page("/b/", "B", "2024-03-05"),
+            page("/c/", "C", "2024-01-10"),
+            page("/d/", "D", "2024-03-20"),
+        ]);
+
+        assert_eq!(
+            years.iter().map(|y| y.year).collect::<Vec<_>>(),
+            [2024, 2023]
+        );
+        let months: Vec<u32> = years[0].months.iter().map(|m| m.month).collect();
+        assert_eq!(months, [3, 1]);
+        let march: Vec<&str> = years[0].months[0]
+            .pages
+            .iter()
+            .map(|p| p.title.as_str())
+            .collect();
+        assert_eq!(march, ["D", "B"]);
+        assert_eq!(years[0].page_count(), 3);
+        assert_eq!(years[1].page_count(), 1);
+    }
+
+    #[test]
+    fn test_same_date_sorts_by_title() {
+        let years = group_by_date(vec![
+            page("/z/", "zebra", "2024-05-01"),
+            page("/a/", "Apple", "2024-05-01"),
+        ]);
+        let titles: Vec<&str> = years[0].pages().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Apple", "zebra"]);
+    }
+
+    #[test]
+    fn test_urls_and_labels() {
+        let years = group_by_date(vec![page("/a/", "A", "2024-03-05T10:00:00Z")]);
+        assert_eq!(years[0].url_path(), "/archive/2024/");
+        assert_eq!(years[0].months[0].url_path(), "/archive/2024/03/");
+        assert_eq!(years[0].months[0].label(), "March 2024");
+    }
+}
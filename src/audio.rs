@@ -5,9 +5,17 @@ static EXTENSION_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\.([0-9a-zA-Z]+)([?#].*)?$").expect("Invalid EXTENSION_RE regex pattern")
 });
 
+static AUDIO_SOURCE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<source src="([^"]*)" type="audio/[^"]*">"#)
+        .expect("Invalid AUDIO_SOURCE_RE regex pattern")
+});
+
 /// Audio file extensions we support
 const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "ogg", "flac", "aac", "m4a", "webm"];
 
+/// Large lossless formats worth transcoding for the browser.
+const TRANSCODABLE_AUDIO_EXTENSIONS: &[&str] = &["wav", "flac", "aiff", "aif"];
+
 #[derive(Debug, PartialEq, Default)]
 pub struct Audio {
     pub url: String,
@@ -75,10 +83,155 @@ impl Audio {
     }
 }
 
+/// Web-friendly codecs that lossless audio can be transcoded to.
+///
+/// Transcoded files are addressed by appending the extension to the original
+/// name (`song.wav` → `song.wav.opus`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioTranscodeFormat {
+    /// AAC in an ADTS stream (plays everywhere, including Safari)
+    Aac,
+    /// Opus in an Ogg container (smaller at the same quality)
+    Opus,
+}
+
+impl AudioTranscodeFormat {
+    /// Parses a `config.audio_transcode` value (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "aac" => Some(Self::Aac),
+            "opus" => Some(Self::Opus),
+            _ => None,
+        }
+    }
+
+    /// File extension appended to the original name.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Aac => "aac",
+            Self::Opus => "opus",
+        }
+    }
+
+    /// MIME type used for the `<source type="...">` attribute and responses.
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Aac => "audio/aac",
+            Self::Opus => "audio/ogg; codecs=opus",
+        }
+    }
+
+    /// ffmpeg encoder name.
+    pub const fn encoder_name(&self) -> &'static str {
+        match self {
+            Self::Aac => "aac",
+            Self::Opus => "libopus",
+        }
+    }
+
+    /// ffmpeg muxer name for the output container.
+    pub const fn muxer_name(&self) -> &'static str {
+        match self {
+            Self::Aac => "adts",
+            Self::Opus => "ogg",
+        }
+    }
+}
+
+/// Returns true for audio files large enough to be worth transcoding (WAV/FLAC/AIFF).
+pub fn is_transcodable_audio(path: &str) -> bool {
+    Audio::extension_from_url(path)
+        .is_some_and(|ext| TRANSCODABLE_AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Parses a request path for a transcoded audio file.
+///
+/// Returns the original audio path when `path` is the original name with the
+/// extension of `format` appended (`music/song.flac.opus` → `music/song.flac`).
+pub fn parse_audio_transcode_request(path: &str, format: AudioTranscodeFormat) -> Option<&str> {
+    let source = path.strip_suffix(format.extension())?.strip_suffix('.')?;
+    is_transcodable_audio(source).then_some(source)
+}
+
+/// Site path of an audio `src` on the page served at `page_url`: resolved the
+/// way the browser would, percent-decoded and without its leading `/`.
+///
+/// Returns `None` for a remote or otherwise non-site URL, which the server
+/// can't transcode.
+pub fn local_source_path(src: &str, page_url: &str) -> Option<String> {
+    static SITE: LazyLock<url::Url> =
+        LazyLock::new(|| url::Url::parse("http://site.invalid/").expect("valid site URL"));
+    let page = SITE.join(page_url).ok()?;
+    let source = page.join(src).ok()?;
+    if source.origin() != SITE.origin() {
+        return None;
+    }
+    let path = percent_encoding::percent_decode_str(source.path()).decode_utf8_lossy();
+    Some(path.trim_start_matches('/').to_string())
+}
+
+/// Adds a transcoded `<source>` ahead of each lossless audio source.
+///
+/// `format_for` receives the decoded `src` of each audio `<source>` and returns
+/// the transcoded format available for it, if any. Browsers pick the first
+/// playable source, so the original stays as the fallback.
+pub fn add_transcoded_sources(
+    html: &str,
+    format_for: impl Fn(&str) -> Option<AudioTranscodeFormat>,
+) -> String {
+    AUDIO_SOURCE_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let source_tag = &caps[0];
+            let src = &caps[1];
+            let decoded = html_escape::decode_html_entities(src);
+            if !is_transcodable_audio(&decoded) {
+                return source_tag.to_string();
+            }
+            match format_for(&decoded) {
+                Some(format) => format!(
+                    r#"<source src="{}.{}" type="{}">{}"#,
+                    src,
+                    format.extension(),
+                    format.mime_type(),
+                    source_tag
+                ),
+                None => source_tag.to_string(),
+            }
+        })
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_local_source_path() {
+        assert_eq!(
+            local_source_path("song.flac", "/music/album/").as_deref(),
+            Some("music/album/song.flac")
+        );
+        assert_eq!(
+            local_source_path("../My%20Song.flac", "/music/album/").as_deref(),
+            Some("music/My Song.flac")
+        );
+        assert_eq!(
+            local_source_path("/audio/a.wav", "/music/").as_deref(),
+            Some("audio/a.wav")
+        );
+        assert_eq!(
+            local_source_path("../../../etc/a.wav", "/music/").as_deref(),
+            Some("etc/a.wav"),
+            "can't climb above the site root"
+        );
+        assert_eq!(
+            local_source_path("https://cdn.example.com/a.flac", "/"),
+            None
+        );
+        assert_eq!(local_source_path("//cdn.example.com/a.flac", "/"), None);
+        assert_eq!(local_source_path("data:audio/wav;base64,AAAA", "/"), None);
+    }
+
     #[test]
     fn test_from_url_and_title_mp3() {
         let audio = Audio::from_url_and_title("podcast.mp3", "Episode 1").unwrap();
@@ -167,4 +320,69 @@ mod tests {
         assert!(Audio::from_url_and_title("song.MP3", "Test").is_some());
         assert!(Audio::from_url_and_title("song.Mp3", "Test").is_some());
     }
+
+    #[test]
+    fn test_audio_transcode_format_from_name() {
+        assert_eq!(
+            AudioTranscodeFormat::from_name("opus"),
+            Some(AudioTranscodeFormat::Opus)
+        );
+        assert_eq!(
+            AudioTranscodeFormat::from_name("AAC"),
+            Some(AudioTranscodeFormat::Aac)
+        );
+        assert_eq!(AudioTranscodeFormat::from_name("mp3"), None);
+    }
+
+    #[test]
+    fn test_is_transcodable_audio() {
+        assert!(is_transcodable_audio("song.wav"));
+        assert!(is_transcodable_audio("music/Song.FLAC"));
+        assert!(is_transcodable_audio("take.aiff"));
+        assert!(!is_transcodable_audio("song.mp3"));
+        assert!(!is_transcodable_audio("song.opus"));
+        assert!(!is_transcodable_audio("noext"));
+    }
+
+    #[test]
+    fn test_parse_audio_transcode_request() {
+        assert_eq!(
+            parse_audio_transcode_request("music/song.flac.opus", AudioTranscodeFormat::Opus),
+            Some("music/song.flac")
+        );
+        assert_eq!(
+            parse_audio_transcode_request("song.wav.aac", AudioTranscodeFormat::Aac),
+            Some("song.wav")
+        );
+        // Wrong format for the configured codec
+        assert_eq!(
+            parse_audio_transcode_request("song.wav.aac", AudioTranscodeFormat::Opus),
+            None
+        );
+        // Original is not a lossless format
+        assert_eq!(
+            parse_audio_transcode_request("song.mp3.opus", AudioTranscodeFormat::Opus),
+            None
+        );
+        assert_eq!(
+            parse_audio_transcode_request("song.opus", AudioTranscodeFormat::Opus),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_transcoded_sources() {
+        let wav = Audio::from_url_and_title("/audio/take%201.wav", "").unwrap();
+        let mp3 = Audio::from_url_and_title("/audio/song.mp3", "").unwrap();
+        let html = format!("{}{}", wav.to_html(false), mp3.to_html(false));
+
+        let result = add_transcoded_sources(&html, |_| Some(AudioTranscodeFormat::Opus));
+        assert!(result.contains(
+            r#"<source src="/audio/take%201.wav.opus" type="audio/ogg; codecs=opus"><source src="/audio/take%201.wav" type="audio/wav">"#
+        ));
+        assert!(!result.contains("song.mp3.opus"));
+
+        let untouched = add_transcoded_sources(&html, |_| None);
+        assert_eq!(untouched, html);
+    }
 }
@@ -0,0 +1,331 @@
+//! Whole-file audio transcoding to web-friendly codecs.
+//!
+//! Large lossless files (WAV, FLAC, AIFF) are re-encoded to AAC or Opus so the
+//! browser downloads a fraction of the bytes. Server mode transcodes on demand
+//! and caches the result in memory; builds pre-generate the files next to the
+//! originals.
+//!
+//! ## URL Patterns
+//!
+//! - `/music/song.flac` - Original audio (served directly)
+//! - `/music/song.flac.opus` - Opus transcode of the original
+//! - `/music/song.flac.aac` - AAC transcode of the original
+
+use crate::audio::AudioTranscodeFormat;
+use crate::video_transcode::{AUDIO_TEMP_PREFIX, TranscodeError, create_transcode_temp_file};
+use ffmpeg_next as ffmpeg;
+use std::path::Path;
+
+/// Conversion factor from kilobits per second to bits per second.
+const KBPS_TO_BPS: usize = 1000;
+
+/// Sample rate required by the Opus encoder.
+const OPUS_SAMPLE_RATE: i32 = 48_000;
+
+/// Create a uniquely-named temporary file for the muxed output in `tmp_dir`
+/// (or the system temp directory).
+///
+/// The file is removed automatically when the returned handle is dropped.
+fn create_audio_temp_file(
+    format: AudioTranscodeFormat,
+    tmp_dir: Option<&Path>,
+) -> Result<tempfile::NamedTempFile, TranscodeError> {
+    create_transcode_temp_file(
+        tempfile::Builder::new()
+            .prefix(AUDIO_TEMP_PREFIX)
+            .suffix(&format!(".{}", format.extension())),
+        tmp_dir,
+    )
+}
+
+/// Build the `abuffer -> abuffersink` graph that converts decoded frames into
+/// the encoder's sample format, rate, layout and (for fixed-size codecs such
+/// as AAC) frame size.
+fn build_filter_graph(
+    decoder: &ffmpeg::decoder::Audio,
+    decoder_layout: ffmpeg::ChannelLayout,
+    in_time_base: ffmpeg::Rational,
+    encoder: &ffmpeg::encoder::Audio,
+) -> Result<ffmpeg::filter::Graph, ffmpeg::Error> {
+    let mut graph = ffmpeg::filter::Graph::new();
+
+    let args = format!(
+        "time_base={}:sample_rate={}:sample_fmt={}:channel_layout=0x{:x}",
+        in_time_base,
+        decoder.rate(),
+        decoder.format().name(),
+        decoder_layout.bits()
+    );
+
+    let abuffer = ffmpeg::filter::find("abuffer").ok_or(ffmpeg::Error::FilterNotFound)?;
+    let abuffersink = ffmpeg::filter::find("abuffersink").ok_or(ffmpeg::Error::FilterNotFound)?;
+    graph.add(&abuffer, "in", &args)?;
+    graph.add(&abuffersink, "out", "")?;
+
+    {
+        let mut out = graph.get("out").ok_or(ffmpeg::Error::FilterNotFound)?;
+        out.set_sample_format(encoder.format());
+        out.set_channel_layout(encoder.channel_layout());
+        out.set_sample_rate(encoder.rate());
+    }
+
+    graph.output("in", 0)?.input("out", 0)?.parse("anull")?;
+    graph.validate()?;
+
+    if let Some(codec) = encoder.codec()
+        && !codec
+            .capabilities()
+            .contains(ffmpeg::codec::capabilities::Capabilities::VARIABLE_FRAME_SIZE)
+    {
+        graph
+            .get("out")
+            .ok_or(ffmpeg::Error::FilterNotFound)?
+            .sink()
+            .set_frame_size(encoder.frame_size());
+    }
+
+    Ok(graph)
+}
+
+/// Transcode the best audio stream of `source_path` to `format` at
+/// `bitrate_kbps`, returning the muxed file contents.
+///
+/// Runs synchronously and decodes the whole file, so callers in async context
+/// should use `spawn_blocking`. Intermediate output goes to `tmp_dir` (the
+/// configured `transcode_tmp_dir`), or the system temp directory when `None`.
+pub fn transcode_audio(
+    source_path: &Path,
+    format: AudioTranscodeFormat,
+    bitrate_kbps: u32,
+    tmp_dir: Option<&Path>,
+) -> Result<Vec<u8>, TranscodeError> {
+    let failed =
+        |what: &str, e: ffmpeg::Error| TranscodeError::TranscodeFailed(format!("{what}: {e}"));
+
+    tracing::info!(
+        "Transcoding audio {} to {} @ {}kbps",
+        source_path.display(),
+        format.extension(),
+        bitrate_kbps
+    );
+
+    // Muxed output goes through a temp file; removed when `temp_file` drops.
+    let temp_file = create_audio_temp_file(format, tmp_dir)?;
+    let temp_path = temp_file.path();
+
+    let mut input_ctx =
+        ffmpeg::format::input(source_path).map_err(|e| TranscodeError::OpenFailed {
+            path: source_path.to_path_buf(),
+            source: e,
+        })?;
+
+    let (audio_stream_index, stream_time_base, codec_params) = {
+        let stream = input_ctx
+            .streams()
+            .best(ffmpeg::media::Type::Audio)
+            .ok_or_else(|| TranscodeError::NoAudioStream {
+                path: source_path.to_path_buf(),
+            })?;
+        (stream.index(), stream.time_base(), stream.parameters())
+    };
+
+    let decoder_ctx = ffmpeg::codec::context::Context::from_parameters(codec_params)
+        .map_err(|e| failed("Failed to create audio decoder context", e))?;
+    let mut decoder = decoder_ctx
+        .decoder()
+        .audio()
+        .map_err(|e| failed("Failed to create audio decoder", e))?;
+
+    // Uncompressed sources often carry no explicit layout; derive one from the
+    // channel count so the filter graph has something to describe.
+    let decoder_layout = if decoder.channel_layout().is_empty() {
+        ffmpeg::ChannelLayout::default(i32::from(decoder.channels()))
+    } else {
+        decoder.channel_layout()
+    };
+
+    // Decode in a 1/sample_rate time base so frame timestamps are sample counts.
+    let in_time_base = ffmpeg::Rational::new(1, decoder.rate() as i32);
+
+    let mut output_ctx = ffmpeg::format::output_as(temp_path, format.muxer_name())
+        .map_err(|e| failed("Failed to create audio output", e))?;
+    let global_header = output_ctx
+        .format()
+        .flags()
+        .contains(ffmpeg::format::flag::Flags::GLOBAL_HEADER);
+
+    let encoder_codec = ffmpeg::encoder::find_by_name(format.encoder_name())
+        .ok_or_else(|| TranscodeError::EncoderNotAvailable(format.encoder_name().to_string()))?
+        .audio()
+        .map_err(|e| failed("Encoder is not an audio encoder", e))?;
+
+    let encoder_ctx = ffmpeg::codec::context::Context::new_with_codec(*encoder_codec);
+    let mut encoder_setup = encoder_ctx
+        .encoder()
+        .audio()
+        .map_err(|e| failed("Failed to create audio encoder", e))?;
+
+    let encoder_rate = match format {
+        AudioTranscodeFormat::Opus => OPUS_SAMPLE_RATE,
+        AudioTranscodeFormat::Aac => decoder.rate() as i32,
+    };
+    let encoder_layout = encoder_codec
+        .channel_layouts()
+        .map(|layouts| layouts.best(decoder_layout.channels()))
+        .unwrap_or(ffmpeg::ChannelLayout::STEREO);
+    let encoder_format = encoder_codec
+        .formats()
+        .and_then(|mut formats| formats.next())
+        .ok_or_else(|| {
+            TranscodeError::TranscodeFailed(format!(
+                "{} reports no supported sample formats",
+                format.encoder_name()
+            ))
+        })?;
+
+    if global_header {
+        encoder_setup.set_flags(ffmpeg::codec::flag::Flags::GLOBAL_HEADER);
+    }
+    encoder_setup.set_rate(encoder_rate);
+    encoder_setup.set_channel_layout(encoder_layout);
+    encoder_setup.set_format(encoder_format);
+    encoder_setup.set_bit_rate(bitrate_kbps as usize * KBPS_TO_BPS);
+    encoder_setup.set_time_base((1, encoder_rate));
+
+    let mut encoder = encoder_setup
+        .open_as(encoder_codec)
+        .map_err(|e| failed("Failed to open audio encoder", e))?;
+    let encoder_time_base = ffmpeg::Rational::new(1, encoder_rate);
+
+    {
+        let mut output_stream = output_ctx
+            .add_stream(encoder_codec)
+            .map_err(|e| failed("Failed to add audio stream", e))?;
+        output_stream.set_time_base(encoder_time_base);
+        output_stream.set_parameters(&encoder);
+    }
+
+    let mut graph = build_filter_graph(&decoder, decoder_layout, in_time_base, &encoder)
+        .map_err(|e| failed("Failed to build audio filter graph", e))?;
+
+    output_ctx
+        .write_header()
+        .map_err(|e| failed("Failed to write header", e))?;
+
+    // The muxer may adjust the stream time base while writing the header.
+    let output_time_base = output_ctx
+        .stream(0)
+        .map(|s| s.time_base())
+        .unwrap_or(encoder_time_base);
+
+    let mut decoded = ffmpeg::frame::Audio::empty();
+    let mut filtered = ffmpeg::frame::Audio::empty();
+    let mut encoded = ffmpeg::Packet::empty();
+
+    // Drains encoded packets into the muxer.
+    let mut write_packets = |encoder: &mut ffmpeg::encoder::Audio,
+                             output_ctx: &mut ffmpeg::format::context::Output|
+     -> Result<(), TranscodeError> {
+        while encoder.receive_packet(&mut encoded).is_ok() {
+            encoded.set_stream(0);
+            encoded.rescale_ts(encoder_time_base, output_time_base);
+            encoded
+                .write_interleaved(output_ctx)
+                .map_err(|e| failed("Failed to write audio packet", e))?;
+        }
+        Ok(())
+    };
+
+    // Pulls converted frames out of the graph and through the encoder.
+    let mut drain_graph = |graph: &mut ffmpeg::filter::Graph,
+                           encoder: &mut ffmpeg::encoder::Audio,
+                           output_ctx: &mut ffmpeg::format::context::Output|
+     -> Result<(), TranscodeError> {
+        let mut sink = graph
+            .get("out")
+            .ok_or_else(|| TranscodeError::TranscodeFailed("Filter sink missing".to_string()))?;
+        while sink.sink().frame(&mut filtered).is_ok() {
+            encoder
+                .send_frame(&filtered)
+                .map_err(|e| failed("Failed to send audio frame to encoder", e))?;
+            write_packets(encoder, output_ctx)?;
+        }
+        Ok(())
+    };
+
+    // Feeds every frame the decoder has ready into the graph.
+    let mut process_decoded = |decoder: &mut ffmpeg::decoder::Audio,
+                               graph: &mut ffmpeg::filter::Graph,
+                               encoder: &mut ffmpeg::encoder::Audio,
+                               output_ctx: &mut ffmpeg::format::context::Output|
+     -> Result<(), TranscodeError> {
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            let timestamp = decoded.timestamp();
+            decoded.set_pts(timestamp);
+            graph
+                .get("in")
+                .ok_or_else(|| {
+                    TranscodeError::TranscodeFailed("Filter source missing".to_string())
+                })?
+                .source()
+                .add(&decoded)
+                .map_err(|e| failed("Failed to filter audio frame", e))?;
+            drain_graph(graph, encoder, output_ctx)?;
+        }
+        Ok(())
+    };
+
+    for (stream, mut packet) in input_ctx.packets() {
+        if stream.index() != audio_stream_index {
+            continue;
+        }
+        packet.rescale_ts(stream_time_base, in_time_base);
+        decoder
+            .send_packet(&packet)
+            .map_err(|e| failed("Failed to send audio packet", e))?;
+        process_decoded(&mut decoder, &mut graph, &mut encoder, &mut output_ctx)?;
+    }
+
+    // Flush decoder, filter graph and encoder in that order.
+    decoder.send_eof().ok();
+    process_decoded(&mut decoder, &mut graph, &mut encoder, &mut output_ctx)?;
+
+    if let Some(mut source) = graph.get("in") {
+        source.source().flush().ok();
+    }
+    drain_graph(&mut graph, &mut encoder, &mut output_ctx)?;
+
+    encoder.send_eof().ok();
+    write_packets(&mut encoder, &mut output_ctx)?;
+
+    output_ctx
+        .write_trailer()
+        .map_err(|e| failed("Failed to write trailer", e))?;
+
+    let data = std::fs::read(temp_path)?;
+
+    tracing::info!(
+        "Transcoded audio {}: {} bytes",
+        source_path.display(),
+        data.len()
+    );
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_temp_files_are_unique_and_cleaned_up() {
+        let a = create_audio_temp_file(AudioTranscodeFormat::Opus, None).unwrap();
+        let b = create_audio_temp_file(AudioTranscodeFormat::Opus, None).unwrap();
+        assert_ne!(a.path(), b.path());
+        assert!(a.path().to_string_lossy().ends_with(".opus"));
+
+        let path = a.path().to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+    }
+}
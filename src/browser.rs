@@ -311,6 +311,10 @@ fn reinit_server(
         tracing::error!("Failed to read config: {e}");
         BrowserError::ServerStartFailed
     })?;
+    config.check_gui_exposure().map_err(|e| {
+        tracing::error!("{e}");
+        BrowserError::ServerStartFailed
+    })?;
 
     let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<u16>();
 
@@ -336,7 +340,7 @@ fn reinit_server(
         .block_on(ready_rx)
         .map_err(|_| BrowserError::ServerStartFailed)?;
 
-    let url = format!("http://{}:{}/", config.host, port);
+    let url = format!("http://{}:{}/", config.host.browse_host(), port);
     Ok((handle, url, config))
 }
 
@@ -3,11 +3,14 @@
 //! Generates static HTML files from markdown, creating a deployable site.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     fs,
     io::{self, Write},
     path::{Path, PathBuf},
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
     time::{Duration, Instant},
 };
 
@@ -21,19 +24,39 @@ use std::sync::Arc;
 use papaya::HashMap as ConcurrentHashMap;
 
 use crate::{
+    archive::{self, ArchiveGroup, ArchivePage, ArchiveYear},
+    audio::{self, AudioTranscodeFormat},
+    build_progress::{BuildProgress, ProgressFormat, format_duration},
+    build_sink::{BuildSink, FsSink},
     config::Config,
+    csp::ContentSecurityPolicy,
+    data::DataFiles,
     embedded_pico,
     errors::BuildError,
-    link_index::{InboundLink, OutboundLink, PageLinks, resolve_relative_url},
-    link_transform::{LinkTransformConfig, make_relative_url},
+    favicon::{self, SiteBranding},
+    feed::{self, FeedFormat, FeedItem, FeedSite},
+    git_metadata::GitMetadata,
+    inline_assets,
+    link_index::{
+        InboundLink, OutboundLink, PageLinks, aggregate_inbound_by_source, resolve_relative_url,
+    },
+    link_transform::{LinkTransformConfig, encode_url_path, make_relative_url},
     markdown,
+    media::{self, ImageVariantFormat},
     oembed_cache::OembedCache,
-    page_context::{self, ModeFlags, PageChrome, UrlMode},
-    repo::{MarkdownInfo, Repo},
-    server::{
-        DEFAULT_FILES, MediaViewerType, generate_breadcrumbs, get_current_dir_name,
-        get_parent_path, markdown_file_to_json,
+    page_assets::PageAssets,
+    page_context::{self, ModeFlags, PageChrome, SearchUi, SiteLanguage, UrlMode},
+    page_nav::{
+        generate_breadcrumbs, get_current_dir_name, get_parent_path, markdown_file_to_json,
     },
+    path_resolver::{PathResolverConfig, ResolvedPath},
+    post_render::{self, PostRenderHook, PostRenderPage},
+    reading_order::ReadingOrder,
+    redirect,
+    repo::{MarkdownInfo, Repo, VisitedDirs},
+    robots::{self, Robots},
+    sanitize::HtmlSanitizer,
+    server::{DEFAULT_FILES, MediaViewerType},
     sorting::sort_files,
     templates::Templates,
 };
@@ -80,6 +103,18 @@ fn build_dir_children_index<'a>(files: impl Iterator<Item = &'a MarkdownInfo>) -
     index
 }
 
+/// Whether a markdown file is published at `url` (e.g. `docs/index.md` at
+/// `/docs/`). Such a page is listed under the parent of its URL.
+fn has_markdown_page(index: &DirChildrenIndex, url: &str) -> bool {
+    let trimmed = url.trim_matches('/');
+    let parent = Path::new(trimmed).parent().unwrap_or(Path::new(""));
+    index.get(parent).is_some_and(|(files, _)| {
+        files
+            .iter()
+            .any(|file| file["url_path"].as_str() == Some(url))
+    })
+}
+
 /// Maximum build concurrency (parallel file processing limit).
 const MAX_BUILD_CONCURRENCY: usize = 32;
 
@@ -100,6 +135,28 @@ fn url_depth(url_path: &str) -> usize {
         .count()
 }
 
+/// The output file for a markdown page and its depth below the output
+/// root, for relative links. Pages are written to `{url}/index.html`, or
+/// with `clean_urls` off to `{url}.html` beside their source (index pages
+/// keep `index.html`).
+pub(crate) fn page_output_path(
+    output_dir: &Path,
+    url_path: &str,
+    is_index_file: bool,
+    clean_urls: bool,
+) -> (PathBuf, usize) {
+    let relative = url_path.trim_matches('/');
+    if relative.is_empty() {
+        return (output_dir.join("index.html"), 0);
+    }
+    let depth = url_depth(url_path);
+    if clean_urls || is_index_file {
+        (output_dir.join(relative).join("index.html"), depth)
+    } else {
+        (output_dir.join(format!("{relative}.html")), depth - 1)
+    }
+}
+
 /// Build the relative path prefix for .mbr assets based on page depth.
 ///
 /// Examples:
@@ -128,55 +185,6 @@ pub(crate) fn relative_root(depth: usize) -> String {
     }
 }
 
-/// Prints a progress stage message to stdout.
-///
-/// This bypasses the logging system to provide direct user feedback during builds.
-fn print_stage(stage: &str) {
-    print!("\r\x1b[K{}", stage);
-    let _ = io::stdout().flush();
-}
-
-/// Prints a progress update with count/total to stdout.
-///
-/// Uses carriage return to update in place for a cleaner terminal experience.
-fn print_progress(stage: &str, current: usize, total: usize) {
-    print!("\r\x1b[K{} ({}/{})", stage, current, total);
-    let _ = io::stdout().flush();
-}
-
-/// Formats a duration for display: "1.23s" or "1m 23.4s" for longer durations.
-fn format_duration(d: Duration) -> String {
-    let secs = d.as_secs_f64();
-    if secs >= 60.0 {
-        format!("{:.0}m {:.1}s", (secs / 60.0).floor(), secs % 60.0)
-    } else {
-        format!("{:.2}s", secs)
-    }
-}
-
-/// Prints a completed stage message with a newline.
-fn print_stage_done(stage: &str, count: usize, duration: Option<Duration>) {
-    if let Some(d) = duration {
-        println!(
-            "\r\x1b[K{} ... {} done ({})",
-            stage,
-            count,
-            format_duration(d)
-        );
-    } else {
-        println!("\r\x1b[K{} ... {} done", stage, count);
-    }
-}
-
-/// Prints a completed stage message without count.
-fn print_done(stage: &str, duration: Option<Duration>) {
-    if let Some(d) = duration {
-        println!("\r\x1b[K{} ... done ({})", stage, format_duration(d));
-    } else {
-        println!("\r\x1b[K{} ... done", stage);
-    }
-}
-
 /// Normalize a path by resolving `.` and `..` components without requiring the path to exist.
 fn normalize_path(path: &Path) -> PathBuf {
     let mut components = Vec::new();
@@ -207,6 +215,55 @@ fn link_target_exists(path: &Path, valid_files: &HashSet<PathBuf>) -> bool {
     valid_files.contains(path) || valid_files.contains(&path.join("index.html"))
 }
 
+/// Loads the curated sidebar navigation, warning about a broken manifest or
+/// entries whose URL doesn't resolve to a page (those are rendered unlinked).
+fn load_site_nav(config: &Config) -> Option<Vec<crate::nav::NavItem>> {
+    let tag_url_sources = crate::config::tag_sources_to_url_sources(&config.tag_sources);
+    let resolver = PathResolverConfig {
+        base_dir: &config.root_dir,
+        canonical_base_dir: None,
+        static_folder: &config.static_folder,
+        markdown_extensions: &config.markdown_extensions,
+        index_file: &config.index_file,
+        tag_sources: &tag_url_sources,
+    };
+    match crate::nav::load_checked(&resolver) {
+        Ok(Some(nav)) => {
+            for url in nav.dangling {
+                eprintln!("Warning: navigation entry {url} does not resolve to a page");
+            }
+            Some(nav.items)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Warning: ignoring sidebar navigation: {e}");
+            None
+        }
+    }
+}
+
+/// Loads the configured reading order, warning (and falling back to sibling
+/// navigation) if its source can't be read.
+fn load_reading_order(
+    config: &Config,
+    site_nav: Option<&[crate::nav::NavItem]>,
+) -> Option<ReadingOrder> {
+    let source = config.reading_order.as_deref()?;
+    match crate::reading_order::load(
+        source,
+        &config.root_dir,
+        site_nav,
+        &config.markdown_extensions,
+        &config.index_file,
+    ) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("Warning: ignoring reading order: {e}");
+            None
+        }
+    }
+}
+
 /// Records the first error observed across parallel (rayon) workers.
 ///
 /// Wraps a `Mutex<Option<E>>`. The guarded data is plain (no invariants a
@@ -257,18 +314,88 @@ pub struct BuildStats {
     pub markdown_pages: usize,
     pub section_pages: usize,
     pub tag_pages: usize,
+    /// Number of date-based archive pages written (see `build_archive_pages`)
+    pub archive_pages: usize,
+    /// Number of feeds written (see `feed_formats`)
+    pub feeds: usize,
     pub assets_linked: usize,
     pub duration: Duration,
-    /// Whether Pagefind search indexing succeeded (None = not attempted)
-    pub pagefind_indexed: Option<bool>,
+    /// Outcome of Pagefind search indexing (None = not attempted)
+    pub pagefind_indexed: Option<SearchIndexStatus>,
     /// Number of broken links detected
     pub broken_links: usize,
     /// Number of pages whose YAML frontmatter failed to parse
     pub frontmatter_errors: usize,
+    /// Number of URLs more than one markdown file maps to
+    pub url_collisions: usize,
     /// Number of links.json files written (for link tracking)
     pub link_files: usize,
+    /// Number of modern-format image variants written (see `image_formats`)
+    pub image_variants: usize,
+    /// Number of transcoded audio files written (see `audio_transcode`)
+    pub audio_transcodes: usize,
+    /// Number of thumbnails written for `{{ gallery(...) }}` shortcodes
+    pub gallery_thumbnails: usize,
+    /// Number of stylesheets, scripts and SVGs inlined into pages
+    /// (see `inline_assets_under_bytes`)
+    pub assets_inlined: usize,
+    /// Number of distinct URLs looked up for oembed embeds
+    pub embeds_fetched: usize,
+    /// Exit code of `post_build_command` (None = not configured)
+    pub post_build_exit_code: Option<i32>,
+    /// Wall-clock time of each stage that ran, keyed by stage name: "scan",
+    /// "clean", "images", "audio", "embeds", "render", "link_files",
+    /// "sections", "tags", "archive", "feeds", "assets", "static", "mbr",
+    /// "thumbnails", "inline", "flat_links", "links", "search", "post_build"
+    pub stage_durations: HashMap<String, Duration>,
+}
+
+impl BuildStats {
+    /// Records the time since `start` as the duration of `stage`.
+    fn record_stage(&mut self, stage: &str, start: Instant) {
+        self.stage_durations
+            .insert(stage.to_string(), start.elapsed());
+    }
+}
+
+/// Formats per-stage timings, slowest first, with each stage's share of
+/// `total`. Returns an empty string when no stages were recorded.
+fn format_stage_breakdown(stage_durations: &HashMap<String, Duration>, total: Duration) -> String {
+    let mut stages: Vec<(&String, &Duration)> = stage_durations.iter().collect();
+    stages.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+    let width = stages.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let total_secs = total.as_secs_f64();
+    let mut out = String::new();
+    for (name, duration) in stages {
+        let share = if total_secs > 0.0 {
+            duration.as_secs_f64() / total_secs * 100.0
+        } else {
+            0.0
+        };
+        out.push_str(&format!(
+            "  {name:<width$}  {:>9}  {share:>3.0}%\n",
+            format_duration(*duration)
+        ));
+    }
+    out
+}
+
+/// How a build produced its Pagefind search index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchIndexStatus {
+    /// Pagefind indexed every page.
+    Indexed,
+    /// The previous build's index was kept because the indexed HTML was
+    /// unchanged (`incremental` builds only).
+    Unchanged,
+    /// Pagefind failed or found nothing to index; the site has no search.
+    Failed,
 }
 
+/// Name of the file in `.mbr/pagefind/` recording the hash of the HTML the
+/// index was built from, for `incremental` builds.
+const SEARCH_INDEX_HASH_FILE: &str = "mbr-content-hash";
+
 /// A broken link detected during build.
 #[derive(Debug, Clone)]
 pub struct BrokenLink {
@@ -281,6 +408,8 @@ pub struct BrokenLink {
 /// Static site builder.
 pub struct Builder {
     config: Config,
+    /// Stage and progress output, redrawn in place on a terminal.
+    progress: BuildProgress,
     templates: Templates,
     output_dir: PathBuf,
     repo: Repo,
@@ -295,16 +424,100 @@ pub struct Builder {
     /// pass (url_path -> error message). Summarized to stderr after the build,
     /// mirroring broken-link reporting.
     frontmatter_errors: Arc<ConcurrentHashMap<String, String>>,
+    /// Modern-format variants generated per image, keyed by the original's
+    /// output path. Filled before rendering so pages can emit `<picture>`.
+    image_variants: Arc<ConcurrentHashMap<PathBuf, Vec<ImageVariantFormat>>>,
+    /// Transcoded format written per lossless audio file, keyed by the
+    /// original's output path. Filled before rendering so pages can list it.
+    audio_variants: Arc<ConcurrentHashMap<PathBuf, AudioTranscodeFormat>>,
+    /// Images shown in galleries (url_path -> source file), collected during
+    /// rendering so their thumbnails can be written afterwards.
+    gallery_images: Arc<ConcurrentHashMap<String, PathBuf>>,
+    /// Each feed's section (`/` for the whole site) and items, picked once
+    /// the repository is scanned. Unset when the build writes no feeds.
+    feeds: OnceLock<Vec<(String, Vec<FeedItem>)>>,
+    /// URL paths of the sections listed in `section_feeds`.
+    feed_sections: BTreeSet<String>,
+    /// Rendered bodies of the pages in `feeds` (published URL -> HTML).
+    feed_bodies: Arc<ConcurrentHashMap<String, String>>,
+    /// Curated sidebar navigation from `.mbr/nav.yml`, if present.
+    site_nav: Option<Vec<crate::nav::NavItem>>,
+    /// Site-wide prev/next sequence, when `reading_order` is configured.
+    reading_order: Option<ReadingOrder>,
+    /// Data files from `data_folder`, for the `data` shortcode.
+    data_files: Option<Arc<DataFiles>>,
+    /// Custom favicon, web app manifest and theme color.
+    branding: SiteBranding,
+    /// Content-Security-Policy embedded in every page, with this build's nonce.
+    csp: Option<ContentSecurityPolicy>,
+    /// `mermaid.initialize()` options from `mermaid_theme` and
+    /// `mermaid_config`, as a JSON object.
+    mermaid_config: String,
+    /// Last commit per source file, when `git_metadata` is on and the root
+    /// is in a git repository.
+    git_metadata: Option<GitMetadata>,
+    /// Root-relative prefix from `build_only`; `None` for a full build.
+    only: Option<PathBuf>,
+    /// Where pages, JSON files and assets are written. The output directory
+    /// on disk unless [`Builder::build_to`] is given another sink.
+    sink: Arc<dyn BuildSink>,
+    /// Transforms run over each rendered page body, in order.
+    post_render_hooks: Vec<Arc<dyn PostRenderHook>>,
+    /// True when building into `server_cache_dir`: pages are rendered the
+    /// way the server renders them (see [`crate::server_cache`]).
+    server_cache: bool,
 }
 
 impl Builder {
     /// Creates a new Builder instance.
     pub fn new(config: Config, output_dir: PathBuf) -> Result<Self, BuildError> {
-        let templates = Templates::new(&config.root_dir, config.template_folder.as_deref())?;
+        let data_files = match &config.data_folder {
+            Some(folder) => Some(Arc::new(DataFiles::load(&config.root_dir.join(folder))?)),
+            None => None,
+        };
+        let templates = Templates::new(&config.root_dir, config.template_folder.as_deref())?
+            .with_date_format(&config.date_format)
+            .with_data(data_files.clone().unwrap_or_default());
         let repo = Repo::init_from_config(&config);
-        let oembed_cache = Arc::new(OembedCache::new(config.oembed_cache_size));
+        let oembed_cache = Arc::new(
+            OembedCache::new(config.oembed_cache_size)
+                .with_concurrency(config.oembed_concurrency)
+                .with_ttl_secs(config.oembed_cache_ttl_secs),
+        );
         let build_link_index = Arc::new(ConcurrentHashMap::new());
         let frontmatter_errors = Arc::new(ConcurrentHashMap::new());
+        let image_variants = Arc::new(ConcurrentHashMap::new());
+        let audio_variants = Arc::new(ConcurrentHashMap::new());
+        let gallery_images = Arc::new(ConcurrentHashMap::new());
+        let feed_bodies = Arc::new(ConcurrentHashMap::new());
+        let site_nav = load_site_nav(&config);
+        let reading_order = load_reading_order(&config, site_nav.as_deref());
+        let branding = SiteBranding::from_config(&config);
+        let csp = ContentSecurityPolicy::from_config(&config);
+        let mermaid_config = config.mermaid_init_json();
+        // One `git log` for the whole tree; pages look themselves up while rendering
+        let git_metadata = config
+            .git_metadata
+            .then(|| GitMetadata::load(&config.root_dir))
+            .flatten();
+        let only = config
+            .build_only
+            .as_deref()
+            .and_then(crate::config::build_only_prefix);
+        let post_render_hooks = post_render::builtin_hooks(
+            HtmlSanitizer::from_config(&config),
+            config.external_links_new_tab,
+            config.base_url.as_deref(),
+        );
+        let feed_sections = config
+            .section_feeds
+            .iter()
+            .filter_map(|value| feed::section_url(value))
+            .collect();
+        let server_cache = config.server_cache_dir.as_ref().is_some_and(|dir| {
+            let cache_dir = std::path::absolute(config.root_dir.join(dir)).ok();
+            cache_dir.is_some() && cache_dir == std::path::absolute(&output_dir).ok()
+        });
 
         tracing::debug!(
             "build: initialized oembed cache with {} bytes max",
@@ -312,6 +525,9 @@ impl Builder {
         );
 
         Ok(Builder {
+            progress: BuildProgress::new(
+                ProgressFormat::from_name(&config.progress_format).unwrap_or_default(),
+            ),
             config,
             templates,
             output_dir,
@@ -319,9 +535,46 @@ impl Builder {
             oembed_cache,
             build_link_index,
             frontmatter_errors,
+            image_variants,
+            audio_variants,
+            gallery_images,
+            feeds: OnceLock::new(),
+            feed_sections,
+            feed_bodies,
+            site_nav,
+            reading_order,
+            data_files,
+            branding,
+            csp,
+            mermaid_config,
+            git_metadata,
+            only,
+            sink: Arc::new(FsSink),
+            post_render_hooks,
+            server_cache,
         })
     }
 
+    /// Adds a transform run over each rendered page body, after any added
+    /// earlier (see [`post_render`]).
+    pub fn with_post_render_hook(mut self, hook: Arc<dyn PostRenderHook>) -> Self {
+        self.post_render_hooks.push(hook);
+        self
+    }
+
+    /// Writes output through `sink` instead of to the output directory.
+    pub fn with_sink(mut self, sink: Arc<dyn BuildSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Builds the static site into `sink`. Steps that need the output on
+    /// disk (see [`crate::build_sink`]) are skipped unless
+    /// [`BuildSink::on_disk`] is true.
+    pub async fn build_to(self, sink: Arc<dyn BuildSink>) -> Result<BuildStats, BuildError> {
+        self.with_sink(sink).build().await
+    }
+
     /// Builds the static site.
     pub async fn build(&self) -> Result<BuildStats, BuildError> {
         let start = Instant::now();
@@ -329,7 +582,7 @@ impl Builder {
 
         // Scan repository for all files
         let stage_start = Instant::now();
-        print_stage("Scanning repository...");
+        self.progress.stage("Scanning repository...");
         self.repo.scan_all()?;
         self.repo.scan_static_folder()?;
         // Build the typed-relationship index once all note titles are known.
@@ -339,58 +592,193 @@ impl Builder {
         // Build the global wikilink name index (always on) so body `[[Name]]`
         // links resolve globally during the render pass and link validation.
         self.repo.build_wikilink_index();
+        for warning in self
+            .repo
+            .tag_index
+            .missing_metadata(&self.config.tag_sources, self.data_files.as_deref())
+        {
+            eprintln!("Warning: {warning}");
+        }
         let file_count = self.repo.markdown_files.pin().len() + self.repo.other_files.pin().len();
-        print_stage_done(
+        self.progress.stage_done(
             "Scanning repository",
             file_count,
             Some(stage_start.elapsed()),
         );
+        stats.record_stage("scan", stage_start);
+
+        // Files sharing a URL overwrite each other's output; report them
+        // before anything is written.
+        let collisions = self.repo.url_path_collisions();
+        stats.url_collisions = collisions.len();
+        if !collisions.is_empty() {
+            eprintln!(
+                "\n⚠️  Files mapping to the same URL ({} total):",
+                collisions.len()
+            );
+            for collision in &collisions {
+                let sources: Vec<String> = collision
+                    .sources
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                eprintln!("   {} ← {}", collision.url_path, sources.join(", "));
+            }
+            eprintln!();
+            if self.config.strict {
+                return Err(BuildError::UrlPathCollisions {
+                    count: collisions.len(),
+                });
+            }
+        }
+
+        // Steps that rework written files or hand them to external tools
+        // need a real output directory.
+        let on_disk = self.sink.on_disk();
+
+        // A partial build updates the previous build in place
+        if self.only.is_some() && on_disk && !self.output_dir.is_dir() {
+            return Err(BuildError::PartialBuildWithoutOutput {
+                path: self.output_dir.clone(),
+            });
+        }
 
         // Prepare output directory
-        let stage_start = Instant::now();
-        print_stage("Cleaning output directory...");
-        self.prepare_output_dir()?;
-        print_done("Cleaning output directory", Some(stage_start.elapsed()));
+        let previous_search_index = if on_disk && self.only.is_none() {
+            let stage_start = Instant::now();
+            self.progress.stage("Cleaning output directory...");
+            let previous_search_index = self.stash_search_index();
+            self.prepare_output_dir()?;
+            self.progress
+                .done("Cleaning output directory", Some(stage_start.elapsed()));
+            stats.record_stage("clean", stage_start);
+            previous_search_index
+        } else {
+            self.progress
+                .finish("Cleaning output directory ... skipped");
+            None
+        };
+
+        // Generate modern-format image variants (before rendering, so pages can
+        // reference them in <picture> markup)
+        if on_disk && !self.config.image_formats.is_empty() {
+            let stage_start = Instant::now();
+            stats.image_variants = self.generate_image_variants()?;
+            stats.record_stage("images", stage_start);
+        }
+
+        // Pre-generate web-friendly transcodes of lossless audio
+        if let Some(format) = self
+            .config
+            .audio_transcode
+            .as_deref()
+            .and_then(AudioTranscodeFormat::from_name)
+        {
+            let stage_start = Instant::now();
+            stats.audio_transcodes = self.generate_audio_transcodes(format)?;
+            stats.record_stage("audio", stage_start);
+        }
+
+        // Fetch every page's embeds once up front; rendering reads them from
+        // the cache. Only when oembed is enabled for the build.
+        if self.config.oembed_timeout_ms > 0 {
+            let stage_start = Instant::now();
+            stats.embeds_fetched = self.prefetch_embeds().await;
+            stats.record_stage("embeds", stage_start);
+        }
+
+        // Pick the feeds' pages up front so rendering keeps only their bodies
+        if self.writes_feeds() && self.config.base_url.is_some() {
+            let _ = self.feeds.set(self.collect_feeds());
+        }
 
         // Render all markdown files
+        let stage_start = Instant::now();
         stats.markdown_pages = self.render_markdown_files().await?;
+        stats.record_stage("render", stage_start);
+        if self.server_cache {
+            self.write_server_cache_marker()?;
+        }
 
-        // Write links.json files (if link tracking is enabled)
-        if self.config.link_tracking {
+        // Write links.json files (if link tracking is enabled). Backlinks need
+        // every page rendered, so partial builds leave the old files.
+        if self.config.link_tracking && self.only.is_none() {
+            let stage_start = Instant::now();
             stats.link_files = self.write_link_files().await?;
+            stats.record_stage("link_files", stage_start);
         }
 
         // Generate directory/section pages
+        let stage_start = Instant::now();
         stats.section_pages = self.render_directory_pages().await?;
+        stats.record_stage("sections", stage_start);
 
-        // Generate tag pages (if enabled)
-        if self.config.build_tag_pages {
+        // Generate tag pages (if enabled). They list pages from the whole
+        // site, so partial builds keep the previous ones.
+        if self.config.build_tag_pages && self.only.is_none() {
+            let stage_start = Instant::now();
             stats.tag_pages = self.render_tag_pages().await?;
+            stats.record_stage("tags", stage_start);
         } else {
-            println!("Generating tag pages ... skipped");
+            self.progress.finish("Generating tag pages ... skipped");
+        }
+
+        // Generate date-based archive pages (if enabled)
+        if self.config.build_archive_pages && self.only.is_none() {
+            let stage_start = Instant::now();
+            stats.archive_pages = self.render_archive_pages().await?;
+            stats.record_stage("archive", stage_start);
+        }
+
+        // Feeds of the newest dated pages. Like the archive, they cover the
+        // whole site, so partial builds keep the previous ones.
+        if self.writes_feeds() {
+            let stage_start = Instant::now();
+            stats.feeds = self.write_feeds()?;
+            stats.record_stage("feeds", stage_start);
         }
 
         // Symlink assets (images, PDFs, etc.)
         let stage_start = Instant::now();
-        print_stage("Linking assets...");
+        self.progress.stage("Linking assets...");
         stats.assets_linked = self.symlink_assets()?;
-        print_stage_done(
+        self.progress.stage_done(
             "Linking assets",
             stats.assets_linked,
             Some(stage_start.elapsed()),
         );
+        stats.record_stage("assets", stage_start);
 
         // Handle static folder overlay
         let stage_start = Instant::now();
-        print_stage("Processing static folder...");
+        self.progress.stage("Processing static folder...");
         self.handle_static_folder()?;
-        print_done("Processing static folder", Some(stage_start.elapsed()));
+        self.progress
+            .done("Processing static folder", Some(stage_start.elapsed()));
+        stats.record_stage("static", stage_start);
 
         // Handle .mbr folder (copy, write defaults, generate site.json)
         let stage_start = Instant::now();
-        print_stage("Copying theme and assets...");
+        self.progress.stage("Copying theme and assets...");
         self.handle_mbr_folder()?;
-        print_done("Copying theme and assets", Some(stage_start.elapsed()));
+        self.progress
+            .done("Copying theme and assets", Some(stage_start.elapsed()));
+        stats.record_stage("mbr", stage_start);
+
+        // Custom favicon, its icon sizes and the web app manifest
+        self.write_site_branding()?;
+
+        // robots.txt, unless the repository or static folder provided one
+        self.write_robots_txt()?;
+
+        // Write thumbnails for images shown by gallery shortcodes and, when
+        // enabled, for card/grid listing cover images
+        self.queue_cover_thumbnails();
+        if !self.gallery_images.is_empty() {
+            let stage_start = Instant::now();
+            stats.gallery_thumbnails = self.generate_gallery_thumbnails()?;
+            stats.record_stage("thumbnails", stage_start);
+        }
 
         // Generate 404.html for GitHub Pages compatibility
         self.generate_404_page()?;
@@ -398,15 +786,34 @@ impl Builder {
         // Generate media viewer pages (videos, pdfs, audio)
         self.generate_media_viewer_pages()?;
 
+        // Inline small stylesheets, scripts and SVGs now that every page and
+        // asset is in place
+        if on_disk && self.config.inline_assets_under_bytes > 0 {
+            let stage_start = Instant::now();
+            stats.assets_inlined = self.inline_small_assets()?;
+            stats.record_stage("inline", stage_start);
+        }
+
+        // Point folder-style links at the .html files that answer them
+        if on_disk && !self.config.clean_urls && !self.server_cache {
+            let stage_start = Instant::now();
+            self.rewrite_flat_links()?;
+            stats.record_stage("flat_links", stage_start);
+        }
+
         // Validate internal links and report broken ones
-        if self.config.skip_link_checks {
-            println!("Validating links ... skipped");
+        // Pages outside a partial build weren't rendered, so links into them
+        // can't be checked
+        if self.config.skip_link_checks || !on_disk || self.only.is_some() {
+            self.progress.finish("Validating links ... skipped");
         } else {
             let stage_start = Instant::now();
-            print_stage("Validating links...");
+            self.progress.stage("Validating links...");
             let broken_links = self.validate_links();
             stats.broken_links = broken_links.len();
-            print_done("Validating links", Some(stage_start.elapsed()));
+            self.progress
+                .done("Validating links", Some(stage_start.elapsed()));
+            stats.record_stage("links", stage_start);
 
             if !broken_links.is_empty() {
                 eprintln!(
@@ -439,19 +846,89 @@ impl Builder {
         }
 
         // Run Pagefind to generate search index
-        let stage_start = Instant::now();
-        print_stage("Building search index...");
-        stats.pagefind_indexed = Some(self.run_pagefind().await);
-        if stats.pagefind_indexed == Some(true) {
-            print_done("Building search index", Some(stage_start.elapsed()));
+        if self.config.skip_search_index || !on_disk {
+            self.progress.finish("Building search index ... skipped");
         } else {
-            println!("\r\x1b[KBuilding search index ... skipped");
+            let stage_start = Instant::now();
+            self.progress.stage("Building search index...");
+            let status = self.update_search_index(previous_search_index).await;
+            match status {
+                SearchIndexStatus::Indexed => self
+                    .progress
+                    .done("Building search index", Some(stage_start.elapsed())),
+                SearchIndexStatus::Unchanged => {
+                    self.progress.finish("Building search index ... unchanged")
+                }
+                SearchIndexStatus::Failed => {
+                    self.progress.finish("Building search index ... skipped")
+                }
+            }
+            stats.pagefind_indexed = Some(status);
+            stats.record_stage("search", stage_start);
         }
 
         stats.duration = start.elapsed();
+
+        if let Some(command) = self.config.post_build_command.as_ref().filter(|_| on_disk) {
+            let stage_start = Instant::now();
+            self.progress.stage("Running post-build command...");
+            stats.post_build_exit_code = Some(self.run_post_build_command(command, &stats)?);
+            self.progress
+                .done("Running post-build command", Some(stage_start.elapsed()));
+            stats.record_stage("post_build", stage_start);
+        }
+
+        println!("\nStage timings (slowest first):");
+        print!(
+            "{}",
+            format_stage_breakdown(&stats.stage_durations, start.elapsed())
+        );
+
         Ok(stats)
     }
 
+    /// Runs `post_build_command` through `sh -c` from the root directory,
+    /// with the output directory and build statistics in `MBR_*` environment
+    /// variables.
+    ///
+    /// The command's output is captured and echoed once it exits. Returns its
+    /// exit code, or an error if it couldn't be started or exited non-zero.
+    fn run_post_build_command(&self, command: &str, stats: &BuildStats) -> Result<i32, BuildError> {
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(&self.config.root_dir)
+            .env("MBR_OUTPUT_DIR", &self.output_dir)
+            .env("MBR_ROOT_DIR", &self.config.root_dir)
+            .env("MBR_MARKDOWN_PAGES", stats.markdown_pages.to_string())
+            .env("MBR_SECTION_PAGES", stats.section_pages.to_string())
+            .env("MBR_TAG_PAGES", stats.tag_pages.to_string())
+            .env("MBR_ARCHIVE_PAGES", stats.archive_pages.to_string())
+            .env("MBR_ASSETS_LINKED", stats.assets_linked.to_string())
+            .env("MBR_BROKEN_LINKS", stats.broken_links.to_string())
+            .env("MBR_LINK_FILES", stats.link_files.to_string())
+            .env("MBR_DURATION_MS", stats.duration.as_millis().to_string())
+            .output()
+            .map_err(|e| BuildError::PostBuildSpawnFailed {
+                command: command.to_string(),
+                source: e,
+            })?;
+
+        if !output.stdout.is_empty() || !output.stderr.is_empty() {
+            println!();
+        }
+        io::stdout().write_all(&output.stdout).ok();
+        io::stderr().write_all(&output.stderr).ok();
+
+        match output.status.code() {
+            Some(0) => Ok(0),
+            _ => Err(BuildError::PostBuildFailed {
+                command: command.to_string(),
+                status: output.status.to_string(),
+            }),
+        }
+    }
+
     /// Creates or cleans the output directory.
     ///
     /// Uses an atomic rename to move the old directory aside, then deletes it in a background
@@ -503,17 +980,9 @@ impl Builder {
             .map(|(path, info)| (path.clone(), info.clone()))
             .collect();
 
-        let count = markdown_files.len();
-        let concurrency = self.get_concurrency();
-
-        tracing::info!(
-            "Rendering {} markdown files with concurrency {}",
-            count,
-            concurrency
-        );
-
         // Pre-build sibling index: group files by parent directory and sort each group once.
         // This turns O(n²) per-file sibling scanning into O(n log n) total.
+        // Partial builds still index every file so prev/next links stay complete.
         let sibling_index = {
             let mut index: HashMap<PathBuf, Vec<serde_json::Value>> = HashMap::new();
             for (_, info) in &markdown_files {
@@ -533,9 +1002,22 @@ impl Builder {
             Arc::new(index)
         };
 
+        let markdown_files: Vec<_> = markdown_files
+            .into_iter()
+            .filter(|(path, _)| self.in_build_scope(path))
+            .collect();
+        let count = markdown_files.len();
+        let concurrency = self.get_concurrency();
+
+        tracing::info!(
+            "Rendering {} markdown files with concurrency {}",
+            count,
+            concurrency
+        );
+
         // Progress counter for parallel rendering
         let completed = Arc::new(AtomicUsize::new(0));
-        print_progress("Rendering markdown", 0, count);
+        self.progress.progress("Rendering markdown", 0, count);
 
         // Use rayon for true CPU parallelism — render_single_markdown_sync does
         // zero async work (all fs ops are sync, oembed is disabled in build mode).
@@ -567,7 +1049,7 @@ impl Builder {
                         // Batch progress: only flush stdout every 100 files or at completion
                         // to avoid mutex contention from 44K+ competing rayon threads.
                         if done.is_multiple_of(100) || done == count {
-                            print_progress("Rendering markdown", done, count);
+                            self.progress.progress("Rendering markdown", done, count);
                         }
                     }
                     Err(e) => error.record(e),
@@ -577,132 +1059,912 @@ impl Builder {
 
         error.into_result()?;
 
-        print_stage_done("Rendering markdown", count, Some(stage_start.elapsed()));
+        self.progress
+            .stage_done("Rendering markdown", count, Some(stage_start.elapsed()));
         Ok(count)
     }
 
-    /// Writes links.json files for all pages with bidirectional link information.
+    /// Writes modern-format variants (`photo.jpg.webp`, ...) for every JPEG/PNG
+    /// asset, as configured by `image_formats`.
     ///
-    /// This method:
-    /// 1. Builds an inbound link index by inverting the outbound links
-    /// 2. Writes links.json files in parallel for each page
-    async fn write_link_files(&self) -> Result<usize, BuildError> {
+    /// Images that fail to decode, or whose variant wouldn't be smaller, are
+    /// skipped and keep being served in their original format only.
+    fn generate_image_variants(&self) -> Result<usize, BuildError> {
         let stage_start = Instant::now();
-        print_stage("Building link index...");
-
-        // Step 1: Build the inbound index by inverting outbound links
-        // For each outbound link from page A to page B, create an inbound link on page B from A
-        // Also collect the outbound index into a regular HashMap for thread-safe parallel access
-        let outbound_guard = self.build_link_index.pin();
-        let mut inbound_index: HashMap<String, Vec<InboundLink>> = HashMap::new();
-        let mut outbound_index: HashMap<String, Vec<OutboundLink>> = HashMap::new();
-
-        for (source_url, (is_index_file, outbound_links)) in outbound_guard.iter() {
-            // Copy outbound links to our local HashMap
-            outbound_index.insert(source_url.clone(), outbound_links.clone());
-
-            for link in outbound_links {
-                // Only track internal links for inbound
-                if !link.internal {
-                    continue;
-                }
-
-                // Resolve the relative URL to an absolute URL based on the source page
-                let target_url = resolve_relative_url(source_url, &link.to, *is_index_file);
-
-                let inbound_link = InboundLink {
-                    from: source_url.clone(),
-                    text: link.text.clone(),
-                    anchor: link.anchor.clone(),
-                };
+        self.progress.stage("Generating image variants...");
 
-                inbound_index
-                    .entry(target_url)
-                    .or_default()
-                    .push(inbound_link);
+        let mut formats = Vec::new();
+        for name in &self.config.image_formats {
+            // Config::validate rejects formats without an encoder
+            let Some(format) = ImageVariantFormat::from_name(name) else {
+                continue;
+            };
+            if !formats.contains(&format) {
+                formats.push(format);
             }
         }
+        // Most compact format first: browsers pick the first <source> they support.
+        formats.sort();
 
-        // Step 2: Collect all known page URLs that can actually render backlinks.
-        // We intentionally exclude inbound_index keys here — those may contain URLs
-        // to static files (PDFs, images) which can't display backlinks. Creating
-        // directories for them would shadow the actual file symlinks during asset linking.
-        let all_page_urls: HashSet<String> = {
-            let mut urls: HashSet<String> = outbound_index.keys().cloned().collect();
-
-            // Include all markdown pages (even those without links)
-            for (_, info) in self.repo.markdown_files.pin().iter() {
-                urls.insert(info.url_path.clone());
-            }
-
-            // Include tag page URLs if tag pages are enabled
-            if self.config.build_tag_pages {
-                for tag_source in &self.config.tag_sources {
-                    let source = tag_source.url_source();
-
-                    // Add tag source index URL (e.g., "/tags/")
-                    urls.insert(format!("/{}/", source));
-
-                    // Add individual tag page URLs (e.g., "/tags/rust/")
-                    for tag in self.repo.tag_index.get_all_tags(&source) {
-                        urls.insert(format!("/{}/{}/", source, tag.normalized));
-                    }
-                }
-            }
-
-            urls
-        };
-
-        let count = all_page_urls.len();
-        let concurrency = self.get_concurrency();
-
-        // Progress counter for parallel writing
-        let completed = Arc::new(AtomicUsize::new(0));
-        print_progress("Writing link files", 0, count);
-
-        let page_urls: Vec<String> = all_page_urls.into_iter().collect();
-
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(concurrency)
-            .build()
-            .map_err(|e| BuildError::CreateDirFailed {
-                path: self.output_dir.clone(),
-                source: std::io::Error::other(format!("Failed to create rayon thread pool: {}", e)),
-            })?;
+        let images: Vec<_> = self
+            .repo
+            .other_files
+            .pin()
+            .iter()
+            .filter(|(_, info)| media::is_transcodable_image(&info.url_path))
+            .map(|(_, info)| info.clone())
+            .collect();
 
         let error: FirstError<BuildError> = FirstError::new();
-
-        pool.install(|| {
+        let written = AtomicUsize::new(0);
+        {
             use rayon::prelude::*;
-            page_urls.par_iter().for_each(|url_path| {
+            images.par_iter().for_each(|file_info| {
                 if error.is_set() {
                     return;
                 }
-                match self.write_single_link_file(url_path, &outbound_index, &inbound_index) {
-                    Ok(()) => {
-                        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-                        if done.is_multiple_of(100) || done == count {
-                            print_progress("Writing link files", done, count);
+                let output_path = self
+                    .output_dir
+                    .join(file_info.url_path.trim_start_matches('/'));
+                if let Some(parent) = output_path.parent()
+                    && let Err(e) = fs::create_dir_all(parent)
+                {
+                    error.record(BuildError::CreateDirFailed {
+                        path: parent.to_path_buf(),
+                        source: e,
+                    });
+                    return;
+                }
+
+                let mut generated = Vec::new();
+                for &format in &formats {
+                    let mut variant_name = output_path.as_os_str().to_os_string();
+                    variant_name.push(".");
+                    variant_name.push(format.extension());
+                    match media::transcode_image(
+                        &file_info.raw_path,
+                        Path::new(&variant_name),
+                        format,
+                    ) {
+                        Ok(true) => generated.push(format),
+                        Ok(false) => {}
+                        Err(e) => {
+                            tracing::warn!(
+                                "Skipping {} variant of {}: {}",
+                                format.extension(),
+                                file_info.raw_path.display(),
+                                e
+                            );
                         }
                     }
-                    Err(e) => error.record(e),
+                }
+                if !generated.is_empty() {
+                    written.fetch_add(generated.len(), Ordering::Relaxed);
+                    self.image_variants.pin().insert(output_path, generated);
                 }
             });
+        }
+        error.into_result()?;
+
+        let written = written.into_inner();
+        self.progress.stage_done(
+            "Generating image variants",
+            written,
+            Some(stage_start.elapsed()),
+        );
+        Ok(written)
+    }
+
+    /// Looks up oembed data for every bare URL in the site's markdown pages,
+    /// filling the cache the render pass reads from.
+    ///
+    /// Each distinct URL is fetched once however many pages embed it, with at
+    /// most `oembed_concurrency` fetches in flight. Failed fetches are cached
+    /// as plain links, so pages still render.
+    async fn prefetch_embeds(&self) -> usize {
+        let stage_start = Instant::now();
+        self.progress.stage("Fetching embeds...");
+
+        let paths: Vec<PathBuf> = self
+            .repo
+            .markdown_files
+            .pin()
+            .iter()
+            .map(|(path, _)| path.clone())
+            .filter(|path| {
+                markdown::RendererKind::for_path(path, &self.config.renderers).is_markdown()
+            })
+            .collect();
+        let urls: HashSet<String> = {
+            use rayon::prelude::*;
+            paths
+                .par_iter()
+                .filter_map(|path| fs::read_to_string(path).ok())
+                .flat_map_iter(|input| markdown::bare_urls(&input, &self.config.markdown_options))
+                .collect()
+        };
+
+        let fetched = markdown::fetch_oembed_urls(
+            urls,
+            self.config.oembed_timeout_ms,
+            &Some(self.oembed_cache.clone()),
+        )
+        .await
+        .len();
+        self.progress
+            .stage_done("Fetching embeds", fetched, Some(stage_start.elapsed()));
+        fetched
+    }
+
+    /// Copies the configured favicon to the site root, writes its generated
+    /// icon sizes to `.mbr/icons/`, and writes `manifest.webmanifest`.
+    fn write_site_branding(&self) -> Result<(), BuildError> {
+        if let Some(icon) = &self.branding.icon {
+            let dest = self.output_dir.join(&icon.file_name);
+            // The asset pass may have linked this very file here; the sink
+            // replaces the link instead of copying through it onto the source.
+            self.sink
+                .copy_file(&icon.source, &dest)
+                .map_err(|e| BuildError::CopyFailed {
+                    from: icon.source.clone(),
+                    to: dest.clone(),
+                    source: e,
+                })?;
+
+            let icon_dir = self.output_dir.join(".mbr").join("icons");
+            if !icon.sizes.is_empty() {
+                self.create_output_dir(&icon_dir)?;
+            }
+            for &size in &icon.sizes {
+                let png = match icon.icon_png(size) {
+                    Ok(png) => png,
+                    Err(e) => {
+                        tracing::warn!("Skipping {size}px icon: {e}");
+                        continue;
+                    }
+                };
+                let path = icon_dir.join(favicon::icon_file_name(size));
+                self.write_output(&path, png)?;
+            }
+        }
+
+        if let Some(manifest) = &self.branding.manifest {
+            let path = self.output_dir.join(favicon::MANIFEST_FILE);
+            self.write_output(&path, manifest)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a feed at the site root for each of `feed_formats`, and one in
+    /// each of `section_feeds` that has dated pages. Skipped without a
+    /// `base_url` to make links absolute.
+    fn write_feeds(&self) -> Result<usize, BuildError> {
+        let Some(base_url) = self.config.base_url.as_deref() else {
+            self.progress
+                .finish("Writing feeds ... skipped (no base_url)");
+            return Ok(0);
+        };
+        let feeds = self.feeds.get().map(Vec::as_slice).unwrap_or_default();
+        let bodies = self.feed_bodies.pin();
+        let formats = self.feed_formats();
+        for (home_path, items) in feeds {
+            let title = self.feed_title(home_path);
+            let site = FeedSite {
+                title: &title,
+                base_url,
+                language: &self.config.site_lang,
+            };
+            let dir = self.output_dir.join(home_path.trim_matches('/'));
+            self.create_output_dir(&dir)?;
+            for format in &formats {
+                let contents = match format {
+                    FeedFormat::Json => feed::json_feed(&site, home_path, items, |url_path| {
+                        bodies.get(url_path).cloned()
+                    }),
+                };
+                self.write_output(&dir.join(format.file_name()), contents)?;
+            }
+        }
+        let count = feeds.len() * formats.len();
+        self.progress.finish(&format!("Writing feeds ... {count}"));
+        Ok(count)
+    }
+
+    /// True if this build writes feeds: a format is set and it isn't a
+    /// partial build.
+    fn writes_feeds(&self) -> bool {
+        !self.feed_formats().is_empty() && self.only.is_none()
+    }
+
+    /// The site feed when `feed_formats` is set, then one per section in
+    /// `section_feeds` that has dated pages, each with its newest items.
+    fn collect_feeds(&self) -> Vec<(String, Vec<FeedItem>)> {
+        let all_items = self.feed_items();
+        let mut feeds: Vec<(String, Vec<FeedItem>)> = Vec::new();
+        if !self.config.feed_formats.is_empty() {
+            feeds.push(("/".to_string(), feed::collect_items(all_items.clone())));
+        }
+        for section in &self.feed_sections {
+            let items = feed::collect_items(
+                all_items
+                    .iter()
+                    .filter(|item| feed::in_section(&item.url_path, section))
+                    .cloned(),
+            );
+            if items.is_empty() {
+                tracing::info!("No dated pages under {section}; skipping its feed");
+                continue;
+            }
+            feeds.push((section.clone(), items));
+        }
+        feeds
+    }
+
+    /// Formats feeds are written in: `feed_formats`, or JSON when only
+    /// `section_feeds` is set.
+    fn feed_formats(&self) -> BTreeSet<FeedFormat> {
+        let formats: BTreeSet<FeedFormat> = self
+            .config
+            .feed_formats
+            .iter()
+            .filter_map(|name| FeedFormat::from_name(name))
+            .collect();
+        if formats.is_empty() && !self.config.section_feeds.is_empty() {
+            return BTreeSet::from([FeedFormat::Json]);
+        }
+        formats
+    }
+
+    /// Every dated page as a feed item, at the URL it's published at.
+    /// Pages asking not to be indexed are left out.
+    fn feed_items(&self) -> Vec<FeedItem> {
+        let no_frontmatter = markdown::SimpleMetadata::new();
+        self.repo
+            .markdown_files
+            .pin()
+            .iter()
+            .filter_map(|(_, info)| {
+                let frontmatter = info.frontmatter.as_ref().unwrap_or(&no_frontmatter);
+                if Robots::for_page(frontmatter, self.config.robots.as_deref()).noindex() {
+                    return None;
+                }
+                let mut item = FeedItem::from_info(info)?;
+                item.url_path = self.page_url(info);
+                Some(item)
+            })
+            .collect()
+    }
+
+    /// True if the rendered body of the page published at `url` goes in a
+    /// feed.
+    fn wants_feed_body(&self, url: &str) -> bool {
+        self.feeds.get().is_some_and(|feeds| {
+            feeds
+                .iter()
+                .any(|(_, items)| items.iter().any(|item| item.url_path == url))
+        })
+    }
+
+    /// Title of the feed for the section at `home_path`: the site's name
+    /// (`site_name`, else the root folder's), then the section's folder.
+    fn feed_title(&self, home_path: &str) -> String {
+        let site_name = self
+            .config
+            .site_name
+            .as_deref()
+            .or_else(|| {
+                self.config
+                    .root_dir
+                    .file_name()
+                    .and_then(|name| name.to_str())
+            })
+            .unwrap_or("mbr");
+        match home_path.trim_matches('/').rsplit('/').next() {
+            Some(section) if !section.is_empty() => format!("{site_name}: {section}"),
+            _ => site_name.to_string(),
+        }
+    }
+
+    /// `<link rel="alternate">` entries for the section page at `url_path`,
+    /// with hrefs relative to `depth`. Empty unless the section has a feed.
+    fn section_feed_links(&self, url_path: &str, depth: usize) -> Vec<serde_json::Value> {
+        if self.config.base_url.is_none() || !self.feed_sections.contains(url_path) {
+            return Vec::new();
+        }
+        let has_dated_pages = self.repo.markdown_files.pin().iter().any(|(_, info)| {
+            info.created_date.is_some() && feed::in_section(&info.url_path, url_path)
         });
+        if !has_dated_pages {
+            return Vec::new();
+        }
+        let title = self.feed_title(url_path);
+        self.feed_formats()
+            .iter()
+            .map(|format| {
+                serde_json::json!({
+                    "href": make_relative_url(&encode_url_path(&format!("{url_path}{}", format.file_name())), depth),
+                    "type": format.mime_type(),
+                    "title": title,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes `robots.txt` at the site root. A `robots.txt` already linked or
+    /// copied there from the repository or static folder is kept.
+    fn write_robots_txt(&self) -> Result<(), BuildError> {
+        let path = self.output_dir.join(robots::ROBOTS_TXT);
+        if self.sink.exists(&path) {
+            return Ok(());
+        }
+        let txt = robots::robots_txt(
+            self.config.robots_txt.as_deref(),
+            self.config.base_url.as_deref(),
+            self.sink
+                .exists(&self.output_dir.join(robots::SITEMAP_FILE)),
+        );
+        self.write_output(&path, txt)
+    }
 
+    /// Queues a thumbnail for every local JPEG/PNG listing cover image when
+    /// card or grid listings show thumbnails. Covers that don't resolve to a
+    /// repo file are left to the link checker.
+    fn queue_cover_thumbnails(&self) {
+        if !self.config.listing_thumbnails || self.config.listing_layout == "list" {
+            return;
+        }
+        let tag_url_sources = crate::config::tag_sources_to_url_sources(&self.config.tag_sources);
+        let resolver = PathResolverConfig {
+            base_dir: &self.config.root_dir,
+            canonical_base_dir: None,
+            static_folder: &self.config.static_folder,
+            markdown_extensions: &self.config.markdown_extensions,
+            index_file: &self.config.index_file,
+            tag_sources: &tag_url_sources,
+        };
+        let gallery_images = self.gallery_images.pin();
+        for info in self.repo.markdown_files.pin().values() {
+            let Some(url_path) = info.cover_image.as_deref().filter(|url| {
+                url.starts_with('/') && !url.starts_with("//") && media::is_transcodable_image(url)
+            }) else {
+                continue;
+            };
+            if let ResolvedPath::StaticFile(source) = crate::path_resolver::resolve_request_path(
+                &resolver,
+                url_path.trim_start_matches('/'),
+            ) && source.is_file()
+            {
+                gallery_images.insert(url_path.to_string(), source);
+            }
+        }
+    }
+
+    /// Writes a thumbnail under `.mbr/thumbs/` for every image shown by a
+    /// `{{ gallery(...) }}` shortcode.
+    ///
+    /// Images that fail to decode are skipped with a warning; their gallery
+    /// tile shows a broken image but still links to the viewer.
+    fn generate_gallery_thumbnails(&self) -> Result<usize, BuildError> {
+        let stage_start = Instant::now();
+        self.progress.stage("Generating gallery thumbnails...");
+
+        let images: Vec<(String, PathBuf)> = self
+            .gallery_images
+            .pin()
+            .iter()
+            .map(|(url_path, source)| (url_path.clone(), source.clone()))
+            .collect();
+
+        let error: FirstError<BuildError> = FirstError::new();
+        let written = AtomicUsize::new(0);
+        {
+            use rayon::prelude::*;
+            images.par_iter().for_each(|(url_path, source)| {
+                if error.is_set() {
+                    return;
+                }
+                let output_path = self
+                    .output_dir
+                    .join(crate::gallery::THUMBNAIL_ROUTE.trim_start_matches('/'))
+                    .join(url_path.trim_start_matches('/'));
+                let data = match media::thumbnail_bytes(
+                    source,
+                    crate::constants::GALLERY_THUMBNAIL_SIZE,
+                ) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        tracing::warn!("Skipping thumbnail of {}: {}", source.display(), e);
+                        return;
+                    }
+                };
+                if let Some(parent) = output_path.parent()
+                    && let Err(e) = self.sink.create_dir(parent)
+                {
+                    error.record(BuildError::CreateDirFailed {
+                        path: parent.to_path_buf(),
+                        source: e,
+                    });
+                    return;
+                }
+                if let Err(e) = self.sink.write_file(&output_path, &data) {
+                    error.record(BuildError::WriteFailed {
+                        path: output_path,
+                        source: e,
+                    });
+                    return;
+                }
+                written.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        error.into_result()?;
+
+        let written = written.into_inner();
+        self.progress.stage_done(
+            "Generating gallery thumbnails",
+            written,
+            Some(stage_start.elapsed()),
+        );
+        Ok(written)
+    }
+
+    /// Wraps images that have generated variants in `<picture>` markup.
+    ///
+    /// `page_output_path` is the page's `index.html` in the output directory,
+    /// used to resolve relative `src` attributes the way a browser would.
+    fn wrap_images_with_variants(&self, page_output_path: &Path, html: String) -> String {
+        if self.image_variants.is_empty() {
+            return html;
+        }
+        let variants = self.image_variants.pin();
+        media::wrap_images_in_picture(&html, |src| {
+            self.resolve_link(page_output_path, src)
+                .and_then(|path| variants.get(&path).cloned())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Inlines local assets smaller than `inline_assets_under_bytes` into every
+    /// generated page (see [`inline_assets`]). Returns the number of tags
+    /// replaced.
+    fn inline_small_assets(&self) -> Result<usize, BuildError> {
+        use rayon::prelude::*;
+
+        let stage_start = Instant::now();
+        self.progress.stage("Inlining small assets...");
+        let threshold = self.config.inline_assets_under_bytes;
+        let mbr_prefix = self.output_dir.join(".mbr");
+        let pages: Vec<PathBuf> = WalkDir::new(&self.output_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "html") && !path.starts_with(&mbr_prefix)
+            })
+            .collect();
+
+        let inlined = AtomicUsize::new(0);
+        pages.par_iter().try_for_each(|page| {
+            let Ok(html) = fs::read_to_string(page) else {
+                return Ok(());
+            };
+            let nonce = self.csp.as_ref().map(|csp| csp.nonce.as_str());
+            let (html, count) = inline_assets::inline_small_assets(&html, nonce, |url| {
+                let path = normalize_path(&self.resolve_link(page, url)?);
+                if !path.starts_with(&self.output_dir) {
+                    return None;
+                }
+                // Metadata follows symlinked assets to their source file
+                let metadata = fs::metadata(&path).ok()?;
+                if !metadata.is_file() || metadata.len() >= threshold {
+                    return None;
+                }
+                fs::read_to_string(&path).ok()
+            });
+            if count == 0 {
+                return Ok(());
+            }
+            inlined.fetch_add(count, Ordering::Relaxed);
+            self.write_output(page, html)
+        })?;
+
+        let inlined = inlined.into_inner();
+        self.progress.stage_done(
+            "Inlining small assets",
+            inlined,
+            Some(stage_start.elapsed()),
+        );
+        Ok(inlined)
+    }
+
+    /// Rewrites trailing-slash links in every built page for `clean_urls =
+    /// false`: a link to a folder becomes its `index.html`, and a link to a
+    /// page becomes the page's `.html` file. A folder's `index.html` wins
+    /// when a page and a folder share a URL, so breadcrumbs keep pointing at
+    /// folder listings. Links to anything else are left for the link checker.
+    fn rewrite_flat_links(&self) -> Result<(), BuildError> {
+        use rayon::prelude::*;
+
+        let stage_start = Instant::now();
+        self.progress.stage("Rewriting links for .html output...");
+        let pages: Vec<PathBuf> = WalkDir::new(&self.output_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+            .collect();
+
+        pages.par_iter().try_for_each(|page| {
+            let Ok(html) = fs::read_to_string(page) else {
+                return Ok(());
+            };
+            let rewritten = crate::link_transform::rewrite_directory_links(&html, |path| {
+                let decoded = percent_decode_str(path).decode_utf8_lossy();
+                let target = match decoded.strip_prefix('/') {
+                    Some(root_relative) => self.output_dir.join(root_relative),
+                    None => page.parent()?.join(decoded.as_ref()),
+                };
+                let target = normalize_path(&target);
+                if !target.starts_with(&self.output_dir) {
+                    return None;
+                }
+                if target.join("index.html").is_file() {
+                    return Some(format!("{path}index.html"));
+                }
+                // Only a named last segment can be a page (not `./` or `../`)
+                let stem = path.trim_end_matches('/');
+                let names_page = stem
+                    .rsplit('/')
+                    .next()
+                    .is_some_and(|last| !matches!(last, "" | "." | ".."));
+                let file_name = target.file_name()?.to_string_lossy();
+                (names_page && target.with_file_name(format!("{file_name}.html")).is_file())
+                    .then(|| format!("{stem}.html"))
+            });
+            if rewritten == html {
+                return Ok(());
+            }
+            self.write_output(page, rewritten)
+        })?;
+
+        self.progress.stage_done(
+            "Rewriting links for .html output",
+            pages.len(),
+            Some(stage_start.elapsed()),
+        );
+        Ok(())
+    }
+
+    /// Writes a transcode (`song.flac.opus`, ...) next to every lossless audio
+    /// asset, as configured by `audio_transcode`.
+    ///
+    /// Files that fail to transcode are skipped and keep being served in their
+    /// original format only.
+    #[cfg(feature = "media-metadata")]
+    fn generate_audio_transcodes(&self, format: AudioTranscodeFormat) -> Result<usize, BuildError> {
+        let stage_start = Instant::now();
+        self.progress.stage("Transcoding audio...");
+
+        let tracks: Vec<_> = self
+            .repo
+            .other_files
+            .pin()
+            .iter()
+            .filter(|(_, info)| audio::is_transcodable_audio(&info.url_path))
+            .map(|(_, info)| info.clone())
+            .collect();
+
+        let bitrate_kbps = self.config.audio_transcode_bitrate_kbps;
+        let error: FirstError<BuildError> = FirstError::new();
+        let written = AtomicUsize::new(0);
+        {
+            use rayon::prelude::*;
+            tracks.par_iter().for_each(|file_info| {
+                if error.is_set() {
+                    return;
+                }
+                let data = match crate::audio_transcode::transcode_audio(
+                    &file_info.raw_path,
+                    format,
+                    bitrate_kbps,
+                    self.config.transcode_tmp_dir.as_deref(),
+                ) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Skipping {} transcode of {}: {}",
+                            format.extension(),
+                            file_info.raw_path.display(),
+                            e
+                        );
+                        return;
+                    }
+                };
+
+                let output_path = self
+                    .output_dir
+                    .join(file_info.url_path.trim_start_matches('/'));
+                if let Some(parent) = output_path.parent()
+                    && let Err(e) = self.sink.create_dir(parent)
+                {
+                    error.record(BuildError::CreateDirFailed {
+                        path: parent.to_path_buf(),
+                        source: e,
+                    });
+                    return;
+                }
+                let mut variant_name = output_path.as_os_str().to_os_string();
+                variant_name.push(".");
+                variant_name.push(format.extension());
+                if let Err(e) = self.sink.write_file(Path::new(&variant_name), &data) {
+                    error.record(BuildError::WriteFailed {
+                        path: PathBuf::from(variant_name),
+                        source: e,
+                    });
+                    return;
+                }
+                written.fetch_add(1, Ordering::Relaxed);
+                self.audio_variants.pin().insert(output_path, format);
+            });
+        }
         error.into_result()?;
 
-        print_stage_done("Writing link files", count, Some(stage_start.elapsed()));
+        let written = written.into_inner();
+        self.progress
+            .stage_done("Transcoding audio", written, Some(stage_start.elapsed()));
+        Ok(written)
+    }
+
+    /// Audio transcoding needs ffmpeg, which this build of mbr doesn't include.
+    #[cfg(not(feature = "media-metadata"))]
+    fn generate_audio_transcodes(&self, format: AudioTranscodeFormat) -> Result<usize, BuildError> {
+        eprintln!(
+            "Warning: this build of mbr has no ffmpeg support; skipping {} audio transcoding",
+            format.extension()
+        );
+        Ok(0)
+    }
+
+    /// Lists the transcoded variant ahead of lossless audio sources that have one.
+    ///
+    /// `page_output_path` is resolved the same way as in
+    /// [`Self::wrap_images_with_variants`].
+    fn add_audio_transcode_sources(&self, page_output_path: &Path, html: String) -> String {
+        if self.audio_variants.is_empty() {
+            return html;
+        }
+        let variants = self.audio_variants.pin();
+        audio::add_transcoded_sources(&html, |src| {
+            self.resolve_link(page_output_path, src)
+                .and_then(|path| variants.get(&path).copied())
+        })
+    }
+
+    /// Offers the server's on-the-fly audio transcodes, for server-cache pages.
+    ///
+    /// Only sources the server can find in the repo or its static folder
+    /// get one; remote and missing files can't be transcoded.
+    fn add_server_audio_sources(&self, page_url: &str, html: String) -> String {
+        if !cfg!(feature = "media-metadata") {
+            return html;
+        }
+        match self
+            .config
+            .audio_transcode
+            .as_deref()
+            .and_then(AudioTranscodeFormat::from_name)
+        {
+            Some(format) => audio::add_transcoded_sources(&html, |src| {
+                let path = audio::local_source_path(src, page_url)?;
+                let root = &self.config.root_dir;
+                (root.join(&path).is_file()
+                    || root.join(&self.config.static_folder).join(&path).is_file())
+                .then_some(format)
+            }),
+            None => html,
+        }
+    }
+
+    /// Writes the marker that lets the server serve these pages.
+    fn write_server_cache_marker(&self) -> Result<(), BuildError> {
+        let marker = crate::server_cache::CacheMarker {
+            clean_urls: self.config.clean_urls,
+            index_file: self.config.index_file.clone(),
+            edit_enabled: self.config.edit_enabled,
+            csp_nonce: self.csp.as_ref().map(|csp| csp.nonce.clone()),
+        };
+        let path = self.output_dir.join(crate::server_cache::MARKER_FILE);
+        if let Some(parent) = path.parent() {
+            self.create_output_dir(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(&marker).expect("marker serializes");
+        self.write_output(&path, json)
+    }
+
+    /// Writes links.json files for all pages with bidirectional link information.
+    ///
+    /// This method:
+    /// 1. Builds an inbound link index by inverting the outbound links
+    /// 2. Writes links.json files in parallel for each page
+    /// 3. Writes the consolidated `.mbr/links.json`, per `link_index_mode`
+    async fn write_link_files(&self) -> Result<usize, BuildError> {
+        let stage_start = Instant::now();
+        self.progress.stage("Building link index...");
+
+        // Step 1: Build the inbound index by inverting outbound links
+        // For each outbound link from page A to page B, create an inbound link on page B from A
+        // Also collect the outbound index into a regular HashMap for thread-safe parallel access
+        let outbound_guard = self.build_link_index.pin();
+        let mut inbound_index: HashMap<String, Vec<InboundLink>> = HashMap::new();
+        let mut outbound_index: HashMap<String, Vec<OutboundLink>> = HashMap::new();
+
+        for (source_url, (is_index_file, outbound_links)) in outbound_guard.iter() {
+            // Copy outbound links to our local HashMap
+            outbound_index.insert(source_url.clone(), outbound_links.clone());
+
+            for link in outbound_links {
+                // Only track internal links for inbound
+                if !link.internal {
+                    continue;
+                }
+
+                // Resolve the relative URL to an absolute URL based on the source page
+                let target_url = resolve_relative_url(source_url, &link.to, *is_index_file);
+
+                let inbound_link = InboundLink {
+                    from: source_url.clone(),
+                    text: link.text.clone(),
+                    anchor: link.anchor.clone(),
+                    context: link.context.clone(),
+                    count: 1,
+                };
+
+                inbound_index
+                    .entry(target_url)
+                    .or_default()
+                    .push(inbound_link);
+            }
+        }
+        for links in inbound_index.values_mut() {
+            *links = aggregate_inbound_by_source(std::mem::take(links));
+        }
+
+        // Step 2: Collect all known page URLs that can actually render backlinks.
+        // We intentionally exclude inbound_index keys here — those may contain URLs
+        // to static files (PDFs, images) which can't display backlinks. Creating
+        // directories for them would shadow the actual file symlinks during asset linking.
+        let all_page_urls: HashSet<String> = {
+            let mut urls: HashSet<String> = outbound_index.keys().cloned().collect();
+
+            // Include all markdown pages (even those without links)
+            for (_, info) in self.repo.markdown_files.pin().iter() {
+                urls.insert(info.url_path.clone());
+            }
+
+            // Include tag page URLs if tag pages are enabled
+            if self.config.build_tag_pages {
+                for tag_source in &self.config.tag_sources {
+                    let source = tag_source.url_source();
+
+                    // Add tag source index URL (e.g., "/tags/")
+                    urls.insert(format!("/{}/", source));
+
+                    // Add individual tag page URLs (e.g., "/tags/rust/")
+                    for tag in self.repo.tag_index.get_all_tags(&source) {
+                        urls.insert(format!("/{}/{}/", source, tag.normalized));
+                    }
+                }
+            }
+
+            urls
+        };
+
+        let mut page_urls: Vec<String> = all_page_urls.into_iter().collect();
+        let mode = self.config.link_index_mode;
+        let mut written = 0;
+
+        if mode.writes_per_page() {
+            written +=
+                self.write_per_page_link_files(&page_urls, &outbound_index, &inbound_index)?;
+        }
+
+        if mode.writes_consolidated() {
+            page_urls.sort_unstable();
+            self.write_consolidated_link_file(&page_urls, &outbound_index, &inbound_index)?;
+            written += 1;
+        }
+
+        self.progress
+            .stage_done("Writing link files", written, Some(stage_start.elapsed()));
+        Ok(written)
+    }
+
+    /// Writes a links.json next to each page, in parallel.
+    fn write_per_page_link_files(
+        &self,
+        page_urls: &[String],
+        outbound_index: &HashMap<String, Vec<OutboundLink>>,
+        inbound_index: &HashMap<String, Vec<InboundLink>>,
+    ) -> Result<usize, BuildError> {
+        let count = page_urls.len();
+        let concurrency = self.get_concurrency();
+
+        // Progress counter for parallel writing
+        let completed = Arc::new(AtomicUsize::new(0));
+        self.progress.progress("Writing link files", 0, count);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(concurrency)
+            .build()
+            .map_err(|e| BuildError::CreateDirFailed {
+                path: self.output_dir.clone(),
+                source: std::io::Error::other(format!("Failed to create rayon thread pool: {}", e)),
+            })?;
+
+        let error: FirstError<BuildError> = FirstError::new();
+
+        pool.install(|| {
+            use rayon::prelude::*;
+            page_urls.par_iter().for_each(|url_path| {
+                if error.is_set() {
+                    return;
+                }
+                let page_links = self.page_links(url_path, outbound_index, inbound_index);
+                match self.write_single_link_file(url_path, &page_links) {
+                    Ok(()) => {
+                        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                        if done.is_multiple_of(100) || done == count {
+                            self.progress.progress("Writing link files", done, count);
+                        }
+                    }
+                    Err(e) => error.record(e),
+                }
+            });
+        });
+
+        error.into_result()?;
         Ok(count)
     }
 
-    /// Writes a single links.json file for a page.
-    fn write_single_link_file(
+    /// Writes `.mbr/links.json`, mapping each page URL to its links, so the
+    /// whole link graph loads in one fetch. `page_urls` should be sorted.
+    fn write_consolidated_link_file(
         &self,
-        url_path: &str,
+        page_urls: &[String],
         outbound_index: &HashMap<String, Vec<OutboundLink>>,
         inbound_index: &HashMap<String, Vec<InboundLink>>,
     ) -> Result<(), BuildError> {
+        let mut all_links = serde_json::Map::new();
+        for url_path in page_urls {
+            let page_links = self.page_links(url_path, outbound_index, inbound_index);
+            all_links.insert(
+                url_path.clone(),
+                serde_json::to_value(page_links).unwrap_or_default(),
+            );
+        }
+
+        let mbr_output = self.output_dir.join(".mbr");
+        self.create_output_dir(&mbr_output)?;
+
+        let output_path = mbr_output.join("links.json");
+        let json = self
+            .to_json(&all_links)
+            .map_err(|e| BuildError::WriteFailed {
+                path: output_path.clone(),
+                source: std::io::Error::other(format!("JSON serialization failed: {}", e)),
+            })?;
+
+        self.write_output(&output_path, json)
+    }
+
+    /// Collects the inbound, outbound, and typed links for a page.
+    fn page_links(
+        &self,
+        url_path: &str,
+        outbound_index: &HashMap<String, Vec<OutboundLink>>,
+        inbound_index: &HashMap<String, Vec<InboundLink>>,
+    ) -> PageLinks {
         // Try to build tag page outbound links, or fall back to the index
         let outbound = self
             .try_build_tag_outbound(url_path)
@@ -716,12 +1978,29 @@ impl Builder {
             Vec::new()
         };
 
-        let page_links = PageLinks {
+        PageLinks {
             inbound,
             outbound,
             relationships,
-        };
+        }
+    }
+
+    /// Serializes the JSON files written alongside pages: indented with
+    /// `pretty_json`, else compact.
+    fn to_json<T: serde::Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<String> {
+        if self.config.pretty_json {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        }
+    }
 
+    /// Writes a single links.json file for a page.
+    fn write_single_link_file(
+        &self,
+        url_path: &str,
+        page_links: &PageLinks,
+    ) -> Result<(), BuildError> {
         // Determine output path: url_path → build/{url_path}/links.json
         let url_path_stripped = url_path.trim_start_matches('/');
         let output_path = if url_path_stripped.is_empty() || url_path == "/" {
@@ -732,26 +2011,83 @@ impl Builder {
 
         // Create parent directories
         if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                path: parent.to_path_buf(),
-                source: e,
-            })?;
+            self.create_output_dir(parent)?;
         }
 
         // Write JSON file
-        let json = serde_json::to_string(&page_links).map_err(|e| BuildError::WriteFailed {
-            path: output_path.clone(),
-            source: std::io::Error::other(format!("JSON serialization failed: {}", e)),
-        })?;
+        let json = self
+            .to_json(page_links)
+            .map_err(|e| BuildError::WriteFailed {
+                path: output_path.clone(),
+                source: std::io::Error::other(format!("JSON serialization failed: {}", e)),
+            })?;
 
-        fs::write(&output_path, json).map_err(|e| BuildError::WriteFailed {
-            path: output_path,
-            source: e,
-        })?;
+        self.write_output(&output_path, json)?;
 
         Ok(())
     }
 
+    /// The page written in place of a page with frontmatter `redirect_to`
+    /// (see [`crate::redirect`]), or `None` when it doesn't redirect. The
+    /// refresh target is made relative at `depth`; the canonical link is
+    /// absolute when `base_url` is set.
+    fn redirect_page(&self, info: &MarkdownInfo, depth: usize) -> Option<String> {
+        let frontmatter = info.frontmatter.as_ref()?;
+        if redirect::has_invalid_target(frontmatter) {
+            tracing::warn!(
+                "Ignoring redirect_to in {}: expected a root-relative path or http(s) URL",
+                info.raw_path.display()
+            );
+            return None;
+        }
+        let target = redirect::redirect_target(frontmatter)?;
+        if !target.starts_with('/') {
+            return Some(redirect::redirect_page(&target, &target));
+        }
+        let href = make_relative_url(&target, depth);
+        let canonical = match self.config.base_url.as_deref() {
+            Some(base_url) => format!("{}{target}", base_url.trim_end_matches('/')),
+            None => href.clone(),
+        };
+        Some(redirect::redirect_page(&href, &canonical))
+    }
+
+    /// The output file for a markdown page and its depth below the output
+    /// root, for relative links. Pages are written to `{url}/index.html`, or
+    /// with `clean_urls` off to `{url}.html` beside their source (index
+    /// pages keep `index.html`).
+    fn page_output_location(&self, url_path: &str, is_index_file: bool) -> (PathBuf, usize) {
+        page_output_path(
+            &self.output_dir,
+            url_path,
+            is_index_file,
+            self.config.clean_urls,
+        )
+    }
+
+    /// Root-relative URL a page is served at from the output directory:
+    /// its `url_path`, or with `clean_urls` off its `.html` file.
+    fn page_url(&self, info: &MarkdownInfo) -> String {
+        if self.config.clean_urls {
+            return info.url_path.clone();
+        }
+        let is_index_file = info
+            .raw_path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .is_some_and(|f| f == self.config.index_file);
+        let (output_path, _) = self.page_output_location(&info.url_path, is_index_file);
+        let relative = output_path
+            .strip_prefix(&self.output_dir)
+            .unwrap_or(&output_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        format!(
+            "/{}",
+            relative.strip_suffix("index.html").unwrap_or(&relative)
+        )
+    }
+
     /// Synchronous version of `render_single_markdown` for use with rayon parallelism.
     ///
     /// All I/O in the render pipeline is already synchronous (std::fs, pulldown-cmark,
@@ -773,12 +2109,25 @@ impl Builder {
             .and_then(|f| f.to_str())
             .is_some_and(|f| f == self.config.index_file);
 
+        // Where the page is written and how deep that is below the root
+        let (output_path, depth) = self.page_output_location(&info.url_path, is_index_file);
+
+        // Pages with `redirect_to` get a stand-in page instead of their body
+        if let Some(html) = self.redirect_page(info, depth) {
+            if let Some(parent) = output_path.parent() {
+                self.create_output_dir(parent)?;
+            }
+            return self.write_output(&output_path, html);
+        }
+
+        // Server-cache pages link like the server: absolute, clean URLs
         let link_transform_config = LinkTransformConfig {
             markdown_extensions: self.config.markdown_extensions.clone(),
             index_file: self.config.index_file.clone(),
             is_index_file,
-            url_depth: Some(url_depth(&info.url_path)),
+            url_depth: (!self.server_cache).then_some(depth),
             current_page_url: info.url_path.clone(),
+            clean_urls: self.config.clean_urls || self.server_cache,
         };
 
         tracing::debug!("build: rendering {}", path.display());
@@ -786,25 +2135,37 @@ impl Builder {
         // Render markdown to HTML synchronously
         // In build mode, server_mode=false and transcode is disabled (transcode is server-only).
         // Build mode defaults `mark_incomplete=false` (off unless config/CLI override).
+        // Server-cache builds use the server's settings for all three.
         let valid_tag_sources = crate::config::tag_sources_to_set(&self.config.tag_sources);
-        let mark_incomplete = self.config.mark_incomplete.unwrap_or(false);
-        let render_result = markdown::render_sync(
+        let mark_incomplete = self.config.mark_incomplete.unwrap_or(self.server_cache);
+        #[cfg(feature = "media-metadata")]
+        let transcode_enabled = self.server_cache && self.config.transcode;
+        #[cfg(not(feature = "media-metadata"))]
+        let transcode_enabled = false;
+        let mut render_result = markdown::render_sync(
             path.to_path_buf(),
             &self.config.root_dir,
             self.config.oembed_timeout_ms,
+            self.config.oembed_fallback,
             link_transform_config,
             Some(self.oembed_cache.clone()),
-            false, // server_mode is false in build mode
-            false, // transcode is disabled in build mode
+            self.server_cache,
+            transcode_enabled,
             valid_tag_sources,
             mark_incomplete,
             &self.config.incomplete_markers,
             Some(self.repo.wikilink_index.clone()),
+            self.data_files.clone(),
+            markdown::RendererKind::for_path(path, &self.config.renderers),
+            &self.config.markdown_options,
         )
         .map_err(|e| BuildError::RenderFailed {
             path: path.to_path_buf(),
             source: Box::new(crate::MbrError::Io(std::io::Error::other(e.to_string()))),
         })?;
+        if self.config.dedupe_title_h1 {
+            render_result.dedupe_title_h1();
+        }
         // Record any frontmatter parse error so it can be summarized after the
         // parallel render pass completes (mirrors broken-link reporting).
         if let Some(err) = render_result.frontmatter_error {
@@ -812,12 +2173,51 @@ impl Builder {
                 .pin()
                 .insert(info.url_path.clone(), err);
         }
+        if !render_result.gallery_images.is_empty() {
+            let gallery_images = self.gallery_images.pin();
+            for image in render_result.gallery_images.drain(..) {
+                gallery_images.insert(image.url_path, image.source);
+            }
+        }
 
         let mut frontmatter = render_result.frontmatter;
-        let headings = render_result.headings;
-        let html = render_result.html;
+        let headings = self.config.toc_options().apply(render_result.headings);
+        // The server doesn't serve the build's image and audio variants
+        let html = if self.server_cache {
+            self.add_server_audio_sources(&info.url_path, render_result.html)
+        } else {
+            let html = self.wrap_images_with_variants(&output_path, render_result.html);
+            self.add_audio_transcode_sources(&output_path, html)
+        };
+        let html = post_render::apply_hooks(
+            &self.post_render_hooks,
+            html,
+            &PostRenderPage {
+                url_path: &info.url_path,
+                server_mode: self.server_cache,
+            },
+        );
+        let html = if self.config.privacy_embeds {
+            media::privacy_embeds(&html)
+        } else {
+            html
+        };
+        let html = match &self.config.media_max_width {
+            Some(max_width) => media::constrain_media_width(&html, max_width),
+            None => html,
+        };
+        let html = if self.config.lazy_images {
+            media::add_lazy_loading(&html)
+        } else {
+            html
+        };
+        let published_url = self.page_url(info);
+        if self.wants_feed_body(&published_url) {
+            self.feed_bodies.pin().insert(published_url, html.clone());
+        }
         let outbound_links = render_result.outbound_links;
         let has_h1 = render_result.has_h1;
+        let title_source = render_result.title_source;
         let word_count = render_result.word_count;
         let readability_counts = crate::readability::ReadabilityCounts {
             words: render_result.word_count,
@@ -835,31 +2235,50 @@ impl Builder {
 
         tracing::debug!("build: rendered {}", path.display());
 
-        // Add markdown_source to frontmatter
-        frontmatter.insert(
-            "markdown_source".to_string(),
-            serde_json::Value::String(info.url_path.clone()),
-        );
-
-        // Indicate static mode (no dynamic search endpoint)
-        // Boolean false is falsy in Tera templates
-        frontmatter.insert("server_mode".to_string(), serde_json::json!(false));
-
-        // Calculate page depth for relative path generation
-        let depth = url_depth(&info.url_path);
-
         // File path (relative to root) for reference
         let relative_path = path
             .strip_prefix(&self.config.root_dir)
             .unwrap_or(path)
             .to_string_lossy();
 
-        // Modified date from file metadata
-        let modified_secs = std::fs::metadata(path)
-            .ok()
-            .and_then(|m| m.modified().ok())
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
+        if self.server_cache {
+            // The same keys the server sets; live reload matches on the
+            // relative source path
+            frontmatter.insert(
+                "markdown_source".to_string(),
+                serde_json::Value::String(relative_path.to_string()),
+            );
+            frontmatter.insert("server_mode".to_string(), "true".into());
+            frontmatter.insert("gui_mode".to_string(), "".into());
+            frontmatter.insert(
+                "edit_enabled".to_string(),
+                if self.config.edit_enabled { "true" } else { "" }.into(),
+            );
+        } else {
+            // Add markdown_source to frontmatter
+            frontmatter.insert(
+                "markdown_source".to_string(),
+                serde_json::Value::String(info.url_path.clone()),
+            );
+
+            // Indicate static mode (no dynamic search endpoint)
+            // Boolean false is falsy in Tera templates
+            frontmatter.insert("server_mode".to_string(), serde_json::json!(false));
+        }
+
+        // Modified date from the last git commit, else file metadata
+        let last_commit = self
+            .git_metadata
+            .as_ref()
+            .and_then(|git| git.last_commit(Path::new(relative_path.as_ref())));
+        let modified_secs = match last_commit {
+            Some(commit) => u64::try_from(commit.timestamp).ok(),
+            None => std::fs::metadata(path)
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        };
 
         // Prev/next sibling pages come from the pre-built index
         let parent_dir = info
@@ -872,53 +2291,74 @@ impl Builder {
 
         // Build the extra context (navigation, TOC, readability, chrome) via
         // the shared builder; static builds relativize URLs to the page depth.
-        let extra_context = page_context::markdown_extra_context(
+        let mut extra_context = page_context::markdown_extra_context(
             &page_context::MarkdownPageParams {
                 breadcrumb_path: std::path::Path::new(&info.url_path),
                 headings: &headings,
                 has_h1,
+                title_source,
                 word_count,
                 readability: &readability_scores,
                 file_path: &relative_path,
                 modified_secs,
+                last_commit,
+                dates: crate::dates::PageDates::new(&frontmatter, modified_secs),
+                robots: Robots::for_page(&frontmatter, self.config.robots.as_deref()),
+                assets: PageAssets::for_page_sanitized(&frontmatter, self.config.sanitize_html),
                 current_url: &info.url_path,
-                siblings,
+                siblings: ReadingOrder::sequence_for(
+                    self.reading_order.as_ref(),
+                    &info.url_path,
+                    siblings,
+                ),
             },
             &page_context::MarkdownContextOptions {
                 tag_sources: &self.config.tag_sources,
                 sidebar_style: &self.config.sidebar_style,
                 sidebar_max_items: self.config.sidebar_max_items,
+                header_height: self.config.header_height.as_deref(),
+                smooth_scroll: self.config.smooth_scroll,
+                search: SearchUi {
+                    placeholder: &self.config.search_placeholder,
+                    result_limit: self.config.search_result_limit,
+                },
+                language: SiteLanguage {
+                    lang: &self.config.site_lang,
+                    dir: &self.config.site_dir,
+                },
+                branding: &self.branding,
                 title_prefix: &self.config.title_prefix,
                 title_suffix: &self.config.title_suffix,
+                site_nav: self.site_nav.as_deref(),
+                interactive_tasklists: self.config.interactive_tasklists.unwrap_or(false),
+                scroll_spy: self.config.scroll_spy,
+                edit_url_template: self.config.edit_url_template.as_deref(),
+                privacy_embeds: self.config.privacy_embeds,
+                csp: self.csp.as_ref(),
+                mermaid_config: &self.mermaid_config,
+            },
+            &if self.server_cache {
+                page_context::UrlMode::Absolute
+            } else {
+                page_context::UrlMode::RelativeToDepth(depth)
             },
-            &page_context::UrlMode::RelativeToDepth(depth),
         );
+        let feeds = self.section_feed_links(&info.url_path, depth);
+        if !feeds.is_empty() && !self.server_cache {
+            extra_context.insert("feeds".to_string(), serde_json::Value::Array(feeds));
+        }
 
         // Render through template (lock-free — uses pre-cloned Tera)
         let html_output =
             Templates::render_markdown_with_tera(tera, &html, frontmatter, extra_context)?;
 
-        // Determine output path: url_path → build/{url_path}/index.html
-        let url_path = info.url_path.trim_start_matches('/');
-        let output_path = if url_path.is_empty() || url_path == "/" {
-            self.output_dir.join("index.html")
-        } else {
-            self.output_dir.join(url_path).join("index.html")
-        };
-
         // Create parent directories
         if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                path: parent.to_path_buf(),
-                source: e,
-            })?;
+            self.create_output_dir(parent)?;
         }
 
         // Write HTML file
-        fs::write(&output_path, html_output).map_err(|e| BuildError::WriteFailed {
-            path: output_path,
-            source: e,
-        })?;
+        self.write_output(&output_path, html_output)?;
 
         Ok(())
     }
@@ -926,28 +2366,20 @@ impl Builder {
     /// Generates directory/section pages in parallel.
     async fn render_directory_pages(&self) -> Result<usize, BuildError> {
         let stage_start = Instant::now();
-        // Collect all directories that need section pages
-        let mut directories: HashSet<PathBuf> = HashSet::new();
-
-        // Add root directory
-        directories.insert(PathBuf::new());
-
-        // Add all parent directories of markdown files
-        for (_, info) in self.repo.markdown_files.pin().iter() {
-            let url_path = info.url_path.trim_start_matches('/').trim_end_matches('/');
-            if !url_path.is_empty() {
-                let mut current = PathBuf::new();
-                for component in Path::new(url_path)
-                    .parent()
-                    .into_iter()
-                    .flat_map(|p| p.components())
-                {
-                    if let std::path::Component::Normal(s) = component {
-                        current.push(s);
-                        directories.insert(current.clone());
-                    }
-                }
-            }
+        // Pre-build a directory→children index so each section page looks up its
+        // direct child files and immediate subdirectories in O(children) instead
+        // of rescanning every markdown file (was O(dirs × files)). Mirrors the
+        // `sibling_index` in `render_markdown_files`. File JSON keeps its
+        // absolute `url_path`; each page relativizes + sorts its own slice, so
+        // output ordering/behavior is preserved. Its keys are every directory
+        // that needs a section page: the root plus each ancestor of a file.
+        let dir_index = Arc::new(build_dir_children_index(
+            self.repo.markdown_files.pin().iter().map(|(_, info)| info),
+        ));
+
+        let mut directories: Vec<PathBuf> = dir_index.keys().cloned().collect();
+        if let Some(prefix) = &self.only {
+            directories.retain(|dir| dir.starts_with(prefix));
         }
 
         let count = directories.len();
@@ -959,25 +2391,12 @@ impl Builder {
             concurrency
         );
 
-        // Pre-build a directory→children index so each section page looks up its
-        // direct child files and immediate subdirectories in O(children) instead
-        // of rescanning every markdown file (was O(dirs × files)). Mirrors the
-        // `sibling_index` in `render_markdown_files`. File JSON keeps its
-        // absolute `url_path`; each page relativizes + sorts its own slice, so
-        // output ordering/behavior is preserved.
-        let dir_index = Arc::new(build_dir_children_index(
-            self.repo.markdown_files.pin().iter().map(|(_, info)| info),
-        ));
-
         // Clone Tera once before entering the rayon pool to avoid per-file lock contention
         let tera_snapshot = self.templates.tera_clone();
 
         // Progress counter for parallel rendering
         let completed = Arc::new(AtomicUsize::new(0));
-        print_progress("Generating sections", 0, count);
-
-        // Convert HashSet to Vec for rayon iteration
-        let directories: Vec<_> = directories.into_iter().collect();
+        self.progress.progress("Generating sections", 0, count);
 
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(concurrency)
@@ -999,7 +2418,7 @@ impl Builder {
                     Ok(()) => {
                         let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
                         if done.is_multiple_of(100) || done == count {
-                            print_progress("Generating sections", done, count);
+                            self.progress.progress("Generating sections", done, count);
                         }
                     }
                     Err(e) => error.record(e),
@@ -1009,7 +2428,8 @@ impl Builder {
 
         error.into_result()?;
 
-        print_stage_done("Generating sections", count, Some(stage_start.elapsed()));
+        self.progress
+            .stage_done("Generating sections", count, Some(stage_start.elapsed()));
         Ok(count)
     }
 
@@ -1027,44 +2447,12 @@ impl Builder {
         let is_root = relative_dir.as_os_str().is_empty();
 
         // Calculate page depth for relative path generation
-        let depth = if is_root {
+        let dir_depth = if is_root {
             0
         } else {
             relative_dir.components().count()
         };
 
-        // Build context for template
-        let mut context: HashMap<String, serde_json::Value> = HashMap::new();
-
-        // Breadcrumbs with relative URLs
-        let breadcrumbs = generate_breadcrumbs(relative_dir);
-        let breadcrumbs_json =
-            page_context::breadcrumbs_to_json(&breadcrumbs, &UrlMode::RelativeToDepth(depth));
-        context.insert(
-            "breadcrumbs".to_string(),
-            serde_json::Value::Array(breadcrumbs_json),
-        );
-
-        // Current directory name
-        let current_dir_name = if is_root {
-            "Home".to_string()
-        } else {
-            get_current_dir_name(relative_dir)
-        };
-        context.insert(
-            "current_dir_name".to_string(),
-            serde_json::Value::String(current_dir_name),
-        );
-
-        // Parent path (relative)
-        if let Some(parent) = get_parent_path(relative_dir) {
-            let relative_parent = make_relative_url(&parent, depth);
-            context.insert(
-                "parent_path".to_string(),
-                serde_json::Value::String(relative_parent),
-            );
-        }
-
         // Collect files in this directory
         let dir_prefix = if is_root {
             "/".to_string()
@@ -1077,89 +2465,165 @@ impl Builder {
         let empty_children: (Vec<serde_json::Value>, HashSet<String>) = Default::default();
         let (dir_files, dir_subdirs) = dir_index.get(relative_dir).unwrap_or(&empty_children);
 
-        // Relativize each file's url_path for this page's depth.
-        let mut files: Vec<serde_json::Value> = dir_files
-            .iter()
-            .map(|file_json| {
-                let mut file_json = file_json.clone();
-                if let Some(obj) = file_json.as_object_mut()
-                    && let Some(abs_url) = obj.get("url_path").and_then(|v| v.as_str())
-                {
-                    obj.insert(
-                        "url_path".to_string(),
-                        serde_json::Value::String(make_relative_url(abs_url, depth)),
-                    );
-                }
-                file_json
-            })
-            .collect();
+        // Sort files using configurable sort order (before splitting into pages)
+        let mut all_files = dir_files.clone();
+        sort_files(&mut all_files, &self.config.sort);
 
-        // Sort files using configurable sort order
-        sort_files(&mut files, &self.config.sort);
+        let chunks = page_context::paginate(&all_files, self.config.page_size);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let page = index + 1;
+            // Later pages live at {dir}/page/N/, two levels deeper
+            let depth = if page > 1 { dir_depth + 2 } else { dir_depth };
 
-        context.insert("files".to_string(), serde_json::Value::Array(files));
+            // Build context for template
+            let mut context: HashMap<String, serde_json::Value> = HashMap::new();
 
-        // Convert subdirs to JSON array with name and relative url_path
-        let subdirs_json: Vec<serde_json::Value> = dir_subdirs
-            .iter()
-            .map(|name| {
-                let abs_url_path = if is_root {
-                    format!("/{}/", name)
-                } else {
-                    format!("{}{}/", dir_prefix, name)
-                };
-                serde_json::json!({
-                    "name": name,
-                    "url_path": make_relative_url(&abs_url_path, depth)
+            // Breadcrumbs with relative URLs
+            let breadcrumbs = generate_breadcrumbs(relative_dir);
+            let breadcrumbs_json =
+                page_context::breadcrumbs_to_json(&breadcrumbs, &UrlMode::RelativeToDepth(depth));
+            context.insert(
+                "breadcrumbs".to_string(),
+                serde_json::Value::Array(breadcrumbs_json),
+            );
+
+            // Current directory name
+            let current_dir_name = if is_root {
+                "Home".to_string()
+            } else {
+                get_current_dir_name(relative_dir)
+            };
+            context.insert(
+                "current_dir_name".to_string(),
+                serde_json::Value::String(current_dir_name),
+            );
+
+            // Parent path (relative)
+            if let Some(parent) = get_parent_path(relative_dir) {
+                let relative_parent = make_relative_url(&parent, depth);
+                context.insert(
+                    "parent_path".to_string(),
+                    serde_json::Value::String(relative_parent),
+                );
+            }
+
+            // Relativize each file's URLs and cover images for this page's depth.
+            let files: Vec<serde_json::Value> = chunk
+                .iter()
+                .map(|file_json| {
+                    let mut file_json = file_json.clone();
+                    if let Some(obj) = file_json.as_object_mut() {
+                        for key in ["url_path", "url", "cover_image", "cover_thumbnail"] {
+                            if let Some(abs_url) = obj.get(key).and_then(|v| v.as_str())
+                                && abs_url.starts_with('/')
+                                && !abs_url.starts_with("//")
+                            {
+                                let relative = make_relative_url(abs_url, depth);
+                                obj.insert(key.to_string(), serde_json::Value::String(relative));
+                            }
+                        }
+                    }
+                    file_json
                 })
-            })
-            .collect();
-        context.insert(
-            "subdirs".to_string(),
-            serde_json::Value::Array(subdirs_json),
-        );
+                .collect();
 
-        // Pass tag_sources configuration for frontend (consistent with markdown pages)
-        context.insert(
-            "tag_sources".to_string(),
-            serde_json::json!(page_context::tag_sources_json(&self.config.tag_sources)),
-        );
+            context.insert("files".to_string(), serde_json::Value::Array(files));
+            page_context::insert_listing_layout(
+                &mut context,
+                &self.config.listing_layout,
+                self.config.listing_thumbnails,
+            );
 
-        // Mode flags, sidebar navigation configuration, and title affixes
-        page_context::insert_page_chrome(
-            &mut context,
-            &PageChrome {
-                mode: ModeFlags::Static { depth },
-                sidebar_style: &self.config.sidebar_style,
-                sidebar_max_items: self.config.sidebar_max_items,
-                title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
-            },
-        );
+            // Convert subdirs to JSON array with name and relative url_path
+            let subdirs_json: Vec<serde_json::Value> = dir_subdirs
+                .iter()
+                .map(|name| {
+                    let abs_url_path = if is_root {
+                        format!("/{}/", name)
+                    } else {
+                        format!("{}{}/", dir_prefix, name)
+                    };
+                    serde_json::json!({
+                        "name": name,
+                        "url_path": make_relative_url(&abs_url_path, depth),
+                        "url": make_relative_url(&encode_url_path(&abs_url_path), depth)
+                    })
+                })
+                .collect();
+            context.insert(
+                "subdirs".to_string(),
+                serde_json::Value::Array(subdirs_json),
+            );
 
-        // Render template (lock-free — uses pre-cloned Tera)
-        let template_name = if is_root { "home.html" } else { "section.html" };
-        let html_output = Templates::render_template_with_tera(tera, template_name, context)?;
+            page_context::insert_pagination_keys(
+                &mut context,
+                &dir_prefix,
+                page,
+                chunks.len(),
+                &UrlMode::RelativeToDepth(depth),
+            );
 
-        // Determine output path
-        let output_path = if is_root {
-            self.output_dir.join("index.html")
-        } else {
-            self.output_dir.join(relative_dir).join("index.html")
-        };
+            // Pass tag_sources configuration for frontend (consistent with markdown pages)
+            context.insert(
+                "tag_sources".to_string(),
+                serde_json::json!(page_context::tag_sources_json(&self.config.tag_sources)),
+            );
 
-        // Only write if file doesn't exist (markdown files take precedence)
-        if !output_path.exists() {
-            if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
+            let feeds = self.section_feed_links(&dir_prefix, depth);
+            if !feeds.is_empty() {
+                context.insert("feeds".to_string(), serde_json::Value::Array(feeds));
             }
 
-            fs::write(&output_path, html_output).map_err(|e| BuildError::WriteFailed {
-                path: output_path,
-                source: e,
-            })?;
+            // Mode flags, sidebar navigation configuration, and title affixes
+            page_context::insert_page_chrome(
+                &mut context,
+                &PageChrome {
+                    mode: ModeFlags::Static { depth },
+                    sidebar_style: &self.config.sidebar_style,
+                    sidebar_max_items: self.config.sidebar_max_items,
+                    header_height: self.config.header_height.as_deref(),
+                    smooth_scroll: self.config.smooth_scroll,
+                    search: Some(SearchUi {
+                        placeholder: &self.config.search_placeholder,
+                        result_limit: self.config.search_result_limit,
+                    }),
+                    language: Some(SiteLanguage {
+                        lang: &self.config.site_lang,
+                        dir: &self.config.site_dir,
+                    }),
+                    branding: Some(&self.branding),
+                    robots: self.config.robots.as_deref(),
+                    csp: self.csp.as_ref(),
+                    title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
+                    site_nav: self.site_nav.as_deref(),
+                },
+            );
+
+            // Render template (lock-free — uses pre-cloned Tera)
+            let template_name = if is_root { "home.html" } else { "section.html" };
+            let html_output = Templates::render_template_with_tera(tera, template_name, context)?;
+
+            // Determine output path
+            let output_path = self
+                .output_dir
+                .join(page_context::paginated_url(&dir_prefix, page).trim_matches('/'))
+                .join("index.html");
+
+            // Only write if file doesn't exist (markdown files take precedence).
+            // A partial build writes over the previous build, so ask the repo
+            // instead of the output directory.
+            let taken = if self.only.is_some() {
+                has_markdown_page(dir_index, &page_context::paginated_url(&dir_prefix, page))
+            } else {
+                self.sink.exists(&output_path)
+            };
+            if !taken {
+                if let Some(parent) = output_path.parent() {
+                    self.create_output_dir(parent)?;
+                }
+
+                self.write_output(&output_path, html_output)?;
+            }
         }
 
         Ok(())
@@ -1193,7 +2657,8 @@ impl Builder {
         }
 
         if tasks.is_empty() {
-            println!("Generating tag pages ... skipped (no tags)");
+            self.progress
+                .finish("Generating tag pages ... skipped (no tags)");
             return Ok(0);
         }
 
@@ -1211,7 +2676,7 @@ impl Builder {
 
         // Progress counter for parallel rendering
         let completed = Arc::new(AtomicUsize::new(0));
-        print_progress("Generating tag pages", 0, count);
+        self.progress.progress("Generating tag pages", 0, count);
 
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(concurrency)
@@ -1238,7 +2703,7 @@ impl Builder {
                     Ok(()) => {
                         let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
                         if done.is_multiple_of(100) || done == count {
-                            print_progress("Generating tag pages", done, count);
+                            self.progress.progress("Generating tag pages", done, count);
                         }
                     }
                     Err(e) => error.record(e),
@@ -1248,7 +2713,8 @@ impl Builder {
 
         error.into_result()?;
 
-        print_stage_done("Generating tag pages", count, Some(stage_start.elapsed()));
+        self.progress
+            .stage_done("Generating tag pages", count, Some(stage_start.elapsed()));
         Ok(count)
     }
 
@@ -1260,26 +2726,21 @@ impl Builder {
         value: &str,
         tera: &tera::Tera,
     ) -> Result<(), BuildError> {
-        let context = self.build_single_tag_page_context(source, value);
-        let context = match context {
-            Some(c) => c,
-            None => return Ok(()),
+        let template = match page_context::tag_data_name(&self.config.tag_sources, source) {
+            Some(_) => "author.html",
+            None => "tag.html",
         };
 
-        let html_output = Templates::render_template_with_tera(tera, "tag.html", context.0)?;
+        // One context per page of the listing (none if the tag is skipped)
+        for (context, output_path) in self.build_single_tag_page_contexts(source, value) {
+            let html_output = Templates::render_template_with_tera(tera, template, context)?;
 
-        // Write file (context.1 = output_path)
-        if !context.1.exists() {
-            if let Some(parent) = context.1.parent() {
-                fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
+            if !self.sink.exists(&output_path) {
+                if let Some(parent) = output_path.parent() {
+                    self.create_output_dir(parent)?;
+                }
+                self.write_output(&output_path, html_output)?;
             }
-            fs::write(&context.1, html_output).map_err(|e| BuildError::WriteFailed {
-                path: context.1,
-                source: e,
-            })?;
         }
 
         Ok(())
@@ -1299,29 +2760,42 @@ impl Builder {
 
         let html_output = Templates::render_template_with_tera(tera, "tag_index.html", context.0)?;
 
-        if !context.1.exists() {
+        if !self.sink.exists(&context.1) {
             if let Some(parent) = context.1.parent() {
-                fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
+                self.create_output_dir(parent)?;
             }
-            fs::write(&context.1, html_output).map_err(|e| BuildError::WriteFailed {
-                path: context.1,
-                source: e,
-            })?;
+            self.write_output(&context.1, html_output)?;
         }
 
         Ok(())
     }
 
-    /// Builds context and output path for a single tag page.
-    /// Returns None if the tag should be skipped (empty sanitized path).
-    fn build_single_tag_page_context(
+    /// Builds context and output path for each page of a tag's listing
+    /// (several with `page_size` set). Returns nothing if the tag should be
+    /// skipped (empty sanitized path).
+    fn build_single_tag_page_contexts(
         &self,
         source: &str,
         value: &str,
-    ) -> Option<(HashMap<String, serde_json::Value>, PathBuf)> {
+    ) -> Vec<(HashMap<String, serde_json::Value>, PathBuf)> {
+        // Sanitize source and value to prevent path traversal
+        let safe_source = crate::wikilink::sanitize_path_component(source);
+        let safe_value = crate::wikilink::sanitize_path_component(value);
+        if safe_source.is_empty() || safe_value.is_empty() {
+            tracing::warn!(
+                "Skipping tag page with empty sanitized source={source:?} value={value:?}"
+            );
+            return Vec::new();
+        }
+
+        let tag_dir = self.output_dir.join(&safe_source).join(&safe_value);
+        if !tag_dir.join("index.html").starts_with(&self.output_dir) {
+            tracing::warn!(
+                "Tag page path escaped output directory: source={source:?} value={value:?}"
+            );
+            return Vec::new();
+        }
+
         // Labels (fallback: raw source name)
         let (singular_label, plural_label) =
             page_context::tag_labels(&self.config.tag_sources, source, source);
@@ -1332,74 +2806,100 @@ impl Builder {
             .get_tag_display(source, value)
             .unwrap_or_else(|| value.to_string());
 
-        let pages = self.repo.tag_index.get_pages(source, value);
+        // Sorted, so pages split the same way on every build
+        let pages = self.repo.pages_with_tag(source, value);
         let url_path = format!("/{}/{}/", source, value);
-        let depth = url_depth(&url_path);
-
-        let mut context: HashMap<String, serde_json::Value> = HashMap::new();
-        page_context::insert_tag_page_keys(
-            &mut context,
-            source,
-            &display_value,
-            &singular_label,
-            &plural_label,
-            &pages,
-            &UrlMode::RelativeToDepth(depth),
-        );
-        page_context::insert_page_chrome(
-            &mut context,
-            &PageChrome {
-                mode: ModeFlags::Static { depth },
-                sidebar_style: &self.config.sidebar_style,
-                sidebar_max_items: self.config.sidebar_max_items,
-                title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
-            },
-        );
+        let chunks = page_context::paginate(&pages, self.config.page_size);
 
-        // Tag pages have synthetic breadcrumbs (Home > plural label)
-        let breadcrumbs_json = vec![
-            serde_json::json!({
-                "name": "Home",
-                "url": make_relative_url("/", depth)
-            }),
-            serde_json::json!({
-                "name": plural_label,
-                "url": make_relative_url(&format!("/{}/", source), depth)
-            }),
-        ];
-        context.insert(
-            "breadcrumbs".to_string(),
-            serde_json::Value::Array(breadcrumbs_json),
-        );
-        context.insert(
-            "current_dir_name".to_string(),
-            serde_json::Value::String(display_value),
-        );
+        let mut contexts = Vec::with_capacity(chunks.len());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let page = index + 1;
+            let depth = url_depth(&page_context::paginated_url(&url_path, page));
 
-        // Sanitize source and value to prevent path traversal
-        let safe_source = crate::wikilink::sanitize_path_component(source);
-        let safe_value = crate::wikilink::sanitize_path_component(value);
-        if safe_source.is_empty() || safe_value.is_empty() {
-            tracing::warn!(
-                "Skipping tag page with empty sanitized source={source:?} value={value:?}"
+            let mut context: HashMap<String, serde_json::Value> = HashMap::new();
+            page_context::insert_tag_page_keys(
+                &mut context,
+                source,
+                &display_value,
+                &singular_label,
+                &plural_label,
+                chunk,
+                &UrlMode::RelativeToDepth(depth),
+            );
+            if chunks.len() > 1 {
+                // page_count is the tag's total, not this page's share
+                context.insert("page_count".to_string(), serde_json::json!(pages.len()));
+            }
+            page_context::insert_pagination_keys(
+                &mut context,
+                &url_path,
+                page,
+                chunks.len(),
+                &UrlMode::RelativeToDepth(depth),
+            );
+            if let Some(name) = page_context::tag_data_name(&self.config.tag_sources, source) {
+                page_context::insert_author_keys(
+                    &mut context,
+                    self.data_files
+                        .as_deref()
+                        .and_then(|data| data.tag_entry(name, value)),
+                    &UrlMode::RelativeToDepth(depth),
+                );
+            }
+            page_context::insert_page_chrome(
+                &mut context,
+                &PageChrome {
+                    mode: ModeFlags::Static { depth },
+                    sidebar_style: &self.config.sidebar_style,
+                    sidebar_max_items: self.config.sidebar_max_items,
+                    header_height: self.config.header_height.as_deref(),
+                    smooth_scroll: self.config.smooth_scroll,
+                    search: Some(SearchUi {
+                        placeholder: &self.config.search_placeholder,
+                        result_limit: self.config.search_result_limit,
+                    }),
+                    language: Some(SiteLanguage {
+                        lang: &self.config.site_lang,
+                        dir: &self.config.site_dir,
+                    }),
+                    branding: Some(&self.branding),
+                    robots: self.config.robots.as_deref(),
+                    csp: self.csp.as_ref(),
+                    title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
+                    site_nav: self.site_nav.as_deref(),
+                },
             );
-            return None;
-        }
-
-        let output_path = self
-            .output_dir
-            .join(&safe_source)
-            .join(&safe_value)
-            .join("index.html");
 
-        if !output_path.starts_with(&self.output_dir) {
-            tracing::warn!(
-                "Tag page path escaped output directory: source={source:?} value={value:?}"
+            // Tag pages have synthetic breadcrumbs (Home > plural label)
+            let breadcrumbs_json = vec![
+                serde_json::json!({
+                    "name": "Home",
+                    "url": make_relative_url("/", depth)
+                }),
+                serde_json::json!({
+                    "name": plural_label,
+                    "url": make_relative_url(&format!("/{}/", source), depth)
+                }),
+            ];
+            context.insert(
+                "breadcrumbs".to_string(),
+                serde_json::Value::Array(breadcrumbs_json),
             );
-            return None;
+            context.insert(
+                "current_dir_name".to_string(),
+                serde_json::Value::String(display_value.clone()),
+            );
+
+            let output_dir = if page > 1 {
+                tag_dir
+                    .join(page_context::PAGINATION_SEGMENT)
+                    .join(page.to_string())
+            } else {
+                tag_dir.clone()
+            };
+            contexts.push((context, output_dir.join("index.html")));
         }
-
-        Some((context, output_path))
+        contexts
     }
 
     /// Builds context and output path for a tag source index page.
@@ -1430,7 +2930,21 @@ impl Builder {
                 mode: ModeFlags::Static { depth },
                 sidebar_style: &self.config.sidebar_style,
                 sidebar_max_items: self.config.sidebar_max_items,
+                header_height: self.config.header_height.as_deref(),
+                smooth_scroll: self.config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &self.config.search_placeholder,
+                    result_limit: self.config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &self.config.site_lang,
+                    dir: &self.config.site_dir,
+                }),
+                branding: Some(&self.branding),
+                robots: self.config.robots.as_deref(),
+                csp: self.csp.as_ref(),
                 title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
+                site_nav: self.site_nav.as_deref(),
             },
         );
 
@@ -1464,6 +2978,225 @@ impl Builder {
         Some((context, output_path))
     }
 
+    /// Generates date-based archive pages in parallel.
+    ///
+    /// Groups every page with a publish date by year and month, and generates:
+    /// - An archive index at `/archive/`
+    /// - A page per year at `/archive/{year}/`
+    /// - A page per month at `/archive/{year}/{month}/`
+    async fn render_archive_pages(&self) -> Result<usize, BuildError> {
+        let stage_start = Instant::now();
+        let years = archive::group_by_date(
+            self.repo
+                .markdown_files
+                .pin()
+                .iter()
+                .filter_map(|(_, info)| ArchivePage::from_info(info)),
+        );
+
+        if years.is_empty() {
+            self.progress
+                .finish("Generating archive pages ... skipped (no dated pages)");
+            return Ok(0);
+        }
+
+        // (Some(year), Some(month)) for a month page, (Some(year), None) for a
+        // year page, (None, None) for the archive index
+        let mut tasks: Vec<(Option<usize>, Option<usize>)> = vec![(None, None)];
+        for (y, year) in years.iter().enumerate() {
+            tasks.push((Some(y), None));
+            for m in 0..year.months.len() {
+                tasks.push((Some(y), Some(m)));
+            }
+        }
+
+        let count = tasks.len();
+        let concurrency = self.get_concurrency();
+
+        tracing::info!(
+            "Rendering {} archive pages with concurrency {}",
+            count,
+            concurrency
+        );
+
+        // Clone Tera once before entering the rayon pool to avoid per-file lock contention
+        let tera_snapshot = self.templates.tera_clone();
+
+        let completed = Arc::new(AtomicUsize::new(0));
+        self.progress.progress("Generating archive pages", 0, count);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(concurrency)
+            .build()
+            .map_err(|e| BuildError::CreateDirFailed {
+                path: self.output_dir.clone(),
+                source: std::io::Error::other(format!("Failed to create rayon thread pool: {}", e)),
+            })?;
+
+        let error: FirstError<BuildError> = FirstError::new();
+
+        pool.install(|| {
+            use rayon::prelude::*;
+            tasks.par_iter().for_each(|(year, month)| {
+                if error.is_set() {
+                    return;
+                }
+                match self.render_single_archive_page_sync(&years, *year, *month, &tera_snapshot) {
+                    Ok(()) => {
+                        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                        if done.is_multiple_of(100) || done == count {
+                            self.progress
+                                .progress("Generating archive pages", done, count);
+                        }
+                    }
+                    Err(e) => error.record(e),
+                }
+            });
+        });
+
+        error.into_result()?;
+
+        self.progress.stage_done(
+            "Generating archive pages",
+            count,
+            Some(stage_start.elapsed()),
+        );
+        Ok(count)
+    }
+
+    /// Synchronous version of archive page rendering for use with rayon.
+    /// Takes a pre-cloned `&Tera` to avoid lock contention.
+    fn render_single_archive_page_sync(
+        &self,
+        years: &[ArchiveYear],
+        year: Option<usize>,
+        month: Option<usize>,
+        tera: &tera::Tera,
+    ) -> Result<(), BuildError> {
+        let Some((context, output_path)) = self.build_archive_page_context(years, year, month)
+        else {
+            return Ok(());
+        };
+
+        let html_output = Templates::render_template_with_tera(tera, "archive.html", context)?;
+
+        // A markdown page at the same URL takes precedence
+        if !self.sink.exists(&output_path) {
+            if let Some(parent) = output_path.parent() {
+                self.create_output_dir(parent)?;
+            }
+            self.write_output(&output_path, html_output)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds context and output path for an archive page: the index when
+    /// `year` is `None`, else that year, or one of its months.
+    fn build_archive_page_context(
+        &self,
+        years: &[ArchiveYear],
+        year: Option<usize>,
+        month: Option<usize>,
+    ) -> Option<(HashMap<String, serde_json::Value>, PathBuf)> {
+        let index_url = format!("/{}/", archive::ARCHIVE_SOURCE);
+        let mut crumbs: Vec<(String, String)> = vec![("Home".to_string(), "/".to_string())];
+
+        let (title, url_path, groups, year_number, month_number) = match (year, month) {
+            (None, _) => {
+                let groups: Vec<ArchiveGroup> = years.iter().map(ArchiveGroup::from).collect();
+                ("Archive".to_string(), index_url, groups, None, None)
+            }
+            (Some(y), None) => {
+                let year = years.get(y)?;
+                crumbs.push(("Archive".to_string(), index_url));
+                let groups: Vec<ArchiveGroup> =
+                    year.months.iter().map(ArchiveGroup::from).collect();
+                (
+                    year.year.to_string(),
+                    year.url_path(),
+                    groups,
+                    Some(year.year),
+                    None,
+                )
+            }
+            (Some(y), Some(m)) => {
+                let year = years.get(y)?;
+                let month = year.months.get(m)?;
+                crumbs.push(("Archive".to_string(), index_url));
+                crumbs.push((year.year.to_string(), year.url_path()));
+                (
+                    month.label(),
+                    month.url_path(),
+                    vec![ArchiveGroup::from(month)],
+                    Some(year.year),
+                    Some(month.month),
+                )
+            }
+        };
+        let depth = url_depth(&url_path);
+
+        let mut context: HashMap<String, serde_json::Value> = HashMap::new();
+        page_context::insert_archive_keys(
+            &mut context,
+            &title,
+            year_number,
+            month_number,
+            &groups,
+            &UrlMode::RelativeToDepth(depth),
+        );
+        page_context::insert_page_chrome(
+            &mut context,
+            &PageChrome {
+                mode: ModeFlags::Static { depth },
+                sidebar_style: &self.config.sidebar_style,
+                sidebar_max_items: self.config.sidebar_max_items,
+                header_height: self.config.header_height.as_deref(),
+                smooth_scroll: self.config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &self.config.search_placeholder,
+                    result_limit: self.config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &self.config.site_lang,
+                    dir: &self.config.site_dir,
+                }),
+                branding: Some(&self.branding),
+                robots: self.config.robots.as_deref(),
+                csp: self.csp.as_ref(),
+                title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
+                site_nav: self.site_nav.as_deref(),
+            },
+        );
+
+        // Archive pages have synthetic breadcrumbs (Home > Archive > year)
+        let breadcrumbs_json: Vec<serde_json::Value> = crumbs
+            .iter()
+            .map(|(name, url)| {
+                serde_json::json!({
+                    "name": name,
+                    "url": make_relative_url(url, depth)
+                })
+            })
+            .collect();
+        context.insert(
+            "breadcrumbs".to_string(),
+            serde_json::Value::Array(breadcrumbs_json),
+        );
+        context.insert(
+            "current_dir_name".to_string(),
+            serde_json::Value::String(title),
+        );
+
+        // Archive URLs are built from numbers, so they're always safe paths
+        let output_path = self
+            .output_dir
+            .join(url_path.trim_matches('/'))
+            .join("index.html");
+
+        Some((context, output_path))
+    }
+
     /// Creates symlinks for static assets.
     fn symlink_assets(&self) -> Result<usize, BuildError> {
         let other_files: Vec<_> = self
@@ -1472,6 +3205,7 @@ impl Builder {
             .pin()
             .iter()
             .map(|(_, info)| info.clone())
+            .filter(|info| self.in_build_scope(&info.raw_path))
             .collect();
 
         let count = other_files.len();
@@ -1482,19 +3216,15 @@ impl Builder {
 
             // Create parent directories
             if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
+                self.create_output_dir(parent)?;
             }
 
             // Calculate relative path from output location to original file
             let target = self.calculate_relative_symlink(&output_path, &file_info.raw_path)?;
 
             // Create symlink (skip if already exists)
-            if !output_path.exists() {
-                #[cfg(unix)]
-                std::os::unix::fs::symlink(&target, &output_path).map_err(|e| {
+            if !self.sink.exists(&output_path) {
+                self.sink.symlink(&target, &output_path).map_err(|e| {
                     BuildError::SymlinkFailed {
                         target: target.clone(),
                         link: output_path.clone(),
@@ -1507,6 +3237,38 @@ impl Builder {
         Ok(count)
     }
 
+    /// Whether a source file is part of this build: always for full builds,
+    /// and under the `build_only` prefix for partial ones.
+    fn in_build_scope(&self, source: &Path) -> bool {
+        match &self.only {
+            Some(prefix) => source
+                .strip_prefix(&self.config.root_dir)
+                .unwrap_or(source)
+                .starts_with(prefix),
+            None => true,
+        }
+    }
+
+    /// Creates an output directory and any missing parents.
+    fn create_output_dir(&self, path: &Path) -> Result<(), BuildError> {
+        self.sink
+            .create_dir(path)
+            .map_err(|e| BuildError::CreateDirFailed {
+                path: path.to_path_buf(),
+                source: e,
+            })
+    }
+
+    /// Writes an output file.
+    fn write_output(&self, path: &Path, contents: impl AsRef<[u8]>) -> Result<(), BuildError> {
+        self.sink
+            .write_file(path, contents.as_ref())
+            .map_err(|e| BuildError::WriteFailed {
+                path: path.to_path_buf(),
+                source: e,
+            })
+    }
+
     /// Calculates a relative path for symlinking.
     fn calculate_relative_symlink(&self, from: &Path, to: &Path) -> Result<PathBuf, BuildError> {
         // Get the directory containing the symlink
@@ -1547,13 +3309,15 @@ impl Builder {
             return Ok(());
         }
 
+        let mut visited = VisitedDirs::new(self.config.follow_symlinks);
         for entry in WalkDir::new(&static_path)
-            .follow_links(true)
+            .follow_links(self.config.follow_symlinks)
             .min_depth(1)
             .into_iter()
+            .filter_entry(|e| visited.admit(e))
             .filter_map(|e| e.ok())
         {
-            if entry.file_type().is_file() {
+            if entry.path().is_file() {
                 let relative = entry.path().strip_prefix(&static_path).map_err(|_| {
                     BuildError::CreateDirFailed {
                         path: entry.path().to_path_buf(),
@@ -1564,18 +3328,14 @@ impl Builder {
                 let output_path = self.output_dir.join(relative);
 
                 // Only symlink if path doesn't already exist (asset wins over static)
-                if !output_path.exists() {
+                if !self.sink.exists(&output_path) {
                     if let Some(parent) = output_path.parent() {
-                        fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                            path: parent.to_path_buf(),
-                            source: e,
-                        })?;
+                        self.create_output_dir(parent)?;
                     }
 
                     let target = self.calculate_relative_symlink(&output_path, entry.path())?;
 
-                    #[cfg(unix)]
-                    std::os::unix::fs::symlink(&target, &output_path).map_err(|e| {
+                    self.sink.symlink(&target, &output_path).map_err(|e| {
                         BuildError::SymlinkFailed {
                             target,
                             link: output_path,
@@ -1594,17 +3354,11 @@ impl Builder {
         let mbr_output = self.output_dir.join(".mbr");
 
         // Step 1: Create .mbr directory
-        fs::create_dir_all(&mbr_output).map_err(|e| BuildError::CreateDirFailed {
-            path: mbr_output.clone(),
-            source: e,
-        })?;
+        self.create_output_dir(&mbr_output)?;
 
         // Write .nojekyll so GitHub Pages serves dotfolders (like .mbr/)
         let nojekyll_path = self.output_dir.join(".nojekyll");
-        fs::write(&nojekyll_path, "").map_err(|e| BuildError::WriteFailed {
-            path: nojekyll_path,
-            source: e,
-        })?;
+        self.write_output(&nojekyll_path, "")?;
 
         // Step 2: Copy repo's .mbr folder if it exists
         let mbr_source = self.config.root_dir.join(".mbr");
@@ -1629,25 +3383,19 @@ impl Builder {
             let output_path = mbr_output.join(filename);
 
             // Only write if file doesn't already exist (repo's .mbr/ wins)
-            if !output_path.exists() {
+            if !self.sink.exists(&output_path) {
                 // Create parent directories for nested paths (e.g., components/mbr-components.js)
                 if let Some(parent) = output_path.parent() {
-                    fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                        path: parent.to_path_buf(),
-                        source: e,
-                    })?;
+                    self.create_output_dir(parent)?;
                 }
 
-                fs::write(&output_path, content).map_err(|e| BuildError::WriteFailed {
-                    path: output_path,
-                    source: e,
-                })?;
+                self.write_output(&output_path, content)?;
             }
         }
 
         // Step 3b: Write themed pico.min.css (only if not already present from repo's .mbr/)
         let pico_output_path = mbr_output.join("pico.min.css");
-        if !pico_output_path.exists() {
+        if !self.sink.exists(&pico_output_path) {
             let pico_content = match embedded_pico::get_pico_css(&self.config.theme) {
                 Some(content) => content,
                 None => {
@@ -1659,10 +3407,7 @@ impl Builder {
                     embedded_pico::get_pico_css("default").ok_or(BuildError::MissingDefaultTheme)?
                 }
             };
-            fs::write(&pico_output_path, pico_content).map_err(|e| BuildError::WriteFailed {
-                path: pico_output_path,
-                source: e,
-            })?;
+            self.write_output(&pico_output_path, pico_content)?;
         }
 
         // Step 4: Generate site.json with sort config and tags
@@ -1671,6 +3416,10 @@ impl Builder {
 
         // Add sort config and tags to the response
         if let Some(obj) = response.as_object_mut() {
+            obj.insert(
+                "schema_version".to_string(),
+                serde_json::json!(crate::repo::SITE_JSON_SCHEMA_VERSION),
+            );
             obj.insert(
                 "sort".to_string(),
                 serde_json::to_value(&self.config.sort).unwrap_or(serde_json::Value::Array(vec![])),
@@ -1719,25 +3468,21 @@ impl Builder {
                 .inject_into_site_json(&mut response);
         }
 
-        let site_json = serde_json::to_string(&response)
+        let site_json = self
+            .to_json(&response)
             .map_err(|e| BuildError::RepoScan(crate::errors::RepoError::JsonSerializeFailed(e)))?;
         let site_json_path = mbr_output.join("site.json");
-        fs::write(&site_json_path, site_json).map_err(|e| BuildError::WriteFailed {
-            path: site_json_path,
-            source: e,
-        })?;
+        self.write_output(&site_json_path, site_json)?;
 
         // Step 4b: Generate media.json with only other_files
         let media_data = serde_json::json!({
             "other_files": &self.repo.other_files,
         });
-        let media_json = serde_json::to_string(&media_data)
+        let media_json = self
+            .to_json(&media_data)
             .map_err(|e| BuildError::RepoScan(crate::errors::RepoError::JsonSerializeFailed(e)))?;
         let media_json_path = mbr_output.join("media.json");
-        fs::write(&media_json_path, media_json).map_err(|e| BuildError::WriteFailed {
-            path: media_json_path,
-            source: e,
-        })?;
+        self.write_output(&media_json_path, media_json)?;
 
         Ok(())
     }
@@ -1766,7 +3511,21 @@ impl Builder {
                 mode: ModeFlags::Static { depth: 0 },
                 sidebar_style: &self.config.sidebar_style,
                 sidebar_max_items: self.config.sidebar_max_items,
+                header_height: self.config.header_height.as_deref(),
+                smooth_scroll: self.config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &self.config.search_placeholder,
+                    result_limit: self.config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &self.config.site_lang,
+                    dir: &self.config.site_dir,
+                }),
+                branding: Some(&self.branding),
+                robots: self.config.robots.as_deref(),
+                csp: self.csp.as_ref(),
                 title_affixes: None,
+                site_nav: None,
             },
         );
 
@@ -1781,10 +3540,7 @@ impl Builder {
 
         let html = self.templates.render_error(context)?;
 
-        fs::write(&output_path, html).map_err(|e| BuildError::WriteFailed {
-            path: output_path,
-            source: e,
-        })?;
+        self.write_output(&output_path, html)?;
 
         Ok(())
     }
@@ -1809,6 +3565,9 @@ impl Builder {
             MediaViewerType::Image,
         ];
 
+        let video_chapters = self.collect_sidecar_chapters("video");
+        let audio_chapters = self.collect_sidecar_chapters("audio");
+
         for media_type in media_types {
             // Determine output path based on media type
             let output_path = match media_type {
@@ -1820,10 +3579,7 @@ impl Builder {
 
             // Create parent directories
             if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                    path: parent.to_path_buf(),
-                    source: e,
-                })?;
+                self.create_output_dir(parent)?;
             }
 
             // Build context for media viewer template
@@ -1848,7 +3604,21 @@ impl Builder {
                     mode: ModeFlags::Static { depth },
                     sidebar_style: &self.config.sidebar_style,
                     sidebar_max_items: self.config.sidebar_max_items,
+                    header_height: self.config.header_height.as_deref(),
+                    smooth_scroll: self.config.smooth_scroll,
+                    search: Some(SearchUi {
+                        placeholder: &self.config.search_placeholder,
+                        result_limit: self.config.search_result_limit,
+                    }),
+                    language: Some(SiteLanguage {
+                        lang: &self.config.site_lang,
+                        dir: &self.config.site_dir,
+                    }),
+                    branding: Some(&self.branding),
+                    robots: self.config.robots.as_deref(),
+                    csp: self.csp.as_ref(),
                     title_affixes: Some((&self.config.title_prefix, &self.config.title_suffix)),
+                    site_nav: self.site_nav.as_deref(),
                 },
             );
 
@@ -1867,17 +3637,152 @@ impl Builder {
                 serde_json::Value::String("../../".to_string()),
             );
 
+            // Chapter markers for every media file of this type, keyed by URL path
+            let chapters = match media_type {
+                MediaViewerType::Video => Some(&video_chapters),
+                MediaViewerType::Audio => Some(&audio_chapters),
+                _ => None,
+            };
+            if let Some(chapters) = chapters.filter(|c| !c.is_empty()) {
+                context.insert(
+                    "chapters_json".to_string(),
+                    serde_json::Value::String(crate::chapters::to_script_json(chapters)),
+                );
+            }
+
             let html = self.templates.render_media_viewer(context)?;
 
-            fs::write(&output_path, html).map_err(|e| BuildError::WriteFailed {
-                path: output_path,
-                source: e,
-            })?;
+            self.write_output(&output_path, html)?;
         }
 
         Ok(())
     }
 
+    /// Loads the `.chapters.json` sidecars of all media files of `filetype`,
+    /// keyed by URL path. Malformed sidecars are reported and skipped.
+    fn collect_sidecar_chapters(
+        &self,
+        filetype: &str,
+    ) -> std::collections::BTreeMap<String, Vec<crate::chapters::Chapter>> {
+        let mut chapters = std::collections::BTreeMap::new();
+        for (_, info) in self.repo.other_files.pin().iter() {
+            if info.filetype() != filetype {
+                continue;
+            }
+            match crate::chapters::load_sidecar(&info.raw_path) {
+                Ok(Some(list)) => {
+                    chapters.insert(info.url_path.clone(), list);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: ignoring chapters for {}: {}", info.url_path, e),
+            }
+        }
+        chapters
+    }
+
+    /// Moves the previous build's search index out of the output directory
+    /// before it is cleaned, so an `incremental` build can reuse it.
+    ///
+    /// The index goes next to the output directory under a name unique to this
+    /// process, so concurrent builds never pick up each other's index.
+    fn stash_search_index(&self) -> Option<PathBuf> {
+        if !self.config.incremental || self.config.skip_search_index {
+            return None;
+        }
+        let index_dir = self.output_dir.join(".mbr").join("pagefind");
+        if !index_dir.join(SEARCH_INDEX_HASH_FILE).is_file() {
+            return None;
+        }
+        let stash = self
+            .output_dir
+            .with_extension(format!("pagefind.{}", std::process::id()));
+        if stash.exists() {
+            let _ = fs::remove_dir_all(&stash);
+        }
+        match fs::rename(&index_dir, &stash) {
+            Ok(()) => Some(stash),
+            Err(e) => {
+                tracing::debug!("Not reusing search index {}: {}", index_dir.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Builds the search index, or for `incremental` builds restores
+    /// `previous` (from [`Self::stash_search_index`]) when the HTML it was
+    /// built from is unchanged.
+    ///
+    /// Pagefind can't load an existing index to update only the changed pages,
+    /// so any change to the indexed HTML re-indexes the whole site.
+    async fn update_search_index(&self, previous: Option<PathBuf>) -> SearchIndexStatus {
+        let index_dir = self.output_dir.join(".mbr").join("pagefind");
+        let content_hash = self.config.incremental.then(|| self.search_content_hash());
+
+        if let Some(stash) = previous {
+            let previous_hash = fs::read_to_string(stash.join(SEARCH_INDEX_HASH_FILE)).ok();
+            if previous_hash.is_some() && previous_hash == content_hash {
+                let restored = fs::create_dir_all(index_dir.parent().unwrap_or(&self.output_dir))
+                    .and_then(|()| fs::rename(&stash, &index_dir));
+                match restored {
+                    Ok(()) => return SearchIndexStatus::Unchanged,
+                    Err(e) => tracing::debug!("Failed to restore search index: {}", e),
+                }
+            }
+            let _ = fs::remove_dir_all(&stash);
+        }
+
+        if !self.run_pagefind().await {
+            return SearchIndexStatus::Failed;
+        }
+        if let Some(hash) = content_hash
+            && let Err(e) = fs::write(index_dir.join(SEARCH_INDEX_HASH_FILE), hash)
+        {
+            tracing::debug!("Failed to record search index hash: {}", e);
+        }
+        SearchIndexStatus::Indexed
+    }
+
+    /// SHA-256 over every HTML file Pagefind indexes (path and contents) plus
+    /// the index language, hex-encoded.
+    fn search_content_hash(&self) -> String {
+        use rayon::prelude::*;
+        use sha2::{Digest, Sha256};
+
+        let mut pages: Vec<(String, Vec<u8>)> = WalkDir::new(&self.output_dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file() && e.path().extension() == Some("html".as_ref()))
+            .map(|e| e.into_path())
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|path| {
+                let relative = path
+                    .strip_prefix(&self.output_dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned();
+                let digest = fs::read(&path)
+                    .map(|bytes| Sha256::digest(bytes).to_vec())
+                    .unwrap_or_default();
+                (relative, digest)
+            })
+            .collect();
+        pages.sort_unstable();
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.config.site_lang.as_bytes());
+        for (path, digest) in &pages {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update(digest);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
     /// Runs Pagefind to generate the search index using the native Rust library.
     ///
     /// Returns true if Pagefind ran successfully, false otherwise.
@@ -1885,9 +3790,9 @@ impl Builder {
         use pagefind::api::PagefindIndex;
         use pagefind::options::PagefindServiceConfig;
 
-        // Create Pagefind index with default options
+        // Index every page in the site language
         let options = PagefindServiceConfig::builder()
-            .force_language("en".to_string())
+            .force_language(self.config.site_lang.clone())
             .build();
 
         let mut index = match PagefindIndex::new(Some(options)) {
@@ -1959,18 +3864,23 @@ impl Builder {
 
     /// Validates internal links in all generated HTML files.
     ///
-    /// Scans all HTML files for `<a href="...">` links, filters to internal links
-    /// (excluding external URLs, mailto:, tel:, etc.), and checks if each link
-    /// resolves to an existing file or directory in the output.
+    /// Scans all HTML files for `<a href="...">` links, `vid` shortcode
+    /// subtitle tracks (`<track kind="subtitles" src="...">`), and stylesheets
+    /// and scripts (including `head_css`/`head_js` page assets), filters to
+    /// internal links (excluding external URLs, mailto:, tel:, etc.), and checks
+    /// if each link resolves to an existing file or directory in the output.
     ///
     /// Returns a list of broken links found.
     fn validate_links(&self) -> Vec<BrokenLink> {
         use rayon::prelude::*;
 
-        // Create selector for anchor tags
-        let selector = match Selector::parse("a[href]") {
-            Ok(s) => s,
-            Err(_) => return Vec::new(), // Should never fail with this simple selector
+        // Create selectors for anchor tags, subtitle tracks and page assets
+        let (Ok(selector), Ok(track_selector), Ok(asset_selector)) = (
+            Selector::parse("a[href]"),
+            Selector::parse(r#"track[kind="subtitles"][src]"#),
+            Selector::parse(r#"link[rel="stylesheet"][href], script[src]"#),
+        ) else {
+            return Vec::new(); // Should never fail with these simple selectors
         };
 
         // Single WalkDir pass: build a HashSet of all files (for O(1) link lookups)
@@ -1979,11 +3889,13 @@ impl Builder {
         let mut html_files: Vec<PathBuf> = Vec::new();
         let mbr_prefix = self.output_dir.join(".mbr");
 
+        let mut visited = VisitedDirs::new(self.config.follow_symlinks);
         for entry in WalkDir::new(&self.output_dir)
-            .follow_links(true)
+            .follow_links(self.config.follow_symlinks)
             .into_iter()
+            .filter_entry(|e| visited.admit(e))
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
+            .filter(|e| e.path().is_file())
         {
             let path = entry.into_path();
             valid_files.insert(path.clone());
@@ -2010,28 +3922,48 @@ impl Builder {
                 let document = Html::parse_document(&html_content);
                 let mut broken = Vec::new();
 
-                for element in document.select(&selector) {
-                    if let Some(href) = element.value().attr("href") {
-                        if href.starts_with("http://")
-                            || href.starts_with("https://")
-                            || href.starts_with("//")
-                            || href.starts_with("mailto:")
-                            || href.starts_with("tel:")
-                            || href.starts_with("javascript:")
-                            || href.starts_with("data:")
-                            || href.starts_with("#")
-                        {
-                            continue;
-                        }
+                let links = document
+                    .select(&selector)
+                    .filter_map(|element| element.value().attr("href"))
+                    .chain(
+                        document
+                            .select(&track_selector)
+                            .filter_map(|element| element.value().attr("src")),
+                    )
+                    // Stylesheets and scripts outside mbr's own `.mbr/` assets
+                    .chain(
+                        document
+                            .select(&asset_selector)
+                            .filter_map(|element| {
+                                let value = element.value();
+                                value.attr("href").or_else(|| value.attr("src"))
+                            })
+                            .filter(|url| {
+                                self.resolve_link(path, url)
+                                    .is_some_and(|resolved| !resolved.starts_with(&mbr_prefix))
+                            }),
+                    );
 
-                        if let Some(resolved) = self.resolve_link(path, href)
-                            && !link_target_exists(&resolved, &valid_files)
-                        {
-                            broken.push(BrokenLink {
-                                source_page: source_page.clone(),
-                                link_url: href.to_string(),
-                            });
-                        }
+                for href in links {
+                    if href.starts_with("http://")
+                        || href.starts_with("https://")
+                        || href.starts_with("//")
+                        || href.starts_with("mailto:")
+                        || href.starts_with("tel:")
+                        || href.starts_with("javascript:")
+                        || href.starts_with("data:")
+                        || href.starts_with("#")
+                    {
+                        continue;
+                    }
+
+                    if let Some(resolved) = self.resolve_link(path, href)
+                        && !link_target_exists(&resolved, &valid_files)
+                    {
+                        broken.push(BrokenLink {
+                            source_page: source_page.clone(),
+                            link_url: href.to_string(),
+                        });
                     }
                 }
 
@@ -2073,10 +4005,12 @@ impl Builder {
 
     /// Recursively copies a directory.
     fn copy_dir_recursive(&self, from: &Path, to: &Path) -> Result<(), BuildError> {
+        let mut visited = VisitedDirs::new(self.config.follow_symlinks);
         for entry in WalkDir::new(from)
-            .follow_links(true)
+            .follow_links(self.config.follow_symlinks)
             .min_depth(1)
             .into_iter()
+            .filter_entry(|e| visited.admit(e))
             .filter_map(|e| e.ok())
         {
             let relative = entry
@@ -2091,22 +4025,18 @@ impl Builder {
             let dest = to.join(relative);
 
             if entry.file_type().is_dir() {
-                fs::create_dir_all(&dest).map_err(|e| BuildError::CreateDirFailed {
-                    path: dest.clone(),
-                    source: e,
-                })?;
-            } else if entry.file_type().is_file() {
+                self.create_output_dir(&dest)?;
+            } else if entry.path().is_file() {
                 if let Some(parent) = dest.parent() {
-                    fs::create_dir_all(parent).map_err(|e| BuildError::CreateDirFailed {
-                        path: parent.to_path_buf(),
+                    self.create_output_dir(parent)?;
+                }
+                self.sink
+                    .copy_file(entry.path(), &dest)
+                    .map_err(|e| BuildError::CopyFailed {
+                        from: entry.path().to_path_buf(),
+                        to: dest.clone(),
                         source: e,
                     })?;
-                }
-                fs::copy(entry.path(), &dest).map_err(|e| BuildError::CopyFailed {
-                    from: entry.path().to_path_buf(),
-                    to: dest.clone(),
-                    source: e,
-                })?;
             }
         }
 
@@ -2166,6 +4096,7 @@ impl Builder {
                 text: page.title,
                 anchor: None,
                 internal: true,
+                context: None,
             });
         }
 
@@ -2183,6 +4114,7 @@ impl Builder {
             text: label,
             anchor: None,
             internal: true,
+            context: None,
         });
 
         outbound
@@ -2199,6 +4131,7 @@ impl Builder {
                 text: tag.display,
                 anchor: None,
                 internal: true,
+                context: None,
             })
             .collect()
     }
@@ -2240,14 +4173,39 @@ mod tests {
         assert!(value < 8);
     }
 
+    #[test]
+    fn test_format_stage_breakdown_slowest_first() {
+        let stages = HashMap::from([
+            ("scan".to_string(), Duration::from_millis(250)),
+            ("render".to_string(), Duration::from_millis(1500)),
+            ("links".to_string(), Duration::from_millis(250)),
+        ]);
+        let out = format_stage_breakdown(&stages, Duration::from_millis(2500));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "  render      1.50s   60%");
+        // Ties fall back to the stage name
+        assert!(lines[1].starts_with("  links "));
+        assert!(lines[2].starts_with("  scan  "));
+        assert!(lines[2].ends_with(" 10%"));
+
+        assert_eq!(format_stage_breakdown(&HashMap::new(), Duration::ZERO), "");
+    }
+
     fn mk_info(url: &str) -> MarkdownInfo {
         MarkdownInfo {
             raw_path: PathBuf::from(url.trim_matches('/')),
             url_path: url.to_string(),
+            url: url.to_string(),
             frontmatter: None,
             created: 0,
             modified: 0,
+            word_count: 0,
+            date: None,
+            created_date: None,
+            updated_date: None,
             relationships: Vec::new(),
+            cover_image: None,
         }
     }
 
@@ -2319,6 +4277,51 @@ mod tests {
         assert_eq!(index[&PathBuf::new()].0.len(), 1); // readme
     }
 
+    /// The index keys are exactly the directories that get section pages, and
+    /// in a deep, wide tree every file is listed once, under its own
+    /// directory only, and every directory lists its own subdirectory.
+    #[test]
+    fn test_build_dir_children_index_deep_tree() {
+        const DEPTH: usize = 12;
+        const WIDTH: usize = 3;
+        const FILES_PER_DIR: usize = 5;
+
+        let mut infos = Vec::new();
+        let mut expected: HashMap<PathBuf, (HashSet<String>, HashSet<String>)> =
+            HashMap::from([(PathBuf::new(), Default::default())]);
+        for branch in 0..WIDTH {
+            let mut dir = PathBuf::new();
+            for level in 0..DEPTH {
+                let name = format!("b{branch}-l{level}");
+                expected.get_mut(&dir).unwrap().1.insert(name.clone());
+                dir.push(name);
+                let files = &mut expected.entry(dir.clone()).or_default().0;
+                for file in 0..FILES_PER_DIR {
+                    let url_path = format!("/{}/f{file}/", dir.display());
+                    files.insert(url_path.clone());
+                    infos.push(mk_info(&url_path));
+                }
+            }
+        }
+
+        let index = build_dir_children_index(infos.iter());
+
+        let keys: HashSet<&PathBuf> = index.keys().collect();
+        assert_eq!(keys, expected.keys().collect());
+        for (dir, (files, subdirs)) in &index {
+            let urls: HashSet<String> = files
+                .iter()
+                .map(|f| f["url_path"].as_str().unwrap().to_string())
+                .collect();
+            assert_eq!(urls.len(), files.len(), "files listed twice in {dir:?}");
+            assert_eq!(urls, expected[dir].0, "files in {dir:?}");
+            assert_eq!(subdirs, &expected[dir].1, "subdirs of {dir:?}");
+        }
+        let deepest: PathBuf = (0..DEPTH).map(|level| format!("b1-l{level}")).collect();
+        assert!(index[&deepest].1.is_empty());
+        assert_eq!(index[&deepest].0.len(), FILES_PER_DIR);
+    }
+
     #[test]
     fn test_build_stats_default() {
         let stats = BuildStats::default();
@@ -2445,8 +4448,10 @@ mod tests {
         let oembed_cache = Arc::new(OembedCache::new(1024));
         let build_link_index = Arc::new(ConcurrentHashMap::new());
         let frontmatter_errors = Arc::new(ConcurrentHashMap::new());
+        let mermaid_config = config.mermaid_init_json();
 
         Builder {
+            progress: BuildProgress::default(),
             config,
             templates,
             output_dir,
@@ -2454,6 +4459,23 @@ mod tests {
             oembed_cache,
             build_link_index,
             frontmatter_errors,
+            image_variants: Arc::new(ConcurrentHashMap::new()),
+            audio_variants: Arc::new(ConcurrentHashMap::new()),
+            gallery_images: Arc::new(ConcurrentHashMap::new()),
+            feeds: OnceLock::new(),
+            feed_sections: BTreeSet::new(),
+            feed_bodies: Arc::new(ConcurrentHashMap::new()),
+            site_nav: None,
+            reading_order: None,
+            data_files: None,
+            branding: SiteBranding::default(),
+            csp: None,
+            mermaid_config,
+            git_metadata: None,
+            only: None,
+            sink: Arc::new(FsSink),
+            post_render_hooks: Vec::new(),
+            server_cache: false,
         }
     }
 
@@ -2896,6 +4918,52 @@ mod tests {
 
         assert_eq!(normalized, to);
     }
+
+    // ============================================================================
+    // Embed Fetching Tests
+    // ============================================================================
+
+    #[tokio::test]
+    async fn test_build_fetches_each_embed_url_once() {
+        use crate::oembed::test_support::{allow_mock_server, html_response, spawn_mock_server};
+        use std::sync::atomic::Ordering;
+
+        let body = r#"<html><head><meta property="og:title" content="Shared Page"/></head></html>"#;
+        let (addr, hits) = spawn_mock_server(move |_| html_response(body)).await;
+        allow_mock_server(addr);
+        let url = format!("http://{addr}/article");
+
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".mbr")).unwrap();
+        for name in ["one", "two", "three"] {
+            std::fs::write(
+                root.path().join(format!("{name}.md")),
+                format!("# {name}\n\n{url}\n"),
+            )
+            .unwrap();
+        }
+
+        let output = tempfile::tempdir().unwrap();
+        let config = crate::config::Config {
+            root_dir: root.path().to_path_buf(),
+            oembed_timeout_ms: 5000,
+            skip_search_index: true,
+            ..Default::default()
+        };
+        let stats = Builder::new(config, output.path().to_path_buf())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(stats.embeds_fetched, 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        for name in ["one", "two", "three"] {
+            let html =
+                std::fs::read_to_string(output.path().join(name).join("index.html")).unwrap();
+            assert!(html.contains("Shared Page"), "{name}: {html}");
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,213 @@
+//! Progress output for static builds (`progress_format`).
+//!
+//! On a terminal each stage redraws a single line in place (carriage return
+//! plus an ANSI clear-line) with a running count, then ends it with a
+//! `... done` line. Those escapes garble logs when stdout is redirected, so
+//! without a terminal the build prints plain lines instead: one per finished
+//! stage, and one at each quarter of a counted stage so slow builds still
+//! show they're moving.
+//!
+//! `progress_format` picks the style: "auto" (the default) checks whether
+//! stdout is a terminal, "tty" and "plain" force one or the other.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Clears the current terminal line before redrawing it.
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+/// A `progress_format` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// In-place updates when stdout is a terminal, plain lines otherwise.
+    #[default]
+    Auto,
+    /// Always redraw in place.
+    Tty,
+    /// Always print plain lines.
+    Plain,
+}
+
+impl ProgressFormat {
+    /// Parses a `progress_format` value (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "tty" => Some(Self::Tty),
+            "plain" => Some(Self::Plain),
+            _ => None,
+        }
+    }
+
+    /// True if progress should be printed as plain lines.
+    pub fn is_plain(&self) -> bool {
+        match self {
+            Self::Auto => !io::stdout().is_terminal(),
+            Self::Tty => false,
+            Self::Plain => true,
+        }
+    }
+}
+
+/// Prints build stages and their progress to stdout.
+///
+/// This bypasses the logging system to give direct feedback during builds.
+#[derive(Debug, Default)]
+pub struct BuildProgress {
+    plain: bool,
+    /// Last quarter of the current stage reported in plain mode.
+    quarter: AtomicUsize,
+}
+
+impl BuildProgress {
+    pub fn new(format: ProgressFormat) -> Self {
+        Self {
+            plain: format.is_plain(),
+            quarter: AtomicUsize::new(0),
+        }
+    }
+
+    /// Shows that `stage` has started.
+    pub fn stage(&self, stage: &str) {
+        if !self.plain {
+            print!("{CLEAR_LINE}{stage}");
+            let _ = io::stdout().flush();
+        }
+    }
+
+    /// Shows `current` of `total` items of `stage` done.
+    pub fn progress(&self, stage: &str, current: usize, total: usize) {
+        if let Some(line) = self.progress_line(stage, current, total) {
+            print!("{line}");
+            let _ = io::stdout().flush();
+        }
+    }
+
+    /// Ends `stage` with its item count and, if known, how long it took.
+    pub fn stage_done(&self, stage: &str, count: usize, duration: Option<Duration>) {
+        match duration {
+            Some(d) => self.finish(&format!(
+                "{stage} ... {count} done ({})",
+                format_duration(d)
+            )),
+            None => self.finish(&format!("{stage} ... {count} done")),
+        }
+    }
+
+    /// Ends `stage` without a count.
+    pub fn done(&self, stage: &str, duration: Option<Duration>) {
+        match duration {
+            Some(d) => self.finish(&format!("{stage} ... done ({})", format_duration(d))),
+            None => self.finish(&format!("{stage} ... done")),
+        }
+    }
+
+    /// Ends the current stage with `line`.
+    pub fn finish(&self, line: &str) {
+        println!("{}", self.finish_line(line));
+    }
+
+    fn progress_line(&self, stage: &str, current: usize, total: usize) -> Option<String> {
+        if !self.plain {
+            return Some(format!("{CLEAR_LINE}{stage} ({current}/{total})"));
+        }
+        if current == 0 {
+            self.quarter.store(0, Ordering::Relaxed);
+            return None;
+        }
+        // The last quarter is covered by the stage's done line
+        let quarter = current * 4 / total.max(1);
+        if quarter == 0
+            || quarter >= 4
+            || self.quarter.fetch_max(quarter, Ordering::Relaxed) >= quarter
+        {
+            return None;
+        }
+        Some(format!("{stage} ({current}/{total})\n"))
+    }
+
+    fn finish_line(&self, line: &str) -> String {
+        if self.plain {
+            line.to_string()
+        } else {
+            format!("{CLEAR_LINE}{line}")
+        }
+    }
+}
+
+/// Formats a duration for display: "1.23s" or "1m 23.4s" for longer durations.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs_f64();
+    if secs >= 60.0 {
+        format!("{:.0}m {:.1}s", (secs / 60.0).floor(), secs % 60.0)
+    } else {
+        format!("{:.2}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_format_from_name() {
+        assert_eq!(
+            ProgressFormat::from_name("auto"),
+            Some(ProgressFormat::Auto)
+        );
+        assert_eq!(ProgressFormat::from_name("TTY"), Some(ProgressFormat::Tty));
+        assert_eq!(
+            ProgressFormat::from_name("plain"),
+            Some(ProgressFormat::Plain)
+        );
+        assert_eq!(ProgressFormat::from_name("json"), None);
+        assert!(ProgressFormat::Plain.is_plain());
+        assert!(!ProgressFormat::Tty.is_plain());
+    }
+
+    #[test]
+    fn test_tty_progress_redraws_in_place() {
+        let progress = BuildProgress::new(ProgressFormat::Tty);
+        assert_eq!(
+            progress
+                .progress_line("Rendering markdown", 100, 400)
+                .as_deref(),
+            Some("\r\x1b[KRendering markdown (100/400)")
+        );
+        assert_eq!(
+            progress.finish_line("Rendering markdown ... 400 done"),
+            "\r\x1b[KRendering markdown ... 400 done"
+        );
+    }
+
+    #[test]
+    fn test_plain_progress_prints_quarters_without_escapes() {
+        let progress = BuildProgress::new(ProgressFormat::Plain);
+        let lines: Vec<String> = [0, 100, 200, 300, 400, 500, 600, 700, 800]
+            .into_iter()
+            .filter_map(|done| progress.progress_line("Rendering markdown", done, 800))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Rendering markdown (200/800)\n",
+                "Rendering markdown (400/800)\n",
+                "Rendering markdown (600/800)\n"
+            ]
+        );
+        assert_eq!(
+            progress.finish_line("Rendering markdown ... 800 done"),
+            "Rendering markdown ... 800 done"
+        );
+
+        // A new stage starts counting again
+        assert_eq!(progress.progress_line("Generating sections", 0, 4), None);
+        assert_eq!(
+            progress
+                .progress_line("Generating sections", 1, 4)
+                .as_deref(),
+            Some("Generating sections (1/4)\n")
+        );
+    }
+}
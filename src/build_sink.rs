@@ -0,0 +1,195 @@
+//! Where a static build writes its output.
+//!
+//! [`Builder`](crate::build::Builder) writes pages, JSON files, theme assets
+//! and asset links through a [`BuildSink`]. [`FsSink`] is the normal target,
+//! the output directory on disk. [`MemorySink`] keeps everything in memory,
+//! for hermetic tests of the full pipeline.
+//!
+//! Some build steps rework files after they're written or hand the output
+//! to an external tool: modern image variants, asset inlining, flat-URL link
+//! rewriting, link validation, the Pagefind search index and
+//! `post_build_command`. They only run when [`BuildSink::on_disk`] is true.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Destination for build output. Paths are the same ones the build would use
+/// on disk, i.e. under the builder's output directory.
+pub trait BuildSink: Send + Sync {
+    /// Writes a file, replacing any existing one. The parent directory is
+    /// created first with [`BuildSink::create_dir`].
+    fn write_file(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates a directory and any missing parents.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates a symlink at `link` pointing to `target` (relative to the
+    /// link's directory).
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// Whether something has already been written at `path`.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Copies a source file into the output, replacing whatever is at `to`.
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.write_file(to, &fs::read(from)?)
+    }
+
+    /// Whether the output is a real directory that later steps and external
+    /// tools can read back.
+    fn on_disk(&self) -> bool {
+        false
+    }
+}
+
+/// Writes build output to the filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsSink;
+
+impl BuildSink for FsSink {
+    fn write_file(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    #[cfg(unix)]
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    // Asset links are skipped on platforms without symlinks.
+    #[cfg(not(unix))]
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        // `to` may be a link to the source itself; replace the link instead of
+        // copying through it.
+        let _ = fs::remove_file(to);
+        fs::copy(from, to).map(|_| ())
+    }
+
+    fn on_disk(&self) -> bool {
+        true
+    }
+}
+
+/// One entry in a [`MemorySink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryEntry {
+    File(Vec<u8>),
+    Dir,
+    Symlink(PathBuf),
+}
+
+/// Keeps build output in memory, keyed by output path.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    entries: Mutex<BTreeMap<PathBuf, MemoryEntry>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entry at `path`, if any.
+    pub fn entry(&self, path: &Path) -> Option<MemoryEntry> {
+        self.lock().get(path).cloned()
+    }
+
+    /// Returns a written file's contents as text.
+    pub fn read_to_string(&self, path: &Path) -> Option<String> {
+        match self.entry(path)? {
+            MemoryEntry::File(bytes) => String::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+
+    /// Paths of all written files, in sorted order.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.lock()
+            .iter()
+            .filter(|(_, entry)| matches!(entry, MemoryEntry::File(_)))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, MemoryEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl BuildSink for MemorySink {
+    fn write_file(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.lock()
+            .insert(path.to_path_buf(), MemoryEntry::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let mut entries = self.lock();
+        for dir in path.ancestors().filter(|p| !p.as_os_str().is_empty()) {
+            entries.entry(dir.to_path_buf()).or_insert(MemoryEntry::Dir);
+        }
+        Ok(())
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let mut entries = self.lock();
+        if entries.contains_key(link) {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+        entries.insert(
+            link.to_path_buf(),
+            MemoryEntry::Symlink(target.to_path_buf()),
+        );
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.lock().contains_key(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_sink() {
+        let sink = MemorySink::new();
+        sink.create_dir(Path::new("/out/docs")).unwrap();
+        assert_eq!(sink.entry(Path::new("/out")), Some(MemoryEntry::Dir));
+
+        sink.write_file(Path::new("/out/docs/index.html"), b"<p>hi</p>")
+            .unwrap();
+        assert!(sink.exists(Path::new("/out/docs/index.html")));
+        assert_eq!(
+            sink.read_to_string(Path::new("/out/docs/index.html"))
+                .as_deref(),
+            Some("<p>hi</p>")
+        );
+
+        sink.symlink(Path::new("../pic.png"), Path::new("/out/docs/pic.png"))
+            .unwrap();
+        assert!(
+            sink.symlink(Path::new("other.png"), Path::new("/out/docs/pic.png"))
+                .is_err()
+        );
+        assert_eq!(sink.files(), vec![PathBuf::from("/out/docs/index.html")]);
+        assert!(!sink.on_disk());
+    }
+}
@@ -1,7 +1,8 @@
 //! Shared primitives for size-bounded in-memory caches.
 //!
 //! The concrete caches in this crate ([`crate::oembed_cache`],
-//! `video_metadata_cache`, `video_transcode_cache`) all bound memory by an
+//! [`crate::image_cache`], `video_metadata_cache`, `video_transcode_cache`)
+//! all bound memory by an
 //! approximate byte budget with the same conventions:
 //!
 //! - A `max_size_bytes` of `0` disables the cache entirely.
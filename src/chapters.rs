@@ -0,0 +1,273 @@
+//! Chapter markers for audio and video.
+//!
+//! Chapters come from either a sidecar file next to the media
+//! (`talk.mp4.chapters.json`) or a page's frontmatter (`chapters:`). Both use
+//! the same shape, a list of `{start, title}` entries:
+//!
+//! ```yaml
+//! chapters:
+//!   - start: 0
+//!     title: Intro
+//!   - start: "12:30"
+//!     title: Questions
+//! ```
+//!
+//! `start` is either a number of seconds or a `mm:ss` / `hh:mm:ss` string.
+
+use crate::errors::ChapterError;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use yaml_rust2::Yaml;
+
+/// Suffix appended to a media file's name to find its chapters sidecar.
+pub const SIDECAR_SUFFIX: &str = ".chapters.json";
+
+/// A single chapter marker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chapter {
+    /// Offset from the start of the media, in seconds
+    pub start: f64,
+    pub title: String,
+}
+
+/// Parses a chapter timestamp: plain seconds (`90`, `90.5`), `mm:ss`, or
+/// `hh:mm:ss`. Minutes and seconds after the first component must be below 60.
+pub fn parse_timestamp(value: &str) -> Option<f64> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 || parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+
+    let (last, leading) = parts.split_last()?;
+    let seconds: f64 = last
+        .parse()
+        .ok()
+        .filter(|s: &f64| s.is_finite() && *s >= 0.0)?;
+    if !leading.is_empty() && seconds >= 60.0 {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for (i, part) in leading.iter().enumerate() {
+        let unit: u64 = part.parse().ok()?;
+        // Only the first component may exceed 59 (e.g. `90:00` for 90 minutes)
+        if i > 0 && unit >= 60 {
+            return None;
+        }
+        total = total * 60.0 + unit as f64;
+    }
+    Some(total * 60.0 + seconds)
+}
+
+/// Parses and validates a chapter list, returning chapters sorted by start time.
+pub fn parse_chapters(value: &serde_json::Value) -> Result<Vec<Chapter>, ChapterError> {
+    let entries = value.as_array().ok_or(ChapterError::NotAList)?;
+
+    let mut chapters = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let index = i + 1;
+        let entry = entry
+            .as_object()
+            .ok_or(ChapterError::NotAnEntry { index })?;
+
+        let start = match entry.get("start") {
+            None | Some(serde_json::Value::Null) => {
+                return Err(ChapterError::MissingStart { index });
+            }
+            Some(serde_json::Value::Number(n)) => n.as_f64().filter(|s| *s >= 0.0),
+            Some(serde_json::Value::String(s)) => parse_timestamp(s),
+            Some(_) => None,
+        }
+        .ok_or_else(|| ChapterError::InvalidTimestamp {
+            index,
+            value: match &entry["start"] {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            },
+        })?;
+
+        let title = entry
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or(ChapterError::MissingTitle { index })?;
+
+        chapters.push(Chapter {
+            start,
+            title: title.to_string(),
+        });
+    }
+
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(chapters)
+}
+
+/// Returns the sidecar path for a media file (`talk.mp4` → `talk.mp4.chapters.json`).
+pub fn sidecar_path(media_path: &Path) -> PathBuf {
+    let mut name = media_path.as_os_str().to_os_string();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Loads chapters from a media file's sidecar.
+///
+/// Returns `Ok(None)` when there is no sidecar.
+pub fn load_sidecar(media_path: &Path) -> Result<Option<Vec<Chapter>>, ChapterError> {
+    let path = sidecar_path(media_path);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(ChapterError::ReadFailed { path, source }),
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|source| ChapterError::InvalidJson { path, source })?;
+    parse_chapters(&value).map(Some)
+}
+
+/// Reads `chapters:` from a page's parsed frontmatter.
+///
+/// Returns `Ok(None)` when the page declares no chapters.
+pub fn from_frontmatter(yaml: &Yaml) -> Result<Option<Vec<Chapter>>, ChapterError> {
+    let Some(value) = yaml
+        .as_hash()
+        .and_then(|hash| hash.get(&Yaml::String("chapters".to_string())))
+    else {
+        return Ok(None);
+    };
+    parse_chapters(&crate::relationships::yaml_to_json(value)).map(Some)
+}
+
+/// Serializes a value as JSON that is safe to embed inside an inline `<script>`.
+pub fn to_script_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "null".to_string())
+        .replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        assert_eq!(parse_timestamp("90"), Some(90.0));
+        assert_eq!(parse_timestamp("12.5"), Some(12.5));
+        assert_eq!(parse_timestamp("1:30"), Some(90.0));
+        assert_eq!(parse_timestamp("01:02:03"), Some(3723.0));
+        assert_eq!(parse_timestamp("90:00"), Some(5400.0));
+        assert_eq!(parse_timestamp(" 0:05.5 "), Some(5.5));
+    }
+
+    #[test]
+    fn test_parse_timestamp_rejects_malformed() {
+        for bad in [
+            "", "abc", "1:60", "1:75:00", "1::30", "-5", "1:2:3:4", ":30",
+        ] {
+            assert_eq!(parse_timestamp(bad), None, "{bad:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_parse_chapters_sorts_and_trims() {
+        let value = json!([
+            {"start": "2:00", "title": " Outro "},
+            {"start": 0, "title": "Intro"},
+        ]);
+        let chapters = parse_chapters(&value).unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter {
+                    start: 0.0,
+                    title: "Intro".to_string()
+                },
+                Chapter {
+                    start: 120.0,
+                    title: "Outro".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_chapters_errors_name_the_entry() {
+        let err = parse_chapters(&json!([
+            {"start": 0, "title": "Intro"},
+            {"start": "1:99", "title": "Bad"},
+        ]))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "chapter 2: invalid timestamp '1:99' (use seconds, mm:ss, or hh:mm:ss)"
+        );
+
+        assert!(matches!(
+            parse_chapters(&json!([{"title": "No start"}])),
+            Err(ChapterError::MissingStart { index: 1 })
+        ));
+        assert!(matches!(
+            parse_chapters(&json!([{"start": 5, "title": ""}])),
+            Err(ChapterError::MissingTitle { index: 1 })
+        ));
+        assert!(matches!(
+            parse_chapters(&json!([{"start": -1, "title": "Negative"}])),
+            Err(ChapterError::InvalidTimestamp { index: 1, .. })
+        ));
+        assert!(matches!(
+            parse_chapters(&json!(["0:00 Intro"])),
+            Err(ChapterError::NotAnEntry { index: 1 })
+        ));
+        assert!(matches!(
+            parse_chapters(&json!({"start": 0})),
+            Err(ChapterError::NotAList)
+        ));
+    }
+
+    #[test]
+    fn test_load_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let media = dir.path().join("talk.mp4");
+
+        assert!(load_sidecar(&media).unwrap().is_none());
+
+        std::fs::write(
+            sidecar_path(&media),
+            r#"[{"start": "0:30", "title": "Setup"}]"#,
+        )
+        .unwrap();
+        let chapters = load_sidecar(&media).unwrap().unwrap();
+        assert_eq!(chapters[0].start, 30.0);
+
+        std::fs::write(sidecar_path(&media), "not json").unwrap();
+        assert!(matches!(
+            load_sidecar(&media),
+            Err(ChapterError::InvalidJson { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_frontmatter() {
+        let yaml = &yaml_rust2::YamlLoader::load_from_str(
+            "title: Episode 1\nchapters:\n  - start: 0\n    title: Intro\n  - start: \"1:05:00\"\n    title: Wrap-up\n",
+        )
+        .unwrap()[0];
+        let chapters = from_frontmatter(yaml).unwrap().unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].start, 3900.0);
+
+        let yaml = &yaml_rust2::YamlLoader::load_from_str("title: No chapters\n").unwrap()[0];
+        assert!(from_frontmatter(yaml).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_to_script_json_escapes_closing_tags() {
+        let json = to_script_json(&vec![Chapter {
+            start: 0.0,
+            title: "</script>".to_string(),
+        }]);
+        assert!(!json.contains("</script>"));
+        assert!(json.contains("\\u003c/script>"));
+    }
+}
@@ -4,37 +4,63 @@ use std::path::PathBuf;
 /// Markdown browser and previewer
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(group = clap::ArgGroup::new("report").args(["stats", "lint"]))]
 pub struct Args {
     /// Launch GUI window (default if no mode specified)
-    #[arg(short, long, conflicts_with_all = ["server", "stdout", "build", "extract_video_metadata", "extract_pdf_cover"])]
+    #[arg(short, long, conflicts_with_all = ["server", "stdout", "build", "extract_video_metadata", "extract_pdf_cover", "serve_build", "stats"])]
     pub gui: bool,
 
     /// Launch HTTP server only (no GUI)
-    #[arg(short, long, conflicts_with_all = ["gui", "stdout", "build", "extract_video_metadata", "extract_pdf_cover"])]
+    #[arg(short, long, conflicts_with_all = ["gui", "stdout", "build", "extract_video_metadata", "extract_pdf_cover", "serve_build", "stats"])]
     pub server: bool,
 
     /// Render single markdown file to stdout (CLI mode)
-    #[arg(short = 'o', long, conflicts_with_all = ["gui", "server", "build", "extract_video_metadata", "extract_pdf_cover"])]
+    #[arg(short = 'o', long, conflicts_with_all = ["gui", "server", "build", "extract_video_metadata", "extract_pdf_cover", "serve_build", "stats"])]
     pub stdout: bool,
 
     /// Build static site (generate HTML for all markdown files)
-    #[arg(short, long, conflicts_with_all = ["gui", "server", "stdout", "extract_video_metadata", "extract_pdf_cover"])]
+    #[arg(short, long, conflicts_with_all = ["gui", "server", "stdout", "extract_video_metadata", "extract_pdf_cover", "serve_build", "stats"])]
     pub build: bool,
 
+    /// After a static build (-b), serve the output directory and open it,
+    /// combining --build and --serve-build for quick local checks.
+    #[arg(long, requires = "build")]
+    pub open: bool,
+
     /// Extract video metadata (cover, chapters, captions) and save as sidecar files.
     /// Takes a video file path and generates .cover.jpg, .chapters.en.vtt, and
     /// .captions.en.vtt files next to it (if the video contains this data).
     #[cfg(feature = "media-metadata")]
-    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "extract_pdf_cover"])]
+    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "extract_pdf_cover", "serve_build", "stats"])]
     pub extract_video_metadata: bool,
 
     /// Extract cover images from PDF files and save as sidecar files.
     /// Takes a PDF file or directory path and generates {file}.cover.jpg next to each PDF.
     /// For directories, recursively processes all .pdf files.
     #[cfg(feature = "media-metadata")]
-    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "extract_video_metadata"])]
+    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "extract_video_metadata", "serve_build", "stats"])]
     pub extract_pdf_cover: bool,
 
+    /// Serve an already-built static site directory (e.g. the output of --build)
+    /// as plain files, without live rendering. Useful for checking a deploy locally.
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["gui", "server", "stdout", "build", "stats"])]
+    pub serve_build: Option<PathBuf>,
+
+    /// Scan the repository and print a summary (pages, words, tags, links,
+    /// orphan pages, media files), then exit. Renders nothing and writes nothing.
+    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "serve_build"])]
+    pub stats: bool,
+
+    /// Render every page and report content problems (empty links, images
+    /// without alt text, skipped heading levels, duplicate headings), then
+    /// exit. Exits non-zero when anything is found. Writes nothing.
+    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "serve_build", "stats"])]
+    pub lint: bool,
+
+    /// Print --stats or --lint output as JSON instead of a text summary.
+    #[arg(long, requires = "report")]
+    pub json: bool,
+
     /// Output directory for static site build (default: "build")
     #[arg(long, default_value = "build")]
     pub output: PathBuf,
@@ -62,7 +88,8 @@ pub struct Args {
     pub template_folder: Option<PathBuf>,
 
     /// Increase logging verbosity (-v = info, -vv = debug, -vvv = trace).
-    /// Default is warn level. Can also set RUST_LOG env var.
+    /// Default is `log_level` from config (warn). MBR_LOG or RUST_LOG, when
+    /// set, take precedence.
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
@@ -70,12 +97,14 @@ pub struct Args {
     #[arg(short, long)]
     pub quiet: bool,
 
-    /// Port to listen on when running in server mode (-s).
+    /// Port to listen on when running in server mode (-s), or "auto" to
+    /// let the OS pick a free one.
     /// Overrides the default port from config (default: 5200).
-    #[arg(short = 'p', long, value_name = "PORT")]
+    #[arg(short = 'p', long, value_name = "PORT", value_parser = crate::config::parse_port_arg)]
     pub port: Option<u16>,
 
     /// Host/IP address to bind to when running in server mode (-s).
+    /// Accepts an IPv4 address or a hostname that resolves to one.
     /// Overrides the default from config (default: 127.0.0.1).
     /// Use 0.0.0.0 to listen on all interfaces.
     #[arg(long, value_name = "HOST")]
@@ -99,6 +128,22 @@ pub struct Args {
     #[arg(long)]
     pub skip_link_checks: bool,
 
+    /// Skip generating the Pagefind search index during static build (-b).
+    /// Useful for fast content-only builds; the built site has no search.
+    #[arg(long)]
+    pub skip_search_index: bool,
+
+    /// Fail the static build (-b) when several markdown files map to the same
+    /// URL (e.g. guide.md and guide/index.md) instead of only warning.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Rebuild only the pages under this path (e.g. docs/) during a static
+    /// build (-b), updating an existing build in place. Tag and archive
+    /// pages, links.json files and link validation are skipped.
+    #[arg(long, value_name = "PREFIX", requires = "build")]
+    pub only: Option<String>,
+
     /// Disable bidirectional link tracking (backlinks).
     /// When disabled, the links.json endpoint returns 404 and no links.json files
     /// are generated during static builds.
@@ -147,33 +192,50 @@ pub struct Args {
     /// config line, then exit. Nothing is written to disk.
     #[arg(long)]
     pub generate_edit_token: bool,
+
+    /// Set up PATH as an mbr site: create `.mbr/` with a commented
+    /// config.toml and the default theme.css and user.css, plus an example
+    /// index.md if there isn't one, then exit.
+    #[arg(long, conflicts_with_all = ["gui", "server", "stdout", "build", "serve_build", "stats", "lint", "generate_edit_token"])]
+    pub init: bool,
+
+    /// With --init, overwrite the config and stylesheets in an existing `.mbr/`.
+    #[arg(long, requires = "init")]
+    pub force: bool,
 }
 
 impl Args {
+    /// The log level asked for with `-q` or `-v`, if either was given.
+    pub fn log_level(&self) -> Option<&'static str> {
+        if self.quiet {
+            return Some("error");
+        }
+        match self.verbose {
+            0 => None,
+            1 => Some("info"),
+            2 => Some("debug"),
+            _ => Some("trace"),
+        }
+    }
+
     /// Get the log level filter string based on verbosity flags.
     /// Returns a filter suitable for tracing_subscriber::EnvFilter.
     pub fn log_level_filter(&self) -> String {
-        let level = if self.quiet {
-            "error"
-        } else {
-            match self.verbose {
-                0 => "warn",
-                1 => "info",
-                2 => "debug",
-                _ => "trace",
-            }
-        };
-
-        // Set level for mbr crate and tower_http (for request logging)
-        format!(
-            "{}={},tower_http={}",
-            env!("CARGO_CRATE_NAME"),
-            level,
-            level
-        )
+        log_filter_for_level(self.log_level().unwrap_or("warn"))
     }
 }
 
+/// Builds a tracing_subscriber::EnvFilter string logging `level` for the mbr
+/// crate and tower_http (for request logging).
+pub fn log_filter_for_level(level: &str) -> String {
+    format!(
+        "{}={},tower_http={}",
+        env!("CARGO_CRATE_NAME"),
+        level,
+        level
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,10 +248,15 @@ mod tests {
             server: false,
             stdout: false,
             build: false,
+            open: false,
             #[cfg(feature = "media-metadata")]
             extract_video_metadata: false,
             #[cfg(feature = "media-metadata")]
             extract_pdf_cover: false,
+            serve_build: None,
+            stats: false,
+            lint: false,
+            json: false,
             output: PathBuf::from("build"),
             path: PathBuf::from("."),
             oembed_timeout_ms: None,
@@ -202,6 +269,9 @@ mod tests {
             theme: None,
             build_concurrency: None,
             skip_link_checks: false,
+            skip_search_index: false,
+            strict: false,
+            only: None,
             no_link_tracking: false,
             no_relationship_tracking: false,
             mark_incomplete: false,
@@ -212,6 +282,8 @@ mod tests {
             transcode: false,
             edit: false,
             generate_edit_token: false,
+            init: false,
+            force: false,
         }
     }
 
@@ -270,6 +342,17 @@ mod tests {
         assert!(filter.contains("=error"));
     }
 
+    #[test]
+    fn test_log_level_only_set_by_flags() {
+        assert_eq!(args_with_verbosity(0, false).log_level(), None);
+        assert_eq!(args_with_verbosity(2, false).log_level(), Some("debug"));
+        assert_eq!(args_with_verbosity(0, true).log_level(), Some("error"));
+        assert_eq!(
+            log_filter_for_level("info"),
+            format!("{}=info,tower_http=info", env!("CARGO_CRATE_NAME"))
+        );
+    }
+
     #[test]
     fn test_log_level_includes_crate_name() {
         let args = args_with_verbosity(0, false);
@@ -343,6 +426,10 @@ mod tests {
     fn test_parse_port() {
         let args = Args::parse_from(["mbr", "-p", "8080"]);
         assert_eq!(args.port, Some(8080));
+
+        let args = Args::parse_from(["mbr", "--port", "auto"]);
+        assert_eq!(args.port, Some(0));
+        assert!(Args::try_parse_from(["mbr", "--port", "0"]).is_err());
     }
 
     #[test]
@@ -388,6 +475,19 @@ mod tests {
         assert!(args.skip_link_checks);
     }
 
+    #[test]
+    fn test_parse_only_requires_build() {
+        let args = Args::parse_from(["mbr", "-b", "--only", "docs/"]);
+        assert_eq!(args.only.as_deref(), Some("docs/"));
+        assert!(Args::try_parse_from(["mbr", "--only", "docs/"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_skip_search_index() {
+        let args = Args::parse_from(["mbr", "-b", "--skip-search-index"]);
+        assert!(args.skip_search_index);
+    }
+
     #[test]
     fn test_parse_no_link_tracking() {
         let args = Args::parse_from(["mbr", "--no-link-tracking"]);
@@ -448,4 +548,71 @@ mod tests {
         let args = Args::parse_from(["mbr", "--title-suffix", " | My Site"]);
         assert_eq!(args.title_suffix, Some(" | My Site".to_string()));
     }
+
+    #[test]
+    fn test_parse_serve_build() {
+        let args = Args::parse_from(["mbr", "--serve-build", "./build"]);
+        assert_eq!(args.serve_build, Some(PathBuf::from("./build")));
+        assert!(!args.server);
+    }
+
+    #[test]
+    fn test_parse_serve_build_conflicts_with_build() {
+        let result = Args::try_parse_from(["mbr", "-b", "--serve-build", "./build"]);
+        assert!(result.is_err(), "--serve-build and --build should conflict");
+    }
+
+    #[test]
+    fn test_parse_build_open() {
+        let args = Args::parse_from(["mbr", "-b", "--open"]);
+        assert!(args.build);
+        assert!(args.open);
+    }
+
+    #[test]
+    fn test_parse_open_requires_build() {
+        let result = Args::try_parse_from(["mbr", "--open"]);
+        assert!(result.is_err(), "--open should require --build");
+    }
+
+    #[test]
+    fn test_parse_stats_json() {
+        let args = Args::parse_from(["mbr", "--stats", "--json", "./notes"]);
+        assert!(args.stats);
+        assert!(args.json);
+        assert_eq!(args.path, PathBuf::from("./notes"));
+    }
+
+    #[test]
+    fn test_parse_stats_conflicts_with_build() {
+        let result = Args::try_parse_from(["mbr", "-b", "--stats"]);
+        assert!(result.is_err(), "--stats and --build should conflict");
+    }
+
+    #[test]
+    fn test_parse_json_requires_stats() {
+        let result = Args::try_parse_from(["mbr", "--json"]);
+        assert!(result.is_err(), "--json should require --stats or --lint");
+    }
+
+    #[test]
+    fn test_parse_lint_json() {
+        let args = Args::parse_from(["mbr", "--lint", "--json"]);
+        assert!(args.lint);
+        assert!(args.json);
+        let result = Args::try_parse_from(["mbr", "--lint", "--stats"]);
+        assert!(result.is_err(), "--lint and --stats should conflict");
+    }
+
+    #[test]
+    fn test_parse_init_force() {
+        let args = Args::parse_from(["mbr", "--init", "--force", "notes"]);
+        assert!(args.init);
+        assert!(args.force);
+        assert_eq!(args.path, PathBuf::from("notes"));
+        let result = Args::try_parse_from(["mbr", "--force"]);
+        assert!(result.is_err(), "--force should require --init");
+        let result = Args::try_parse_from(["mbr", "--init", "-s"]);
+        assert!(result.is_err(), "--init and --server should conflict");
+    }
 }
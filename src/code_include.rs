@@ -0,0 +1,234 @@
+//! Source file excerpts via the `code` shortcode.
+//!
+//! ```markdown
+//! {{ code(path="src/main.rs", lines="10-30") }}
+//! ```
+//!
+//! Embeds a file (relative to the repo root) as a code block, so docs quote
+//! the real source instead of a copy that drifts. The language comes from the
+//! file extension and highlighting is done by highlight.js like any fenced
+//! block. `lines` is optional: a single line (`12`) or an inclusive range
+//! (`10-30`), counted from 1.
+
+use crate::errors::CodeIncludeError;
+use crate::shortcode;
+use std::path::{Component, Path};
+
+/// A parsed `{{ code(...) }}` shortcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeInclude {
+    /// Source file, relative to the repo root.
+    pub path: String,
+    /// The `lines` parameter as written, if given.
+    pub lines: Option<String>,
+}
+
+impl CodeInclude {
+    /// Parses `text` as a code shortcode. Returns `None` if it isn't one or
+    /// has no `path`.
+    pub fn from_shortcode(text: &str) -> Option<Self> {
+        let attrs = shortcode::parse(text, "code")?;
+        Some(Self {
+            path: attrs
+                .get("path")?
+                .trim()
+                .trim_start_matches('/')
+                .to_string(),
+            lines: attrs.get("lines").map(|lines| lines.trim().to_string()),
+        })
+    }
+
+    /// Reads the file under `root` and returns the selected lines.
+    pub fn excerpt(&self, root: &Path) -> Result<String, CodeIncludeError> {
+        let relative = Path::new(&self.path);
+        if self.path.is_empty()
+            || relative
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(CodeIncludeError::InvalidPath {
+                path: self.path.clone(),
+            });
+        }
+        let file = root.join(relative);
+        if !file.is_file() {
+            return Err(CodeIncludeError::NotFound {
+                path: self.path.clone(),
+            });
+        }
+        let source =
+            std::fs::read_to_string(&file).map_err(|source| CodeIncludeError::ReadFailed {
+                path: self.path.clone(),
+                source,
+            })?;
+        let lines: Vec<&str> = source.lines().collect();
+        let Some(spec) = &self.lines else {
+            return Ok(lines.join("\n"));
+        };
+        let (start, end) = parse_range(spec).ok_or_else(|| CodeIncludeError::InvalidRange {
+            lines: spec.clone(),
+        })?;
+        if end > lines.len() {
+            return Err(CodeIncludeError::RangeOutOfBounds {
+                path: self.path.clone(),
+                lines: spec.clone(),
+                line_count: lines.len(),
+            });
+        }
+        Ok(lines[start - 1..end].join("\n"))
+    }
+
+    /// highlight.js language for the file, from its extension.
+    pub fn language(&self) -> Option<String> {
+        let ext = Path::new(&self.path)
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+        let language = match ext.as_str() {
+            "rs" => "rust",
+            "py" | "pyi" => "python",
+            "js" | "mjs" | "cjs" | "jsx" => "javascript",
+            "ts" | "mts" | "cts" | "tsx" => "typescript",
+            "rb" => "ruby",
+            "sh" | "bash" | "zsh" => "bash",
+            "yml" => "yaml",
+            "md" | "markdown" => "markdown",
+            "html" | "htm" | "svg" => "xml",
+            "kt" | "kts" => "kotlin",
+            "h" => "c",
+            "hpp" | "cc" | "cxx" => "cpp",
+            "cs" => "csharp",
+            _ => ext.as_str(),
+        };
+        Some(language.to_string())
+    }
+}
+
+/// Parses `12` or `10-30` into an inclusive, 1-based `(start, end)`.
+fn parse_range(spec: &str) -> Option<(usize, usize)> {
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let line = spec.trim().parse().ok()?;
+            (line, line)
+        }
+    };
+    (start >= 1 && start <= end).then_some((start, end))
+}
+
+/// Renders an excerpt as a code block highlight.js picks up.
+pub fn code_html(code: &str, language: Option<&str>) -> String {
+    let class = language
+        .map(|lang| {
+            format!(
+                r#" class="language-{}""#,
+                html_escape::encode_double_quoted_attribute(lang)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "<pre><code{class}>{}\n</code></pre>\n",
+        html_escape::encode_text(code)
+    )
+}
+
+/// Renders a failed include as a visible notice in place of the code.
+pub fn error_html(error: &CodeIncludeError) -> String {
+    format!(
+        r#"<p class="mbr-code-include-error" role="alert">{}</p>"#,
+        html_escape::encode_text(&error.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn include(path: &str, lines: Option<&str>) -> CodeInclude {
+        CodeInclude {
+            path: path.to_string(),
+            lines: lines.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_from_shortcode() {
+        assert_eq!(
+            CodeInclude::from_shortcode(r#"{{ code(path="src/main.rs", lines="10-30") }}"#),
+            Some(include("src/main.rs", Some("10-30")))
+        );
+        assert_eq!(
+            CodeInclude::from_shortcode("{{ code(path=\u{201C}/build.rs\u{201D}) }}"),
+            Some(include("build.rs", None))
+        );
+        assert_eq!(
+            CodeInclude::from_shortcode(r#"{{ code(lines="1-2") }}"#),
+            None
+        );
+        assert_eq!(
+            CodeInclude::from_shortcode(r#"{{ gallery(dir="a") }}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_excerpt_selects_lines() {
+        let root = TempDir::new().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "one\ntwo\r\nthree\nfour\n").unwrap();
+
+        let excerpt = |lines| include("src/lib.rs", lines).excerpt(root.path());
+        assert_eq!(excerpt(None).unwrap(), "one\ntwo\nthree\nfour");
+        assert_eq!(excerpt(Some("2-3")).unwrap(), "two\nthree");
+        assert_eq!(excerpt(Some("4")).unwrap(), "four");
+        assert!(matches!(
+            excerpt(Some("3-5")),
+            Err(CodeIncludeError::RangeOutOfBounds { line_count: 4, .. })
+        ));
+        for bad in ["0-2", "3-2", "a-b", "", "2-"] {
+            assert!(
+                matches!(
+                    excerpt(Some(bad)),
+                    Err(CodeIncludeError::InvalidRange { .. })
+                ),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_excerpt_rejects_missing_and_escaping_paths() {
+        let root = TempDir::new().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        assert!(matches!(
+            include("src/missing.rs", None).excerpt(root.path()),
+            Err(CodeIncludeError::NotFound { .. })
+        ));
+        assert!(matches!(
+            include("src", None).excerpt(root.path()),
+            Err(CodeIncludeError::NotFound { .. })
+        ));
+        assert!(matches!(
+            include("../secret.txt", None).excerpt(root.path()),
+            Err(CodeIncludeError::InvalidPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_language_from_extension() {
+        assert_eq!(include("a/b.rs", None).language().as_deref(), Some("rust"));
+        assert_eq!(include("x.YML", None).language().as_deref(), Some("yaml"));
+        assert_eq!(include("x.go", None).language().as_deref(), Some("go"));
+        assert_eq!(include("Makefile", None).language(), None);
+    }
+
+    #[test]
+    fn test_code_html_escapes() {
+        assert_eq!(
+            code_html("if a < b {}", Some("rust")),
+            "<pre><code class=\"language-rust\">if a &lt; b {}\n</code></pre>\n"
+        );
+        assert_eq!(code_html("x", None), "<pre><code>x\n</code></pre>\n");
+    }
+}
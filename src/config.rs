@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize, Serializer};
 use std::{
-    net::IpAddr,
+    collections::HashMap,
+    net::{IpAddr, ToSocketAddrs},
     path::{Path, PathBuf},
 };
 
@@ -14,11 +15,15 @@ use crate::errors::ConfigError;
 const DEFAULT_PORT: u16 = 5200;
 const DEFAULT_OEMBED_TIMEOUT_MS: u64 = 500;
 const DEFAULT_OEMBED_CACHE_SIZE: usize = 2 * 1024 * 1024; // 2 MB
+const DEFAULT_STREAM_RENDER_THRESHOLD: u64 = 1024 * 1024; // 1 MB
+const DEFAULT_MAX_RENDER_BYTES: u64 = 64 * 1024 * 1024; // 64 MB
+const DEFAULT_SERVER_RENDER_CONCURRENCY: usize = 8;
 
 /// Configuration for a single sort field in multi-level sorting.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SortField {
-    /// Field to sort by: "title", "filename", "created", "modified", or any frontmatter field
+    /// Field to sort by: "title", "filename", "created", "modified", "date",
+    /// "created_date", "updated_date", or any frontmatter field
     pub field: String,
     /// Sort order: "asc" or "desc"
     #[serde(default = "default_sort_order")]
@@ -28,6 +33,10 @@ pub struct SortField {
     pub compare: String,
 }
 
+fn default_date_format() -> String {
+    crate::dates::DEFAULT_DATE_FORMAT.to_string()
+}
+
 fn default_sort_order() -> String {
     "asc".to_string()
 }
@@ -44,6 +53,14 @@ fn default_relationship_tracking() -> bool {
     true
 }
 
+fn default_respect_gitignore() -> bool {
+    true
+}
+
+fn default_follow_symlinks() -> bool {
+    true
+}
+
 /// Default markers that flag a block as incomplete.
 ///
 /// A block whose first text matches `^(MARKER)\b` (uppercase, word boundary)
@@ -61,6 +78,96 @@ fn default_build_tag_pages() -> bool {
     true
 }
 
+fn default_clean_urls() -> bool {
+    true
+}
+
+fn default_lazy_images() -> bool {
+    true
+}
+
+fn default_scroll_spy() -> bool {
+    true
+}
+
+fn default_oembed_concurrency() -> usize {
+    crate::oembed_cache::DEFAULT_OEMBED_CONCURRENCY
+}
+
+fn default_inbound_cache_size() -> usize {
+    crate::link_grep::DEFAULT_INBOUND_CACHE_SIZE
+}
+
+fn default_inbound_cache_ttl_secs() -> u64 {
+    crate::link_grep::DEFAULT_INBOUND_CACHE_TTL_SECS
+}
+
+fn default_stream_render_threshold() -> u64 {
+    DEFAULT_STREAM_RENDER_THRESHOLD
+}
+
+fn default_server_render_concurrency() -> usize {
+    DEFAULT_SERVER_RENDER_CONCURRENCY
+}
+
+fn default_max_render_bytes() -> u64 {
+    DEFAULT_MAX_RENDER_BYTES
+}
+
+fn default_search_placeholder() -> String {
+    "Search files...".to_string()
+}
+
+fn default_search_result_limit() -> usize {
+    20
+}
+
+fn default_site_lang() -> String {
+    "en".to_string()
+}
+
+fn default_site_dir() -> String {
+    "ltr".to_string()
+}
+
+const DEFAULT_AUDIO_TRANSCODE_BITRATE_KBPS: u32 = 128;
+
+fn default_audio_transcode_bitrate_kbps() -> u32 {
+    DEFAULT_AUDIO_TRANSCODE_BITRATE_KBPS
+}
+
+fn default_access_log_format() -> String {
+    "human".to_string()
+}
+
+fn default_progress_format() -> String {
+    "auto".to_string()
+}
+
+fn default_log_level() -> String {
+    "warn".to_string()
+}
+
+fn default_ffmpeg_log_level() -> String {
+    "fatal".to_string()
+}
+
+/// Accepted `log_level` values, least to most verbose.
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Accepted `ffmpeg_log_level` values (ffmpeg's own level names), least to
+/// most verbose.
+pub const FFMPEG_LOG_LEVELS: &[&str] = &[
+    "quiet", "panic", "fatal", "error", "warning", "info", "verbose", "debug", "trace",
+];
+
+/// Accepted `mermaid_theme` values (mermaid's built-in themes).
+pub const MERMAID_THEMES: &[&str] = &["default", "neutral", "dark", "forest", "base"];
+
+fn default_listing_layout() -> String {
+    "list".to_string()
+}
+
 fn default_sidebar_style() -> String {
     "panel".to_string()
 }
@@ -71,6 +178,14 @@ fn default_sidebar_max_items() -> usize {
     DEFAULT_SIDEBAR_MAX_ITEMS
 }
 
+fn default_toc_min_level() -> u8 {
+    1
+}
+
+fn default_toc_max_level() -> u8 {
+    6
+}
+
 /// Configuration for a tag source - a frontmatter field that contains tags.
 ///
 /// # Examples
@@ -88,6 +203,13 @@ fn default_sidebar_max_items() -> usize {
 ///     { field = "taxonomy.performers", label = "Performer", label_plural = "Performers" }
 /// ]
 /// ```
+///
+/// Author pages at `/authors/<name>/`, with bios from `data/authors.yml`:
+/// ```toml
+/// tag_sources = [
+///     { field = "authors", fields = ["author"], label = "Author", data = "authors" }
+/// ]
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TagSource {
     /// The frontmatter field to extract tags from.
@@ -103,9 +225,26 @@ pub struct TagSource {
     /// Auto-derived from field name if not specified.
     #[serde(default)]
     pub label_plural: Option<String>,
+
+    /// Additional frontmatter fields merged into this source (e.g., `["keywords"]`
+    /// alongside `field = "tags"`). Their values share this source's URLs and labels.
+    #[serde(default)]
+    pub fields: Vec<String>,
+
+    /// Data file (by name, from `data_folder`) with metadata for each value,
+    /// keyed by value (e.g., `"authors"` for `data/authors.yml`). Sources with
+    /// metadata render their pages with `author.html`.
+    #[serde(default)]
+    pub data: Option<String>,
 }
 
 impl TagSource {
+    /// Returns every frontmatter field that contributes to this source:
+    /// the primary `field` followed by any additional `fields`.
+    pub fn all_fields(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.field.as_str()).chain(self.fields.iter().map(String::as_str))
+    }
+
     /// Returns the singular label for this tag source.
     ///
     /// Priority:
@@ -178,13 +317,16 @@ pub fn default_tag_sources() -> Vec<TagSource> {
         field: "tags".to_string(),
         label: None,
         label_plural: None,
+        fields: Vec::new(),
+        data: None,
     }]
 }
 
 /// Converts tag sources to a HashSet of field names for wikilink matching.
 ///
-/// The HashSet contains the field names from each TagSource, which are used
+/// The HashSet contains the primary field name of each TagSource, which is used
 /// to detect valid tag link patterns like `[[Tags:rust]]` or `[text](tags:value)`.
+/// Additional `fields` share the primary field's URL, so they aren't separate sources.
 pub fn tag_sources_to_set(sources: &[TagSource]) -> std::collections::HashSet<String> {
     sources.iter().map(|s| s.field.clone()).collect()
 }
@@ -322,17 +464,144 @@ pub fn default_sort_config() -> Vec<SortField> {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct IpArray(pub [u8; 4]);
 
+impl IpArray {
+    /// Whether this is `0.0.0.0`, i.e. every network interface.
+    pub fn is_unspecified(&self) -> bool {
+        self.0 == [0, 0, 0, 0]
+    }
+
+    /// Address to put in URLs the user opens: `127.0.0.1` when bound to all
+    /// interfaces (which isn't connectable everywhere), else the host itself.
+    pub fn browse_host(&self) -> IpArray {
+        if self.is_unspecified() {
+            IpArray([127, 0, 0, 1])
+        } else {
+            *self
+        }
+    }
+
+    /// Parses an IPv4 address, or resolves a hostname (such as `localhost`)
+    /// to its first IPv4 address.
+    pub fn resolve(host: &str) -> Result<Self, ConfigError> {
+        let invalid = || ConfigError::InvalidHost {
+            host: host.to_string(),
+        };
+        let trimmed = host.trim();
+        if trimmed.is_empty() {
+            return Err(invalid());
+        }
+        match trimmed.parse::<IpAddr>() {
+            Ok(IpAddr::V4(v4)) => Ok(IpArray(v4.octets())),
+            Ok(IpAddr::V6(_)) => Err(invalid()),
+            Err(_) => (trimmed, 0)
+                .to_socket_addrs()
+                .map_err(|_| invalid())?
+                .find_map(|addr| match addr.ip() {
+                    IpAddr::V4(v4) => Some(IpArray(v4.octets())),
+                    IpAddr::V6(_) => None,
+                })
+                .ok_or_else(invalid),
+        }
+    }
+}
+
+/// Port value meaning "let the OS pick a free port" (stored as 0).
+pub const AUTO_PORT: &str = "auto";
+
+/// A `port` setting as written: a number, or a string such as `"auto"`
+/// (environment variables and some TOML files quote numbers).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PortSetting {
+    Number(i64),
+    Text(String),
+}
+
+impl PortSetting {
+    /// The port to bind: 1-65535, or 0 for [`AUTO_PORT`]. A literal 0 is
+    /// rejected so "auto" is always spelled out.
+    fn resolve(&self) -> Result<u16, ConfigError> {
+        let port = match self {
+            PortSetting::Text(text) if text.trim().eq_ignore_ascii_case(AUTO_PORT) => return Ok(0),
+            PortSetting::Text(text) => text.trim().parse::<i64>().ok(),
+            PortSetting::Number(n) => Some(*n),
+        };
+        port.and_then(|p| u16::try_from(p).ok())
+            .filter(|&p| p != 0)
+            .ok_or_else(|| ConfigError::InvalidPort {
+                value: match self {
+                    PortSetting::Number(n) => n.to_string(),
+                    PortSetting::Text(text) => text.clone(),
+                },
+            })
+    }
+}
+
+/// Parses a CLI `--port` value with the same rules as the `port` setting.
+pub fn parse_port_arg(value: &str) -> Result<u16, String> {
+    PortSetting::Text(value.to_string())
+        .resolve()
+        .map_err(|e| e.to_string())
+}
+
+fn deserialize_port<'de, D>(deserializer: D) -> Result<u16, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    PortSetting::deserialize(deserializer)?
+        .resolve()
+        .map_err(serde::de::Error::custom)
+}
+
+fn serialize_port<S>(port: &u16, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match port {
+        0 => serializer.serialize_str(AUTO_PORT),
+        port => serializer.serialize_u16(*port),
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub root_dir: PathBuf,
+    /// IPv4 address or hostname to bind the server to. Hostnames are
+    /// resolved once, at startup.
+    /// Default: "127.0.0.1".
     pub host: IpArray,
+    /// Port to listen on, or "auto" (stored as 0) to let the OS pick a
+    /// free one.
+    /// Default: 5200.
+    #[serde(
+        deserialize_with = "deserialize_port",
+        serialize_with = "serialize_port"
+    )]
     pub port: u16,
     pub static_folder: String,
     pub markdown_extensions: Vec<String>,
+    /// Renderers for other text formats, keyed by extension
+    /// (e.g. `txt = "plaintext"`). Listed extensions are scanned and served
+    /// as pages alongside `markdown_extensions`.
+    /// Default: empty.
+    #[serde(default)]
+    pub renderers: HashMap<String, crate::markdown::RendererKind>,
     pub theme: String,
     pub index_file: String,
     pub ignore_dirs: Vec<String>,
     pub ignore_globs: Vec<String>,
+    /// Skip files matched by the repository's root `.gitignore` (and
+    /// `.git/info/exclude`) when scanning and grepping for inbound links.
+    /// The `.mbr/` folder and the static folder are never ignored.
+    /// Default: true.
+    #[serde(default = "default_respect_gitignore")]
+    pub respect_gitignore: bool,
+    /// Scan folders reached through symlinks. Each folder is walked once
+    /// however many links lead to it, so symlink cycles can't hang a scan.
+    /// When false, symlinked folders are skipped (symlinked files are kept).
+    /// Default: true.
+    #[serde(default = "default_follow_symlinks")]
+    pub follow_symlinks: bool,
     /// Directories to ignore in the file watcher. These directories will not trigger
     /// live reload events when files inside them change.
     pub watcher_ignore_dirs: Vec<String>,
@@ -341,14 +610,43 @@ pub struct Config {
     /// Set to 0 to disable oembed fetching entirely (uses plain links for all URLs
     /// except YouTube and Giphy which are embedded without network calls).
     pub oembed_timeout_ms: u64,
+    /// How a bare URL is rendered when fetching its metadata fails: `link`
+    /// (a plain link), `card` (an "embed unavailable" card with the URL) or
+    /// `omit` (nothing).
+    /// Default: link.
+    #[serde(default)]
+    pub oembed_fallback: crate::oembed::OembedFallback,
     /// Maximum size in bytes for the oembed cache. The cache stores fetched page
     /// metadata to avoid redundant network requests when rendering multiple files.
     /// Set to 0 to disable caching entirely. Default: 2MB (2097152 bytes).
     pub oembed_cache_size: usize,
+    /// Maximum number of oembed/OpenGraph fetches in flight at once, within a
+    /// page and, during builds, across the whole site. Default: 8.
+    #[serde(default = "default_oembed_concurrency")]
+    pub oembed_concurrency: usize,
+    /// Seconds a cached oembed result is reused before the URL is fetched
+    /// again. Set to 0 to keep entries until they're evicted for space.
+    /// Default: 0.
+    #[serde(default)]
+    pub oembed_cache_ttl_secs: u64,
+    /// Maximum size in bytes for the server's cache of backlinks found by
+    /// scanning the repo. Set to 0 to scan on every request.
+    /// Default: 1MB (1048576 bytes).
+    #[serde(default = "default_inbound_cache_size")]
+    pub inbound_cache_size: usize,
+    /// Seconds cached backlinks are reused before the repo is scanned again.
+    /// Default: 60.
+    #[serde(default = "default_inbound_cache_ttl_secs")]
+    pub inbound_cache_ttl_secs: u64,
     /// Optional template folder that overrides the default .mbr/ and compiled defaults.
     /// Files found here take precedence; missing files fall back to compiled defaults.
     #[serde(default)]
     pub template_folder: Option<PathBuf>,
+    /// Folder of `.json` and `.csv` data files, relative to the root, exposed to
+    /// templates via `data()` and to pages via the `{{ data(file="...") }}` shortcode.
+    /// Default: None (no data files).
+    #[serde(default)]
+    pub data_folder: Option<String>,
     /// Sort configuration for file listings. Supports multi-level sorting by any field.
     /// Default: sort by title (falling back to filename), ascending, string comparison.
     #[serde(default = "default_sort_config")]
@@ -357,16 +655,92 @@ pub struct Config {
     /// None = auto-detect based on CPU cores (2x cores, capped at 32).
     #[serde(default)]
     pub build_concurrency: Option<usize>,
+    /// How `--build` reports progress: "auto" redraws a status line in place
+    /// when stdout is a terminal and prints plain lines otherwise (CI, log
+    /// files); "tty" and "plain" force one style.
+    /// Default: "auto".
+    #[serde(default = "default_progress_format")]
+    pub progress_format: String,
     /// Enable dynamic video transcoding to serve lower-resolution variants (720p, 480p).
     /// Only active in server/GUI mode. Videos are transcoded on-demand as HLS segments
     /// and cached in memory. Default: false (disabled).
     #[serde(default)]
     pub transcode: bool,
+    /// Force a specific H.264 encoder for video transcoding (e.g. "libx264",
+    /// "h264_nvenc"), or "software" to skip hardware encoders entirely.
+    /// Default: None (auto-detect, preferring hardware encoders).
+    #[serde(default)]
+    pub video_encoder: Option<String>,
+    /// Directory for intermediate transcode output (HLS segments, audio transcodes).
+    /// Created if missing. Default: None (the system temp directory).
+    #[serde(default)]
+    pub transcode_tmp_dir: Option<PathBuf>,
+    /// Transcode lossless audio (WAV, FLAC, AIFF) to a web-friendly codec ("aac" or
+    /// "opus"). Server/GUI mode transcodes on demand and caches the result in memory;
+    /// static builds pre-generate `song.flac.opus` next to the original. Players list
+    /// the transcode first and keep the original as a fallback.
+    /// Default: None (disabled). Requires the media-metadata feature.
+    #[serde(default)]
+    pub audio_transcode: Option<String>,
+    /// Target bitrate in kbps for `audio_transcode`. Default: 128.
+    #[serde(default = "default_audio_transcode_bitrate_kbps")]
+    pub audio_transcode_bitrate_kbps: u32,
     /// Skip internal link validation during static site builds.
     /// When true, the build will not check if internal links point to valid files.
     /// Default: false (link checking enabled).
     #[serde(default)]
     pub skip_link_checks: bool,
+    /// Skip generating the Pagefind search index during static site builds.
+    /// Builds are faster, but the static site has no search.
+    /// Default: false (search index generated).
+    #[serde(default)]
+    pub skip_search_index: bool,
+    /// Indent the `site.json`, `media.json` and `links.json` files written by
+    /// static builds, for reading and diffing them while developing.
+    /// Default: false (compact).
+    #[serde(default)]
+    pub pretty_json: bool,
+    /// Reuse work from the previous static build in the same output directory.
+    /// The Pagefind search index is kept as-is when the HTML it covers hasn't
+    /// changed since it was generated.
+    /// Default: false.
+    #[serde(default)]
+    pub incremental: bool,
+    /// Only rebuild pages, section pages and assets under this path (relative
+    /// to the root, e.g. "docs/"). The output directory isn't cleaned, so the
+    /// rest of the previous build stays as it was. Usually set with `--only`.
+    /// Default: None (full build).
+    #[serde(default)]
+    pub build_only: Option<String>,
+    /// Fail static builds on problems that otherwise only warn: markdown
+    /// files that map to the same URL (e.g. `guide.md` and `guide/index.md`).
+    /// Default: false.
+    #[serde(default)]
+    pub strict: bool,
+    /// Static builds inline local stylesheets, scripts and SVG images smaller
+    /// than this many bytes into the pages that reference them. Tags with a
+    /// `data-no-inline` attribute are left alone.
+    /// Default: 0 (disabled).
+    #[serde(default)]
+    pub inline_assets_under_bytes: u64,
+    /// Shell command run (via `sh -c`, from the root directory) after a
+    /// successful static build, e.g. a deploy script. Build statistics are
+    /// passed as `MBR_*` environment variables. A non-zero exit fails the build.
+    /// Default: None.
+    #[serde(default)]
+    pub post_build_command: Option<String>,
+    /// In static builds, read each page's last commit (author, date, short
+    /// hash) from git. It's passed to templates as `last_commit` and replaces
+    /// the file modification time. Files git doesn't track keep their
+    /// modification time.
+    /// Default: false (git isn't run).
+    #[serde(default)]
+    pub git_metadata: bool,
+    /// Checks run by `mbr --lint`, as a `[lint]` table of booleans:
+    /// `empty_links`, `missing_alt`, `heading_levels`, `duplicate_headings`.
+    /// Default: all enabled.
+    #[serde(default)]
+    pub lint: crate::lint::LintOptions,
     /// Enable bidirectional link tracking (backlinks).
     /// When enabled, generates links.json endpoints/files for each page with inbound/outbound links.
     /// Server mode: lazy grep-based discovery on-demand with caching.
@@ -374,6 +748,12 @@ pub struct Config {
     /// Default: true (enabled).
     #[serde(default = "default_link_tracking")]
     pub link_tracking: bool,
+    /// Which links.json files static builds write:
+    /// - "per_page": a links.json next to each page (default)
+    /// - "consolidated": one `.mbr/links.json` mapping URL to links
+    /// - "both": per-page files plus the consolidated index
+    #[serde(default)]
+    pub link_index_mode: crate::link_index::LinkIndexMode,
     /// Tag sources configuration for extracting tags from frontmatter fields.
     /// Supports dot-notation for nested fields (e.g., "taxonomy.tags").
     /// Default: extract from "tags" field.
@@ -396,6 +776,84 @@ pub struct Config {
     /// Default: true (enabled).
     #[serde(default = "default_build_tag_pages")]
     pub build_tag_pages: bool,
+    /// Write static build pages as `{url}/index.html`, served at
+    /// `/guide/`-style URLs. When false, markdown pages are written as
+    /// `guide.html` beside their source and every link names its `.html`
+    /// file, for hosts that don't map folders to `index.html`.
+    /// Default: true.
+    #[serde(default = "default_clean_urls")]
+    pub clean_urls: bool,
+    /// Maximum files per directory page and pages per tag page in static
+    /// builds. Longer listings continue on `page/2/`, `page/3/`, and so on.
+    /// Default: 0 (no pagination).
+    #[serde(default)]
+    pub page_size: usize,
+    /// Layout of the pages in directory listings: "list", "cards", or
+    /// "grid". Cards and grid show each page's cover image, taken from
+    /// frontmatter `image` or else the first image in the content.
+    /// Default: "list".
+    #[serde(default = "default_listing_layout")]
+    pub listing_layout: String,
+    /// Show card and grid cover images as thumbnails rather than full-size
+    /// images. Applies to local JPEG/PNG covers; static builds write the
+    /// thumbnails under `.mbr/thumbs/`.
+    /// Default: false.
+    #[serde(default)]
+    pub listing_thumbnails: bool,
+    /// Generate date-based archive pages during static site builds: an
+    /// /archive/ index plus /archive/{year}/ and /archive/{year}/{month}/
+    /// listings of pages with a `created` or `date` frontmatter field.
+    /// Default: false (disabled).
+    #[serde(default)]
+    pub build_archive_pages: bool,
+    /// Feeds of the newest pages with a `created` or `date` frontmatter field,
+    /// written at the site root during static builds: "json" for a JSON Feed
+    /// (`feed.json`). Needs `base_url`, since feed links must be absolute.
+    /// Default: empty (no feeds).
+    #[serde(default)]
+    pub feed_formats: Vec<String>,
+    /// Sections (relative to the root, e.g. "blog/") that get their own feed
+    /// of the dated pages under them, written into the section's folder in
+    /// each of `feed_formats` (JSON when none are set) and linked from the
+    /// section's page. Sections without dated pages get no feed.
+    /// Default: empty.
+    #[serde(default)]
+    pub section_feeds: Vec<String>,
+    /// Modern image formats to generate alongside JPEG/PNG images during static
+    /// builds. Only "webp" is supported, and it's encoded losslessly. Each
+    /// variant is written next to the original as `photo.jpg.webp`, and `<img>`
+    /// tags are wrapped in `<picture>` markup.
+    /// Default: empty (no transcoding).
+    #[serde(default)]
+    pub image_formats: Vec<String>,
+    /// Content-Type overrides for served files, keyed by extension
+    /// (e.g. `opus = "audio/ogg; codecs=opus"`). Layered over the built-in
+    /// corrections for HLS (`m3u8`, `ts`) and MP4 media (`m4v`, `m4a`).
+    /// Default: empty.
+    #[serde(default)]
+    pub mime_overrides: HashMap<String, String>,
+    /// File the server appends one line per request to. When unset, the
+    /// lines are logged at info level instead (shown with `-v`).
+    /// Default: none.
+    #[serde(default)]
+    pub access_log: Option<PathBuf>,
+    /// Access log line format: "human" (`GET /docs/ 200 5123B 3.2ms`) or
+    /// "combined" (Apache/NCSA combined log format).
+    /// Default: "human".
+    #[serde(default = "default_access_log_format")]
+    pub access_log_format: String,
+    /// Log level for mbr's own messages: "error", "warn", "info", "debug" or
+    /// "trace". The `-v`/`-q` flags and the `MBR_LOG`/`RUST_LOG` environment
+    /// variables take precedence.
+    /// Default: "warn".
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// How much ffmpeg itself logs while reading media metadata and
+    /// transcoding: one of ffmpeg's level names ("quiet", "panic", "fatal",
+    /// "error", "warning", "info", "verbose", "debug", "trace").
+    /// Default: "fatal".
+    #[serde(default = "default_ffmpeg_log_level")]
+    pub ffmpeg_log_level: String,
     /// Sidebar navigation style.
     /// - "panel": Three-pane modal browser (default, existing mbr-browse)
     /// - "single": Persistent single-column sidebar (new mbr-browse-single)
@@ -405,6 +863,54 @@ pub struct Config {
     /// Default: 100. Only applies when sidebar_style = "single".
     #[serde(default = "default_sidebar_max_items")]
     pub sidebar_max_items: usize,
+    /// Shallowest heading level (1-6) listed in the table of contents.
+    /// Default: 1.
+    #[serde(default = "default_toc_min_level")]
+    pub toc_min_level: u8,
+    /// Deepest heading level (1-6) listed in the table of contents.
+    /// Default: 6.
+    #[serde(default = "default_toc_max_level")]
+    pub toc_max_level: u8,
+    /// Number table of contents entries (`1.`, `1.1`) from the heading hierarchy.
+    /// Default: false.
+    #[serde(default)]
+    pub toc_numbered: bool,
+    /// Markdown syntax extensions (tables, footnotes, smart punctuation, ...)
+    /// as a `[markdown_options]` table of booleans.
+    /// Default: all enabled.
+    #[serde(default)]
+    pub markdown_options: crate::markdown::MarkdownOptions,
+    /// Height of the sticky page header as a CSS length (e.g. "4rem"), used
+    /// to offset anchor jumps so headings aren't hidden beneath it.
+    /// Default: None (the theme's 3em).
+    #[serde(default)]
+    pub header_height: Option<String>,
+    /// Animate jumps to in-page anchors (honors `prefers-reduced-motion`).
+    /// Default: false.
+    #[serde(default)]
+    pub smooth_scroll: bool,
+    /// Highlight the table-of-contents entry for the section currently in
+    /// view as the reader scrolls.
+    /// Default: true.
+    #[serde(default = "default_scroll_spy")]
+    pub scroll_spy: bool,
+    /// Placeholder text for the search box.
+    /// Default: "Search files...".
+    #[serde(default = "default_search_placeholder")]
+    pub search_placeholder: String,
+    /// Maximum number of search results shown (server search and Pagefind).
+    /// Default: 20.
+    #[serde(default = "default_search_result_limit")]
+    pub search_result_limit: usize,
+    /// Site-wide sequence for prev/next page links, replacing directory
+    /// siblings for the pages it lists.
+    /// - "nav": follow the curated `.mbr/nav.yml` manifest
+    /// - any other value: a markdown file relative to the root (e.g.
+    ///   "SUMMARY.md") whose links define the order
+    ///
+    /// Default: None (prev/next step through directory siblings).
+    #[serde(default)]
+    pub reading_order: Option<String>,
     /// Text to prepend to all page titles (e.g., "My Site: ").
     /// Default: empty string (no prefix).
     #[serde(default)]
@@ -413,6 +919,160 @@ pub struct Config {
     /// Default: empty string (no suffix).
     #[serde(default)]
     pub title_suffix: String,
+    /// Language of the site as a BCP 47 tag (e.g. "en", "pt-BR"), emitted as
+    /// `<html lang>`. Pages can override it with frontmatter `lang`.
+    /// Default: "en".
+    #[serde(default = "default_site_lang")]
+    pub site_lang: String,
+    /// Text direction of the site: "ltr", "rtl", or "auto", emitted as
+    /// `<html dir>`. Pages can override it with frontmatter `dir`.
+    /// Default: "ltr".
+    #[serde(default = "default_site_dir")]
+    pub site_dir: String,
+    /// Name of the site, used for the generated `manifest.webmanifest`
+    /// (which is only written when this is set).
+    /// Default: None.
+    #[serde(default)]
+    pub site_name: Option<String>,
+    /// Favicon file relative to the root directory (`.ico`, `.png`, `.svg`,
+    /// `.jpg`, `.gif` or `.webp`), served as `favicon.<ext>` at the site root
+    /// in place of the built-in icon.
+    /// Default: None.
+    #[serde(default)]
+    pub favicon: Option<String>,
+    /// Browser UI color, emitted as `<meta name="theme-color">` and in the
+    /// web app manifest (e.g. "#336699").
+    /// Default: None.
+    #[serde(default)]
+    pub theme_color: Option<String>,
+    /// Splash screen background color for the web app manifest.
+    /// Default: None.
+    #[serde(default)]
+    pub background_color: Option<String>,
+    /// Default `<meta name="robots">` content for every page (e.g.
+    /// "noindex, nofollow" for a staging site). Pages override it with
+    /// `robots`, `noindex` or `nofollow` frontmatter.
+    /// Default: None (no tag).
+    #[serde(default)]
+    pub robots: Option<String>,
+    /// Contents of the `robots.txt` served and written at the site root. A
+    /// `robots.txt` in the repository or static folder takes precedence.
+    /// Default: None (allow all crawlers, naming `sitemap.xml` when the site
+    /// has one and `base_url` is set).
+    #[serde(default)]
+    pub robots_txt: Option<String>,
+    /// Content-Security-Policy sent as a header by the server and embedded
+    /// as `<meta http-equiv>` in built pages. `{nonce}` is replaced with the
+    /// nonce carried by mbr's inline scripts and styles, e.g.
+    /// "script-src 'self' 'nonce-{nonce}'".
+    /// Default: None.
+    #[serde(default)]
+    pub content_security_policy: Option<String>,
+    /// Drop a page's first H1 from the rendered body when it repeats the
+    /// frontmatter `title` (compared trimmed and case-insensitively), for
+    /// templates that already display the title.
+    /// Default: false.
+    #[serde(default)]
+    pub dedupe_title_h1: bool,
+    /// Public URL of the site (e.g. "https://notes.example.com/"). Links to
+    /// this host count as internal when decorating external links.
+    /// Default: None.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// URL for editing a page's source, with `{path}` standing for the file
+    /// path relative to the root (e.g.
+    /// "https://github.com/me/notes/edit/main/{path}"). Pages get the result
+    /// as `edit_url` and the default template links to it.
+    /// Default: None (no edit link).
+    #[serde(default)]
+    pub edit_url_template: Option<String>,
+    /// The site's canonical host (e.g. "notes.example.com"). Links written as
+    /// full `http(s)://` URLs on this host are found as backlinks, the same
+    /// as root-relative links.
+    /// Default: None.
+    #[serde(default)]
+    pub site_host: Option<String>,
+    /// Open external `http(s)` links in a new tab, with
+    /// `rel="noopener noreferrer"` and the `mbr-external-link` class.
+    /// Default: false.
+    #[serde(default)]
+    pub external_links_new_tab: bool,
+    /// Add `loading="lazy"` and `decoding="async"` to content images after
+    /// the first, so long image-heavy pages load faster. Images that already
+    /// set `loading` are left alone.
+    /// Default: true.
+    #[serde(default = "default_lazy_images")]
+    pub lazy_images: bool,
+    /// Largest width, as a CSS length (e.g. "40rem", "100%"), for content
+    /// images, videos and embedded iframes. Height follows the aspect ratio.
+    /// An element's `data-max-width` attribute overrides it (`none` opts out).
+    /// Default: None (no limit beyond the theme's).
+    #[serde(default)]
+    pub media_max_width: Option<String>,
+    /// Wrap YouTube and Vimeo players in a click-to-play facade and point
+    /// them at their privacy domains (`youtube-nocookie.com`, Vimeo's
+    /// `dnt=1`), so nothing loads from the provider until the reader clicks.
+    /// Default: false.
+    #[serde(default)]
+    pub privacy_embeds: bool,
+    /// Mermaid theme for diagrams: "default", "neutral", "dark", "forest"
+    /// or "base".
+    /// Default: None (follows the reader's light/dark mode).
+    #[serde(default)]
+    pub mermaid_theme: Option<String>,
+    /// Extra options for `mermaid.initialize()` as a JSON object, e.g.
+    /// `{"fontFamily": "Inter", "flowchart": {"curve": "basis"}}`.
+    /// `mermaid_theme` takes precedence over a `theme` key here.
+    /// Default: None.
+    #[serde(default)]
+    pub mermaid_config: Option<String>,
+    /// Run each rendered page through an allowlist HTML sanitizer, removing
+    /// scripts, event handlers and unsafe URLs from raw HTML in markdown.
+    /// For sites with untrusted authors. See [`crate::sanitize`].
+    /// Default: false.
+    #[serde(default)]
+    pub sanitize_html: bool,
+    /// Tags kept by `sanitize_html` on top of the built-in allowlist
+    /// (`script` and `style` are always removed).
+    /// Default: empty.
+    #[serde(default)]
+    pub sanitize_allow_tags: Vec<String>,
+    /// Attributes kept on every tag by `sanitize_html` on top of the
+    /// built-in allowlist.
+    /// Default: empty.
+    #[serde(default)]
+    pub sanitize_allow_attributes: Vec<String>,
+    /// Markdown files at least this many bytes are streamed by the server:
+    /// the page shell is sent first and the body follows in chunks as it
+    /// renders, instead of waiting for the whole page. Set to 0 to always
+    /// render pages in full before sending.
+    /// Default: 1MB (1048576 bytes).
+    #[serde(default = "default_stream_render_threshold")]
+    pub stream_render_threshold: u64,
+    /// Maximum number of pages the server renders at once. Further page
+    /// requests wait for a slot; media and static files are served without
+    /// waiting. Default: 8.
+    #[serde(default = "default_server_render_concurrency")]
+    pub server_render_concurrency: usize,
+    /// Folder (relative to the root) of pages pre-rendered for the server.
+    /// A build into this folder renders pages as the server would; the server
+    /// then sends each one that is newer than its markdown and the settings,
+    /// and renders the rest live (see [`crate::server_cache`]).
+    /// Default: None (every page renders live).
+    #[serde(default)]
+    pub server_cache_dir: Option<PathBuf>,
+    /// Largest markdown file, in bytes, the server will load and render.
+    /// Bigger files get a "too large to preview" page instead of being read
+    /// into memory. Media files are streamed in ranges and aren't affected.
+    /// Set to 0 for no limit.
+    /// Default: 64MB (67108864 bytes).
+    #[serde(default = "default_max_render_bytes")]
+    pub max_render_bytes: u64,
+    /// strftime-style format for displaying dates via the `format_date`
+    /// template filter (e.g. "%Y-%m-%d").
+    /// Default: "%B %-d, %Y" ("January 5, 2024").
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
     /// Markers that flag a block as incomplete. A paragraph, heading, list
     /// item, or table cell whose first text matches `^(MARKER)\b` gets
     /// wrapped in `<span class="mbr-incomplete">…</span>`.
@@ -424,6 +1084,11 @@ pub struct Config {
     /// `--mark-incomplete` / `--no-mark-incomplete` force a value.
     #[serde(default)]
     pub mark_incomplete: Option<bool>,
+    /// Let readers tick task list checkboxes, remembering their state in the
+    /// browser's localStorage per page (nothing is written to disk).
+    /// `None` = mode default (on for server/GUI, off for static build).
+    #[serde(default)]
+    pub interactive_tasklists: Option<bool>,
     /// Enable the in-browser markdown editing endpoints (`/.mbr/raw`,
     /// `/.mbr/edit`) in server/GUI mode. Off by default. Intended for private
     /// use (e.g. GUI on localhost). CLI flag `--edit` also enables it.
@@ -439,6 +1104,12 @@ pub struct Config {
     /// loopback edits are allowed without a token (still CSRF-protected).
     #[serde(default)]
     pub edit_require_token_on_loopback: bool,
+    /// Let the GUI run with `host` set to `0.0.0.0`, which serves the
+    /// repository on every network interface. Without it the GUI refuses to
+    /// start; server mode only warns.
+    /// Default: false.
+    #[serde(default)]
+    pub allow_network_exposure: bool,
 }
 
 impl std::fmt::Display for IpArray {
@@ -453,13 +1124,8 @@ impl<'de> Deserialize<'de> for IpArray {
     where
         D: serde::Deserializer<'de>,
     {
-        let ip_str = String::deserialize(deserializer)?;
-        let ip: IpAddr = ip_str.parse().map_err(serde::de::Error::custom)?;
-
-        match ip {
-            IpAddr::V4(v4) => Ok(IpArray(v4.octets())),
-            IpAddr::V6(_) => Err(serde::de::Error::custom("IPv6 addresses are not supported")),
-        }
+        let host = String::deserialize(deserializer)?;
+        IpArray::resolve(&host).map_err(serde::de::Error::custom)
     }
 }
 
@@ -481,6 +1147,7 @@ impl Default for Config {
             port: DEFAULT_PORT,
             static_folder: "static".to_string(),
             markdown_extensions: vec!["md".to_string()],
+            renderers: HashMap::new(),
             theme: "default".to_string(),
             index_file: "index.md".to_string(),
             ignore_dirs: [
@@ -505,35 +1172,158 @@ impl Default for Config {
             .into_iter()
             .map(|x| x.to_string())
             .collect(),
+            respect_gitignore: true,
+            follow_symlinks: true,
             watcher_ignore_dirs: [".direnv", ".git", "result", "target", "build"]
                 .into_iter()
                 .map(|x| x.to_string())
                 .collect(),
             oembed_timeout_ms: DEFAULT_OEMBED_TIMEOUT_MS,
+            oembed_fallback: crate::oembed::OembedFallback::default(),
             oembed_cache_size: DEFAULT_OEMBED_CACHE_SIZE,
+            oembed_concurrency: default_oembed_concurrency(),
+            oembed_cache_ttl_secs: 0,
+            inbound_cache_size: default_inbound_cache_size(),
+            inbound_cache_ttl_secs: default_inbound_cache_ttl_secs(),
             template_folder: None,
+            data_folder: None,
             sort: default_sort_config(),
             build_concurrency: None, // Auto-detect based on CPU cores
+            progress_format: default_progress_format(),
             transcode: false,        // Disabled by default
+            video_encoder: None,     // Auto-detect by default
+            transcode_tmp_dir: None, // System temp directory
+            audio_transcode: None,   // Disabled by default
+            audio_transcode_bitrate_kbps: DEFAULT_AUDIO_TRANSCODE_BITRATE_KBPS,
             skip_link_checks: false, // Link checking enabled by default
-            link_tracking: true,     // Bidirectional link tracking enabled by default
+            skip_search_index: false,
+            pretty_json: false,
+            incremental: false,
+            build_only: None,
+            strict: false,
+            inline_assets_under_bytes: 0,
+            post_build_command: None,
+            git_metadata: false,
+            lint: crate::lint::LintOptions::default(),
+            link_tracking: true, // Bidirectional link tracking enabled by default
+            link_index_mode: crate::link_index::LinkIndexMode::PerPage,
             tag_sources: default_tag_sources(),
             relationship_tracking: true, // Typed relationship tracking enabled by default
             relationship_types: default_relationship_types(),
             build_tag_pages: true, // Tag pages enabled by default
+            clean_urls: true,
+            build_archive_pages: false,
+            feed_formats: Vec::new(),
+            section_feeds: Vec::new(),
+            page_size: 0,
+            listing_layout: default_listing_layout(),
+            listing_thumbnails: false,
+            image_formats: Vec::new(), // No image transcoding by default
+            mime_overrides: HashMap::new(),
+            access_log: None,
+            access_log_format: default_access_log_format(),
+            log_level: default_log_level(),
+            ffmpeg_log_level: default_ffmpeg_log_level(),
             sidebar_style: default_sidebar_style(),
             sidebar_max_items: default_sidebar_max_items(),
+            toc_min_level: default_toc_min_level(),
+            toc_max_level: default_toc_max_level(),
+            toc_numbered: false,
+            markdown_options: crate::markdown::MarkdownOptions::default(),
+            header_height: None,
+            smooth_scroll: false,
+            scroll_spy: default_scroll_spy(),
+            search_placeholder: default_search_placeholder(),
+            search_result_limit: default_search_result_limit(),
+            reading_order: None,
             title_prefix: String::new(),
             title_suffix: String::new(),
+            site_lang: default_site_lang(),
+            site_dir: default_site_dir(),
+            site_name: None,
+            favicon: None,
+            theme_color: None,
+            background_color: None,
+            robots: None,
+            robots_txt: None,
+            content_security_policy: None,
+            dedupe_title_h1: false,
+            base_url: None,
+            edit_url_template: None,
+            site_host: None,
+            external_links_new_tab: false,
+            lazy_images: default_lazy_images(),
+            media_max_width: None,
+            privacy_embeds: false,
+            mermaid_theme: None,
+            mermaid_config: None,
+            sanitize_html: false,
+            sanitize_allow_tags: Vec::new(),
+            sanitize_allow_attributes: Vec::new(),
+            stream_render_threshold: default_stream_render_threshold(),
+            server_render_concurrency: default_server_render_concurrency(),
+            server_cache_dir: None,
+            max_render_bytes: default_max_render_bytes(),
+            date_format: default_date_format(),
             incomplete_markers: default_incomplete_markers(),
             mark_incomplete: None,
+            interactive_tasklists: None,
             edit_enabled: false,
             edit_token_hash: None,
             edit_require_token_on_loopback: false,
+            allow_network_exposure: false,
+        }
+    }
+}
+
+/// Parses `build_only` into a root-relative path. Returns `None` for empty,
+/// absolute or `..` paths. A leading `./` is ignored.
+pub fn build_only_prefix(value: &str) -> Option<PathBuf> {
+    let path = Path::new(value.trim());
+    let path = path.strip_prefix(".").unwrap_or(path);
+    let normal = path
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    (normal && path.components().next().is_some()).then(|| path.to_path_buf())
+}
+
+/// Returns true for a plain non-negative CSS length (`4rem`, `56px`, `0`).
+///
+/// The value is written into an inline `<style>` block, so anything beyond a
+/// number and a unit is rejected.
+pub(crate) fn is_css_length(value: &str) -> bool {
+    if value == "0" {
+        return true;
+    }
+    ["px", "rem", "em", "vh", "%"].iter().any(|unit| {
+        value.strip_suffix(unit).is_some_and(|number| {
+            !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit() || c == '.')
+                && number.parse::<f64>().is_ok()
+        })
+    })
+}
+
+/// Returns true for a hex color (`#369`, `#336699`, `#336699cc`) or a named
+/// color (`white`, `rebeccapurple`).
+fn is_css_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
         }
+        None => !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic()),
     }
 }
 
+/// Returns true for a BCP 47-shaped language tag: alphanumeric subtags of
+/// 1-8 characters separated by `-`, starting with a letter (`en`, `pt-BR`).
+fn is_language_tag(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_alphabetic())
+        && value.split('-').all(|part| {
+            (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
 /// Returns true if the given path is the user's home directory.
 fn is_home_dir(path: &Path) -> bool {
     std::env::var_os("HOME")
@@ -541,16 +1331,46 @@ fn is_home_dir(path: &Path) -> bool {
         .is_some_and(|home| path == home)
 }
 
+/// Environment variable with extra root markers, comma-separated
+/// (e.g. `MBR_ROOT_MARKERS=.hg,notes.index`).
+///
+/// This can't be a config file setting: the config file is looked up in the
+/// root these markers are used to find.
+pub const ROOT_MARKERS_ENV: &str = "MBR_ROOT_MARKERS";
+
+/// Parses a comma-separated marker list, dropping blank entries.
+pub fn parse_root_markers(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|marker| !marker.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Search upward from the given path to find a repository root directory.
+///
+/// Uses the extra markers from [`ROOT_MARKERS_ENV`], if set. See
+/// [`find_root_dir_with_markers`] for the search order.
+pub fn find_root_dir(start_path: &Path) -> PathBuf {
+    let extra_markers = std::env::var(ROOT_MARKERS_ENV)
+        .map(|value| parse_root_markers(&value))
+        .unwrap_or_default();
+    find_root_dir_with_markers(start_path, &extra_markers)
+}
+
 /// Search upward from the given path to find a repository root directory.
 ///
-/// Searches for directory markers (`.mbr`, `.git`, `.zk`, `.obsidian`) then
-/// file markers (`book.toml`, `mkdocs.yml`, `docusaurus.config.js`) in ancestor
-/// directories. Falls back to the start path's directory if no markers found.
+/// Searches ancestor directories for, in order of precedence: a `.mbr`
+/// directory, then each of `extra_markers` (a file or directory), then the
+/// directory markers `.git`, `.zk`, `.obsidian`, then the file markers
+/// `book.toml`, `mkdocs.yml`, `docusaurus.config.js`. Falls back to the start
+/// path's directory if no markers found.
 ///
 /// Skips matches at `$HOME` to avoid using the entire home directory as root
 /// (e.g., when `~/.git` exists for a dotfiles repo).
-pub fn find_root_dir(start_path: &Path) -> PathBuf {
-    const DIR_MARKERS: &[&str] = &[".mbr", ".git", ".zk", ".obsidian"];
+pub fn find_root_dir_with_markers(start_path: &Path, extra_markers: &[String]) -> PathBuf {
+    const DIR_MARKERS: &[&str] = &[".git", ".zk", ".obsidian"];
     const FILE_MARKERS: &[&str] = &["book.toml", "mkdocs.yml", "docusaurus.config.js"];
 
     let dir = if start_path.is_dir() {
@@ -559,65 +1379,142 @@ pub fn find_root_dir(start_path: &Path) -> PathBuf {
         start_path.parent().unwrap_or(start_path)
     };
 
-    for marker in DIR_MARKERS {
-        if let Some(root) = dir
-            .ancestors()
-            .find(|a| a.join(marker).is_dir())
-            .map(|p| p.to_path_buf())
-        {
-            if is_home_dir(&root) {
-                break;
-            }
-            return root;
-        }
-    }
-
-    for marker in FILE_MARKERS {
-        if let Some(root) = dir
-            .ancestors()
-            .find(|a| a.join(marker).is_file())
-            .map(|p| p.to_path_buf())
-        {
-            if is_home_dir(&root) {
-                break;
+    let extra_markers: Vec<&str> = extra_markers.iter().map(String::as_str).collect();
+    type IsMarker = fn(&Path) -> bool;
+    let marker_groups: [(&[&str], IsMarker); 4] = [
+        (&[".mbr"], Path::is_dir),
+        (&extra_markers, Path::exists),
+        (DIR_MARKERS, Path::is_dir),
+        (FILE_MARKERS, Path::is_file),
+    ];
+
+    for (markers, is_marker) in marker_groups {
+        for marker in markers {
+            if let Some(root) = dir
+                .ancestors()
+                .find(|a| is_marker(&a.join(marker)))
+                .map(|p| p.to_path_buf())
+            {
+                if is_home_dir(&root) {
+                    break;
+                }
+                return root;
             }
-            return root;
         }
     }
 
     dir.to_path_buf()
 }
 
+/// Checks `host` and `port` before the full extract, so a bad value is
+/// reported as an error naming the setting rather than a generic parse
+/// failure. Values of the wrong type are left for the extract to report.
+fn check_listen_address(figment: &Figment) -> Result<(), ConfigError> {
+    if let Ok(host) = figment.extract_inner::<String>("host") {
+        IpArray::resolve(&host)?;
+    }
+    if let Ok(port) = figment.extract_inner::<PortSetting>("port") {
+        port.resolve()?;
+    }
+    Ok(())
+}
+
 impl Config {
+    /// The defaults for a site rooted at `root`, ignoring any config file.
+    pub fn default_for(root: &Path) -> Self {
+        Self {
+            root_dir: root.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
     pub fn read(search_config_from: &Path) -> Result<Self, crate::MbrError> {
         let default_config = Config::default();
         let root_dir = find_root_dir(search_config_from);
-        let mut config: Config = Figment::new()
+        let figment = Figment::new()
             .merge(Serialized::defaults(default_config))
             .merge(Env::prefixed("MBR_"))
-            .merge(Toml::file(root_dir.join(".mbr/config.toml")))
+            .merge(Toml::file(root_dir.join(".mbr/config.toml")));
+        check_listen_address(&figment)?;
+        let mut config: Config = figment
             .extract()
             .map_err(|e| ConfigError::ParseFailed(Box::new(e)))?;
         tracing::debug!("Loaded config: {:?}", &config);
         config.root_dir = root_dir;
         config.validate()?;
+        config.register_renderer_extensions();
         Ok(config)
     }
 
+    /// Adds each `renderers` extension to `markdown_extensions`, so the repo
+    /// scan, URL building and link resolution treat those files as pages.
+    pub fn register_renderer_extensions(&mut self) {
+        for extension in self.renderers.keys() {
+            let extension = crate::content_type::normalize_extension(extension);
+            if !self.markdown_extensions.contains(&extension) {
+                self.markdown_extensions.push(extension);
+            }
+        }
+    }
+
+    /// Table of contents filtering and numbering for rendered pages.
+    pub fn toc_options(&self) -> crate::markdown::TocOptions {
+        crate::markdown::TocOptions {
+            min_level: self.toc_min_level,
+            max_level: self.toc_max_level,
+            numbered: self.toc_numbered,
+        }
+    }
+
+    /// Options for `mermaid.initialize()` from `mermaid_theme` and
+    /// `mermaid_config`, as a JSON object safe to inline in a `<script>`.
+    /// `{}` when neither is set, so the theme follows light/dark mode.
+    pub fn mermaid_init_json(&self) -> String {
+        let mut options = self
+            .mermaid_config
+            .as_deref()
+            .and_then(|config| serde_json::from_str::<serde_json::Map<_, _>>(config).ok())
+            .unwrap_or_default();
+        if let Some(theme) = &self.mermaid_theme {
+            options.insert("theme".to_string(), serde_json::json!(theme));
+        }
+        crate::page_assets::escape_inline(&serde_json::Value::Object(options).to_string())
+    }
+
     /// Validates the configuration values.
     ///
     /// Checks that numeric configuration options are within valid bounds:
-    /// - `port`: Must be 1-65535 (port 0 means "auto-assign", which isn't useful for display)
     /// - `sidebar_max_items`: Must be > 0
+    /// - `toc_min_level`/`toc_max_level`: Must satisfy 1 <= min <= max <= 6
+    /// - `header_height`: If set, must be a CSS length such as `4rem` or `56px`
+    /// - `media_max_width`: If set, must be a CSS length
+    /// - `search_result_limit`: Must be > 0
+    /// - `site_lang`: Must look like a language tag (letters, digits, `-`)
+    /// - `site_dir`: Must be "ltr", "rtl", or "auto"
+    /// - `post_build_command`: If set, must not be blank
+    /// - `site_host`: If set, must be a bare host (optionally with a port)
     /// - `build_concurrency`: If set, must be > 0
+    /// - `progress_format`: Must be "auto", "tty" or "plain"
+    /// - `oembed_concurrency`: Must be > 0
+    /// - `server_render_concurrency`: Must be > 0
+    /// - `audio_transcode_bitrate_kbps`: Must be > 0
+    /// - `video_encoder`: If set, must be a bare encoder name (letters, digits, `_`)
+    /// - `mime_overrides`: Keys must be non-empty extensions, values `type/subtype`
+    /// - `access_log_format`: Must be "human" or "combined"
+    /// - `log_level`, `ffmpeg_log_level`: Must be a known level name
+    /// - `renderers`: Keys must be non-empty extensions
+    /// - `build_only`: If set, must be a relative path inside the root
+    /// - `section_feeds`: Entries must be relative paths inside the root
+    /// - `mermaid_theme`: If set, must be one of mermaid's built-in themes
+    /// - `mermaid_config`: If set, must be a JSON object
+    /// - `sanitize_allow_tags`, `sanitize_allow_attributes`: Entries must be
+    ///   tag/attribute names; `script` and `style` can't be allowed
+    ///
+    /// An `edit_url_template` without `{path}` is allowed but logs a warning,
+    /// since every page would link to the same URL.
     ///
     /// Note: `oembed_cache_size` of 0 is valid (disables caching).
     pub fn validate(&self) -> Result<(), ConfigError> {
-        // Port 0 means "let OS pick a port" which isn't useful for a server URL
-        if self.port == 0 {
-            return Err(ConfigError::InvalidPort { port: self.port });
-        }
-
         // sidebar_max_items of 0 would show no items
         if self.sidebar_max_items == 0 {
             return Err(ConfigError::InvalidSidebarMaxItems {
@@ -625,64 +1522,376 @@ impl Config {
             });
         }
 
-        // build_concurrency of 0 would mean no parallelism (None means auto-detect)
-        if matches!(self.build_concurrency, Some(0)) {
-            return Err(ConfigError::InvalidBuildConcurrency { value: 0 });
+        if !(1..=6).contains(&self.toc_min_level)
+            || !(self.toc_min_level..=6).contains(&self.toc_max_level)
+        {
+            return Err(ConfigError::InvalidTocLevels {
+                min: self.toc_min_level,
+                max: self.toc_max_level,
+            });
         }
 
-        // Refuse to expose an unauthenticated writable endpoint to the network:
-        // editing on a non-loopback host requires a token hash.
-        if self.edit_enabled
-            && !std::net::Ipv4Addr::from(self.host.0).is_loopback()
-            && self.edit_token_hash.is_none()
+        if let Some(height) = &self.header_height
+            && !is_css_length(height)
         {
-            return Err(ConfigError::EditingRequiresToken);
+            return Err(ConfigError::InvalidHeaderHeight {
+                value: height.clone(),
+            });
         }
 
-        Ok(())
-    }
-}
+        if let Some(width) = &self.media_max_width
+            && !is_css_length(width)
+        {
+            return Err(ConfigError::InvalidMediaMaxWidth {
+                value: width.clone(),
+            });
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        if self.search_result_limit == 0 {
+            return Err(ConfigError::InvalidSearchResultLimit {
+                value: self.search_result_limit,
+            });
+        }
 
-    #[test]
-    fn test_title_case() {
-        assert_eq!(title_case("tags"), "Tag");
-        assert_eq!(title_case("performers"), "Performer");
-        assert_eq!(title_case("category"), "Category");
-        assert_eq!(title_case("Tag"), "Tag");
-        assert_eq!(title_case("s"), "S");
-        assert_eq!(title_case(""), "");
-    }
+        if !is_language_tag(&self.site_lang) {
+            return Err(ConfigError::InvalidSiteLang {
+                value: self.site_lang.clone(),
+            });
+        }
 
-    #[test]
-    fn test_tag_source_singular_label_explicit() {
-        let source = TagSource {
-            field: "taxonomy.performers".to_string(),
-            label: Some("Performer".to_string()),
-            label_plural: None,
-        };
-        assert_eq!(source.singular_label(), "Performer");
-    }
+        if !matches!(self.site_dir.as_str(), "ltr" | "rtl" | "auto") {
+            return Err(ConfigError::InvalidSiteDir {
+                value: self.site_dir.clone(),
+            });
+        }
 
-    #[test]
-    fn test_tag_source_singular_label_derived() {
-        let source = TagSource {
-            field: "tags".to_string(),
-            label: None,
-            label_plural: None,
-        };
-        assert_eq!(source.singular_label(), "Tag");
-    }
+        if crate::build_progress::ProgressFormat::from_name(&self.progress_format).is_none() {
+            return Err(ConfigError::InvalidProgressFormat {
+                value: self.progress_format.clone(),
+            });
+        }
 
-    #[test]
-    fn test_tag_source_singular_label_derived_nested() {
-        let source = TagSource {
+        if crate::access_log::AccessLogFormat::from_name(&self.access_log_format).is_none() {
+            return Err(ConfigError::InvalidAccessLogFormat {
+                value: self.access_log_format.clone(),
+            });
+        }
+
+        if !LOG_LEVELS.contains(&self.log_level.as_str()) {
+            return Err(ConfigError::InvalidLogLevel {
+                value: self.log_level.clone(),
+            });
+        }
+
+        if !FFMPEG_LOG_LEVELS.contains(&self.ffmpeg_log_level.as_str()) {
+            return Err(ConfigError::InvalidFfmpegLogLevel {
+                value: self.ffmpeg_log_level.clone(),
+            });
+        }
+
+        if !matches!(self.listing_layout.as_str(), "list" | "cards" | "grid") {
+            return Err(ConfigError::InvalidListingLayout {
+                value: self.listing_layout.clone(),
+            });
+        }
+
+        if self
+            .post_build_command
+            .as_ref()
+            .is_some_and(|command| command.trim().is_empty())
+        {
+            return Err(ConfigError::EmptyPostBuildCommand);
+        }
+
+        if let Some(source) = &self.reading_order
+            && (source.trim().is_empty() || Path::new(source).is_absolute())
+        {
+            return Err(ConfigError::InvalidReadingOrder {
+                value: source.clone(),
+            });
+        }
+
+        if let Some(favicon) = &self.favicon
+            && (Path::new(favicon).is_absolute() || crate::favicon::favicon_mime(favicon).is_none())
+        {
+            return Err(ConfigError::InvalidFavicon {
+                value: favicon.clone(),
+            });
+        }
+
+        for (field, color) in [
+            ("theme_color", &self.theme_color),
+            ("background_color", &self.background_color),
+        ] {
+            if let Some(value) = color
+                && !is_css_color(value)
+            {
+                return Err(ConfigError::InvalidColor {
+                    field,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        if let Some(theme) = &self.mermaid_theme
+            && !MERMAID_THEMES.contains(&theme.as_str())
+        {
+            return Err(ConfigError::InvalidMermaidTheme {
+                value: theme.clone(),
+            });
+        }
+
+        if let Some(config) = &self.mermaid_config {
+            match serde_json::from_str::<serde_json::Value>(config) {
+                Ok(serde_json::Value::Object(_)) => {}
+                Ok(_) => {
+                    return Err(ConfigError::InvalidMermaidConfig {
+                        reason: "expected a JSON object".to_string(),
+                    });
+                }
+                Err(e) => {
+                    return Err(ConfigError::InvalidMermaidConfig {
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        let bad_tag = self.sanitize_allow_tags.iter().find(|tag| {
+            !crate::sanitize::is_valid_name(tag)
+                || crate::sanitize::FORBIDDEN_TAGS.contains(&tag.to_ascii_lowercase().as_str())
+        });
+        if let Some(tag) = bad_tag {
+            return Err(ConfigError::InvalidSanitizeAllowlist {
+                field: "sanitize_allow_tags",
+                value: tag.clone(),
+            });
+        }
+        if let Some(attribute) = self
+            .sanitize_allow_attributes
+            .iter()
+            .find(|attribute| !crate::sanitize::is_valid_name(attribute))
+        {
+            return Err(ConfigError::InvalidSanitizeAllowlist {
+                field: "sanitize_allow_attributes",
+                value: attribute.clone(),
+            });
+        }
+
+        if let Some(policy) = &self.content_security_policy
+            && !crate::csp::is_valid_policy(policy)
+        {
+            return Err(ConfigError::InvalidContentSecurityPolicy {
+                value: policy.clone(),
+            });
+        }
+
+        if let Some(folder) = &self.data_folder
+            && (folder.trim().is_empty() || Path::new(folder).is_absolute())
+        {
+            return Err(ConfigError::InvalidDataFolder {
+                value: folder.clone(),
+            });
+        }
+
+        if let Some(base_url) = &self.base_url
+            && !url::Url::parse(base_url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+        {
+            return Err(ConfigError::InvalidBaseUrl {
+                value: base_url.clone(),
+            });
+        }
+
+        if let Some(host) = &self.site_host
+            && (host.contains("://")
+                || !url::Url::parse(&format!("http://{host}"))
+                    .is_ok_and(|url| url.has_host() && url.path() == "/" && !host.ends_with('/')))
+        {
+            return Err(ConfigError::InvalidSiteHost {
+                value: host.clone(),
+            });
+        }
+
+        if !crate::dates::is_valid_format(&self.date_format) {
+            return Err(ConfigError::InvalidDateFormat {
+                format: self.date_format.clone(),
+            });
+        }
+
+        // build_concurrency of 0 would mean no parallelism (None means auto-detect)
+        if matches!(self.build_concurrency, Some(0)) {
+            return Err(ConfigError::InvalidBuildConcurrency { value: 0 });
+        }
+
+        // oembed_concurrency of 0 would never fetch anything
+        if self.oembed_concurrency == 0 {
+            return Err(ConfigError::InvalidOembedConcurrency { value: 0 });
+        }
+
+        // server_render_concurrency of 0 would never render a page
+        if self.server_render_concurrency == 0 {
+            return Err(ConfigError::InvalidServerRenderConcurrency { value: 0 });
+        }
+
+        if let Some(format) = self.image_formats.iter().find(|f| {
+            !crate::media::ImageVariantFormat::from_name(f)
+                .is_some_and(|format| format.encoder_available())
+        }) {
+            return Err(ConfigError::InvalidImageFormat {
+                format: format.clone(),
+            });
+        }
+
+        if let Some(format) = self
+            .feed_formats
+            .iter()
+            .find(|f| crate::feed::FeedFormat::from_name(f).is_none())
+        {
+            return Err(ConfigError::InvalidFeedFormat {
+                format: format.clone(),
+            });
+        }
+
+        if let Some(section) = self
+            .section_feeds
+            .iter()
+            .find(|s| crate::feed::section_url(s).is_none())
+        {
+            return Err(ConfigError::InvalidSectionFeed {
+                value: section.clone(),
+            });
+        }
+
+        if let Some(codec) = &self.audio_transcode
+            && crate::audio::AudioTranscodeFormat::from_name(codec).is_none()
+        {
+            return Err(ConfigError::InvalidAudioTranscode {
+                codec: codec.clone(),
+            });
+        }
+
+        if self.audio_transcode_bitrate_kbps == 0 {
+            return Err(ConfigError::InvalidAudioTranscodeBitrate {
+                value: self.audio_transcode_bitrate_kbps,
+            });
+        }
+
+        if let Some(name) = &self.video_encoder
+            && (name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            return Err(ConfigError::InvalidVideoEncoder { name: name.clone() });
+        }
+
+        if let Some((extension, mime)) = self.mime_overrides.iter().find(|(ext, mime)| {
+            crate::content_type::normalize_extension(ext).is_empty()
+                || !crate::content_type::is_valid_mime(mime.trim())
+        }) {
+            return Err(ConfigError::InvalidMimeOverride {
+                extension: extension.clone(),
+                mime: mime.clone(),
+            });
+        }
+
+        if let Some(extension) = self
+            .renderers
+            .keys()
+            .find(|ext| crate::content_type::normalize_extension(ext).is_empty())
+        {
+            return Err(ConfigError::InvalidRendererExtension {
+                extension: extension.clone(),
+            });
+        }
+
+        if let Some(prefix) = &self.build_only
+            && build_only_prefix(prefix).is_none()
+        {
+            return Err(ConfigError::InvalidBuildOnly {
+                value: prefix.clone(),
+            });
+        }
+
+        // Refuse to expose an unauthenticated writable endpoint to the network:
+        // editing on a non-loopback host requires a token hash.
+        if self.edit_enabled
+            && !std::net::Ipv4Addr::from(self.host.0).is_loopback()
+            && self.edit_token_hash.is_none()
+        {
+            return Err(ConfigError::EditingRequiresToken);
+        }
+
+        if let Some(template) = &self.edit_url_template
+            && !template.contains(crate::page_context::EDIT_URL_PATH)
+        {
+            tracing::warn!(
+                "edit_url_template {template:?} has no {} placeholder; every page will link to the same URL",
+                crate::page_context::EDIT_URL_PATH
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the GUI may bind to `host`: binding to all interfaces
+    /// needs `allow_network_exposure`, since the window makes it easy to
+    /// miss that the repository is also served to the network.
+    pub fn check_gui_exposure(&self) -> Result<(), ConfigError> {
+        if self.host.is_unspecified() && !self.allow_network_exposure {
+            return Err(ConfigError::NetworkExposureNotAllowed {
+                host: self.host.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::RendererKind;
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(title_case("tags"), "Tag");
+        assert_eq!(title_case("performers"), "Performer");
+        assert_eq!(title_case("category"), "Category");
+        assert_eq!(title_case("Tag"), "Tag");
+        assert_eq!(title_case("s"), "S");
+        assert_eq!(title_case(""), "");
+    }
+
+    #[test]
+    fn test_tag_source_singular_label_explicit() {
+        let source = TagSource {
+            field: "taxonomy.performers".to_string(),
+            label: Some("Performer".to_string()),
+            label_plural: None,
+            fields: vec![],
+            data: None,
+        };
+        assert_eq!(source.singular_label(), "Performer");
+    }
+
+    #[test]
+    fn test_tag_source_singular_label_derived() {
+        let source = TagSource {
+            field: "tags".to_string(),
+            label: None,
+            label_plural: None,
+            fields: vec![],
+            data: None,
+        };
+        assert_eq!(source.singular_label(), "Tag");
+    }
+
+    #[test]
+    fn test_tag_source_singular_label_derived_nested() {
+        let source = TagSource {
             field: "taxonomy.performers".to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         };
         assert_eq!(source.singular_label(), "Performer");
     }
@@ -693,6 +1902,8 @@ mod tests {
             field: "taxonomy.performers".to_string(),
             label: None,
             label_plural: Some("Performers".to_string()),
+            fields: vec![],
+            data: None,
         };
         assert_eq!(source.plural_label(), "Performers");
     }
@@ -703,16 +1914,35 @@ mod tests {
             field: "tags".to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         };
         assert_eq!(source.plural_label(), "Tags");
     }
 
+    #[test]
+    fn test_tag_source_all_fields() {
+        let json = r#"{"field": "tags", "fields": ["keywords", "topics"]}"#;
+        let source: TagSource = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            source.all_fields().collect::<Vec<_>>(),
+            vec!["tags", "keywords", "topics"]
+        );
+        assert_eq!(source.url_source(), "tags");
+        assert_eq!(
+            tag_sources_to_set(&[source]),
+            std::collections::HashSet::from(["tags".to_string()])
+        );
+    }
+
     #[test]
     fn test_tag_source_url_source() {
         let source = TagSource {
             field: "Tags".to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         };
         assert_eq!(source.url_source(), "tags");
 
@@ -720,6 +1950,8 @@ mod tests {
             field: "taxonomy.Performers".to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         };
         assert_eq!(source.url_source(), "taxonomy.performers");
     }
@@ -805,6 +2037,8 @@ mod tests {
             field: "taxonomy.tags".to_string(),
             label: Some("Tag".to_string()),
             label_plural: Some("Tags".to_string()),
+            fields: vec![],
+            data: None,
         };
 
         let json = serde_json::to_string(&source).unwrap();
@@ -819,6 +2053,7 @@ mod tests {
         assert_eq!(source.field, "tags");
         assert!(source.label.is_none());
         assert!(source.label_plural.is_none());
+        assert!(source.fields.is_empty());
     }
 
     // ==================== Config Validation Tests ====================
@@ -830,15 +2065,68 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_port_zero_fails() {
-        let config = Config {
-            port: 0,
-            ..Default::default()
-        };
-        let result = config.validate();
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(matches!(err, ConfigError::InvalidPort { port: 0 }));
+    fn test_port_setting_resolve() {
+        assert_eq!(PortSetting::Number(8080).resolve().unwrap(), 8080);
+        assert_eq!(
+            PortSetting::Text("8080".to_string()).resolve().unwrap(),
+            8080
+        );
+        assert_eq!(PortSetting::Text("auto".to_string()).resolve().unwrap(), 0);
+        assert_eq!(
+            PortSetting::Text(" AUTO ".to_string()).resolve().unwrap(),
+            0
+        );
+
+        for bad in [
+            PortSetting::Number(0),
+            PortSetting::Number(-1),
+            PortSetting::Number(65536),
+            PortSetting::Text("0".to_string()),
+            PortSetting::Text("http".to_string()),
+        ] {
+            assert!(
+                matches!(bad.resolve(), Err(ConfigError::InvalidPort { .. })),
+                "{bad:?} should be rejected"
+            );
+        }
+        assert_eq!(parse_port_arg("auto"), Ok(0));
+        assert!(parse_port_arg("0").is_err());
+    }
+
+    #[test]
+    fn test_ip_array_resolve() {
+        assert_eq!(IpArray::resolve("0.0.0.0").unwrap(), IpArray([0, 0, 0, 0]));
+        assert_eq!(
+            IpArray::resolve(" 192.168.1.10 ").unwrap(),
+            IpArray([192, 168, 1, 10])
+        );
+        assert!(IpArray::resolve("localhost").unwrap().0[0] == 127);
+        for bad in ["", "::1", "999.1.1.1", "no-such-host.invalid"] {
+            assert!(
+                matches!(IpArray::resolve(bad), Err(ConfigError::InvalidHost { .. })),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_reports_bad_listen_address() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".mbr")).unwrap();
+        let config_file = repo.path().join(".mbr/config.toml");
+
+        std::fs::write(&config_file, "port = 0\n").unwrap();
+        let err = Config::read(repo.path()).unwrap_err();
+        assert!(err.to_string().contains("port"), "{err}");
+
+        std::fs::write(&config_file, "host = \"300.0.0.1\"\n").unwrap();
+        let err = Config::read(repo.path()).unwrap_err();
+        assert!(err.to_string().contains("300.0.0.1"), "{err}");
+
+        std::fs::write(&config_file, "host = \"localhost\"\nport = \"auto\"\n").unwrap();
+        let config = Config::read(repo.path()).unwrap();
+        assert_eq!(config.port, 0);
+        assert_eq!(config.host.0[0], 127);
     }
 
     #[test]
@@ -922,6 +2210,42 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_validate_oembed_concurrency() {
+        let config = Config {
+            oembed_concurrency: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidOembedConcurrency { value: 0 })
+        ));
+
+        let config = Config {
+            oembed_concurrency: 1,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_server_render_concurrency() {
+        let config = Config {
+            server_render_concurrency: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidServerRenderConcurrency { value: 0 })
+        ));
+
+        let config = Config {
+            server_render_concurrency: 1,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_build_concurrency_none_passes() {
         let config = Config {
@@ -953,64 +2277,800 @@ mod tests {
     }
 
     #[test]
-    fn test_default_title_prefix_empty() {
-        let config = Config::default();
-        assert_eq!(config.title_prefix, "");
-    }
-
-    #[test]
-    fn test_default_title_suffix_empty() {
-        let config = Config::default();
-        assert_eq!(config.title_suffix, "");
-    }
+    fn test_validate_image_formats() {
+        let config = Config {
+            image_formats: vec!["webp".to_string(), "WebP".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
 
-    #[test]
-    fn test_validate_editing_non_loopback_without_token_fails() {
+        // No AVIF encoder is compiled in
         let config = Config {
-            edit_enabled: true,
-            host: IpArray([0, 0, 0, 0]),
-            edit_token_hash: None,
+            image_formats: vec!["webp".to_string(), "AVIF".to_string()],
             ..Default::default()
         };
         assert!(matches!(
             config.validate(),
-            Err(ConfigError::EditingRequiresToken)
+            Err(ConfigError::InvalidImageFormat { format }) if format == "AVIF"
         ));
-    }
 
-    #[test]
-    fn test_validate_editing_non_loopback_with_token_passes() {
         let config = Config {
-            edit_enabled: true,
-            host: IpArray([0, 0, 0, 0]),
-            edit_token_hash: Some("$argon2id$fake".to_string()),
+            image_formats: vec!["jxl".to_string()],
             ..Default::default()
         };
-        assert!(config.validate().is_ok());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidImageFormat { format }) if format == "jxl"
+        ));
     }
 
     #[test]
-    fn test_validate_editing_loopback_without_token_passes() {
+    fn test_validate_feed_formats() {
         let config = Config {
-            edit_enabled: true,
-            host: IpArray([127, 0, 0, 1]),
-            edit_token_hash: None,
+            feed_formats: vec!["JSON".to_string()],
             ..Default::default()
         };
         assert!(config.validate().is_ok());
-    }
 
-    #[test]
-    fn test_validate_oembed_cache_size_zero_is_valid() {
-        // Zero means disabled, which is valid
         let config = Config {
-            oembed_cache_size: 0,
+            feed_formats: vec!["rss".to_string()],
             ..Default::default()
         };
-        assert!(config.validate().is_ok());
-    }
-
-    // ==================== find_root_dir Edge Case Tests ====================
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidFeedFormat { format }) if format == "rss"
+        ));
+
+        let config = Config {
+            section_feeds: vec!["../outside".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidSectionFeed { value }) if value == "../outside"
+        ));
+    }
+
+    #[test]
+    fn test_validate_audio_transcode() {
+        let config = Config {
+            audio_transcode: Some("Opus".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            audio_transcode: Some("mp3".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidAudioTranscode { codec }) if codec == "mp3"
+        ));
+
+        let config = Config {
+            audio_transcode: Some("aac".to_string()),
+            audio_transcode_bitrate_kbps: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidAudioTranscodeBitrate { value: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_validate_mime_overrides() {
+        let config = Config {
+            mime_overrides: HashMap::from([(".opus".to_string(), "audio/ogg".to_string())]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for (extension, mime) in [("opus", "ogg"), ("", "audio/ogg"), ("opus", "audio/o\ngg")] {
+            let config = Config {
+                mime_overrides: HashMap::from([(extension.to_string(), mime.to_string())]),
+                ..Default::default()
+            };
+            assert!(
+                matches!(
+                    config.validate(),
+                    Err(ConfigError::InvalidMimeOverride { .. })
+                ),
+                "{extension:?} = {mime:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_renderers() {
+        let config = Config {
+            renderers: HashMap::from([(".txt".to_string(), RendererKind::Plaintext)]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            renderers: HashMap::from([(" . ".to_string(), RendererKind::Plaintext)]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidRendererExtension { .. })
+        ));
+    }
+
+    #[test]
+    fn test_register_renderer_extensions() {
+        let mut config = Config {
+            markdown_extensions: vec!["md".to_string()],
+            renderers: HashMap::from([
+                (".TXT".to_string(), RendererKind::Plaintext),
+                ("md".to_string(), RendererKind::Markdown),
+            ]),
+            ..Default::default()
+        };
+        config.register_renderer_extensions();
+        assert_eq!(config.markdown_extensions, vec!["md", "txt"]);
+    }
+
+    #[test]
+    fn test_validate_video_encoder() {
+        for name in ["libx264", "h264_nvenc", "software"] {
+            let config = Config {
+                video_encoder: Some(name.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{name} should be accepted");
+        }
+
+        for name in ["", "libx264 -preset slow"] {
+            let config = Config {
+                video_encoder: Some(name.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidVideoEncoder { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_reading_order() {
+        for source in ["nav", "SUMMARY.md", "docs/order.md"] {
+            let config = Config {
+                reading_order: Some(source.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{source} should be accepted");
+        }
+
+        for source in ["", "  ", "/etc/order.md"] {
+            let config = Config {
+                reading_order: Some(source.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidReadingOrder { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_toc_levels() {
+        for (min, max) in [(1, 6), (2, 3), (4, 4)] {
+            let config = Config {
+                toc_min_level: min,
+                toc_max_level: max,
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{min}..{max} should be accepted");
+        }
+
+        for (min, max) in [(0, 6), (3, 2), (2, 7)] {
+            let config = Config {
+                toc_min_level: min,
+                toc_max_level: max,
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidTocLevels { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_toc_options_from_config() {
+        let config = Config {
+            toc_min_level: 2,
+            toc_numbered: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.toc_options(),
+            crate::markdown::TocOptions {
+                min_level: 2,
+                max_level: 6,
+                numbered: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_markdown_options_partial_table() {
+        let config: Config = Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::string(
+                "[markdown_options]\ntables = false\nsmart_punctuation = false\nheading_ids = \"preserve-unicode\"\n",
+            ))
+            .extract()
+            .unwrap();
+        assert!(!config.markdown_options.tables);
+        assert!(!config.markdown_options.smart_punctuation);
+        assert!(config.markdown_options.footnotes);
+        assert_eq!(
+            config.markdown_options.heading_ids,
+            crate::slug::SlugStyle::PreserveUnicode
+        );
+    }
+
+    #[test]
+    fn test_validate_site_host() {
+        for host in ["notes.example.com", "localhost:5200"] {
+            let config = Config {
+                site_host: Some(host.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{host} should be valid");
+        }
+
+        for host in [
+            "https://notes.example.com",
+            "example.com/docs",
+            "",
+            "bad host",
+        ] {
+            let config = Config {
+                site_host: Some(host.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                matches!(config.validate(), Err(ConfigError::InvalidSiteHost { .. })),
+                "{host:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_base_url() {
+        let config = Config {
+            base_url: Some("https://notes.example.com/".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for base_url in ["notes.example.com", "ftp://example.com/", "/docs/"] {
+            let config = Config {
+                base_url: Some(base_url.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidBaseUrl { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_media_max_width() {
+        let config = Config {
+            media_max_width: Some("100%".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            media_max_width: Some("40rem; color: red".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidMediaMaxWidth { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_header_height() {
+        for height in ["4rem", "56px", "3.5em", "10vh", "0"] {
+            let config = Config {
+                header_height: Some(height.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{height} should be valid");
+        }
+
+        for height in ["", "4", "rem", "-2px", "4rem; color: red", "calc(2em)"] {
+            let config = Config {
+                header_height: Some(height.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidHeaderHeight { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_site_lang_and_dir() {
+        for (lang, dir) in [
+            ("en", "ltr"),
+            ("ar", "rtl"),
+            ("pt-BR", "auto"),
+            ("zh-Hant-TW", "ltr"),
+        ] {
+            let config = Config {
+                site_lang: lang.to_string(),
+                site_dir: dir.to_string(),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{lang}/{dir} should be valid");
+        }
+
+        for lang in ["", "-en", "en-", "en_US", "en\" onload=\"x", "1en"] {
+            let config = Config {
+                site_lang: lang.to_string(),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidSiteLang { .. })
+            ));
+        }
+
+        let config = Config {
+            site_dir: "RTL".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidSiteDir { .. })
+        ));
+    }
+
+    #[test]
+    fn test_build_only_prefix() {
+        assert_eq!(build_only_prefix("docs/"), Some(PathBuf::from("docs")));
+        assert_eq!(
+            build_only_prefix("./docs/api"),
+            Some(PathBuf::from("docs/api"))
+        );
+        assert_eq!(build_only_prefix(""), None);
+        assert_eq!(build_only_prefix("."), None);
+        assert_eq!(build_only_prefix("/docs"), None);
+        assert_eq!(build_only_prefix("docs/../secret"), None);
+
+        let config = Config {
+            build_only: Some("../elsewhere".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidBuildOnly { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_log_levels() {
+        assert!(Config::default().validate().is_ok());
+
+        let config = Config {
+            log_level: "debug".to_string(),
+            ffmpeg_log_level: "warning".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            log_level: "verbose".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidLogLevel { .. })
+        ));
+
+        let config = Config {
+            ffmpeg_log_level: "warn".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidFfmpegLogLevel { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_mermaid_settings() {
+        let config = Config {
+            mermaid_theme: Some("forest".to_string()),
+            mermaid_config: Some(r#"{"fontFamily": "Inter"}"#.to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            mermaid_theme: Some("solarized".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidMermaidTheme { .. })
+        ));
+
+        for bad in ["{fontFamily: 'Inter'}", "[1, 2]", ""] {
+            let config = Config {
+                mermaid_config: Some(bad.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                matches!(
+                    config.validate(),
+                    Err(ConfigError::InvalidMermaidConfig { .. })
+                ),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_sanitize_allowlist() {
+        let config = Config {
+            sanitize_allow_tags: vec!["marquee".to_string()],
+            sanitize_allow_attributes: vec!["behavior".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for tags in [vec!["Script".to_string()], vec!["<b>".to_string()]] {
+            let config = Config {
+                sanitize_allow_tags: tags,
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidSanitizeAllowlist {
+                    field: "sanitize_allow_tags",
+                    ..
+                })
+            ));
+        }
+        let config = Config {
+            sanitize_allow_attributes: vec![String::new()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidSanitizeAllowlist {
+                field: "sanitize_allow_attributes",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mermaid_init_json() {
+        assert_eq!(Config::default().mermaid_init_json(), "{}");
+
+        let config = Config {
+            mermaid_theme: Some("forest".to_string()),
+            mermaid_config: Some(r#"{"theme": "dark", "themeCSS": "</script><b>"}"#.to_string()),
+            ..Default::default()
+        };
+        let json = config.mermaid_init_json();
+        assert!(!json.contains("</script>"), "{json}");
+        let options: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(options["theme"], "forest");
+        assert_eq!(options["themeCSS"], "</script><b>");
+    }
+
+    #[test]
+    fn test_validate_access_log_format() {
+        for format in ["human", "combined"] {
+            let config = Config {
+                access_log_format: format.to_string(),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{format} should be valid");
+        }
+
+        let config = Config {
+            access_log_format: "json".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidAccessLogFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_progress_format() {
+        for format in ["auto", "tty", "plain"] {
+            let config = Config {
+                progress_format: format.to_string(),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{format} should be valid");
+        }
+
+        let config = Config {
+            progress_format: "fancy".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidProgressFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_listing_layout() {
+        for layout in ["list", "cards", "grid"] {
+            let config = Config {
+                listing_layout: layout.to_string(),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{layout} should be valid");
+        }
+
+        let config = Config {
+            listing_layout: "masonry".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidListingLayout { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_post_build_command() {
+        let config = Config {
+            post_build_command: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::EmptyPostBuildCommand)
+        ));
+
+        let config = Config {
+            post_build_command: Some("./deploy.sh".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_search_result_limit() {
+        let config = Config {
+            search_result_limit: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidSearchResultLimit { value: 0 })
+        ));
+
+        let config = Config {
+            search_result_limit: 50,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_data_folder() {
+        let config = Config {
+            data_folder: Some("data".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for folder in ["", "/srv/data"] {
+            let config = Config {
+                data_folder: Some(folder.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidDataFolder { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_favicon() {
+        for favicon in ["favicon.ico", "images/logo.PNG", "logo.svg"] {
+            let config = Config {
+                favicon: Some(favicon.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{favicon}");
+        }
+
+        for favicon in ["", "logo.bmp", "/srv/logo.png"] {
+            let config = Config {
+                favicon: Some(favicon.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidFavicon { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_content_security_policy() {
+        let config = Config {
+            content_security_policy: Some("default-src 'self'; script-src 'nonce-{nonce}'".into()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for policy in ["", "default-src 'self';\nscript-src *"] {
+            let config = Config {
+                content_security_policy: Some(policy.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidContentSecurityPolicy { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_colors() {
+        for color in ["#369", "#336699", "#336699cc", "white"] {
+            let config = Config {
+                theme_color: Some(color.to_string()),
+                background_color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{color}");
+        }
+
+        for color in ["", "#12", "#ggg", "red\" onload=\"x"] {
+            let config = Config {
+                background_color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigError::InvalidColor {
+                    field: "background_color",
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_date_format() {
+        let config = Config {
+            date_format: "%d.%m.%Y".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            date_format: "%Y-%Q".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidDateFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_default_title_prefix_empty() {
+        let config = Config::default();
+        assert_eq!(config.title_prefix, "");
+    }
+
+    #[test]
+    fn test_default_title_suffix_empty() {
+        let config = Config::default();
+        assert_eq!(config.title_suffix, "");
+    }
+
+    #[test]
+    fn test_validate_editing_non_loopback_without_token_fails() {
+        let config = Config {
+            edit_enabled: true,
+            host: IpArray([0, 0, 0, 0]),
+            edit_token_hash: None,
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::EditingRequiresToken)
+        ));
+    }
+
+    #[test]
+    fn test_validate_editing_non_loopback_with_token_passes() {
+        let config = Config {
+            edit_enabled: true,
+            host: IpArray([0, 0, 0, 0]),
+            edit_token_hash: Some("$argon2id$fake".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_check_gui_exposure() {
+        assert!(Config::default().check_gui_exposure().is_ok());
+
+        let lan = Config {
+            host: IpArray([192, 168, 1, 10]),
+            ..Default::default()
+        };
+        assert!(lan.check_gui_exposure().is_ok());
+
+        let all = Config {
+            host: IpArray([0, 0, 0, 0]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            all.check_gui_exposure(),
+            Err(ConfigError::NetworkExposureNotAllowed { .. })
+        ));
+        let allowed = Config {
+            allow_network_exposure: true,
+            ..all
+        };
+        assert!(allowed.check_gui_exposure().is_ok());
+    }
+
+    #[test]
+    fn test_browse_host() {
+        assert_eq!(IpArray([0, 0, 0, 0]).browse_host(), IpArray([127, 0, 0, 1]));
+        assert_eq!(
+            IpArray([192, 168, 1, 10]).browse_host(),
+            IpArray([192, 168, 1, 10])
+        );
+    }
+
+    #[test]
+    fn test_validate_editing_loopback_without_token_passes() {
+        let config = Config {
+            edit_enabled: true,
+            host: IpArray([127, 0, 0, 1]),
+            edit_token_hash: None,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_oembed_cache_size_zero_is_valid() {
+        // Zero means disabled, which is valid
+        let config = Config {
+            oembed_cache_size: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    // ==================== find_root_dir Edge Case Tests ====================
 
     #[test]
     fn test_find_root_dir_file_without_markers_returns_parent() {
@@ -1062,6 +3122,41 @@ mod tests {
         assert!(root.is_dir());
     }
 
+    #[test]
+    fn test_find_root_dir_with_extra_markers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("project");
+        let nested = project.join("docs").join("guide");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        std::fs::write(project.join("notes.index"), "").unwrap();
+        let file_path = nested.join("test.md");
+        std::fs::write(&file_path, "# Hello").unwrap();
+
+        // Without the custom marker, .git further up wins
+        assert_eq!(find_root_dir_with_markers(&file_path, &[]), tmp.path());
+
+        // A custom marker (file or directory) takes precedence over .git
+        let markers = vec![".hg".to_string(), "notes.index".to_string()];
+        assert_eq!(find_root_dir_with_markers(&file_path, &markers), project);
+
+        // ...but .mbr still marks the root, since the config lives there
+        std::fs::create_dir(nested.parent().unwrap().join(".mbr")).unwrap();
+        assert_eq!(
+            find_root_dir_with_markers(&file_path, &markers),
+            nested.parent().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_root_markers() {
+        assert_eq!(
+            parse_root_markers(" .hg, notes.index ,,"),
+            vec![".hg".to_string(), "notes.index".to_string()]
+        );
+        assert!(parse_root_markers("").is_empty());
+    }
+
     #[test]
     fn test_is_home_dir() {
         if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
@@ -6,6 +6,12 @@ pub const WORDS_PER_MINUTE: usize = 200;
 /// JPEG encoding quality (0-100 scale) for cover images.
 pub const JPEG_QUALITY: u8 = 85;
 
+/// Longest side in pixels of gallery thumbnails.
+pub const GALLERY_THUMBNAIL_SIZE: u32 = 480;
+
+/// Approximate size of each body chunk when the server streams a large page.
+pub const STREAM_RENDER_CHUNK_BYTES: usize = 64 * 1024;
+
 /// YouTube embed width in pixels (16:9 aspect ratio).
 pub const YOUTUBE_EMBED_WIDTH: u16 = 560;
 
@@ -0,0 +1,121 @@
+//! Content-Type overrides for served files.
+//!
+//! Static files are normally typed by extension (via `mime_guess`), which gets
+//! a few media formats wrong for browsers: HLS segments come back as
+//! `video/vnd.dlna.mpeg-tts` and playlists as `audio/x-mpegurl`. Built-in
+//! corrections for those are layered under the user's `mime_overrides`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Corrections for media formats mbr serves, keyed by lowercase extension.
+const BUILTIN_OVERRIDES: &[(&str, &str)] = &[
+    ("m3u8", "application/vnd.apple.mpegurl"),
+    ("ts", "video/mp2t"),
+    // Browsers only recognize the MP4 container under its standard types
+    ("m4v", "video/mp4"),
+    ("m4a", "audio/mp4"),
+];
+
+/// Normalizes a configured extension key: lowercase, without a leading dot.
+pub fn normalize_extension(extension: &str) -> String {
+    extension
+        .trim()
+        .trim_start_matches('.')
+        .to_ascii_lowercase()
+}
+
+/// Returns true if `value` looks like a `type/subtype` media type that can be
+/// sent as a header (parameters such as `; charset=utf-8` are allowed).
+pub fn is_valid_mime(value: &str) -> bool {
+    let essence = value.split(';').next().unwrap_or_default().trim();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    is_token(kind) && is_token(subtype) && value.chars().all(|c| c.is_ascii() && !c.is_control())
+}
+
+/// Extension → Content-Type map consulted before the default detection.
+#[derive(Debug, Clone, Default)]
+pub struct MimeOverrides {
+    by_extension: HashMap<String, String>,
+}
+
+impl MimeOverrides {
+    /// Builds the map from the built-in corrections plus the configured
+    /// `mime_overrides` (which win on conflicts).
+    pub fn new(configured: &HashMap<String, String>) -> Self {
+        let mut by_extension: HashMap<String, String> = BUILTIN_OVERRIDES
+            .iter()
+            .map(|(ext, mime)| (ext.to_string(), mime.to_string()))
+            .collect();
+        for (ext, mime) in configured {
+            by_extension.insert(normalize_extension(ext), mime.trim().to_string());
+        }
+        Self { by_extension }
+    }
+
+    /// The override for `path`'s extension, if any.
+    pub fn lookup(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.by_extension.get(&ext).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_overrides() {
+        let overrides = MimeOverrides::new(&HashMap::new());
+        assert_eq!(
+            overrides.lookup(Path::new("videos/demo-720p-000.ts")),
+            Some("video/mp2t")
+        );
+        assert_eq!(
+            overrides.lookup(Path::new("videos/demo.M3U8")),
+            Some("application/vnd.apple.mpegurl")
+        );
+        assert_eq!(overrides.lookup(Path::new("images/photo.png")), None);
+        assert_eq!(overrides.lookup(Path::new("README")), None);
+    }
+
+    #[test]
+    fn test_configured_override_wins() {
+        let configured = HashMap::from([
+            (".TS".to_string(), "text/x-typescript".to_string()),
+            ("opus".to_string(), "audio/ogg; codecs=opus".to_string()),
+        ]);
+        let overrides = MimeOverrides::new(&configured);
+        assert_eq!(
+            overrides.lookup(Path::new("src/app.ts")),
+            Some("text/x-typescript")
+        );
+        assert_eq!(
+            overrides.lookup(Path::new("music/song.opus")),
+            Some("audio/ogg; codecs=opus")
+        );
+        // Built-ins that weren't overridden remain
+        assert_eq!(overrides.lookup(Path::new("clip.m4v")), Some("video/mp4"));
+    }
+
+    #[test]
+    fn test_is_valid_mime() {
+        for valid in [
+            "video/mp4",
+            "audio/ogg; codecs=opus",
+            "application/vnd.apple.mpegurl",
+        ] {
+            assert!(is_valid_mime(valid), "{valid:?} should be valid");
+        }
+        for invalid in ["", "mp4", "video/", "/mp4", "video/mp4\n", "vidéo/mp4"] {
+            assert!(!is_valid_mime(invalid), "{invalid:?} should be invalid");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Content-Security-Policy support.
+//!
+//! With `content_security_policy` set, the server sends the policy as a
+//! `Content-Security-Policy` header and static builds embed it in every page
+//! as `<meta http-equiv="Content-Security-Policy">`.
+//!
+//! mbr's templates contain a few inline `<script>` and `<style>` blocks. Each
+//! carries a `nonce` attribute (the `csp_nonce` template variable), and
+//! `{nonce}` in the policy is replaced with the same value, so a policy like
+//! `script-src 'self' 'nonce-{nonce}'` allows them. The nonce is generated
+//! when the server starts and once per build.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+
+use crate::Config;
+
+/// Placeholder in `content_security_policy` replaced with the nonce.
+pub const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// Directives browsers ignore when the policy is delivered in a `<meta>` tag.
+const HEADER_ONLY_DIRECTIVES: &[&str] = &["frame-ancestors", "report-uri", "report-to", "sandbox"];
+
+/// A configured policy with its nonce filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    /// The policy, with `{nonce}` replaced.
+    pub policy: String,
+    /// Nonce for inline `<script>` and `<style>` tags.
+    pub nonce: String,
+}
+
+impl ContentSecurityPolicy {
+    /// The configured policy with a fresh nonce, or `None` when unset.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let policy = config.content_security_policy.as_deref()?;
+        Some(Self::new(policy, generate_nonce()))
+    }
+
+    pub fn new(policy: &str, nonce: String) -> Self {
+        Self {
+            policy: policy.trim().replace(NONCE_PLACEHOLDER, &nonce),
+            nonce,
+        }
+    }
+
+    /// The policy for a `<meta>` tag, without the directives that only work
+    /// as a header (browsers warn about them otherwise).
+    pub fn meta_policy(&self) -> String {
+        self.policy
+            .split(';')
+            .map(str::trim)
+            .filter(|directive| {
+                let name = directive.split_whitespace().next().unwrap_or_default();
+                !directive.is_empty()
+                    && !HEADER_ONLY_DIRECTIVES
+                        .iter()
+                        .any(|d| d.eq_ignore_ascii_case(name))
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// A random 128-bit nonce, hex-encoded.
+pub fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    crate::edit_auth::to_hex(&bytes)
+}
+
+/// True when `policy` can be sent as a header value: non-empty and printable
+/// ASCII only.
+pub fn is_valid_policy(policy: &str) -> bool {
+    !policy.trim().is_empty()
+        && policy
+            .bytes()
+            .all(|b| b == b'\t' || (0x20..0x7f).contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_placeholder_is_replaced() {
+        let csp = ContentSecurityPolicy::new(
+            " default-src 'self'; script-src 'self' 'nonce-{nonce}'; style-src 'self' 'nonce-{nonce}' ",
+            "abc123".to_string(),
+        );
+        assert_eq!(
+            csp.policy,
+            "default-src 'self'; script-src 'self' 'nonce-abc123'; style-src 'self' 'nonce-abc123'"
+        );
+    }
+
+    #[test]
+    fn test_meta_policy_drops_header_only_directives() {
+        let csp = ContentSecurityPolicy::new(
+            "default-src 'self'; frame-ancestors 'none'; Report-URI /csp; img-src *;",
+            String::new(),
+        );
+        assert_eq!(csp.meta_policy(), "default-src 'self'; img-src *");
+    }
+
+    #[test]
+    fn test_generate_nonce_is_random_hex() {
+        let nonce = generate_nonce();
+        assert_eq!(nonce.len(), 32);
+        assert!(nonce.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(nonce, generate_nonce());
+    }
+
+    #[test]
+    fn test_is_valid_policy() {
+        assert!(is_valid_policy("default-src 'self'"));
+        assert!(!is_valid_policy("  "));
+        assert!(!is_valid_policy("default-src 'self'\nscript-src *"));
+        assert!(!is_valid_policy("default-src 'sélf'"));
+    }
+}
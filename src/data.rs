@@ -0,0 +1,406 @@
+//! Structured data files for templates and the `data` shortcode.
+//!
+//! With `data_folder` configured, every `.json`, `.yaml`/`.yml` and `.csv`
+//! file directly in that folder is loaded and keyed by its file name without
+//! the extension (`data/people.csv` becomes `people`). JSON and YAML files are
+//! used as-is; CSV files become an array of objects keyed by the header row,
+//! with string values.
+//!
+//! Templates read them with the `data()` function, and markdown pages can
+//! render a file of records as a table with the shortcode:
+//!
+//! ```markdown
+//! {{ data(file="people", columns="name,email") }}
+//! ```
+
+use crate::errors::DataError;
+use crate::shortcode;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Data files loaded from the `data_folder`, keyed by file stem.
+#[derive(Debug, Clone, Default)]
+pub struct DataFiles {
+    files: Map<String, Value>,
+    /// Header order of CSV files (JSON object keys come back sorted).
+    columns: HashMap<String, Vec<String>>,
+}
+
+impl DataFiles {
+    /// Loads every `.json`, `.yaml`/`.yml` and `.csv` file directly inside
+    /// `dir`. Other files and subdirectories are ignored.
+    pub fn load(dir: &Path) -> Result<Self, DataError> {
+        let entries = std::fs::read_dir(dir).map_err(|source| DataError::ReadDirFailed {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+
+        let mut data = Self::default();
+        let mut sources: HashMap<String, PathBuf> = HashMap::new();
+        for path in paths {
+            let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+                continue;
+            };
+            let ext = ext.to_ascii_lowercase();
+            if !matches!(ext.as_str(), "json" | "yaml" | "yml" | "csv") {
+                continue;
+            }
+            let Some(name) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            if let Some(first) = sources.get(&name) {
+                return Err(DataError::DuplicateName {
+                    name,
+                    first: first.clone(),
+                    second: path,
+                });
+            }
+
+            let text = std::fs::read_to_string(&path).map_err(|source| DataError::ReadFailed {
+                path: path.clone(),
+                source,
+            })?;
+            let value = if ext == "json" {
+                serde_json::from_str(&text).map_err(|source| DataError::InvalidJson {
+                    path: path.clone(),
+                    source,
+                })?
+            } else if ext == "yaml" || ext == "yml" {
+                let docs = yaml_rust2::YamlLoader::load_from_str(&text).map_err(|e| {
+                    DataError::InvalidYaml {
+                        path: path.clone(),
+                        message: e.to_string(),
+                    }
+                })?;
+                docs.first()
+                    .map(crate::relationships::yaml_to_json)
+                    .unwrap_or(Value::Null)
+            } else {
+                let (header, records) =
+                    parse_csv(&text).map_err(|(line, message)| DataError::InvalidCsv {
+                        path: path.clone(),
+                        line,
+                        message,
+                    })?;
+                data.columns.insert(name.clone(), header);
+                Value::Array(records)
+            };
+            data.files.insert(name.clone(), value);
+            sources.insert(name, path);
+        }
+        Ok(data)
+    }
+
+    /// Returns the contents of the data file `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.files.get(name)
+    }
+
+    /// The entry for tag value `value` in data file `name`: the file must be
+    /// an object, and its keys are compared the way tag values are
+    /// normalized, so `Jane Doe:` matches `author: jane doe`.
+    pub fn tag_entry(&self, name: &str, value: &str) -> Option<&Value> {
+        let value = crate::wikilink::normalize_tag_value(value);
+        self.get(name)?
+            .as_object()?
+            .iter()
+            .find(|(key, _)| crate::wikilink::normalize_tag_value(key) == value)
+            .map(|(_, entry)| entry)
+    }
+
+    /// All data files as one object keyed by name.
+    pub fn to_value(&self) -> Value {
+        Value::Object(self.files.clone())
+    }
+
+    /// Renders a `{{ data(file="...") }}` shortcode as an HTML table.
+    ///
+    /// Returns `None` if `text` isn't a data shortcode, or if the file is
+    /// unknown or isn't an array of records, so the text is left as written.
+    pub fn shortcode_html(&self, text: &str) -> Option<String> {
+        let attrs = shortcode::parse(text, "data")?;
+        let columns = attrs.get("columns").map(|columns| {
+            columns
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect::<Vec<_>>()
+        });
+        self.table_html(attrs.get("file")?, columns.as_deref())
+    }
+
+    /// Renders the records in data file `name` as a table with the given
+    /// `columns`, defaulting to the CSV header (or the keys of the first
+    /// JSON record).
+    fn table_html(&self, name: &str, columns: Option<&[String]>) -> Option<String> {
+        let records = self.get(name)?.as_array()?;
+        if !records.iter().all(Value::is_object) {
+            return None;
+        }
+        let columns: Vec<String> = match columns {
+            Some(columns) => columns.to_vec(),
+            None => match self.columns.get(name) {
+                Some(header) => header.clone(),
+                None => records
+                    .first()
+                    .and_then(Value::as_object)
+                    .map(|record| record.keys().cloned().collect())
+                    .unwrap_or_default(),
+            },
+        };
+
+        let mut html = String::from("<table class=\"mbr-data\">\n<thead><tr>");
+        for column in &columns {
+            html.push_str(&format!("<th>{}</th>", html_escape::encode_text(column)));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for record in records {
+            html.push_str("<tr>");
+            for column in &columns {
+                let cell = match record.get(column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                };
+                html.push_str(&format!("<td>{}</td>", html_escape::encode_text(&cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        Some(html)
+    }
+}
+
+/// Parses CSV text (RFC 4180: quoted fields may hold commas, newlines and
+/// doubled `""` quotes) into its header and one object per row. Blank lines
+/// are skipped.
+///
+/// Errors carry the 1-based line number where the problem starts.
+fn parse_csv(text: &str) -> Result<(Vec<String>, Vec<Value>), (usize, String)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut field_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                in_quotes = true;
+                field_line = line;
+            }
+            '"' => return Err((line, "unexpected quote in unquoted field".to_string())),
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                quoted = false;
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            _ if quoted => {
+                return Err((line, "unexpected text after closing quote".to_string()));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err((field_line, "unterminated quoted field".to_string()));
+    }
+    if quoted || !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+
+    let mut rows = rows
+        .into_iter()
+        .filter(|(_, row)| !(row.len() == 1 && row[0].is_empty()));
+    let Some((header_line, header)) = rows.next() else {
+        return Err((1, "missing header row".to_string()));
+    };
+    let header: Vec<String> = header.into_iter().map(|h| h.trim().to_string()).collect();
+    for (i, name) in header.iter().enumerate() {
+        if name.is_empty() {
+            return Err((header_line, format!("column {} has an empty name", i + 1)));
+        }
+        if header[..i].contains(name) {
+            return Err((header_line, format!("duplicate column {name:?}")));
+        }
+    }
+
+    let mut records = Vec::new();
+    for (row_line, row) in rows {
+        if row.len() != header.len() {
+            return Err((
+                row_line,
+                format!("expected {} fields, found {}", header.len(), row.len()),
+            ));
+        }
+        let record: Map<String, Value> = header
+            .iter()
+            .cloned()
+            .zip(row.into_iter().map(Value::String))
+            .collect();
+        records.push(Value::Object(record));
+    }
+    Ok((header, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let csv = "name,quote\r\nAda,\"Hello, \"\"world\"\"\"\n\nGrace,\"two\nlines\"\n";
+        let (header, records) = parse_csv(csv).unwrap();
+        assert_eq!(header, vec!["name", "quote"]);
+        assert_eq!(
+            records,
+            vec![
+                json!({"name": "Ada", "quote": "Hello, \"world\""}),
+                json!({"name": "Grace", "quote": "two\nlines"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        assert_eq!(
+            parse_csv("a,b\n1,2\n3\n").unwrap_err(),
+            (3, "expected 2 fields, found 1".to_string())
+        );
+        assert_eq!(
+            parse_csv("a,b\n1,\"open\n").unwrap_err(),
+            (2, "unterminated quoted field".to_string())
+        );
+        assert_eq!(parse_csv("a,a\n").unwrap_err().1, "duplicate column \"a\"");
+        assert_eq!(parse_csv("\n\n").unwrap_err().1, "missing header row");
+    }
+
+    #[test]
+    fn test_load_keys_files_by_name() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("people.csv"), "name,role\nAda,Engineer\n").unwrap();
+        std::fs::write(dir.path().join("site.json"), r#"{"owner": "Ada"}"#).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        std::fs::write(dir.path().join("authors.yml"), "ada:\n  bio: Engineer\n").unwrap();
+
+        let data = DataFiles::load(dir.path()).unwrap();
+        assert_eq!(
+            data.to_value(),
+            json!({
+                "authors": {"ada": {"bio": "Engineer"}},
+                "people": [{"name": "Ada", "role": "Engineer"}],
+                "site": {"owner": "Ada"},
+            })
+        );
+    }
+
+    #[test]
+    fn test_tag_entry_matches_normalized_keys() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("authors.yaml"),
+            "Jane Doe:\n  bio: Writes things\nbob:\n  bio: Edits things\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("list.json"), "[]").unwrap();
+
+        let data = DataFiles::load(dir.path()).unwrap();
+        assert_eq!(
+            data.tag_entry("authors", "jane doe"),
+            Some(&json!({"bio": "Writes things"}))
+        );
+        assert_eq!(
+            data.tag_entry("authors", "Bob"),
+            Some(&json!({"bio": "Edits things"}))
+        );
+        assert_eq!(data.tag_entry("authors", "carol"), None);
+        assert_eq!(data.tag_entry("list", "anything"), None);
+        assert_eq!(data.tag_entry("missing", "bob"), None);
+    }
+
+    #[test]
+    fn test_load_reports_malformed_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("bad.json"), "{nope").unwrap();
+        let err = DataFiles::load(dir.path()).unwrap_err();
+        assert!(matches!(err, DataError::InvalidJson { .. }), "{err}");
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("bad.yml"), "a: [unclosed\n").unwrap();
+        let err = DataFiles::load(dir.path()).unwrap_err();
+        assert!(matches!(err, DataError::InvalidYaml { .. }), "{err}");
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("people.csv"), "a\n").unwrap();
+        std::fs::write(dir.path().join("people.json"), "[]").unwrap();
+        let err = DataFiles::load(dir.path()).unwrap_err();
+        assert!(matches!(err, DataError::DuplicateName { .. }), "{err}");
+    }
+
+    #[test]
+    fn test_shortcode_html() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("people.csv"),
+            "name,role\nAda,<Engineer>\nGrace,Admiral\n",
+        )
+        .unwrap();
+        let data = DataFiles::load(dir.path()).unwrap();
+
+        let html = data.shortcode_html(r#"{{ data(file="people") }}"#).unwrap();
+        assert!(html.contains("<th>name</th><th>role</th>"), "{html}");
+        assert!(
+            html.contains("<td>Ada</td><td>&lt;Engineer&gt;</td>"),
+            "{html}"
+        );
+
+        let html = data
+            .shortcode_html("{{ data(file=\u{201C}people\u{201D}, columns=\u{201C}role\u{201D}) }}")
+            .unwrap();
+        assert!(html.contains("<th>role</th></tr>"), "{html}");
+        assert!(!html.contains("<td>Ada</td>"), "{html}");
+
+        assert_eq!(data.shortcode_html(r#"{{ data(file="missing") }}"#), None);
+        assert_eq!(data.shortcode_html(r#"{{ vid(path="a.mp4") }}"#), None);
+    }
+}
@@ -0,0 +1,211 @@
+//! Frontmatter date parsing and display formatting.
+//!
+//! `date` values arrive in many shapes (`2024-01-05`, `2024-01-05T10:00:00Z`,
+//! `Jan 5, 2024`). They're normalized to a Unix timestamp (UTC; dates without
+//! a time are taken as midnight) for sorting, and formatted for display with
+//! the `date_format` setting via the `format_date` template filter.
+//!
+//! `created` and `updated` are read the same way, giving each page a publish
+//! date (`created`, else `date`) and a last-updated date (`updated`, else the
+//! file's modification time).
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::markdown::SimpleMetadata;
+
+/// Default `date_format`: "January 5, 2024".
+pub const DEFAULT_DATE_FORMAT: &str = "%B %-d, %Y";
+
+/// Date-time layouts with an explicit UTC offset (besides RFC 3339).
+const OFFSET_DATETIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S %z", "%Y-%m-%d %H:%M %z"];
+
+/// Date-time layouts without an offset, read as UTC.
+const NAIVE_DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Date-only layouts, with abbreviated (`%b`) and full (`%B`) month names.
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%b %d, %Y",
+    "%B %d, %Y",
+    "%b %d %Y",
+    "%B %d %Y",
+    "%d %b %Y",
+    "%d %B %Y",
+];
+
+/// Parses a date string into a Unix timestamp (seconds, UTC).
+pub fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp());
+    }
+    if let Some(dt) = OFFSET_DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| DateTime::parse_from_str(value, fmt).ok())
+    {
+        return Some(dt.timestamp());
+    }
+    if let Some(dt) = NAIVE_DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+    {
+        return Some(dt.and_utc().timestamp());
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Frontmatter fields holding dates.
+pub const DATE_FIELDS: &[&str] = &["date", "created", "updated"];
+
+/// Reads and normalizes a frontmatter date `field` (see [`DATE_FIELDS`]).
+///
+/// Returns `Ok(None)` when the field is absent, and `Err` with the raw value
+/// when it's present but not a recognized date.
+pub fn frontmatter_date(frontmatter: &SimpleMetadata, field: &str) -> Result<Option<i64>, String> {
+    let raw = match frontmatter.get(field) {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    parse_date(&raw).map(Some).ok_or(raw)
+}
+
+/// A page's publish and last-updated dates, as timestamps. Unrecognized
+/// frontmatter dates are treated as missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageDates {
+    /// Frontmatter `date`.
+    pub date: Option<i64>,
+    /// Frontmatter `created`, else `date`.
+    pub created: Option<i64>,
+    /// Frontmatter `updated`, else the file's modification time.
+    pub updated: Option<i64>,
+}
+
+impl PageDates {
+    pub fn new(frontmatter: &SimpleMetadata, modified_secs: Option<u64>) -> Self {
+        let get = |field| frontmatter_date(frontmatter, field).ok().flatten();
+        let date = get("date");
+        Self {
+            date,
+            created: get("created").or(date),
+            updated: get("updated").or(modified_secs.map(|secs| secs as i64)),
+        }
+    }
+}
+
+/// Returns true if `format` is a valid strftime-style format string.
+pub fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Formats a Unix timestamp (UTC) with a strftime-style `format`. Returns
+/// `None` for out-of-range timestamps or invalid formats.
+pub fn format_timestamp(timestamp: i64, format: &str) -> Option<String> {
+    if !is_valid_format(format) {
+        return None;
+    }
+    let dt = DateTime::from_timestamp(timestamp, 0)?;
+    Some(dt.format(format).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_formats() {
+        let midnight = 1704412800; // 2024-01-05T00:00:00Z
+        for value in [
+            "2024-01-05",
+            "2024/01/05",
+            "Jan 5, 2024",
+            "January 5, 2024",
+            "5 Jan 2024",
+            " 2024-01-05 ",
+        ] {
+            assert_eq!(parse_date(value), Some(midnight), "{value:?}");
+        }
+
+        let ten_am = midnight + 10 * 3600;
+        for value in [
+            "2024-01-05T10:00:00Z",
+            "2024-01-05T11:00:00+01:00",
+            "2024-01-05 10:00:00",
+            "2024-01-05T10:00",
+            "2024-01-05 05:00:00 -0500",
+        ] {
+            assert_eq!(parse_date(value), Some(ten_am), "{value:?}");
+        }
+
+        for value in ["", "soon", "2024-13-01", "circa 1855"] {
+            assert_eq!(parse_date(value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_frontmatter_date() {
+        let mut fm = SimpleMetadata::new();
+        assert_eq!(frontmatter_date(&fm, "date"), Ok(None));
+
+        fm.insert("date".to_string(), serde_json::json!("2024-01-05"));
+        assert_eq!(frontmatter_date(&fm, "date"), Ok(Some(1704412800)));
+
+        fm.insert("updated".to_string(), serde_json::json!("next tuesday"));
+        assert_eq!(
+            frontmatter_date(&fm, "updated"),
+            Err("next tuesday".to_string())
+        );
+    }
+
+    #[test]
+    fn test_page_dates_fallbacks() {
+        let mut fm = SimpleMetadata::new();
+        assert_eq!(
+            PageDates::new(&fm, Some(1700000000)),
+            PageDates {
+                date: None,
+                created: None,
+                updated: Some(1700000000),
+            }
+        );
+
+        fm.insert("date".to_string(), serde_json::json!("2024-01-05"));
+        let dates = PageDates::new(&fm, Some(1700000000));
+        assert_eq!(dates.created, Some(1704412800));
+        assert_eq!(dates.updated, Some(1700000000));
+
+        fm.insert("created".to_string(), serde_json::json!("2023-12-31"));
+        fm.insert("updated".to_string(), serde_json::json!("2024-02-01"));
+        let dates = PageDates::new(&fm, Some(1700000000));
+        assert_eq!(dates.date, Some(1704412800));
+        assert_eq!(dates.created, Some(1703980800));
+        assert_eq!(dates.updated, Some(1706745600));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp(1704412800, DEFAULT_DATE_FORMAT).as_deref(),
+            Some("January 5, 2024")
+        );
+        assert_eq!(
+            format_timestamp(1704412800, "%Y-%m-%d").as_deref(),
+            Some("2024-01-05")
+        );
+        assert_eq!(format_timestamp(1704412800, "%Q"), None);
+        assert!(is_valid_format("%d/%m/%Y"));
+        assert!(!is_valid_format("%Q"));
+    }
+}
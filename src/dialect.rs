@@ -0,0 +1,419 @@
+//! Source clean-up for markdown dialects: MDX (`.mdx`) and Quarto (`.qmd`).
+//!
+//! Both are markdown with extra syntax that only their own toolchains run.
+//! mbr renders the markdown and, with `mdx_mode = "strip"` (the default),
+//! removes what it can't run before parsing:
+//!
+//! - MDX: `import`/`export` statements, JSX component tags (`<Callout>`,
+//!   `<Chart data={x} />`, `<>` fragments) and `{/* comments */}`. The text
+//!   between a component's tags is kept.
+//! - Quarto: `:::` div fences and `{{< shortcodes >}}` are dropped, and code
+//!   cells (```` ```{python} ````) become ordinary fenced code blocks.
+//!
+//! Frontmatter, fenced code and inline code are never touched. Lowercase tags
+//! are HTML and go through the normal raw-HTML handling. With
+//! `mdx_mode = "passthrough"` the source is parsed as written.
+
+/// What to do with MDX and Quarto syntax that mbr can't render
+/// (`markdown_options.mdx_mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MdxMode {
+    /// Remove it before parsing, keeping the text inside components.
+    #[default]
+    Strip,
+    /// Leave it in the source, so JSX reaches the page as raw HTML.
+    Passthrough,
+}
+
+/// Removes MDX-only syntax from `input` (see the module docs).
+pub fn strip_mdx(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for (verbatim, text) in segments(input) {
+        if verbatim {
+            out.push_str(&text);
+        } else {
+            out.push_str(&strip_jsx(&drop_esm(&text)));
+        }
+    }
+    out
+}
+
+/// Removes Quarto-only syntax from `input` (see the module docs).
+pub fn strip_quarto(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for (verbatim, text) in segments(input) {
+        if verbatim {
+            out.push_str(&plain_code_fences(&text));
+        } else {
+            let text: String = text
+                .split_inclusive('\n')
+                .filter(|line| !line.trim_start().starts_with(":::"))
+                .collect();
+            out.push_str(&strip_shortcodes(&text));
+        }
+    }
+    out
+}
+
+/// Splits `input` into runs of lines, flagged `true` for frontmatter and
+/// fenced code (left as written) and `false` for everything else.
+fn segments(input: &str) -> Vec<(bool, String)> {
+    let mut segments: Vec<(bool, String)> = Vec::new();
+    let mut push = |verbatim: bool, line: &str| match segments.last_mut() {
+        Some((last, text)) if *last == verbatim => text.push_str(line),
+        _ => segments.push((verbatim, line.to_string())),
+    };
+
+    let mut lines = input.split_inclusive('\n').peekable();
+    if let Some(open) = lines.next_if(|line| line.trim_end() == "---") {
+        push(true, open);
+        for line in lines.by_ref() {
+            push(true, line);
+            if matches!(line.trim_end(), "---" | "...") {
+                break;
+            }
+        }
+    }
+
+    let mut fence: Option<(char, usize)> = None;
+    for line in lines {
+        match fence {
+            Some((ch, len)) => {
+                push(true, line);
+                if fence_marker(line)
+                    .is_some_and(|(c, n, info)| c == ch && n >= len && info.trim().is_empty())
+                {
+                    fence = None;
+                }
+            }
+            None => match fence_marker(line) {
+                Some((ch, len, _)) => {
+                    fence = Some((ch, len));
+                    push(true, line);
+                }
+                None => push(false, line),
+            },
+        }
+    }
+    segments
+}
+
+/// The fence character, its run length and the info string if `line` is a
+/// code fence (indented at most three spaces).
+fn fence_marker(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == ch).count();
+    (len >= 3).then(|| (ch, len, &trimmed[len..]))
+}
+
+/// Drops `import`/`export` statements, each running to the next blank line.
+fn drop_esm(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut paragraph_start = true;
+    let mut in_esm = false;
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if paragraph_start && (line.starts_with("import ") || line.starts_with("export ")) {
+            in_esm = true;
+        }
+        if in_esm && blank {
+            in_esm = false;
+        }
+        if !in_esm {
+            out.push_str(line);
+        }
+        paragraph_start = blank;
+    }
+    out
+}
+
+/// Removes JSX component tags and `{/* comments */}`, skipping inline code.
+/// Lines inside a component lose the indentation of its first line of
+/// content, so children indented for readability don't turn into indented
+/// code blocks, while deeper indents (nested lists) are kept.
+fn strip_jsx(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // Content indent of each open component, set by its first non-blank line
+    let mut open: Vec<Option<usize>> = Vec::new();
+    let mut line_start = true;
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        if line_start && let Some(base) = open.last_mut() {
+            let line = rest.split_inclusive('\n').next().unwrap_or(rest);
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            if !line.trim().is_empty() {
+                let base = *base.get_or_insert(indent);
+                rest = &rest[indent.min(base)..];
+            }
+            line_start = false;
+            continue;
+        }
+        line_start = false;
+        if ch == '`' {
+            let span = code_span_len(rest);
+            out.push_str(&rest[..span]);
+            rest = &rest[span..];
+            continue;
+        }
+        if rest.starts_with("{/*")
+            && let Some(end) = rest.find("*/}")
+        {
+            rest = &rest[end + 3..];
+            continue;
+        }
+        if let Some((len, change)) = jsx_tag(rest) {
+            match change {
+                TagKind::Open => open.push(None),
+                TagKind::Close => {
+                    open.pop();
+                }
+                TagKind::SelfClosing => {}
+            }
+            rest = &rest[len..];
+            continue;
+        }
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+        line_start = ch == '\n';
+    }
+    out
+}
+
+/// Length of the inline code span (or bare backtick run) at the start of `s`.
+fn code_span_len(s: &str) -> usize {
+    let ticks = s.chars().take_while(|&c| c == '`').count();
+    let fence = &s[..ticks];
+    let mut offset = ticks;
+    while let Some(found) = s[offset..].find(fence) {
+        let start = offset + found;
+        let run = s[start..].chars().take_while(|&c| c == '`').count();
+        if run == ticks {
+            return start + ticks;
+        }
+        offset = start + run;
+    }
+    ticks
+}
+
+enum TagKind {
+    Open,
+    Close,
+    SelfClosing,
+}
+
+/// Length and kind of the JSX component tag at the start of `s`: a tag whose
+/// name starts with an uppercase letter, or a `<>`/`</>` fragment. Quoted
+/// strings and `{...}` expressions in attributes may contain `>`.
+fn jsx_tag(s: &str) -> Option<(usize, TagKind)> {
+    let after = s.strip_prefix('<')?;
+    let (closing, name) = match after.strip_prefix('/') {
+        Some(name) => (true, name),
+        None => (false, after),
+    };
+    if !(name.starts_with('>') || name.starts_with(|c: char| c.is_ascii_uppercase())) {
+        return None;
+    }
+    let mut quote = None;
+    let mut braces = 0usize;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if braces == 0 => quote = Some(c),
+            (None, '{') => braces += 1,
+            (None, '}') => braces = braces.saturating_sub(1),
+            (None, '>') if braces == 0 => {
+                let kind = if closing {
+                    TagKind::Close
+                } else if s[..i].ends_with('/') {
+                    TagKind::SelfClosing
+                } else {
+                    TagKind::Open
+                };
+                return Some((i + 1, kind));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes Quarto `{{< ... >}}` shortcodes.
+fn strip_shortcodes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{<") {
+        let Some(end) = rest[start..].find(">}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        rest = &rest[start + end + 3..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Rewrites Quarto code cell fences (```` ```{python} ````, ```` ```{r, echo=FALSE} ````)
+/// as plain fences with the language as the info string.
+fn plain_code_fences(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let Some((ch, len, info)) = fence_marker(line) else {
+                return line.to_string();
+            };
+            let Some(cell) = info.trim().strip_prefix('{') else {
+                return line.to_string();
+            };
+            let language: String = cell
+                .trim_start_matches('.')
+                .chars()
+                .take_while(|c| !matches!(c, ' ' | ',' | '}'))
+                .collect();
+            let indent = &line[..line.len() - line.trim_start_matches(' ').len()];
+            let newline = if line.ends_with('\n') { "\n" } else { "" };
+            format!("{indent}{}{language}{newline}", ch.to_string().repeat(len))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_mdx() {
+        let input = "\
+---
+title: Guide
+---
+import { Callout } from '../components/Callout'
+export const meta = {
+  draft: false,
+}
+
+# Guide
+
+<Callout type=\"warning\" onClose={() => setOpen(x > 1)}>
+  Be **careful** here.
+</Callout>
+
+Press <Kbd>Ctrl</Kbd> then <kbd>C</kbd>.{/* note to self */}
+
+<Chart data={[1, 2]} />
+
+```jsx
+<Callout>kept in code</Callout>
+```
+
+Inline `<Button />` stays.
+";
+        assert_eq!(
+            strip_mdx(input),
+            "\
+---
+title: Guide
+---
+
+# Guide
+
+
+Be **careful** here.
+
+
+Press Ctrl then <kbd>C</kbd>.
+
+
+
+```jsx
+<Callout>kept in code</Callout>
+```
+
+Inline `<Button />` stays.
+"
+        );
+    }
+
+    #[test]
+    fn test_strip_mdx_keeps_nested_indents() {
+        let input = "\
+<Callout>
+  Steps:
+
+  - Install
+    - with cargo
+  <Note>
+    Done.
+  </Note>
+</Callout>
+";
+        assert_eq!(
+            strip_mdx(input),
+            "\n\
+Steps:
+
+- Install
+  - with cargo
+
+Done.
+
+
+"
+        );
+    }
+
+    #[test]
+    fn test_strip_mdx_leaves_plain_markdown_alone() {
+        let input = "# Title {#intro}\n\na < b and 3 > 2, {{ data(file=\"x.csv\") }}\n\n<div class=\"x\">hi</div>\n";
+        assert_eq!(strip_mdx(input), input);
+    }
+
+    #[test]
+    fn test_strip_quarto() {
+        let input = "\
+---
+title: Report
+---
+
+::: {.callout-note}
+Read this first.
+:::
+
+```{python}
+#| echo: false
+print(1)
+```
+
+```{r, echo=FALSE}
+summary(x)
+```
+
+Text{{< pagebreak >}} continues.
+";
+        assert_eq!(
+            strip_quarto(input),
+            "\
+---
+title: Report
+---
+
+Read this first.
+
+```python
+#| echo: false
+print(1)
+```
+
+```r
+summary(x)
+```
+
+Text continues.
+"
+        );
+    }
+}
@@ -53,7 +53,7 @@ pub fn content_hash(bytes: &[u8]) -> String {
     to_hex(&digest)
 }
 
-fn to_hex(bytes: &[u8]) -> String {
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
     use std::fmt::Write;
     bytes
         .iter()
@@ -157,6 +157,57 @@ fn get_fluid_color_css(color: &str) -> Option<&'static [u8]> {
     }
 }
 
+/// Browser UI colors for a Pico theme, for `<meta name="theme-color">`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemeColors {
+    /// The theme's primary color, used in light mode.
+    pub light: &'static str,
+    /// Pico's dark-mode page background.
+    pub dark: &'static str,
+}
+
+/// Pico's dark-mode `--pico-background-color` (`rgb(19, 22.5, 30.5)`).
+const PICO_DARK_BACKGROUND: &str = "#13171f";
+
+/// Returns the browser UI colors for a theme, or None if invalid. Fluid
+/// variants share their color's palette.
+pub fn get_theme_colors(theme: &str) -> Option<ThemeColors> {
+    let light = match theme.trim() {
+        "" | "default" | "fluid" => "#0172ad",
+        theme => get_color_primary(theme.strip_prefix("fluid.").unwrap_or(theme))?,
+    };
+    Some(ThemeColors {
+        light,
+        dark: PICO_DARK_BACKGROUND,
+    })
+}
+
+/// Returns the `--pico-primary-background` of a color variant.
+fn get_color_primary(color: &str) -> Option<&'static str> {
+    match color {
+        "amber" => Some("#ffbf00"),
+        "blue" => Some("#2060df"),
+        "cyan" => Some("#047878"),
+        "fuchsia" => Some("#c1208b"),
+        "green" => Some("#398712"),
+        "grey" => Some("#ababab"),
+        "indigo" => Some("#524ed2"),
+        "jade" => Some("#007a50"),
+        "lime" => Some("#a5d601"),
+        "orange" => Some("#d24317"),
+        "pink" => Some("#d92662"),
+        "pumpkin" => Some("#ff9500"),
+        "purple" => Some("#9236a4"),
+        "red" => Some("#c52f21"),
+        "sand" => Some("#ccc6b4"),
+        "slate" => Some("#525f7a"),
+        "violet" => Some("#7540bf"),
+        "yellow" => Some("#f2df0d"),
+        "zinc" => Some("#646b79"),
+        _ => None,
+    }
+}
+
 /// Returns a formatted string of valid theme values for error messages.
 pub fn valid_themes_display() -> String {
     let mut themes = vec!["default".to_string(), "fluid".to_string()];
@@ -221,6 +272,32 @@ mod tests {
         assert!(get_pico_css("  fluid.amber  ").is_some());
     }
 
+    #[test]
+    fn test_theme_colors() {
+        assert_eq!(
+            get_theme_colors("default"),
+            Some(ThemeColors {
+                light: "#0172ad",
+                dark: "#13171f",
+            })
+        );
+        assert_eq!(get_theme_colors(""), get_theme_colors("fluid"));
+        assert_eq!(get_theme_colors("amber").unwrap().light, "#ffbf00");
+        assert_eq!(get_theme_colors("fluid.amber"), get_theme_colors("amber"));
+        assert!(get_theme_colors("azure").is_none());
+        assert!(get_theme_colors("invalid").is_none());
+
+        // Every theme with CSS has colors, and each color reads from its CSS
+        for color in VALID_COLORS {
+            let colors = get_theme_colors(color).unwrap();
+            let css = std::str::from_utf8(get_pico_css(color).unwrap()).unwrap();
+            assert!(
+                css.contains(&format!("--pico-primary-background:{}", colors.light)),
+                "Wrong primary color for {color}"
+            );
+        }
+    }
+
     #[test]
     fn test_valid_themes_display() {
         let display = valid_themes_display();
@@ -37,6 +37,9 @@ pub enum MbrError {
     #[error("Build error: {0}")]
     Build(Box<BuildError>),
 
+    #[error("Init error: {0}")]
+    Init(#[from] InitError),
+
     #[cfg(feature = "media-metadata")]
     #[error("Video metadata error: {0}")]
     Metadata(#[from] MetadataError),
@@ -85,6 +88,13 @@ pub enum ServerError {
 
     #[error("Tracing initialization failed")]
     TracingInit,
+
+    #[error("Failed to open access log: {path}")]
+    AccessLogOpen {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 /// Errors related to configuration loading and parsing.
@@ -116,25 +126,166 @@ pub enum ConfigError {
     TemplateFolderNotDirectory { path: PathBuf },
 
     #[error(
-        "Invalid host address: {host}. Must be a valid IPv4 address (e.g., 127.0.0.1 or 0.0.0.0)"
+        "Invalid host: {host}. Must be an IPv4 address (e.g., 127.0.0.1 or 0.0.0.0) or a hostname that resolves to one"
     )]
     InvalidHost { host: String },
 
-    #[error("Invalid port: {port}. Port must be between 1 and 65535")]
-    InvalidPort { port: u16 },
+    #[error(
+        "Invalid port: {value}. Port must be between 1 and 65535, or \"auto\" to pick a free port"
+    )]
+    InvalidPort { value: String },
 
     #[error("Invalid sidebar_max_items: {value}. Must be greater than 0")]
     InvalidSidebarMaxItems { value: usize },
 
+    #[error(
+        "Invalid reading_order: {value:?}. Use \"nav\" or a markdown file path relative to the root"
+    )]
+    InvalidReadingOrder { value: String },
+
+    #[error(
+        "Invalid favicon: {value:?}. Use an .ico, .png, .svg, .jpg, .gif or .webp file relative to the root"
+    )]
+    InvalidFavicon { value: String },
+
+    #[error(
+        "Invalid content_security_policy: {value:?}. Use a single line of printable ASCII, e.g. \"default-src 'self'\""
+    )]
+    InvalidContentSecurityPolicy { value: String },
+
+    #[error("Invalid {field}: {value:?}. Use a hex color such as \"#336699\" or a color name")]
+    InvalidColor { field: &'static str, value: String },
+
+    #[error("Invalid date_format: {format:?}. Use strftime-style specifiers such as \"%Y-%m-%d\"")]
+    InvalidDateFormat { format: String },
+
+    #[error(
+        "Invalid TOC levels: toc_min_level = {min}, toc_max_level = {max}. Use 1 <= min <= max <= 6"
+    )]
+    InvalidTocLevels { min: u8, max: u8 },
+
+    #[error("Invalid header_height: {value:?}. Use a CSS length such as \"4rem\" or \"56px\"")]
+    InvalidHeaderHeight { value: String },
+
+    #[error("Invalid media_max_width: {value:?}. Use a CSS length such as \"40rem\" or \"100%\"")]
+    InvalidMediaMaxWidth { value: String },
+
+    #[error("Invalid search_result_limit: {value}. Must be greater than 0")]
+    InvalidSearchResultLimit { value: usize },
+
+    #[error("Invalid site_lang: {value:?}. Use a language tag such as \"en\" or \"pt-BR\"")]
+    InvalidSiteLang { value: String },
+
+    #[error("Invalid site_dir: {value:?}. Use \"ltr\", \"rtl\", or \"auto\"")]
+    InvalidSiteDir { value: String },
+
+    #[error("Invalid listing_layout: {value:?}. Use \"list\", \"cards\", or \"grid\"")]
+    InvalidListingLayout { value: String },
+
+    #[error("Invalid access_log_format: {value:?}. Use \"human\" or \"combined\"")]
+    InvalidAccessLogFormat { value: String },
+
+    #[error("Invalid progress_format: {value:?}. Use \"auto\", \"tty\" or \"plain\"")]
+    InvalidProgressFormat { value: String },
+
+    #[error(
+        "Invalid log_level: {value:?}. Use \"error\", \"warn\", \"info\", \"debug\" or \"trace\""
+    )]
+    InvalidLogLevel { value: String },
+
+    #[error(
+        "Invalid ffmpeg_log_level: {value:?}. Use one of ffmpeg's levels: quiet, panic, fatal, error, warning, info, verbose, debug or trace"
+    )]
+    InvalidFfmpegLogLevel { value: String },
+
+    #[error("post_build_command is empty. Remove it or set a shell command")]
+    EmptyPostBuildCommand,
+
+    #[error(
+        "Invalid base_url: {value:?}. Use an absolute http(s) URL such as \"https://notes.example.com/\""
+    )]
+    InvalidBaseUrl { value: String },
+
+    #[error(
+        "Invalid site_host: {value:?}. Use a bare host name such as \"notes.example.com\" (no scheme or path)"
+    )]
+    InvalidSiteHost { value: String },
+
+    #[error(
+        "Invalid mermaid_theme: {value:?}. Use \"default\", \"neutral\", \"dark\", \"forest\" or \"base\""
+    )]
+    InvalidMermaidTheme { value: String },
+
+    #[error(
+        "Invalid mermaid_config: {reason}. Use a JSON object such as '{{\"fontFamily\": \"Inter\"}}'"
+    )]
+    InvalidMermaidConfig { reason: String },
+
+    #[error(
+        "Invalid {field} entry {value:?}. Use a tag or attribute name such as \"kbd\" (script and style are never allowed)"
+    )]
+    InvalidSanitizeAllowlist { field: &'static str, value: String },
+
+    #[error("Invalid data_folder: {value:?}. Use a folder path relative to the root")]
+    InvalidDataFolder { value: String },
+
     #[error("Invalid build_concurrency: {value}. Must be greater than 0")]
     InvalidBuildConcurrency { value: usize },
 
+    #[error("Invalid oembed_concurrency: {value}. Must be greater than 0")]
+    InvalidOembedConcurrency { value: usize },
+
+    #[error("Invalid server_render_concurrency: {value}. Must be greater than 0")]
+    InvalidServerRenderConcurrency { value: usize },
+
+    #[error("Invalid image format: {format}. Supported formats are \"webp\"")]
+    InvalidImageFormat { format: String },
+
+    #[error("Invalid feed format: {format}. Supported formats are \"json\"")]
+    InvalidFeedFormat { format: String },
+
+    #[error(
+        "Invalid section_feeds entry {value:?}. Use a folder relative to the root directory, such as \"blog/\""
+    )]
+    InvalidSectionFeed { value: String },
+
+    #[error("Invalid audio_transcode: {codec}. Supported codecs are \"aac\" and \"opus\"")]
+    InvalidAudioTranscode { codec: String },
+
+    #[error("Invalid audio_transcode_bitrate_kbps: {value}. Must be greater than 0")]
+    InvalidAudioTranscodeBitrate { value: u32 },
+
+    #[error(
+        "Invalid video_encoder: {name:?}. Use an ffmpeg encoder name such as \"libx264\", or \"software\""
+    )]
+    InvalidVideoEncoder { name: String },
+
+    #[error(
+        "Invalid mime_overrides entry {extension:?} = {mime:?}. Use a file extension and a type/subtype media type"
+    )]
+    InvalidMimeOverride { extension: String, mime: String },
+
+    #[error("Invalid renderers entry {extension:?}. Use a file extension such as \"txt\"")]
+    InvalidRendererExtension { extension: String },
+
+    #[error(
+        "Invalid build_only {value:?}. Use a path relative to the root directory, such as \"docs/\""
+    )]
+    InvalidBuildOnly { value: String },
+
     #[error(
         "Editing is enabled on a non-loopback host but no edit_token_hash is set. \
          Run `mbr --generate-edit-token` and add the printed edit_token_hash to \
          .mbr/config.toml, or bind to 127.0.0.1."
     )]
     EditingRequiresToken,
+
+    #[error(
+        "Refusing to start the GUI on {host}: that serves the whole repository to \
+         every network interface with no authentication. Use --host 127.0.0.1, \
+         or set allow_network_exposure = true in .mbr/config.toml if this is intended."
+    )]
+    NetworkExposureNotAllowed { host: String },
 }
 
 /// Errors related to markdown parsing and rendering.
@@ -385,6 +536,160 @@ pub enum OembedError {
     Http(#[from] reqwest::Error),
 }
 
+/// Errors from parsing chapter markers (sidecar `.chapters.json` or
+/// frontmatter `chapters:`). Entry indexes are 1-based for display.
+#[derive(Debug, Error)]
+pub enum ChapterError {
+    #[error("chapters must be a list of {{start, title}} entries")]
+    NotAList,
+
+    #[error("chapter {index}: expected an entry with `start` and `title`")]
+    NotAnEntry { index: usize },
+
+    #[error("chapter {index}: missing `start`")]
+    MissingStart { index: usize },
+
+    #[error("chapter {index}: invalid timestamp '{value}' (use seconds, mm:ss, or hh:mm:ss)")]
+    InvalidTimestamp { index: usize, value: String },
+
+    #[error("chapter {index}: missing or empty `title`")]
+    MissingTitle { index: usize },
+
+    #[error("Failed to read chapters file {}: {source}", path.display())]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid JSON in chapters file {}: {source}", path.display())]
+    InvalidJson {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Errors from loading data files from the `data_folder`.
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error("Failed to read data folder {}: {source}", path.display())]
+    ReadDirFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to read data file {}: {source}", path.display())]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid JSON in data file {}: {source}", path.display())]
+    InvalidJson {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid YAML in data file {}: {message}", path.display())]
+    InvalidYaml { path: PathBuf, message: String },
+
+    #[error("Invalid CSV in data file {} (line {line}): {message}", path.display())]
+    InvalidCsv {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+
+    #[error(
+        "Data files {} and {} both define `{name}`",
+        first.display(),
+        second.display()
+    )]
+    DuplicateName {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
+/// Errors from resolving a `{{ code(...) }}` shortcode.
+#[derive(Debug, Error)]
+pub enum CodeIncludeError {
+    #[error("Code include path `{path}` must be relative to the root and stay inside it")]
+    InvalidPath { path: String },
+
+    #[error("Code include file `{path}` not found")]
+    NotFound { path: String },
+
+    #[error("Failed to read code include file `{path}`: {source}")]
+    ReadFailed {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Code include line range `{lines}` is invalid (expected e.g. `12` or `10-30`)")]
+    InvalidRange { lines: String },
+
+    #[error("Code include line range `{lines}` is past the end of `{path}` ({line_count} lines)")]
+    RangeOutOfBounds {
+        path: String,
+        lines: String,
+        line_count: usize,
+    },
+}
+
+/// Errors from resolving a `{{ figure(...) }}` shortcode.
+#[derive(Debug, Error)]
+pub enum FigureError {
+    #[error("Figure image `{src}` must be relative to the root and stay inside it")]
+    InvalidPath { src: String },
+
+    #[error("Figure image `{src}` is not an image file")]
+    NotAnImage { src: String },
+
+    #[error("Figure image `{src}` not found")]
+    NotFound { src: String },
+}
+
+/// Errors from scaffolding a new `.mbr/` folder with `--init`.
+#[derive(Debug, Error)]
+pub enum InitError {
+    #[error("{} already exists; use --force to overwrite its config and stylesheets", path.display())]
+    AlreadyExists { path: PathBuf },
+
+    #[error("Failed to write {}: {source}", path.display())]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors from loading the sidebar navigation manifest (`.mbr/nav.yml`).
+#[derive(Debug, Error)]
+pub enum NavError {
+    #[error("Failed to read navigation file {}: {source}", path.display())]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid navigation file {}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+
+    #[error("navigation entries need a non-empty `title`")]
+    MissingTitle,
+
+    #[error("navigation entry '{title}' needs a `url` or `children`")]
+    EmptyEntry { title: String },
+}
+
 /// Errors related to static site building.
 #[derive(Debug, Error)]
 pub enum BuildError {
@@ -440,8 +745,30 @@ pub enum BuildError {
     #[error("Configuration error")]
     Config(Box<ConfigError>),
 
+    #[error("Data file error: {0}")]
+    Data(#[from] DataError),
+
     #[error("Default Pico theme is missing from embedded assets")]
     MissingDefaultTheme,
+
+    #[error("Failed to run post_build_command: {command}")]
+    PostBuildSpawnFailed {
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("post_build_command exited with {status}: {command}")]
+    PostBuildFailed { command: String, status: String },
+
+    #[error("{count} URL path(s) claimed by more than one markdown file (strict mode)")]
+    UrlPathCollisions { count: usize },
+
+    #[error(
+        "Partial builds (--only) update an existing build, but {} doesn't exist. Run a full build first",
+        path.display()
+    )]
+    PartialBuildWithoutOutput { path: PathBuf },
 }
 
 // Convenience type alias for Results using MbrError
@@ -516,7 +843,9 @@ mod tests {
         assert!(err.to_string().contains("invalid-host"));
         assert!(err.to_string().contains("IPv4"));
 
-        let err = ConfigError::InvalidPort { port: 0 };
+        let err = ConfigError::InvalidPort {
+            value: "0".to_string(),
+        };
         assert!(err.to_string().contains("0"));
         assert!(err.to_string().contains("Port must be between 1 and 65535"));
 
@@ -0,0 +1,311 @@
+//! Custom favicon and web app manifest.
+//!
+//! A configured `favicon` is served from the site root as `favicon.<ext>`
+//! (and as `/favicon.ico` in server mode, which browsers request on their
+//! own). Raster favicons are also resized to square PNG icons under
+//! `.mbr/icons/` for the manifest and `apple-touch-icon`. The
+//! `manifest.webmanifest` is generated when `site_name` is set.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use crate::Config;
+use crate::embedded_pico::{self, ThemeColors};
+
+/// File name of the generated web app manifest, at the site root.
+pub const MANIFEST_FILE: &str = "manifest.webmanifest";
+
+/// Square PNG icon sizes generated from a raster favicon. Sizes larger than
+/// the favicon itself are skipped rather than upscaled.
+pub const ICON_SIZES: &[u32] = &[180, 192, 512];
+
+/// Size linked as `apple-touch-icon`.
+const TOUCH_ICON_SIZE: u32 = 180;
+
+/// Favicon extensions accepted by `favicon`, with their MIME types.
+const FAVICON_TYPES: &[(&str, &str)] = &[
+    ("ico", "image/x-icon"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+];
+
+/// Returns the MIME type for a supported favicon path, by extension.
+pub fn favicon_mime(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    FAVICON_TYPES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, mime)| *mime)
+}
+
+/// File name of a generated icon, under `.mbr/icons/`.
+pub fn icon_file_name(size: u32) -> String {
+    format!("icon-{size}.png")
+}
+
+/// The configured favicon, resolved against the root directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteIcon {
+    /// The favicon file in the repository.
+    pub source: PathBuf,
+    /// Name it's served under at the site root (`favicon.<ext>`).
+    pub file_name: String,
+    pub mime: &'static str,
+    /// Generated PNG icon sizes (empty for SVG/ICO or unreadable images).
+    pub sizes: Vec<u32>,
+}
+
+impl SiteIcon {
+    /// Resolves `config.favicon`, warning and returning `None` when the file
+    /// is missing.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let favicon = config.favicon.as_deref()?;
+        let mime = favicon_mime(favicon)?;
+        let source = config.root_dir.join(favicon);
+        if !source.is_file() {
+            tracing::warn!("favicon not found: {}", source.display());
+            return None;
+        }
+        let ext = source
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let sizes = if matches!(mime, "image/x-icon" | "image/svg+xml") {
+            Vec::new()
+        } else {
+            match image::image_dimensions(&source) {
+                Ok((width, height)) => ICON_SIZES
+                    .iter()
+                    .copied()
+                    .filter(|&size| size <= width.min(height))
+                    .collect(),
+                Err(e) => {
+                    tracing::warn!("Failed to read favicon {}: {}", source.display(), e);
+                    Vec::new()
+                }
+            }
+        };
+        Some(Self {
+            source,
+            file_name: format!("favicon.{ext}"),
+            mime,
+            sizes,
+        })
+    }
+
+    /// Encodes the favicon as a `size`x`size` PNG, cropping it to a square.
+    pub fn icon_png(&self, size: u32) -> Result<Vec<u8>, image::ImageError> {
+        let img = image::open(&self.source)?.resize_to_fill(
+            size,
+            size,
+            image::imageops::FilterType::Lanczos3,
+        );
+        let mut encoded = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut encoded, image::ImageFormat::Png)?;
+        Ok(encoded.into_inner())
+    }
+}
+
+/// Favicon, manifest and theme color for a site, shared by server and build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteBranding {
+    pub icon: Option<SiteIcon>,
+    /// `manifest.webmanifest` contents; `None` unless `site_name` is set.
+    pub manifest: Option<String>,
+    pub theme_color: Option<String>,
+    /// Light/dark browser UI colors of the Pico theme, used when
+    /// `theme_color` isn't set.
+    pub theme_colors: Option<ThemeColors>,
+}
+
+impl SiteBranding {
+    pub fn from_config(config: &Config) -> Self {
+        let icon = SiteIcon::from_config(config);
+        let manifest = config
+            .site_name
+            .as_deref()
+            .map(|name| manifest_json(config, name, icon.as_ref()));
+        Self {
+            icon,
+            manifest,
+            theme_color: config.theme_color.clone(),
+            theme_colors: embedded_pico::get_theme_colors(&config.theme),
+        }
+    }
+
+    /// True when nothing is configured, so pages keep the built-in favicon.
+    /// The theme's own colors don't count.
+    pub fn is_empty(&self) -> bool {
+        self.icon.is_none() && self.manifest.is_none() && self.theme_color.is_none()
+    }
+
+    /// Root-relative path of the `apple-touch-icon`, if one is generated.
+    pub fn touch_icon(&self) -> Option<String> {
+        let icon = self.icon.as_ref()?;
+        icon.sizes
+            .contains(&TOUCH_ICON_SIZE)
+            .then(|| format!(".mbr/icons/{}", icon_file_name(TOUCH_ICON_SIZE)))
+    }
+}
+
+/// Builds the web app manifest. Icon URLs are relative to the manifest, so
+/// the site works when deployed under a subpath.
+fn manifest_json(config: &Config, name: &str, icon: Option<&SiteIcon>) -> String {
+    let icons: Vec<serde_json::Value> = match icon {
+        Some(icon) if !icon.sizes.is_empty() => icon
+            .sizes
+            .iter()
+            .map(|size| {
+                json!({
+                    "src": format!(".mbr/icons/{}", icon_file_name(*size)),
+                    "sizes": format!("{size}x{size}"),
+                    "type": "image/png",
+                })
+            })
+            .collect(),
+        Some(icon) => vec![json!({
+            "src": icon.file_name,
+            "sizes": "any",
+            "type": icon.mime,
+        })],
+        None => Vec::new(),
+    };
+
+    let mut manifest = json!({
+        "name": name,
+        "short_name": name,
+        "lang": config.site_lang,
+        "dir": config.site_dir,
+        "start_url": "./",
+        "display": "standalone",
+        "icons": icons,
+    });
+    if let Some(color) = &config.theme_color {
+        manifest["theme_color"] = json!(color);
+    }
+    if let Some(color) = &config.background_color {
+        manifest["background_color"] = json!(color);
+    }
+    serde_json::to_string_pretty(&manifest).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config_with_favicon(dir: &TempDir, name: &str) -> Config {
+        Config {
+            root_dir: dir.path().to_path_buf(),
+            favicon: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn write_png(path: &Path, size: u32) {
+        image::RgbaImage::from_pixel(size, size, image::Rgba([200, 40, 40, 255]))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_favicon_mime() {
+        assert_eq!(favicon_mime("icon.PNG"), Some("image/png"));
+        assert_eq!(favicon_mime("assets/logo.svg"), Some("image/svg+xml"));
+        assert_eq!(favicon_mime("favicon.ico"), Some("image/x-icon"));
+        assert_eq!(favicon_mime("favicon.bmp"), None);
+        assert_eq!(favicon_mime("favicon"), None);
+    }
+
+    #[test]
+    fn test_raster_icon_sizes_skip_upscaling() {
+        let dir = TempDir::new().unwrap();
+        write_png(&dir.path().join("logo.png"), 200);
+
+        let icon = SiteIcon::from_config(&config_with_favicon(&dir, "logo.png")).unwrap();
+        assert_eq!(icon.file_name, "favicon.png");
+        assert_eq!(icon.sizes, [180, 192]);
+
+        let png = icon.icon_png(192).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (192, 192));
+    }
+
+    #[test]
+    fn test_svg_icon_has_no_generated_sizes() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("logo.svg"), "<svg/>").unwrap();
+
+        let icon = SiteIcon::from_config(&config_with_favicon(&dir, "logo.svg")).unwrap();
+        assert_eq!(icon.file_name, "favicon.svg");
+        assert!(icon.sizes.is_empty());
+    }
+
+    #[test]
+    fn test_missing_favicon_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        assert!(SiteIcon::from_config(&config_with_favicon(&dir, "nope.png")).is_none());
+    }
+
+    #[test]
+    fn test_manifest_requires_site_name() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            root_dir: dir.path().to_path_buf(),
+            theme_color: Some("#336699".to_string()),
+            ..Default::default()
+        };
+        let branding = SiteBranding::from_config(&config);
+        assert!(branding.manifest.is_none());
+        assert!(!branding.is_empty());
+        assert!(SiteBranding::from_config(&Config::default()).is_empty());
+    }
+
+    #[test]
+    fn test_theme_colors_follow_pico_theme() {
+        let config = Config {
+            theme: "fluid.jade".to_string(),
+            ..Default::default()
+        };
+        let colors = SiteBranding::from_config(&config).theme_colors.unwrap();
+        assert_eq!(colors.light, "#007a50");
+        assert_eq!(colors.dark, "#13171f");
+    }
+
+    #[test]
+    fn test_manifest_lists_generated_icons() {
+        let dir = TempDir::new().unwrap();
+        write_png(&dir.path().join("logo.png"), 512);
+        let config = Config {
+            site_name: Some("Field Notes".to_string()),
+            theme_color: Some("#336699".to_string()),
+            background_color: Some("white".to_string()),
+            ..config_with_favicon(&dir, "logo.png")
+        };
+
+        let branding = SiteBranding::from_config(&config);
+        let manifest: serde_json::Value =
+            serde_json::from_str(branding.manifest.as_deref().unwrap()).unwrap();
+        assert_eq!(manifest["name"], "Field Notes");
+        assert_eq!(manifest["start_url"], "./");
+        assert_eq!(manifest["theme_color"], "#336699");
+        assert_eq!(manifest["background_color"], "white");
+        let sizes: Vec<&str> = manifest["icons"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|icon| icon["sizes"].as_str().unwrap())
+            .collect();
+        assert_eq!(sizes, ["180x180", "192x192", "512x512"]);
+        assert_eq!(
+            branding.touch_icon().as_deref(),
+            Some(".mbr/icons/icon-180.png")
+        );
+    }
+}
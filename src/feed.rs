@@ -0,0 +1,305 @@
+//! Site feeds for static builds.
+//!
+//! With `feed_formats` set (and a `base_url` to make links absolute), the
+//! build writes a feed of the site's newest dated pages: those with a
+//! frontmatter `created` or `date`, the same pages the archive lists.
+//!
+//! - `json`: `feed.json`, a [JSON Feed 1.1](https://jsonfeed.org/version/1.1)
+//!
+//! `section_feeds` adds a feed per listed section (e.g. `blog/` gets
+//! `/blog/feed.json`) holding only the dated pages under it, linked from the
+//! section's page with `<link rel="alternate">`.
+//!
+//! Items are collected once by [`collect_items`] and each format renders
+//! the same list, so every feed carries the same pages in the same order.
+
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::archive::ArchivePage;
+use crate::link_transform::{absolute_html_links, encode_url_path};
+use crate::repo::MarkdownInfo;
+
+/// File name of the JSON Feed at the site root.
+pub const JSON_FEED_FILE: &str = "feed.json";
+
+/// Most items a feed carries; older pages are left to the archive.
+pub const MAX_FEED_ITEMS: usize = 50;
+
+/// A feed format named in `config.feed_formats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeedFormat {
+    Json,
+}
+
+impl FeedFormat {
+    /// Parses a `config.feed_formats` entry (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// File the feed is written to, at the site root or in its section.
+    pub const fn file_name(&self) -> &'static str {
+        match self {
+            Self::Json => JSON_FEED_FILE,
+        }
+    }
+
+    /// MIME type for the `<link rel="alternate">` pointing at the feed.
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/feed+json",
+        }
+    }
+}
+
+/// Parses a `config.section_feeds` entry into the section's URL path
+/// (`blog/` -> `/blog/`). Returns `None` for the root, absolute or `..`
+/// paths.
+pub fn section_url(value: &str) -> Option<String> {
+    let prefix = crate::config::build_only_prefix(value.trim().trim_start_matches('/'))?;
+    let segments: Vec<String> = prefix
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(format!("/{}/", segments.join("/")))
+}
+
+/// True if the page at `url_path` belongs in the feed of the section at
+/// `section` (a page under it, not the section page itself).
+pub fn in_section(url_path: &str, section: &str) -> bool {
+    url_path.len() > section.len() && url_path.starts_with(section)
+}
+
+/// A dated page included in the feeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedItem {
+    /// Root-relative URL the built page is served at.
+    pub url_path: String,
+    pub title: String,
+    /// Frontmatter `description`, if any.
+    pub summary: Option<String>,
+    /// Publish date as a Unix timestamp (UTC).
+    pub published: i64,
+    /// Last-updated date, when later than the publish date.
+    pub updated: Option<i64>,
+}
+
+impl FeedItem {
+    /// The feed item for a page, or `None` when it has no publish date or
+    /// redirects elsewhere.
+    pub fn from_info(info: &MarkdownInfo) -> Option<Self> {
+        let page = ArchivePage::from_info(info)?;
+        Some(Self {
+            url_path: page.url_path,
+            title: page.title,
+            summary: page.description,
+            published: page.date,
+            updated: info.updated_date.filter(|&updated| updated > page.date),
+        })
+    }
+}
+
+/// The site a feed describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedSite<'a> {
+    pub title: &'a str,
+    /// Public URL of the site; feed links are made absolute against it.
+    pub base_url: &'a str,
+    /// Language of the site's pages (e.g. "en").
+    pub language: &'a str,
+}
+
+impl FeedSite<'_> {
+    /// Absolute URL of the root-relative `path`, percent-encoded.
+    pub fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            encode_url_path(path.trim_start_matches('/'))
+        )
+    }
+}
+
+/// Orders items newest first and keeps the [`MAX_FEED_ITEMS`] newest.
+/// Items published at the same time are ordered by title, then URL.
+pub fn collect_items(items: impl IntoIterator<Item = FeedItem>) -> Vec<FeedItem> {
+    let mut items: Vec<FeedItem> = items.into_iter().collect();
+    items.sort_by(|a, b| {
+        b.published
+            .cmp(&a.published)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+            .then_with(|| a.url_path.cmp(&b.url_path))
+    });
+    items.truncate(MAX_FEED_ITEMS);
+    items
+}
+
+/// Renders `items` as a JSON Feed 1.1 document for the section at
+/// `home_path` (`/` for the whole site). `content_html` supplies the rendered
+/// body of the page at a URL path, when there is one; its links are made
+/// absolute against the page's URL.
+pub fn json_feed(
+    site: &FeedSite<'_>,
+    home_path: &str,
+    items: &[FeedItem],
+    content_html: impl Fn(&str) -> Option<String>,
+) -> String {
+    let items: Vec<Value> = items
+        .iter()
+        .map(|item| {
+            let url = site.url(&item.url_path);
+            let body = content_html(&item.url_path).map(|html| match Url::parse(&url) {
+                Ok(page_url) => absolute_html_links(&html, &page_url),
+                Err(_) => html,
+            });
+            let mut entry = json!({
+                "id": url,
+                "url": url,
+                "title": item.title,
+                // Every item needs content; a page without a body is empty
+                "content_html": body.unwrap_or_default(),
+            });
+            if let Some(summary) = &item.summary {
+                entry["summary"] = json!(summary);
+            }
+            if let Some(published) = rfc3339(item.published) {
+                entry["date_published"] = json!(published);
+            }
+            if let Some(updated) = item.updated.and_then(rfc3339) {
+                entry["date_modified"] = json!(updated);
+            }
+            entry
+        })
+        .collect();
+    let feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": site.title,
+        "home_page_url": site.url(home_path),
+        "feed_url": site.url(&format!("{home_path}{JSON_FEED_FILE}")),
+        "language": site.language,
+        "items": items,
+    });
+    serde_json::to_string_pretty(&feed).unwrap_or_default()
+}
+
+/// Formats a Unix timestamp as an RFC 3339 date-time in UTC.
+fn rfc3339(timestamp: i64) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SITE: FeedSite<'static> = FeedSite {
+        title: "Field Notes",
+        base_url: "https://notes.example.com/",
+        language: "en",
+    };
+
+    fn item(url_path: &str, title: &str, date: &str) -> FeedItem {
+        FeedItem {
+            url_path: url_path.to_string(),
+            title: title.to_string(),
+            summary: None,
+            published: crate::dates::parse_date(date).unwrap(),
+            updated: None,
+        }
+    }
+
+    #[test]
+    fn test_feed_format_from_name() {
+        assert_eq!(FeedFormat::from_name("JSON"), Some(FeedFormat::Json));
+        assert_eq!(FeedFormat::from_name("rss"), None);
+        assert_eq!(FeedFormat::Json.file_name(), "feed.json");
+    }
+
+    #[test]
+    fn test_section_url() {
+        assert_eq!(section_url("blog/").as_deref(), Some("/blog/"));
+        assert_eq!(section_url("/blog").as_deref(), Some("/blog/"));
+        assert_eq!(
+            section_url("./notes/daily/").as_deref(),
+            Some("/notes/daily/")
+        );
+        for bad in ["", "/", "../blog", "blog/../.."] {
+            assert_eq!(section_url(bad), None, "{bad:?}");
+        }
+        assert!(in_section("/blog/post/", "/blog/"));
+        assert!(!in_section("/blog/", "/blog/"));
+        assert!(!in_section("/blogroll/", "/blog/"));
+    }
+
+    #[test]
+    fn test_section_feed_urls() {
+        let feed = json_feed(&SITE, "/blog/", &[], |_| None);
+        let feed: Value = serde_json::from_str(&feed).unwrap();
+        assert_eq!(feed["home_page_url"], "https://notes.example.com/blog/");
+        assert_eq!(feed["feed_url"], "https://notes.example.com/blog/feed.json");
+        assert_eq!(feed["items"], json!([]));
+    }
+
+    #[test]
+    fn test_collect_items_newest_first() {
+        let mut items = vec![
+            item("/a/", "A", "2023-12-31"),
+            item("/c/", "C", "2024-03-05"),
+            item("/b/", "B", "2024-03-05"),
+        ];
+        items.extend((0..MAX_FEED_ITEMS).map(|i| item(&format!("/old{i}/"), "Old", "2001-01-01")));
+        let items = collect_items(items);
+        assert_eq!(items.len(), MAX_FEED_ITEMS);
+        let urls: Vec<&str> = items.iter().take(3).map(|i| i.url_path.as_str()).collect();
+        assert_eq!(urls, ["/b/", "/c/", "/a/"]);
+    }
+
+    #[test]
+    fn test_json_feed_shape() {
+        let items = vec![
+            FeedItem {
+                summary: Some("Bench setup".to_string()),
+                updated: Some(crate::dates::parse_date("2024-04-01").unwrap()),
+                ..item("/posts/setup/", "Setup", "2024-03-05")
+            },
+            item("/posts/empty/", "Empty", "2024-01-01"),
+        ];
+        let feed = json_feed(&SITE, "/", &items, |url_path| {
+            (url_path == "/posts/setup/")
+                .then(|| r#"<p>See <a href="../../docs/">docs</a></p>"#.to_string())
+        });
+        let feed: Value = serde_json::from_str(&feed).unwrap();
+
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["title"], "Field Notes");
+        assert_eq!(feed["home_page_url"], "https://notes.example.com/");
+        assert_eq!(feed["feed_url"], "https://notes.example.com/feed.json");
+        assert_eq!(feed["language"], "en");
+
+        let items = feed["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            json!({
+                "id": "https://notes.example.com/posts/setup/",
+                "url": "https://notes.example.com/posts/setup/",
+                "title": "Setup",
+                "content_html": r#"<p>See <a href="https://notes.example.com/docs/">docs</a></p>"#,
+                "summary": "Bench setup",
+                "date_published": "2024-03-05T00:00:00Z",
+                "date_modified": "2024-04-01T00:00:00Z",
+            })
+        );
+        // Every item has an id and content, as the spec requires
+        for item in items {
+            assert!(item["id"].is_string());
+            assert!(item["content_html"].is_string());
+        }
+        assert!(items[1].get("summary").is_none());
+        assert!(items[1].get("date_modified").is_none());
+    }
+}
@@ -0,0 +1,246 @@
+//! Numbered figures via the `figure` and `list_of_figures` shortcodes.
+//!
+//! ```markdown
+//! {{ list_of_figures() }}
+//!
+//! {{ figure(src="images/setup.png", caption="The test setup") }}
+//! ```
+//!
+//! Each figure renders as a `<figure>` with a numbered `<figcaption>`
+//! ("Figure 1. The test setup") and an `id` of `figure-N` to link to.
+//! Numbering starts at 1 on every page. `src` is relative to the repo root
+//! like `gallery` folders, and `alt` defaults to the caption.
+//!
+//! `list_of_figures` lists the page's figures as links, wherever it appears
+//! on the page, including above the figures themselves.
+
+use crate::errors::FigureError;
+use crate::shortcode;
+use regex::Regex;
+use std::path::{Component, Path};
+use std::sync::LazyLock;
+
+static LIST_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*\{\{\s*list_of_figures\s*\(\s*\)\s*\}\}\s*$"#)
+        .expect("Invalid LIST_RE regex pattern")
+});
+
+/// Stands in for `{{ list_of_figures() }}` until the whole page has been
+/// processed and every figure is known.
+pub const LIST_PLACEHOLDER: &str = "<!-- mbr:list-of-figures -->";
+
+/// File extensions accepted as figure images.
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "webp", "avif", "gif", "bmp", "tif", "tiff", "svg",
+];
+
+/// A parsed `{{ figure(...) }}` shortcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Figure {
+    /// Image file, relative to the repo root.
+    pub src: String,
+    pub caption: String,
+    /// Alt text, if different from the caption.
+    pub alt: Option<String>,
+}
+
+/// A figure as rendered on its page, for the list of figures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberedFigure {
+    /// 1-based position among the page's figures.
+    pub number: usize,
+    pub caption: String,
+}
+
+impl NumberedFigure {
+    /// The figure's `id` on the page.
+    pub fn id(&self) -> String {
+        format!("figure-{}", self.number)
+    }
+}
+
+impl Figure {
+    /// Parses `text` as a figure shortcode. Returns `None` if it isn't one
+    /// or has no `src`.
+    pub fn from_shortcode(text: &str) -> Option<Self> {
+        let attrs = shortcode::parse(text, "figure")?;
+        Some(Self {
+            src: attrs.get("src")?.trim().trim_start_matches('/').to_string(),
+            caption: attrs
+                .get("caption")
+                .map(|caption| caption.trim().to_string())
+                .unwrap_or_default(),
+            alt: attrs.get("alt").map(|alt| alt.trim().to_string()),
+        })
+    }
+
+    /// Checks that `src` is an image inside `root`, returning its
+    /// root-relative URL path.
+    pub fn url_path(&self, root: &Path) -> Result<String, FigureError> {
+        let relative = Path::new(&self.src);
+        if self.src.is_empty()
+            || relative
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(FigureError::InvalidPath {
+                src: self.src.clone(),
+            });
+        }
+        let is_image = relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+        if !is_image {
+            return Err(FigureError::NotAnImage {
+                src: self.src.clone(),
+            });
+        }
+        if !root.join(relative).is_file() {
+            return Err(FigureError::NotFound {
+                src: self.src.clone(),
+            });
+        }
+        Ok(format!("/{}", self.src))
+    }
+}
+
+/// True if `text` is a `{{ list_of_figures() }}` shortcode.
+pub fn is_list_shortcode(text: &str) -> bool {
+    LIST_RE.is_match(text)
+}
+
+/// Renders figure `number` with the image at `src` (as the page should
+/// link it).
+pub fn figure_html(figure: &Figure, number: usize, src: &str) -> String {
+    let alt = figure.alt.as_deref().unwrap_or(&figure.caption);
+    let caption = if figure.caption.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", html_escape::encode_text(&figure.caption))
+    };
+    format!(
+        r#"<figure class="mbr-figure" id="figure-{number}"><img src="{}" alt="{}"><figcaption><span class="mbr-figure-number">Figure {number}.</span>{caption}</figcaption></figure>"#,
+        html_escape::encode_double_quoted_attribute(src),
+        html_escape::encode_double_quoted_attribute(alt),
+    )
+}
+
+/// Renders the list of figures. Empty when the page has none.
+pub fn list_html(figures: &[NumberedFigure]) -> String {
+    if figures.is_empty() {
+        return String::new();
+    }
+    let mut html =
+        String::from(r#"<nav class="mbr-list-of-figures" aria-label="List of figures"><ol>"#);
+    for figure in figures {
+        html.push_str(&format!(
+            r##"<li><a href="#{}">Figure {}. {}</a></li>"##,
+            figure.id(),
+            figure.number,
+            html_escape::encode_text(&figure.caption)
+        ));
+    }
+    html.push_str("</ol></nav>");
+    html
+}
+
+/// Renders a figure that couldn't be shown as a visible notice.
+pub fn error_html(error: &FigureError) -> String {
+    format!(
+        r#"<p class="mbr-figure-error" role="alert">{}</p>"#,
+        html_escape::encode_text(&error.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn figure(src: &str) -> Figure {
+        Figure {
+            src: src.to_string(),
+            caption: "Setup".to_string(),
+            alt: None,
+        }
+    }
+
+    #[test]
+    fn test_from_shortcode() {
+        assert_eq!(
+            Figure::from_shortcode(
+                r#"{{ figure(src="/images/a.png", caption="The setup", alt="Bench") }}"#
+            ),
+            Some(Figure {
+                src: "images/a.png".to_string(),
+                caption: "The setup".to_string(),
+                alt: Some("Bench".to_string()),
+            })
+        );
+        assert_eq!(
+            Figure::from_shortcode("{{ figure(src=\u{201C}a.png\u{201D}) }}"),
+            Some(Figure {
+                src: "a.png".to_string(),
+                caption: String::new(),
+                alt: None,
+            })
+        );
+        assert!(Figure::from_shortcode(r#"{{ figure(caption="x") }}"#).is_none());
+        assert!(Figure::from_shortcode(r#"{{ gallery(dir="x") }}"#).is_none());
+        assert!(is_list_shortcode("{{ list_of_figures() }}"));
+        assert!(!is_list_shortcode("{{ list_of_figures }}"));
+    }
+
+    #[test]
+    fn test_url_path_validation() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("images")).unwrap();
+        std::fs::write(dir.path().join("images/a.PNG"), b"png").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"text").unwrap();
+
+        assert_eq!(
+            figure("images/a.PNG").url_path(dir.path()).unwrap(),
+            "/images/a.PNG"
+        );
+        assert!(matches!(
+            figure("../a.png").url_path(dir.path()),
+            Err(FigureError::InvalidPath { .. })
+        ));
+        assert!(matches!(
+            figure("notes.txt").url_path(dir.path()),
+            Err(FigureError::NotAnImage { .. })
+        ));
+        assert!(matches!(
+            figure("images/missing.png").url_path(dir.path()),
+            Err(FigureError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_figure_and_list_html() {
+        let fig = Figure {
+            caption: "A <b> & c".to_string(),
+            ..figure("a.png")
+        };
+        assert_eq!(
+            figure_html(&fig, 2, "../a.png"),
+            r#"<figure class="mbr-figure" id="figure-2"><img src="../a.png" alt="A &lt;b&gt; &amp; c"><figcaption><span class="mbr-figure-number">Figure 2.</span> A &lt;b&gt; &amp; c</figcaption></figure>"#
+        );
+        let list = list_html(&[
+            NumberedFigure {
+                number: 1,
+                caption: "One".to_string(),
+            },
+            NumberedFigure {
+                number: 2,
+                caption: "Two".to_string(),
+            },
+        ]);
+        assert_eq!(
+            list,
+            r##"<nav class="mbr-list-of-figures" aria-label="List of figures"><ol><li><a href="#figure-1">Figure 1. One</a></li><li><a href="#figure-2">Figure 2. Two</a></li></ol></nav>"##
+        );
+        assert_eq!(list_html(&[]), "");
+    }
+}
@@ -0,0 +1,258 @@
+//! Image galleries via the `gallery` shortcode.
+//!
+//! ```markdown
+//! {{ gallery(dir="photos/trip", sort="date") }}
+//! ```
+//!
+//! Lists the JPEG and PNG images directly inside `dir` (relative to the repo
+//! root) and renders them as a grid of thumbnails, each linking to the image
+//! media viewer. `sort` is `name` (the default) or `date`, which orders by the
+//! EXIF capture date and puts undated images last.
+//!
+//! Thumbnails live under `/.mbr/thumbs/` mirroring the image's own URL path.
+//! Static builds write them alongside the site; the server generates them
+//! on request.
+
+use crate::media;
+use crate::shortcode;
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use std::path::{Path, PathBuf};
+
+/// Characters escaped in thumbnail and viewer URLs. `/` is kept so paths stay
+/// readable; `&` and `=` are escaped so the viewer's `?path=` value survives.
+const URL_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`');
+
+/// URL prefix thumbnails are served from, followed by the image's URL path.
+pub const THUMBNAIL_ROUTE: &str = "/.mbr/thumbs";
+
+/// Root-relative URL of the thumbnail for the image at `url_path`.
+pub fn thumbnail_url(url_path: &str) -> String {
+    utf8_percent_encode(&format!("{THUMBNAIL_ROUTE}{url_path}"), URL_ENCODE_SET).to_string()
+}
+
+/// Order of images in a gallery.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GallerySort {
+    /// File name, case-insensitive.
+    #[default]
+    Name,
+    /// EXIF capture date, oldest first. Images without one follow by name.
+    Date,
+}
+
+/// A parsed `{{ gallery(...) }}` shortcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gallery {
+    /// Folder of images, relative to the repo root.
+    pub dir: String,
+    pub sort: GallerySort,
+}
+
+/// One image shown in a gallery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GalleryImage {
+    /// The image file on disk.
+    pub source: PathBuf,
+    /// Root-relative URL path of the image (e.g. `/photos/trip/beach.jpg`).
+    pub url_path: String,
+}
+
+impl GalleryImage {
+    /// Root-relative URL of the image's thumbnail.
+    pub fn thumbnail_url(&self) -> String {
+        thumbnail_url(&self.url_path)
+    }
+
+    /// Root-relative URL of the image media viewer for this image.
+    pub fn viewer_url(&self) -> String {
+        format!(
+            "/.mbr/images/?path={}",
+            utf8_percent_encode(&self.url_path, URL_ENCODE_SET)
+        )
+    }
+}
+
+impl Gallery {
+    /// Parses `text` as a gallery shortcode. Returns `None` if it isn't one
+    /// or has no `dir`.
+    pub fn from_shortcode(text: &str) -> Option<Self> {
+        let attrs = shortcode::parse(text, "gallery")?;
+        let dir = attrs.get("dir")?.trim().trim_matches('/').to_string();
+        let sort = match attrs.get("sort") {
+            Some(sort) if sort.eq_ignore_ascii_case("date") => GallerySort::Date,
+            _ => GallerySort::Name,
+        };
+        Some(Self { dir, sort })
+    }
+
+    /// Lists the gallery's images in display order.
+    ///
+    /// Returns `None` when the folder doesn't exist under `root` or would
+    /// escape it.
+    pub fn images(&self, root: &Path) -> Option<Vec<GalleryImage>> {
+        if self.dir.split('/').any(|segment| segment == "..") {
+            return None;
+        }
+        let dir = root.join(&self.dir);
+        let entries = std::fs::read_dir(&dir).ok()?;
+        let url_dir = if self.dir.is_empty() {
+            "/".to_string()
+        } else {
+            format!("/{}/", self.dir)
+        };
+        let mut images: Vec<GalleryImage> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?.to_string();
+                media::is_transcodable_image(&name).then(|| GalleryImage {
+                    url_path: format!("{url_dir}{name}"),
+                    source: path,
+                })
+            })
+            .collect();
+        images.sort_by_cached_key(|image| image.url_path.to_lowercase());
+        if self.sort == GallerySort::Date {
+            sort_by_date(&mut images, media::exif_capture_date);
+        }
+        Some(images)
+    }
+}
+
+/// Stable-sorts `images` by the date `date_of` reports, oldest first, moving
+/// images without a date to the end in their current order.
+fn sort_by_date(images: &mut [GalleryImage], date_of: impl Fn(&Path) -> Option<String>) {
+    images.sort_by_cached_key(|image| match date_of(&image.source) {
+        Some(date) => (false, date),
+        None => (true, String::new()),
+    });
+}
+
+/// Renders `images` as a thumbnail grid. `link` maps each root-relative URL
+/// to the form the page should use (relative in static builds).
+pub fn gallery_html(images: &[GalleryImage], link: impl Fn(&str) -> String) -> String {
+    let mut html = String::from(r#"<div class="mbr-gallery">"#);
+    for image in images {
+        let name = image
+            .source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        html.push_str(&format!(
+            r#"<a class="mbr-gallery-item" href="{}"><img src="{}" alt="{}" loading="lazy" decoding="async"></a>"#,
+            html_escape::encode_double_quoted_attribute(&link(&image.viewer_url())),
+            html_escape::encode_double_quoted_attribute(&link(&image.thumbnail_url())),
+            html_escape::encode_double_quoted_attribute(&name),
+        ));
+    }
+    html.push_str("</div>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_shortcode() {
+        assert_eq!(
+            Gallery::from_shortcode(r#"{{ gallery(dir="photos/trip/") }}"#),
+            Some(Gallery {
+                dir: "photos/trip".to_string(),
+                sort: GallerySort::Name,
+            })
+        );
+        assert_eq!(
+            Gallery::from_shortcode(
+                "{{ gallery(dir=\u{201C}photos\u{201D}, sort=\u{201C}date\u{201D}) }}"
+            )
+            .map(|g| g.sort),
+            Some(GallerySort::Date)
+        );
+        assert_eq!(
+            Gallery::from_shortcode(r#"{{ gallery(sort="date") }}"#),
+            None
+        );
+        assert_eq!(Gallery::from_shortcode(r#"{{ vid(path="a.mp4") }}"#), None);
+    }
+
+    #[test]
+    fn test_images_lists_supported_files_by_name() {
+        let root = TempDir::new().unwrap();
+        let dir = root.path().join("photos");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in [
+            "b.png",
+            "A.jpg",
+            "c.JPEG",
+            "notes.md",
+            "clip.mp4",
+            "nested/d.jpg",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let gallery = Gallery::from_shortcode(r#"{{ gallery(dir="photos") }}"#).unwrap();
+        let urls: Vec<String> = gallery
+            .images(root.path())
+            .unwrap()
+            .into_iter()
+            .map(|image| image.url_path)
+            .collect();
+        assert_eq!(urls, ["/photos/A.jpg", "/photos/b.png", "/photos/c.JPEG"]);
+
+        let missing = Gallery::from_shortcode(r#"{{ gallery(dir="nope") }}"#).unwrap();
+        assert_eq!(missing.images(root.path()), None);
+        let escaping = Gallery::from_shortcode(r#"{{ gallery(dir="../photos") }}"#).unwrap();
+        assert_eq!(escaping.images(root.path()), None);
+    }
+
+    #[test]
+    fn test_sort_by_date_puts_undated_last() {
+        let image = |name: &str| GalleryImage {
+            source: PathBuf::from(name),
+            url_path: format!("/{name}"),
+        };
+        let mut images = vec![
+            image("a.jpg"),
+            image("b.jpg"),
+            image("c.jpg"),
+            image("d.jpg"),
+        ];
+        sort_by_date(&mut images, |path| match path.to_str() {
+            Some("b.jpg") => Some("2024:06:01 10:00:00".to_string()),
+            Some("d.jpg") => Some("2023:01:15 08:30:00".to_string()),
+            _ => None,
+        });
+        let order: Vec<&str> = images.iter().map(|i| i.url_path.as_str()).collect();
+        assert_eq!(order, ["/d.jpg", "/b.jpg", "/a.jpg", "/c.jpg"]);
+    }
+
+    #[test]
+    fn test_gallery_html_links_viewer_and_thumbnail() {
+        let images = [GalleryImage {
+            source: PathBuf::from("/repo/photos/my beach.jpg"),
+            url_path: "/photos/my beach.jpg".to_string(),
+        }];
+        let html = gallery_html(&images, |url| url.to_string());
+        assert_eq!(
+            html,
+            r#"<div class="mbr-gallery"><a class="mbr-gallery-item" href="/.mbr/images/?path=/photos/my%20beach.jpg"><img src="/.mbr/thumbs/photos/my%20beach.jpg" alt="my beach" loading="lazy" decoding="async"></a></div>"#
+        );
+
+        let html = gallery_html(&images, |url| format!("..{url}"));
+        assert!(html.contains(r#"src="../.mbr/thumbs/photos/my%20beach.jpg""#));
+    }
+}
@@ -0,0 +1,147 @@
+//! Last-commit information from git, for "last updated by" lines in static
+//! builds.
+//!
+//! A single `git log` over the root directory is read up front and the newest
+//! commit touching each file is kept, so looking up a page afterwards is a
+//! map lookup. Files git doesn't track have no entry and keep their
+//! filesystem modification time.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Serialize;
+
+/// Separates commits in the `git log` output.
+const RECORD_SEPARATOR: char = '\u{1e}';
+/// Separates fields of a commit header.
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// The newest commit that touched a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitCommit {
+    /// Author name.
+    pub author: String,
+    /// Commit date as a Unix timestamp.
+    pub timestamp: i64,
+    /// Abbreviated commit hash.
+    pub hash: String,
+}
+
+/// Last commit per file, keyed by path relative to the root directory.
+#[derive(Debug, Default)]
+pub struct GitMetadata {
+    commits: HashMap<PathBuf, GitCommit>,
+}
+
+impl GitMetadata {
+    /// Reads the history of `root`. Returns `None` when `root` isn't inside a
+    /// git work tree or `git` can't be run.
+    pub fn load(root: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "core.quotePath=false", "log", "--relative"])
+            .args(["--name-only", "--no-renames", "--no-merges"])
+            .arg(format!(
+                "--format={RECORD_SEPARATOR}%h{FIELD_SEPARATOR}%an{FIELD_SEPARATOR}%ct"
+            ))
+            .arg("--")
+            .arg(".")
+            .output()
+            .inspect_err(|e| tracing::warn!("git_metadata: couldn't run git: {e}"))
+            .ok()?;
+        if !output.status.success() {
+            tracing::warn!(
+                "git_metadata: {} is not a git repository; using file modification times",
+                root.display()
+            );
+            return None;
+        }
+        let metadata = Self::parse_log(&String::from_utf8_lossy(&output.stdout));
+        tracing::debug!("git_metadata: read history for {} files", metadata.len());
+        Some(metadata)
+    }
+
+    /// Parses `git log --name-only` output in the format used by [`Self::load`].
+    /// Commits are newest first, so the first one naming a file wins.
+    fn parse_log(log: &str) -> Self {
+        let mut commits = HashMap::new();
+        for record in log.split(RECORD_SEPARATOR).filter(|r| !r.is_empty()) {
+            let mut lines = record.lines();
+            let Some(header) = lines.next() else {
+                continue;
+            };
+            let mut fields = header.split(FIELD_SEPARATOR);
+            let (Some(hash), Some(author), Some(Ok(timestamp))) = (
+                fields.next(),
+                fields.next(),
+                fields.next().map(str::parse::<i64>),
+            ) else {
+                continue;
+            };
+            let commit = GitCommit {
+                author: author.to_string(),
+                timestamp,
+                hash: hash.to_string(),
+            };
+            for file in lines.filter(|line| !line.is_empty()) {
+                commits
+                    .entry(PathBuf::from(file))
+                    .or_insert_with(|| commit.clone());
+            }
+        }
+        Self { commits }
+    }
+
+    /// The newest commit touching `relative_path` (relative to the root).
+    pub fn last_commit(&self, relative_path: &Path) -> Option<&GitCommit> {
+        self.commits.get(relative_path)
+    }
+
+    /// Number of files with a known commit.
+    pub fn len(&self) -> usize {
+        self.commits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_keeps_newest_commit_per_file() {
+        let log = "\u{1e}b2c3d4e\u{1f}Ada Lovelace\u{1f}1700000000\n\ndocs/guide.md\n\n\
+                   \u{1e}a1b2c3d\u{1f}Charles Babbage\u{1f}1690000000\n\ndocs/guide.md\nreadme.md\n";
+        let metadata = GitMetadata::parse_log(log);
+
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(
+            metadata.last_commit(Path::new("docs/guide.md")),
+            Some(&GitCommit {
+                author: "Ada Lovelace".to_string(),
+                timestamp: 1700000000,
+                hash: "b2c3d4e".to_string(),
+            })
+        );
+        assert_eq!(
+            metadata
+                .last_commit(Path::new("readme.md"))
+                .map(|c| c.author.as_str()),
+            Some("Charles Babbage")
+        );
+        assert!(metadata.last_commit(Path::new("untracked.md")).is_none());
+    }
+
+    #[test]
+    fn test_parse_log_skips_malformed_headers() {
+        let log = "\u{1e}abc\u{1f}No Date\n\nfile.md\n";
+        assert!(GitMetadata::parse_log(log).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! `.gitignore` support for repository scanning.
+//!
+//! Loads the repository's root `.gitignore` (plus `.git/info/exclude`) once and
+//! answers "is this path ignored?" for the repo scanner and the inbound-link
+//! grep walk. Nested `.gitignore` files in subdirectories are not consulted.
+//!
+//! The `.mbr/` folder and the configured static folder are always exempt, so a
+//! broad rule such as `*` or `static/` can't hide templates or overlay assets.
+
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Compiled gitignore rules for a repository root.
+#[derive(Debug)]
+pub struct GitignoreFilter {
+    matcher: Gitignore,
+    root_dir: PathBuf,
+    /// Canonical form of `root_dir`, so canonicalized scan paths strip cleanly.
+    canonical_root_dir: Option<PathBuf>,
+    /// Root-relative directories that are never ignored (`.mbr`, static folder).
+    exempt: Vec<PathBuf>,
+}
+
+impl GitignoreFilter {
+    /// Loads `.gitignore` and `.git/info/exclude` from `root_dir`.
+    ///
+    /// Returns `None` when neither file exists or they contain no rules, so
+    /// callers can skip matching entirely for repos without a gitignore.
+    pub fn load(root_dir: &Path, static_folder: &str) -> Option<Self> {
+        let mut builder = GitignoreBuilder::new(root_dir);
+        for file in [
+            root_dir.join(".gitignore"),
+            root_dir.join(".git").join("info").join("exclude"),
+        ] {
+            if file.is_file()
+                && let Some(e) = builder.add(&file)
+            {
+                tracing::warn!("Failed to parse {}: {}", file.display(), e);
+            }
+        }
+
+        let matcher = builder
+            .build()
+            .inspect_err(|e| tracing::warn!("Failed to build gitignore matcher: {}", e))
+            .ok()?;
+        if matcher.is_empty() {
+            return None;
+        }
+
+        let mut exempt = vec![PathBuf::from(".mbr")];
+        let static_folder = static_folder.trim_matches('/');
+        if !static_folder.is_empty() {
+            exempt.push(PathBuf::from(static_folder));
+        }
+
+        Some(Self {
+            matcher,
+            root_dir: root_dir.to_path_buf(),
+            canonical_root_dir: root_dir.canonicalize().ok(),
+            exempt,
+        })
+    }
+
+    /// Returns true if `path` (absolute, or relative to the root) is ignored by
+    /// the gitignore rules, either directly or via an ignored parent directory.
+    ///
+    /// Paths outside the root and paths inside exempt folders are never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = if path.is_absolute() {
+            match self
+                .canonical_root_dir
+                .as_deref()
+                .and_then(|root| path.strip_prefix(root).ok())
+                .or_else(|| path.strip_prefix(&self.root_dir).ok())
+            {
+                Some(rel) => rel,
+                None => return false,
+            }
+        } else {
+            path
+        };
+
+        if relative.as_os_str().is_empty() || self.exempt.iter().any(|e| relative.starts_with(e)) {
+            return false;
+        }
+
+        self.matcher
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_with_gitignore(contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_without_gitignore_returns_none() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(GitignoreFilter::load(dir.path(), "static").is_none());
+    }
+
+    #[test]
+    fn test_load_empty_gitignore_returns_none() {
+        let dir = repo_with_gitignore("# only a comment\n");
+        assert!(GitignoreFilter::load(dir.path(), "static").is_none());
+    }
+
+    #[test]
+    fn test_ignores_matching_files_and_dirs() {
+        let dir = repo_with_gitignore("node_modules/\n*.tmp\n");
+        let filter = GitignoreFilter::load(dir.path(), "static").unwrap();
+
+        assert!(filter.is_ignored(&dir.path().join("node_modules"), true));
+        assert!(filter.is_ignored(&dir.path().join("notes/scratch.tmp"), false));
+        assert!(!filter.is_ignored(&dir.path().join("notes/readme.md"), false));
+    }
+
+    #[test]
+    fn test_ignores_files_under_ignored_parent() {
+        let dir = repo_with_gitignore("out/\n");
+        let filter = GitignoreFilter::load(dir.path(), "static").unwrap();
+
+        assert!(filter.is_ignored(&dir.path().join("out/deep/page.md"), false));
+    }
+
+    #[test]
+    fn test_negation_whitelists() {
+        let dir = repo_with_gitignore("*.md\n!keep.md\n");
+        let filter = GitignoreFilter::load(dir.path(), "static").unwrap();
+
+        assert!(filter.is_ignored(&dir.path().join("drop.md"), false));
+        assert!(!filter.is_ignored(&dir.path().join("keep.md"), false));
+    }
+
+    #[test]
+    fn test_mbr_and_static_folder_are_exempt() {
+        let dir = repo_with_gitignore("*\n");
+        let filter = GitignoreFilter::load(dir.path(), "assets").unwrap();
+
+        assert!(!filter.is_ignored(&dir.path().join(".mbr/config.toml"), false));
+        assert!(!filter.is_ignored(&dir.path().join("assets/img/logo.png"), false));
+        assert!(filter.is_ignored(&dir.path().join("notes.md"), false));
+    }
+
+    #[test]
+    fn test_reads_git_info_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        std::fs::write(dir.path().join(".git/info/exclude"), "private/\n").unwrap();
+        let filter = GitignoreFilter::load(dir.path(), "static").unwrap();
+
+        assert!(filter.is_ignored(&dir.path().join("private"), true));
+    }
+
+    #[test]
+    fn test_relative_paths_and_paths_outside_root() {
+        let dir = repo_with_gitignore("drafts/\n");
+        let filter = GitignoreFilter::load(dir.path(), "static").unwrap();
+
+        assert!(filter.is_ignored(Path::new("drafts/idea.md"), false));
+        assert!(!filter.is_ignored(Path::new("/elsewhere/drafts/idea.md"), false));
+    }
+}
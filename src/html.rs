@@ -855,6 +855,23 @@ pub fn push_html_mbr_with_attrs<'a, I>(
         .expect("writing to a String cannot fail")
 }
 
+/// Like [`push_html_mbr_with_attrs`], but writes to any `fmt::Write` sink.
+pub fn write_html_mbr_with_attrs<'a, I, W>(
+    writer: W,
+    iter: I,
+    section_attrs: HashMap<usize, ParsedAttrs>,
+) -> core::fmt::Result
+where
+    I: Iterator<Item = Event<'a>>,
+    W: core::fmt::Write,
+{
+    write_html_fmt_with_config(
+        writer,
+        iter,
+        HtmlConfig::mbr_with_section_attrs(section_attrs),
+    )
+}
+
 /// Push HTML with explicit configuration.
 ///
 /// Allows fine-grained control over which MBR extensions are enabled.
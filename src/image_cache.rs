@@ -0,0 +1,117 @@
+//! Cache for images the server generates from repository files.
+//!
+//! Gallery thumbnails and site icons are decoded and resized from a source
+//! image, which is far slower than serving a file. Entries are keyed by the
+//! source's path and modification time, so editing the image regenerates it
+//! and the stale entry ages out.
+
+use crate::cache::{Entry, SizeBoundedMap};
+use axum::body::Bytes;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Identifies one generated image.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ImageCacheKey {
+    /// The source image.
+    pub path: PathBuf,
+    /// The source's modification time when the image was generated.
+    pub modified: SystemTime,
+    /// What was generated, e.g. `"thumbnail"` or `"icon-192"`.
+    pub variant: String,
+}
+
+impl ImageCacheKey {
+    /// Key for `variant` of the image at `path`, or `None` if the file's
+    /// modification time can't be read.
+    pub fn for_file(path: &Path, variant: impl Into<String>) -> Option<Self> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+        Some(Self {
+            path: path.to_path_buf(),
+            modified,
+            variant: variant.into(),
+        })
+    }
+}
+
+/// Thread-safe, size-bounded cache of generated images.
+///
+/// A thin wrapper around the shared [`SizeBoundedMap`] with oldest-first
+/// eviction at insert time.
+pub struct ImageCache {
+    cache: SizeBoundedMap<ImageCacheKey, Bytes>,
+}
+
+impl ImageCache {
+    /// Creates a cache holding up to `max_size_bytes` of images; 0 disables it.
+    pub fn new(max_size_bytes: usize) -> Self {
+        Self {
+            cache: SizeBoundedMap::new(max_size_bytes),
+        }
+    }
+
+    /// Returns the cached image for `key`, if present.
+    pub fn get(&self, key: &ImageCacheKey) -> Option<Bytes> {
+        self.cache.with_entry(key, |entry| entry.value.clone())
+    }
+
+    /// Caches `bytes` under `key`, evicting the oldest images if over budget.
+    pub fn insert(&self, key: ImageCacheKey, bytes: Bytes) {
+        if self.cache.is_disabled() {
+            return;
+        }
+        let key_len = key.path.as_os_str().len() + key.variant.len();
+        let size_bytes = Entry::<Bytes>::weigh(bytes.len(), key_len);
+        let (_, new_size) = self.cache.insert_weighted(key, bytes, size_bytes);
+        if new_size > self.cache.max_size() {
+            self.cache
+                .evict_until_freed(new_size - self.cache.max_size(), |_, entry| {
+                    Some(entry.inserted_at)
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_follows_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, b"jpeg").unwrap();
+        filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(1_000_000, 0)).unwrap();
+        let key = ImageCacheKey::for_file(&path, "thumbnail").unwrap();
+
+        let cache = ImageCache::new(1024 * 1024);
+        cache.insert(key.clone(), Bytes::from_static(&[1, 2, 3]));
+        assert_eq!(cache.get(&key).as_deref(), Some(&[1u8, 2, 3][..]));
+
+        filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(2_000_000, 0)).unwrap();
+        let edited = ImageCacheKey::for_file(&path, "thumbnail").unwrap();
+        assert_eq!(cache.get(&edited), None, "an edited image misses");
+        assert_eq!(
+            ImageCacheKey::for_file(&dir.path().join("gone.jpg"), "x"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_evicts_oldest_over_budget() {
+        let key = |variant: &str| ImageCacheKey {
+            path: PathBuf::from("a.png"),
+            modified: SystemTime::UNIX_EPOCH,
+            variant: variant.to_string(),
+        };
+        let cache = ImageCache::new(300);
+        cache.insert(key("first"), Bytes::from(vec![0; 150]));
+        cache.insert(key("second"), Bytes::from(vec![0; 150]));
+        assert_eq!(cache.get(&key("first")), None);
+        assert!(cache.get(&key("second")).is_some());
+
+        let disabled = ImageCache::new(0);
+        disabled.insert(key("first"), Bytes::from(vec![0; 10]));
+        assert_eq!(disabled.get(&key("first")), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! Scaffolding for `mbr --init`.
+//!
+//! Creates a `.mbr/` folder holding a commented `config.toml` that lists the
+//! most useful settings at their defaults, plus copies of the built-in
+//! `theme.css` and `user.css` to customize. A starter `index.md` is added
+//! when the folder doesn't have one yet.
+//!
+//! An existing `.mbr/` is left alone unless `force` is set, which rewrites
+//! those three files and nothing else. The starter page is never overwritten.
+
+use crate::config::Config;
+use crate::errors::InitError;
+use crate::server::DEFAULT_FILES;
+use std::path::{Path, PathBuf};
+
+/// Settings written to the starter `config.toml`: the key, a one-line
+/// description, and an example for settings that default to unset.
+const CONFIG_KEYS: &[(&str, &str, Option<&str>)] = &[
+    (
+        "port",
+        "Port for the server and GUI (\"auto\" picks a free one)",
+        None,
+    ),
+    (
+        "host",
+        "Address to listen on (use \"0.0.0.0\" for every interface)",
+        None,
+    ),
+    (
+        "theme",
+        "Pico color theme, e.g. \"amber\" or \"fluid.jade\"",
+        None,
+    ),
+    ("index_file", "File shown for a folder's URL", None),
+    (
+        "markdown_extensions",
+        "File extensions rendered as pages",
+        None,
+    ),
+    (
+        "static_folder",
+        "Folder served as-is at the site root",
+        None,
+    ),
+    ("ignore_dirs", "Folders left out of the site", None),
+    ("ignore_globs", "Files left out of the site", None),
+    (
+        "sidebar_style",
+        "Sidebar navigation: \"panel\" or \"single\"",
+        None,
+    ),
+    (
+        "site_name",
+        "Site name, used for the web app manifest",
+        Some("\"My Notes\""),
+    ),
+    (
+        "base_url",
+        "Public URL of the built site",
+        Some("\"https://notes.example.com/\""),
+    ),
+    (
+        "external_links_new_tab",
+        "Open links to other sites in a new tab",
+        None,
+    ),
+    (
+        "oembed_timeout_ms",
+        "Time allowed to fetch link previews (0 to disable)",
+        None,
+    ),
+    (
+        "build_concurrency",
+        "Pages rendered in parallel by --build",
+        Some("4"),
+    ),
+    (
+        "log_level",
+        "Logging: \"error\", \"warn\", \"info\", \"debug\" or \"trace\"",
+        None,
+    ),
+];
+
+const EXAMPLE_PAGE: &str = "\
+---
+title: Welcome
+---
+
+# Welcome
+
+This folder is now an mbr site. Edit this page, add more markdown files
+next to it, and browse them with `mbr` (or `mbr -s` for the server).
+
+- Settings live in `.mbr/config.toml`.
+- Style changes go in `.mbr/user.css`.
+";
+
+/// Creates the `.mbr/` scaffold under `root`, returning the files written.
+pub fn init_repo(root: &Path, force: bool) -> Result<Vec<PathBuf>, InitError> {
+    let mbr_dir = root.join(".mbr");
+    if mbr_dir.exists() && !force {
+        return Err(InitError::AlreadyExists { path: mbr_dir });
+    }
+    std::fs::create_dir_all(&mbr_dir).map_err(|source| InitError::WriteFailed {
+        path: mbr_dir.clone(),
+        source,
+    })?;
+
+    let config = Config::default_for(root);
+    let mut files: Vec<(PathBuf, &[u8])> = Vec::new();
+    let config_toml = config_toml(&config);
+    files.push((mbr_dir.join("config.toml"), config_toml.as_bytes()));
+    for name in ["theme.css", "user.css"] {
+        if let Some(content) = default_file(name) {
+            files.push((mbr_dir.join(name), content));
+        }
+    }
+    let index = root.join(&config.index_file);
+    if !index.exists() {
+        files.push((index, EXAMPLE_PAGE.as_bytes()));
+    }
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, content) in files {
+        std::fs::write(&path, content).map_err(|source| InitError::WriteFailed {
+            path: path.clone(),
+            source,
+        })?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// The built-in asset served at `/{name}`.
+fn default_file(name: &str) -> Option<&'static [u8]> {
+    DEFAULT_FILES
+        .iter()
+        .find(|(route, _, _)| route.strip_prefix('/') == Some(name))
+        .map(|(_, content, _)| *content)
+}
+
+/// Renders [`CONFIG_KEYS`] as commented-out settings with `config`'s values.
+fn config_toml(config: &Config) -> String {
+    let defaults = serde_json::to_value(config).unwrap_or_default();
+    let mut toml = String::from(
+        "# mbr configuration. Uncomment a setting to change it; the values\n\
+         # shown are the defaults. Every setting is described at\n\
+         # https://zmre.github.io/mbr-markdown-browser/reference/configuration/\n",
+    );
+    for (key, description, example) in CONFIG_KEYS {
+        let value = match (example, defaults.get(key)) {
+            (Some(example), _) => example.to_string(),
+            (None, Some(value)) if !value.is_null() => value.to_string(),
+            _ => continue,
+        };
+        toml.push_str(&format!("\n# {description}\n# {key} = {value}\n"));
+    }
+    toml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_repo_scaffolds_mbr_folder() {
+        let dir = TempDir::new().unwrap();
+        let written = init_repo(dir.path(), false).unwrap();
+        assert_eq!(written.len(), 4);
+
+        let config_toml = std::fs::read_to_string(dir.path().join(".mbr/config.toml")).unwrap();
+        assert!(config_toml.contains("# port = 5200\n"), "{config_toml}");
+        assert!(
+            config_toml.contains("# theme = \"default\"\n"),
+            "{config_toml}"
+        );
+        assert!(
+            config_toml.contains("# markdown_extensions = [\"md\"]\n"),
+            "{config_toml}"
+        );
+        assert!(config_toml.contains("# site_name = \"My Notes\"\n"));
+        assert_eq!(
+            std::fs::read(dir.path().join(".mbr/theme.css")).unwrap(),
+            include_bytes!("../templates/theme.css")
+        );
+        assert!(dir.path().join(".mbr/user.css").is_file());
+        assert!(
+            std::fs::read_to_string(dir.path().join("index.md"))
+                .unwrap()
+                .contains("# Welcome")
+        );
+
+        // The commented template parses as the defaults
+        let config = Config::read(dir.path()).unwrap();
+        assert_eq!(config.port, 5200);
+        assert_eq!(config.theme, "default");
+    }
+
+    #[test]
+    fn test_init_repo_refuses_existing_folder_without_force() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".mbr")).unwrap();
+        std::fs::write(dir.path().join(".mbr/user.css"), "body { color: red; }").unwrap();
+        std::fs::write(dir.path().join("index.md"), "# Mine\n").unwrap();
+
+        assert!(matches!(
+            init_repo(dir.path(), false),
+            Err(InitError::AlreadyExists { .. })
+        ));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".mbr/user.css")).unwrap(),
+            "body { color: red; }"
+        );
+
+        let written = init_repo(dir.path(), true).unwrap();
+        assert_eq!(written.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".mbr/user.css")).unwrap(),
+            "/* intentionally blank */\n"
+        );
+        // An existing page is never replaced
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("index.md")).unwrap(),
+            "# Mine\n"
+        );
+    }
+}
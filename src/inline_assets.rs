@@ -0,0 +1,364 @@
+//! Inlining of small local assets into built pages.
+//!
+//! With `inline_assets_under_bytes` set, the build replaces references to
+//! small local files with their contents, saving a request each:
+//!
+//! - `<link rel="stylesheet" href="...">` becomes `<style>...</style>`
+//! - `<script src="..."></script>` becomes `<script>...</script>`
+//! - `<img src="....svg">` becomes the `<svg>` markup itself
+//!
+//! Tags carrying `data-no-inline` are left alone, as is anything whose
+//! meaning would change once inlined: stylesheets with relative `url()` or
+//! `@import` references, `defer`/`async` classic scripts, module scripts
+//! that import other modules, and SVGs containing scripts.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Matches stylesheet links, SVG candidates and empty external scripts.
+static ASSET_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<(?:link|img)\s[^>]*>|<script\s[^>]*>\s*</script\s*>")
+        .expect("Invalid ASSET_TAG_RE regex pattern")
+});
+/// Matches one attribute, with a double-quoted, single-quoted or bare value.
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\s([A-Za-z][\w:.-]*)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#)
+        .expect("Invalid ATTR_RE regex pattern")
+});
+/// Matches the target of a `url(...)` or `@import "..."` in a stylesheet.
+static CSS_REF_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(?:url\(\s*['"]?|@import\s+['"])([^'")\s]*)"#)
+        .expect("Invalid CSS_REF_RE regex pattern")
+});
+/// Matches a static or dynamic module import.
+static JS_IMPORT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bimport\b").expect("Invalid JS_IMPORT_RE regex pattern"));
+
+/// Opt-out attribute: tags that carry it are never inlined.
+pub const NO_INLINE_ATTR: &str = "data-no-inline";
+
+/// Attributes that only make sense on an external stylesheet `<link>`.
+const LINK_ONLY_ATTRS: &[&str] = &[
+    "rel",
+    "href",
+    "type",
+    "as",
+    "crossorigin",
+    "integrity",
+    "referrerpolicy",
+    "fetchpriority",
+];
+/// Attributes that only make sense on an external `<script>`.
+const SCRIPT_ONLY_ATTRS: &[&str] = &[
+    "src",
+    "integrity",
+    "crossorigin",
+    "charset",
+    "referrerpolicy",
+    "fetchpriority",
+];
+/// `<img>` attributes carried over to an inlined `<svg>`.
+const SVG_KEPT_ATTRS: &[&str] = &["id", "class", "style", "width", "height"];
+
+/// One attribute of a tag; `value` is kept HTML-escaped as written.
+struct Attr<'a> {
+    name: String,
+    value: Option<&'a str>,
+}
+
+fn parse_attrs(tag: &str) -> Vec<Attr<'_>> {
+    // Skip the tag name so `<link` isn't read as an attribute
+    let start = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let end = tag.find('>').unwrap_or(tag.len());
+    ATTR_RE
+        .captures_iter(&tag[start..end])
+        .map(|caps| Attr {
+            name: caps[1].to_ascii_lowercase(),
+            value: caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map(|m| m.as_str()),
+        })
+        .collect()
+}
+
+fn attr<'a>(attrs: &[Attr<'a>], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|a| a.name == name)
+        .map(|a| a.value.unwrap_or_default())
+}
+
+/// Serializes the attributes `keep` accepts back into tag markup.
+fn write_attrs(attrs: &[Attr<'_>], keep: impl Fn(&str) -> bool) -> String {
+    attrs
+        .iter()
+        .filter(|a| keep(&a.name))
+        .map(|a| match a.value {
+            Some(value) => format!(r#" {}="{}""#, a.name, value.replace('"', "&quot;")),
+            None => format!(" {}", a.name),
+        })
+        .collect()
+}
+
+/// True for URLs that point at another site or aren't files at all.
+fn is_external(url: &str) -> bool {
+    url.is_empty()
+        || url.starts_with("//")
+        || url.starts_with('#')
+        || url
+            .split_once(':')
+            .is_some_and(|(scheme, _)| !scheme.contains('/'))
+}
+
+/// Decodes an attribute value into a URL, or `None` for external URLs.
+fn local_url(value: &str) -> Option<String> {
+    let url = html_escape::decode_html_entities(value).trim().to_string();
+    (!is_external(&url)).then_some(url)
+}
+
+/// Replaces references to small local assets with their contents.
+///
+/// `load` is called with the (entity-decoded) URL of each local asset and
+/// returns its contents, or `None` to leave the reference as is (too large,
+/// missing, ...). Inlined `<style>` and `<script>` tags get `nonce`, the
+/// page's Content-Security-Policy nonce, if any. Returns the new HTML and the
+/// number of tags inlined.
+pub fn inline_small_assets(
+    html: &str,
+    nonce: Option<&str>,
+    load: impl Fn(&str) -> Option<String>,
+) -> (String, usize) {
+    let mut inlined = 0;
+    let out = ASSET_TAG_RE.replace_all(html, |caps: &regex::Captures| {
+        let tag = &caps[0];
+        let attrs = parse_attrs(tag);
+        if attr(&attrs, NO_INLINE_ATTR).is_some() {
+            return tag.to_string();
+        }
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        // Inline code needs the CSP nonce; a nonce on the original tag is kept
+        let nonce = nonce
+            .filter(|_| attr(&attrs, "nonce").is_none())
+            .map(|nonce| format!(r#" nonce="{nonce}""#))
+            .unwrap_or_default();
+        let replacement = match name.as_str() {
+            "link" => inline_stylesheet(&attrs, &load)
+                .map(|style| format!("<style{nonce}{}", &style["<style".len()..])),
+            "script" => inline_script(&attrs, &load)
+                .map(|script| format!("<script{nonce}{}", &script["<script".len()..])),
+            _ => inline_svg(&attrs, &load),
+        };
+        match replacement {
+            Some(replacement) => {
+                inlined += 1;
+                replacement
+            }
+            None => tag.to_string(),
+        }
+    });
+    (out.into_owned(), inlined)
+}
+
+fn inline_stylesheet(attrs: &[Attr<'_>], load: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let is_stylesheet = attr(attrs, "rel")?
+        .split_ascii_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("stylesheet"));
+    if !is_stylesheet {
+        return None;
+    }
+    let css = load(&local_url(attr(attrs, "href")?)?)?;
+    // Relative references would resolve against the page instead of the file
+    let relative_ref = CSS_REF_RE
+        .captures_iter(&css)
+        .any(|caps| !is_external(&caps[1]) && !caps[1].starts_with('/'));
+    if relative_ref || css.to_ascii_lowercase().contains("</style") {
+        return None;
+    }
+    let kept = write_attrs(attrs, |name| !LINK_ONLY_ATTRS.contains(&name));
+    Some(format!("<style{kept}>{css}</style>"))
+}
+
+fn inline_script(attrs: &[Attr<'_>], load: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let is_module = attr(attrs, "type").is_some_and(|t| t.eq_ignore_ascii_case("module"));
+    // Inline classic scripts run immediately, so deferred ones must stay
+    // external. Inline modules are always deferred.
+    if !is_module && (attr(attrs, "defer").is_some() || attr(attrs, "async").is_some()) {
+        return None;
+    }
+    let js = load(&local_url(attr(attrs, "src")?)?)?;
+    // A module's imports resolve against its own URL
+    if (is_module && JS_IMPORT_RE.is_match(&js)) || js.to_ascii_lowercase().contains("</script") {
+        return None;
+    }
+    let kept = write_attrs(attrs, |name| {
+        !SCRIPT_ONLY_ATTRS.contains(&name) && !(is_module && matches!(name, "defer" | "async"))
+    });
+    Some(format!("<script{kept}>{js}</script>"))
+}
+
+fn inline_svg(attrs: &[Attr<'_>], load: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let src = local_url(attr(attrs, "src")?)?;
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    if !path.to_ascii_lowercase().ends_with(".svg") {
+        return None;
+    }
+    let svg = load(&src)?;
+    // Drop the XML prolog, doctype and comments around the root element
+    let start = svg.find("<svg")?;
+    let end = svg.rfind("</svg>")? + "</svg>".len();
+    let svg = &svg[start..end];
+    // Scripts in an `<img>` SVG never run; inlined they would
+    if svg.to_ascii_lowercase().contains("<script") {
+        return None;
+    }
+
+    // Attributes from the `<img>` come first so they win over the SVG's own
+    let mut img_attrs = write_attrs(attrs, |name| SVG_KEPT_ATTRS.contains(&name));
+    match attr(attrs, "alt") {
+        Some(alt) if !alt.trim().is_empty() => {
+            img_attrs.push_str(&format!(
+                r#" role="img" aria-label="{}""#,
+                alt.replace('"', "&quot;")
+            ));
+        }
+        _ => img_attrs.push_str(r#" aria-hidden="true""#),
+    }
+    Some(format!("<svg{img_attrs}{}", &svg["<svg".len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn inline(html: &str, files: &[(&str, &str)]) -> (String, usize) {
+        let files: HashMap<&str, &str> = files.iter().copied().collect();
+        inline_small_assets(html, None, |url| files.get(url).map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_inlines_stylesheet_keeping_media() {
+        let (html, count) = inline(
+            r#"<link rel="stylesheet" href="..&#x2F;.mbr&#x2F;theme.css" media="print" />"#,
+            &[("../.mbr/theme.css", "body { color: red; }")],
+        );
+        assert_eq!(html, r#"<style media="print">body { color: red; }</style>"#);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_leaves_other_links_alone() {
+        let html = r#"<link rel="prefetch" href="site.json" /><link rel="stylesheet" href="https://cdn.example.com/x.css" />"#;
+        let (out, count) = inline(html, &[("site.json", "{}")]);
+        assert_eq!(out, html);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_skips_stylesheets_with_relative_references() {
+        let html = r#"<link rel="stylesheet" href="fonts.css">"#;
+        let (out, _) = inline(
+            html,
+            &[("fonts.css", "@font-face { src: url('font.woff2'); }")],
+        );
+        assert_eq!(out, html);
+
+        let (out, count) = inline(
+            html,
+            &[(
+                "fonts.css",
+                "a { background: url(data:image/png;base64,AA==) } b { background: url(/bg.png) }",
+            )],
+        );
+        assert!(out.starts_with("<style>"), "{out}");
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_inlines_scripts() {
+        let (html, count) = inline(
+            r#"<script src="app.js" id="app"></script>"#,
+            &[("app.js", "console.log(1);")],
+        );
+        assert_eq!(html, r#"<script id="app">console.log(1);</script>"#);
+        assert_eq!(count, 1);
+
+        let (html, _) = inline(
+            r#"<script defer type="module" src="mod.js"></script>"#,
+            &[("mod.js", "export const x = 1;")],
+        );
+        assert_eq!(
+            html,
+            r#"<script type="module">export const x = 1;</script>"#
+        );
+    }
+
+    #[test]
+    fn test_skips_scripts_that_would_change_behavior() {
+        for (html, js) in [
+            (r#"<script defer src="a.js"></script>"#, "run();"),
+            (
+                r#"<script type="module" src="a.js"></script>"#,
+                "import { x } from './x.js';",
+            ),
+            (r#"<script src="a.js"></script>"#, "s = '</script>';"),
+        ] {
+            let (out, count) = inline(html, &[("a.js", js)]);
+            assert_eq!(out, html);
+            assert_eq!(count, 0);
+        }
+    }
+
+    #[test]
+    fn test_inlines_svg_images() {
+        let (html, count) = inline(
+            r#"<p><img src="logo.svg" alt="Logo" class="brand" loading="lazy"></p>"#,
+            &[(
+                "logo.svg",
+                "<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\"><rect/></svg>\n",
+            )],
+        );
+        assert_eq!(
+            html,
+            r#"<p><svg class="brand" role="img" aria-label="Logo" xmlns="http://www.w3.org/2000/svg" width="10"><rect/></svg></p>"#
+        );
+        assert_eq!(count, 1);
+
+        let (html, _) = inline(
+            r#"<img src="x.svg" alt="">"#,
+            &[("x.svg", "<svg><script>alert(1)</script></svg>")],
+        );
+        assert_eq!(html, r#"<img src="x.svg" alt="">"#);
+    }
+
+    #[test]
+    fn test_inlined_code_gets_csp_nonce() {
+        let html =
+            r#"<link rel="stylesheet" href="a.css"><script src="a.js"></script><img src="a.svg">"#;
+        let (out, count) = inline_small_assets(html, Some("abc"), |url| {
+            Some(match url {
+                "a.svg" => "<svg></svg>".to_string(),
+                _ => "x".to_string(),
+            })
+        });
+        assert_eq!(
+            out,
+            r#"<style nonce="abc">x</style><script nonce="abc">x</script><svg aria-hidden="true"></svg>"#
+        );
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_respects_opt_out_and_missing_files() {
+        let html = r#"<link rel="stylesheet" href="a.css" data-no-inline><img src="b.svg"><img src="c.png">"#;
+        let (out, count) = inline(html, &[("a.css", "a{}"), ("c.png", "not an svg")]);
+        assert_eq!(out, html);
+        assert_eq!(count, 0);
+    }
+}
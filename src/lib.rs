@@ -32,41 +32,76 @@ pub fn http_client(timeout: std::time::Duration) -> reqwest::Client {
         .expect("failed to build HTTP client")
 }
 
+pub mod access_log;
+pub mod archive;
 pub mod attrs;
 pub mod audio;
+#[cfg(feature = "media-metadata")]
+pub mod audio_transcode;
 #[cfg(feature = "gui")]
 pub mod browser;
 pub mod build;
+pub mod build_progress;
+pub mod build_sink;
 pub mod cache;
+pub mod chapters;
 pub mod cli;
+pub mod code_include;
 pub mod config;
 pub mod constants;
+pub mod content_type;
+pub mod csp;
+pub mod data;
+pub mod dates;
+pub mod dialect;
 pub mod edit_auth;
 pub mod embedded_hljs;
 pub mod embedded_katex;
 pub mod embedded_pico;
 pub mod errors;
+pub mod favicon;
+pub mod feed;
+pub mod figure;
+pub mod gallery;
+pub mod git_metadata;
+pub mod gitignore;
 pub mod html;
+pub mod image_cache;
+pub mod init;
+pub mod inline_assets;
 pub mod link_grep;
 pub mod link_index;
 pub mod link_transform;
+pub mod lint;
 pub mod markdown;
 pub mod media;
+pub mod nav;
 pub mod oembed;
 pub mod oembed_cache;
+pub mod page_assets;
 pub mod page_context;
 pub mod page_errors;
+pub mod page_nav;
 pub mod path_resolver;
 #[cfg(feature = "media-metadata")]
 pub mod pdf_metadata;
+pub mod post_render;
 #[cfg(feature = "ffi")]
 pub mod quicklook;
 pub mod readability;
+pub mod reading_order;
+pub mod redirect;
 pub mod relationships;
 pub mod repo;
+pub mod robots;
+pub mod sanitize;
 pub mod search;
 pub mod server;
+pub mod server_cache;
+pub mod shortcode;
+pub mod slug;
 pub mod sorting;
+pub mod stats;
 pub mod tag_index;
 pub mod templates;
 pub mod vid;
@@ -83,7 +118,9 @@ pub mod wikilink;
 pub mod wikilink_index;
 
 pub use build::{BuildStats, Builder};
-pub use config::{Config, RelationType, SortField, TagSource, find_root_dir};
+pub use config::{
+    Config, RelationType, SortField, TagSource, find_root_dir, find_root_dir_with_markers,
+};
 #[cfg(feature = "media-metadata")]
 pub use errors::MetadataError;
 #[cfg(feature = "media-metadata")]
@@ -99,5 +136,6 @@ pub use quicklook::{
 };
 pub use search::{SearchEngine, SearchQuery, SearchResponse, SearchResult, SearchScope};
 pub use sorting::sort_files;
+pub use tag_index::{TagInfo, TaggedPage};
 #[cfg(feature = "media-metadata")]
 pub use video_transcode::TranscodeError;
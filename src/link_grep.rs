@@ -23,12 +23,21 @@ use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 use walkdir::WalkDir;
 
-use crate::link_index::InboundLink;
-use crate::repo::should_ignore;
+use crate::gitignore::GitignoreFilter;
+use crate::link_index::{InboundLink, aggregate_inbound_by_source, link_context};
+use crate::repo::{VisitedDirs, should_ignore};
+use crate::slug::{SlugStyle, slugify};
+
+/// Inbound link cache size used unless configured: 1 MB.
+pub const DEFAULT_INBOUND_CACHE_SIZE: usize = 1024 * 1024;
+
+/// How long inbound link results stay fresh unless configured, in seconds.
+pub const DEFAULT_INBOUND_CACHE_TTL_SECS: u64 = 60;
 
 /// Result of scanning for inbound links to a page.
 #[derive(Clone)]
@@ -99,6 +108,7 @@ impl InboundLinkCache {
                     l.from.len()
                         + l.text.len()
                         + l.anchor.as_ref().map(|a| a.len()).unwrap_or(0)
+                        + l.context.as_ref().map(|c| c.len()).unwrap_or(0)
                         + 32
                 })
                 .sum::<usize>()
@@ -260,6 +270,8 @@ fn compute_relative_path(source_folder: &str, target_path: &str) -> String {
 /// - Relative paths without prefix: `b/c/1`
 /// - Relative paths with `./`: `./b/c/1`
 /// - Parent traversal: `../a/b/c/1`, `../../a/b/c/1`
+/// - Full URLs on the site's own host, when `site_host` is set:
+///   `https://example.com/a/b/c/1` and `http://example.com/a/b/c/1`
 ///
 /// For each base pattern, generates variants:
 /// - Without trailing slash: `b/c/1`
@@ -270,10 +282,15 @@ fn compute_relative_path(source_folder: &str, target_path: &str) -> String {
 /// # Arguments
 /// * `source_folder` - The URL path of the folder containing the source file (e.g., `/docs/`)
 /// * `target_url_path` - The full URL path of the target (e.g., `/a/b/c/1/`)
+/// * `site_host` - The site's canonical host (e.g., `example.com`), if configured
 ///
 /// # Returns
 /// A vector of all patterns that could be valid links to the target from this folder
-fn compute_patterns_for_folder(source_folder: &str, target_url_path: &str) -> Vec<String> {
+fn compute_patterns_for_folder(
+    source_folder: &str,
+    target_url_path: &str,
+    site_host: Option<&str>,
+) -> Vec<String> {
     let mut patterns = HashSet::new();
 
     // Normalize target (strip leading/trailing slashes for the base)
@@ -289,6 +306,13 @@ fn compute_patterns_for_folder(source_folder: &str, target_url_path: &str) -> Ve
     let abs_path = format!("/{}", target_normalized);
     add_pattern_variants(&mut patterns, &abs_path);
 
+    // Full URLs pointing at the site's own host resolve to the same absolute path
+    if let Some(host) = site_host {
+        for scheme in ["https", "http"] {
+            add_pattern_variants(&mut patterns, &format!("{scheme}://{host}{abs_path}"));
+        }
+    }
+
     // 2. Relative path from this folder
     let relative = compute_relative_path(source_folder, target_url_path);
 
@@ -326,17 +350,19 @@ fn add_pattern_variants(patterns: &mut HashSet<String>, base: &str) {
 /// # Arguments
 /// * `target_url_path` - The URL path being searched for (e.g., "/docs/guide/")
 /// * `all_folders` - Set of all folder URL paths in the repository
+/// * `site_host` - The site's canonical host, for matching full-URL links
 ///
 /// # Returns
 /// HashMap from folder URL path to patterns valid for that folder
 fn build_folder_patterns(
     target_url_path: &str,
     all_folders: &HashSet<String>,
+    site_host: Option<&str>,
 ) -> HashMap<String, Vec<String>> {
     all_folders
         .iter()
         .map(|folder| {
-            let patterns = compute_patterns_for_folder(folder, target_url_path);
+            let patterns = compute_patterns_for_folder(folder, target_url_path, site_host);
             (folder.clone(), patterns)
         })
         .collect()
@@ -436,6 +462,30 @@ fn build_ref_extraction_regex(patterns: &[String]) -> Option<Regex> {
     Regex::new(&pattern).ok()
 }
 
+/// Which files [`find_inbound_links`] searches and how it matches links.
+#[derive(Debug, Clone, Copy)]
+pub struct InboundLinkSearch<'a> {
+    /// Root directory of the markdown repository
+    pub root_dir: &'a Path,
+    /// List of valid markdown file extensions
+    pub markdown_extensions: &'a [String],
+    /// Directories to skip during scanning
+    pub ignore_dirs: &'a [String],
+    /// Glob patterns for files to ignore
+    pub ignore_globs: &'a [String],
+    /// Optional `.gitignore` rules; matching files and folders are skipped
+    pub gitignore: Option<&'a GitignoreFilter>,
+    /// The site's canonical host; links written as full `http(s)://` URLs on
+    /// this host are matched by their path
+    pub site_host: Option<&'a str>,
+    /// Slug style for wikilink anchors (`[[Page#Some Heading]]`), so they
+    /// match the target's heading IDs
+    pub heading_ids: SlugStyle,
+    /// Search folders reached through symlinks (each folder once); when
+    /// false, symlinked folders are skipped
+    pub follow_symlinks: bool,
+}
+
 /// Find all inbound links to a target page by grep-searching markdown files.
 ///
 /// This scans all markdown files in the repository looking for links that point
@@ -443,20 +493,24 @@ fn build_ref_extraction_regex(patterns: &[String]) -> Option<Regex> {
 ///
 /// # Arguments
 /// * `target_url_path` - The URL path being linked to (e.g., "/docs/guide/")
-/// * `root_dir` - Root directory of the markdown repository
-/// * `markdown_extensions` - List of valid markdown file extensions
-/// * `ignore_dirs` - Directories to skip during scanning
-/// * `ignore_globs` - Glob patterns for files to ignore
+/// * `search` - The repository to search and how to match links
 ///
 /// # Returns
 /// A vector of `InboundLink` structs representing pages that link to the target.
 pub fn find_inbound_links(
     target_url_path: &str,
-    root_dir: &Path,
-    markdown_extensions: &[String],
-    ignore_dirs: &[String],
-    ignore_globs: &[String],
+    search: &InboundLinkSearch<'_>,
 ) -> Vec<InboundLink> {
+    let InboundLinkSearch {
+        root_dir,
+        markdown_extensions,
+        ignore_dirs,
+        ignore_globs,
+        gitignore,
+        site_host,
+        heading_ids,
+        follow_symlinks,
+    } = *search;
     let start = Instant::now();
     let mut inbound_links = Vec::new();
 
@@ -470,12 +524,20 @@ pub fn find_inbound_links(
 
     // First pass: collect all unique folder paths and their files
     let mut folder_files: HashMap<String, Vec<(PathBuf, String)>> = HashMap::new();
+    let mut visited = VisitedDirs::new(follow_symlinks);
 
     for entry in WalkDir::new(root_dir)
-        .follow_links(true)
+        .follow_links(follow_symlinks)
         .into_iter()
         .filter_entry(|e| {
             let path = e.path();
+            if !visited.admit(e) {
+                return false;
+            }
+            // Skip gitignored files and directories
+            if gitignore.is_some_and(|gi| gi.is_ignored(path, e.file_type().is_dir())) {
+                return false;
+            }
             // Skip ignored directories
             if path.is_dir()
                 && let Some(name) = path.file_name().and_then(|n| n.to_str())
@@ -527,7 +589,7 @@ pub fn find_inbound_links(
     let all_folders: HashSet<String> = folder_files.keys().cloned().collect();
 
     // Build patterns for each folder
-    let folder_patterns = build_folder_patterns(target_url_path, &all_folders);
+    let folder_patterns = build_folder_patterns(target_url_path, &all_folders, site_host);
 
     // Build Aho-Corasick automatons for each folder (case-insensitive for wiki links)
     let mut folder_automatons: HashMap<String, Option<AhoCorasick>> = HashMap::new();
@@ -601,11 +663,14 @@ pub fn find_inbound_links(
                 for cap in regex.captures_iter(&content) {
                     let text = cap.get(1).map(|m| m.as_str()).unwrap_or("");
                     let anchor = cap.get(2).map(|m| format!("#{}", m.as_str()));
+                    let whole = cap.get(0).expect("capture group 0 always matches");
 
                     inbound_links.push(InboundLink {
                         from: source_url_path.clone(),
                         text: text.to_string(),
                         anchor,
+                        context: grep_link_context(&content, whole.start(), whole.end(), text),
+                        count: 1,
                     });
                     found_link = true;
                 }
@@ -619,7 +684,7 @@ pub fn find_inbound_links(
                         if s.is_empty() {
                             None
                         } else {
-                            Some(format!("#{}", s))
+                            Some(format!("#{}", slugify(s, heading_ids)))
                         }
                     });
 
@@ -636,16 +701,18 @@ pub fn find_inbound_links(
                                 .to_string()
                         });
 
+                    let whole = cap.get(0).expect("capture group 0 always matches");
+                    let context = grep_link_context(&content, whole.start(), whole.end(), &text);
                     let link = InboundLink {
                         from: source_url_path.clone(),
                         text,
                         anchor,
+                        context,
+                        count: 1,
                     };
 
-                    if !inbound_links.contains(&link) {
-                        inbound_links.push(link);
-                        found_link = true;
-                    }
+                    inbound_links.push(link);
+                    found_link = true;
                 }
             }
 
@@ -659,15 +726,21 @@ pub fn find_inbound_links(
                     if let Ok(use_regex) = Regex::new(&use_pattern) {
                         for use_cap in use_regex.captures_iter(&content) {
                             let text = use_cap.get(1).map(|m| m.as_str()).unwrap_or("");
+                            let whole = use_cap.get(0).expect("capture group 0 always matches");
 
                             let link = InboundLink {
                                 from: source_url_path.clone(),
                                 text: text.to_string(),
                                 anchor: None,
+                                context: grep_link_context(
+                                    &content,
+                                    whole.start(),
+                                    whole.end(),
+                                    text,
+                                ),
+                                count: 1,
                             };
-                            if !inbound_links.contains(&link) {
-                                inbound_links.push(link);
-                            }
+                            inbound_links.push(link);
                         }
                     }
                 }
@@ -675,13 +748,9 @@ pub fn find_inbound_links(
         }
     }
 
-    // Deduplicate inbound links by source file - if a page links to the target
-    // multiple times, we only keep the first occurrence
-    let mut seen_sources: HashSet<String> = HashSet::new();
-    let deduplicated_links: Vec<InboundLink> = inbound_links
-        .into_iter()
-        .filter(|link| seen_sources.insert(link.from.clone()))
-        .collect();
+    // One entry per source file - if a page links to the target multiple
+    // times, the first occurrence is kept and the rest add to its count
+    let deduplicated_links = aggregate_inbound_by_source(inbound_links);
 
     tracing::debug!(
         "Scanned {} files for inbound links to {} in {:?}, found {}",
@@ -694,6 +763,38 @@ pub fn find_inbound_links(
     deduplicated_links
 }
 
+/// Matches wikilinks, inline links/images, and reference links so context
+/// snippets show their text rather than markdown syntax.
+static LINK_SYNTAX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\[([^\]|]*)(?:\|([^\]]*))?\]\]|!?\[([^\]]*)\]\([^)]*\)|\[([^\]]*)\]\[[^\]]*\]")
+        .expect("Invalid LINK_SYNTAX_RE regex pattern")
+});
+
+/// Replaces markdown link syntax in `text` with the link text.
+fn strip_link_syntax(text: &str) -> std::borrow::Cow<'_, str> {
+    LINK_SYNTAX_RE.replace_all(text, |caps: &regex::Captures| {
+        [2, 1, 3, 4]
+            .into_iter()
+            .find_map(|i| caps.get(i).filter(|m| !m.as_str().is_empty()))
+            .map_or("", |m| m.as_str())
+            .to_string()
+    })
+}
+
+/// Context for a link matched at `content[start..end]`, taken from the
+/// surrounding paragraph (text between blank lines).
+fn grep_link_context(content: &str, start: usize, end: usize, link_text: &str) -> Option<String> {
+    let paragraph_start = content[..start].rfind("\n\n").map_or(0, |i| i + 2);
+    let paragraph_end = content[end..]
+        .find("\n\n")
+        .map_or(content.len(), |i| end + i);
+    link_context(
+        &strip_link_syntax(&content[paragraph_start..start]),
+        link_text,
+        &strip_link_syntax(&content[end..paragraph_end]),
+    )
+}
+
 /// Gets the folder URL path from a file URL path.
 /// `/a/b/c/` -> `/a/b/`
 /// `/a/` -> `/`
@@ -738,6 +839,20 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    /// A search of `root_dir` with nothing ignored, following symlinks.
+    fn search<'a>(root_dir: &'a Path, markdown_extensions: &'a [String]) -> InboundLinkSearch<'a> {
+        InboundLinkSearch {
+            root_dir,
+            markdown_extensions,
+            ignore_dirs: &[],
+            ignore_globs: &[],
+            gitignore: None,
+            site_host: None,
+            heading_ids: SlugStyle::default(),
+            follow_symlinks: true,
+        }
+    }
+
     // ========== compute_relative_path tests ==========
 
     #[test]
@@ -793,7 +908,7 @@ mod tests {
 
     #[test]
     fn test_compute_patterns_for_folder_root() {
-        let patterns = compute_patterns_for_folder("/", "/a/b/c/1/");
+        let patterns = compute_patterns_for_folder("/", "/a/b/c/1/", None);
 
         // Should include absolute path variants
         assert!(patterns.contains(&"/a/b/c/1".to_string()));
@@ -810,7 +925,7 @@ mod tests {
 
     #[test]
     fn test_compute_patterns_for_folder_same_directory() {
-        let patterns = compute_patterns_for_folder("/a/b/", "/a/b/c/1/");
+        let patterns = compute_patterns_for_folder("/a/b/", "/a/b/c/1/", None);
 
         // Should include absolute path
         assert!(patterns.contains(&"/a/b/c/1".to_string()));
@@ -822,7 +937,7 @@ mod tests {
 
     #[test]
     fn test_compute_patterns_for_folder_sibling() {
-        let patterns = compute_patterns_for_folder("/d/", "/a/b/c/1/");
+        let patterns = compute_patterns_for_folder("/d/", "/a/b/c/1/", None);
 
         // Should include absolute path
         assert!(patterns.contains(&"/a/b/c/1".to_string()));
@@ -832,9 +947,20 @@ mod tests {
         assert!(patterns.contains(&"../a/b/c/1/".to_string()));
     }
 
+    #[test]
+    fn test_compute_patterns_for_folder_site_host() {
+        let patterns = compute_patterns_for_folder("/d/", "/a/1/", None);
+        assert!(!patterns.iter().any(|p| p.contains("://")));
+
+        let patterns = compute_patterns_for_folder("/d/", "/a/1/", Some("example.com"));
+        assert!(patterns.contains(&"https://example.com/a/1".to_string()));
+        assert!(patterns.contains(&"http://example.com/a/1/".to_string()));
+        assert!(patterns.contains(&"https://example.com/a/1#".to_string()));
+    }
+
     #[test]
     fn test_compute_patterns_for_folder_deeper_sibling() {
-        let patterns = compute_patterns_for_folder("/d/e/", "/a/b/c/1/");
+        let patterns = compute_patterns_for_folder("/d/e/", "/a/b/c/1/", None);
 
         // Should include absolute path
         assert!(patterns.contains(&"/a/b/c/1".to_string()));
@@ -884,6 +1010,8 @@ mod tests {
             from: "/other/".to_string(),
             text: "Link text".to_string(),
             anchor: None,
+            context: None,
+            count: 1,
         }];
 
         cache.insert("/docs/".to_string(), links.clone());
@@ -902,6 +1030,8 @@ mod tests {
             from: "/other/".to_string(),
             text: "Link text".to_string(),
             anchor: None,
+            context: None,
+            count: 1,
         }];
 
         cache.insert("/docs/".to_string(), links.clone());
@@ -924,17 +1054,23 @@ mod tests {
             from: "/a/".to_string(),
             text: "A".to_string(),
             anchor: None,
+            context: None,
+            count: 1,
         }];
         let two = vec![
             InboundLink {
                 from: "/a/".to_string(),
                 text: "A".to_string(),
                 anchor: None,
+                context: None,
+                count: 1,
             },
             InboundLink {
                 from: "/b/".to_string(),
                 text: "B".to_string(),
                 anchor: None,
+                context: None,
+                count: 1,
             },
         ];
 
@@ -956,6 +1092,8 @@ mod tests {
             from: "/other/".to_string(),
             text: "Link".to_string(),
             anchor: None,
+            context: None,
+            count: 1,
         }];
 
         cache.insert("/docs/".to_string(), links);
@@ -981,22 +1119,45 @@ mod tests {
         .unwrap();
 
         let extensions = vec!["md".to_string()];
-        let ignore_dirs: Vec<String> = vec![];
-        let ignore_globs: Vec<String> = vec![];
 
-        let links = find_inbound_links(
-            "/target/",
-            temp_dir.path(),
-            &extensions,
-            &ignore_dirs,
-            &ignore_globs,
-        );
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &extensions));
 
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].from, "/source/");
         assert_eq!(links[0].text, "link to target");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_find_inbound_links_symlink_cycle() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("notes")).unwrap();
+        fs::write(temp_dir.path().join("target.md"), "# Target").unwrap();
+        fs::write(
+            temp_dir.path().join("notes/source.md"),
+            "See [target](/target/).",
+        )
+        .unwrap();
+        // Links back to its own parent: following it never ends on its own
+        std::os::unix::fs::symlink("..", temp_dir.path().join("notes/loop")).unwrap();
+
+        let find = |follow_symlinks| {
+            find_inbound_links(
+                "/target/",
+                &InboundLinkSearch {
+                    follow_symlinks,
+                    ..search(temp_dir.path(), &["md".to_string()])
+                },
+            )
+        };
+
+        for follow_symlinks in [true, false] {
+            let links = find(follow_symlinks);
+            assert_eq!(links.len(), 1, "follow_symlinks = {follow_symlinks}");
+            assert_eq!(links[0].from, "/notes/source/");
+        }
+    }
+
     #[test]
     fn test_find_inbound_links_with_anchor() {
         let temp_dir = TempDir::new().unwrap();
@@ -1011,19 +1172,97 @@ mod tests {
         )
         .unwrap();
 
-        let links = find_inbound_links("/target/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
 
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].anchor, Some("#section".to_string()));
     }
 
+    #[test]
+    fn test_find_inbound_links_counts_repeats_per_source() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("target.md"), "# Target").unwrap();
+        fs::write(
+            temp_dir.path().join("source.md"),
+            "[first](target/) then [second](target/#part) and [[target]].",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("other.md"), "[once](target/)").unwrap();
+
+        let mut links =
+            find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
+        links.sort_by(|a, b| a.from.cmp(&b.from));
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].from, "/other/");
+        assert_eq!(links[0].count, 1);
+        assert_eq!(links[1].from, "/source/");
+        assert_eq!(links[1].text, "first");
+        assert_eq!(links[1].anchor, None);
+        assert_eq!(links[1].count, 3);
+    }
+
+    #[test]
+    fn test_find_inbound_links_context() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("target.md"), "# Target").unwrap();
+        fs::write(
+            temp_dir.path().join("source.md"),
+            "# Source\n\nCompare [[other]] with the [target page](target/) before\ndeciding.\n\nUnrelated paragraph.",
+        )
+        .unwrap();
+
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(
+            links[0].context.as_deref(),
+            Some("Compare other with the target page before deciding.")
+        );
+    }
+
+    #[test]
+    fn test_find_inbound_links_full_site_url() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("docs/target.md"), "# Target").unwrap();
+        fs::write(
+            temp_dir.path().join("source.md"),
+            "Read [the guide](https://notes.example.com/docs/target/#setup).",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("elsewhere.md"),
+            "Not ours: [mirror](https://other.example.com/docs/target/).",
+        )
+        .unwrap();
+
+        let find = |site_host| {
+            find_inbound_links(
+                "/docs/target/",
+                &InboundLinkSearch {
+                    site_host,
+                    ..search(temp_dir.path(), &["md".to_string()])
+                },
+            )
+        };
+
+        assert!(find(None).is_empty(), "full URLs need site_host");
+
+        let links = find(Some("notes.example.com"));
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].from, "/source/");
+        assert_eq!(links[0].text, "the guide");
+        assert_eq!(links[0].anchor.as_deref(), Some("#setup"));
+    }
+
     #[test]
     fn test_find_inbound_links_wiki_style_basic() {
         let temp_dir = TempDir::new().unwrap();
         fs::write(temp_dir.path().join("Japan.md"), "# Japan").unwrap();
         fs::write(temp_dir.path().join("source.md"), "See also: [[Japan]]").unwrap();
 
-        let links = find_inbound_links("/Japan/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/Japan/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].from, "/source/");
         assert_eq!(links[0].text, "Japan");
@@ -1039,7 +1278,7 @@ mod tests {
         )
         .unwrap();
 
-        let links = find_inbound_links("/Japan/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/Japan/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].text, "the Land of the Rising Sun");
     }
@@ -1048,11 +1287,16 @@ mod tests {
     fn test_find_inbound_links_wiki_style_with_anchor() {
         let temp_dir = TempDir::new().unwrap();
         fs::write(temp_dir.path().join("Japan.md"), "# Japan").unwrap();
-        fs::write(temp_dir.path().join("source.md"), "See [[Japan#History]].").unwrap();
+        fs::write(
+            temp_dir.path().join("source.md"),
+            "See [[Japan#Early History]].",
+        )
+        .unwrap();
 
-        let links = find_inbound_links("/Japan/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/Japan/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 1);
-        assert_eq!(links[0].anchor, Some("#History".to_string()));
+        // Slugified to match the heading ID the anchor names
+        assert_eq!(links[0].anchor, Some("#early-history".to_string()));
     }
 
     #[test]
@@ -1065,7 +1309,7 @@ mod tests {
         )
         .unwrap();
 
-        let links = find_inbound_links("/Japan/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/Japan/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 1);
     }
 
@@ -1080,9 +1324,11 @@ mod tests {
         .unwrap();
 
         // Even though source.md links to target via both markdown and wiki syntax,
-        // we deduplicate by source file - only one inbound link per source page
-        let links = find_inbound_links("/target/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        // we aggregate by source file - one inbound link per source page, counted
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 1);
+        assert_eq!(links[0].text, "standard");
+        assert_eq!(links[0].count, 2);
     }
 
     #[test]
@@ -1097,7 +1343,7 @@ mod tests {
         .unwrap();
 
         // Two different source files linking to the same target = two inbound links
-        let links = find_inbound_links("/target/", temp_dir.path(), &["md".to_string()], &[], &[]);
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
         assert_eq!(links.len(), 2);
     }
 
@@ -1122,10 +1368,7 @@ mod tests {
 
         let links = find_inbound_links(
             "/coins/tricks/3-fly/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
@@ -1154,10 +1397,7 @@ mod tests {
 
         let links = find_inbound_links(
             "/coins/tricks/3-fly/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
@@ -1186,10 +1426,7 @@ mod tests {
 
         let links = find_inbound_links(
             "/coins/tricks/3-fly/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
@@ -1217,10 +1454,7 @@ mod tests {
 
         let links = find_inbound_links(
             "/a/b/c/target/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
@@ -1244,10 +1478,7 @@ mod tests {
 
         let links = find_inbound_links(
             "/coins/tricks/3-fly/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
@@ -1271,13 +1502,39 @@ mod tests {
 
         let links = find_inbound_links(
             "/coins/tricks/3-fly/",
-            temp_dir.path(),
-            &["md".to_string()],
-            &[],
-            &[],
+            &search(temp_dir.path(), &["md".to_string()]),
         );
 
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].from, "/coins/index/");
     }
+
+    #[test]
+    fn test_find_inbound_links_skips_gitignored_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "vendor/\n").unwrap();
+        fs::write(temp_dir.path().join("target.md"), "# Target").unwrap();
+        fs::write(temp_dir.path().join("source.md"), "See [link](target/).").unwrap();
+        fs::create_dir_all(temp_dir.path().join("vendor")).unwrap();
+        fs::write(
+            temp_dir.path().join("vendor").join("copy.md"),
+            "See [link](/target/).",
+        )
+        .unwrap();
+
+        let gitignore = GitignoreFilter::load(temp_dir.path(), "static").unwrap();
+        let links = find_inbound_links(
+            "/target/",
+            &InboundLinkSearch {
+                gitignore: Some(&gitignore),
+                ..search(temp_dir.path(), &["md".to_string()])
+            },
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].from, "/source/");
+
+        // Without the gitignore filter the vendored copy is found too.
+        let links = find_inbound_links("/target/", &search(temp_dir.path(), &["md".to_string()]));
+        assert_eq!(links.len(), 2);
+    }
 }
@@ -5,6 +5,7 @@
 
 use papaya::HashMap as ConcurrentHashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 
@@ -20,6 +21,10 @@ pub struct OutboundLink {
     pub anchor: Option<String>,
     /// Whether this is an internal link (true) or external (false)
     pub internal: bool,
+    /// Text surrounding the link in its block, carried over to the target's
+    /// inbound link in static builds. Not serialized with outbound links.
+    #[serde(skip)]
+    pub context: Option<String>,
 }
 
 /// An inbound link pointing to a markdown page.
@@ -32,6 +37,112 @@ pub struct InboundLink {
     /// Optional anchor fragment targeted
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    /// About 100 characters of text surrounding the link, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// How many times the source page links here
+    #[serde(default = "default_inbound_count")]
+    pub count: usize,
+}
+
+fn default_inbound_count() -> usize {
+    1
+}
+
+/// Merges inbound links from the same source page into one entry.
+///
+/// The first link from each source keeps its text, anchor, and context; its
+/// `count` is increased by the counts of the later ones. Source order is kept.
+pub fn aggregate_inbound_by_source(links: Vec<InboundLink>) -> Vec<InboundLink> {
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut aggregated: Vec<InboundLink> = Vec::with_capacity(links.len());
+    for link in links {
+        match positions.get(&link.from) {
+            Some(&i) => aggregated[i].count += link.count,
+            None => {
+                positions.insert(link.from.clone(), aggregated.len());
+                aggregated.push(link);
+            }
+        }
+    }
+    aggregated
+}
+
+/// Characters of surrounding text kept on each side of a link's context.
+const CONTEXT_RADIUS_CHARS: usize = 50;
+
+/// Builds the context snippet for a link from the text before it, the link
+/// text itself, and the text after it.
+///
+/// Keeps up to [`CONTEXT_RADIUS_CHARS`] characters on each side (cut on word
+/// boundaries and marked with `…` when trimmed) and collapses whitespace.
+/// Returns `None` when there is no surrounding text, since the context would
+/// only repeat the link text.
+pub fn link_context(before: &str, link_text: &str, after: &str) -> Option<String> {
+    if before.trim().is_empty() && after.trim().is_empty() {
+        return None;
+    }
+
+    // Last CONTEXT_RADIUS_CHARS characters of `before`, starting at a word
+    let mut start = before
+        .char_indices()
+        .rev()
+        .nth(CONTEXT_RADIUS_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    if start > 0
+        && let Some(space) = before[start..].find(char::is_whitespace)
+    {
+        start += space;
+    }
+
+    // First CONTEXT_RADIUS_CHARS characters of `after`, ending at a word
+    let mut end = after
+        .char_indices()
+        .nth(CONTEXT_RADIUS_CHARS)
+        .map_or(after.len(), |(i, _)| i);
+    if end < after.len()
+        && let Some(space) = after[..end].rfind(char::is_whitespace)
+    {
+        end = space;
+    }
+
+    let mut context = String::new();
+    if start > 0 {
+        context.push('…');
+    }
+    context.push_str(&before[start..]);
+    context.push_str(link_text);
+    context.push_str(&after[..end]);
+    let mut context = context.split_whitespace().collect::<Vec<_>>().join(" ");
+    if end < after.len() {
+        context.push('…');
+    }
+    Some(context)
+}
+
+/// Which `links.json` files a static build writes (`config.link_index_mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkIndexMode {
+    /// One `links.json` next to each page.
+    #[default]
+    PerPage,
+    /// A single `.mbr/links.json` mapping each page URL to its links.
+    Consolidated,
+    /// Both the per-page files and the consolidated index.
+    Both,
+}
+
+impl LinkIndexMode {
+    /// True if a `links.json` should be written next to each page.
+    pub fn writes_per_page(self) -> bool {
+        matches!(self, Self::PerPage | Self::Both)
+    }
+
+    /// True if the site-wide `.mbr/links.json` should be written.
+    pub fn writes_consolidated(self) -> bool {
+        matches!(self, Self::Consolidated | Self::Both)
+    }
 }
 
 /// Links data for a single page (used in API responses).
@@ -361,6 +472,71 @@ impl LinkCache {
 mod tests {
     use super::*;
 
+    fn inbound(from: &str, text: &str) -> InboundLink {
+        InboundLink {
+            from: from.to_string(),
+            text: text.to_string(),
+            anchor: None,
+            context: None,
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_inbound_by_source() {
+        let links = vec![
+            inbound("/a/", "first"),
+            inbound("/b/", "other"),
+            inbound("/a/", "second"),
+            inbound("/a/", "third"),
+        ];
+        let aggregated = aggregate_inbound_by_source(links);
+        assert_eq!(aggregated.len(), 2);
+        assert_eq!(aggregated[0].from, "/a/");
+        assert_eq!(aggregated[0].text, "first");
+        assert_eq!(aggregated[0].count, 3);
+        assert_eq!(aggregated[1].from, "/b/");
+        assert_eq!(aggregated[1].count, 1);
+    }
+
+    #[test]
+    fn test_inbound_count_defaults_to_one() {
+        let link: InboundLink = serde_json::from_str(r#"{"from":"/a/","text":"x"}"#).unwrap();
+        assert_eq!(link.count, 1);
+    }
+
+    #[test]
+    fn test_link_context_short_block() {
+        assert_eq!(
+            link_context("See the ", "install guide", " first.").as_deref(),
+            Some("See the install guide first.")
+        );
+        assert_eq!(link_context("", "Guide", "  "), None);
+    }
+
+    #[test]
+    fn test_link_context_trims_on_word_boundaries() {
+        let before = "word ".repeat(30);
+        let after = " más texto aquí".repeat(10);
+        let context = link_context(&before, "target", &after).unwrap();
+        assert_eq!(
+            context,
+            format!(
+                "… {}target{} más…",
+                "word ".repeat(9),
+                " más texto aquí".repeat(3)
+            )
+        );
+    }
+
+    #[test]
+    fn test_link_context_multibyte_boundaries() {
+        let before = "日本語".repeat(40);
+        let context = link_context(&before, "リンク", "。").unwrap();
+        assert!(context.starts_with('…'));
+        assert!(context.ends_with("リンク。"));
+    }
+
     #[test]
     fn test_split_url_anchor_with_anchor() {
         let (path, anchor) = split_url_anchor("/docs/guide/#section");
@@ -437,6 +613,7 @@ mod tests {
             text: "Other Page".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         cache.insert("/docs/".to_string(), links.clone());
@@ -462,6 +639,7 @@ mod tests {
             text: "Other Page".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         cache.insert("/docs/".to_string(), links.clone());
@@ -485,6 +663,7 @@ mod tests {
             text: "A".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
         let two = vec![
             OutboundLink {
@@ -492,12 +671,14 @@ mod tests {
                 text: "A".to_string(),
                 anchor: None,
                 internal: true,
+                context: None,
             },
             OutboundLink {
                 to: "/b/".to_string(),
                 text: "B".to_string(),
                 anchor: None,
                 internal: true,
+                context: None,
             },
         ];
 
@@ -521,6 +702,7 @@ mod tests {
             text: "Other".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         cache.insert("/docs/".to_string(), links);
@@ -534,6 +716,7 @@ mod tests {
             text: "Guide".to_string(),
             anchor: Some("#intro".to_string()),
             internal: true,
+            context: None,
         };
 
         let json = serde_json::to_string(&link).unwrap();
@@ -550,6 +733,7 @@ mod tests {
             text: "Docs".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         };
 
         let json = serde_json::to_string(&link).unwrap();
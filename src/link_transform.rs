@@ -17,6 +17,68 @@
 //! 1. Adding `../` prefix for regular markdown files (not index files)
 //! 2. Replacing markdown extensions with trailing slash
 //! 3. Collapsing index file references to their directory
+//!
+//! Raw HTML written in a page (`<img src="images/x.png">`) gets the same
+//! treatment through [`transform_html_links`], so it resolves like the
+//! equivalent markdown link.
+//!
+//! A separate post-render pass, [`decorate_external_links`], marks links that
+//! leave the site so they open in a new tab. Builds with `clean_urls` off
+//! run [`rewrite_directory_links`] over every page so folder-style links name
+//! the file that answers them.
+//!
+//! ## Encoding
+//!
+//! Page URLs come from file names, which may hold spaces, unicode or
+//! characters with a meaning in URLs (`my note.md`, `café.md`, `c#.md`).
+//! Generated hrefs carry the percent-encoded form from [`encode_url_path`];
+//! [`transform_link`] re-encodes link paths the same way whether the author
+//! wrote `my%20note.md` or `<my note.md>`, and anything touching the
+//! filesystem decodes them first.
+
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static ANCHOR_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<a\s[^>]*>").expect("Invalid ANCHOR_TAG_RE regex pattern"));
+static HREF_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\shref\s*=\s*"([^"]*)""#).expect("Invalid HREF_ATTR_RE regex pattern")
+});
+static HTML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<[a-z][a-z0-9-]*\s[^>]*>").expect("Invalid HTML_TAG_RE regex pattern")
+});
+static URL_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(\s(?:href|src|poster)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#)
+        .expect("Invalid URL_ATTR_RE regex pattern")
+});
+static TARGET_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\starget\s*="#).expect("Invalid TARGET_ATTR_RE regex pattern")
+});
+static REL_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\srel\s*=\s*"([^"]*)""#).expect("Invalid REL_ATTR_RE regex pattern")
+});
+static CLASS_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\sclass\s*=\s*"([^"]*)""#).expect("Invalid CLASS_ATTR_RE regex pattern")
+});
+
+/// Characters escaped in generated URL paths. `/` is kept so the path
+/// structure survives; non-ASCII is always escaped.
+const URL_PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'\'')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Class added to decorated external links (styled with an "external" icon).
+pub const EXTERNAL_LINK_CLASS: &str = "mbr-external-link";
 
 /// Configuration for link transformation.
 #[derive(Debug, Clone)]
@@ -37,6 +99,11 @@ pub struct LinkTransformConfig {
     /// current page context (e.g. CLI/QuickLook paths, which never resolve
     /// wikilinks globally).
     pub current_page_url: String,
+    /// Whether pages are served at trailing-slash URLs (`/docs/guide/`).
+    /// When false (builds with `clean_urls = false`), each page is a
+    /// `guide.html` file beside its source, so relative links get no extra
+    /// `../` and markdown links point at `.html` files.
+    pub clean_urls: bool,
 }
 
 impl Default for LinkTransformConfig {
@@ -47,6 +114,7 @@ impl Default for LinkTransformConfig {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         }
     }
 }
@@ -62,6 +130,9 @@ impl Default for LinkTransformConfig {
 /// 5. Relative markdown links → prepend `../` (if not index file), replace extension with `/`
 /// 6. Relative static files → prepend `../` (if not index file)
 ///
+/// With `clean_urls` off, no `../` is prepended and markdown links point at
+/// the page's `.html` file (`index.html` for index files) instead.
+///
 /// # Examples
 ///
 /// ```
@@ -73,6 +144,7 @@ impl Default for LinkTransformConfig {
 ///     is_index_file: false,
 ///     url_depth: None,
 ///     current_page_url: String::new(),
+///     clean_urls: true,
 /// };
 ///
 /// // Regular markdown file: add ../ and trailing slash
@@ -84,6 +156,10 @@ impl Default for LinkTransformConfig {
 ///
 /// // Absolute URLs unchanged
 /// assert_eq!(transform_link("https://example.com", &config), "https://example.com");
+///
+/// // Flat .html output
+/// let flat_config = LinkTransformConfig { clean_urls: false, ..config.clone() };
+/// assert_eq!(transform_link("other.md", &flat_config), "other.html");
 /// ```
 pub fn transform_link(url: &str, config: &LinkTransformConfig) -> String {
     // Empty or whitespace-only
@@ -103,9 +179,11 @@ pub fn transform_link(url: &str, config: &LinkTransformConfig) -> String {
 
     // Root-relative URLs — convert to relative in build mode
     if url.starts_with('/') {
+        let (path, suffix) = split_url_parts(url);
+        let url = format!("{}{}", normalize_encoding(&path), suffix);
         return match config.url_depth {
-            Some(depth) => make_relative_url(url, depth),
-            None => url.to_string(),
+            Some(depth) => make_relative_url(&url, depth),
+            None => url,
         };
     }
 
@@ -131,19 +209,23 @@ pub fn transform_link(url: &str, config: &LinkTransformConfig) -> String {
         return url.to_string();
     }
 
-    // Normalize: strip leading "./"
+    // Normalize: consistent percent-encoding, strip leading "./"
+    let path = normalize_encoding(&path);
     let path = path.strip_prefix("./").unwrap_or(&path);
 
     // Count and strip existing "../" prefixes
     let (parent_count, remaining_path) = count_parent_traversals(path);
 
+    // Pages at trailing-slash URLs sit one level below their source folder,
+    // except index files (and every page in flat .html output)
+    let prefix = if config.is_index_file || !config.clean_urls {
+        "../".repeat(parent_count)
+    } else {
+        "../".repeat(parent_count + 1)
+    };
+
     // If nothing remains after stripping ../, just return with adjusted parents
     if remaining_path.is_empty() {
-        let prefix = if config.is_index_file {
-            "../".repeat(parent_count)
-        } else {
-            "../".repeat(parent_count + 1)
-        };
         return format!("{}{}", prefix, suffix);
     }
 
@@ -162,21 +244,17 @@ pub fn transform_link(url: &str, config: &LinkTransformConfig) -> String {
                 .strip_suffix(index_stem)
                 .unwrap_or(base_path)
                 .trim_end_matches('/');
-            if stripped.is_empty() {
+            match (stripped.is_empty(), config.clean_urls) {
                 // Just "index.md" -> "./" for index files, "../" for regular
-                "".to_string()
-            } else {
-                format!("{}/", stripped)
+                (true, true) => "".to_string(),
+                (true, false) => "index.html".to_string(),
+                (false, true) => format!("{}/", stripped),
+                (false, false) => format!("{}/index.html", stripped),
             }
-        } else {
+        } else if config.clean_urls {
             format!("{}/", base_path)
-        };
-
-        // Build prefix based on parent count and whether current file is index
-        let prefix = if config.is_index_file {
-            "../".repeat(parent_count)
         } else {
-            "../".repeat(parent_count + 1)
+            format!("{}.html", base_path)
         };
 
         // Handle edge case: if final_path is empty and we have no prefix, use "./"
@@ -188,15 +266,29 @@ pub fn transform_link(url: &str, config: &LinkTransformConfig) -> String {
     }
 
     // Static file: just add ../ prefix
-    let prefix = if config.is_index_file {
-        "../".repeat(parent_count)
-    } else {
-        "../".repeat(parent_count + 1)
-    };
-
     format!("{}{}{}", prefix, remaining_path, suffix)
 }
 
+/// Percent-encodes a human-readable URL path for use in an href
+/// (`/my note/` -> `/my%20note/`).
+///
+/// ```
+/// use mbr::link_transform::encode_url_path;
+///
+/// assert_eq!(encode_url_path("/my note/"), "/my%20note/");
+/// assert_eq!(encode_url_path("/café/"), "/caf%C3%A9/");
+/// assert_eq!(encode_url_path("/c#/100%/"), "/c%23/100%25/");
+/// ```
+pub fn encode_url_path(path: &str) -> String {
+    utf8_percent_encode(path, URL_PATH_ENCODE_SET).to_string()
+}
+
+/// Re-encodes a link path as [`encode_url_path`] would, whether or not the
+/// author already percent-encoded it.
+pub(crate) fn normalize_encoding(path: &str) -> String {
+    encode_url_path(&percent_decode_str(path).decode_utf8_lossy())
+}
+
 /// Check if a URL is absolute (has protocol or is protocol-relative).
 fn is_absolute_url(url: &str) -> bool {
     url.starts_with("http://")
@@ -275,6 +367,159 @@ pub fn make_relative_url(absolute_url: &str, depth: usize) -> String {
     }
 }
 
+/// Marks external links in rendered HTML to open in a new tab: adds
+/// `target="_blank"`, `rel="noopener noreferrer"` and the
+/// [`EXTERNAL_LINK_CLASS`] class to each `<a>` whose `href` is an
+/// `http(s)://` URL on a host other than `base_url`'s.
+///
+/// Runs after rendering, so the relative and root-relative links produced by
+/// [`transform_link`] (and `#anchor` links) are never touched. An existing
+/// `target` is kept, and existing `rel` and `class` values are extended.
+pub fn decorate_external_links(html: &str, base_url: Option<&str>) -> String {
+    let site_host = base_url
+        .and_then(|base| url::Url::parse(base).ok())
+        .and_then(|base| base.host_str().map(str::to_ascii_lowercase));
+
+    ANCHOR_TAG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let tag = &caps[0];
+            let Some(href) = HREF_ATTR_RE.captures(tag) else {
+                return tag.to_string();
+            };
+            let href = html_escape::decode_html_entities(&href[1]);
+            let Ok(url) = url::Url::parse(href.trim()) else {
+                return tag.to_string();
+            };
+            let is_external = matches!(url.scheme(), "http" | "https")
+                && url.host_str().is_some_and(|host| {
+                    site_host
+                        .as_deref()
+                        .is_none_or(|site| !host.eq_ignore_ascii_case(site))
+                });
+            if !is_external {
+                return tag.to_string();
+            }
+
+            let mut tag = tag.to_string();
+            let mut added = String::new();
+            if !TARGET_ATTR_RE.is_match(&tag) {
+                added.push_str(r#" target="_blank""#);
+            }
+            match extend_attr_tokens(&tag, &REL_ATTR_RE, &["noopener", "noreferrer"]) {
+                Some(extended) => tag = extended,
+                None => added.push_str(r#" rel="noopener noreferrer""#),
+            }
+            match extend_attr_tokens(&tag, &CLASS_ATTR_RE, &[EXTERNAL_LINK_CLASS]) {
+                Some(extended) => tag = extended,
+                None => added.push_str(&format!(r#" class="{EXTERNAL_LINK_CLASS}""#)),
+            }
+            // Insert before the closing `>`
+            tag.insert_str(tag.len() - 1, &added);
+            tag
+        })
+        .into_owned()
+}
+
+/// Applies [`transform_link`] to the `href`, `src` and `poster` attributes
+/// of raw HTML from a markdown page, so `<img src="images/x.png">` in
+/// `docs/guide.md` becomes `../images/x.png` like `![](images/x.png)` does.
+///
+/// Only whole tags are rewritten; a tag split across several raw HTML events
+/// is left as written.
+pub fn transform_html_links(html: &str, config: &LinkTransformConfig) -> String {
+    rewrite_url_attrs(html, |url| transform_link(url, config))
+}
+
+/// Makes the `href`, `src` and `poster` URLs in rendered HTML absolute
+/// against `page_url`, the public URL of the page, so the HTML still works
+/// away from the site (e.g. in a feed reader). URLs that are already
+/// absolute are left as written.
+pub fn absolute_html_links(html: &str, page_url: &url::Url) -> String {
+    rewrite_url_attrs(html, |url| {
+        if url::Url::parse(url).is_ok() {
+            return url.to_string();
+        }
+        page_url
+            .join(url)
+            .map_or_else(|_| url.to_string(), String::from)
+    })
+}
+
+/// Replaces each `href`, `src` and `poster` value in the tags of `html` with
+/// `rewrite` of its decoded value, re-encoded for the attribute.
+fn rewrite_url_attrs(html: &str, rewrite: impl Fn(&str) -> String) -> String {
+    HTML_TAG_RE
+        .replace_all(html, |tag: &regex::Captures| {
+            URL_ATTR_RE
+                .replace_all(&tag[0], |caps: &regex::Captures| {
+                    let (raw, quote) = match caps.get(2) {
+                        Some(value) => (value.as_str(), '"'),
+                        None => (caps.get(3).map_or("", |m| m.as_str()), '\''),
+                    };
+                    let url = html_escape::decode_html_entities(raw);
+                    let transformed = rewrite(&url);
+                    if transformed == url {
+                        return caps[0].to_string();
+                    }
+                    let value = if quote == '"' {
+                        html_escape::encode_double_quoted_attribute(&transformed)
+                    } else {
+                        html_escape::encode_single_quoted_attribute(&transformed)
+                    };
+                    format!("{}{quote}{value}{quote}", &caps[1])
+                })
+                .into_owned()
+        })
+        .into_owned()
+}
+
+/// Rewrites each trailing-slash `href` in rendered HTML (`../docs/guide/`)
+/// for flat `.html` output. `resolve` receives the decoded path, without any
+/// query or fragment, and returns its replacement (e.g. `../docs/guide.html`
+/// or `../docs/index.html`), or `None` to leave the link alone. Absolute
+/// URLs are never passed to it.
+pub fn rewrite_directory_links(html: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+    HREF_ATTR_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let whole = &caps[0];
+            let href = html_escape::decode_html_entities(&caps[1]);
+            let (path, suffix) = split_url_parts(&href);
+            if !path.ends_with('/') || is_absolute_url(&path) || path.contains(':') {
+                return whole.to_string();
+            }
+            let Some(new_path) = resolve(&path) else {
+                return whole.to_string();
+            };
+            let value = caps.get(1).map_or(0..0, |m| m.range());
+            let offset = caps.get(0).map_or(0, |m| m.start());
+            format!(
+                "{}{}{}",
+                &whole[..value.start - offset],
+                html_escape::encode_double_quoted_attribute(&format!("{new_path}{suffix}")),
+                &whole[value.end - offset..]
+            )
+        })
+        .into_owned()
+}
+
+/// Adds any `tokens` missing from the space-separated attribute value captured
+/// by `attr_re` (group 1). Returns `None` if the tag has no such attribute.
+fn extend_attr_tokens(tag: &str, attr_re: &Regex, tokens: &[&str]) -> Option<String> {
+    let value = attr_re.captures(tag)?.get(1)?;
+    let mut values: Vec<&str> = value.as_str().split_whitespace().collect();
+    for token in tokens {
+        if !values.iter().any(|v| v.eq_ignore_ascii_case(token)) {
+            values.push(token);
+        }
+    }
+    Some(format!(
+        "{}{}{}",
+        &tag[..value.start()],
+        values.join(" "),
+        &tag[value.end()..]
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,6 +531,7 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         }
     }
 
@@ -329,6 +575,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encoding_normalized() {
+        // Raw and pre-encoded spellings of a file name give the same href
+        for url in ["my note.md", "my%20note.md"] {
+            assert_eq!(transform_link(url, &regular_config()), "../my%20note/");
+        }
+        assert_eq!(
+            transform_link("café.md#top", &regular_config()),
+            "../caf%C3%A9/#top"
+        );
+        assert_eq!(transform_link("c%23.md", &regular_config()), "../c%23/");
+        assert_eq!(
+            transform_link("/docs/my note/", &regular_config()),
+            "/docs/my%20note/"
+        );
+        let build = LinkTransformConfig {
+            url_depth: Some(1),
+            ..regular_config()
+        };
+        assert_eq!(transform_link("/café/", &build), "../caf%C3%A9/");
+    }
+
     #[test]
     fn test_index_collapse() {
         assert_eq!(
@@ -669,6 +937,147 @@ mod tests {
         assert_eq!(make_relative_url("/docs/", 2), "../../docs/");
         assert_eq!(make_relative_url("/docs/guide/", 2), "../../docs/guide/");
     }
+
+    #[test]
+    fn test_decorate_external_links() {
+        let html = r#"<p><a href="https://example.com/a?x=1&amp;y=2">Ext</a></p>"#;
+        assert_eq!(
+            decorate_external_links(html, None),
+            r#"<p><a href="https://example.com/a?x=1&amp;y=2" target="_blank" rel="noopener noreferrer" class="mbr-external-link">Ext</a></p>"#
+        );
+    }
+
+    #[test]
+    fn test_decorate_external_links_leaves_internal_links() {
+        let html = concat!(
+            r#"<a href="../other/">Sibling</a>"#,
+            r#"<a href="/docs/">Root</a>"#,
+            r##"<a href="#intro">Anchor</a>"##,
+            r#"<a href="mailto:me@example.com">Mail</a>"#,
+            r#"<a href="https://Notes.Example.com/docs/">Own site</a>"#,
+        );
+        assert_eq!(
+            decorate_external_links(html, Some("https://notes.example.com/")),
+            html
+        );
+    }
+
+    #[test]
+    fn test_decorate_external_links_extends_existing_attributes() {
+        let html =
+            r#"<a class="oembed" rel="nofollow" target="_self" href="http://example.com/">x</a>"#;
+        assert_eq!(
+            decorate_external_links(html, Some("https://notes.example.com/")),
+            r#"<a class="oembed mbr-external-link" rel="nofollow noopener noreferrer" target="_self" href="http://example.com/">x</a>"#
+        );
+    }
+
+    #[test]
+    fn test_absolute_html_links() {
+        let page = url::Url::parse("https://notes.example.com/posts/setup/").unwrap();
+        let html = concat!(
+            r#"<p><a href="../../docs/guide/">Guide</a> "#,
+            r#"<img src="bench.png" alt=""> "#,
+            r#"<a href='/tags/?a=1&amp;b=2'>Tags</a> "#,
+            r##"<a href="#intro">Intro</a> "##,
+            r#"<a href="https://example.com/">Ext</a> "#,
+            r#"<a href="mailto:me@example.com">Mail</a></p>"#,
+        );
+        assert_eq!(
+            absolute_html_links(html, &page),
+            concat!(
+                r#"<p><a href="https://notes.example.com/docs/guide/">Guide</a> "#,
+                r#"<img src="https://notes.example.com/posts/setup/bench.png" alt=""> "#,
+                r#"<a href='https://notes.example.com/tags/?a=1&amp;b=2'>Tags</a> "#,
+                r##"<a href="https://notes.example.com/posts/setup/#intro">Intro</a> "##,
+                r#"<a href="https://example.com/">Ext</a> "#,
+                r#"<a href="mailto:me@example.com">Mail</a></p>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_flat_output_links() {
+        let config = LinkTransformConfig {
+            clean_urls: false,
+            ..regular_config()
+        };
+        assert_eq!(transform_link("other.md", &config), "other.html");
+        assert_eq!(
+            transform_link("../docs/guide.md#setup", &config),
+            "../docs/guide.html#setup"
+        );
+        assert_eq!(transform_link("sub/index.md", &config), "sub/index.html");
+        assert_eq!(transform_link("index.md", &config), "index.html");
+        // Pages sit beside their source, so static files need no extra ../
+        assert_eq!(transform_link("images/a.png", &config), "images/a.png");
+        assert_eq!(transform_link("../", &config), "../");
+    }
+
+    #[test]
+    fn test_transform_html_links_sibling_assets() {
+        let html = r#"<img src="images/x.png" alt="X"><a href='files/report.pdf'>PDF</a>"#;
+        // guide.md is served at /docs/guide/, one level below its folder
+        assert_eq!(
+            transform_html_links(html, &regular_config()),
+            r#"<img src="../images/x.png" alt="X"><a href='../files/report.pdf'>PDF</a>"#
+        );
+        // index.md is served at its folder's own URL
+        assert_eq!(transform_html_links(html, &index_config()), html);
+    }
+
+    #[test]
+    fn test_transform_html_links_matches_markdown_rules() {
+        let html = concat!(
+            r#"<video poster="../covers/a.jpg" src="clip.mp4"></video>"#,
+            r#"<a href="other.md#intro">Other</a>"#,
+            r#"<a href="https://example.com/x.png">Ext</a>"#,
+            r##"<a href="/root/">Root</a> <a href="#top">Top</a>"##,
+            r#"<a href="a.png?x=1&amp;y=2">Q</a>"#,
+        );
+        assert_eq!(
+            transform_html_links(html, &regular_config()),
+            concat!(
+                r#"<video poster="../../covers/a.jpg" src="../clip.mp4"></video>"#,
+                r#"<a href="../other/#intro">Other</a>"#,
+                r#"<a href="https://example.com/x.png">Ext</a>"#,
+                r##"<a href="/root/">Root</a> <a href="#top">Top</a>"##,
+                r#"<a href="../a.png?x=1&amp;y=2">Q</a>"#,
+            )
+        );
+        // Text outside tags is never touched
+        assert_eq!(
+            transform_html_links(r#"src="x.png""#, &regular_config()),
+            r#"src="x.png""#
+        );
+    }
+
+    #[test]
+    fn test_rewrite_directory_links() {
+        let html = concat!(
+            r#"<a href="..&#x2F;docs&#x2F;guide&#x2F;#intro">Guide</a>"#,
+            r#"<a href="../docs/">Docs</a>"#,
+            r#"<a href="https://example.com/">Ext</a>"#,
+            r#"<a href="image.png">File</a>"#,
+            r#"<a href="../missing/">Missing</a>"#,
+        );
+        let rewritten = rewrite_directory_links(html, |path| match path {
+            "../docs/guide/" => Some("../docs/guide.html".to_string()),
+            "../docs/" => Some("../docs/index.html".to_string()),
+            "https://example.com/" => panic!("absolute URLs aren't resolved"),
+            _ => None,
+        });
+        assert_eq!(
+            rewritten,
+            concat!(
+                r#"<a href="../docs/guide.html#intro">Guide</a>"#,
+                r#"<a href="../docs/index.html">Docs</a>"#,
+                r#"<a href="https://example.com/">Ext</a>"#,
+                r#"<a href="image.png">File</a>"#,
+                r#"<a href="../missing/">Missing</a>"#,
+            )
+        );
+    }
 }
 
 #[cfg(test)]
@@ -683,6 +1092,7 @@ mod proptests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         }
     }
 
@@ -0,0 +1,431 @@
+//! Content checks for `mbr --lint`.
+//!
+//! Each page is rendered the way a static build renders it and the HTML is
+//! checked for links with no target or text, images without alt text,
+//! headings that skip a level and repeated headings. Nothing is written.
+//! Checks can be turned off one by one in the `[lint]` config table.
+
+use crate::Config;
+use crate::errors::{MarkdownError, RepoError};
+use crate::link_transform::LinkTransformConfig;
+use crate::markdown::{self, RendererKind};
+use crate::repo::Repo;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+/// Which checks `mbr --lint` runs, as the `[lint]` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LintOptions {
+    /// Links with no target (`[text]()`) or no text (`[](page.md)`).
+    pub empty_links: bool,
+    /// Images with missing or blank alt text.
+    pub missing_alt: bool,
+    /// Headings more than one level below the previous heading (`#` then `###`).
+    pub heading_levels: bool,
+    /// Headings with the same text as an earlier one on the page.
+    pub duplicate_headings: bool,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            empty_links: true,
+            missing_alt: true,
+            heading_levels: true,
+            duplicate_headings: true,
+        }
+    }
+}
+
+/// The kind of problem a [`LintIssue`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCheck {
+    EmptyLink,
+    MissingAlt,
+    HeadingLevel,
+    DuplicateHeading,
+}
+
+impl LintCheck {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LintCheck::EmptyLink => "empty_link",
+            LintCheck::MissingAlt => "missing_alt",
+            LintCheck::HeadingLevel => "heading_level",
+            LintCheck::DuplicateHeading => "duplicate_heading",
+        }
+    }
+}
+
+/// One problem found in a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    /// Source file, relative to the root directory.
+    pub file: PathBuf,
+    /// URL of the page.
+    pub url: String,
+    pub check: LintCheck,
+    /// Anchor of the heading the problem is under (the heading itself for
+    /// heading checks); `None` before the first heading.
+    pub section: Option<String>,
+    pub message: String,
+}
+
+/// Problems found across a repository.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    /// Pages checked.
+    pub pages: usize,
+    /// Number of entries in `issues`.
+    pub issue_count: usize,
+    /// Issues sorted by file, in page order within a file.
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// Scans the repository at `config.root_dir` and lints every page.
+    pub fn scan(config: &Config) -> Result<Self, RepoError> {
+        let repo = Repo::init_from_config(config);
+        repo.scan_all()?;
+        repo.build_wikilink_index();
+        Ok(Self::from_repo(&repo, config))
+    }
+
+    /// Lints the pages of an already scanned repository.
+    pub fn from_repo(repo: &Repo, config: &Config) -> Self {
+        use rayon::prelude::*;
+
+        let mut pages: Vec<(PathBuf, String)> = repo
+            .markdown_files
+            .pin()
+            .iter()
+            .map(|(path, info)| (path.clone(), info.url_path.clone()))
+            .collect();
+        pages.sort();
+        let data_files = config.data_folder.as_ref().and_then(|folder| {
+            crate::data::DataFiles::load(&config.root_dir.join(folder))
+                .inspect_err(|e| tracing::warn!("lint: couldn't load data files: {e}"))
+                .ok()
+                .map(Arc::new)
+        });
+
+        let issues: Vec<LintIssue> = pages
+            .par_iter()
+            .flat_map_iter(|(path, url)| {
+                let html = match render_page(repo, config, data_files.clone(), path, url) {
+                    Ok(html) => html,
+                    Err(e) => {
+                        tracing::warn!("lint: skipping {}: {e}", path.display());
+                        String::new()
+                    }
+                };
+                let file = path.strip_prefix(&config.root_dir).unwrap_or(path);
+                lint_html(&html, &config.lint)
+                    .into_iter()
+                    .map(|finding| LintIssue {
+                        file: file.to_path_buf(),
+                        url: url.clone(),
+                        check: finding.check,
+                        section: finding.section,
+                        message: finding.message,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        LintReport {
+            pages: pages.len(),
+            issue_count: issues.len(),
+            issues,
+        }
+    }
+
+    /// Human-readable report, one issue per line, then a total.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for issue in &self.issues {
+            let anchor = issue
+                .section
+                .as_ref()
+                .map(|id| format!("#{id}"))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "{}{anchor}: {} ({})",
+                issue.file.display(),
+                issue.message,
+                issue.check.as_str()
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{} issue(s) in {} page(s)",
+            self.issue_count, self.pages
+        );
+        out
+    }
+}
+
+/// Renders a page body as a static build would, without the page template.
+fn render_page(
+    repo: &Repo,
+    config: &Config,
+    data_files: Option<Arc<crate::data::DataFiles>>,
+    path: &Path,
+    url: &str,
+) -> Result<String, MarkdownError> {
+    let is_index_file = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .is_some_and(|f| f == config.index_file);
+    let transform = LinkTransformConfig {
+        markdown_extensions: config.markdown_extensions.clone(),
+        index_file: config.index_file.clone(),
+        is_index_file,
+        url_depth: None,
+        current_page_url: url.to_string(),
+        clean_urls: true,
+    };
+    markdown::render_sync(
+        path.to_path_buf(),
+        &config.root_dir,
+        0, // no network fetches
+        config.oembed_fallback,
+        transform,
+        None,
+        false,
+        false,
+        crate::config::tag_sources_to_set(&config.tag_sources),
+        false,
+        &config.incomplete_markers,
+        Some(repo.wikilink_index.clone()),
+        data_files,
+        RendererKind::for_path(path, &config.renderers),
+        &config.markdown_options,
+    )
+    .map(|result| result.html)
+}
+
+/// A problem found by [`lint_html`], before it's tied to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub check: LintCheck,
+    pub section: Option<String>,
+    pub message: String,
+}
+
+static CHECKED_ELEMENTS: LazyLock<Selector> = LazyLock::new(|| {
+    Selector::parse("h1, h2, h3, h4, h5, h6, a, img").expect("valid lint selector")
+});
+
+static IMAGES: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("img").expect("valid image selector"));
+
+/// Runs the enabled checks over a rendered page body, in document order.
+pub fn lint_html(html: &str, options: &LintOptions) -> Vec<LintFinding> {
+    let document = Html::parse_fragment(html);
+    let mut findings = Vec::new();
+    let mut section: Option<String> = None;
+    let mut previous_level: Option<u8> = None;
+    let mut seen_headings: HashSet<String> = HashSet::new();
+
+    for element in document.select(&CHECKED_ELEMENTS) {
+        let mut report = |check, message: String, section: &Option<String>| {
+            findings.push(LintFinding {
+                check,
+                section: section.clone(),
+                message,
+            });
+        };
+        match element.value().name() {
+            "a" => {
+                if !options.empty_links {
+                    continue;
+                }
+                let text = element_text(element);
+                let href = element.value().attr("href").unwrap_or("").trim();
+                if href.is_empty() {
+                    report(
+                        LintCheck::EmptyLink,
+                        format!("link {} has no target", quote_text(&text)),
+                        &section,
+                    );
+                } else if text.is_empty() && !has_label(element) {
+                    report(
+                        LintCheck::EmptyLink,
+                        format!("link to {href} has no text"),
+                        &section,
+                    );
+                }
+            }
+            "img" => {
+                let alt = element.value().attr("alt").unwrap_or("").trim();
+                if options.missing_alt && alt.is_empty() {
+                    let src = element.value().attr("src").unwrap_or("");
+                    report(
+                        LintCheck::MissingAlt,
+                        format!("image {src} has no alt text"),
+                        &section,
+                    );
+                }
+            }
+            name => {
+                let level = name[1..].parse::<u8>().unwrap_or(1);
+                let text = element_text(element);
+                section = element.value().attr("id").map(str::to_string);
+                if options.heading_levels
+                    && let Some(previous) = previous_level
+                    && level > previous + 1
+                {
+                    report(
+                        LintCheck::HeadingLevel,
+                        format!(
+                            "heading {} jumps from h{previous} to h{level}",
+                            quote_text(&text)
+                        ),
+                        &section,
+                    );
+                }
+                if options.duplicate_headings && !seen_headings.insert(text.to_lowercase()) {
+                    report(
+                        LintCheck::DuplicateHeading,
+                        format!("heading {} repeats an earlier heading", quote_text(&text)),
+                        &section,
+                    );
+                }
+                previous_level = Some(level);
+            }
+        }
+    }
+    findings
+}
+
+/// An element's text with whitespace collapsed.
+fn element_text(element: ElementRef<'_>) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether a link without text is still named, by an `aria-label`, a
+/// `title` or an image with alt text.
+fn has_label(link: ElementRef<'_>) -> bool {
+    let named = |attr| {
+        link.value()
+            .attr(attr)
+            .is_some_and(|value: &str| !value.trim().is_empty())
+    };
+    named("aria-label")
+        || named("title")
+        || link.select(&IMAGES).any(|img| {
+            img.value()
+                .attr("alt")
+                .is_some_and(|alt| !alt.trim().is_empty())
+        })
+}
+
+fn quote_text(text: &str) -> String {
+    if text.is_empty() {
+        "(no text)".to_string()
+    } else {
+        format!("\"{text}\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks(html: &str, options: &LintOptions) -> Vec<(LintCheck, Option<String>)> {
+        lint_html(html, options)
+            .into_iter()
+            .map(|f| (f.check, f.section))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_html_finds_each_problem() {
+        let html = r#"<h1 id="title">Title</h1>
+<p><a href=""></a> <a href="">x</a> <a href="/ok/">fine</a> <a href="/pic/"><img src="a.png" alt="Pic" /></a></p>
+<h3 id="skip">Skip</h3>
+<h2 id="dup">Dup</h2>
+<p><a href="http://example.com"></a> <img src="b.png" alt="" /> <img src="c.png" alt="ok" /></p>
+<h2 id="dup-1">dup</h2>"#;
+        let sec = |id: &str| Some(id.to_string());
+        assert_eq!(
+            checks(html, &LintOptions::default()),
+            vec![
+                (LintCheck::EmptyLink, sec("title")),
+                (LintCheck::EmptyLink, sec("title")),
+                (LintCheck::HeadingLevel, sec("skip")),
+                (LintCheck::EmptyLink, sec("dup")),
+                (LintCheck::MissingAlt, sec("dup")),
+                (LintCheck::DuplicateHeading, sec("dup-1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_html_messages() {
+        let findings = lint_html(
+            r#"<a href="">Docs</a><img src="x.png"><h1>A</h1><h4>B</h4>"#,
+            &LintOptions::default(),
+        );
+        let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "link \"Docs\" has no target",
+                "image x.png has no alt text",
+                "heading \"B\" jumps from h1 to h4",
+            ]
+        );
+        assert_eq!(findings[0].section, None);
+    }
+
+    #[test]
+    fn test_lint_checks_can_be_disabled() {
+        let html = r#"<h1>A</h1><h3>A</h3><a href=""></a><img src="x.png">"#;
+        let off = LintOptions {
+            empty_links: false,
+            missing_alt: false,
+            heading_levels: false,
+            duplicate_headings: false,
+        };
+        assert!(lint_html(html, &off).is_empty());
+        let only_alt = LintOptions {
+            missing_alt: true,
+            ..off
+        };
+        assert_eq!(checks(html, &only_alt), vec![(LintCheck::MissingAlt, None)]);
+    }
+
+    #[test]
+    fn test_lint_report_summary() {
+        let report = LintReport {
+            pages: 2,
+            issue_count: 1,
+            issues: vec![LintIssue {
+                file: PathBuf::from("docs/a.md"),
+                url: "/docs/a/".to_string(),
+                check: LintCheck::MissingAlt,
+                section: Some("intro".to_string()),
+                message: "image a.png has no alt text".to_string(),
+            }],
+        };
+        assert_eq!(
+            report.summary(),
+            "docs/a.md#intro: image a.png has no alt text (missing_alt)\n1 issue(s) in 2 page(s)\n"
+        );
+    }
+}
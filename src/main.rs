@@ -1,6 +1,4 @@
-use std::path::Path;
-#[cfg(feature = "gui")]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
 #[cfg(feature = "gui")]
@@ -43,23 +41,46 @@ fn show_folder_picker() -> Option<PathBuf> {
         .pick_folder()
 }
 
+/// Maps an `ffmpeg_log_level` config value to ffmpeg's own level.
+#[cfg(feature = "media-metadata")]
+fn ffmpeg_log_level(name: &str) -> ffmpeg_next::log::Level {
+    use ffmpeg_next::log::Level;
+    match name {
+        "quiet" => Level::Quiet,
+        "panic" => Level::Panic,
+        "error" => Level::Error,
+        "warning" => Level::Warning,
+        "info" => Level::Info,
+        "verbose" => Level::Verbose,
+        "debug" => Level::Debug,
+        "trace" => Level::Trace,
+        _ => Level::Fatal,
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), MbrError> {
     // Suppress ffmpeg warnings/info messages from the metadata crate
-    // These would otherwise clutter stdout/stderr when processing video files
+    // These would otherwise clutter stdout/stderr when processing video files.
+    // `ffmpeg_log_level` in config can raise this once the config is read.
     #[cfg(feature = "media-metadata")]
     ffmpeg_next::log::set_level(ffmpeg_next::log::Level::Fatal);
 
     let args = cli::Args::parse();
 
-    // Initialize tracing/logging based on verbosity flags
+    // Initialize tracing/logging: MBR_LOG, then RUST_LOG, then the -v/-q
+    // flags. Without any of them, the filter starts at warn and is swapped for
+    // the config's `log_level` once the config has been read.
     // Use try_init to allow server to re-configure if needed (it uses tower_http logging)
-    let log_filter = args.log_level_filter();
+    let env_filter = ["MBR_LOG", "RUST_LOG"]
+        .into_iter()
+        .find_map(|var| tracing_subscriber::EnvFilter::try_from_env(var).ok());
+    let log_level_from_config = env_filter.is_none() && args.log_level().is_none();
+    let (log_filter, log_filter_handle) = tracing_subscriber::reload::Layer::new(
+        env_filter.unwrap_or_else(|| args.log_level_filter().into()),
+    );
     let _ = tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| log_filter.into()),
-        )
+        .with(log_filter)
         .with(tracing_subscriber::fmt::layer())
         .try_init();
 
@@ -97,15 +118,23 @@ async fn main() -> Result<(), MbrError> {
         std::process::exit(0);
     }
 
-    // Determine if we're in GUI mode (no --server, --stdout, --build, --extract-video-metadata, --extract-pdf-cover flags)
+    // Determine if we're in GUI mode (no --server, --stdout, --build, --serve-build, --stats, --lint, --extract-video-metadata, --extract-pdf-cover flags)
     #[cfg(all(feature = "gui", feature = "media-metadata"))]
     let is_gui_mode = !args.server
         && !args.stdout
         && !args.build
         && !args.extract_video_metadata
-        && !args.extract_pdf_cover;
+        && !args.extract_pdf_cover
+        && !args.stats
+        && !args.lint
+        && args.serve_build.is_none();
     #[cfg(all(feature = "gui", not(feature = "media-metadata")))]
-    let is_gui_mode = !args.server && !args.stdout && !args.build;
+    let is_gui_mode = !args.server
+        && !args.stdout
+        && !args.build
+        && !args.stats
+        && !args.lint
+        && args.serve_build.is_none();
     #[cfg(not(feature = "gui"))]
     let _is_gui_mode = false;
 
@@ -136,8 +165,29 @@ async fn main() -> Result<(), MbrError> {
 
     let is_directory = absolute_path.is_dir();
 
+    // Init mode: scaffold `.mbr/` in the given folder, before any config is
+    // read from it (or from a parent folder's `.mbr/`)
+    if args.init {
+        if !is_directory {
+            eprintln!("Error: --init needs a folder, not a file");
+            std::process::exit(1);
+        }
+        for path in mbr::init::init_repo(&absolute_path, args.force)? {
+            println!("Wrote {}", path.display());
+        }
+        return Ok(());
+    }
+
     let mut config = Config::read(&absolute_path)?;
 
+    if log_level_from_config {
+        let _ = log_filter_handle.reload(tracing_subscriber::EnvFilter::new(
+            cli::log_filter_for_level(&config.log_level),
+        ));
+    }
+    #[cfg(feature = "media-metadata")]
+    ffmpeg_next::log::set_level(ffmpeg_log_level(&config.ffmpeg_log_level));
+
     // Apply CLI overrides
     if let Some(timeout) = args.oembed_timeout_ms {
         config.oembed_timeout_ms = timeout;
@@ -166,17 +216,7 @@ async fn main() -> Result<(), MbrError> {
         config.port = port;
     }
     if let Some(ref host) = args.host {
-        let ip: std::net::IpAddr = host
-            .parse()
-            .map_err(|_| ConfigError::InvalidHost { host: host.clone() })?;
-        match ip {
-            std::net::IpAddr::V4(v4) => {
-                config.host = mbr::config::IpArray(v4.octets());
-            }
-            std::net::IpAddr::V6(_) => {
-                return Err(ConfigError::InvalidHost { host: host.clone() }.into());
-            }
-        }
+        config.host = mbr::config::IpArray::resolve(host)?;
     }
     if let Some(ref theme) = args.theme {
         config.theme = theme.clone();
@@ -193,6 +233,18 @@ async fn main() -> Result<(), MbrError> {
     if args.skip_link_checks {
         config.skip_link_checks = true;
     }
+    // Apply skip_search_index from CLI
+    if args.skip_search_index {
+        config.skip_search_index = true;
+    }
+
+    // Apply strict from CLI
+    if args.strict {
+        config.strict = true;
+    }
+    if let Some(ref prefix) = args.only {
+        config.build_only = Some(prefix.clone());
+    }
     // Apply no_link_tracking from CLI
     if args.no_link_tracking {
         config.link_tracking = false;
@@ -324,6 +376,65 @@ async fn main() -> Result<(), MbrError> {
         }
     }
 
+    // Serve-build mode - plain static file server over an already-built site
+    if let Some(ref build_dir) = args.serve_build {
+        let build_dir = build_dir
+            .canonicalize()
+            .map_err(|e| ConfigError::CanonicalizeFailed {
+                path: build_dir.clone(),
+                source: e,
+            })?;
+        if !build_dir.is_dir() {
+            eprintln!(
+                "Error: --serve-build requires a directory, got {}",
+                build_dir.display()
+            );
+            std::process::exit(1);
+        }
+
+        warn_if_non_loopback_bind(&config.host);
+        tracing::info!("Serving built site from: {}", build_dir.display());
+        let server = server::Server::init_static(
+            config.host.0,
+            config.port,
+            build_dir,
+            &config.mime_overrides,
+        );
+        server.start().await?;
+        return Ok(());
+    }
+
+    // Stats mode - scan the repository and print a summary, rendering nothing
+    if args.stats {
+        let stats = mbr::stats::SiteStats::scan(&config)?;
+        if args.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&stats).expect("stats serialize to JSON")
+            );
+        } else {
+            print!("{}", stats.summary());
+        }
+        return Ok(());
+    }
+
+    // Lint mode - render every page in memory and report content problems
+    if args.lint {
+        let report = mbr::lint::LintReport::scan(&config)?;
+        if args.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("lint report serializes to JSON")
+            );
+        } else {
+            print!("{}", report.summary());
+        }
+        if report.issue_count > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     if args.build {
         // Build mode - generate static site
         // Default oembed timeout to 0 (disabled) for fastest builds unless explicitly set via CLI.
@@ -350,7 +461,10 @@ async fn main() -> Result<(), MbrError> {
 
             tracing::info!("Building static site to: {}", output_dir.display());
 
-            let builder = Builder::new(config, output_dir)?;
+            let preview = args
+                .open
+                .then(|| (config.host, config.port, config.mime_overrides.clone()));
+            let builder = Builder::new(config, output_dir.clone())?;
             let stats = builder.build().await?;
 
             if stats.broken_links > 0 {
@@ -368,6 +482,9 @@ async fn main() -> Result<(), MbrError> {
                     stats.markdown_pages, stats.section_pages, stats.assets_linked, stats.duration
                 );
             }
+            if let Some((host, port, mime_overrides)) = preview {
+                open_built_site(host, port, output_dir, &mime_overrides).await?;
+            }
             return Ok(());
         }
     } else if args.stdout {
@@ -395,16 +512,25 @@ async fn main() -> Result<(), MbrError> {
             // CLI stdout mode renders a single file with no repo index, so
             // body wikilinks never resolve globally; the page URL is unused.
             current_page_url: String::new(),
+            clean_urls: true,
         };
 
         // CLI mode: server_mode=false, transcode disabled (transcode is server-only).
         // Stdout/CLI mode mirrors build defaults (off unless explicitly enabled).
         let valid_tag_sources = mbr::config::tag_sources_to_set(&config.tag_sources);
         let mark_incomplete = config.mark_incomplete.unwrap_or(false);
+        let data_files = config.data_folder.as_ref().and_then(|folder| {
+            mbr::data::DataFiles::load(&config.root_dir.join(folder))
+                .inspect_err(|e| tracing::error!("Error loading data files: {e}"))
+                .ok()
+                .map(std::sync::Arc::new)
+        });
+        let renderer = markdown::RendererKind::for_path(&input_path, &config.renderers);
         let render_result = markdown::render(
             input_path,
             config.root_dir.as_path(),
             config.oembed_timeout_ms,
+            config.oembed_fallback,
             link_transform_config,
             false, // server_mode is false in CLI mode
             false, // transcode is disabled in CLI mode
@@ -412,14 +538,20 @@ async fn main() -> Result<(), MbrError> {
             mark_incomplete,
             &config.incomplete_markers,
             None, // no repo wikilink index in CLI stdout mode
+            data_files.clone(),
+            renderer,
+            &config.markdown_options,
         )
         .await
         .inspect_err(|e| tracing::error!("Error rendering markdown: {:?}", e))?;
         let templates =
             templates::Templates::new(&config.root_dir, config.template_folder.as_deref())
-                .inspect_err(|e| tracing::error!("Error parsing template: {e}"))?;
+                .inspect_err(|e| tracing::error!("Error parsing template: {e}"))?
+                .with_date_format(&config.date_format)
+                .with_data(data_files.unwrap_or_default());
+        let html = mbr::sanitize::sanitize_for_config(&config, render_result.html);
         let html_output = templates.render_markdown(
-            &render_result.html,
+            &html,
             render_result.frontmatter,
             std::collections::HashMap::new(),
         )?;
@@ -435,18 +567,20 @@ async fn main() -> Result<(), MbrError> {
             &config.markdown_extensions,
         );
         warn_if_non_loopback_bind(&config.host);
-        tracing::info!(
-            "Server running at http://{}:{}/{}",
-            config.host,
-            config.port,
-            url_path
-        );
-
-        server.start().await?;
+        // Log the bound address, since port 0 picks one when binding
+        let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+        let log_url = async {
+            if let Ok(addr) = ready_rx.await {
+                tracing::info!("Server running at http://{addr}/{url_path}");
+            }
+        };
+        let (started, ()) = tokio::join!(server.start_with_ready_signal(Some(ready_tx)), log_url);
+        started?;
     } else {
         // GUI mode - default when no flags specified (or explicit -g)
         #[cfg(feature = "gui")]
         {
+            config.check_gui_exposure()?;
             warn_if_non_loopback_bind(&config.host);
             let config_copy = config.clone();
             let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<u16>();
@@ -479,8 +613,9 @@ async fn main() -> Result<(), MbrError> {
                 }
             };
 
-            let base_url =
-                url::Url::parse(format!("http://{}:{}/", config.host, actual_port).as_str())?;
+            let base_url = url::Url::parse(
+                format!("http://{}:{}/", config.host.browse_host(), actual_port).as_str(),
+            )?;
 
             // For media files, redirect to the appropriate viewer URL
             let url = if !is_directory {
@@ -532,6 +667,45 @@ async fn main() -> Result<(), MbrError> {
     Ok(())
 }
 
+/// Serves a freshly built site and opens it in a window (`--build --open`).
+///
+/// Uses the same port retry as GUI mode. Without the `gui` feature the URL is
+/// printed and the site is served until interrupted.
+async fn open_built_site(
+    host: mbr::config::IpArray,
+    port: u16,
+    output_dir: PathBuf,
+    mime_overrides: &std::collections::HashMap<String, String>,
+) -> Result<(), MbrError> {
+    warn_if_non_loopback_bind(&host);
+    let mut server = server::Server::init_static(host.0, port, output_dir, mime_overrides);
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<u16>();
+    let handle =
+        tokio::spawn(async move { server.start_with_port_retry(Some(ready_tx), 10).await });
+
+    let Ok(actual_port) = ready_rx.await else {
+        // The sender is dropped only when the server failed to start
+        return match handle.await {
+            Ok(Err(e)) => Err(e.into()),
+            _ => Ok(()),
+        };
+    };
+    let url = format!("http://{}:{actual_port}/", host.browse_host());
+
+    #[cfg(feature = "gui")]
+    {
+        browser::launch_url(&url)?;
+    }
+    #[cfg(not(feature = "gui"))]
+    {
+        println!("Open {url} in a browser (Ctrl-C to stop)");
+        if let Ok(Err(e)) = handle.await {
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
 /// Returns true if the given IPv4 octets represent a loopback address (127.0.0.0/8).
 fn is_loopback_host(octets: [u8; 4]) -> bool {
     std::net::Ipv4Addr::from(octets).is_loopback()
@@ -541,6 +715,11 @@ fn is_loopback_host(octets: [u8; 4]) -> bool {
 /// non-loopback address, since mbr has no authentication.
 fn warn_if_non_loopback_bind(host: &mbr::config::IpArray) {
     if !is_loopback_host(host.0) {
+        let host = if host.is_unspecified() {
+            format!("{host} (all network interfaces)")
+        } else {
+            host.to_string()
+        };
         tracing::warn!(
             "Binding to {host} exposes this server beyond localhost: the entire markdown repository is readable by anyone who can reach this address, with no authentication, and expensive operations (search, video transcoding, PDF extraction) can be triggered remotely. Use --host 127.0.0.1 unless this is intended."
         );
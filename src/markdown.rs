@@ -1,10 +1,16 @@
 use crate::attrs::ParsedAttrs;
+use crate::code_include::{self, CodeInclude, code_html};
+use crate::data::DataFiles;
+use crate::dialect::{self, MdxMode};
 use crate::errors::MarkdownError;
-use crate::link_index::{OutboundLink, is_internal_link, split_url_anchor};
-use crate::link_transform::{LinkTransformConfig, transform_link};
+use crate::figure::{self, Figure, NumberedFigure};
+use crate::gallery::{Gallery, GalleryImage, gallery_html};
+use crate::link_index::{OutboundLink, is_internal_link, link_context, split_url_anchor};
+use crate::link_transform::{LinkTransformConfig, transform_html_links, transform_link};
 use crate::media::MediaEmbed;
-use crate::oembed::PageInfo;
+use crate::oembed::{OembedFallback, PageInfo};
 use crate::oembed_cache::OembedCache;
+use crate::slug::{HeadingIds, SlugStyle, slugify};
 use crate::vid::Vid;
 use crate::wikilink::{parse_tag_link, transform_wikilinks};
 use crate::wikilink_index::WikilinkIndex;
@@ -14,11 +20,13 @@ use pulldown_cmark::{
 };
 use regex::Regex;
 use std::{
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     fs::{self, File},
     io::Read,
     path::{Path, PathBuf},
-    sync::Arc,
+    rc::Rc,
+    sync::{Arc, LazyLock},
 };
 use yaml_rust2::{Yaml, YamlLoader};
 
@@ -39,6 +47,119 @@ pub(crate) fn markdown_options() -> Options {
     Options::all()
 }
 
+/// Optional markdown syntax extensions (the `[markdown_options]` config table).
+///
+/// Everything is on by default, matching [`markdown_options`]. Turning an
+/// extension off makes the parser treat its syntax as plain CommonMark text,
+/// e.g. `| a | b |` rows stay a paragraph and straight quotes stay straight.
+/// Wikilinks, heading attributes and frontmatter blocks are always enabled
+/// because other mbr features depend on them.
+///
+/// `heading_ids` isn't a parser flag but picks how heading anchor IDs are
+/// derived from their text (see [`crate::slug`]), `normalize_line_endings`
+/// cleans up the source before it reaches the parser, `html_links`
+/// controls link rewriting in raw HTML, and `autolink_bare_urls` turns URLs
+/// in running text into links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    /// Pipe tables.
+    pub tables: bool,
+    /// Footnote references and definitions (`[^1]`).
+    pub footnotes: bool,
+    /// `~~strikethrough~~`.
+    pub strikethrough: bool,
+    /// `- [ ]` / `- [x]` task list items.
+    pub tasklists: bool,
+    /// Curly quotes, en/em dashes and ellipses.
+    pub smart_punctuation: bool,
+    /// GitHub-flavored extras such as `> [!NOTE]` alert blockquotes.
+    pub gfm: bool,
+    /// `$inline$` and `$$display$$` math.
+    pub math: bool,
+    /// Definition lists (`term` followed by `: definition`).
+    pub definition_lists: bool,
+    /// `^superscript^` and `~subscript~`.
+    pub super_subscript: bool,
+    /// Slug style for heading anchor IDs: `github`, `ascii` or
+    /// `preserve-unicode`.
+    pub heading_ids: SlugStyle,
+    /// Strip a leading UTF-8 byte order mark and convert CRLF line endings to
+    /// LF when reading a file, so files saved on Windows parse like any other.
+    pub normalize_line_endings: bool,
+    /// Rewrite relative `href`/`src` attributes in raw HTML the way markdown
+    /// links are rewritten, so they resolve from the page's URL.
+    pub html_links: bool,
+    /// Link `https://...` URLs written in running text, as GitHub does. A
+    /// URL alone on its line is still embedded as a link card.
+    pub autolink_bare_urls: bool,
+    /// What to do with MDX and Quarto syntax in `.mdx`/`.qmd` files:
+    /// `strip` or `passthrough` (see [`crate::dialect`]).
+    pub mdx_mode: MdxMode,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            strikethrough: true,
+            tasklists: true,
+            smart_punctuation: true,
+            gfm: true,
+            math: true,
+            definition_lists: true,
+            super_subscript: true,
+            heading_ids: SlugStyle::default(),
+            normalize_line_endings: true,
+            html_links: true,
+            autolink_bare_urls: true,
+            mdx_mode: MdxMode::default(),
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Applies the source clean-up options to freshly read file contents,
+    /// including `mdx_mode` for files `renderer` handles as a dialect.
+    pub(crate) fn prepare_source(&self, source: String, renderer: RendererKind) -> String {
+        let source = if self.normalize_line_endings {
+            normalize_line_endings(source)
+        } else {
+            source
+        };
+        match (renderer, self.mdx_mode) {
+            (RendererKind::Mdx, MdxMode::Strip) => dialect::strip_mdx(&source),
+            (RendererKind::Quarto, MdxMode::Strip) => dialect::strip_quarto(&source),
+            _ => source,
+        }
+    }
+
+    /// The pulldown-cmark options for these settings.
+    pub(crate) fn parser_options(&self) -> Options {
+        let mut options = markdown_options();
+        for (enabled, flag) in [
+            (self.tables, Options::ENABLE_TABLES),
+            (self.footnotes, Options::ENABLE_OLD_FOOTNOTES),
+            (self.strikethrough, Options::ENABLE_STRIKETHROUGH),
+            (self.tasklists, Options::ENABLE_TASKLISTS),
+            (self.smart_punctuation, Options::ENABLE_SMART_PUNCTUATION),
+            (self.gfm, Options::ENABLE_GFM),
+            (self.math, Options::ENABLE_MATH),
+            (self.definition_lists, Options::ENABLE_DEFINITION_LIST),
+            (
+                self.super_subscript,
+                Options::ENABLE_SUPERSCRIPT | Options::ENABLE_SUBSCRIPT,
+            ),
+        ] {
+            if !enabled {
+                options.remove(flag);
+            }
+        }
+        options
+    }
+}
+
 /// Result of parsing a markdown file without rendering to HTML.
 ///
 /// Owns the source string so consumers can iterate over events
@@ -69,6 +190,21 @@ impl ParsedDocument {
     }
 }
 
+/// Strips a leading UTF-8 byte order mark and converts CRLF line endings to LF.
+///
+/// A BOM in front of `---` keeps the frontmatter block from being recognized,
+/// and stray `\r`s can end up in frontmatter values.
+pub fn normalize_line_endings(mut source: String) -> String {
+    if source.starts_with('\u{feff}') {
+        source.drain(..'\u{feff}'.len_utf8());
+    }
+    if source.contains("\r\n") {
+        source.replace("\r\n", "\n")
+    } else {
+        source
+    }
+}
+
 /// Parse a markdown file into a [`ParsedDocument`] without rendering to HTML.
 ///
 /// Reads the file, extracts frontmatter and headings, and returns the parsed
@@ -82,8 +218,13 @@ pub fn parse<P: AsRef<Path>>(file: P) -> Result<ParsedDocument, MarkdownError> {
         path: file.to_path_buf(),
         source: e,
     })?;
+    let options = MarkdownOptions::default();
+    let markdown_input = options.prepare_source(
+        markdown_input,
+        RendererKind::for_path(file, &HashMap::new()),
+    );
 
-    let (events, headings, _section_attrs) = collect_events_and_headings(&markdown_input);
+    let (events, headings, _section_attrs) = collect_events_and_headings(&markdown_input, &options);
     let has_h1 = headings.first().is_some_and(|h| h.level == 1);
 
     // Single pass: extract frontmatter and count words
@@ -118,12 +259,7 @@ pub fn parse<P: AsRef<Path>>(file: P) -> Result<ParsedDocument, MarkdownError> {
         }
     }
 
-    if !frontmatter.contains_key("title") && has_h1 {
-        frontmatter.insert(
-            "title".to_string(),
-            serde_json::Value::String(headings[0].text.clone()),
-        );
-    }
+    derive_title(&mut frontmatter, &headings, file);
 
     Ok(ParsedDocument {
         source: markdown_input,
@@ -140,6 +276,154 @@ pub struct HeadingInfo {
     pub level: u8,
     pub text: String,
     pub id: String,
+    /// Outline number (`1.`, `1.2`) when `toc_numbered` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+}
+
+/// Which headings appear in the table of contents, and whether they're
+/// numbered (the `toc_min_level`, `toc_max_level` and `toc_numbered` config
+/// options).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TocOptions {
+    pub min_level: u8,
+    pub max_level: u8,
+    pub numbered: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            min_level: 1,
+            max_level: 6,
+            numbered: false,
+        }
+    }
+}
+
+impl TocOptions {
+    /// Drops headings outside `min_level..=max_level` and, when `numbered`,
+    /// numbers the rest from their nesting. Skipped levels don't add a
+    /// component: an H4 directly under an H2 is numbered `1.1`, not `1.0.1`.
+    pub fn apply(&self, headings: Vec<HeadingInfo>) -> Vec<HeadingInfo> {
+        let mut headings: Vec<HeadingInfo> = headings
+            .into_iter()
+            .filter(|h| (self.min_level..=self.max_level).contains(&h.level))
+            .collect();
+        if self.numbered {
+            // Open (level, count) pairs from the outermost heading inward
+            let mut stack: Vec<(u8, u32)> = Vec::new();
+            for heading in &mut headings {
+                let mut closed = None;
+                while let Some(&(level, _)) = stack.last()
+                    && level > heading.level
+                {
+                    closed = stack.pop();
+                }
+                match stack.last_mut() {
+                    Some((level, count)) if *level == heading.level => *count += 1,
+                    // Continue the count of a closed deeper heading at this depth
+                    _ => stack.push((heading.level, closed.map_or(1, |(_, count)| count + 1))),
+                }
+                let parts: Vec<String> = stack.iter().map(|(_, count)| count.to_string()).collect();
+                heading.number = Some(if parts.len() == 1 {
+                    format!("{}.", parts[0])
+                } else {
+                    parts.join(".")
+                });
+            }
+        }
+        headings
+    }
+}
+
+/// Where a page's `title` came from, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleSource {
+    /// The frontmatter `title` field
+    Frontmatter,
+    /// The document's first H1 heading
+    Heading,
+    /// The file name, when there is neither
+    Filename,
+}
+
+/// Title derived from a file name (`docs/rust-guide.md` → `rust-guide`).
+pub fn title_from_filename(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Untitled")
+        .to_string()
+}
+
+/// Fills in `frontmatter["title"]` when the page doesn't set one: the text of
+/// the first H1 anywhere in the document, else the file name. This matches
+/// how directory listings title a page (see [`extract_metadata_from_file`]).
+pub fn derive_title(
+    frontmatter: &mut SimpleMetadata,
+    headings: &[HeadingInfo],
+    file: &Path,
+) -> TitleSource {
+    if frontmatter.contains_key("title") {
+        return TitleSource::Frontmatter;
+    }
+    let (title, source) = match headings.iter().find(|h| h.level == 1) {
+        Some(h1) => (h1.text.clone(), TitleSource::Heading),
+        None => (title_from_filename(file), TitleSource::Filename),
+    };
+    frontmatter.insert("title".to_string(), serde_json::Value::String(title));
+    source
+}
+
+/// How a document file is turned into HTML, chosen by extension through
+/// `config.renderers`.
+///
+/// Files whose extension has no entry render as markdown, except `.mdx` and
+/// `.qmd`, which default to their dialects. Other text formats (org-mode,
+/// reStructuredText) can be added as variants here and dispatched in
+/// `render_non_markdown`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RendererKind {
+    /// The full markdown pipeline (frontmatter, links, media embeds, etc.).
+    #[default]
+    Markdown,
+    /// Escaped text wrapped in `<pre class="mbr-plaintext">`.
+    Plaintext,
+    /// Markdown with MDX's imports, exports and JSX components handled per
+    /// `markdown_options.mdx_mode`.
+    Mdx,
+    /// Markdown with Quarto's div fences, shortcodes and code cells handled
+    /// per `markdown_options.mdx_mode`.
+    Quarto,
+}
+
+impl RendererKind {
+    /// Looks up the renderer for `path` by its (case-insensitive) extension.
+    pub fn for_path(path: &Path, renderers: &HashMap<String, RendererKind>) -> Self {
+        let Some(ext) = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+        else {
+            return Self::default();
+        };
+        renderers
+            .iter()
+            .find(|(key, _)| crate::content_type::normalize_extension(key) == ext)
+            .map(|(_, kind)| *kind)
+            .unwrap_or(match ext.as_str() {
+                "mdx" => Self::Mdx,
+                "qmd" => Self::Quarto,
+                _ => Self::Markdown,
+            })
+    }
+
+    /// True for the renderers that run the markdown pipeline.
+    pub fn is_markdown(&self) -> bool {
+        matches!(self, Self::Markdown | Self::Mdx | Self::Quarto)
+    }
 }
 
 /// Result of rendering a markdown file to HTML.
@@ -164,6 +448,8 @@ pub struct MarkdownRenderResult {
     pub outbound_links: Vec<OutboundLink>,
     /// True if the document's first heading is an H1 (affects title rendering)
     pub has_h1: bool,
+    /// Where `frontmatter["title"]` came from
+    pub title_source: TitleSource,
     /// Word count of the document (excluding code blocks and metadata)
     pub word_count: usize,
     /// Sentence count of the document (excluding code blocks and metadata).
@@ -177,10 +463,55 @@ pub struct MarkdownRenderResult {
     /// Computed via [`crate::readability::count_syllables`] for each
     /// whitespace-delimited word during rendering.
     pub syllable_count: usize,
+    /// Images shown by `{{ gallery(...) }}` shortcodes, whose thumbnails a
+    /// static build must generate
+    pub gallery_images: Vec<GalleryImage>,
+}
+
+impl MarkdownRenderResult {
+    /// Removes the first H1 from the rendered body when its text matches the
+    /// frontmatter title (trimmed, case-insensitive), so templates that show
+    /// the title don't repeat it.
+    ///
+    /// An empty element keeps the heading's `id` so its TOC entry and any
+    /// `#anchor` links still resolve. Returns true if the heading was removed.
+    pub fn dedupe_title_h1(&mut self) -> bool {
+        if self.title_source != TitleSource::Frontmatter {
+            return false;
+        }
+        let Some(title) = self.frontmatter.get("title").and_then(|t| t.as_str()) else {
+            return false;
+        };
+        let Some(pos) = self.headings.iter().position(|h| h.level == 1) else {
+            return false;
+        };
+        let h1 = &self.headings[pos];
+        if h1.text.trim().to_lowercase() != title.trim().to_lowercase() {
+            return false;
+        }
+
+        let open = format!("<h1 id=\"{}\"", h1.id);
+        let Some(start) = self.html.find(&open) else {
+            return false;
+        };
+        let Some(end) = self.html[start..]
+            .find("</h1>")
+            .map(|i| start + i + "</h1>".len())
+        else {
+            return false;
+        };
+        let anchor = format!("<span id=\"{}\"></span>", h1.id);
+        self.html.replace_range(start..end, &anchor);
+        if pos == 0 {
+            self.has_h1 = false;
+        }
+        true
+    }
 }
 
 struct EventState {
-    #[allow(dead_code)] // Reserved for future use (resolving relative paths)
+    /// Repo root, used to resolve `{{ gallery(dir="...") }}` folders and
+    /// `{{ code(path="...") }}` files
     root_path: PathBuf,
     /// Track the current media embed type (if any) for proper closing tags
     current_media: Option<MediaEmbed>,
@@ -190,12 +521,23 @@ struct EventState {
     metadata_parsed: Option<Yaml>,
     /// Configuration for transforming relative links
     link_transform_config: LinkTransformConfig,
+    /// Whether raw HTML links get the same transform (`html_links` option)
+    html_links: bool,
     /// Global name index for Obsidian-style body-wikilink (`[[Name]]`)
     /// resolution. `None` when there is no repo context (CLI/QuickLook paths);
     /// only bare wikilinks not found in the current folder consult it.
     wikilink_index: Option<Arc<WikilinkIndex>>,
+    /// Data files for `{{ data(file="...") }}` shortcodes. `None` when no
+    /// `data_folder` is configured or there is no repo context.
+    data_files: Option<Arc<DataFiles>>,
+    /// Images shown by `{{ gallery(...) }}` shortcodes, in page order
+    gallery_images: Vec<GalleryImage>,
+    /// Figures numbered so far by `{{ figure(...) }}` shortcodes
+    figures: Vec<NumberedFigure>,
     /// Pre-fetched oembed results for bare URLs (populated during parallel fetch phase)
     prefetched_oembed: HashMap<String, PageInfo>,
+    /// How bare URLs whose oembed fetch failed are rendered
+    oembed_fallback: OembedFallback,
     /// True in server/GUI mode, false in build/CLI mode
     server_mode: bool,
     /// True when dynamic video transcoding is enabled
@@ -225,6 +567,51 @@ struct EventState {
     /// valid fields like `style` are lost); surfaced to the user via the
     /// per-page error reporting and a build-mode summary.
     frontmatter_error: Option<String>,
+    /// Plain text of the current block (paragraph, heading, list item, table
+    /// cell), used to give collected links their surrounding context
+    block_text: String,
+    /// Offset into `block_text` where the current link's text starts
+    link_text_start: usize,
+    /// Links in the current block awaiting context: `(index into
+    /// collected_links, start, end)` byte offsets into `block_text`
+    pending_link_contexts: Vec<(usize, usize, usize)>,
+}
+
+impl EventState {
+    /// Accumulates block text and link offsets so each collected link can
+    /// be given the text surrounding it once its block ends.
+    fn track_link_context(&mut self, event: &Event<'_>) {
+        match event {
+            Event::Start(Tag::Paragraph | Tag::Heading { .. } | Tag::Item | Tag::TableCell)
+            | Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell,
+            ) => self.flush_link_contexts(),
+            Event::Start(Tag::Link { .. }) => self.link_text_start = self.block_text.len(),
+            Event::End(TagEnd::Link) => self.pending_link_contexts.push((
+                self.collected_links.len(),
+                self.link_text_start,
+                self.block_text.len(),
+            )),
+            Event::Text(text) | Event::Code(text) if !self.in_metadata && !self.in_code_block => {
+                self.block_text.push_str(text)
+            }
+            Event::SoftBreak | Event::HardBreak => self.block_text.push(' '),
+            _ => {}
+        }
+    }
+
+    fn flush_link_contexts(&mut self) {
+        for (index, start, end) in self.pending_link_contexts.drain(..) {
+            if let Some(link) = self.collected_links.get_mut(index) {
+                link.context = link_context(
+                    &self.block_text[..start],
+                    &self.block_text[start..end],
+                    &self.block_text[end..],
+                );
+            }
+        }
+        self.block_text.clear();
+    }
 }
 
 pub type SimpleMetadata = HashMap<String, serde_json::Value>;
@@ -273,6 +660,32 @@ fn count_sentence_terminators(text: &str) -> (usize, bool) {
     (count, ends_with_terminator)
 }
 
+/// Returns the destination of the first image in markdown content, as
+/// written in the source. Video, audio and other media embeds written with
+/// image syntax are skipped.
+///
+/// Used as the listing cover image when frontmatter has no `image`.
+pub fn extract_first_image(markdown_input: &str) -> Option<String> {
+    find_first_image(MDParser::new_ext(
+        markdown_input,
+        Options::ENABLE_YAML_STYLE_METADATA_BLOCKS,
+    ))
+}
+
+/// [`extract_first_image`] over events already being parsed.
+fn find_first_image<'a>(mut events: impl Iterator<Item = Event<'a>>) -> Option<String> {
+    events.find_map(|event| match event {
+        Event::Start(Tag::Image {
+            dest_url, title, ..
+        }) if !dest_url.is_empty()
+            && MediaEmbed::from_url_and_title(&dest_url, &title).is_none() =>
+        {
+            Some(dest_url.to_string())
+        }
+        _ => None,
+    })
+}
+
 /// Extracts the first H1 heading text from markdown content.
 ///
 /// This is used to provide a title fallback when no frontmatter title exists.
@@ -394,20 +807,22 @@ fn transform_rule_attrs(events: Vec<Event<'_>>) -> (Vec<Event<'_>>, HashMap<usiz
 /// `transform_rule_attrs`) into one. The rule-attrs detection uses a 3-element
 /// look-back buffer: when we encounter `End(Paragraph)`, we check if the preceding
 /// two events form the `Start(Paragraph), Text("em-dash + attrs")` pattern.
-fn collect_events_and_headings(
-    markdown_input: &str,
+fn collect_events_and_headings<'a>(
+    markdown_input: &'a str,
+    markdown_options: &MarkdownOptions,
 ) -> (
-    Vec<Event<'_>>,
+    Vec<Event<'a>>,
     Vec<HeadingInfo>,
     HashMap<usize, ParsedAttrs>,
 ) {
-    let parser = MDParser::new_ext(markdown_input, markdown_options());
+    let parser = MDParser::new_ext(markdown_input, markdown_options.parser_options());
     let parser = TextMergeStream::new(parser);
 
     let mut events = Vec::new();
     let mut headings = Vec::new();
-    let mut anchor_ids: HashMap<String, usize> = HashMap::new();
+    let mut heading_ids = HeadingIds::new(markdown_options.heading_ids);
     let mut in_heading_text: Option<String> = None;
+    let mut explicit_heading_id: Option<String> = None;
     let mut section_attrs = HashMap::new();
     let mut section_index = 0;
     let mut hint_open = false;
@@ -415,8 +830,9 @@ fn collect_events_and_headings(
     for event in parser {
         match &event {
             // --- Heading extraction ---
-            Event::Start(Tag::Heading { .. }) => {
+            Event::Start(Tag::Heading { id, .. }) => {
                 in_heading_text = Some(String::new());
+                explicit_heading_id = id.as_ref().map(|id| id.to_string());
                 events.push(event);
             }
             Event::Text(text) if in_heading_text.is_some() => {
@@ -442,7 +858,11 @@ fn collect_events_and_headings(
             }
             Event::End(TagEnd::Heading(heading_level)) => {
                 if let Some(text) = in_heading_text.take() {
-                    let id = generate_anchor_id(&text, &mut anchor_ids);
+                    // `{#custom-id}` wins over the generated ID
+                    let id = match explicit_heading_id.take() {
+                        Some(id) => heading_ids.claim(id),
+                        None => heading_ids.next_id(&text),
+                    };
                     let level_num = match heading_level {
                         HeadingLevel::H1 => 1,
                         HeadingLevel::H2 => 2,
@@ -456,6 +876,7 @@ fn collect_events_and_headings(
                         level: level_num,
                         text: text.clone(),
                         id: id.clone(),
+                        number: None,
                     });
 
                     // Walk backward to find the matching Start(Heading) and inject the ID
@@ -544,20 +965,140 @@ fn collect_events_and_headings(
                 events.push(event);
             }
 
+            // --- Wikilink anchors ---
+            // `[[Page#Some Heading]]` names a heading by its text; point it at
+            // the ID that heading gets.
+            Event::Start(Tag::Link {
+                link_type: link_type @ LinkType::WikiLink { .. },
+                dest_url,
+                title,
+                id,
+            }) if dest_url.split_once('#').is_some_and(|(_, a)| !a.is_empty()) => {
+                let (page, anchor) = dest_url.split_once('#').unwrap_or_default();
+                let dest_url = format!("{page}#{}", slugify(anchor, markdown_options.heading_ids));
+                events.push(Event::Start(Tag::Link {
+                    link_type: *link_type,
+                    dest_url: dest_url.into(),
+                    title: title.clone(),
+                    id: id.clone(),
+                }));
+            }
+
             _ => {
                 events.push(event);
             }
         }
     }
 
+    if markdown_options.autolink_bare_urls {
+        events = autolink_bare_urls(events);
+    }
+
     (events, headings, section_attrs)
 }
 
+static BARE_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bhttps?://[^\s<>]+").expect("Invalid BARE_URL_RE regex pattern")
+});
+
+/// Turns `http(s)://` URLs in running text into autolinks, the way GitHub
+/// does, so they get the same link handling as `<https://...>`.
+///
+/// A text run that is only a URL is left for the oembed pass (see
+/// [`collect_bare_urls`]), and shortcode parameters are left alone.
+fn autolink_bare_urls(events: Vec<Event<'_>>) -> Vec<Event<'_>> {
+    let mut output = Vec::with_capacity(events.len());
+    // Links, images, code blocks and frontmatter can't contain autolinks
+    let mut skip_depth = 0usize;
+    // Nor can inline HTML anchors like `<a href="...">docs at https://...</a>`
+    let mut html_link_depth = 0usize;
+
+    for event in events {
+        match &event {
+            Event::Start(
+                Tag::Link { .. } | Tag::Image { .. } | Tag::CodeBlock(_) | Tag::MetadataBlock(_),
+            ) => skip_depth += 1,
+            Event::End(
+                TagEnd::Link | TagEnd::Image | TagEnd::CodeBlock | TagEnd::MetadataBlock(_),
+            ) => skip_depth = skip_depth.saturating_sub(1),
+            Event::InlineHtml(html) if is_html_tag(html, "a") => html_link_depth += 1,
+            Event::InlineHtml(html) if is_html_tag(html, "/a") => {
+                html_link_depth = html_link_depth.saturating_sub(1)
+            }
+            Event::Text(text)
+                if skip_depth == 0
+                    && html_link_depth == 0
+                    && text.contains("://")
+                    && !(text.starts_with("http") && !text.contains(' '))
+                    && !text.trim_start().starts_with("{{") =>
+            {
+                push_autolinked_text(text, &mut output);
+                continue;
+            }
+            _ => {}
+        }
+        output.push(event);
+    }
+
+    output
+}
+
+/// True if `html` opens a `<name ...>` tag (case-insensitive); pass `/a` to
+/// match `</a>`.
+fn is_html_tag(html: &str, name: &str) -> bool {
+    let Some(tag) = html.strip_prefix('<') else {
+        return false;
+    };
+    tag.get(..name.len())
+        .is_some_and(|found| found.eq_ignore_ascii_case(name))
+        && tag[name.len()..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace())
+}
+
+/// Pushes `text` as text and autolink events, leaving trailing sentence
+/// punctuation (and an unbalanced closing paren) outside each link.
+fn push_autolinked_text<'a>(text: &str, output: &mut Vec<Event<'a>>) {
+    let mut last = 0;
+    for found in BARE_URL_RE.find_iter(text) {
+        let mut url = found.as_str();
+        loop {
+            let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+            let trimmed = match trimmed.strip_suffix(')') {
+                Some(inner) if trimmed.matches('(').count() < trimmed.matches(')').count() => inner,
+                _ => trimmed,
+            };
+            if trimmed.len() == url.len() {
+                break;
+            }
+            url = trimmed;
+        }
+        if url.len() <= "https://".len() {
+            continue;
+        }
+        if found.start() > last {
+            output.push(Event::Text(text[last..found.start()].to_string().into()));
+        }
+        let dest_url: CowStr<'a> = url.to_string().into();
+        output.push(Event::Start(Tag::Link {
+            link_type: LinkType::Autolink,
+            dest_url: dest_url.clone(),
+            title: CowStr::from(""),
+            id: CowStr::from(""),
+        }));
+        output.push(Event::Text(dest_url));
+        output.push(Event::End(TagEnd::Link));
+        last = found.start() + url.len();
+    }
+    if last < text.len() {
+        output.push(Event::Text(text[last..].to_string().into()));
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn render(
     file: PathBuf,
     root_path: &Path,
     oembed_timeout_ms: u64,
+    oembed_fallback: OembedFallback,
     link_transform_config: LinkTransformConfig,
     server_mode: bool,
     transcode_enabled: bool,
@@ -565,11 +1106,15 @@ pub async fn render(
     mark_incomplete: bool,
     incomplete_markers: &[String],
     wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    renderer: RendererKind,
+    markdown_options: &MarkdownOptions,
 ) -> Result<MarkdownRenderResult, MarkdownError> {
     render_with_cache(
         file,
         root_path,
         oembed_timeout_ms,
+        oembed_fallback,
         link_transform_config,
         None,
         server_mode,
@@ -578,6 +1123,9 @@ pub async fn render(
         mark_incomplete,
         incomplete_markers,
         wikilink_index,
+        data_files,
+        renderer,
+        markdown_options,
     )
     .await
 }
@@ -591,11 +1139,125 @@ pub async fn render(
 /// - `server_mode`: True in server/GUI mode, false in build/CLI mode
 /// - `transcode_enabled`: True when dynamic video transcoding is enabled
 /// - `valid_tag_sources`: Set of valid tag source names for wikilink transformation
+/// - `data_files`: Data files for `{{ data(...) }}` shortcodes, if configured
+/// - `renderer`: How to interpret the file (see [`RendererKind::for_path`])
+/// - `markdown_options`: Which syntax extensions the parser recognizes
 #[allow(clippy::too_many_arguments)]
 pub async fn render_with_cache(
     file: PathBuf,
     root_path: &Path,
     oembed_timeout_ms: u64,
+    oembed_fallback: OembedFallback,
+    link_transform_config: LinkTransformConfig,
+    oembed_cache: Option<Arc<OembedCache>>,
+    server_mode: bool,
+    transcode_enabled: bool,
+    valid_tag_sources: HashSet<String>,
+    mark_incomplete: bool,
+    incomplete_markers: &[String],
+    wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    renderer: RendererKind,
+    markdown_options: &MarkdownOptions,
+) -> Result<MarkdownRenderResult, MarkdownError> {
+    render_to(
+        file,
+        root_path,
+        oembed_timeout_ms,
+        oembed_fallback,
+        link_transform_config,
+        oembed_cache,
+        server_mode,
+        transcode_enabled,
+        valid_tag_sources,
+        mark_incomplete,
+        incomplete_markers,
+        wikilink_index,
+        data_files,
+        renderer,
+        markdown_options,
+        BodySink::Buffered,
+    )
+    .await
+}
+
+/// A render whose body HTML is still being generated.
+pub struct StreamingRender {
+    /// Everything but the body: `html` is empty.
+    pub result: MarkdownRenderResult,
+    /// The body HTML in pieces, each ending between two top-level blocks so
+    /// they can be post-processed independently. Closes when the body is done.
+    pub body: tokio::sync::mpsc::Receiver<String>,
+}
+
+/// Like [`render_with_cache`], but returns as soon as the document has been
+/// processed, generating the body HTML on a blocking thread and sending it
+/// in pieces of roughly `chunk_bytes`.
+///
+/// Lets the server send the page shell before the body of a very large
+/// document is ready. Generation stops early if the receiver is dropped.
+#[allow(clippy::too_many_arguments)]
+pub async fn render_streaming(
+    file: PathBuf,
+    root_path: &Path,
+    oembed_timeout_ms: u64,
+    oembed_fallback: OembedFallback,
+    link_transform_config: LinkTransformConfig,
+    oembed_cache: Option<Arc<OembedCache>>,
+    server_mode: bool,
+    transcode_enabled: bool,
+    valid_tag_sources: HashSet<String>,
+    mark_incomplete: bool,
+    incomplete_markers: &[String],
+    wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    renderer: RendererKind,
+    markdown_options: &MarkdownOptions,
+    chunk_bytes: usize,
+) -> Result<StreamingRender, MarkdownError> {
+    let (tx, body) = tokio::sync::mpsc::channel(STREAM_CHANNEL_CAPACITY);
+    let result = render_to(
+        file,
+        root_path,
+        oembed_timeout_ms,
+        oembed_fallback,
+        link_transform_config,
+        oembed_cache,
+        server_mode,
+        transcode_enabled,
+        valid_tag_sources,
+        mark_incomplete,
+        incomplete_markers,
+        wikilink_index,
+        data_files,
+        renderer,
+        markdown_options,
+        BodySink::Streamed { chunk_bytes, tx },
+    )
+    .await?;
+    Ok(StreamingRender { result, body })
+}
+
+/// Body chunks generated ahead of the reader before generation waits.
+const STREAM_CHANNEL_CAPACITY: usize = 4;
+
+/// Where [`render_to`] puts the body HTML.
+enum BodySink {
+    /// In `MarkdownRenderResult::html`.
+    Buffered,
+    /// Sent in chunks from a blocking thread; `html` is left empty.
+    Streamed {
+        chunk_bytes: usize,
+        tx: tokio::sync::mpsc::Sender<String>,
+    },
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn render_to(
+    file: PathBuf,
+    root_path: &Path,
+    oembed_timeout_ms: u64,
+    oembed_fallback: OembedFallback,
     link_transform_config: LinkTransformConfig,
     oembed_cache: Option<Arc<OembedCache>>,
     server_mode: bool,
@@ -604,6 +1266,10 @@ pub async fn render_with_cache(
     mark_incomplete: bool,
     incomplete_markers: &[String],
     wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    renderer: RendererKind,
+    markdown_options: &MarkdownOptions,
+    sink: BodySink,
 ) -> Result<MarkdownRenderResult, MarkdownError> {
     // Read markdown input. Use tokio's async filesystem API so this (potentially
     // slow) read does not block a tokio worker thread in the async render path.
@@ -614,6 +1280,15 @@ pub async fn render_with_cache(
                 path: file.clone(),
                 source: e,
             })?;
+    let raw_markdown_input = markdown_options.prepare_source(raw_markdown_input, renderer);
+
+    if let Some(mut result) = render_non_markdown(renderer, &raw_markdown_input, &file) {
+        if let BodySink::Streamed { tx, .. } = sink {
+            // Nothing to generate incrementally; the receiver gets one chunk.
+            let _ = tx.send(std::mem::take(&mut result.html)).await;
+        }
+        return Ok(result);
+    }
 
     // Transform [[Source:value]] wikilinks to standard markdown links before parsing
     let markdown_input = if valid_tag_sources.is_empty() {
@@ -625,7 +1300,8 @@ pub async fn render_with_cache(
     // Single merged pass: collect events, extract headings with anchor IDs,
     // and detect `--- {attrs}` rule patterns (merging what was previously
     // the heading extraction loop + transform_rule_attrs into one iteration).
-    let (events_with_ids, headings, section_attrs) = collect_events_and_headings(&markdown_input);
+    let (events_with_ids, headings, section_attrs) =
+        collect_events_and_headings(&markdown_input, markdown_options);
 
     // Detect if the first heading is an H1 (used for conditional title rendering in templates)
     let has_h1 = headings.first().is_some_and(|h| h.level == 1);
@@ -645,10 +1321,13 @@ pub async fn render_with_cache(
         root_path,
         link_transform_config,
         prefetched_oembed,
+        oembed_fallback,
         server_mode,
         transcode_enabled,
         valid_tag_sources,
         wikilink_index,
+        data_files,
+        markdown_options.html_links,
     );
 
     // Pass 3 (optional): wrap blocks starting with TK/TODO/FIXME/XXX in
@@ -662,15 +1341,85 @@ pub async fn render_with_cache(
         processed_events
     };
 
-    // Generate HTML output and extract frontmatter
-    finalize_render(
-        processed_events,
-        state,
-        section_attrs,
-        &markdown_input,
-        headings,
-        has_h1,
-    )
+    match sink {
+        // Generate HTML output and extract frontmatter
+        BodySink::Buffered => finalize_render(
+            processed_events,
+            state,
+            section_attrs,
+            &markdown_input,
+            &file,
+            headings,
+            has_h1,
+        ),
+        BodySink::Streamed { chunk_bytes, tx } => {
+            // The events borrow `markdown_input`; own them so HTML generation
+            // can outlive this call.
+            let events: Vec<Event<'static>> = processed_events
+                .into_iter()
+                .map(Event::into_static)
+                .collect();
+            tokio::task::spawn_blocking(move || {
+                write_html_chunks(events, section_attrs, chunk_bytes, |chunk| {
+                    tx.blocking_send(chunk).is_ok()
+                });
+            });
+            Ok(render_metadata(state, &file, headings, has_h1))
+        }
+    }
+}
+
+/// Generates the body HTML for `events`, handing it to `send` in pieces of at
+/// least `chunk_bytes` (the last may be smaller). Pieces only end where the
+/// next top-level block starts, so none splits a tag or element. Stops once
+/// `send` returns false.
+fn write_html_chunks(
+    events: Vec<Event<'static>>,
+    section_attrs: HashMap<usize, ParsedAttrs>,
+    chunk_bytes: usize,
+    mut send: impl FnMut(String) -> bool,
+) {
+    /// `fmt::Write` into a buffer the event iterator can also drain.
+    struct SharedBuffer(Rc<RefCell<String>>);
+
+    impl std::fmt::Write for SharedBuffer {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.borrow_mut().push_str(s);
+            Ok(())
+        }
+    }
+
+    let buffer = Rc::new(RefCell::new(String::with_capacity(chunk_bytes * 2)));
+    let open = Cell::new(true);
+    let mut depth = 0usize;
+    let mut events = events.into_iter();
+    // The writer pulls the next event only after writing the previous one, so
+    // when it asks for an event at depth 0 the buffer ends between blocks.
+    let chunked = std::iter::from_fn(|| {
+        if depth == 0 && buffer.borrow().len() >= chunk_bytes {
+            let chunk = std::mem::take(&mut *buffer.borrow_mut());
+            if !send(chunk) {
+                open.set(false);
+            }
+        }
+        if !open.get() {
+            return None;
+        }
+        let event = events.next()?;
+        match &event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        Some(event)
+    });
+    crate::html::write_html_mbr_with_attrs(SharedBuffer(buffer.clone()), chunked, section_attrs)
+        .expect("writing to a String cannot fail");
+
+    let rest = std::mem::take(&mut *buffer.borrow_mut());
+    if open.get() && !rest.is_empty() {
+        send(rest);
+    }
 }
 
 /// Runs process_event over all events, returning the processed events and final state.
@@ -684,10 +1433,13 @@ fn process_all_events<'a>(
     root_path: &Path,
     link_transform_config: LinkTransformConfig,
     prefetched_oembed: HashMap<String, PageInfo>,
+    oembed_fallback: OembedFallback,
     server_mode: bool,
     transcode_enabled: bool,
     valid_tag_sources: HashSet<String>,
     wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    html_links: bool,
 ) -> (Vec<Event<'a>>, EventState) {
     let mut state = EventState {
         root_path: root_path.to_path_buf(),
@@ -697,8 +1449,13 @@ fn process_all_events<'a>(
         metadata_source: None,
         metadata_parsed: None,
         link_transform_config,
+        html_links,
         wikilink_index,
+        data_files,
+        gallery_images: Vec::new(),
+        figures: Vec::new(),
         prefetched_oembed,
+        oembed_fallback,
         server_mode,
         transcode_enabled,
         collected_links: Vec::new(),
@@ -711,14 +1468,28 @@ fn process_all_events<'a>(
         syllable_count: 0,
         block_needs_sentence_bump: false,
         frontmatter_error: None,
+        block_text: String::new(),
+        link_text_start: 0,
+        pending_link_contexts: Vec::new(),
     };
     let mut processed_events = Vec::with_capacity(events.len());
 
     for event in events {
+        state.track_link_context(&event);
         let (processed, new_state) = process_event(event, state);
         state = new_state;
         processed_events.push(processed);
     }
+    state.flush_link_contexts();
+
+    // The list of figures can come before the figures it lists
+    let mut list_of_figures = None;
+    for event in &mut processed_events {
+        if matches!(event, Event::Html(html) if html.as_ref() == figure::LIST_PLACEHOLDER) {
+            let html = list_of_figures.get_or_insert_with(|| figure::list_html(&state.figures));
+            *event = Event::Html(html.clone().into());
+        }
+    }
 
     (processed_events, state)
 }
@@ -810,19 +1581,39 @@ fn mark_incomplete_blocks<'a>(events: Vec<Event<'a>>, marker_re: &Regex) -> Vec<
 /// Generates final HTML output and constructs the MarkdownRenderResult.
 ///
 /// Shared finalization logic for both `render_with_cache` and `render_sync`:
-/// deduplicates outbound links, generates HTML via `push_html_mbr_with_attrs`,
-/// extracts frontmatter, and injects H1 title fallback.
+/// generates HTML via `push_html_mbr_with_attrs`, then fills in the rest via
+/// [`render_metadata`].
 fn finalize_render(
     processed_events: Vec<Event<'_>>,
     state: EventState,
     section_attrs: HashMap<usize, ParsedAttrs>,
     markdown_input: &str,
+    file: &Path,
     headings: Vec<HeadingInfo>,
     has_h1: bool,
 ) -> Result<MarkdownRenderResult, MarkdownError> {
     // Write to a new String buffer with MBR extensions (sections, mermaid)
     let mut html_output = String::with_capacity(markdown_input.len() * 2);
+    crate::html::push_html_mbr_with_attrs(
+        &mut html_output,
+        processed_events.into_iter(),
+        section_attrs,
+    );
+
+    let mut result = render_metadata(state, file, headings, has_h1);
+    result.html = html_output;
+    Ok(result)
+}
 
+/// Builds the MarkdownRenderResult from everything event processing
+/// collected, leaving `html` empty: deduplicates outbound links, extracts
+/// frontmatter, and injects H1 title fallback.
+fn render_metadata(
+    state: EventState,
+    file: &Path,
+    headings: Vec<HeadingInfo>,
+    has_h1: bool,
+) -> MarkdownRenderResult {
     // Deduplicate outbound links by target URL - if a page links to the same
     // target multiple times, we only keep the first occurrence
     let mut seen_targets: HashSet<String> = HashSet::new();
@@ -832,44 +1623,104 @@ fn finalize_render(
         .filter(|link| seen_targets.insert(link.to.clone()))
         .collect();
 
-    crate::html::push_html_mbr_with_attrs(
-        &mut html_output,
-        processed_events.into_iter(),
-        section_attrs,
-    );
-
-    // Extract frontmatter and inject H1 title if no frontmatter title exists
+    // Extract frontmatter and fill in the title from the first H1 or file name
     let mut frontmatter = yaml_frontmatter_simplified(&state.metadata_parsed);
-    if !frontmatter.contains_key("title")
-        && let Some(h1_text) = headings
-            .first()
-            .filter(|h| h.level == 1)
-            .map(|h| h.text.clone())
-    {
-        frontmatter.insert("title".to_string(), serde_json::Value::String(h1_text));
+    let title_source = derive_title(&mut frontmatter, &headings, file);
+
+    // Replace the lossy `chapters` value with the validated list. A malformed
+    // list is reported like any other frontmatter error.
+    let mut frontmatter_error = state.frontmatter_error;
+    frontmatter.remove("chapters");
+    if let Some(yaml) = &state.metadata_parsed {
+        match crate::chapters::from_frontmatter(yaml) {
+            Ok(Some(chapters)) => {
+                frontmatter.insert("chapters".to_string(), serde_json::json!(chapters));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("Invalid chapters in frontmatter: {e}");
+                frontmatter_error.get_or_insert_with(|| format!("chapters: {e}"));
+            }
+        }
+    }
+
+    for field in crate::dates::DATE_FIELDS {
+        if let Err(raw) = crate::dates::frontmatter_date(&frontmatter, field) {
+            tracing::warn!("Unrecognized {field} date in frontmatter: {raw}");
+            frontmatter_error.get_or_insert_with(|| format!("{field}: unrecognized date {raw:?}"));
+        }
     }
 
-    Ok(MarkdownRenderResult {
+    MarkdownRenderResult {
         frontmatter,
-        frontmatter_error: state.frontmatter_error,
+        frontmatter_error,
         headings,
-        html: html_output,
+        html: String::new(),
         outbound_links: deduplicated_links,
         has_h1,
+        title_source,
         word_count: state.word_count,
         sentence_count: state.sentence_count,
         syllable_count: state.syllable_count,
-    })
+        gallery_images: state.gallery_images,
+    }
 }
 
-/// Synchronous version of `render_with_cache()` for use from rayon threads.
-///
-/// Performs the same rendering pipeline but without async: file reading (already sync),
-/// wikilink transformation, merged heading + rule-attrs pass, process_event pass, and
-/// HTML generation.
+/// Renders `input` with a non-markdown renderer, or returns `None` when the
+/// file should go through the markdown pipeline.
+fn render_non_markdown(
+    renderer: RendererKind,
+    input: &str,
+    file: &Path,
+) -> Option<MarkdownRenderResult> {
+    match renderer {
+        RendererKind::Markdown | RendererKind::Mdx | RendererKind::Quarto => None,
+        RendererKind::Plaintext => Some(render_plaintext(input, file)),
+    }
+}
+
+/// Renders plain text as a single escaped `<pre>` block.
 ///
-/// No-network embeds (Giphy, GitHub gist, and bare-URL media) ARE produced in the
-/// sync path — they are pure CPU (regex/string) and require no I/O, so they work in
+/// There is no frontmatter or heading structure, so the title comes from the
+/// file name. Words and sentences are still counted for reading time.
+fn render_plaintext(input: &str, file: &Path) -> MarkdownRenderResult {
+    let mut frontmatter = SimpleMetadata::new();
+    let title_source = derive_title(&mut frontmatter, &[], file);
+
+    let mut word_count = 0;
+    let mut syllable_count = 0;
+    for word in input.split_whitespace() {
+        word_count += 1;
+        syllable_count += crate::readability::count_syllables(word);
+    }
+    let (sentence_count, _) = count_sentence_terminators(input);
+
+    MarkdownRenderResult {
+        frontmatter,
+        frontmatter_error: None,
+        headings: Vec::new(),
+        html: format!(
+            "<pre class=\"mbr-plaintext\">{}</pre>\n",
+            html_escape::encode_text(input)
+        ),
+        outbound_links: Vec::new(),
+        has_h1: false,
+        title_source,
+        word_count,
+        sentence_count,
+        syllable_count,
+        gallery_images: Vec::new(),
+    }
+}
+
+/// Synchronous version of `render_with_cache()` for use from rayon threads.
+///
+/// Performs the same rendering pipeline but without async: file reading (already sync),
+/// wikilink transformation, merged heading + rule-attrs pass, process_event pass, and
+/// HTML generation.
+///
+/// No-network embeds (Giphy, GitHub gist, and bare-URL media) ARE produced in the
+/// sync path — they are pure CPU (regex/string) and require no I/O, so they work in
 /// build mode regardless of `oembed_timeout_ms`. Only OpenGraph network fetches are
 /// skipped here; when `oembed_timeout_ms > 0` and a cache is present, previously
 /// cached network results are also merged in (but never fetched fresh).
@@ -878,6 +1729,7 @@ pub fn render_sync(
     file: PathBuf,
     root_path: &Path,
     oembed_timeout_ms: u64,
+    oembed_fallback: OembedFallback,
     link_transform_config: LinkTransformConfig,
     oembed_cache: Option<Arc<OembedCache>>,
     server_mode: bool,
@@ -886,12 +1738,20 @@ pub fn render_sync(
     mark_incomplete: bool,
     incomplete_markers: &[String],
     wikilink_index: Option<Arc<WikilinkIndex>>,
+    data_files: Option<Arc<DataFiles>>,
+    renderer: RendererKind,
+    markdown_options: &MarkdownOptions,
 ) -> Result<MarkdownRenderResult, MarkdownError> {
     // Read markdown input
     let raw_markdown_input = fs::read_to_string(&file).map_err(|e| MarkdownError::ReadFailed {
         path: file.clone(),
         source: e,
     })?;
+    let raw_markdown_input = markdown_options.prepare_source(raw_markdown_input, renderer);
+
+    if let Some(result) = render_non_markdown(renderer, &raw_markdown_input, &file) {
+        return Ok(result);
+    }
 
     // Transform [[Source:value]] wikilinks to standard markdown links before parsing
     let markdown_input = if valid_tag_sources.is_empty() {
@@ -902,7 +1762,8 @@ pub fn render_sync(
 
     // Single merged pass: collect events, extract headings with anchor IDs,
     // and detect `--- {attrs}` rule patterns.
-    let (events_with_ids, headings, section_attrs) = collect_events_and_headings(&markdown_input);
+    let (events_with_ids, headings, section_attrs) =
+        collect_events_and_headings(&markdown_input, markdown_options);
 
     // Detect if the first heading is an H1
     let has_h1 = headings.first().is_some_and(|h| h.level == 1);
@@ -926,10 +1787,13 @@ pub fn render_sync(
         root_path,
         link_transform_config,
         prefetched_oembed,
+        oembed_fallback,
         server_mode,
         transcode_enabled,
         valid_tag_sources,
         wikilink_index,
+        data_files,
+        markdown_options.html_links,
     );
 
     // Pass 3 (optional): wrap blocks starting with TK/TODO/FIXME/XXX in
@@ -949,6 +1813,7 @@ pub fn render_sync(
         state,
         section_attrs,
         &markdown_input,
+        &file,
         headings,
         has_h1,
     )
@@ -1009,74 +1874,73 @@ fn collect_bare_urls(events: &[Event<'_>]) -> HashSet<String> {
     urls
 }
 
-/// Fetches oembed data for a collection of URLs in parallel.
-///
-/// Uses the cache when available to avoid redundant network requests.
-/// New results are stored in the cache for future use.
+/// Fetches oembed data for the bare URLs in `events`, see [`fetch_oembed_urls`].
 async fn prefetch_oembed_urls(
     events: &[Event<'_>],
     oembed_timeout_ms: u64,
     oembed_cache: &Option<Arc<OembedCache>>,
 ) -> HashMap<String, PageInfo> {
     let urls = collect_bare_urls(events);
-
-    if urls.is_empty() {
-        return HashMap::new();
-    }
-
-    tracing::debug!("oembed prefetch: found {} bare URLs to fetch", urls.len());
-
-    // Partition URLs into cached and uncached
-    let (cached, uncached): (Vec<_>, Vec<_>) = urls
-        .into_iter()
-        .partition(|url| oembed_cache.as_ref().and_then(|c| c.get(url)).is_some());
-
-    let mut results = HashMap::new();
-
-    // Add cached results
-    if let Some(cache) = oembed_cache {
-        for url in cached {
-            if let Some(info) = cache.get(&url) {
-                results.insert(url, info);
-            }
-        }
+    if !urls.is_empty() {
+        tracing::debug!("oembed prefetch: found {} bare URLs to fetch", urls.len());
     }
+    fetch_oembed_urls(urls, oembed_timeout_ms, oembed_cache).await
+}
 
-    // Fetch uncached URLs in parallel
-    if !uncached.is_empty() {
-        tracing::debug!(
-            "oembed prefetch: {} cached, {} to fetch",
-            results.len(),
-            uncached.len()
-        );
-
-        let fetch_futures: Vec<_> = uncached
-            .into_iter()
-            .map(|url| async move {
-                tracing::debug!("oembed fetch start: {}", url);
-                let result = PageInfo::new_from_url(&url, oembed_timeout_ms)
-                    .await
-                    .unwrap_or_else(|_| PageInfo {
-                        url: url.clone(),
-                        ..Default::default()
-                    });
-                tracing::debug!("oembed fetch complete: {}", url);
-                (url, result)
-            })
-            .collect();
+/// Fetches oembed data for `urls` concurrently.
+///
+/// Goes through the cache when available: cached URLs aren't fetched, new
+/// results are stored, a URL already being fetched for another page is
+/// waited on rather than requested again, and the cache's concurrency limit
+/// applies across every page sharing it.
+pub async fn fetch_oembed_urls(
+    urls: impl IntoIterator<Item = String>,
+    oembed_timeout_ms: u64,
+    oembed_cache: &Option<Arc<OembedCache>>,
+) -> HashMap<String, PageInfo> {
+    use futures::StreamExt;
 
-        let fetched: Vec<_> = futures::future::join_all(fetch_futures).await;
+    let fetch = |url: String| async move {
+        tracing::debug!("oembed fetch start: {}", url);
+        let result = PageInfo::new_from_url(&url, oembed_timeout_ms)
+            .await
+            .unwrap_or_else(|_| PageInfo {
+                url: url.clone(),
+                failed: true,
+                ..Default::default()
+            });
+        tracing::debug!("oembed fetch complete: {}", url);
+        result
+    };
 
-        // Store results and cache them
-        for (url, info) in fetched {
-            if let Some(cache) = oembed_cache {
-                cache.insert(url.clone(), info.clone());
-            }
-            results.insert(url, info);
-        }
-    }
+    futures::stream::iter(urls)
+        .map(|url| async move {
+            let info = match oembed_cache {
+                Some(cache) => cache.get_or_fetch(&url, || fetch(url.clone())).await,
+                None => fetch(url.clone()).await,
+            };
+            (url, info)
+        })
+        .buffer_unordered(
+            oembed_cache
+                .as_ref()
+                .map_or(crate::oembed_cache::DEFAULT_OEMBED_CONCURRENCY, |cache| {
+                    cache.concurrency()
+                }),
+        )
+        .collect()
+        .await
+}
 
-    results
+/// Returns the bare URLs in a markdown document that would be looked up for
+/// oembed embeds when it renders.
+///
+/// Lets a build fetch every page's embeds up front, so rendering can take
+/// them from the cache.
+pub fn bare_urls(markdown_input: &str, markdown_options: &MarkdownOptions) -> HashSet<String> {
+    let events: Vec<Event<'_>> =
+        MDParser::new_ext(markdown_input, markdown_options.parser_options()).collect();
+    collect_bare_urls(&events)
 }
 
 fn yaml_frontmatter_simplified(y: &Option<Yaml>) -> SimpleMetadata {
@@ -1154,9 +2018,63 @@ pub struct FileMetadata {
     pub metadata: SimpleMetadata,
     /// Typed relationships declared in frontmatter (unresolved endpoints).
     pub relationships: Vec<crate::relationships::RawRelationship>,
+    /// Estimated body word count (see [`estimate_word_count`]), extrapolated
+    /// from the sampled prefix for files larger than it.
+    pub word_count: usize,
+    /// Destination of the first content image in the sampled prefix, as
+    /// written in the source. Only looked up when frontmatter has no `image`.
+    pub first_image: Option<String>,
 }
 
-pub fn extract_metadata_from_file<P: AsRef<Path>>(path: P) -> Result<FileMetadata, MarkdownError> {
+/// Cheap word count for listings, without rendering: splits the source on
+/// whitespace, skipping YAML frontmatter, fenced code blocks, and bare
+/// markup tokens (`#`, `-`, `|`, ...). Close to, but not exactly, the
+/// rendered page's `word_count`.
+pub fn estimate_word_count(markdown_input: &str) -> usize {
+    let mut lines = markdown_input.lines().peekable();
+    if lines.peek().is_some_and(|line| line.trim_end() == "---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if matches!(line.trim_end(), "---" | "...") {
+                break;
+            }
+        }
+    }
+
+    let mut fence: Option<&str> = None;
+    let mut count = 0;
+    for line in lines {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+        count += line
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+    }
+    count
+}
+
+/// Reads a markdown file's frontmatter, title and word count for listings.
+///
+/// `normalize_line_endings` applies [`normalize_line_endings`] to the prefix
+/// that is read (see [`MarkdownOptions::normalize_line_endings`]).
+pub fn extract_metadata_from_file<P: AsRef<Path>>(
+    path: P,
+    normalize_line_endings: bool,
+) -> Result<FileMetadata, MarkdownError> {
     let path = path.as_ref();
     // Only read the first 8KB - frontmatter is always at the top
     let mut file = File::open(path).map_err(|e| MarkdownError::ReadFailed {
@@ -1171,30 +2089,39 @@ pub fn extract_metadata_from_file<P: AsRef<Path>>(path: P) -> Result<FileMetadat
             path: path.to_path_buf(),
             source: e,
         })?;
-    let markdown_input = String::from_utf8_lossy(&buffer);
+    let mut markdown_input = String::from_utf8_lossy(&buffer).into_owned();
+    if normalize_line_endings {
+        markdown_input = self::normalize_line_endings(markdown_input);
+    }
     let parser = MDParser::new_ext(&markdown_input, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
-    let parser = TextMergeStream::new(parser);
-    let mut in_metadata = false;
+    let mut events = TextMergeStream::new(parser).peekable();
     let mut hm = HashMap::new();
     let mut relationships = Vec::new();
-    for event in parser.take(4) {
-        match &event {
-            Event::Start(Tag::MetadataBlock(MetadataBlockKind::YamlStyle)) => {
-                in_metadata = true;
-            }
-            Event::End(TagEnd::MetadataBlock(MetadataBlockKind::YamlStyle)) => {
-                break;
-            }
-            Event::Text(text) if in_metadata => {
-                let metadata_parsed = YamlLoader::load_from_str(text).map(|ys| ys[0].clone()).ok();
+    // Frontmatter can only be the first event; anything else is left for the
+    // first image search below.
+    if matches!(
+        events.peek(),
+        Some(Event::Start(Tag::MetadataBlock(
+            MetadataBlockKind::YamlStyle
+        )))
+    ) {
+        for event in events.by_ref() {
+            match &event {
+                Event::End(TagEnd::MetadataBlock(MetadataBlockKind::YamlStyle)) => {
+                    break;
+                }
+                Event::Text(text) => {
+                    let metadata_parsed =
+                        YamlLoader::load_from_str(text).map(|ys| ys[0].clone()).ok();
 
-                if let Some(ref yaml) = metadata_parsed {
-                    relationships = crate::relationships::parse_relationships(yaml);
+                    if let Some(ref yaml) = metadata_parsed {
+                        relationships = crate::relationships::parse_relationships(yaml);
+                    }
+                    hm = yaml_frontmatter_simplified(&metadata_parsed);
+                    break;
                 }
-                hm = yaml_frontmatter_simplified(&metadata_parsed);
-                break;
+                _ => {}
             }
-            _ => {}
         }
     }
 
@@ -1205,50 +2132,25 @@ pub fn extract_metadata_from_file<P: AsRef<Path>>(path: P) -> Result<FileMetadat
         hm.insert("title".to_string(), serde_json::Value::String(h1_text));
     }
 
-    Ok(FileMetadata {
-        metadata: hm,
-        relationships,
-    })
-}
-
-/// Generates a URL-safe anchor ID from heading text.
-/// Handles duplicates by appending -2, -3, etc.
-fn generate_anchor_id(text: &str, anchor_ids: &mut HashMap<String, usize>) -> String {
-    // Convert to lowercase and replace spaces and special chars with dashes
-    let base_id = text
-        .to_lowercase()
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '-' {
-                c
-            } else if c.is_whitespace() {
-                '-'
-            } else {
-                // Remove special characters
-                ' '
-            }
-        })
-        .collect::<String>()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join("-");
-
-    // Handle empty IDs
-    let base_id = if base_id.is_empty() {
-        "heading".to_string()
+    // Carry on through the body from where the frontmatter ended
+    let first_image = if hm.contains_key("image") {
+        None
     } else {
-        base_id
+        find_first_image(events)
     };
 
-    // Check for duplicates and increment counter
-    let count = anchor_ids.entry(base_id.clone()).or_insert(0);
-    *count += 1;
-
-    if *count == 1 {
-        base_id
-    } else {
-        format!("{}-{}", base_id, count)
+    // Only a prefix was read for large files; scale its count to the full size
+    let mut word_count = estimate_word_count(&markdown_input);
+    if file_len > read_len && read_len > 0 {
+        word_count = word_count * file_len / read_len;
     }
+
+    Ok(FileMetadata {
+        metadata: hm,
+        relationships,
+        word_count,
+        first_image,
+    })
 }
 
 /// Processes a single markdown event, transforming it as needed.
@@ -1296,6 +2198,14 @@ fn process_event(
                 (event, state)
             }
         }
+        Event::Html(html) if state.html_links => {
+            let html = transform_html_links(html, &state.link_transform_config);
+            (Event::Html(html.into()), state)
+        }
+        Event::InlineHtml(html) if state.html_links => {
+            let html = transform_html_links(html, &state.link_transform_config);
+            (Event::InlineHtml(html.into()), state)
+        }
         Event::Start(Tag::MetadataBlock(v)) => {
             state.metadata_source = Some(*v);
             state.in_metadata = true;
@@ -1375,6 +2285,7 @@ fn process_event(
                     text: std::mem::take(&mut state.current_link_text),
                     anchor,
                     internal,
+                    context: None,
                 };
                 state.collected_links.push(link);
             }
@@ -1457,10 +2368,74 @@ fn process_event(
                         url: url_str,
                         ..Default::default()
                     });
-                (Event::Html(info.html().into()), state)
+                (
+                    Event::Html(info.html_with_fallback(state.oembed_fallback).into()),
+                    state,
+                )
             } else if text.trim_start().starts_with("{{") {
-                if let Some(mut vid) = Vid::from_vid(text) {
+                if let Some(html) = state
+                    .data_files
+                    .as_ref()
+                    .filter(|_| !state.in_code_block)
+                    .and_then(|data| data.shortcode_html(text))
+                {
+                    (Event::Html(html.into()), state)
+                } else if let Some(gallery) =
+                    Gallery::from_shortcode(text).filter(|_| !state.in_code_block)
+                {
+                    match gallery.images(&state.root_path) {
+                        Some(images) => {
+                            if images.is_empty() {
+                                tracing::warn!("gallery folder {:?} has no images", gallery.dir);
+                            }
+                            let html = gallery_html(&images, |url| {
+                                transform_link(url, &state.link_transform_config)
+                            });
+                            state.gallery_images.extend(images);
+                            (Event::Html(html.into()), state)
+                        }
+                        None => {
+                            tracing::warn!("gallery folder {:?} not found", gallery.dir);
+                            (event, state)
+                        }
+                    }
+                } else if let Some(include) =
+                    CodeInclude::from_shortcode(text).filter(|_| !state.in_code_block)
+                {
+                    let html = match include.excerpt(&state.root_path) {
+                        Ok(code) => code_html(&code, include.language().as_deref()),
+                        Err(e) => {
+                            tracing::warn!("{e}");
+                            code_include::error_html(&e)
+                        }
+                    };
+                    (Event::Html(html.into()), state)
+                } else if let Some(fig) =
+                    Figure::from_shortcode(text).filter(|_| !state.in_code_block)
+                {
+                    let html = match fig.url_path(&state.root_path) {
+                        Ok(url_path) => {
+                            let number = state.figures.len() + 1;
+                            state.figures.push(NumberedFigure {
+                                number,
+                                caption: fig.caption.clone(),
+                            });
+                            let src = transform_link(&url_path, &state.link_transform_config);
+                            figure::figure_html(&fig, number, &src)
+                        }
+                        Err(e) => {
+                            tracing::warn!("{e}");
+                            figure::error_html(&e)
+                        }
+                    };
+                    (Event::Html(html.into()), state)
+                } else if figure::is_list_shortcode(text) && !state.in_code_block {
+                    (Event::Html(figure::LIST_PLACEHOLDER.into()), state)
+                } else if let Some(mut vid) = Vid::from_vid(text) {
                     vid.url = transform_link(&vid.url, &state.link_transform_config);
+                    for track in &mut vid.subtitles {
+                        track.src = transform_link(&track.src, &state.link_transform_config);
+                    }
                     (
                         Event::Html(
                             vid.to_html(false, state.server_mode, state.transcode_enabled)
@@ -1508,12 +2483,14 @@ mod tests {
             is_index_file,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         // Tests run with server_mode=false, transcode_enabled=false, mark_incomplete=false
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1521,6 +2498,9 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
@@ -1539,12 +2519,14 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let owned: Vec<String> = markers.iter().map(|s| s.to_string()).collect();
         let result = render(
             path,
             &root,
             0,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1552,13 +2534,58 @@ mod tests {
             true,
             &owned,
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .await
+        .unwrap();
+        result.html
+    }
+
+    /// Render with oembed enabled and the given fallback for failed fetches.
+    async fn render_markdown_with_fallback(
+        content: &str,
+        oembed_timeout_ms: u64,
+        fallback: OembedFallback,
+    ) -> String {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let path = file.path().to_path_buf();
+        let root = path.parent().unwrap().to_path_buf();
+        let result = render(
+            path,
+            &root,
+            oembed_timeout_ms,
+            fallback,
+            LinkTransformConfig::default(),
+            false,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
         result.html
     }
 
+    /// A bare URL whose fetch always fails: oembed refuses loopback
+    /// addresses, so this fails without touching the network.
+    const FAILING_OEMBED_URL: &str = "http://127.0.0.1:9/broken-embed";
+
     async fn render_result(content: &str) -> MarkdownRenderResult {
+        render_result_with_options(content, &MarkdownOptions::default()).await
+    }
+
+    async fn render_result_with_options(
+        content: &str,
+        markdown_options: &MarkdownOptions,
+    ) -> MarkdownRenderResult {
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(content.as_bytes()).unwrap();
         let path = file.path().to_path_buf();
@@ -1569,11 +2596,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         render(
             path,
             &root,
             0,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1581,11 +2610,230 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            markdown_options,
         )
         .await
         .unwrap()
     }
 
+    #[tokio::test]
+    async fn markdown_options_toggle_tables() {
+        let table = "| a | b |\n|---|---|\n| 1 | 2 |\n";
+        let html = render_result(table).await.html;
+        assert!(html.contains("<table>"), "tables on by default: {html}");
+
+        let options = MarkdownOptions {
+            tables: false,
+            ..Default::default()
+        };
+        let html = render_result_with_options(table, &options).await.html;
+        assert!(!html.contains("<table>"), "tables disabled: {html}");
+        assert!(html.contains("| a | b |"), "rows stay as text: {html}");
+    }
+
+    #[tokio::test]
+    async fn markdown_options_toggle_smart_punctuation() {
+        let text = "She said \"hi\" -- didn't she...\n";
+        let html = render_result(text).await.html;
+        assert!(html.contains("“hi”"), "curly quotes by default: {html}");
+        assert!(html.contains('…'), "ellipsis by default: {html}");
+
+        let options = MarkdownOptions {
+            smart_punctuation: false,
+            ..Default::default()
+        };
+        let html = render_result_with_options(text, &options).await.html;
+        assert!(html.contains("\"hi\""), "straight quotes kept: {html}");
+        assert!(html.contains("didn't"), "apostrophe kept: {html}");
+        assert!(html.contains("--") && html.contains("..."), "{html}");
+    }
+
+    #[tokio::test]
+    async fn markdown_options_toggle_autolink_bare_urls() {
+        let text = "See https://example.com/docs_(v2)/a?b=1. Or (https://example.org).\n\n\
+                    `https://code.example.com` and <https://auto.example.com>\n";
+        let result = render_result(text).await;
+        let html = &result.html;
+        assert!(
+            html.contains(r#"See <a href="https://example.com/docs_(v2)/a?b=1">https://example.com/docs_(v2)/a?b=1</a>. Or (<a href="https://example.org">https://example.org</a>)."#),
+            "linked by default: {html}"
+        );
+        assert!(
+            html.contains("<code>https://code.example.com</code>"),
+            "{html}"
+        );
+        assert_eq!(
+            html.matches("https://auto.example.com").count(),
+            2,
+            "{html}"
+        );
+        // Autolinks are collected like any other link
+        assert!(
+            result
+                .outbound_links
+                .iter()
+                .any(|link| link.to == "https://example.org"),
+            "{:?}",
+            result.outbound_links
+        );
+        // Text inside a raw HTML anchor is already linked
+        let html = render_result(
+            "Read <a href=\"https://example.com\">the docs at https://example.com</A> \
+             or https://example.net.\n",
+        )
+        .await
+        .html;
+        assert!(
+            html.contains(r#"<a href="https://example.com">the docs at https://example.com</A>"#),
+            "not nested: {html}"
+        );
+        assert!(
+            html.contains(r#"<a href="https://example.net">https://example.net</a>."#),
+            "linked after the anchor: {html}"
+        );
+
+        // A URL on its own is left for oembed
+        let alone = vec![Event::Text("https://alone.example.com".into())];
+        assert_eq!(autolink_bare_urls(alone.clone()), alone);
+
+        let options = MarkdownOptions {
+            autolink_bare_urls: false,
+            ..Default::default()
+        };
+        let html = render_result_with_options(text, &options).await.html;
+        assert!(
+            html.contains("See https://example.com/docs_(v2)/a?b=1. Or (https://example.org)."),
+            "left as text: {html}"
+        );
+    }
+
+    #[test]
+    fn normalize_line_endings_strips_bom_and_crlf() {
+        assert_eq!(
+            normalize_line_endings("\u{feff}---\r\ntitle: A\r\n---\r\n".to_string()),
+            "---\ntitle: A\n---\n"
+        );
+        // Only a leading BOM is removed; lone CRs are left alone
+        assert_eq!(
+            normalize_line_endings("a\u{feff}b\rc".to_string()),
+            "a\u{feff}b\rc"
+        );
+    }
+
+    #[tokio::test]
+    async fn markdown_options_normalize_line_endings() {
+        let windows = "\u{feff}---\r\ntitle: Saved on Windows\r\n---\r\n\r\n# Heading\r\n";
+        let result = render_result(windows).await;
+        assert_eq!(
+            result.frontmatter.get("title"),
+            Some(&serde_json::Value::String("Saved on Windows".to_string()))
+        );
+
+        // Without normalization the BOM hides the frontmatter block
+        let options = MarkdownOptions {
+            normalize_line_endings: false,
+            ..Default::default()
+        };
+        let result = render_result_with_options(windows, &options).await;
+        assert_ne!(
+            result.frontmatter.get("title"),
+            Some(&serde_json::Value::String("Saved on Windows".to_string()))
+        );
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(windows.as_bytes()).unwrap();
+        let meta = extract_metadata_from_file(file.path(), true).unwrap();
+        assert_eq!(
+            meta.metadata.get("title"),
+            Some(&serde_json::Value::String("Saved on Windows".to_string()))
+        );
+    }
+
+    #[test]
+    fn markdown_options_parser_flags() {
+        let options = MarkdownOptions {
+            footnotes: false,
+            super_subscript: false,
+            ..Default::default()
+        }
+        .parser_options();
+        assert!(!options.contains(Options::ENABLE_FOOTNOTES));
+        assert!(!options.contains(Options::ENABLE_SUBSCRIPT));
+        assert!(options.contains(Options::ENABLE_TABLES));
+        // Features other parts of mbr rely on can't be turned off
+        assert!(options.contains(Options::ENABLE_WIKILINKS));
+        assert!(options.contains(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS));
+        assert_eq!(MarkdownOptions::default().parser_options(), Options::all());
+    }
+
+    #[tokio::test]
+    async fn heading_ids_are_unique_and_unicode_aware() {
+        let md = "# Setup\n\n## Setup\n\n## Setup\n\n## Café Ünïcode\n\n[[#Café Ünïcode]]\n";
+        let result = render_result(md).await;
+        let ids: Vec<&str> = result.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["setup", "setup-1", "setup-2", "café-ünïcode"]);
+        // Heading anchors, TOC entries and wikilink anchors all agree
+        assert!(
+            result.html.contains(r#"<h2 id="setup-1">"#),
+            "{}",
+            result.html
+        );
+        assert!(
+            result.html.contains(r#"<h2 id="café-ünïcode">"#),
+            "{}",
+            result.html
+        );
+        // (percent-encoded in the href, which browsers decode before matching)
+        assert!(
+            result
+                .html
+                .contains(r##"href="#caf%C3%A9-%C3%BCn%C3%AFcode""##),
+            "{}",
+            result.html
+        );
+
+        let options = MarkdownOptions {
+            heading_ids: SlugStyle::Ascii,
+            ..Default::default()
+        };
+        let result = render_result_with_options(md, &options).await;
+        let ids: Vec<&str> = result.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["setup", "setup-1", "setup-2", "cafe-unicode"]);
+        assert!(
+            result.html.contains(r#"<h2 id="cafe-unicode">"#),
+            "{}",
+            result.html
+        );
+    }
+
+    #[tokio::test]
+    async fn explicit_heading_id_is_kept_and_reserved() {
+        let result = render_result("## Intro {#setup}\n\n## Setup\n").await;
+        let ids: Vec<&str> = result.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["setup", "setup-1"]);
+        assert!(
+            result.html.contains(r#"<h2 id="setup">Intro</h2>"#),
+            "{}",
+            result.html
+        );
+    }
+
+    #[tokio::test]
+    async fn wikilink_anchor_is_slugified() {
+        let result = render_result("See [[Other Page#Getting Started]].").await;
+        assert!(
+            result.html.contains("#getting-started\""),
+            "{}",
+            result.html
+        );
+        assert_eq!(
+            result.outbound_links[0].anchor.as_deref(),
+            Some("#getting-started")
+        );
+    }
+
     /// Renders `content` with an explicit current-page URL and wikilink index,
     /// for exercising Obsidian-style body-wikilink resolution.
     async fn render_with_wikilinks(
@@ -1604,11 +2852,13 @@ mod tests {
             is_index_file: false,
             url_depth,
             current_page_url: current_page_url.to_string(),
+            clean_urls: true,
         };
         render(
             path,
             &root,
             0,
+            OembedFallback::default(),
             config,
             true, // server_mode
             false,
@@ -1616,6 +2866,9 @@ mod tests {
             false,
             &[],
             wikilink_index,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap()
@@ -1694,6 +2947,27 @@ mod tests {
         assert_eq!(result.outbound_links[0].to, "patrick-walsh");
     }
 
+    #[tokio::test]
+    async fn outbound_links_capture_block_context() {
+        let result = render_with_wikilinks(
+            "# Title\n\nRead the [`setup` guide](setup/) before\nstarting.\n\n- see [notes](notes/)\n\n[alone](alone/)",
+            "/page/",
+            None,
+            None,
+        )
+        .await;
+
+        assert_eq!(
+            result.outbound_links[0].context.as_deref(),
+            Some("Read the setup guide before starting.")
+        );
+        assert_eq!(
+            result.outbound_links[1].context.as_deref(),
+            Some("see notes")
+        );
+        assert_eq!(result.outbound_links[2].context, None);
+    }
+
     #[tokio::test]
     async fn invalid_yaml_frontmatter_is_captured_not_swallowed() {
         // Regression: this frontmatter uses `*` list markers with TAB
@@ -1724,6 +2998,47 @@ mod tests {
         assert!(result.frontmatter.contains_key("style"));
     }
 
+    #[tokio::test]
+    async fn frontmatter_chapters_are_normalized() {
+        let content = "---\nchapters:\n  - start: \"1:30\"\n    title: Middle\n  - start: 0\n    title: Intro\n---\n# Talk\n";
+        let result = render_result(content).await;
+        assert!(result.frontmatter_error.is_none());
+        assert_eq!(
+            result.frontmatter["chapters"],
+            serde_json::json!([
+                {"start": 0.0, "title": "Intro"},
+                {"start": 90.0, "title": "Middle"},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_frontmatter_chapters_are_reported() {
+        let content =
+            "---\ntitle: Talk\nchapters:\n  - start: soon\n    title: Intro\n---\n# Talk\n";
+        let result = render_result(content).await;
+        let err = result
+            .frontmatter_error
+            .expect("chapter error should be reported");
+        assert!(err.contains("chapter 1: invalid timestamp 'soon'"), "{err}");
+        assert!(!result.frontmatter.contains_key("chapters"));
+        assert!(result.frontmatter.contains_key("title"));
+    }
+
+    #[tokio::test]
+    async fn unrecognized_frontmatter_date_is_reported() {
+        let result = render_result("---\ndate: Jan 5, 2024\n---\n# Post\n").await;
+        assert!(result.frontmatter_error.is_none());
+
+        let result = render_result("---\ndate: the other day\n---\n# Post\n").await;
+        let err = result
+            .frontmatter_error
+            .expect("date error should be reported");
+        assert_eq!(err, r#"date: unrecognized date "the other day""#);
+        // The raw value is kept for templates
+        assert_eq!(result.frontmatter["date"], "the other day");
+    }
+
     #[test]
     fn extract_metadata_from_file_returns_relationships() {
         let mut file = NamedTempFile::new().unwrap();
@@ -1732,7 +3047,7 @@ mod tests {
             "---\ntype: person\nborn: 1901-05-02\nrelationships:\n  - type: child\n    from: \"[[Sam Doe]]\"\n---\n# John\n"
         )
         .unwrap();
-        let result = extract_metadata_from_file(file.path()).unwrap();
+        let result = extract_metadata_from_file(file.path(), true).unwrap();
         assert_eq!(
             result.metadata.get("type"),
             Some(&serde_json::Value::String("person".to_string()))
@@ -1742,6 +3057,22 @@ mod tests {
         assert_eq!(result.relationships[0].from.as_deref(), Some("[[Sam Doe]]"));
     }
 
+    #[test]
+    fn extract_metadata_from_file_finds_first_image() {
+        let cases = [
+            ("![Lead](lead.png)\n\nBody\n", Some("lead.png")),
+            ("---\ntitle: T\n---\n![Lead](lead.png)\n", Some("lead.png")),
+            ("---\nimage: cover.jpg\n---\n![Lead](lead.png)\n", None),
+            ("---\ntitle: T\n---\nNo images\n", None),
+        ];
+        for (md, expected) in cases {
+            let mut file = NamedTempFile::new().unwrap();
+            write!(file, "{md}").unwrap();
+            let result = extract_metadata_from_file(file.path(), true).unwrap();
+            assert_eq!(result.first_image.as_deref(), expected, "{md:?}");
+        }
+    }
+
     #[test]
     fn sentence_terminator_basic_cases() {
         assert_eq!(count_sentence_terminators(""), (0, false));
@@ -1790,6 +3121,29 @@ mod tests {
         assert_eq!(result.syllable_count, 0);
     }
 
+    #[test]
+    fn test_estimate_word_count_skips_frontmatter_and_code() {
+        let md = "---\ntitle: Four words in title\n---\n# Intro\n\n- one two\n\n```sh\necho not counted\n```\n\n| a | b |\n|---|---|\n";
+        assert_eq!(estimate_word_count(md), 5);
+        assert_eq!(estimate_word_count(""), 0);
+        // A thematic break that isn't frontmatter is not mistaken for it
+        assert_eq!(estimate_word_count("Hello\n\n---\n\nworld"), 2);
+    }
+
+    #[test]
+    fn test_extract_metadata_extrapolates_word_count() {
+        let mut file = NamedTempFile::new().unwrap();
+        let body = "word ".repeat(4000);
+        write!(file, "---\ntitle: Long\n---\n{body}").unwrap();
+        let meta = extract_metadata_from_file(file.path(), true).unwrap();
+        // Only the first 8KB is read; the estimate should still be close
+        assert!(
+            (3800..=4200).contains(&meta.word_count),
+            "estimate was {}",
+            meta.word_count
+        );
+    }
+
     #[tokio::test]
     async fn test_canceled_checkbox_dash() {
         let md = "- [-] canceled task";
@@ -1854,11 +3208,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1866,6 +3222,9 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
@@ -1875,6 +3234,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_first_image_skips_media_embeds() {
+        let md = "Intro\n\n![clip](demo.mp4)\n\n![Cover](images/cover.jpg \"A cover\")\n\n![](later.png)";
+        assert_eq!(
+            extract_first_image(md),
+            Some("images/cover.jpg".to_string())
+        );
+        assert_eq!(extract_first_image("No images here"), None);
+    }
+
     // H1 extraction tests
     #[test]
     fn test_extract_first_h1_basic() {
@@ -1924,11 +3293,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1936,6 +3307,9 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
@@ -1955,11 +3329,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1967,6 +3343,9 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
@@ -1987,11 +3366,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -1999,16 +3380,32 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
         assert!(result.has_h1);
+        assert_eq!(result.title_source, TitleSource::Heading);
         assert_eq!(
             result.frontmatter.get("title"),
             Some(&serde_json::Value::String("My Document Title".to_string()))
         );
     }
 
+    #[tokio::test]
+    async fn test_title_fallback_from_later_h1() {
+        // The first H1 anywhere titles the page, as in directory listings
+        let result = render_result("## Overview\n\n# Late Title\n\nBody.").await;
+        assert!(!result.has_h1);
+        assert_eq!(result.title_source, TitleSource::Heading);
+        assert_eq!(
+            result.frontmatter.get("title"),
+            Some(&serde_json::Value::String("Late Title".to_string()))
+        );
+    }
+
     #[tokio::test]
     async fn test_frontmatter_title_takes_precedence() {
         // Frontmatter title should take precedence over H1
@@ -2023,11 +3420,13 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -2035,10 +3434,14 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
         assert!(result.has_h1);
+        assert_eq!(result.title_source, TitleSource::Frontmatter);
         assert_eq!(
             result.frontmatter.get("title"),
             Some(&serde_json::Value::String("Frontmatter Title".to_string()))
@@ -2046,8 +3449,33 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_no_title_when_no_frontmatter_and_no_h1() {
-        // No frontmatter and no H1 - should have no title
+    async fn test_dedupe_title_h1_strips_matching_heading() {
+        let mut result =
+            render_result("---\ntitle: My Page\n---\n\n#  my page \n\n## Details\n").await;
+        assert!(result.dedupe_title_h1());
+        assert!(!result.html.contains("<h1"));
+        // The anchor survives for the TOC entry
+        assert!(result.html.contains(r#"<span id="my-page"></span>"#));
+        assert_eq!(result.headings.len(), 2);
+        assert!(!result.has_h1);
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_title_h1_keeps_differing_heading() {
+        let mut result = render_result("---\ntitle: My Page\n---\n\n# Something Else\n").await;
+        assert!(!result.dedupe_title_h1());
+        assert!(result.html.contains(">Something Else</h1>"));
+        assert!(result.has_h1);
+
+        // A title derived from the H1 itself is never a duplicate
+        let mut result = render_result("# Only Heading\n").await;
+        assert!(!result.dedupe_title_h1());
+        assert!(result.html.contains(">Only Heading</h1>"));
+    }
+
+    #[tokio::test]
+    async fn test_title_falls_back_to_filename() {
+        // No frontmatter and no H1 - title comes from the file name
         let md = "## Subsection\n\nSome content.";
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(md.as_bytes()).unwrap();
@@ -2059,11 +3487,14 @@ mod tests {
             is_index_file: false,
             url_depth: None,
             current_page_url: String::new(),
+            clean_urls: true,
         };
+        let expected = title_from_filename(&path);
         let result = render(
             path,
             &root,
             100,
+            OembedFallback::default(),
             config,
             false,
             false,
@@ -2071,11 +3502,18 @@ mod tests {
             false,
             &[],
             None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
         )
         .await
         .unwrap();
         assert!(!result.has_h1);
-        assert_eq!(result.frontmatter.get("title"), None);
+        assert_eq!(result.title_source, TitleSource::Filename);
+        assert_eq!(
+            result.frontmatter.get("title"),
+            Some(&serde_json::Value::String(expected))
+        );
     }
 
     // Media embed tests
@@ -2133,6 +3571,95 @@ mod tests {
         assert!(html.contains("youtube-nocookie.com/embed/dQw4w9WgXcQ"));
     }
 
+    #[tokio::test]
+    async fn test_oembed_fallback_link() {
+        let md = format!("Before\n\n{FAILING_OEMBED_URL}\n\nAfter");
+        let html = render_markdown_with_fallback(&md, 100, OembedFallback::Link).await;
+        assert!(html.contains(&format!(r#"<a href="{FAILING_OEMBED_URL}">"#)));
+        assert!(!html.contains("mbr-embed-unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_oembed_fallback_card() {
+        let md = format!("Before\n\n{FAILING_OEMBED_URL}\n\nAfter");
+        let html = render_markdown_with_fallback(&md, 100, OembedFallback::Card).await;
+        assert!(html.contains("mbr-embed-unavailable"), "Got: {html}");
+        assert!(html.contains("<svg class=\"mbr-embed-unavailable-icon\""));
+        assert!(html.contains(&format!(r#"href="{FAILING_OEMBED_URL}""#)));
+        assert!(html.contains(&format!("<p>{FAILING_OEMBED_URL}</p>")));
+    }
+
+    #[tokio::test]
+    async fn test_sanitize_keeps_generated_markup() {
+        let md = format!(
+            "# Title {{#top .lead}}\n\n\
+             | Left | Right |\n|:-----|------:|\n| a | b |\n\n\
+             - [x] done\n- [ ] todo\n\n\
+             Note[^1]\n\n[^1]: The note.\n\n\
+             ```mermaid\ngraph TD; A-->B\n```\n\n\
+             ```rust\nfn main() {{}}\n```\n\n\
+             ![Talk](https://youtu.be/dQw4w9WgXcQ)\n\n\
+             ![Clip](clip.mp4)\n\n\
+             ![Song](song.mp3)\n\n\
+             ![Paper](paper.pdf)\n\n\
+             {FAILING_OEMBED_URL}\n"
+        );
+        let html = render_markdown_with_fallback(&md, 100, OembedFallback::Card).await;
+        let sanitized = crate::sanitize::HtmlSanitizer::default().clean(&html);
+
+        // Every element and attribute mbr emitted is still there
+        fn shape(html: &str) -> Vec<(String, Vec<String>)> {
+            scraper::Html::parse_fragment(html)
+                .root_element()
+                .descendent_elements()
+                .map(|el| {
+                    let mut attrs: Vec<String> = el
+                        .value()
+                        .attrs()
+                        .map(|(name, _)| name.to_string())
+                        .collect();
+                    attrs.sort();
+                    (el.value().name().to_string(), attrs)
+                })
+                .collect()
+        }
+        assert_eq!(shape(&sanitized), shape(&html), "{sanitized}");
+        for expected in [
+            "mermaid",
+            "youtube-nocookie.com/embed/dQw4w9WgXcQ",
+            "<video controls",
+            r#"kind="chapters""#,
+            "<mbr-video-extras",
+            "<audio controls",
+            r#"data="../paper.pdf""#,
+            r#"viewBox="0 0 24 24""#,
+            r#"type="checkbox""#,
+        ] {
+            assert!(sanitized.contains(expected), "{expected} in {sanitized}");
+        }
+
+        let html = render_markdown("<p onclick=\"x()\">Hi</p>\n\n<script>x()</script>\n").await;
+        let sanitized = crate::sanitize::HtmlSanitizer::default().clean(&html);
+        assert!(!sanitized.contains("x()"), "{sanitized}");
+        assert!(sanitized.contains("Hi"));
+    }
+
+    #[tokio::test]
+    async fn test_oembed_fallback_omit() {
+        let md = format!("Before\n\n{FAILING_OEMBED_URL}\n\nAfter");
+        let html = render_markdown_with_fallback(&md, 100, OembedFallback::Omit).await;
+        assert!(!html.contains("127.0.0.1"), "Got: {html}");
+        assert!(html.contains("Before") && html.contains("After"));
+    }
+
+    #[tokio::test]
+    async fn test_oembed_fallback_ignored_when_oembed_disabled() {
+        // With oembed off nothing failed, so the URL stays a plain link
+        let md = format!("{FAILING_OEMBED_URL}\n");
+        let html = render_markdown_with_fallback(&md, 0, OembedFallback::Omit).await;
+        assert!(html.contains(&format!(r#"<a href="{FAILING_OEMBED_URL}">"#)));
+    }
+
     #[tokio::test]
     async fn test_pdf_embed_from_image_syntax() {
         let md = "![Important Document](report.pdf)";
@@ -2195,6 +3722,63 @@ mod tests {
         assert!(html.contains("<img"));
     }
 
+    fn heading(level: u8, text: &str) -> HeadingInfo {
+        HeadingInfo {
+            level,
+            text: text.to_string(),
+            id: text.to_lowercase(),
+            number: None,
+        }
+    }
+
+    #[test]
+    fn test_toc_options_level_bounds() {
+        let headings = vec![
+            heading(1, "Title"),
+            heading(2, "Intro"),
+            heading(3, "Detail"),
+            heading(4, "Aside"),
+        ];
+        let toc = TocOptions {
+            min_level: 2,
+            max_level: 3,
+            numbered: false,
+        };
+        let texts: Vec<String> = toc
+            .apply(headings.clone())
+            .into_iter()
+            .map(|h| h.text)
+            .collect();
+        assert_eq!(texts, vec!["Intro", "Detail"]);
+        assert_eq!(TocOptions::default().apply(headings).len(), 4);
+    }
+
+    #[test]
+    fn test_toc_options_numbering_with_skipped_levels() {
+        let headings = vec![
+            heading(3, "Preface"),
+            heading(2, "One"),
+            heading(4, "Skipped"),
+            heading(3, "Sibling"),
+            heading(4, "Deep"),
+            heading(2, "Two"),
+            heading(3, "Child"),
+        ];
+        let toc = TocOptions {
+            numbered: true,
+            ..Default::default()
+        };
+        let numbers: Vec<String> = toc
+            .apply(headings)
+            .into_iter()
+            .map(|h| h.number.unwrap())
+            .collect();
+        assert_eq!(
+            numbers,
+            vec!["1.", "2.", "2.1", "2.2", "2.2.1", "3.", "3.1"]
+        );
+    }
+
     #[tokio::test]
     async fn test_vid_shortcode() {
         let md = r#"{{ vid(path="test/video.mp4") }}"#;
@@ -2207,6 +3791,350 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_data_shortcode_renders_table() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("people.csv"), "name,role\nAda,Engineer\n").unwrap();
+        let data = Arc::new(DataFiles::load(dir.path()).unwrap());
+
+        let page = dir.path().join("page.md");
+        std::fs::write(
+            &page,
+            "# People\n\n{{ data(file=\"people\") }}\n\n{{ data(file=\"missing\") }}\n\n```\n{{ data(file=\"people\") }}\n```\n",
+        )
+        .unwrap();
+        let config = LinkTransformConfig {
+            markdown_extensions: vec!["md".to_string()],
+            index_file: "index.md".to_string(),
+            is_index_file: false,
+            url_depth: None,
+            current_page_url: String::new(),
+            clean_urls: true,
+        };
+        let html = render(
+            page,
+            dir.path(),
+            0,
+            OembedFallback::default(),
+            config,
+            false,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            Some(data),
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .await
+        .unwrap()
+        .html;
+        assert!(html.contains("<table class=\"mbr-data\">"), "{html}");
+        assert!(html.contains("<td>Ada</td><td>Engineer</td>"), "{html}");
+        // Unknown files and code blocks are left as written
+        assert!(html.contains("missing"), "{html}");
+        assert_eq!(html.matches("<table").count(), 1, "{html}");
+    }
+
+    #[tokio::test]
+    async fn test_gallery_shortcode_renders_grid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("photos/empty")).unwrap();
+        std::fs::write(dir.path().join("photos/b.png"), b"").unwrap();
+        std::fs::write(dir.path().join("photos/a.jpg"), b"").unwrap();
+
+        let page = dir.path().join("page.md");
+        std::fs::write(
+            &page,
+            "{{ gallery(dir=\"photos\") }}\n\n{{ gallery(dir=\"photos/empty\") }}\n\n{{ gallery(dir=\"missing\") }}\n\n```\n{{ gallery(dir=\"photos\") }}\n```\n",
+        )
+        .unwrap();
+        let config = LinkTransformConfig {
+            markdown_extensions: vec!["md".to_string()],
+            index_file: "index.md".to_string(),
+            is_index_file: false,
+            url_depth: Some(1),
+            current_page_url: "/page/".to_string(),
+            clean_urls: true,
+        };
+        let result = render(
+            page,
+            dir.path(),
+            0,
+            OembedFallback::default(),
+            config,
+            false,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .await
+        .unwrap();
+        let html = &result.html;
+        assert_eq!(
+            html.matches("<div class=\"mbr-gallery\">").count(),
+            2,
+            "{html}"
+        );
+        let a = html.find("thumbs/photos/a.jpg").expect(html);
+        let b = html.find("thumbs/photos/b.png").expect(html);
+        assert!(a < b, "sorted by name: {html}");
+        // Links are made relative for static builds
+        assert!(
+            html.contains("href=\"../.mbr/images/?path=/photos/a.jpg\""),
+            "{html}"
+        );
+        // Missing folders and code blocks are left as written
+        assert!(html.contains("missing"), "{html}");
+        let urls: Vec<&str> = result
+            .gallery_images
+            .iter()
+            .map(|image| image.url_path.as_str())
+            .collect();
+        assert_eq!(urls, ["/photos/a.jpg", "/photos/b.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_code_shortcode_embeds_source() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.rs"),
+            "// header\nfn main() {\n    let x = 1 < 2;\n}\n",
+        )
+        .unwrap();
+
+        let page = dir.path().join("page.md");
+        std::fs::write(
+            &page,
+            "{{ code(path=\"src/main.rs\", lines=\"2-4\") }}\n\n{{ code(path=\"src/main.rs\", lines=\"3-9\") }}\n\n{{ code(path=\"src/gone.rs\") }}\n\n```\n{{ code(path=\"src/main.rs\") }}\n```\n",
+        )
+        .unwrap();
+        let html = render_sync(
+            page,
+            dir.path(),
+            0,
+            OembedFallback::default(),
+            LinkTransformConfig {
+                markdown_extensions: vec!["md".to_string()],
+                index_file: "index.md".to_string(),
+                is_index_file: false,
+                url_depth: None,
+                current_page_url: "/page/".to_string(),
+                clean_urls: true,
+            },
+            None,
+            false,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .unwrap()
+        .html;
+        assert!(
+            html.contains(
+                "<pre><code class=\"language-rust\">fn main() {\n    let x = 1 &lt; 2;\n}\n</code></pre>"
+            ),
+            "{html}"
+        );
+        assert!(!html.contains("// header"), "{html}");
+        assert!(
+            html.contains("line range `3-9` is past the end of `src/main.rs` (4 lines)"),
+            "{html}"
+        );
+        assert!(
+            html.contains("Code include file `src/gone.rs` not found"),
+            "{html}"
+        );
+        // Code blocks are left as written
+        assert_eq!(
+            html.matches("class=\"mbr-code-include-error\"").count(),
+            2,
+            "{html}"
+        );
+        assert!(html.contains("{{ code(path="), "{html}");
+    }
+
+    fn render_figure_page(root: &Path, name: &str, content: &str) -> String {
+        let page = root.join(name);
+        std::fs::write(&page, content).unwrap();
+        render_sync(
+            page,
+            root,
+            0,
+            OembedFallback::default(),
+            LinkTransformConfig {
+                markdown_extensions: vec!["md".to_string()],
+                index_file: "index.md".to_string(),
+                is_index_file: false,
+                url_depth: None,
+                current_page_url: format!("/{}/", name.trim_end_matches(".md")),
+                clean_urls: true,
+            },
+            None,
+            false,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .unwrap()
+        .html
+    }
+
+    #[tokio::test]
+    async fn test_figures_are_numbered_and_listed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("images")).unwrap();
+        std::fs::write(dir.path().join("images/a.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("images/b.svg"), b"<svg/>").unwrap();
+
+        let html = render_figure_page(
+            dir.path(),
+            "report.md",
+            "{{ list_of_figures() }}\n\n\
+             {{ figure(src=\"images/a.png\", caption=\"The setup\") }}\n\n\
+             {{ figure(src=\"images/missing.png\", caption=\"Gone\") }}\n\n\
+             {{ figure(src=\"images/b.svg\", caption=\"Results\") }}\n",
+        );
+        assert!(
+            html.contains(concat!(
+                r#"<nav class="mbr-list-of-figures" aria-label="List of figures"><ol>"#,
+                r##"<li><a href="#figure-1">Figure 1. The setup</a></li>"##,
+                r##"<li><a href="#figure-2">Figure 2. Results</a></li></ol></nav>"##,
+            )),
+            "{html}"
+        );
+        assert!(
+            html.contains(concat!(
+                r#"<figure class="mbr-figure" id="figure-1"><img src="/images/a.png" alt="The setup">"#,
+                r#"<figcaption><span class="mbr-figure-number">Figure 1.</span> The setup</figcaption></figure>"#,
+            )),
+            "{html}"
+        );
+        assert!(
+            html.contains(r#"id="figure-2"><img src="/images/b.svg""#),
+            "{html}"
+        );
+        // A missing image is reported and doesn't take a number
+        assert!(
+            html.contains("Figure image `images/missing.png` not found"),
+            "{html}"
+        );
+        assert!(!html.contains("figure-3"), "{html}");
+
+        // Numbering starts over on each page
+        let html = render_figure_page(
+            dir.path(),
+            "other.md",
+            "{{ figure(src=\"images/b.svg\", caption=\"Again\") }}\n\n{{ list_of_figures() }}\n",
+        );
+        assert!(
+            html.contains(r#"id="figure-1"><img src="/images/b.svg""#),
+            "{html}"
+        );
+        assert!(
+            html.contains(r##"<li><a href="#figure-1">Figure 1. Again</a></li>"##),
+            "{html}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_streaming_matches_buffered() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let page = dir.path().join("page.md");
+        let mut md = String::from("---\ntitle: Long\n---\n\n# Long\n\n");
+        for i in 0..20 {
+            md.push_str(&format!(
+                "## Part {i}\n\nSome *text* with a [link](other.md).\n\n- one\n- two\n\n"
+            ));
+        }
+        std::fs::write(&page, md).unwrap();
+        let config = LinkTransformConfig {
+            markdown_extensions: vec!["md".to_string()],
+            index_file: "index.md".to_string(),
+            is_index_file: false,
+            url_depth: None,
+            current_page_url: "/page/".to_string(),
+            clean_urls: true,
+        };
+        let buffered = render(
+            page.clone(),
+            dir.path(),
+            0,
+            OembedFallback::default(),
+            config.clone(),
+            true,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut streaming = render_streaming(
+            page,
+            dir.path(),
+            0,
+            OembedFallback::default(),
+            config,
+            None,
+            true,
+            false,
+            HashSet::new(),
+            false,
+            &[],
+            None,
+            None,
+            RendererKind::Markdown,
+            &MarkdownOptions::default(),
+            256,
+        )
+        .await
+        .unwrap();
+        assert!(streaming.result.html.is_empty());
+        assert_eq!(streaming.result.headings.len(), buffered.headings.len());
+        assert_eq!(streaming.result.frontmatter, buffered.frontmatter);
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) = streaming.body.recv().await {
+            chunks.push(chunk);
+        }
+        assert!(chunks.len() > 1, "{chunks:?}");
+        // Chunks break between blocks, never inside one (the last may be just
+        // the closing section tag)
+        for chunk in &chunks[1..chunks.len() - 1] {
+            let block = chunk.trim_start();
+            assert!(
+                ["<h2", "<p>", "<ul>"]
+                    .iter()
+                    .any(|tag| block.starts_with(tag)),
+                "{chunk}"
+            );
+        }
+        assert_eq!(chunks.concat(), buffered.html);
+    }
+
     #[tokio::test]
     async fn test_vid_shortcode_with_spaces() {
         let md = r#"{{ vid(path="Eric Jones/Eric Jones - Metal 3.mp4")}}"#;
@@ -3202,4 +5130,77 @@ mod tests {
         assert!(html.contains("foo bar"));
         assert!(html.contains("print(1)"));
     }
+
+    #[test]
+    fn test_renderer_kind_for_path() {
+        let renderers = HashMap::from([(".TXT".to_string(), RendererKind::Plaintext)]);
+        assert_eq!(
+            RendererKind::for_path(Path::new("notes/todo.txt"), &renderers),
+            RendererKind::Plaintext
+        );
+        assert_eq!(
+            RendererKind::for_path(Path::new("notes/todo.md"), &renderers),
+            RendererKind::Markdown
+        );
+        assert_eq!(
+            RendererKind::for_path(Path::new("README"), &renderers),
+            RendererKind::Markdown
+        );
+        // Dialects are picked up by extension unless `renderers` says otherwise
+        assert_eq!(
+            RendererKind::for_path(Path::new("docs/intro.MDX"), &renderers),
+            RendererKind::Mdx
+        );
+        assert_eq!(
+            RendererKind::for_path(Path::new("report.qmd"), &renderers),
+            RendererKind::Quarto
+        );
+        let renderers = HashMap::from([("mdx".to_string(), RendererKind::Markdown)]);
+        assert_eq!(
+            RendererKind::for_path(Path::new("docs/intro.mdx"), &renderers),
+            RendererKind::Markdown
+        );
+    }
+
+    #[test]
+    fn test_prepare_source_mdx_mode() {
+        let source = "import X from './x'\n\n<Note>Hi</Note>\r\n";
+        let strip = MarkdownOptions::default();
+        assert_eq!(
+            strip.prepare_source(source.into(), RendererKind::Mdx),
+            "\nHi\n"
+        );
+        assert_eq!(
+            strip.prepare_source(source.into(), RendererKind::Markdown),
+            "import X from './x'\n\n<Note>Hi</Note>\n"
+        );
+        let passthrough = MarkdownOptions {
+            mdx_mode: MdxMode::Passthrough,
+            ..Default::default()
+        };
+        assert_eq!(
+            passthrough.prepare_source(source.into(), RendererKind::Mdx),
+            "import X from './x'\n\n<Note>Hi</Note>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_plaintext() {
+        let result = render_plaintext(
+            "# Not a heading\nx < y. Done!\n",
+            Path::new("notes/todo.txt"),
+        );
+        assert_eq!(
+            result.html,
+            "<pre class=\"mbr-plaintext\"># Not a heading\nx &lt; y. Done!\n</pre>\n"
+        );
+        assert!(result.headings.is_empty());
+        assert_eq!(
+            result.frontmatter.get("title"),
+            Some(&serde_json::json!("todo"))
+        );
+        assert_eq!(result.title_source, TitleSource::Filename);
+        assert_eq!(result.word_count, 8);
+        assert_eq!(result.sentence_count, 2);
+    }
 }
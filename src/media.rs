@@ -193,6 +193,456 @@ impl MediaEmbed {
     }
 }
 
+/// Matches the `<img src="...">` tags pulldown-cmark emits for `![alt](url)`.
+static IMG_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<img src="([^"]*)"[^>]*>"#).expect("Invalid IMG_TAG_RE regex pattern")
+});
+
+/// Modern image formats that static builds can generate next to JPEG/PNG originals.
+///
+/// Ordered by preference (most compact first), matching `<source>` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ImageVariantFormat {
+    Avif,
+    WebP,
+}
+
+impl ImageVariantFormat {
+    /// Parses a `config.image_formats` entry (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "avif" => Some(Self::Avif),
+            "webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    /// File extension appended to the original name (`photo.jpg.webp`).
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Avif => "avif",
+            Self::WebP => "webp",
+        }
+    }
+
+    /// MIME type used for the `<source type="...">` attribute.
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Avif => "image/avif",
+            Self::WebP => "image/webp",
+        }
+    }
+
+    const fn image_format(&self) -> image::ImageFormat {
+        match self {
+            Self::Avif => image::ImageFormat::Avif,
+            Self::WebP => image::ImageFormat::WebP,
+        }
+    }
+
+    /// True if this build of mbr includes an encoder for the format.
+    pub fn encoder_available(&self) -> bool {
+        self.image_format().writing_enabled()
+    }
+}
+
+/// Returns true for images that can be transcoded to a modern format (JPEG/PNG).
+pub fn is_transcodable_image(path: &str) -> bool {
+    MediaEmbed::extension_from_url(path)
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "jpg" | "jpeg" | "png"))
+}
+
+/// Transcodes a JPEG/PNG image at `source` into `format`, writing it to `dest`.
+///
+/// The WebP encoder is lossless only, so PNGs usually shrink but JPEG photos
+/// rarely do.
+///
+/// Returns `Ok(false)` without writing anything when the encoded variant isn't
+/// smaller than the original, since serving it would only cost bandwidth.
+pub fn transcode_image(
+    source: &std::path::Path,
+    dest: &std::path::Path,
+    format: ImageVariantFormat,
+) -> Result<bool, image::ImageError> {
+    let original_len = std::fs::metadata(source)?.len();
+    let img = image::open(source)?;
+    // Encoders only accept 8-bit buffers; normalize 16-bit PNGs and friends.
+    let img = if img.color().has_alpha() {
+        image::DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        image::DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut encoded, format.image_format())?;
+    let encoded = encoded.into_inner();
+    if encoded.len() as u64 >= original_len {
+        return Ok(false);
+    }
+
+    std::fs::write(dest, encoded)?;
+    Ok(true)
+}
+
+/// Encodes a thumbnail of the JPEG/PNG image at `source` that fits within
+/// `max_size` pixels on each side, honouring its EXIF orientation.
+///
+/// PNGs stay PNG so transparency survives; everything else becomes JPEG.
+/// Images already within bounds are re-encoded at their original size.
+pub fn thumbnail_bytes(
+    source: &std::path::Path,
+    max_size: u32,
+) -> Result<Vec<u8>, image::ImageError> {
+    use image::ImageDecoder;
+
+    let mut decoder = image::ImageReader::open(source)?
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = image::DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    if img.width() > max_size || img.height() > max_size {
+        img = img.thumbnail(max_size, max_size);
+    }
+
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    let is_png = MediaEmbed::extension_from_url(&source.to_string_lossy())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if is_png {
+        img.write_to(&mut encoded, image::ImageFormat::Png)?;
+    } else {
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
+            &mut encoded,
+            crate::constants::JPEG_QUALITY,
+        );
+        image::DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)?;
+    }
+    Ok(encoded.into_inner())
+}
+
+/// Reads the EXIF capture date of the image at `path` as
+/// `YYYY:MM:DD HH:MM:SS`, which sorts chronologically as a string.
+///
+/// Prefers `DateTimeOriginal`, falling back to the IFD0 `DateTime`.
+pub fn exif_capture_date(path: &std::path::Path) -> Option<String> {
+    use image::ImageDecoder;
+
+    let mut decoder = image::ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?;
+    let exif = decoder.exif_metadata().ok()??;
+    exif_date_from_tiff(&exif)
+}
+
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const EXIF_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const EXIF_DATE_TIME: u16 = 0x0132;
+
+/// Extracts the capture date from raw EXIF (TIFF-structured) bytes.
+fn exif_date_from_tiff(data: &[u8]) -> Option<String> {
+    let data = data.strip_prefix(b"Exif\0\0").unwrap_or(data);
+    let little_endian = match data.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| -> Option<u16> {
+        let bytes: [u8; 2] = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    // Value offset of `tag` within the IFD at `ifd`, if present.
+    let find_tag = |ifd: usize, tag: u16| -> Option<usize> {
+        let count = u16_at(ifd)? as usize;
+        (0..count)
+            .map(|i| ifd + 2 + i * 12)
+            .find(|&entry| u16_at(entry) == Some(tag))
+            .map(|entry| entry + 8)
+    };
+    // ASCII dates are 20 bytes (19 chars + NUL), always stored out of line.
+    let date_at = |value: usize| -> Option<String> {
+        let start = u32_at(value)? as usize;
+        let raw = data.get(start..start + 19)?;
+        let date = std::str::from_utf8(raw).ok()?;
+        (date.is_ascii() && !date.starts_with("0000")).then(|| date.to_string())
+    };
+
+    let ifd0 = u32_at(4)? as usize;
+    find_tag(ifd0, EXIF_IFD_POINTER)
+        .and_then(u32_at)
+        .and_then(|exif_ifd| find_tag(exif_ifd as usize, EXIF_DATE_TIME_ORIGINAL))
+        .and_then(date_at)
+        .or_else(|| find_tag(ifd0, EXIF_DATE_TIME).and_then(date_at))
+}
+
+/// Wraps `<img>` tags in `<picture>` markup offering modern-format sources.
+///
+/// `variants_for` receives each image's (HTML-decoded) `src` and returns the
+/// formats that were generated for it, most preferred first. Images without
+/// variants are left untouched so browsers fall back to the original.
+pub fn wrap_images_in_picture(
+    html: &str,
+    variants_for: impl Fn(&str) -> Vec<ImageVariantFormat>,
+) -> String {
+    IMG_TAG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let img_tag = &caps[0];
+            let src = &caps[1];
+            let variants = variants_for(&html_escape::decode_html_entities(src));
+            if variants.is_empty() {
+                return img_tag.to_string();
+            }
+            let sources: String = variants
+                .iter()
+                .map(|format| {
+                    format!(
+                        r#"<source type="{}" srcset="{}.{}">"#,
+                        format.mime_type(),
+                        src,
+                        format.extension()
+                    )
+                })
+                .collect();
+            format!("<picture>{sources}{img_tag}</picture>")
+        })
+        .into_owned()
+}
+
+/// Matches any `<img>` tag, including ones written as raw HTML.
+static ANY_IMG_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<img\b[^>]*>").expect("Invalid ANY_IMG_TAG_RE regex pattern"));
+
+/// Matches an explicit `loading` attribute inside an `<img>` tag.
+static LOADING_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\sloading\s*=").expect("Invalid LOADING_ATTR_RE regex pattern")
+});
+
+/// Adds `loading="lazy"` and `decoding="async"` to every `<img>` but the first.
+///
+/// The first image stays eager since it's often the largest contentful paint.
+/// Images that already carry a `loading` attribute are left untouched.
+pub fn add_lazy_loading(html: &str) -> String {
+    add_lazy_loading_after(html, &mut false)
+}
+
+/// [`add_lazy_loading`] for a page sent in pieces. `seen_first` carries over
+/// between pieces so only the page's first image stays eager.
+pub fn add_lazy_loading_after(html: &str, seen_first: &mut bool) -> String {
+    ANY_IMG_TAG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let img_tag = &caps[0];
+            if !std::mem::replace(seen_first, true) || LOADING_ATTR_RE.is_match(img_tag) {
+                return img_tag.to_string();
+            }
+            let (head, close) = match img_tag.strip_suffix("/>") {
+                Some(head) => (head.trim_end(), " />"),
+                None => (&img_tag[..img_tag.len() - 1], ">"),
+            };
+            format!(r#"{head} loading="lazy" decoding="async"{close}"#)
+        })
+        .into_owned()
+}
+
+/// Matches the opening tag of an `<img>`, `<video>` or `<iframe>`.
+static MEDIA_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<(img|video|iframe)\b[^>]*>").expect("Invalid MEDIA_TAG_RE regex pattern")
+});
+
+/// Matches a `style` attribute, double- or single-quoted.
+static STYLE_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\sstyle\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("Invalid STYLE_ATTR_RE regex pattern")
+});
+
+/// Matches the author's per-element `data-max-width` override.
+static MAX_WIDTH_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\sdata-max-width\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("Invalid MAX_WIDTH_ATTR_RE regex pattern")
+});
+
+/// Matches a numeric `width` or `height` attribute.
+static DIMENSION_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\s(width|height)\s*=\s*["']?(\d+)["']?"#)
+        .expect("Invalid DIMENSION_ATTR_RE regex pattern")
+});
+
+/// Caps the width of every `<img>`, `<video>` and `<iframe>` at `max_width`
+/// (a CSS length) with an inline style, keeping the aspect ratio: height is
+/// set to `auto`, and iframes with `width`/`height` attributes also get a
+/// matching `aspect-ratio`.
+///
+/// An element's `data-max-width` attribute overrides the limit, and
+/// `data-max-width="none"` leaves it alone. Elements whose style already sets
+/// `max-width` are left alone too.
+pub fn constrain_media_width(html: &str, max_width: &str) -> String {
+    MEDIA_TAG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let tag = &caps[0];
+            let limit = match MAX_WIDTH_ATTR_RE
+                .captures(tag)
+                .and_then(|c| c.get(1).or_else(|| c.get(2)))
+                .map(|m| m.as_str().trim())
+            {
+                Some("none") => return tag.to_string(),
+                Some(value) if crate::config::is_css_length(value) => value,
+                _ => max_width,
+            };
+            let style = STYLE_ATTR_RE.captures(tag);
+            let existing = style
+                .as_ref()
+                .and_then(|c| c.get(1).or_else(|| c.get(2)))
+                .map_or("", |m| m.as_str());
+            if existing.to_ascii_lowercase().contains("max-width") {
+                return tag.to_string();
+            }
+
+            let mut decl = format!("max-width: {limit}; height: auto;");
+            if caps[1].eq_ignore_ascii_case("iframe") {
+                let dimension = |name: &str| {
+                    DIMENSION_ATTR_RE
+                        .captures_iter(tag)
+                        .find(|c| c[1].eq_ignore_ascii_case(name))
+                        .map(|c| c[2].to_string())
+                };
+                match (dimension("width"), dimension("height")) {
+                    (Some(width), Some(height)) => {
+                        decl.push_str(&format!(" aspect-ratio: {width} / {height};"));
+                    }
+                    // Without a ratio, an auto height would collapse the frame
+                    _ => decl = format!("max-width: {limit};"),
+                }
+            }
+
+            match style.and_then(|c| c.get(0)) {
+                // The author's own declarations come last so they still win
+                Some(attr) => format!(
+                    r#"{} style="{decl} {existing}"{}"#,
+                    &tag[..attr.start()],
+                    &tag[attr.end()..]
+                ),
+                None => {
+                    let (head, close) = match tag.strip_suffix("/>") {
+                        Some(head) => (head.trim_end(), " />"),
+                        None => (&tag[..tag.len() - 1], ">"),
+                    };
+                    format!(r#"{head} style="{decl}"{close}"#)
+                }
+            }
+        })
+        .into_owned()
+}
+
+/// Matches an `<iframe>` element, including raw HTML ones.
+static IFRAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<iframe\b([^>]*)>.*?</iframe>").expect("Invalid IFRAME_RE regex pattern")
+});
+
+/// Matches the `src` attribute of an `<iframe>`, double- or single-quoted.
+static IFRAME_SRC_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\ssrc\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("Invalid IFRAME_SRC_RE regex pattern")
+});
+
+/// Matches the `title` attribute of an `<iframe>`.
+static IFRAME_TITLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\stitle\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("Invalid IFRAME_TITLE_RE regex pattern")
+});
+
+/// Matches a YouTube player URL on the tracking domain.
+static YOUTUBE_PLAYER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?i)(?:https?:)?//(?:www\.)?youtube\.com/embed/")
+        .expect("Invalid YOUTUBE_PLAYER_RE regex pattern")
+});
+
+/// Matches a Vimeo player URL.
+static VIMEO_PLAYER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?i)(?:https?:)?//player\.vimeo\.com/video/\d+")
+        .expect("Invalid VIMEO_PLAYER_RE regex pattern")
+});
+
+/// Rewrites YouTube and Vimeo player iframes for privacy: YouTube players
+/// move to `youtube-nocookie.com`, Vimeo players get `dnt=1`, and each is
+/// wrapped in a click-to-play facade so nothing loads from the provider
+/// until the reader asks for it.
+///
+/// The facade holds the player in a `<template>`; the `_embed_facades.html`
+/// script swaps it in on click. Other iframes are left untouched.
+pub fn privacy_embeds(html: &str) -> String {
+    IFRAME_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let iframe = &caps[0];
+            let Some(src_caps) = IFRAME_SRC_RE.captures(&caps[1]) else {
+                return iframe.to_string();
+            };
+            let src = src_caps
+                .get(1)
+                .or_else(|| src_caps.get(2))
+                .map_or("", |m| m.as_str());
+            let (provider, private_src, thumbnail) = if YOUTUBE_PLAYER_RE.is_match(src)
+                || src.contains("youtube-nocookie.com/embed/")
+            {
+                let private_src = YOUTUBE_PLAYER_RE
+                    .replace(src, "https://www.youtube-nocookie.com/embed/")
+                    .into_owned();
+                let thumbnail = MediaEmbed::extract_youtube_id(src)
+                    .map(|id| format!("https://i.ytimg.com/vi/{id}/hqdefault.jpg"));
+                ("youtube", private_src, thumbnail)
+            } else if VIMEO_PLAYER_RE.is_match(src) {
+                let private_src = if src.contains("dnt=") {
+                    src.to_string()
+                } else if src.contains('?') {
+                    format!("{src}&amp;dnt=1")
+                } else {
+                    format!("{src}?dnt=1")
+                };
+                ("vimeo", private_src, None)
+            } else {
+                return iframe.to_string();
+            };
+
+            let attrs = &caps[1];
+            let src_attr = src_caps.get(0).expect("whole match");
+            // Everything after the attributes: `>` through `</iframe>`
+            let rest = &iframe[caps.get(1).expect("attrs").end() - caps.get(0).expect("iframe").start()..];
+            let player = format!(
+                r#"<iframe{} src="{private_src}"{}{rest}"#,
+                &attrs[..src_attr.start()],
+                &attrs[src_attr.end()..],
+            );
+            let title = IFRAME_TITLE_RE
+                .captures(attrs)
+                .and_then(|t| t.get(1).or_else(|| t.get(2)))
+                .map_or("video".into(), |m| {
+                    html_escape::encode_double_quoted_attribute(
+                        &html_escape::decode_html_entities(m.as_str()),
+                    )
+                    .into_owned()
+                });
+            let thumbnail = thumbnail
+                .map(|src| format!(r#"<img src="{src}" alt="" loading="lazy" decoding="async" />"#))
+                .unwrap_or_default();
+            format!(
+                r#"<div class="mbr-embed-facade" data-provider="{provider}"><button type="button" class="mbr-embed-facade-play" aria-label="Play {title}">{thumbnail}</button><template>{player}</template></div>"#
+            )
+        })
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -413,4 +863,286 @@ mod tests {
         assert_eq!(youtube.html_close(), "</figcaption></figure>");
         assert_eq!(pdf.html_close(), "</figcaption></figure>");
     }
+
+    // Image variant tests
+    #[test]
+    fn test_image_variant_format_from_name() {
+        assert_eq!(
+            ImageVariantFormat::from_name("webp"),
+            Some(ImageVariantFormat::WebP)
+        );
+        assert_eq!(
+            ImageVariantFormat::from_name("AVIF"),
+            Some(ImageVariantFormat::Avif)
+        );
+        assert_eq!(ImageVariantFormat::from_name("jxl"), None);
+        assert!(ImageVariantFormat::WebP.encoder_available());
+    }
+
+    #[test]
+    fn test_is_transcodable_image() {
+        assert!(is_transcodable_image("photos/cat.JPG"));
+        assert!(is_transcodable_image("diagram.png"));
+        assert!(!is_transcodable_image("anim.gif"));
+        assert!(!is_transcodable_image("icon.svg"));
+    }
+
+    #[test]
+    fn test_transcode_image_png_to_webp() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("flat.png");
+        let dest = dir.path().join("flat.png.webp");
+        // A large flat-color image compresses far better as lossless WebP.
+        image::RgbImage::from_pixel(256, 256, image::Rgb([200, 30, 30]))
+            .save(&source)
+            .unwrap();
+
+        let written = transcode_image(&source, &dest, ImageVariantFormat::WebP).unwrap();
+        assert!(written);
+        assert!(image::open(&dest).is_ok());
+    }
+
+    #[test]
+    fn test_transcode_image_rejects_non_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("broken.png");
+        std::fs::write(&source, b"not an image").unwrap();
+
+        let result = transcode_image(
+            &source,
+            &dir.path().join("broken.png.webp"),
+            ImageVariantFormat::WebP,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_bytes_scales_down_and_keeps_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = dir.path().join("wide.png");
+        image::RgbaImage::from_pixel(400, 200, image::Rgba([10, 20, 30, 128]))
+            .save(&png)
+            .unwrap();
+        let thumb = image::load_from_memory(&thumbnail_bytes(&png, 100).unwrap()).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (100, 50));
+        assert!(thumb.color().has_alpha());
+
+        let jpg = dir.path().join("small.jpg");
+        image::RgbImage::from_pixel(40, 30, image::Rgb([200, 30, 30]))
+            .save(&jpg)
+            .unwrap();
+        let bytes = thumbnail_bytes(&jpg, 100).unwrap();
+        assert_eq!(
+            image::guess_format(&bytes).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+        let thumb = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (40, 30));
+    }
+
+    /// Builds a minimal TIFF blob: IFD0 with the given entries, followed by the
+    /// value area. Entries are (tag, type, count, value-or-offset).
+    fn tiff(big_endian: bool, entries: &[(u16, u16, u32, u32)], tail: &[u8]) -> Vec<u8> {
+        let u16b = |v: u16| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+        let u32b = |v: u32| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(if big_endian { b"MM" } else { b"II" });
+        out.extend_from_slice(&u16b(42));
+        out.extend_from_slice(&u32b(8));
+        out.extend_from_slice(&u16b(entries.len() as u16));
+        for &(tag, kind, count, value) in entries {
+            out.extend_from_slice(&u16b(tag));
+            out.extend_from_slice(&u16b(kind));
+            out.extend_from_slice(&u32b(count));
+            out.extend_from_slice(&u32b(value));
+        }
+        out.extend_from_slice(&u32b(0));
+        out.extend_from_slice(tail);
+        out
+    }
+
+    #[test]
+    fn test_exif_date_prefers_date_time_original() {
+        // IFD0 at 8 with two entries ends at 8 + 2 + 24 + 4 = 38.
+        let exif_ifd = 38u32;
+        let mut tail = Vec::new();
+        // Exif IFD: one entry pointing at its date, then the two date strings.
+        tail.extend_from_slice(&1u16.to_le_bytes());
+        tail.extend_from_slice(&EXIF_DATE_TIME_ORIGINAL.to_le_bytes());
+        tail.extend_from_slice(&2u16.to_le_bytes());
+        tail.extend_from_slice(&20u32.to_le_bytes());
+        tail.extend_from_slice(&(exif_ifd + 18).to_le_bytes());
+        tail.extend_from_slice(&0u32.to_le_bytes());
+        tail.extend_from_slice(b"2021:07:04 12:00:00\0");
+        tail.extend_from_slice(b"2024:01:01 00:00:00\0");
+        let blob = tiff(
+            false,
+            &[
+                (EXIF_DATE_TIME, 2, 20, exif_ifd + 38),
+                (EXIF_IFD_POINTER, 4, 1, exif_ifd),
+            ],
+            &tail,
+        );
+        assert_eq!(
+            exif_date_from_tiff(&blob).as_deref(),
+            Some("2021:07:04 12:00:00")
+        );
+
+        let mut prefixed = b"Exif\0\0".to_vec();
+        prefixed.extend_from_slice(&blob);
+        assert!(exif_date_from_tiff(&prefixed).is_some());
+    }
+
+    #[test]
+    fn test_exif_date_falls_back_to_date_time() {
+        let blob = tiff(
+            true,
+            &[(EXIF_DATE_TIME, 2, 20, 26)],
+            b"2019:12:31 23:59:59\0",
+        );
+        assert_eq!(
+            exif_date_from_tiff(&blob).as_deref(),
+            Some("2019:12:31 23:59:59")
+        );
+        assert_eq!(exif_date_from_tiff(b"garbage"), None);
+        assert_eq!(exif_date_from_tiff(&blob[..20]), None);
+    }
+
+    #[test]
+    fn test_wrap_images_in_picture() {
+        let html = r#"<p><img src="cat.jpg" alt="Cat" /> <img src="dog.gif" alt="Dog" /></p>"#;
+        let wrapped = wrap_images_in_picture(html, |src| {
+            if src == "cat.jpg" {
+                vec![ImageVariantFormat::Avif, ImageVariantFormat::WebP]
+            } else {
+                Vec::new()
+            }
+        });
+        assert_eq!(
+            wrapped,
+            r#"<p><picture><source type="image/avif" srcset="cat.jpg.avif"><source type="image/webp" srcset="cat.jpg.webp"><img src="cat.jpg" alt="Cat" /></picture> <img src="dog.gif" alt="Dog" /></p>"#
+        );
+    }
+
+    #[test]
+    fn test_add_lazy_loading_skips_first_image() {
+        let html =
+            r#"<p><img src="a.jpg" alt="A" /> <img src="b.jpg" alt="B" /> <img src="c.jpg"></p>"#;
+        assert_eq!(
+            add_lazy_loading(html),
+            r#"<p><img src="a.jpg" alt="A" /> <img src="b.jpg" alt="B" loading="lazy" decoding="async" /> <img src="c.jpg" loading="lazy" decoding="async"></p>"#
+        );
+    }
+
+    #[test]
+    fn test_add_lazy_loading_respects_explicit_loading() {
+        let html = r#"<img src="a.jpg" /><img src="b.jpg" loading="eager" />"#;
+        assert_eq!(add_lazy_loading(html), html);
+    }
+
+    #[test]
+    fn test_privacy_embeds_youtube_facade() {
+        let html = MediaEmbed::YouTube {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            caption: None,
+        }
+        .to_html(false, false, false);
+        let out = privacy_embeds(&html);
+        assert!(out.contains(r#"<div class="mbr-embed-facade" data-provider="youtube">"#));
+        assert!(out.contains(r#"aria-label="Play YouTube video player""#));
+        assert!(out.contains(r#"<img src="https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg""#));
+        // The player only exists inside the template
+        let (before, after) = out.split_once("<template>").unwrap();
+        assert!(!before.contains("<iframe"));
+        assert!(after.contains(r#"src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ""#));
+        assert!(after.contains("</iframe></template></div>"));
+    }
+
+    #[test]
+    fn test_privacy_embeds_rewrites_raw_youtube_iframe() {
+        let html = r#"<iframe width="560" src='https://www.youtube.com/embed/dQw4w9WgXcQ?start=30' title='Say "hi"'></iframe>"#;
+        let out = privacy_embeds(html);
+        assert!(out.contains(
+            r#"<iframe width="560" src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?start=30" title='Say "hi"'></iframe>"#
+        ));
+        assert!(out.contains(r#"aria-label="Play Say &quot;hi&quot;""#));
+    }
+
+    #[test]
+    fn test_privacy_embeds_vimeo_do_not_track() {
+        let out =
+            privacy_embeds(r#"<iframe src="https://player.vimeo.com/video/76979871"></iframe>"#);
+        assert!(out.contains(r#"data-provider="vimeo""#));
+        assert!(out.contains(r#"src="https://player.vimeo.com/video/76979871?dnt=1""#));
+        assert!(out.contains(r#"aria-label="Play video""#));
+
+        let out = privacy_embeds(
+            r#"<iframe src="https://player.vimeo.com/video/76979871?h=abc&amp;dnt=0"></iframe>"#,
+        );
+        assert!(out.contains(r#"src="https://player.vimeo.com/video/76979871?h=abc&amp;dnt=0""#));
+        let out = privacy_embeds(
+            r#"<iframe src="https://player.vimeo.com/video/76979871?h=abc"></iframe>"#,
+        );
+        assert!(out.contains(r#"src="https://player.vimeo.com/video/76979871?h=abc&amp;dnt=1""#));
+    }
+
+    #[test]
+    fn test_privacy_embeds_leaves_other_iframes() {
+        let html = r#"<p>Map</p><iframe src="https://www.openstreetmap.org/export/embed.html"></iframe><iframe srcdoc="x"></iframe>"#;
+        assert_eq!(privacy_embeds(html), html);
+    }
+
+    #[test]
+    fn test_constrain_media_width_adds_style() {
+        let html = r#"<p><img src="a.png" alt="A" /></p><video controls src="v.mp4"></video>"#;
+        assert_eq!(
+            constrain_media_width(html, "40rem"),
+            r#"<p><img src="a.png" alt="A" style="max-width: 40rem; height: auto;" /></p><video controls src="v.mp4" style="max-width: 40rem; height: auto;"></video>"#
+        );
+    }
+
+    #[test]
+    fn test_constrain_media_width_iframe_keeps_aspect_ratio() {
+        let out = constrain_media_width(
+            r#"<iframe width="560" height="315" src="x"></iframe><iframe src="y"></iframe>"#,
+            "100%",
+        );
+        assert!(out.contains(
+            r#"<iframe width="560" height="315" src="x" style="max-width: 100%; height: auto; aspect-ratio: 560 / 315;">"#
+        ));
+        assert!(out.contains(r#"<iframe src="y" style="max-width: 100%;">"#));
+    }
+
+    #[test]
+    fn test_constrain_media_width_author_overrides() {
+        // Per-element limit, merged ahead of the author's own style
+        let out = constrain_media_width(
+            r#"<img src="a.png" data-max-width="200px" style="border: 0">"#,
+            "40rem",
+        );
+        assert_eq!(
+            out,
+            r#"<img src="a.png" data-max-width="200px" style="max-width: 200px; height: auto; border: 0">"#
+        );
+        // Opting out, an explicit max-width, and a bogus override
+        let untouched =
+            r#"<img src="a.png" data-max-width="none"><img src="b.png" style="max-width: 10px">"#;
+        assert_eq!(constrain_media_width(untouched, "40rem"), untouched);
+        assert!(
+            constrain_media_width(r#"<img data-max-width="1px; color: red">"#, "40rem")
+                .contains("max-width: 40rem;")
+        );
+    }
 }
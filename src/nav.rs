@@ -0,0 +1,229 @@
+//! Curated sidebar navigation from `.mbr/nav.yml` (or `nav.yaml` / `nav.toml`).
+//!
+//! When present, the manifest is passed to templates as `site_nav` alongside
+//! the auto-generated sidebar. Entries have a `title` and either a `url`, a
+//! list of `children`, or both:
+//!
+//! ```yaml
+//! nav:
+//!   - title: Getting Started
+//!     url: /docs/start/
+//!   - title: Guides
+//!     children:
+//!       - title: Install
+//!         url: /docs/install/
+//!       - title: Upstream docs
+//!         url: https://example.com/docs
+//! ```
+//!
+//! Site URLs start with `/` and are checked against the repository; entries
+//! that don't resolve keep their title but lose their link.
+
+use crate::errors::NavError;
+use crate::path_resolver::{
+    PathResolverConfig, ResolvedPath, normalize_link_target, resolve_request_path,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Manifest file names looked up in `.mbr/`, in order of precedence.
+pub const NAV_FILES: &[&str] = &["nav.yml", "nav.yaml", "nav.toml"];
+
+/// A sidebar entry: a link, a section heading, or a linked section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavItem {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<NavItem>,
+}
+
+#[derive(Deserialize)]
+struct NavFile {
+    nav: Vec<NavItem>,
+}
+
+/// Returns true for URLs pointing into the site (`/docs/`), as opposed to
+/// external (`https://…`, `//cdn…`, `mailto:…`) or relative links.
+pub fn is_site_url(url: &str) -> bool {
+    url.starts_with('/') && !url.starts_with("//")
+}
+
+/// Returns the manifest path under `root_dir`, if one exists.
+pub fn manifest_path(root_dir: &Path) -> Option<PathBuf> {
+    let mbr_dir = root_dir.join(".mbr");
+    NAV_FILES
+        .iter()
+        .map(|name| mbr_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Loads and validates the navigation manifest under `root_dir`.
+///
+/// Returns `Ok(None)` when there is no manifest.
+pub fn load(root_dir: &Path) -> Result<Option<Vec<NavItem>>, NavError> {
+    let Some(path) = manifest_path(root_dir) else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path).map_err(|source| NavError::ReadFailed {
+        path: path.clone(),
+        source,
+    })?;
+    let invalid = |message: String| NavError::Invalid {
+        path: path.clone(),
+        message,
+    };
+
+    let file: NavFile = if path.extension().is_some_and(|ext| ext == "toml") {
+        use figment::providers::{Format, Toml};
+        figment::Figment::from(Toml::string(&contents))
+            .extract()
+            .map_err(|e| invalid(e.to_string()))?
+    } else {
+        let docs =
+            yaml_rust2::YamlLoader::load_from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        let yaml = docs
+            .first()
+            .ok_or_else(|| invalid("file is empty".into()))?;
+        serde_json::from_value(crate::relationships::yaml_to_json(yaml))
+            .map_err(|e| invalid(e.to_string()))?
+    };
+
+    validate_items(&file.nav)?;
+    Ok(Some(file.nav))
+}
+
+fn validate_items(items: &[NavItem]) -> Result<(), NavError> {
+    for item in items {
+        if item.title.trim().is_empty() {
+            return Err(NavError::MissingTitle);
+        }
+        if item.url.is_none() && item.children.is_empty() {
+            return Err(NavError::EmptyEntry {
+                title: item.title.clone(),
+            });
+        }
+        validate_items(&item.children)?;
+    }
+    Ok(())
+}
+
+/// Returns true if a site URL resolves to a page, directory, or file.
+pub fn url_resolves(resolver: &PathResolverConfig<'_>, url: &str) -> bool {
+    !matches!(
+        resolve_request_path(resolver, &normalize_link_target(url)),
+        ResolvedPath::NotFound
+    )
+}
+
+/// Navigation loaded by [`load_checked`].
+#[derive(Debug)]
+pub struct CheckedNav {
+    pub items: Vec<NavItem>,
+    /// Site URLs that didn't resolve (their entries were unlinked), for the
+    /// caller to report.
+    pub dangling: Vec<String>,
+}
+
+/// Loads the manifest under the resolver's base directory and unlinks entries
+/// whose site URL doesn't resolve.
+pub fn load_checked(resolver: &PathResolverConfig<'_>) -> Result<Option<CheckedNav>, NavError> {
+    Ok(load(resolver.base_dir)?.map(|mut items| {
+        let dangling = prune_dangling(&mut items, &|url| url_resolves(resolver, url));
+        CheckedNav { items, dangling }
+    }))
+}
+
+/// Drops the link from site-URL entries that don't pass `exists`, keeping
+/// their title and children. Returns the dangling URLs in manifest order.
+pub fn prune_dangling(items: &mut [NavItem], exists: &dyn Fn(&str) -> bool) -> Vec<String> {
+    let mut dangling = Vec::new();
+    for item in items {
+        if let Some(url) = item.url.as_deref()
+            && is_site_url(url)
+            && !exists(url)
+        {
+            dangling.extend(item.url.take());
+        }
+        dangling.extend(prune_dangling(&mut item.children, exists));
+    }
+    dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(name: &str, contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".mbr")).unwrap();
+        std::fs::write(dir.path().join(".mbr").join(name), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_yaml_and_toml() {
+        let yaml = write_manifest(
+            "nav.yml",
+            "nav:\n  - title: Start\n    url: /start/\n  - title: Guides\n    children:\n      - title: Install\n        url: /install/\n",
+        );
+        let nav = load(yaml.path()).unwrap().unwrap();
+        assert_eq!(nav.len(), 2);
+        assert_eq!(nav[0].url.as_deref(), Some("/start/"));
+        assert_eq!(nav[1].children[0].title, "Install");
+
+        let toml = write_manifest(
+            "nav.toml",
+            "[[nav]]\ntitle = \"Start\"\nurl = \"/start/\"\n\n[[nav]]\ntitle = \"Guides\"\n[[nav.children]]\ntitle = \"Install\"\nurl = \"/install/\"\n",
+        );
+        assert_eq!(load(toml.path()).unwrap().unwrap(), nav);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(load(empty.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_entries() {
+        let dir = write_manifest("nav.yml", "nav:\n  - title: Orphan\n");
+        assert!(matches!(
+            load(dir.path()),
+            Err(NavError::EmptyEntry { title }) if title == "Orphan"
+        ));
+
+        let dir = write_manifest("nav.yml", "nav:\n  - url: /start/\n");
+        assert!(matches!(load(dir.path()), Err(NavError::Invalid { .. })));
+
+        let dir = write_manifest("nav.yml", "nav:\n  - title: \" \"\n    url: /a/\n");
+        assert!(matches!(load(dir.path()), Err(NavError::MissingTitle)));
+    }
+
+    #[test]
+    fn test_prune_dangling_keeps_titles_and_external_links() {
+        let mut nav = vec![NavItem {
+            title: "Guides".to_string(),
+            url: Some("/guides/".to_string()),
+            children: vec![
+                NavItem {
+                    title: "Gone".to_string(),
+                    url: Some("/gone/#intro".to_string()),
+                    children: vec![],
+                },
+                NavItem {
+                    title: "Elsewhere".to_string(),
+                    url: Some("https://example.com/".to_string()),
+                    children: vec![],
+                },
+            ],
+        }];
+        let dangling = prune_dangling(&mut nav, &|url| url == "/guides/");
+        assert_eq!(dangling, vec!["/gone/#intro".to_string()]);
+        assert_eq!(nav[0].url.as_deref(), Some("/guides/"));
+        assert_eq!(nav[0].children[0].url, None);
+        assert_eq!(nav[0].children[0].title, "Gone");
+        assert_eq!(
+            nav[0].children[1].url.as_deref(),
+            Some("https://example.com/")
+        );
+    }
+}
@@ -4,6 +4,7 @@ use futures::StreamExt;
 use regex::Regex;
 use reqwest::Client;
 use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::net::IpAddr;
 use std::sync::LazyLock;
@@ -193,6 +194,23 @@ static GIPHY_PAGE_RE: LazyLock<Regex> = LazyLock::new(|| {
 static GIST_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"https?://gist\.github\.com/").unwrap());
 
+/// How a bare URL whose metadata fetch failed is rendered
+/// (`config.oembed_fallback`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OembedFallback {
+    /// A plain link to the URL.
+    #[default]
+    Link,
+    /// An "embed unavailable" card showing the URL and a generic thumbnail.
+    Card,
+    /// Nothing at all.
+    Omit,
+}
+
+/// Generic link thumbnail for [`OembedFallback::Card`].
+const FALLBACK_CARD_ICON: &str = r#"<svg class="mbr-embed-unavailable-icon" viewBox="0 0 24 24" width="48" height="48" fill="none" stroke="currentColor" stroke-width="1.5" stroke-linecap="round" stroke-linejoin="round" aria-hidden="true"><path d="M10 13a5 5 0 0 0 7.54.54l3-3a5 5 0 0 0-7.07-7.07l-1.72 1.71"/><path d="M14 11a5 5 0 0 0-7.54-.54l-3 3a5 5 0 0 0 7.07 7.07l1.71-1.71"/></svg>"#;
+
 #[derive(Default, Clone)]
 pub struct PageInfo {
     pub url: String,
@@ -203,6 +221,8 @@ pub struct PageInfo {
     /// Image/favicon URL, stored RAW; escaped at render time for attribute context
     pub image: Option<String>,
     pub embed_html: Option<String>, // For YouTube and other embeddable content
+    /// True when fetching the page failed, as opposed to oembed being disabled
+    pub failed: bool,
 }
 
 impl PageInfo {
@@ -338,6 +358,7 @@ impl PageInfo {
                 );
                 Ok(PageInfo {
                     url: url.to_string(),
+                    failed: true,
                     ..Default::default()
                 })
             }
@@ -348,6 +369,10 @@ impl PageInfo {
     /// hop, manual redirect following (max [`MAX_REDIRECTS`]), a content-type
     /// check, and a body size cap of [`MAX_OEMBED_BODY_BYTES`].
     async fn fetch_page_info(url: &str, timeout: Duration) -> Result<Self, OembedError> {
+        #[cfg(test)]
+        if test_support::is_allowed(url) {
+            return Self::fetch_page_info_inner(url, timeout, |_| true).await;
+        }
         Self::fetch_page_info_inner(url, timeout, is_public_ip).await
     }
 
@@ -489,6 +514,7 @@ impl PageInfo {
             description,
             image,
             embed_html: None,
+            failed: false,
         })
     }
 
@@ -500,6 +526,31 @@ impl PageInfo {
         )
     }
 
+    /// Like [`Self::html`], but a failed fetch is rendered as `fallback`
+    /// says.
+    pub fn html_with_fallback(&self, fallback: OembedFallback) -> String {
+        if !self.failed {
+            return self.html();
+        }
+        match fallback {
+            OembedFallback::Link => self.html(),
+            OembedFallback::Card => format!(
+                "<article class='mbr-social-link-box mbr-embed-unavailable'>
+                    {}
+                    <a href=\"{}\" class='mbr-social-link'>
+                        <header>Embed unavailable</header>
+                        <p>{}</p>
+                    </a>
+                </article>
+                ",
+                FALLBACK_CARD_ICON,
+                html_escape::encode_double_quoted_attribute(&self.url),
+                html_escape::encode_text(&self.url),
+            ),
+            OembedFallback::Omit => String::new(),
+        }
+    }
+
     pub fn html(&self) -> String {
         // If we have embed HTML (e.g., YouTube), use that
         if let Some(embed) = &self.embed_html {
@@ -545,20 +596,41 @@ impl PageInfo {
     }
 }
 
+/// Loopback mock servers for oembed tests here and in other modules.
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use std::net::{Ipv4Addr, SocketAddr};
-    use std::sync::Arc;
+pub(crate) mod test_support {
+    use std::net::SocketAddr;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use tokio::net::TcpListener;
 
+    /// Mock server origins (`http://127.0.0.1:PORT`) that skip the public-IP
+    /// check, so a whole render can go through the real fetch path.
+    static ALLOWED_ORIGINS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Lets fetches reach the mock server at `addr`.
+    pub(crate) fn allow_mock_server(addr: SocketAddr) {
+        ALLOWED_ORIGINS
+            .lock()
+            .unwrap()
+            .push(format!("http://{addr}/"));
+    }
+
+    /// True if `url` points at a mock server passed to [`allow_mock_server`].
+    pub(crate) fn is_allowed(url: &str) -> bool {
+        ALLOWED_ORIGINS
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|origin| url.starts_with(origin.as_str()))
+    }
+
     /// Spawn a minimal HTTP/1.1 mock server on 127.0.0.1:0. The response is
     /// built from the bound address (so redirect targets can reference the
     /// server itself) and served identically for every connection. Returns
     /// the bound address and a counter of received requests.
-    async fn spawn_mock_server(
+    pub(crate) async fn spawn_mock_server(
         build_response: impl FnOnce(SocketAddr) -> String,
     ) -> (SocketAddr, Arc<AtomicUsize>) {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -585,13 +657,21 @@ mod tests {
         (addr, hits)
     }
 
-    fn html_response(body: &str) -> String {
+    pub(crate) fn html_response(body: &str) -> String {
         format!(
             "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
             body.len(),
             body
         )
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{html_response, spawn_mock_server};
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::Ordering;
 
     #[test]
     fn test_is_public_ip() {
@@ -901,6 +981,7 @@ mod tests {
             description: Some("A longer description text".to_string()),
             image: Some("https://example.com/image.png".to_string()),
             embed_html: None,
+            failed: false,
         };
         let size = info.estimated_size();
         // Size should include all the string lengths
@@ -920,6 +1001,7 @@ mod tests {
             description: None,
             image: None,
             embed_html: Some("<div></div>".to_string()),
+            failed: false,
         };
         let cloned = info.clone();
         assert_eq!(cloned.url, info.url);
@@ -1135,11 +1217,48 @@ mod tests {
     }
 
     // Tests for PageInfo::html()
+    #[test]
+    fn test_html_with_fallback_only_applies_to_failures() {
+        let ok = PageInfo {
+            url: "https://example.com/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(ok.html_with_fallback(OembedFallback::Omit), ok.html());
+
+        let failed = PageInfo {
+            url: "https://example.com/?a=1&b=\"2\"".to_string(),
+            failed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            failed.html_with_fallback(OembedFallback::Link),
+            failed.html()
+        );
+        assert_eq!(failed.html_with_fallback(OembedFallback::Omit), "");
+        let card = failed.html_with_fallback(OembedFallback::Card);
+        assert!(card.contains("mbr-embed-unavailable"));
+        assert!(card.contains(r#"href="https://example.com/?a=1&amp;b=&quot;2&quot;""#));
+        assert!(card.contains("<p>https://example.com/?a=1&amp;b=\"2\"</p>"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_failure_is_marked_failed() {
+        let info = PageInfo::new_from_url("http://127.0.0.1:9/", 100)
+            .await
+            .unwrap();
+        assert!(info.failed);
+        let disabled = PageInfo::new_from_url("http://127.0.0.1:9/", 0)
+            .await
+            .unwrap();
+        assert!(!disabled.failed);
+    }
+
     #[test]
     fn test_html_with_embed() {
         let info = PageInfo {
             url: "https://example.com".to_string(),
             embed_html: Some("<div>embedded</div>".to_string()),
+            failed: false,
             ..Default::default()
         };
         let html = info.html();
@@ -1154,6 +1273,7 @@ mod tests {
             description: Some("My description".to_string()),
             image: Some("https://example.com/image.png".to_string()),
             embed_html: None,
+            failed: false,
         };
         let html = info.html();
         assert!(html.contains("mbr-social-link-box"));
@@ -1170,6 +1290,7 @@ mod tests {
             description: None,
             image: None,
             embed_html: None,
+            failed: false,
         };
         let html = info.html();
         assert!(html.contains("mbr-social-link-box"));
@@ -1185,6 +1306,7 @@ mod tests {
             description: Some("ignored without title".to_string()),
             image: None,
             embed_html: None,
+            failed: false,
         };
         let html = info.html();
         // Should be a plain link
@@ -1294,6 +1416,35 @@ mod tests {
         assert!(html.contains("https://media.giphy.com/media/CAxbo8KC2A0y4/giphy.gif"));
     }
 
+    #[tokio::test]
+    async fn test_cached_fetches_request_each_url_once() {
+        use crate::oembed_cache::OembedCache;
+
+        let body = "<html><head><title>Shared</title></head></html>";
+        let (addr, hits) = spawn_mock_server(|_| html_response(body)).await;
+        let url = format!("http://127.0.0.1:{}/", addr.port());
+        let cache = OembedCache::new(1024 * 1024).with_concurrency(4);
+
+        // Several pages rendering at once, all embedding the same link
+        let fetch = || async {
+            PageInfo::fetch_page_info_inner(&url, Duration::from_secs(5), |_| true)
+                .await
+                .unwrap()
+        };
+        let pages =
+            futures::future::join_all((0..8).map(|_| cache.get_or_fetch(&url, fetch))).await;
+        assert!(
+            pages
+                .iter()
+                .all(|info| info.title.as_deref() == Some("Shared"))
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // A page rendered afterwards is served from the cache
+        cache.get_or_fetch(&url, fetch).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_giphy_webp_extension() {
         let url = "https://media.giphy.com/media/CAxbo8KC2A0y4/giphy.webp";
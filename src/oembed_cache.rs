@@ -6,12 +6,20 @@
 use crate::cache::Entry;
 use crate::oembed::PageInfo;
 use lru::LruCache;
+use std::collections::HashMap;
+use std::future::Future;
 use std::num::NonZeroUsize;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OnceCell, Semaphore};
 
 /// Maximum number of entries in the oembed LRU cache.
 const OEMBED_CACHE_MAX_ENTRIES: usize = 10_000;
 
+/// Fetches allowed in flight at once unless set with
+/// [`OembedCache::with_concurrency`].
+pub const DEFAULT_OEMBED_CONCURRENCY: usize = 8;
+
 /// Thread-safe cache for OEmbed page information.
 ///
 /// Uses an LRU cache with size-based eviction for O(1) get/insert
@@ -27,6 +35,13 @@ pub struct OembedCache {
     current_size: Mutex<usize>,
     /// Maximum allowed size in bytes
     max_size: usize,
+    /// Fetches in progress, so concurrent lookups of one URL share a request.
+    inflight: Mutex<HashMap<String, Arc<OnceCell<PageInfo>>>>,
+    /// Bounds fetches in flight across everything sharing this cache.
+    fetch_permits: Semaphore,
+    concurrency: usize,
+    /// Entries older than this are refetched; `None` keeps them until evicted.
+    ttl: Option<Duration>,
 }
 
 impl OembedCache {
@@ -49,13 +64,87 @@ impl OembedCache {
             cache: Mutex::new(LruCache::new(cap)),
             current_size: Mutex::new(0),
             max_size: max_size_bytes,
+            inflight: Mutex::new(HashMap::new()),
+            fetch_permits: Semaphore::new(DEFAULT_OEMBED_CONCURRENCY),
+            concurrency: DEFAULT_OEMBED_CONCURRENCY,
+            ttl: None,
+        }
+    }
+
+    /// Sets how many seconds an entry is reused before it's treated as a
+    /// miss and fetched again. 0 keeps entries until they're evicted.
+    pub fn with_ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl = (ttl_secs > 0).then(|| Duration::from_secs(ttl_secs));
+        self
+    }
+
+    /// Sets how many fetches made through [`get_or_fetch`](Self::get_or_fetch)
+    /// may be in flight at once (at least 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        let concurrency = concurrency.max(1);
+        self.fetch_permits = Semaphore::new(concurrency);
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// How many fetches may be in flight at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Returns the cached page info for `url`, or runs `fetch` and caches its
+    /// result.
+    ///
+    /// Concurrent callers asking for the same URL share one `fetch`, so pages
+    /// rendered in parallel that embed the same link request it only once.
+    /// This holds even when caching is disabled. Fetches for different URLs
+    /// wait for a free slot once [`concurrency`](Self::concurrency) are running.
+    pub async fn get_or_fetch<F, Fut>(&self, url: &str, fetch: F) -> PageInfo
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = PageInfo>,
+    {
+        if let Some(info) = self.get(url) {
+            return info;
         }
+
+        let cell = {
+            let mut inflight = self
+                .inflight
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            // A fetch may have finished between the miss above and taking the
+            // lock; its result is cached before it leaves `inflight`.
+            if let Some(info) = self.get(url) {
+                return info;
+            }
+            inflight.entry(url.to_string()).or_default().clone()
+        };
+
+        let mut fetched = false;
+        let info = cell
+            .get_or_init(|| async {
+                fetched = true;
+                // The semaphore is never closed, so acquiring can't fail
+                let _permit = self.fetch_permits.acquire().await.ok();
+                fetch().await
+            })
+            .await
+            .clone();
+        if fetched {
+            self.insert(url.to_string(), info.clone());
+            self.inflight
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .remove(url);
+        }
+        info
     }
 
     /// Retrieves cached page info for a URL if present.
     ///
-    /// Returns `None` if the URL is not in the cache.
-    /// Promotes the entry to most-recently-used on access.
+    /// Returns `None` if the URL is not in the cache or its entry has
+    /// expired. Promotes the entry to most-recently-used on access.
     pub fn get(&self, url: &str) -> Option<PageInfo> {
         if self.max_size == 0 {
             return None;
@@ -67,6 +156,20 @@ impl OembedCache {
             .cache
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let expired = cache
+            .peek(url)
+            .zip(self.ttl)
+            .is_some_and(|(entry, ttl)| entry.inserted_at.elapsed() >= ttl);
+        if expired {
+            tracing::debug!("oembed cache expired: {}", url);
+            if let Some(entry) = cache.pop(url) {
+                *self
+                    .current_size
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) -= entry.size_bytes;
+            }
+            return None;
+        }
         match cache.get(url) {
             Some(entry) => {
                 tracing::debug!("oembed cache hit: {}", url);
@@ -150,6 +253,7 @@ mod tests {
             description: Some("Test description".to_string()),
             image: None,
             embed_html: None,
+            failed: false,
         }
     }
 
@@ -167,6 +271,23 @@ mod tests {
         assert_eq!(retrieved.unwrap().url, url);
     }
 
+    #[test]
+    fn test_expired_entries_are_misses() {
+        let url = "https://example.com/page";
+        let mut cache = OembedCache::new(1024 * 1024).with_ttl_secs(60);
+        assert_eq!(cache.ttl, Some(Duration::from_secs(60)));
+        cache.insert(url.to_string(), make_page_info(url));
+        assert!(cache.get(url).is_some());
+
+        cache.ttl = Some(Duration::ZERO);
+        assert!(cache.get(url).is_none());
+        assert!(cache.is_empty());
+        assert_eq!(cache.current_size(), 0);
+
+        // 0 keeps entries until evicted
+        assert_eq!(OembedCache::new(1024).with_ttl_secs(0).ttl, None);
+    }
+
     #[test]
     fn test_cache_miss() {
         let cache = OembedCache::new(1024 * 1024);
@@ -175,6 +296,53 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_get_or_fetch_shares_concurrent_fetches() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = OembedCache::new(1024 * 1024);
+        let calls = AtomicUsize::new(0);
+        let url = "https://example.com/shared";
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            make_page_info(url)
+        };
+
+        let results =
+            futures::future::join_all((0..5).map(|_| cache.get_or_fetch(url, fetch))).await;
+        assert!(results.iter().all(|info| info.url == url));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Later lookups come from the cache
+        cache.get_or_fetch(url, fetch).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_fetch_limits_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = OembedCache::new(1024 * 1024).with_concurrency(2);
+        assert_eq!(cache.concurrency(), 2);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let urls: Vec<String> = (0..6).map(|i| format!("https://example.com/{i}")).collect();
+
+        futures::future::join_all(urls.iter().map(|url| {
+            cache.get_or_fetch(url, || async {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                make_page_info(url)
+            })
+        }))
+        .await;
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 6);
+    }
+
     #[test]
     fn test_disabled_cache() {
         let cache = OembedCache::new(0); // Disabled
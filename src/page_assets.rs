@@ -0,0 +1,217 @@
+//! Per-page stylesheets and scripts from frontmatter.
+//!
+//! ```yaml
+//! head_css: /styles/wide-tables.css        # linked in <head>
+//! head_js:
+//!   - /scripts/chart-setup.js               # loaded at the end of <body>
+//!   - "document.body.dataset.page = 'demo';"
+//! ```
+//!
+//! Each field takes a string or a list. A single-line value without spaces
+//! that ends in the right extension (`.css`, or `.js`/`.mjs`), or is an
+//! `http(s)://` URL, is a file; anything else is inline code. File paths are
+//! relative to the repository root (a leading `/` is optional), so they
+//! resolve the same way as any other repo or static-folder file and are
+//! included in static builds. Inline code is emitted in a `<style>` or
+//! `<script>` block carrying the CSP nonce.
+//!
+//! With `sanitize_html` on, only linked stylesheets are kept (see
+//! [`PageAssets::sanitized`]): scripts and inline CSS would let a page's
+//! author run code the sanitizer strips from the body.
+
+use serde_json::Value;
+
+use crate::markdown::SimpleMetadata;
+
+/// Frontmatter field listing the page's extra stylesheets.
+pub const HEAD_CSS_FIELD: &str = "head_css";
+
+/// Frontmatter field listing the page's extra scripts.
+pub const HEAD_JS_FIELD: &str = "head_js";
+
+/// One stylesheet or script for a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageAsset {
+    /// Site-root-relative (`/styles/page.css`) or external URL.
+    Url(String),
+    /// Inline CSS or JavaScript.
+    Inline(String),
+}
+
+impl PageAsset {
+    fn parse(value: &str, extensions: &[&str]) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        if is_external(value) {
+            return Some(Self::Url(value.to_string()));
+        }
+        let is_path = !value.contains(char::is_whitespace)
+            && extensions
+                .iter()
+                .any(|ext| value.to_ascii_lowercase().ends_with(ext));
+        Some(if is_path {
+            Self::Url(format!(
+                "/{}",
+                value.trim_start_matches("./").trim_start_matches('/')
+            ))
+        } else {
+            Self::Inline(value.to_string())
+        })
+    }
+}
+
+/// The extra stylesheets and scripts a page asks for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageAssets {
+    pub styles: Vec<PageAsset>,
+    pub scripts: Vec<PageAsset>,
+}
+
+impl PageAssets {
+    /// Reads `head_css` and `head_js` from a page's frontmatter.
+    pub fn for_page(frontmatter: &SimpleMetadata) -> Self {
+        Self {
+            styles: parse_field(frontmatter.get(HEAD_CSS_FIELD), &[".css"]),
+            scripts: parse_field(frontmatter.get(HEAD_JS_FIELD), &[".js", ".mjs"]),
+        }
+    }
+
+    /// Reads `head_css` and `head_js` as [`Self::for_page`] does, keeping
+    /// only what [`Self::sanitized`] allows when `sanitize` is set.
+    pub fn for_page_sanitized(frontmatter: &SimpleMetadata, sanitize: bool) -> Self {
+        let assets = Self::for_page(frontmatter);
+        if sanitize { assets.sanitized() } else { assets }
+    }
+
+    /// The assets allowed with `sanitize_html` on: linked stylesheets. Every
+    /// script and any inline CSS is dropped, since it would run with the CSP
+    /// nonce regardless of what the sanitizer removes from the body.
+    pub fn sanitized(self) -> Self {
+        Self {
+            styles: self
+                .styles
+                .into_iter()
+                .filter(|asset| matches!(asset, PageAsset::Url(_)))
+                .collect(),
+            scripts: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty() && self.scripts.is_empty()
+    }
+}
+
+fn parse_field(value: Option<&Value>, extensions: &[&str]) -> Vec<PageAsset> {
+    match value {
+        Some(Value::String(s)) => PageAsset::parse(s, extensions).into_iter().collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .filter_map(|s| PageAsset::parse(s, extensions))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_external(value: &str) -> bool {
+    value.starts_with("https://") || value.starts_with("http://") || value.starts_with("//")
+}
+
+/// Keeps inline code from closing its `<style>`/`<script>` element early.
+///
+/// `</` only appears in CSS and JavaScript inside strings and comments, where
+/// `<\/` means the same thing.
+pub fn escape_inline(code: &str) -> String {
+    code.replace("</", "<\\/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn frontmatter(pairs: &[(&str, Value)]) -> SimpleMetadata {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_paths_and_inline_code() {
+        let assets = PageAssets::for_page(&frontmatter(&[
+            ("head_css", json!("styles/wide.css")),
+            (
+                "head_js",
+                json!([
+                    "/scripts/chart.js",
+                    "https://cdn.example.com/lib.js",
+                    "console.log('hi');",
+                    "  "
+                ]),
+            ),
+        ]));
+        assert_eq!(
+            assets.styles,
+            vec![PageAsset::Url("/styles/wide.css".to_string())]
+        );
+        assert_eq!(
+            assets.scripts,
+            vec![
+                PageAsset::Url("/scripts/chart.js".to_string()),
+                PageAsset::Url("https://cdn.example.com/lib.js".to_string()),
+                PageAsset::Inline("console.log('hi');".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrong_extension_is_inline() {
+        let assets = PageAssets::for_page(&frontmatter(&[
+            ("head_css", json!("table { width: 100%; }")),
+            ("head_js", json!("setup.css")),
+        ]));
+        assert_eq!(
+            assets.styles,
+            vec![PageAsset::Inline("table { width: 100%; }".to_string())]
+        );
+        assert_eq!(
+            assets.scripts,
+            vec![PageAsset::Inline("setup.css".to_string())]
+        );
+        assert!(PageAssets::for_page(&SimpleMetadata::new()).is_empty());
+    }
+
+    #[test]
+    fn test_sanitized_keeps_only_linked_styles() {
+        let fm = frontmatter(&[
+            (
+                "head_css",
+                json!(["styles/wide.css", "body { color: red; }"]),
+            ),
+            ("head_js", json!(["/scripts/chart.js", "alert(1);"])),
+        ]);
+        assert_eq!(
+            PageAssets::for_page_sanitized(&fm, true),
+            PageAssets {
+                styles: vec![PageAsset::Url("/styles/wide.css".to_string())],
+                scripts: Vec::new(),
+            }
+        );
+        assert_eq!(
+            PageAssets::for_page_sanitized(&fm, false),
+            PageAssets::for_page(&fm)
+        );
+    }
+
+    #[test]
+    fn test_escape_inline() {
+        assert_eq!(
+            escape_inline("const s = '</script><b>';"),
+            "const s = '<\\/script><b>';"
+        );
+    }
+}
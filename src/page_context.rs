@@ -19,14 +19,23 @@
 use std::collections::HashMap;
 use std::path::Path;
 
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
 use serde_json::{Value, json};
 
+use crate::archive::ArchiveGroup;
 use crate::build::{relative_base, relative_root};
 use crate::config::TagSource;
-use crate::link_transform::make_relative_url;
-use crate::markdown::HeadingInfo;
+use crate::csp::ContentSecurityPolicy;
+use crate::dates::PageDates;
+use crate::favicon::{MANIFEST_FILE, SiteBranding};
+use crate::git_metadata::GitCommit;
+use crate::link_transform::{encode_url_path, make_relative_url};
+use crate::markdown::{HeadingInfo, TitleSource};
+use crate::nav::{NavItem, is_site_url};
+use crate::page_assets::{PageAsset, PageAssets, escape_inline};
+use crate::page_nav::{Breadcrumb, generate_breadcrumbs, get_current_dir_name};
 use crate::readability::ReadabilityScores;
-use crate::server::{Breadcrumb, generate_breadcrumbs, get_current_dir_name};
+use crate::robots::Robots;
 use crate::tag_index::{TagInfo, TaggedPage};
 
 /// How URLs are emitted into a template context.
@@ -47,15 +56,18 @@ impl UrlMode {
     }
 
     /// Extracts the `url_path` of a sibling-page JSON object for prev/next
-    /// navigation, preserving each mode's historical behavior:
-    /// server inserts the raw value (JSON `null` if missing), build falls
-    /// back to `/` and relativizes.
+    /// navigation, percent-encoded, preserving each mode's historical
+    /// behavior: server inserts JSON `null` if it's missing, build falls back
+    /// to `/` and relativizes.
     fn nav_url(&self, page: &Value) -> Value {
+        let url = page
+            .get("url_path")
+            .and_then(|v| v.as_str())
+            .map(encode_url_path);
         match self {
-            UrlMode::Absolute => json!(page.get("url_path")),
+            UrlMode::Absolute => json!(url),
             UrlMode::RelativeToDepth(depth) => {
-                let url = page.get("url_path").and_then(|v| v.as_str()).unwrap_or("/");
-                json!(make_relative_url(url, *depth))
+                json!(make_relative_url(url.as_deref().unwrap_or("/"), *depth))
             }
         }
     }
@@ -84,9 +96,28 @@ pub struct PageChrome<'a> {
     pub mode: ModeFlags,
     pub sidebar_style: &'a str,
     pub sidebar_max_items: usize,
+    /// Sticky header height (CSS length) for offsetting anchor jumps.
+    pub header_height: Option<&'a str>,
+    /// Animate jumps to in-page anchors.
+    pub smooth_scroll: bool,
+    /// Search box settings; `None` for error pages, whose `_search.html`
+    /// falls back to the built-in defaults.
+    pub search: Option<SearchUi<'a>>,
+    /// Site language and direction; `None` for error pages, which use the
+    /// template defaults (`en`, `ltr`).
+    pub language: Option<SiteLanguage<'a>>,
+    /// Favicon, manifest and theme color; `None` for server error pages,
+    /// which link the built-in favicon.
+    pub branding: Option<&'a SiteBranding>,
+    /// Site-wide `<meta name="robots">` content; `None` omits the tag.
+    pub robots: Option<&'a str>,
+    /// Content-Security-Policy, when configured.
+    pub csp: Option<&'a ContentSecurityPolicy>,
     /// `Some((prefix, suffix))` for content pages; `None` for error pages,
     /// which historically omit `title_prefix`/`title_suffix`.
     pub title_affixes: Option<(&'a str, &'a str)>,
+    /// Curated navigation from `.mbr/nav.yml`, inserted as `site_nav`.
+    pub site_nav: Option<&'a [NavItem]>,
 }
 
 /// Inserts the shared chrome key set into a template context.
@@ -112,10 +143,137 @@ pub fn insert_page_chrome(ctx: &mut HashMap<String, Value>, chrome: &PageChrome<
         "sidebar_max_items".to_string(),
         json!(chrome.sidebar_max_items),
     );
+    insert_scroll_settings(ctx, chrome.header_height, chrome.smooth_scroll);
+    if let Some(search) = &chrome.search {
+        insert_search_settings(ctx, search);
+    }
+    if let Some(language) = &chrome.language {
+        insert_site_language(ctx, language);
+    }
+    if let Some(branding) = chrome.branding {
+        insert_site_branding(ctx, branding);
+    }
+    if let Some(robots) = chrome.robots {
+        ctx.insert("robots".to_string(), json!(robots));
+    }
+    if let Some(csp) = chrome.csp {
+        insert_csp(ctx, csp, matches!(chrome.mode, ModeFlags::Static { .. }));
+    }
     if let Some((prefix, suffix)) = chrome.title_affixes {
         ctx.insert("title_prefix".to_string(), json!(prefix));
         ctx.insert("title_suffix".to_string(), json!(suffix));
     }
+    if let Some(nav) = chrome.site_nav {
+        let url_mode = match &chrome.mode {
+            ModeFlags::Server { .. } => UrlMode::Absolute,
+            ModeFlags::Static { depth } => UrlMode::RelativeToDepth(*depth),
+        };
+        ctx.insert("site_nav".to_string(), site_nav_to_json(nav, &url_mode));
+    }
+}
+
+/// Inserts the anchor-scroll keys read by `_head.html`'s inline style.
+fn insert_scroll_settings(
+    ctx: &mut HashMap<String, Value>,
+    header_height: Option<&str>,
+    smooth_scroll: bool,
+) {
+    if let Some(height) = header_height {
+        ctx.insert("header_height".to_string(), json!(height));
+    }
+    ctx.insert("smooth_scroll".to_string(), json!(smooth_scroll));
+}
+
+/// Search box settings read by the `_search.html` partial.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchUi<'a> {
+    pub placeholder: &'a str,
+    pub result_limit: usize,
+}
+
+/// Site-wide `<html lang dir>` defaults.
+#[derive(Debug, Clone, Copy)]
+pub struct SiteLanguage<'a> {
+    pub lang: &'a str,
+    pub dir: &'a str,
+}
+
+/// Inserts `site_lang` and `site_dir`. Markdown templates prefer the page's
+/// frontmatter `lang`/`dir` over these.
+fn insert_site_language(ctx: &mut HashMap<String, Value>, language: &SiteLanguage<'_>) {
+    ctx.insert("site_lang".to_string(), json!(language.lang));
+    ctx.insert("site_dir".to_string(), json!(language.dir));
+}
+
+/// Inserts the `favicon`, `touch_icon`, `site_manifest` and `theme_color`
+/// keys read by `_head.html`, which links the built-in favicon without them.
+/// Without a `theme_color`, the Pico theme's colors go in
+/// `theme_color_light` and `theme_color_dark`.
+fn insert_site_branding(ctx: &mut HashMap<String, Value>, branding: &SiteBranding) {
+    if let Some(icon) = &branding.icon {
+        ctx.insert(
+            "favicon".to_string(),
+            json!({ "file": icon.file_name, "type": icon.mime }),
+        );
+    }
+    if let Some(touch_icon) = branding.touch_icon() {
+        ctx.insert("touch_icon".to_string(), json!(touch_icon));
+    }
+    if branding.manifest.is_some() {
+        ctx.insert("site_manifest".to_string(), json!(MANIFEST_FILE));
+    }
+    if let Some(color) = &branding.theme_color {
+        ctx.insert("theme_color".to_string(), json!(color));
+    } else if let Some(colors) = &branding.theme_colors {
+        ctx.insert("theme_color_light".to_string(), json!(colors.light));
+        ctx.insert("theme_color_dark".to_string(), json!(colors.dark));
+    }
+}
+
+/// Inserts `csp_nonce` for inline scripts and styles. Static pages also get
+/// `content_security_policy` for `_head.html`'s `<meta>` tag; the server
+/// sends the policy as a header instead.
+fn insert_csp(ctx: &mut HashMap<String, Value>, csp: &ContentSecurityPolicy, is_static: bool) {
+    ctx.insert("csp_nonce".to_string(), json!(csp.nonce));
+    if is_static {
+        ctx.insert(
+            "content_security_policy".to_string(),
+            json!(csp.meta_policy()),
+        );
+    }
+}
+
+/// Inserts `search_placeholder` and `search_result_limit`.
+fn insert_search_settings(ctx: &mut HashMap<String, Value>, search: &SearchUi<'_>) {
+    ctx.insert("search_placeholder".to_string(), json!(search.placeholder));
+    ctx.insert(
+        "search_result_limit".to_string(),
+        json!(search.result_limit),
+    );
+}
+
+/// Converts curated navigation to its template representation, rewriting
+/// site URLs per the given mode (external links are left as-is).
+pub fn site_nav_to_json(items: &[NavItem], url_mode: &UrlMode) -> Value {
+    Value::Array(
+        items
+            .iter()
+            .map(|item| {
+                let url = item.url.as_deref().map(|url| {
+                    if is_site_url(url) {
+                        url_mode.rewrite(url)
+                    } else {
+                        url.to_string()
+                    }
+                });
+                json!({
+                    "title": item.title,
+                    "url": url,
+                    "children": site_nav_to_json(&item.children, url_mode),
+                })
+            })
+            .collect(),
+    )
 }
 
 /// Serializes tag-source configuration as a JSON string for safe template
@@ -127,6 +285,7 @@ pub fn tag_sources_json(tag_sources: &[TagSource]) -> String {
             .map(|ts| {
                 json!({
                     "field": ts.field,
+                    "fields": ts.fields,
                     "urlSource": ts.url_source(),
                     "label": ts.singular_label(),
                     "labelPlural": ts.plural_label()
@@ -161,6 +320,121 @@ pub fn tag_labels(
         .unwrap_or_else(|| (fallback_base.to_string(), format!("{}s", fallback_base)))
 }
 
+/// Name of the data file holding per-value metadata for a tag source (its
+/// `data` setting). Pages of such sources render with `author.html`.
+pub fn tag_data_name<'a>(tag_sources: &'a [TagSource], source: &str) -> Option<&'a str> {
+    tag_sources
+        .iter()
+        .find(|ts| ts.url_source() == source)
+        .and_then(|ts| ts.data.as_deref())
+}
+
+/// Inserts `author`: the tag value's entry from its source's data file
+/// (an empty object when it has none). A site-absolute `avatar` is rewritten
+/// like other page URLs.
+pub fn insert_author_keys(
+    ctx: &mut HashMap<String, Value>,
+    entry: Option<&Value>,
+    url_mode: &UrlMode,
+) {
+    let mut author = entry
+        .filter(|e| e.is_object())
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    if let Some(avatar) = author.get_mut("avatar")
+        && let Some(url) = avatar.as_str()
+        && url.starts_with('/')
+        && !url.starts_with("//")
+    {
+        *avatar = json!(url_mode.rewrite(url));
+    }
+    ctx.insert("author".to_string(), author);
+}
+
+/// Minutes to read `word_count` words at [`crate::constants::WORDS_PER_MINUTE`],
+/// rounded up.
+pub fn reading_time_minutes(word_count: usize) -> usize {
+    word_count.div_ceil(crate::constants::WORDS_PER_MINUTE)
+}
+
+/// Placeholder in `edit_url_template` replaced by the page's source path.
+pub const EDIT_URL_PATH: &str = "{path}";
+
+/// Characters escaped in the source path substituted into an edit URL. `/`
+/// is kept so the path maps onto the forge's directory layout.
+const EDIT_PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Fills `{path}` in an `edit_url_template` with a page's root-relative
+/// source path.
+pub fn edit_url(template: &str, file_path: &str) -> String {
+    let path = file_path.replace('\\', "/");
+    let encoded = utf8_percent_encode(path.trim_start_matches('/'), EDIT_PATH_ENCODE_SET);
+    template.replace(EDIT_URL_PATH, &encoded.to_string())
+}
+
+/// URL segment before the page number of paginated listings (`/dir/page/2/`).
+pub const PAGINATION_SEGMENT: &str = "page";
+
+/// Splits a listing into pages of at most `page_size` items. Returns a
+/// single page with everything when `page_size` is 0 or the listing fits,
+/// and always at least one (possibly empty) page.
+pub fn paginate<T>(items: &[T], page_size: usize) -> Vec<&[T]> {
+    if page_size == 0 || items.len() <= page_size {
+        return vec![items];
+    }
+    items.chunks(page_size).collect()
+}
+
+/// Absolute URL of page `page` (1-based) of the listing at `base_url`:
+/// `base_url` itself for the first page, else `{base_url}page/{page}/`.
+pub fn paginated_url(base_url: &str, page: usize) -> String {
+    if page <= 1 {
+        base_url.to_string()
+    } else {
+        format!("{base_url}{PAGINATION_SEGMENT}/{page}/")
+    }
+}
+
+/// Inserts the directory listing layout keys read by `section.html` and
+/// `home.html`: `listing_layout` and `listing_thumbnails`.
+pub fn insert_listing_layout(ctx: &mut HashMap<String, Value>, layout: &str, thumbnails: bool) {
+    ctx.insert("listing_layout".to_string(), json!(layout));
+    ctx.insert("listing_thumbnails".to_string(), json!(thumbnails));
+}
+
+/// Inserts `pagination` for page `current` (1-based) of `total` when the
+/// listing spans more than one page: `current`, `total`, and `prev_url` /
+/// `next_url` (omitted at either end).
+pub fn insert_pagination_keys(
+    ctx: &mut HashMap<String, Value>,
+    base_url: &str,
+    current: usize,
+    total: usize,
+    url_mode: &UrlMode,
+) {
+    if total <= 1 {
+        return;
+    }
+    let mut pagination = json!({ "current": current, "total": total });
+    if current > 1 {
+        pagination["prev_url"] = json!(url_mode.rewrite(&paginated_url(base_url, current - 1)));
+    }
+    if current < total {
+        pagination["next_url"] = json!(url_mode.rewrite(&paginated_url(base_url, current + 1)));
+    }
+    ctx.insert("pagination".to_string(), pagination);
+}
+
 /// Inserts the shared key set for a tag page (`tag.html`).
 pub fn insert_tag_page_keys(
     ctx: &mut HashMap<String, Value>,
@@ -180,8 +454,11 @@ pub fn insert_tag_page_keys(
         .map(|p| {
             json!({
                 "url_path": url_mode.rewrite(&p.url_path),
+                "url": url_mode.rewrite(&encode_url_path(&p.url_path)),
                 "title": p.title,
                 "description": p.description,
+                "word_count": p.word_count,
+                "reading_time_minutes": reading_time_minutes(p.word_count),
             })
         })
         .collect();
@@ -189,6 +466,54 @@ pub fn insert_tag_page_keys(
     ctx.insert("page_count".to_string(), json!(pages.len()));
 }
 
+/// Inserts the shared key set for an archive page (`archive.html`).
+/// `archive_year`/`archive_month` are omitted on the pages above them.
+pub fn insert_archive_keys(
+    ctx: &mut HashMap<String, Value>,
+    title: &str,
+    year: Option<i32>,
+    month: Option<u32>,
+    groups: &[ArchiveGroup],
+    url_mode: &UrlMode,
+) {
+    ctx.insert("archive_title".to_string(), json!(title));
+    if let Some(year) = year {
+        ctx.insert("archive_year".to_string(), json!(year));
+    }
+    if let Some(month) = month {
+        ctx.insert("archive_month".to_string(), json!(month));
+    }
+    let groups_json: Vec<Value> = groups
+        .iter()
+        .map(|g| {
+            let pages: Vec<Value> = g
+                .pages
+                .iter()
+                .map(|p| {
+                    json!({
+                        "url_path": url_mode.rewrite(&p.url_path),
+                        "url": url_mode.rewrite(&encode_url_path(&p.url_path)),
+                        "title": p.title,
+                        "description": p.description,
+                        "date": p.date,
+                        "word_count": p.word_count,
+                        "reading_time_minutes": reading_time_minutes(p.word_count),
+                    })
+                })
+                .collect();
+            json!({
+                "label": g.label,
+                "url_path": url_mode.rewrite(&g.url_path),
+                "page_count": pages.len(),
+                "pages": pages,
+            })
+        })
+        .collect();
+    let page_count: usize = groups.iter().map(|g| g.pages.len()).sum();
+    ctx.insert("groups".to_string(), json!(groups_json));
+    ctx.insert("page_count".to_string(), json!(page_count));
+}
+
 /// Inserts the shared key set for a tag source index page (`tag_index.html`).
 pub fn insert_tag_index_keys(
     ctx: &mut HashMap<String, Value>,
@@ -229,6 +554,26 @@ pub fn insert_error_keys(
     }
 }
 
+/// Inserts `page_styles` and `page_scripts`: lists of `{url}` (root-relative
+/// paths rewritten for the URL mode) or `{inline}` (code safe to place in a
+/// `<style>`/`<script>` element) objects.
+fn insert_page_assets(ctx: &mut HashMap<String, Value>, assets: &PageAssets, url_mode: &UrlMode) {
+    let to_json = |items: &[PageAsset]| -> Value {
+        items
+            .iter()
+            .map(|asset| match asset {
+                PageAsset::Url(url) if url.starts_with("/") && !url.starts_with("//") => {
+                    json!({ "url": url_mode.rewrite(url) })
+                }
+                PageAsset::Url(url) => json!({ "url": url }),
+                PageAsset::Inline(code) => json!({ "inline": escape_inline(code) }),
+            })
+            .collect()
+    };
+    ctx.insert("page_styles".to_string(), to_json(&assets.styles));
+    ctx.insert("page_scripts".to_string(), to_json(&assets.scripts));
+}
+
 /// Page-specific inputs for the shared markdown-page context builder.
 ///
 /// Sibling computation stays with the caller (server memoizes via a cache,
@@ -238,12 +583,23 @@ pub struct MarkdownPageParams<'a> {
     pub breadcrumb_path: &'a Path,
     pub headings: &'a [HeadingInfo],
     pub has_h1: bool,
+    /// Where the page's `title` came from (templates only add a visible
+    /// heading for frontmatter titles).
+    pub title_source: TitleSource,
     pub word_count: usize,
     pub readability: &'a ReadabilityScores,
     /// File path relative to the repository root.
     pub file_path: &'a str,
     /// File mtime as seconds since the Unix epoch, if available.
     pub modified_secs: Option<u64>,
+    /// Last git commit touching the source file, when `git_metadata` is on.
+    pub last_commit: Option<&'a GitCommit>,
+    /// Normalized publish and last-updated dates.
+    pub dates: PageDates,
+    /// Search engine directives from frontmatter and the site default.
+    pub robots: Robots,
+    /// Extra stylesheets and scripts from `head_css`/`head_js` frontmatter.
+    pub assets: PageAssets,
     /// Absolute URL of this page, used to locate it among its siblings.
     pub current_url: &'a str,
     /// Sorted sibling pages (JSON objects with `url_path`/`title`).
@@ -255,8 +611,27 @@ pub struct MarkdownContextOptions<'a> {
     pub tag_sources: &'a [TagSource],
     pub sidebar_style: &'a str,
     pub sidebar_max_items: usize,
+    pub header_height: Option<&'a str>,
+    pub smooth_scroll: bool,
+    pub search: SearchUi<'a>,
+    pub language: SiteLanguage<'a>,
+    pub branding: &'a SiteBranding,
     pub title_prefix: &'a str,
     pub title_suffix: &'a str,
+    pub site_nav: Option<&'a [NavItem]>,
+    /// Let readers tick task list checkboxes (state kept in localStorage).
+    pub interactive_tasklists: bool,
+    /// Highlight the TOC entry for the section in view while scrolling.
+    pub scroll_spy: bool,
+    /// `edit_url_template`; pages get `edit_url` when set.
+    pub edit_url_template: Option<&'a str>,
+    /// Wrap video players in click-to-play facades (loads their script).
+    pub privacy_embeds: bool,
+    /// Content-Security-Policy, when configured.
+    pub csp: Option<&'a ContentSecurityPolicy>,
+    /// `mermaid.initialize()` options (a JSON object, see
+    /// [`crate::config::Config::mermaid_init_json`]).
+    pub mermaid_config: &'a str,
 }
 
 /// Builds the `extra_context` map shared by server-mode and static-build
@@ -288,6 +663,7 @@ pub fn markdown_extra_context(
     // Heading TOC
     ctx.insert("headings".to_string(), json!(params.headings));
     ctx.insert("has_h1".to_string(), json!(params.has_h1));
+    ctx.insert("title_source".to_string(), json!(params.title_source));
 
     // Tag sources configuration for frontend tag linking (pre-serialized as
     // a JSON string for safe template rendering in a JavaScript context)
@@ -295,15 +671,13 @@ pub fn markdown_extra_context(
         "tag_sources".to_string(),
         json!(tag_sources_json(opts.tag_sources)),
     );
+    ctx.insert("mermaid_config".to_string(), json!(opts.mermaid_config));
 
-    // Word count and reading time (200 words per minute)
-    let reading_time_minutes = params
-        .word_count
-        .div_ceil(crate::constants::WORDS_PER_MINUTE);
+    // Word count and reading time
     ctx.insert("word_count".to_string(), json!(params.word_count));
     ctx.insert(
         "reading_time_minutes".to_string(),
-        json!(reading_time_minutes),
+        json!(reading_time_minutes(params.word_count)),
     );
 
     // Readability scores (Flesch Reading Ease + Flesch-Kincaid Grade Level).
@@ -320,6 +694,12 @@ pub fn markdown_extra_context(
 
     // File path (relative to root) for reference
     ctx.insert("file_path".to_string(), json!(params.file_path));
+    if let Some(template) = opts.edit_url_template {
+        ctx.insert(
+            "edit_url".to_string(),
+            json!(edit_url(template, params.file_path)),
+        );
+    }
 
     // Sidebar navigation configuration and title affixes
     ctx.insert("sidebar_style".to_string(), json!(opts.sidebar_style));
@@ -327,13 +707,49 @@ pub fn markdown_extra_context(
         "sidebar_max_items".to_string(),
         json!(opts.sidebar_max_items),
     );
+    insert_scroll_settings(&mut ctx, opts.header_height, opts.smooth_scroll);
+    insert_search_settings(&mut ctx, &opts.search);
+    insert_site_language(&mut ctx, &opts.language);
+    insert_site_branding(&mut ctx, opts.branding);
+    if let Some(csp) = opts.csp {
+        insert_csp(
+            &mut ctx,
+            csp,
+            matches!(url_mode, UrlMode::RelativeToDepth(_)),
+        );
+    }
     ctx.insert("title_prefix".to_string(), json!(opts.title_prefix));
     ctx.insert("title_suffix".to_string(), json!(opts.title_suffix));
+    if let Some(nav) = opts.site_nav {
+        ctx.insert("site_nav".to_string(), site_nav_to_json(nav, url_mode));
+    }
+    ctx.insert(
+        "interactive_tasklists".to_string(),
+        json!(opts.interactive_tasklists),
+    );
+    ctx.insert("scroll_spy".to_string(), json!(opts.scroll_spy));
+    ctx.insert("privacy_embeds".to_string(), json!(opts.privacy_embeds));
+
+    // Always set, so a raw frontmatter `robots` list never reaches the template
+    ctx.insert("robots".to_string(), json!(params.robots.content()));
+
+    if !params.assets.is_empty() {
+        insert_page_assets(&mut ctx, &params.assets, url_mode);
+    }
 
     // Modified date from file metadata
     if let Some(secs) = params.modified_secs {
         ctx.insert("modified_timestamp".to_string(), json!(secs));
     }
+    if let Some(commit) = params.last_commit {
+        ctx.insert("last_commit".to_string(), json!(commit));
+    }
+    if let Some(created) = params.dates.created {
+        ctx.insert("created_date".to_string(), json!(created));
+    }
+    if let Some(updated) = params.dates.updated {
+        ctx.insert("updated_date".to_string(), json!(updated));
+    }
 
     // Prev/next sibling pages for navigation
     if let Some(current_idx) = params.siblings.iter().position(|f| {
@@ -381,6 +797,8 @@ mod tests {
             field: field.to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         }
     }
 
@@ -409,7 +827,15 @@ mod tests {
                 },
                 sidebar_style: "auto",
                 sidebar_max_items: 10,
+                header_height: Some("4rem"),
+                smooth_scroll: true,
+                search: None,
+                language: None,
+                branding: None,
+                robots: Some("noindex"),
+                csp: None,
                 title_affixes: Some(("pre ", " suf")),
+                site_nav: None,
             },
         );
         assert_eq!(ctx.get("server_mode"), Some(&json!(true)));
@@ -419,6 +845,9 @@ mod tests {
         assert_eq!(ctx.get("sidebar_max_items"), Some(&json!(10)));
         assert_eq!(ctx.get("title_prefix"), Some(&json!("pre ")));
         assert_eq!(ctx.get("title_suffix"), Some(&json!(" suf")));
+        assert_eq!(ctx.get("header_height"), Some(&json!("4rem")));
+        assert_eq!(ctx.get("smooth_scroll"), Some(&json!(true)));
+        assert_eq!(ctx.get("robots"), Some(&json!("noindex")));
         assert!(!ctx.contains_key("relative_root"));
     }
 
@@ -434,7 +863,15 @@ mod tests {
                 },
                 sidebar_style: "auto",
                 sidebar_max_items: 10,
+                header_height: None,
+                smooth_scroll: false,
+                search: None,
+                language: None,
+                branding: None,
+                robots: None,
+                csp: None,
                 title_affixes: None,
+                site_nav: None,
             },
         );
         assert_eq!(ctx.get("server_mode"), Some(&json!(true)));
@@ -442,6 +879,7 @@ mod tests {
         assert!(!ctx.contains_key("relative_base"));
         assert!(!ctx.contains_key("title_prefix"));
         assert!(!ctx.contains_key("title_suffix"));
+        assert!(!ctx.contains_key("header_height"));
     }
 
     #[test]
@@ -453,13 +891,29 @@ mod tests {
                 mode: ModeFlags::Static { depth: 2 },
                 sidebar_style: "auto",
                 sidebar_max_items: 5,
+                header_height: None,
+                smooth_scroll: false,
+                search: None,
+                language: None,
+                branding: None,
+                robots: None,
+                csp: Some(&ContentSecurityPolicy::new(
+                    "script-src 'nonce-{nonce}'; frame-ancestors 'none'",
+                    "n0nce".to_string(),
+                )),
                 title_affixes: Some(("", "")),
+                site_nav: None,
             },
         );
         assert_eq!(ctx.get("server_mode"), Some(&json!(false)));
         assert_eq!(ctx.get("relative_base"), Some(&json!("../../.mbr/")));
         assert_eq!(ctx.get("relative_root"), Some(&json!("../../")));
         assert!(!ctx.contains_key("gui_mode"));
+        assert_eq!(ctx.get("csp_nonce"), Some(&json!("n0nce")));
+        assert_eq!(
+            ctx.get("content_security_policy"),
+            Some(&json!("script-src 'nonce-n0nce'"))
+        );
     }
 
     #[test]
@@ -499,6 +953,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_paginate() {
+        let items = [1, 2, 3, 4, 5];
+        assert_eq!(paginate(&items, 0), vec![&items[..]]);
+        assert_eq!(paginate(&items, 5), vec![&items[..]]);
+        assert_eq!(paginate(&items, 2), vec![&[1, 2][..], &[3, 4], &[5]]);
+        assert_eq!(paginate::<i32>(&[], 2), vec![&[] as &[i32]]);
+    }
+
+    #[test]
+    fn test_insert_pagination_keys() {
+        let mut ctx = HashMap::new();
+        insert_pagination_keys(&mut ctx, "/blog/", 1, 1, &UrlMode::Absolute);
+        assert!(!ctx.contains_key("pagination"));
+
+        insert_pagination_keys(&mut ctx, "/blog/", 2, 3, &UrlMode::Absolute);
+        assert_eq!(
+            ctx["pagination"],
+            json!({
+                "current": 2,
+                "total": 3,
+                "prev_url": "/blog/",
+                "next_url": "/blog/page/3/",
+            })
+        );
+
+        // Page 3 of /blog/ sits at depth 3
+        insert_pagination_keys(&mut ctx, "/blog/", 3, 3, &UrlMode::RelativeToDepth(3));
+        assert_eq!(
+            ctx["pagination"],
+            json!({
+                "current": 3,
+                "total": 3,
+                "prev_url": make_relative_url("/blog/page/2/", 3),
+            })
+        );
+    }
+
     #[test]
     fn test_tag_labels_configured_source() {
         let sources = vec![tag_source("tags")];
@@ -599,13 +1091,54 @@ mod tests {
         assert!(!ctx.contains_key("error_message"));
     }
 
+    #[test]
+    fn test_edit_url() {
+        let template = "https://github.com/me/notes/edit/main/{path}";
+        assert_eq!(
+            edit_url(template, "docs/My Note #2.md"),
+            "https://github.com/me/notes/edit/main/docs/My%20Note%20%232.md"
+        );
+        assert_eq!(
+            edit_url(template, "docs\\windows.md"),
+            "https://github.com/me/notes/edit/main/docs/windows.md"
+        );
+        // Without a placeholder the template is used as is
+        assert_eq!(
+            edit_url("https://example.com/edit", "a.md"),
+            "https://example.com/edit"
+        );
+    }
+
     fn markdown_opts(sources: &[TagSource]) -> MarkdownContextOptions<'_> {
         MarkdownContextOptions {
             tag_sources: sources,
             sidebar_style: "auto",
             sidebar_max_items: 10,
+            header_height: None,
+            smooth_scroll: false,
+            search: SearchUi {
+                placeholder: "Search files...",
+                result_limit: 20,
+            },
+            language: SiteLanguage {
+                lang: "en",
+                dir: "ltr",
+            },
+            branding: &SiteBranding {
+                icon: None,
+                manifest: None,
+                theme_color: None,
+                theme_colors: None,
+            },
             title_prefix: "",
             title_suffix: "",
+            site_nav: None,
+            interactive_tasklists: false,
+            scroll_spy: false,
+            edit_url_template: None,
+            privacy_embeds: false,
+            csp: None,
+            mermaid_config: "{}",
         }
     }
 
@@ -624,33 +1157,59 @@ mod tests {
             level: 2,
             text: "Intro".to_string(),
             id: "intro".to_string(),
+            number: None,
         }];
+        let commit = GitCommit {
+            author: "Ada".to_string(),
+            timestamp: 1700000000,
+            hash: "b2c3d4e".to_string(),
+        };
         let params = MarkdownPageParams {
             breadcrumb_path: Path::new("docs/b"),
             headings: &headings,
             has_h1: true,
+            title_source: TitleSource::Heading,
             word_count: 401,
             readability: &scores,
             file_path: "docs/b.md",
             modified_secs: Some(1700000000),
+            last_commit: Some(&commit),
+            dates: PageDates {
+                date: None,
+                created: Some(1690000000),
+                updated: Some(1700000000),
+            },
+            robots: Robots::default(),
+            assets: PageAssets::default(),
             current_url: "/docs/b/",
             siblings: &siblings,
         };
         let ctx = markdown_extra_context(&params, &markdown_opts(&[]), &UrlMode::Absolute);
 
         assert_eq!(ctx.get("has_h1"), Some(&json!(true)));
+        assert_eq!(ctx.get("title_source"), Some(&json!("heading")));
         assert_eq!(ctx.get("word_count"), Some(&json!(401)));
         // 401 words at 200 wpm rounds up to 3 minutes
         assert_eq!(ctx.get("reading_time_minutes"), Some(&json!(3)));
         assert_eq!(ctx.get("flesch_reading_ease"), Some(&json!(65.0)));
         assert_eq!(ctx.get("file_path"), Some(&json!("docs/b.md")));
         assert_eq!(ctx.get("modified_timestamp"), Some(&json!(1700000000u64)));
+        assert_eq!(
+            ctx.get("last_commit"),
+            Some(&json!({"author": "Ada", "timestamp": 1700000000, "hash": "b2c3d4e"}))
+        );
+        assert_eq!(ctx.get("created_date"), Some(&json!(1690000000)));
+        assert_eq!(ctx.get("updated_date"), Some(&json!(1700000000)));
         assert_eq!(ctx.get("prev_page").unwrap()["url"], "/docs/a/");
         assert_eq!(ctx.get("prev_page").unwrap()["title"], "A");
         assert_eq!(ctx.get("next_page").unwrap()["url"], "/docs/c/");
         // Server mode: no relative path variables
         assert!(!ctx.contains_key("relative_base"));
         assert!(!ctx.contains_key("relative_root"));
+        // No head_css/head_js: no asset keys
+        assert!(!ctx.contains_key("page_styles"));
+        // No edit_url_template: no edit link
+        assert!(!ctx.contains_key("edit_url"));
         // Mode flags are the caller's responsibility (frontmatter)
         assert!(!ctx.contains_key("server_mode"));
         assert!(!ctx.contains_key("gui_mode"));
@@ -670,15 +1229,47 @@ mod tests {
             breadcrumb_path: Path::new("/docs/b/"),
             headings: &[],
             has_h1: false,
+            title_source: TitleSource::Frontmatter,
             word_count: 0,
             readability: &scores,
             file_path: "docs/b.md",
             modified_secs: None,
+            last_commit: None,
+            dates: PageDates::default(),
+            robots: Robots::default(),
+            assets: PageAssets {
+                styles: vec![PageAsset::Url("/styles/wide.css".to_string())],
+                scripts: vec![
+                    PageAsset::Url("https://cdn.example.com/lib.js".to_string()),
+                    PageAsset::Inline("init('</script>');".to_string()),
+                ],
+            },
             current_url: "/docs/b/",
             siblings: &siblings,
         };
-        let ctx =
-            markdown_extra_context(&params, &markdown_opts(&[]), &UrlMode::RelativeToDepth(2));
+        let opts = MarkdownContextOptions {
+            edit_url_template: Some("https://github.com/me/notes/edit/main/{path}"),
+            ..markdown_opts(&[])
+        };
+        let ctx = markdown_extra_context(&params, &opts, &UrlMode::RelativeToDepth(2));
+
+        assert_eq!(
+            ctx.get("edit_url"),
+            Some(&json!("https://github.com/me/notes/edit/main/docs/b.md"))
+        );
+
+        // Page assets: local paths relativized, external URLs kept, inline escaped
+        assert_eq!(
+            ctx.get("page_styles"),
+            Some(&json!([{ "url": "../../styles/wide.css" }]))
+        );
+        assert_eq!(
+            ctx.get("page_scripts"),
+            Some(&json!([
+                { "url": "https://cdn.example.com/lib.js" },
+                { "inline": "init('<\\/script>');" }
+            ]))
+        );
 
         // None scores serialize as JSON null
         assert_eq!(ctx.get("flesch_reading_ease"), Some(&json!(null)));
@@ -704,10 +1295,15 @@ mod tests {
             breadcrumb_path: Path::new("docs/b"),
             headings: &[],
             has_h1: false,
+            title_source: TitleSource::Filename,
             word_count: 10,
             readability: &scores,
             file_path: "docs/b.md",
             modified_secs: None,
+            last_commit: None,
+            dates: PageDates::default(),
+            robots: Robots::default(),
+            assets: PageAssets::default(),
             current_url: "/not-in-list/",
             siblings: &[],
         };
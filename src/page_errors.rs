@@ -36,6 +36,8 @@ pub enum MediaKind {
     Video,
     Audio,
     Source,
+    /// An explicit `<track kind="subtitles">` from the `vid` shortcode.
+    Track,
 }
 
 /// A single problem detected on a page.
@@ -49,8 +51,8 @@ pub enum PageError {
         #[serde(skip_serializing_if = "Option::is_none")]
         anchor: Option<String>,
     },
-    /// A media element (`<img>`, `<video>`, `<audio>`, `<source>`) whose
-    /// internal `src` does not resolve.
+    /// A media element (`<img>`, `<video>`, `<audio>`, `<source>`, subtitle
+    /// `<track>`) whose internal `src` does not resolve.
     BrokenMediaReference { src: String, kind: MediaKind },
     /// A literal `[[...]]` that was not transformed into a link.
     UnresolvedWikilink { raw: String },
@@ -127,8 +129,11 @@ pub fn validate_internal_links(
     errors
 }
 
-/// Validates `<img>`, `<video>`, `<audio>` and `<source>` `src` attributes in
-/// the rendered HTML.
+/// Validates `<img>`, `<video>`, `<audio>`, `<source>` and subtitle `<track>`
+/// `src` attributes in the rendered HTML.
+///
+/// Only `kind="subtitles"` tracks are checked: the captions and chapters
+/// tracks every video gets are optional sidecars that usually don't exist.
 ///
 /// `page_url` is the page's canonical directory-style URL (e.g.
 /// `/docs/guide/`; `/` for the root page). The rendered HTML contains srcs
@@ -145,11 +150,12 @@ pub fn validate_media_references(
     // selectors below compile once per call which keeps the API ergonomic.
     let doc = Html::parse_document(html);
 
-    let specs: [(&str, MediaKind); 4] = [
+    let specs: [(&str, MediaKind); 5] = [
         ("img[src]", MediaKind::Image),
         ("video[src]", MediaKind::Video),
         ("audio[src]", MediaKind::Audio),
         ("source[src]", MediaKind::Source),
+        (r#"track[kind="subtitles"][src]"#, MediaKind::Track),
     ];
 
     let mut errors = Vec::new();
@@ -318,6 +324,7 @@ mod tests {
             text: "bad".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -342,6 +349,7 @@ mod tests {
             text: "ok".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -361,6 +369,7 @@ mod tests {
             text: "ext".to_string(),
             anchor: None,
             internal: false,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -380,6 +389,7 @@ mod tests {
             text: "anchor".to_string(),
             anchor: Some("#section".to_string()),
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -404,12 +414,14 @@ mod tests {
                 text: "no trailing slash".to_string(),
                 anchor: None,
                 internal: true,
+                context: None,
             },
             OutboundLink {
                 to: "/IronCore%20Swag%20T-shirts%20Gifts/".to_string(),
                 text: "trailing slash".to_string(),
                 anchor: None,
                 internal: true,
+                context: None,
             },
         ];
 
@@ -431,6 +443,7 @@ mod tests {
             text: "apostrophe".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -451,6 +464,7 @@ mod tests {
             text: "unicode".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -471,6 +485,7 @@ mod tests {
             text: "anchor and query".to_string(),
             anchor: Some("#top".to_string()),
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -490,6 +505,7 @@ mod tests {
             text: "gone".to_string(),
             anchor: None,
             internal: true,
+            context: None,
         }];
 
         let errs = validate_internal_links(&outbound, &cfg);
@@ -609,6 +625,31 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn only_missing_subtitle_tracks_are_reported() {
+        let (_guard, base) = media_setup();
+        let exts = vec!["md".to_string()];
+        let tags: Vec<String> = vec![];
+        let cfg = make_config(&base, &exts, "index.md", &tags);
+
+        let html = r#"
+            <video>
+                <track kind="subtitles" src="./gone.en.vtt" srclang="en">
+                <track kind="captions" src="./clip.mp4.captions.en.vtt" srclang="en">
+                <track kind="chapters" src="./clip.mp4.chapters.en.vtt" srclang="en">
+            </video>
+        "#;
+        let errs = validate_media_references(html, &cfg, "/");
+
+        assert_eq!(
+            errs,
+            vec![PageError::BrokenMediaReference {
+                src: "./gone.en.vtt".to_string(),
+                kind: MediaKind::Track,
+            }]
+        );
+    }
+
     #[test]
     fn percent_encoded_relative_img_next_to_markdown_is_ignored() {
         // Encoded relative srcs must resolve against the page URL first and
@@ -0,0 +1,381 @@
+//! Helpers shared by the server and static builds for assembling page
+//! context: breadcrumb trails, "up" links and the JSON form of a markdown
+//! file used in listings and prev/next navigation.
+//!
+//! They work on repository-relative paths and [`MarkdownInfo`], so library
+//! users can build the same context for their own pages.
+
+use std::path::Path;
+
+use crate::link_transform::encode_url_path;
+use crate::repo::MarkdownInfo;
+
+/// A breadcrumb entry for navigation.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Breadcrumb {
+    pub name: String,
+    pub url: String,
+}
+
+impl Breadcrumb {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+        }
+    }
+}
+
+/// Generates breadcrumb navigation from a relative path.
+///
+/// Always starts with "Home" → "/" and includes all path components.
+/// The last component is not included in the returned breadcrumbs (it's the current page).
+pub fn generate_breadcrumbs(relative_path: &Path) -> Vec<Breadcrumb> {
+    let path_components: Vec<_> = relative_path
+        .components()
+        .filter_map(|c| {
+            if let std::path::Component::Normal(s) = c {
+                s.to_str()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // For root (no path components), return empty breadcrumbs
+    // The current page name will be shown separately, avoiding "Home > Home"
+    if path_components.is_empty() {
+        return vec![];
+    }
+
+    // Start with Home
+    let mut breadcrumbs = vec![Breadcrumb::new("Home", "/")];
+
+    // Add all but the last component (last is current page/directory)
+    for (idx, _) in path_components
+        .iter()
+        .enumerate()
+        .take(path_components.len().saturating_sub(1))
+    {
+        let partial_path: std::path::PathBuf = path_components.iter().take(idx + 1).collect();
+        let url = encode_url_path(&format!("/{}/", partial_path.to_string_lossy()));
+        let name = path_components[idx].to_string();
+        breadcrumbs.push(Breadcrumb::new(name, url));
+    }
+
+    breadcrumbs
+}
+
+/// Gets the current directory name from a relative path.
+pub fn get_current_dir_name(relative_path: &Path) -> String {
+    relative_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(String::from)
+        .unwrap_or_else(|| "Home".to_string())
+}
+
+/// Gets the parent path URL for "up" navigation.
+pub fn get_parent_path(relative_path: &Path) -> Option<String> {
+    let path_components: Vec<_> = relative_path
+        .components()
+        .filter_map(|c| {
+            if let std::path::Component::Normal(s) = c {
+                s.to_str()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if path_components.len() > 1 {
+        let parent: std::path::PathBuf = path_components
+            .iter()
+            .take(path_components.len() - 1)
+            .collect();
+        Some(encode_url_path(&format!("/{}/", parent.to_string_lossy())))
+    } else if !path_components.is_empty() {
+        Some("/".to_string())
+    } else {
+        None
+    }
+}
+
+/// Transforms markdown file info into a JSON value for template rendering.
+pub fn markdown_file_to_json(file_info: &MarkdownInfo) -> serde_json::Value {
+    use serde_json::json;
+
+    let title = file_info
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("title"))
+        .cloned()
+        .unwrap_or_else(|| {
+            serde_json::Value::String(crate::markdown::title_from_filename(&file_info.raw_path))
+        });
+
+    let description = file_info
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("description"))
+        .cloned();
+
+    let tags = file_info
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("tags"))
+        .cloned();
+
+    let note_type = file_info
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("type"))
+        .cloned();
+
+    // Local JPEG/PNG covers can be shown through the gallery thumbnailer
+    let cover_thumbnail = file_info
+        .cover_image
+        .as_deref()
+        .filter(|url| {
+            url.starts_with('/')
+                && !url.starts_with("//")
+                && crate::media::is_transcodable_image(url)
+        })
+        .map(crate::gallery::thumbnail_url);
+
+    let modified_date = chrono::DateTime::from_timestamp(file_info.modified as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    json!({
+        "title": title,
+        "url_path": file_info.url_path,
+        "url": file_info.url,
+        "description": description,
+        "tags": tags,
+        "type": note_type,
+        "modified_date": modified_date,
+        "modified": file_info.modified,
+        "date": file_info.date,
+        "created_date": file_info.created_date,
+        "updated_date": file_info.updated_date,
+        "word_count": file_info.word_count,
+        "reading_time_minutes": crate::page_context::reading_time_minutes(file_info.word_count),
+        "cover_image": file_info.cover_image,
+        "cover_thumbnail": cover_thumbnail,
+        "name": file_info.raw_path.file_name().and_then(|s| s.to_str()).unwrap_or(""),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_generate_breadcrumbs_root() {
+        let path = Path::new("");
+        let breadcrumbs = generate_breadcrumbs(path);
+
+        // Root returns empty breadcrumbs to avoid "Home > Home" duplication
+        // The template handles showing just "Home" as the current page
+        assert_eq!(breadcrumbs.len(), 0);
+    }
+
+    #[test]
+    fn test_generate_breadcrumbs_single_level() {
+        let path = Path::new("docs");
+        let breadcrumbs = generate_breadcrumbs(path);
+
+        // Home only - "docs" is the current directory, not shown in breadcrumbs
+        assert_eq!(breadcrumbs.len(), 1);
+        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
+    }
+
+    #[test]
+    fn test_generate_breadcrumbs_two_levels() {
+        let path = Path::new("docs/api");
+        let breadcrumbs = generate_breadcrumbs(path);
+
+        assert_eq!(breadcrumbs.len(), 2);
+        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
+        assert_eq!(breadcrumbs[1], Breadcrumb::new("docs", "/docs/"));
+    }
+
+    #[test]
+    fn test_generate_breadcrumbs_deep_nesting() {
+        let path = Path::new("/a/b/c/d");
+        let breadcrumbs = generate_breadcrumbs(path);
+
+        assert_eq!(breadcrumbs.len(), 4);
+        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
+        assert_eq!(breadcrumbs[1], Breadcrumb::new("a", "/a/"));
+        assert_eq!(breadcrumbs[2], Breadcrumb::new("b", "/a/b/"));
+        assert_eq!(breadcrumbs[3], Breadcrumb::new("c", "/a/b/c/"));
+    }
+
+    #[test]
+    fn test_get_current_dir_name_root() {
+        let path = Path::new("");
+        assert_eq!(get_current_dir_name(path), "Home");
+    }
+
+    #[test]
+    fn test_get_current_dir_name_single_level() {
+        let path = Path::new("docs");
+        assert_eq!(get_current_dir_name(path), "docs");
+    }
+
+    #[test]
+    fn test_get_current_dir_name_nested() {
+        let path = Path::new("a/b/c");
+        assert_eq!(get_current_dir_name(path), "c");
+    }
+
+    #[test]
+    fn test_get_parent_path_root() {
+        let path = Path::new("");
+        assert_eq!(get_parent_path(path), None);
+    }
+
+    #[test]
+    fn test_get_parent_path_single_level() {
+        let path = Path::new("docs");
+        assert_eq!(get_parent_path(path), Some("/".to_string()));
+    }
+
+    #[test]
+    fn test_get_parent_path_two_levels() {
+        let path = Path::new("docs/api");
+        assert_eq!(get_parent_path(path), Some("/docs/".to_string()));
+    }
+
+    #[test]
+    fn test_get_parent_path_deep() {
+        let path = Path::new("a/b/c/d");
+        assert_eq!(get_parent_path(path), Some("/a/b/c/".to_string()));
+    }
+
+    #[test]
+    fn test_markdown_file_to_json_with_frontmatter() {
+        let mut frontmatter = HashMap::new();
+        frontmatter.insert(
+            "title".to_string(),
+            serde_json::Value::String("My Title".to_string()),
+        );
+        frontmatter.insert(
+            "description".to_string(),
+            serde_json::Value::String("My description".to_string()),
+        );
+        frontmatter.insert("tags".to_string(), serde_json::json!(["rust", "testing"]));
+
+        let file_info = MarkdownInfo {
+            raw_path: PathBuf::from("/root/test.md"),
+            url_path: "/test/".to_string(),
+            url: "/test/".to_string(),
+            frontmatter: Some(frontmatter),
+            created: 1699000000,
+            modified: 1700000000,
+            word_count: 450,
+            date: Some(1704412800),
+            created_date: None,
+            updated_date: None,
+            relationships: Vec::new(),
+            cover_image: Some("/images/cover.jpg".to_string()),
+        };
+
+        let json = markdown_file_to_json(&file_info);
+
+        assert_eq!(json["title"], "My Title");
+        assert_eq!(json["url_path"], "/test/");
+        assert_eq!(json["description"], "My description");
+        assert_eq!(json["tags"], serde_json::json!(["rust", "testing"]));
+        assert_eq!(json["modified"], 1700000000);
+        assert_eq!(json["date"], 1704412800);
+        assert_eq!(json["word_count"], 450);
+        assert_eq!(json["reading_time_minutes"], 3);
+        assert_eq!(json["name"], "test.md");
+        assert_eq!(json["cover_image"], "/images/cover.jpg");
+        assert_eq!(json["cover_thumbnail"], "/.mbr/thumbs/images/cover.jpg");
+    }
+
+    #[test]
+    fn test_markdown_file_to_json_without_frontmatter() {
+        let file_info = MarkdownInfo {
+            raw_path: PathBuf::from("/root/my-document.md"),
+            url_path: "/my-document/".to_string(),
+            url: "/my-document/".to_string(),
+            frontmatter: None,
+            created: 1699000000,
+            modified: 1700000000,
+            word_count: 0,
+            date: None,
+            created_date: None,
+            updated_date: None,
+            relationships: Vec::new(),
+            cover_image: None,
+        };
+
+        let json = markdown_file_to_json(&file_info);
+
+        // Should use file stem as title when no frontmatter
+        assert_eq!(json["title"], "my-document");
+        assert_eq!(json["url_path"], "/my-document/");
+        assert!(json["description"].is_null());
+        assert!(json["tags"].is_null());
+        assert!(json["cover_image"].is_null());
+        assert!(json["cover_thumbnail"].is_null());
+    }
+
+    #[test]
+    fn test_markdown_file_to_json_partial_frontmatter() {
+        let mut frontmatter = HashMap::new();
+        frontmatter.insert(
+            "title".to_string(),
+            serde_json::Value::String("Only Title".to_string()),
+        );
+        // No description or tags
+
+        let file_info = MarkdownInfo {
+            raw_path: PathBuf::from("/root/partial.md"),
+            url_path: "/partial/".to_string(),
+            url: "/partial/".to_string(),
+            frontmatter: Some(frontmatter),
+            created: 1699000000,
+            modified: 1700000000,
+            word_count: 0,
+            date: None,
+            created_date: None,
+            updated_date: None,
+            relationships: Vec::new(),
+            cover_image: None,
+        };
+
+        let json = markdown_file_to_json(&file_info);
+
+        assert_eq!(json["title"], "Only Title");
+        assert!(json["description"].is_null());
+        assert!(json["tags"].is_null());
+    }
+
+    #[test]
+    fn test_breadcrumb_equality() {
+        let b1 = Breadcrumb::new("Home", "/");
+        let b2 = Breadcrumb::new("Home", "/");
+        let b3 = Breadcrumb::new("Docs", "/docs/");
+
+        assert_eq!(b1, b2);
+        assert_ne!(b1, b3);
+    }
+
+    #[test]
+    fn test_server_reexports_same_helpers() {
+        let path = Path::new("docs/guide");
+        assert_eq!(
+            crate::server::generate_breadcrumbs(path),
+            generate_breadcrumbs(path)
+        );
+        assert_eq!(crate::server::get_parent_path(path), get_parent_path(path));
+    }
+}
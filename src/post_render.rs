@@ -0,0 +1,193 @@
+//! Transforms applied to a page's rendered HTML before it's written or served.
+//!
+//! A [`PostRenderHook`] receives the HTML of a markdown page body (not the
+//! surrounding template) and returns the rewritten HTML. Hooks run in the
+//! order they were added, after mbr's own markdown rendering. Library users
+//! add their own with [`Builder::with_post_render_hook`] or
+//! [`ServerConfig::with_post_render_hook`]:
+//!
+//! ```
+//! use mbr::post_render::{PostRenderHook, PostRenderPage};
+//!
+//! struct StripedTables;
+//!
+//! impl PostRenderHook for StripedTables {
+//!     fn apply(&self, html: String, _page: &PostRenderPage<'_>) -> String {
+//!         html.replace("<table>", r#"<table class="striped">"#)
+//!     }
+//! }
+//! ```
+//!
+//! The server streams large pages, and then calls each hook once per part
+//! of the body. Parts are split between top-level blocks, so a hook that
+//! rewrites individual elements sees each element whole.
+//!
+//! mbr's HTML sanitizer ([`HtmlSanitizer`]) and external-link decoration
+//! ([`ExternalLinks`]) are themselves hooks, added first when
+//! `sanitize_html` and `external_links_new_tab` are on. [`HeadingAnchors`] is
+//! available for sites that want permalinks without JavaScript.
+//!
+//! [`Builder::with_post_render_hook`]: crate::build::Builder::with_post_render_hook
+//! [`ServerConfig::with_post_render_hook`]: crate::server::ServerConfig::with_post_render_hook
+
+use regex::Regex;
+use std::sync::{Arc, LazyLock};
+
+use crate::sanitize::HtmlSanitizer;
+
+static HEADING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)(<h([1-6])\b[^>]*\sid="([^"]+)"[^>]*>)(.*?)(</h[1-6]>)"#)
+        .expect("Invalid HEADING_RE regex pattern")
+});
+
+/// Class of the permalink [`HeadingAnchors`] appends to each heading. The
+/// heading enhancer component uses the same class and skips headings that
+/// already have one.
+pub const HEADING_ANCHOR_CLASS: &str = "mbr-heading-anchor";
+
+/// The page a hook is transforming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostRenderPage<'a> {
+    /// Root-relative URL of the page (e.g. `/docs/guide/`).
+    pub url_path: &'a str,
+    /// True in server/GUI mode, false in static builds.
+    pub server_mode: bool,
+}
+
+/// A transform over a page's rendered body HTML.
+pub trait PostRenderHook: Send + Sync {
+    /// Returns `html` rewritten for `page`.
+    fn apply(&self, html: String, page: &PostRenderPage<'_>) -> String;
+}
+
+/// Runs `hooks` over `html` in order.
+pub fn apply_hooks(
+    hooks: &[Arc<dyn PostRenderHook>],
+    html: String,
+    page: &PostRenderPage<'_>,
+) -> String {
+    hooks.iter().fold(html, |html, hook| hook.apply(html, page))
+}
+
+/// The hooks mbr enables from its own config, ahead of any added by a
+/// library user. The sanitizer, when on, runs first so the other hooks'
+/// markup is kept.
+pub fn builtin_hooks(
+    sanitizer: Option<HtmlSanitizer>,
+    external_links_new_tab: bool,
+    base_url: Option<&str>,
+) -> Vec<Arc<dyn PostRenderHook>> {
+    let mut hooks: Vec<Arc<dyn PostRenderHook>> = Vec::new();
+    if let Some(sanitizer) = sanitizer {
+        hooks.push(Arc::new(sanitizer));
+    }
+    if external_links_new_tab {
+        hooks.push(Arc::new(ExternalLinks {
+            base_url: base_url.map(str::to_string),
+        }));
+    }
+    hooks
+}
+
+/// Opens external links in a new tab (see
+/// [`crate::link_transform::decorate_external_links`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExternalLinks {
+    /// Public URL of the site; links to its host aren't external.
+    pub base_url: Option<String>,
+}
+
+impl PostRenderHook for ExternalLinks {
+    fn apply(&self, html: String, _page: &PostRenderPage<'_>) -> String {
+        crate::link_transform::decorate_external_links(&html, self.base_url.as_deref())
+    }
+}
+
+/// Appends a `#` permalink to every heading with an `id`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeadingAnchors;
+
+impl PostRenderHook for HeadingAnchors {
+    fn apply(&self, html: String, _page: &PostRenderPage<'_>) -> String {
+        HEADING_RE
+            .replace_all(&html, |caps: &regex::Captures| {
+                if caps[4].contains(HEADING_ANCHOR_CLASS) {
+                    return caps[0].to_string();
+                }
+                format!(
+                    r##"{}{}<a class="{HEADING_ANCHOR_CLASS}" href="#{}" aria-label="Permalink">#</a>{}"##,
+                    &caps[1], &caps[4], &caps[3], &caps[5]
+                )
+            })
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: PostRenderPage<'static> = PostRenderPage {
+        url_path: "/docs/guide/",
+        server_mode: false,
+    };
+
+    struct Append(&'static str);
+
+    impl PostRenderHook for Append {
+        fn apply(&self, html: String, page: &PostRenderPage<'_>) -> String {
+            format!("{html}{}{}", self.0, page.url_path)
+        }
+    }
+
+    #[test]
+    fn test_apply_hooks_in_order() {
+        let hooks: Vec<Arc<dyn PostRenderHook>> =
+            vec![Arc::new(Append("a")), Arc::new(Append("b"))];
+        assert_eq!(
+            apply_hooks(&hooks, "<p>x</p>".to_string(), &PAGE),
+            "<p>x</p>a/docs/guide/b/docs/guide/"
+        );
+        assert_eq!(apply_hooks(&[], "<p>x</p>".to_string(), &PAGE), "<p>x</p>");
+    }
+
+    #[test]
+    fn test_builtin_hooks() {
+        assert!(builtin_hooks(None, false, None).is_empty());
+        let hooks = builtin_hooks(
+            Some(HtmlSanitizer::default()),
+            true,
+            Some("https://notes.example.com/"),
+        );
+        let html = apply_hooks(
+            &hooks,
+            r#"<a href="https://example.com/" onclick="x()">Out</a><a href="https://notes.example.com/a/">In</a>"#
+                .to_string(),
+            &PAGE,
+        );
+        assert_eq!(
+            html,
+            r#"<a href="https://example.com/" target="_blank" rel="noopener noreferrer" class="mbr-external-link">Out</a><a href="https://notes.example.com/a/">In</a>"#
+        );
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let html = concat!(
+            r#"<h1 id="title">Title</h1><p>Body</p>"#,
+            r#"<h2 id="a-b" class="x">A <em>b</em></h2>"#,
+            r#"<h3>No id</h3>"#,
+        );
+        let anchored = HeadingAnchors.apply(html.to_string(), &PAGE);
+        assert_eq!(
+            anchored,
+            concat!(
+                r##"<h1 id="title">Title<a class="mbr-heading-anchor" href="#title" aria-label="Permalink">#</a></h1><p>Body</p>"##,
+                r##"<h2 id="a-b" class="x">A <em>b</em><a class="mbr-heading-anchor" href="#a-b" aria-label="Permalink">#</a></h2>"##,
+                r#"<h3>No id</h3>"#,
+            )
+        );
+        // Running twice doesn't add a second anchor
+        assert_eq!(HeadingAnchors.apply(anchored.clone(), &PAGE), anchored);
+    }
+}
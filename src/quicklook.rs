@@ -143,6 +143,7 @@ pub fn render_preview_with_config(
         // QuickLook previews a single file with no repo index, so body
         // wikilinks never resolve globally; the page URL is unused.
         current_page_url: String::new(),
+        clean_urls: true,
     };
 
     // Create a minimal tokio runtime for async markdown rendering
@@ -165,6 +166,7 @@ pub fn render_preview_with_config(
                 path.clone(),
                 &root_path,
                 0,
+                config.oembed_fallback,
                 link_config,
                 false,                            // server_mode is false in QuickLook
                 false,                            // transcode is disabled in QuickLook
@@ -172,6 +174,9 @@ pub fn render_preview_with_config(
                 mark_incomplete,
                 &incomplete_markers,
                 None, // no repo wikilink index in QuickLook
+                None, // no data files in QuickLook
+                markdown::RendererKind::Markdown,
+                &config.markdown_options,
             )
             .await
         })
@@ -179,8 +184,8 @@ pub fn render_preview_with_config(
             message: e.to_string(),
         })?;
     let frontmatter = render_result.frontmatter;
-    let headings = render_result.headings;
-    let html = render_result.html;
+    let headings = config.toc_options().apply(render_result.headings);
+    let html = crate::sanitize::sanitize_for_config(&config, render_result.html);
 
     // Calculate base URL for relative asset resolution
     // Use root_path (markdown repo root) to properly resolve root-relative paths like /videos/
@@ -295,7 +300,7 @@ fn render_quicklook_template(
     let inline_css = build_inline_css(ql_config, &config.theme, &custom_theme, &custom_user_css);
 
     // Build inline JavaScript
-    let inline_js = build_inline_js(ql_config);
+    let inline_js = build_inline_js(ql_config, &config.mermaid_init_json());
 
     // Create Tera template engine with QuickLook template
     let mut tera = Tera::default();
@@ -389,8 +394,10 @@ fn build_inline_css(
     css
 }
 
-/// Build the inline JavaScript string.
-fn build_inline_js(config: &QuickLookConfig) -> String {
+/// Build the inline JavaScript string. `mermaid_options` is the
+/// `mermaid.initialize()` JSON from the repo config, so previews draw
+/// diagrams like the server does.
+fn build_inline_js(config: &QuickLookConfig, mermaid_options: &str) -> String {
     let mut js = String::with_capacity(JS_PREALLOC_BYTES);
 
     // Syntax highlighting - use embedded_hljs module
@@ -432,6 +439,9 @@ fn build_inline_js(config: &QuickLookConfig) -> String {
     if config.include_mermaid {
         js.push_str(get_embedded_file("/mermaid.min.js"));
         js.push('\n');
+        js.push_str(&format!(
+            "window.__MBR_MERMAID_CONFIG__ = {mermaid_options};\n"
+        ));
     }
 
     // QuickLook-specific initialization
@@ -501,10 +511,10 @@ document.addEventListener('DOMContentLoaded', function() {
 
     // Initialize mermaid diagrams
     if (typeof mermaid !== 'undefined') {
-        mermaid.initialize({
-            startOnLoad: true,
+        // mermaid_theme/mermaid_config override the light/dark default
+        mermaid.initialize(Object.assign({
             theme: window.matchMedia('(prefers-color-scheme: dark)').matches ? 'dark' : 'default'
-        });
+        }, window.__MBR_MERMAID_CONFIG__, { startOnLoad: true }));
     }
 
     // Intercept link clicks - only allow anchor links
@@ -568,7 +578,7 @@ const QUICKLOOK_TEMPLATE: &str = r##"<!doctype html>
             <ul>
             {% for heading in headings %}
                 <li class="toc-h{{ heading.level }}">
-                    <a href="#{{ heading.id }}">{{ heading.text }}</a>
+                    <a href="#{{ heading.id }}">{% if heading.number %}{{ heading.number }} {% endif %}{{ heading.text }}</a>
                 </li>
             {% endfor %}
             </ul>
@@ -710,10 +720,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quicklook_uses_configured_mermaid_theme() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mbr_dir = temp_dir.path().join(".mbr");
+        std::fs::create_dir(&mbr_dir).unwrap();
+        std::fs::write(mbr_dir.join("config.toml"), r#"mermaid_theme = "forest""#).unwrap();
+        let file_path = temp_dir.path().join("test.md");
+        std::fs::write(&file_path, "```mermaid\ngraph TD; A-->B\n```\n").unwrap();
+
+        let html = render_preview(file_path.to_str().unwrap().to_string(), None).unwrap();
+
+        assert!(html.contains(r#"window.__MBR_MERMAID_CONFIG__ = {"theme":"forest"};"#));
+    }
+
     #[test]
     fn test_quicklook_js_includes_initialization() {
         let config = QuickLookConfig::default();
-        let js = build_inline_js(&config);
+        let js = build_inline_js(&config, "{}");
 
         // Should include initialization code
         assert!(js.contains("DOMContentLoaded"));
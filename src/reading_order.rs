@@ -0,0 +1,294 @@
+//! Site-wide reading order for prev/next page navigation.
+//!
+//! By default prev/next links step through a page's directory siblings. With
+//! `reading_order` configured they follow one sequence across the whole site
+//! instead, taken from either the curated navigation manifest (`"nav"`) or a
+//! markdown file listing pages in order, such as an mdBook-style `SUMMARY.md`:
+//!
+//! ```markdown
+//! - [Introduction](README.md)
+//! - [Setup](guide/setup.md)
+//!   - [Linux](guide/linux.md)
+//! ```
+//!
+//! Pages not in the sequence keep sibling navigation.
+
+use crate::errors::NavError;
+use crate::nav::{NavItem, is_site_url};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// `reading_order` value that follows the curated navigation manifest.
+pub const NAV_SOURCE: &str = "nav";
+
+/// An ordered list of pages, in the `{url_path, title}` shape used for
+/// sibling navigation.
+#[derive(Debug, Clone, Default)]
+pub struct ReadingOrder {
+    pages: Vec<Value>,
+    urls: HashSet<String>,
+}
+
+impl ReadingOrder {
+    fn push(&mut self, url: &str, title: &str) {
+        if let Some(url) = normalize_page_url(url)
+            && self.urls.insert(url.clone())
+        {
+            self.pages.push(json!({"url_path": url, "title": title}));
+        }
+    }
+
+    /// Builds the order from the navigation manifest, depth-first.
+    pub fn from_nav(items: &[NavItem]) -> Self {
+        fn walk(order: &mut ReadingOrder, items: &[NavItem]) {
+            for item in items {
+                if let Some(url) = item.url.as_deref()
+                    && is_site_url(url)
+                {
+                    order.push(url, &item.title);
+                }
+                walk(order, &item.children);
+            }
+        }
+        let mut order = Self::default();
+        walk(&mut order, items);
+        order
+    }
+
+    /// Builds the order from the links in a markdown file, in document order.
+    ///
+    /// Links to markdown files are resolved relative to the file and mapped to
+    /// their page URLs (links leaving `root_dir` are skipped); site URLs
+    /// (`/docs/`) are used as-is.
+    pub fn from_summary(
+        summary: &str,
+        summary_path: &Path,
+        root_dir: &Path,
+        markdown_extensions: &[String],
+        index_file: &str,
+    ) -> Self {
+        let base_dir = summary_path.parent().unwrap_or(root_dir);
+        let mut order = Self::default();
+        let mut current: Option<(String, String)> = None;
+
+        for event in Parser::new(summary) {
+            match event {
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    current = Some((dest_url.to_string(), String::new()));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, title)) = current.as_mut() {
+                        title.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::Link) => {
+                    let Some((dest, title)) = current.take() else {
+                        continue;
+                    };
+                    if is_site_url(&dest) {
+                        order.push(&dest, &title);
+                    } else if let Some(path) =
+                        markdown_link_path(&dest, base_dir, markdown_extensions)
+                        && path.starts_with(root_dir)
+                    {
+                        let url = crate::repo::build_markdown_url_path(&path, root_dir, index_file);
+                        order.push(&url, &title);
+                    }
+                }
+                _ => {}
+            }
+        }
+        order
+    }
+
+    /// The pages in order.
+    pub fn pages(&self) -> &[Value] {
+        &self.pages
+    }
+
+    /// Returns true if the page at `url_path` is part of the sequence.
+    pub fn contains(&self, url_path: &str) -> bool {
+        self.urls.contains(url_path)
+    }
+
+    /// Returns the pages to step through from `url_path`: the reading order if
+    /// the page is part of it, else `siblings`.
+    pub fn sequence_for<'a>(
+        order: Option<&'a Self>,
+        url_path: &str,
+        siblings: &'a [Value],
+    ) -> &'a [Value] {
+        match order {
+            Some(order) if order.contains(url_path) => order.pages(),
+            _ => siblings,
+        }
+    }
+}
+
+/// Normalizes a page URL to the `/dir/page/` form used for `url_path`,
+/// dropping any fragment or query. Returns `None` for URLs that aren't pages
+/// (no trailing slash, e.g. `/files/report.pdf`).
+fn normalize_page_url(url: &str) -> Option<String> {
+    let base = url.split(['#', '?']).next().unwrap_or(url);
+    if !base.ends_with('/') {
+        return None;
+    }
+    let decoded = percent_encoding::percent_decode_str(base).decode_utf8_lossy();
+    let trimmed = decoded.trim_matches('/');
+    Some(if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{trimmed}/")
+    })
+}
+
+/// Resolves a relative link to a markdown file against `base_dir`, without
+/// touching the filesystem. Returns `None` for external or non-markdown links.
+fn markdown_link_path(
+    dest: &str,
+    base_dir: &Path,
+    markdown_extensions: &[String],
+) -> Option<PathBuf> {
+    if dest.contains("://") || dest.starts_with("//") || dest.starts_with("mailto:") {
+        return None;
+    }
+    let dest = dest.split(['#', '?']).next().unwrap_or(dest);
+    let dest = percent_encoding::percent_decode_str(dest).decode_utf8_lossy();
+    let ext = Path::new(dest.as_ref()).extension()?.to_str()?;
+    if !markdown_extensions
+        .iter()
+        .any(|e| e.eq_ignore_ascii_case(ext))
+    {
+        return None;
+    }
+
+    let mut path = base_dir.to_path_buf();
+    for component in Path::new(dest.as_ref()).components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::Normal(part) => path.push(part),
+            _ => {}
+        }
+    }
+    Some(path)
+}
+
+/// Loads the configured reading order: [`NAV_SOURCE`] for the navigation
+/// manifest, otherwise a markdown file path relative to `root_dir`.
+///
+/// Returns `Ok(None)` for `"nav"` when there is no manifest.
+pub fn load(
+    source: &str,
+    root_dir: &Path,
+    site_nav: Option<&[NavItem]>,
+    markdown_extensions: &[String],
+    index_file: &str,
+) -> Result<Option<ReadingOrder>, NavError> {
+    if source == NAV_SOURCE {
+        return Ok(site_nav.map(ReadingOrder::from_nav));
+    }
+    let path = root_dir.join(source);
+    let summary = std::fs::read_to_string(&path).map_err(|source| NavError::ReadFailed {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(Some(ReadingOrder::from_summary(
+        &summary,
+        &path,
+        root_dir,
+        markdown_extensions,
+        index_file,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(order: &ReadingOrder) -> Vec<&str> {
+        order
+            .pages()
+            .iter()
+            .map(|p| p["url_path"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_from_nav_flattens_depth_first() {
+        let nav = vec![
+            NavItem {
+                title: "Intro".to_string(),
+                url: Some("/intro/".to_string()),
+                children: vec![],
+            },
+            NavItem {
+                title: "Guides".to_string(),
+                url: None,
+                children: vec![
+                    NavItem {
+                        title: "Setup".to_string(),
+                        url: Some("/guides/setup/#first".to_string()),
+                        children: vec![],
+                    },
+                    NavItem {
+                        title: "Upstream".to_string(),
+                        url: Some("https://example.com/".to_string()),
+                        children: vec![],
+                    },
+                ],
+            },
+            NavItem {
+                title: "Intro again".to_string(),
+                url: Some("/intro/".to_string()),
+                children: vec![],
+            },
+        ];
+        let order = ReadingOrder::from_nav(&nav);
+        assert_eq!(urls(&order), vec!["/intro/", "/guides/setup/"]);
+        assert_eq!(order.pages()[1]["title"], "Setup");
+    }
+
+    #[test]
+    fn test_from_summary_resolves_markdown_links() {
+        let root = Path::new("/site");
+        let summary = "# Summary\n\n- [Welcome](index.md)\n- [Setup](guide/setup.md)\n  - [Linux `x86`](guide/linux.md#install)\n- [Notes](../outside.md)\n- [Tags](/tags/)\n- [Docs](https://example.com/)\n- [PDF](files/report.pdf)\n";
+        let order = ReadingOrder::from_summary(
+            summary,
+            &root.join("SUMMARY.md"),
+            root,
+            &["md".to_string()],
+            "index.md",
+        );
+        assert_eq!(
+            urls(&order),
+            vec!["/", "/guide/setup/", "/guide/linux/", "/tags/"]
+        );
+        assert_eq!(order.pages()[2]["title"], "Linux x86");
+    }
+
+    #[test]
+    fn test_sequence_for_falls_back_to_siblings() {
+        let order = ReadingOrder::from_nav(&[NavItem {
+            title: "A".to_string(),
+            url: Some("/a/".to_string()),
+            children: vec![],
+        }]);
+        let siblings = vec![json!({"url_path": "/b/", "title": "B"})];
+        assert_eq!(
+            ReadingOrder::sequence_for(Some(&order), "/a/", &siblings).len(),
+            1
+        );
+        assert_eq!(
+            ReadingOrder::sequence_for(Some(&order), "/b/", &siblings)[0]["url_path"],
+            "/b/"
+        );
+        assert_eq!(
+            ReadingOrder::sequence_for(None, "/a/", &siblings)[0]["url_path"],
+            "/b/"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Redirect pages from `redirect_to` frontmatter.
+//!
+//! A page that was merged or renamed can be left behind with just
+//!
+//! ```yaml
+//! ---
+//! redirect_to: /new/path/
+//! ---
+//! ```
+//!
+//! The server answers its URL with a `301 Moved Permanently` to the target.
+//! Static builds can't send status codes, so they write a minimal page with a
+//! `<meta http-equiv="refresh">` and a `<link rel="canonical">` pointing at
+//! the target instead of rendering the body. Redirect pages are left out of
+//! search, feeds and the archive.
+//!
+//! Targets are root-relative paths (`/new/path/`, optionally with a `#` or
+//! `?` suffix) or `http(s)://` URLs. Any other value is ignored and the page
+//! renders as usual.
+
+use crate::link_transform::normalize_encoding;
+use crate::markdown::SimpleMetadata;
+
+/// Frontmatter key naming the page's redirect target.
+pub const REDIRECT_FIELD: &str = "redirect_to";
+
+/// The redirect target in `frontmatter`, percent-encoded for use as a URL,
+/// or `None` when the page doesn't redirect or the value isn't a
+/// root-relative path or `http(s)` URL.
+pub fn redirect_target(frontmatter: &SimpleMetadata) -> Option<String> {
+    let value = frontmatter.get(REDIRECT_FIELD)?.as_str()?.trim();
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return Some(value.to_string());
+    }
+    if !value.starts_with('/') || value.starts_with("//") {
+        return None;
+    }
+    let split = value.find(['#', '?']).unwrap_or(value.len());
+    let (path, suffix) = value.split_at(split);
+    Some(format!("{}{suffix}", normalize_encoding(path)))
+}
+
+/// True if `frontmatter` sets `redirect_to` to something that isn't a valid
+/// target, so the caller can warn that it was ignored.
+pub fn has_invalid_target(frontmatter: &SimpleMetadata) -> bool {
+    frontmatter.contains_key(REDIRECT_FIELD) && redirect_target(frontmatter).is_none()
+}
+
+/// The minimal page a static build writes for a redirect: `href` is where
+/// the browser is sent (relative to the page in builds), `canonical` is the
+/// target's public URL when `base_url` is known, else the same as `href`.
+pub fn redirect_page(href: &str, canonical: &str) -> String {
+    let href = html_escape::encode_double_quoted_attribute(href);
+    let canonical = html_escape::encode_double_quoted_attribute(canonical);
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Redirecting…</title>\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         <meta http-equiv=\"refresh\" content=\"0; url={href}\">\n\
+         <link rel=\"canonical\" href=\"{canonical}\">\n\
+         </head>\n\
+         <body>\n\
+         <p>This page has moved to <a href=\"{href}\">{href}</a>.</p>\n\
+         </body>\n\
+         </html>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frontmatter(value: serde_json::Value) -> SimpleMetadata {
+        SimpleMetadata::from([(REDIRECT_FIELD.to_string(), value)])
+    }
+
+    #[test]
+    fn test_redirect_target() {
+        let target = |value: &str| redirect_target(&frontmatter(value.into()));
+        assert_eq!(target("/new/path/").as_deref(), Some("/new/path/"));
+        assert_eq!(target(" /new/path/ ").as_deref(), Some("/new/path/"));
+        assert_eq!(
+            target("/my notes/café/#top").as_deref(),
+            Some("/my%20notes/caf%C3%A9/#top")
+        );
+        // Already-encoded paths aren't encoded twice
+        assert_eq!(target("/my%20notes/").as_deref(), Some("/my%20notes/"));
+        assert_eq!(
+            target("https://example.com/moved/").as_deref(),
+            Some("https://example.com/moved/")
+        );
+        for bad in [
+            "",
+            "new/path/",
+            "../new/",
+            "//evil.example/",
+            "javascript:x()",
+        ] {
+            assert_eq!(target(bad), None, "{bad:?}");
+            assert!(has_invalid_target(&frontmatter(bad.into())));
+        }
+        assert_eq!(redirect_target(&frontmatter(serde_json::json!(3))), None);
+        assert!(!has_invalid_target(&SimpleMetadata::new()));
+    }
+
+    #[test]
+    fn test_redirect_page() {
+        let page = redirect_page("../new/?a=1&b=2", "https://example.com/new/?a=1&b=2");
+        assert!(
+            page.contains(r#"<meta http-equiv="refresh" content="0; url=../new/?a=1&amp;b=2">"#)
+        );
+        assert!(
+            page.contains(r#"<link rel="canonical" href="https://example.com/new/?a=1&amp;b=2">"#)
+        );
+        assert!(page.contains(r#"<meta name="robots" content="noindex">"#));
+        assert!(!page.contains("data-pagefind-body"));
+    }
+}
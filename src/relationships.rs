@@ -387,8 +387,8 @@ pub fn parse_relationships(yaml: &Yaml) -> Vec<RawRelationship> {
 }
 
 /// Converts a `yaml_rust2::Yaml` value to a `serde_json::Value`, preserving
-/// structure (used for free-form edge attributes).
-fn yaml_to_json(y: &Yaml) -> serde_json::Value {
+/// structure (used for free-form edge attributes and chapter markers).
+pub(crate) fn yaml_to_json(y: &Yaml) -> serde_json::Value {
     use serde_json::Value;
     match y {
         Yaml::String(s) => Value::String(s.clone()),
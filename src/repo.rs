@@ -16,10 +16,18 @@ use walkdir::WalkDir;
 use crate::Config;
 use crate::config::{RelationType, TagSource};
 use crate::errors::RepoError;
+use crate::gitignore::GitignoreFilter;
 use crate::relationships::{NoteRelInput, RawRelationship, RelationshipIndex};
-use crate::tag_index::{TagIndex, TaggedPage};
+use crate::tag_index::{TagIndex, TagInfo, TaggedPage};
 use crate::wikilink_index::WikilinkIndex;
 
+/// Version of the `site.json` shape, written as its top-level `schema_version`.
+///
+/// Bump this whenever a top-level or per-file field is added, removed or
+/// changes meaning, so downstream tools can detect the change. See the
+/// "Site Metadata" section of `docs/reference/integration.md` for the history.
+pub const SITE_JSON_SCHEMA_VERSION: u32 = 3;
+
 #[derive(Clone, Serialize)]
 pub struct Repo {
     #[serde(skip)]
@@ -38,6 +46,9 @@ pub struct Repo {
     ignore_globs: Vec<String>,
     #[serde(skip)]
     compiled_ignore_globs: Vec<glob::Pattern>,
+    /// Root `.gitignore` rules, when `respect_gitignore` is on and the repo has any.
+    #[serde(skip)]
+    gitignore: Option<Arc<GitignoreFilter>>,
     #[serde(skip)]
     pub scanned_folders: HashSet<PathBuf>,
     #[serde(skip)]
@@ -57,6 +68,12 @@ pub struct Repo {
     /// Configured tag sources for frontmatter extraction.
     #[serde(skip)]
     tag_sources: Vec<TagSource>,
+    /// Strip BOMs and CRLF line endings before reading frontmatter.
+    #[serde(skip)]
+    normalize_line_endings: bool,
+    /// Scan folders reached through symlinks.
+    #[serde(skip)]
+    follow_symlinks: bool,
     /// Whether text extraction has been performed for searchable files.
     #[serde(skip)]
     text_extracted: Arc<AtomicBool>,
@@ -117,18 +134,113 @@ impl Serialize for OtherFiles {
     }
 }
 
+/// Markdown files that all map to the same URL (e.g. `guide.md` and
+/// `guide/index.md`), so only one of them can be served or built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlPathCollision {
+    pub url_path: String,
+    /// The conflicting files relative to the root directory, sorted.
+    pub sources: Vec<PathBuf>,
+}
+
 #[derive(Clone, Serialize)]
 pub struct MarkdownInfo {
     pub raw_path: PathBuf,
+    /// Root-relative URL as readable text (`/my note/`), for display and
+    /// lookups.
     pub url_path: String,
+    /// `url_path` percent-encoded for use in hrefs (`/my%20note/`).
+    pub url: String,
     pub created: u64,
     pub modified: u64,
     pub frontmatter: Option<crate::markdown::SimpleMetadata>,
+    /// Estimated word count from the scan (not the rendered count).
+    pub word_count: usize,
+    /// Frontmatter `date` normalized to a Unix timestamp, if recognized.
+    pub date: Option<i64>,
+    /// Publish date: frontmatter `created`, else `date`.
+    pub created_date: Option<i64>,
+    /// Last-updated date: frontmatter `updated`, else the file's mtime.
+    pub updated_date: Option<i64>,
     /// Typed relationships declared in frontmatter (unresolved endpoints).
     /// Skipped in serialization — resolved relationships are exposed via the
     /// relationship index in site.json/links.json instead.
     #[serde(skip)]
     pub relationships: Vec<RawRelationship>,
+    /// Root-relative URL of the listing cover image: frontmatter `image`,
+    /// else the first content image. Remote URLs are kept as-is.
+    pub cover_image: Option<String>,
+}
+
+impl MarkdownInfo {
+    /// Fills the normalized dates from the frontmatter and mtime. Unrecognized
+    /// dates are reported when the page renders, so here they're just missing.
+    pub fn with_dates(mut self) -> Self {
+        let empty = crate::markdown::SimpleMetadata::new();
+        let dates = crate::dates::PageDates::new(
+            self.frontmatter.as_ref().unwrap_or(&empty),
+            Some(self.modified),
+        );
+        self.date = dates.date;
+        self.created_date = dates.created;
+        self.updated_date = dates.updated;
+        self
+    }
+
+    /// Fills `cover_image` from frontmatter `image`, else `first_image` (see
+    /// [`crate::markdown::FileMetadata::first_image`]). Relative paths resolve
+    /// against the source file's folder, like links in the rendered page.
+    pub fn with_cover_image(mut self, first_image: Option<String>, root_dir: &Path) -> Self {
+        let image = self
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get("image"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or(first_image);
+        let source_dir = self
+            .raw_path
+            .parent()
+            .and_then(|dir| pathdiff::diff_paths(dir, root_dir))
+            .unwrap_or_default();
+        self.cover_image = image.and_then(|image| resolve_cover_image(&image, &source_dir));
+        self
+    }
+
+    /// True if the page's frontmatter `redirect_to` sends it elsewhere (see
+    /// [`crate::redirect`]); such pages stay out of search and listings.
+    pub fn is_redirect(&self) -> bool {
+        self.frontmatter
+            .as_ref()
+            .is_some_and(|fm| crate::redirect::redirect_target(fm).is_some())
+    }
+}
+
+/// Resolves an image reference from a markdown file in `source_dir` (relative
+/// to the repo root) to a root-relative URL. Remote, `data:` and
+/// root-relative URLs are returned unchanged.
+fn resolve_cover_image(image: &str, source_dir: &Path) -> Option<String> {
+    let image = image.trim();
+    if image.is_empty() {
+        return None;
+    }
+    if image.starts_with('/') || image.starts_with("data:") || image.contains("://") {
+        return Some(image.to_string());
+    }
+    let mut segments: Vec<String> = source_dir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    for part in image.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment.to_string()),
+        }
+    }
+    Some(format!("/{}", segments.join("/")))
 }
 
 #[derive(Clone, Serialize)]
@@ -479,6 +591,11 @@ impl StaticFileMetadata {
 
 impl Repo {
     pub fn init_from_config(c: &Config) -> Self {
+        let gitignore = if c.respect_gitignore {
+            GitignoreFilter::load(&c.root_dir, &c.static_folder).map(Arc::new)
+        } else {
+            None
+        };
         Self::init(
             c.root_dir.clone(),
             c.static_folder.clone(),
@@ -489,6 +606,41 @@ impl Repo {
             &c.tag_sources[..],
             &c.relationship_types[..],
         )
+        .with_gitignore(gitignore)
+        .with_normalize_line_endings(c.markdown_options.normalize_line_endings)
+        .with_follow_symlinks(c.follow_symlinks)
+    }
+
+    /// Sets the gitignore rules applied during scans (`None` disables them).
+    #[must_use]
+    pub fn with_gitignore(mut self, gitignore: Option<Arc<GitignoreFilter>>) -> Self {
+        self.gitignore = gitignore;
+        self
+    }
+
+    /// Sets whether BOMs and CRLF line endings are normalized before
+    /// frontmatter is read (on by default).
+    #[must_use]
+    pub fn with_normalize_line_endings(mut self, normalize: bool) -> Self {
+        self.normalize_line_endings = normalize;
+        self
+    }
+
+    /// Sets whether scans descend into symlinked folders (on by default).
+    #[must_use]
+    pub fn with_follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Whether scans descend into symlinked folders.
+    pub fn follow_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Returns the gitignore rules applied during scans, if any.
+    pub fn gitignore(&self) -> Option<&Arc<GitignoreFilter>> {
+        self.gitignore.as_ref()
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -519,6 +671,7 @@ impl Repo {
             ignore_dirs: ignore_dirs.to_vec(),
             ignore_globs: ignore_globs.to_vec(),
             compiled_ignore_globs,
+            gitignore: None,
             index_file: index_file.into(),
             scanned_folders: HashSet::new(),
             queued_folders: HashMap::new(),
@@ -530,6 +683,8 @@ impl Repo {
             )),
             wikilink_index: Arc::new(WikilinkIndex::new()),
             tag_sources: tag_sources.to_vec(),
+            normalize_line_endings: true,
+            follow_symlinks: true,
             text_extracted: Arc::new(AtomicBool::new(false)),
             media_populated: Arc::new(AtomicBool::new(false)),
             scan_complete: Arc::new(AtomicBool::new(false)),
@@ -549,22 +704,39 @@ impl Repo {
                     source,
                 })?;
 
-        // Skip if already scanned
-        if self.scanned_folders.pin().contains(&start_folder) {
+        // Skip if already scanned, which also ends symlink cycles and scans
+        // each folder once however many links lead to it
+        if !self.scanned_folders.pin().insert(start_folder.clone()) {
             return Ok(());
         }
         tracing::debug!("Scanning folder: {:?}", relative_folder_path_ref);
-        self.scanned_folders.pin().insert(start_folder.clone());
+
+        // A symlinked folder outside the root is walked by its path under the
+        // root, so its pages get URLs beneath the link
+        let walk_folder = if start_folder.starts_with(&self.root_dir) {
+            start_folder
+        } else {
+            joined
+        };
 
         // Walk directory with filtering (using pre-compiled patterns for efficiency)
         let walkdir_start = Instant::now();
-        let dir_walker = WalkDir::new(start_folder.clone())
-            .follow_links(true)
+        let dir_walker = WalkDir::new(walk_folder)
+            .follow_links(self.follow_symlinks)
             .min_depth(1)
             .max_depth(1)
             .into_iter()
             .filter_entry(|e| {
-                !should_ignore_compiled(e.path(), &self.ignore_dirs, &self.compiled_ignore_globs)
+                (self.follow_symlinks || !is_symlinked_dir(e))
+                    && !should_ignore_compiled(
+                        e.path(),
+                        &self.ignore_dirs,
+                        &self.compiled_ignore_globs,
+                    )
+                    && !self
+                        .gitignore
+                        .as_ref()
+                        .is_some_and(|gi| gi.is_ignored(e.path(), e.file_type().is_dir()))
             });
 
         let mut markdown = std::collections::HashMap::new();
@@ -587,12 +759,19 @@ impl Repo {
                     let url = build_markdown_url_path(path, &self.root_dir, &self.index_file);
                     let mdfile = MarkdownInfo {
                         raw_path: path.to_path_buf(),
+                        url: crate::link_transform::encode_url_path(&url),
                         url_path: url,
                         created,
                         modified,
                         frontmatter: None,
+                        word_count: 0,
+                        date: None,
+                        created_date: None,
+                        updated_date: None,
                         relationships: Vec::new(),
-                    };
+                        cover_image: None,
+                    }
+                    .with_dates();
                     markdown.insert(path.to_path_buf(), mdfile);
                 } else {
                     tracing::warn!("Couldn't process markdown file at {:?}", path);
@@ -622,10 +801,16 @@ impl Repo {
         markdown
             .into_par_iter()
             .for_each(|(mdfile, mddetails): (PathBuf, MarkdownInfo)| {
-                let file_meta = crate::markdown::extract_metadata_from_file(&mdfile).ok();
+                let file_meta = crate::markdown::extract_metadata_from_file(
+                    &mdfile,
+                    self.normalize_line_endings,
+                )
+                .ok();
                 let details = if let Some(file_meta) = file_meta {
                     let frontmatter = file_meta.metadata;
                     let relationships = file_meta.relationships;
+                    let word_count = file_meta.word_count;
+                    let first_image = file_meta.first_image;
                     // Extract tags from frontmatter for each configured tag source
                     let title = get_page_title(&frontmatter, &mddetails.raw_path);
                     let description = frontmatter
@@ -634,8 +819,11 @@ impl Repo {
                         .map(|s| s.to_string());
 
                     for tag_source in &self.tag_sources {
-                        // Look up the field (supports dot notation like "taxonomy.tags")
-                        if let Some(tag_value_json) = frontmatter.get(&tag_source.field) {
+                        // Look up each field (supports dot notation like "taxonomy.tags");
+                        // values from all of a source's fields merge under its primary field
+                        for tag_value_json in
+                            tag_source.all_fields().filter_map(|f| frontmatter.get(f))
+                        {
                             // Extract tag values (handles both arrays and comma-separated strings)
                             for tag_value in extract_tag_values(tag_value_json) {
                                 let page = if let Some(ref desc) = description {
@@ -647,7 +835,8 @@ impl Repo {
                                     )
                                 } else {
                                     TaggedPage::new(&mddetails.url_path, &title, &tag_value)
-                                };
+                                }
+                                .with_word_count(word_count);
                                 self.tag_index.add_page(&tag_source.field, &tag_value, page);
                             }
                         }
@@ -655,9 +844,12 @@ impl Repo {
 
                     MarkdownInfo {
                         frontmatter: Some(frontmatter),
+                        word_count,
                         relationships,
                         ..mddetails
                     }
+                    .with_dates()
+                    .with_cover_image(first_image, &self.root_dir)
                 } else {
                     mddetails
                 };
@@ -760,6 +952,27 @@ impl Repo {
         Ok(())
     }
 
+    /// Returns every `url_path` that more than one scanned markdown file maps
+    /// to, sorted by URL. Call after a scan.
+    pub fn url_path_collisions(&self) -> Vec<UrlPathCollision> {
+        let mut by_url: std::collections::BTreeMap<String, Vec<PathBuf>> = Default::default();
+        for (path, info) in self.markdown_files.pin().iter() {
+            let relative = path.strip_prefix(&self.root_dir).unwrap_or(path);
+            by_url
+                .entry(info.url_path.clone())
+                .or_default()
+                .push(relative.to_path_buf());
+        }
+        by_url
+            .into_iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .map(|(url_path, mut sources)| {
+                sources.sort();
+                UrlPathCollision { url_path, sources }
+            })
+            .collect()
+    }
+
     /// Scan the static folder and its subdirectories.
     /// Deferred from scan_all() so mark_scan_complete() fires faster.
     pub fn scan_static_folder(&self) -> Result<(), RepoError> {
@@ -948,129 +1161,151 @@ impl Repo {
         // scan finished. After clear(), scan_all() will re-scan synchronously in handlers.
     }
 
-    /// Surgically invalidate a single file, updating only the affected cache entries.
+    /// Re-reads a single created or modified file and updates its entry in
+    /// `markdown_files` or `other_files`, along with its tags, without a
+    /// rescan. A file that no longer exists (or is now gitignored) is removed
+    /// as by [`Self::remove_file`].
     ///
-    /// Much cheaper than `clear()` + `scan_all()` for small batches of file changes.
-    pub fn invalidate_file(&self, abs_path: &Path, event: &crate::watcher::ChangeEventType) {
+    /// Relationships and wikilinks resolve across the whole repo, so rebuild
+    /// them afterwards with [`Self::build_relationship_index`] and
+    /// [`Self::build_wikilink_index`].
+    pub fn reload_file(&self, abs_path: &Path) {
+        let ignored = self
+            .gitignore
+            .as_ref()
+            .is_some_and(|gi| gi.is_ignored(abs_path, false));
+        if ignored || !abs_path.is_file() {
+            self.remove_file(abs_path);
+            return;
+        }
+
         let extension = abs_path.extension().and_then(|x| x.to_str()).unwrap_or("");
-        let is_markdown = is_markdown_extension(extension, &self.markdown_extensions);
+        if !is_markdown_extension(extension, &self.markdown_extensions) {
+            let url = build_static_url_path(abs_path, &self.root_dir, &self.static_folder);
+            let info = OtherFileInfo {
+                raw_path: abs_path.to_path_buf(),
+                url_path: url,
+                metadata: StaticFileMetadata::empty(abs_path).populate_basic(),
+                extracted_text: None,
+            };
+            self.other_files.pin().insert(abs_path.to_path_buf(), info);
+            return;
+        }
 
-        match event {
-            crate::watcher::ChangeEventType::Deleted => {
-                if is_markdown {
-                    self.markdown_files.pin().remove(abs_path);
-                } else {
-                    self.other_files.pin().remove(abs_path);
-                }
-            }
-            crate::watcher::ChangeEventType::Created => {
-                if is_markdown {
-                    if let Ok((_filesize, created, modified)) = file_details_from_path(abs_path) {
-                        let url =
-                            build_markdown_url_path(abs_path, &self.root_dir, &self.index_file);
-                        let file_meta = crate::markdown::extract_metadata_from_file(abs_path).ok();
-                        let (frontmatter, relationships) = match file_meta {
-                            Some(fm) => (Some(fm.metadata), fm.relationships),
-                            None => (None, Vec::new()),
-                        };
-
-                        // Add tags from frontmatter
-                        if let Some(ref fm) = frontmatter {
-                            let title = get_page_title(fm, abs_path);
-                            let description = fm
-                                .get("description")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-                            for tag_source in &self.tag_sources {
-                                if let Some(tag_value_json) = fm.get(&tag_source.field) {
-                                    for tag_value in extract_tag_values(tag_value_json) {
-                                        let page = if let Some(ref desc) = description {
-                                            TaggedPage::with_description(
-                                                &url, &title, desc, &tag_value,
-                                            )
-                                        } else {
-                                            TaggedPage::new(&url, &title, &tag_value)
-                                        };
-                                        self.tag_index.add_page(
-                                            &tag_source.field,
-                                            &tag_value,
-                                            page,
-                                        );
-                                    }
-                                }
-                            }
-                        }
+        let Ok((_filesize, created, modified)) = file_details_from_path(abs_path) else {
+            return;
+        };
+        let url = build_markdown_url_path(abs_path, &self.root_dir, &self.index_file);
+        let file_meta =
+            crate::markdown::extract_metadata_from_file(abs_path, self.normalize_line_endings).ok();
+        let (frontmatter, relationships, word_count, first_image) = match file_meta {
+            Some(fm) => (
+                Some(fm.metadata),
+                fm.relationships,
+                fm.word_count,
+                fm.first_image,
+            ),
+            None => (None, Vec::new(), 0, None),
+        };
+        let info = MarkdownInfo {
+            raw_path: abs_path.to_path_buf(),
+            url: crate::link_transform::encode_url_path(&url),
+            url_path: url,
+            created,
+            modified,
+            frontmatter,
+            word_count,
+            date: None,
+            created_date: None,
+            updated_date: None,
+            relationships,
+            cover_image: None,
+        }
+        .with_dates()
+        .with_cover_image(first_image, &self.root_dir);
 
-                        let info = MarkdownInfo {
-                            raw_path: abs_path.to_path_buf(),
-                            url_path: url,
-                            created,
-                            modified,
-                            frontmatter,
-                            relationships,
-                        };
-                        self.markdown_files
-                            .pin()
-                            .insert(abs_path.to_path_buf(), info);
-                    }
-                } else {
-                    let url = build_static_url_path(abs_path, &self.root_dir, &self.static_folder);
-                    let info = OtherFileInfo {
-                        raw_path: abs_path.to_path_buf(),
-                        url_path: url,
-                        metadata: StaticFileMetadata::empty(abs_path).populate_basic(),
-                        extracted_text: None,
-                    };
-                    self.other_files.pin().insert(abs_path.to_path_buf(), info);
-                }
-            }
-            crate::watcher::ChangeEventType::Modified => {
-                if is_markdown {
-                    // Re-extract frontmatter and update
-                    if let Ok((_filesize, created, modified)) = file_details_from_path(abs_path) {
-                        let url =
-                            build_markdown_url_path(abs_path, &self.root_dir, &self.index_file);
-                        let file_meta = crate::markdown::extract_metadata_from_file(abs_path).ok();
-                        let (frontmatter, relationships) = match file_meta {
-                            Some(fm) => (Some(fm.metadata), fm.relationships),
-                            None => (None, Vec::new()),
-                        };
-                        let info = MarkdownInfo {
-                            raw_path: abs_path.to_path_buf(),
-                            url_path: url,
-                            created,
-                            modified,
-                            frontmatter,
-                            relationships,
-                        };
-                        self.markdown_files
-                            .pin()
-                            .insert(abs_path.to_path_buf(), info);
-                    }
-                } else {
-                    // Update basic metadata for modified static files
-                    let url = build_static_url_path(abs_path, &self.root_dir, &self.static_folder);
-                    let info = OtherFileInfo {
-                        raw_path: abs_path.to_path_buf(),
-                        url_path: url,
-                        metadata: StaticFileMetadata::empty(abs_path).populate_basic(),
-                        extracted_text: None,
-                    };
-                    self.other_files.pin().insert(abs_path.to_path_buf(), info);
-                }
-            }
+        if let Some(old) = self.markdown_files.pin().get(abs_path) {
+            self.tag_index.remove_page(&old.url_path);
         }
+        self.index_tags(&info);
+        self.markdown_files
+            .pin()
+            .insert(abs_path.to_path_buf(), info);
+    }
+
+    /// Drops a deleted file from `markdown_files` or `other_files`, and a
+    /// markdown file's page from the tag index.
+    pub fn remove_file(&self, abs_path: &Path) {
+        if let Some(old) = self.markdown_files.pin().remove(abs_path) {
+            self.tag_index.remove_page(&old.url_path);
+        }
+        self.other_files.pin().remove(abs_path);
     }
 
-    /// Rebuild the tag index from the current cached markdown files.
-    ///
-    /// Call this after surgical file invalidation when tags may have changed
-    /// (e.g., after deleting or modifying markdown files).
     /// Returns the configured tag sources.
     pub fn tag_sources(&self) -> &[TagSource] {
         &self.tag_sources
     }
 
+    /// Pages tagged `value` under the tag `source` (a frontmatter field such
+    /// as `tags`), sorted by title. Source and value match case-insensitively,
+    /// and spaces and underscores in the value are interchangeable.
+    ///
+    /// Reads the tag index, so the repo must have been scanned first.
+    ///
+    /// ```
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// # let root = dir.path().canonicalize().unwrap();
+    /// # std::fs::write(
+    /// #     root.join("guide.md"),
+    /// #     "---\ntitle: Rust Guide\ndescription: Getting started\ntags: [Rust, CLI]\n---\n",
+    /// # ).unwrap();
+    /// let config = mbr::Config {
+    ///     root_dir: root,
+    ///     ..Default::default()
+    /// };
+    /// let repo = mbr::repo::Repo::init_from_config(&config);
+    /// repo.scan_all().unwrap();
+    ///
+    /// let pages = repo.pages_with_tag("tags", "rust");
+    /// assert_eq!(pages[0].url_path, "/guide/");
+    /// assert_eq!(pages[0].title, "Rust Guide");
+    /// assert_eq!(pages[0].description.as_deref(), Some("Getting started"));
+    /// ```
+    pub fn pages_with_tag(&self, source: &str, value: &str) -> Vec<TaggedPage> {
+        let mut pages = self.tag_index.get_pages(source, value);
+        pages.sort_by(|a, b| {
+            a.title
+                .to_lowercase()
+                .cmp(&b.title.to_lowercase())
+                .then_with(|| a.url_path.cmp(&b.url_path))
+        });
+        pages
+    }
+
+    /// Every tag used under `source`, sorted by display name, with the
+    /// number of pages carrying it.
+    ///
+    /// ```
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// # let root = dir.path().canonicalize().unwrap();
+    /// # std::fs::write(root.join("a.md"), "---\ntags: [Rust, CLI]\n---\n").unwrap();
+    /// # std::fs::write(root.join("b.md"), "---\ntags: [rust]\n---\n").unwrap();
+    /// let config = mbr::Config {
+    ///     root_dir: root,
+    ///     ..Default::default()
+    /// };
+    /// let repo = mbr::repo::Repo::init_from_config(&config);
+    /// repo.scan_all().unwrap();
+    ///
+    /// let tags = repo.all_tags("tags");
+    /// let names: Vec<_> = tags.iter().map(|t| (t.normalized.as_str(), t.count)).collect();
+    /// assert_eq!(names, [("cli", 1), ("rust", 2)]);
+    /// ```
+    pub fn all_tags(&self, source: &str) -> Vec<TagInfo> {
+        self.tag_index.get_all_tags(source)
+    }
+
     /// Rebuild the relationship index from the current cached markdown files.
     ///
     /// Must run after a scan (or file-change invalidation) once all note titles
@@ -1121,13 +1356,7 @@ impl Repo {
                     .frontmatter
                     .as_ref()
                     .map(|fm| get_page_title(fm, &info.raw_path))
-                    .unwrap_or_else(|| {
-                        info.raw_path
-                            .file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or("Untitled")
-                            .to_string()
-                    });
+                    .unwrap_or_else(|| crate::markdown::title_from_filename(&info.raw_path));
                 let stem = info
                     .raw_path
                     .file_stem()
@@ -1165,32 +1394,38 @@ impl Repo {
             .collect()
     }
 
+    /// Rebuild the tag index from the current cached markdown files.
+    ///
+    /// Single-file changes don't need this: [`Self::reload_file`] and
+    /// [`Self::remove_file`] keep the index current.
     pub fn rebuild_tag_index(&self) {
         self.tag_index.clear();
         let pin = self.markdown_files.pin();
         for (_, info) in pin.iter() {
-            if let Some(ref fm) = info.frontmatter {
-                let title = get_page_title(fm, &info.raw_path);
-                let description = fm
-                    .get("description")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-                for tag_source in &self.tag_sources {
-                    if let Some(tag_value_json) = fm.get(&tag_source.field) {
-                        for tag_value in extract_tag_values(tag_value_json) {
-                            let page = if let Some(ref desc) = description {
-                                TaggedPage::with_description(
-                                    &info.url_path,
-                                    &title,
-                                    desc,
-                                    &tag_value,
-                                )
-                            } else {
-                                TaggedPage::new(&info.url_path, &title, &tag_value)
-                            };
-                            self.tag_index.add_page(&tag_source.field, &tag_value, page);
-                        }
+            self.index_tags(info);
+        }
+    }
+
+    /// Adds a page to the tag index under each of its frontmatter tags.
+    fn index_tags(&self, info: &MarkdownInfo) {
+        let Some(ref fm) = info.frontmatter else {
+            return;
+        };
+        let title = get_page_title(fm, &info.raw_path);
+        let description = fm
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        for tag_source in &self.tag_sources {
+            for tag_value_json in tag_source.all_fields().filter_map(|f| fm.get(f)) {
+                for tag_value in extract_tag_values(tag_value_json) {
+                    let page = if let Some(ref desc) = description {
+                        TaggedPage::with_description(&info.url_path, &title, desc, &tag_value)
+                    } else {
+                        TaggedPage::new(&info.url_path, &title, &tag_value)
                     }
+                    .with_word_count(info.word_count);
+                    self.tag_index.add_page(&tag_source.field, &tag_value, page);
                 }
             }
         }
@@ -1260,6 +1495,46 @@ pub fn should_ignore(path: &Path, ignore_dirs: &[String], ignore_globs: &[String
     })
 }
 
+/// True if `entry` is a symlink to a directory.
+pub(crate) fn is_symlinked_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.path_is_symlink() && entry.path().is_dir()
+}
+
+/// Directories already walked, by canonical path. Walks that follow
+/// symlinks use it to visit each folder once, however many links lead to
+/// it, so cycles and shared targets aren't scanned repeatedly.
+#[derive(Debug, Default)]
+pub(crate) struct VisitedDirs {
+    dirs: std::collections::HashSet<PathBuf>,
+    follow_symlinks: bool,
+}
+
+impl VisitedDirs {
+    pub(crate) fn new(follow_symlinks: bool) -> Self {
+        Self {
+            dirs: std::collections::HashSet::new(),
+            follow_symlinks,
+        }
+    }
+
+    /// Whether a walk should enter `entry`: files always, directories the
+    /// first time they're reached (never through a symlink when symlinks
+    /// aren't followed).
+    pub(crate) fn admit(&mut self, entry: &walkdir::DirEntry) -> bool {
+        let symlinked = is_symlinked_dir(entry);
+        if !symlinked && !entry.file_type().is_dir() {
+            return true;
+        }
+        if symlinked && !self.follow_symlinks {
+            return false;
+        }
+        entry
+            .path()
+            .canonicalize()
+            .is_ok_and(|dir| self.dirs.insert(dir))
+    }
+}
+
 /// Checks if a path should be ignored using pre-compiled glob patterns.
 /// This is more efficient than `should_ignore` when processing many files.
 fn should_ignore_compiled(
@@ -1395,17 +1670,12 @@ pub fn extract_tag_values(value: &serde_json::Value) -> Vec<String> {
 /// Priority:
 /// 1. `title` field in frontmatter
 /// 2. Filename stem (without extension)
-fn get_page_title(frontmatter: &crate::markdown::SimpleMetadata, path: &Path) -> String {
+pub(crate) fn get_page_title(frontmatter: &crate::markdown::SimpleMetadata, path: &Path) -> String {
     frontmatter
         .get("title")
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
-        .unwrap_or_else(|| {
-            path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Untitled")
-                .to_string()
-        })
+        .unwrap_or_else(|| crate::markdown::title_from_filename(path))
 }
 
 #[cfg(test)]
@@ -1564,6 +1834,174 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pages_with_tag_and_all_tags() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("zeta.md"),
+            "---\ntitle: Zeta\ntags: [Rust Lang]\n---\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("alpha.md"),
+            "---\ntitle: alpha\ndescription: First\ntags: rust_lang, cli\n---\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("untagged.md"), "# Untagged").unwrap();
+
+        let config = Config {
+            root_dir: root.clone(),
+            ..Default::default()
+        };
+        let repo = Repo::init_from_config(&config);
+        repo.scan_all().unwrap();
+
+        let pages = repo.pages_with_tag("Tags", "rust lang");
+        let titles: Vec<_> = pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["alpha", "Zeta"]);
+        assert_eq!(pages[0].url_path, "/alpha/");
+        assert_eq!(pages[0].description.as_deref(), Some("First"));
+        assert!(repo.pages_with_tag("tags", "missing").is_empty());
+        assert!(repo.pages_with_tag("performers", "rust lang").is_empty());
+
+        let tags = repo.all_tags("tags");
+        let counts: Vec<_> = tags
+            .iter()
+            .map(|t| (t.normalized.as_str(), t.count))
+            .collect();
+        assert_eq!(counts, [("cli", 1), ("rust_lang", 2)]);
+        assert!(repo.all_tags("performers").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_symlinks() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let root = dir.path().join("site");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(root.join("notes/a.md"), "# A").unwrap();
+        std::fs::write(outside.join("b.md"), "# B").unwrap();
+        // A cycle back to the root and a link to a folder outside it
+        std::os::unix::fs::symlink("..", root.join("notes/up")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+
+        let urls = |follow_symlinks| {
+            let config = Config {
+                root_dir: root.canonicalize().unwrap(),
+                follow_symlinks,
+                ..Default::default()
+            };
+            let repo = Repo::init_from_config(&config);
+            repo.scan_all().unwrap();
+            let mut urls: Vec<String> = repo
+                .markdown_files
+                .pin()
+                .values()
+                .map(|info| info.url_path.clone())
+                .collect();
+            urls.sort();
+            urls
+        };
+
+        assert_eq!(urls(true), ["/linked/b/", "/notes/a/"]);
+        assert_eq!(urls(false), ["/notes/a/"]);
+    }
+
+    #[test]
+    fn test_reload_and_remove_file_keep_tags_current() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.md"), "---\ntitle: A\ntags: [rust, cli]\n---\n").unwrap();
+
+        let config = Config {
+            root_dir: root.clone(),
+            ..Default::default()
+        };
+        let repo = Repo::init_from_config(&config);
+        repo.scan_all().unwrap();
+        let tag_urls = |value: &str| -> Vec<String> {
+            repo.pages_with_tag("tags", value)
+                .into_iter()
+                .map(|p| p.url_path)
+                .collect()
+        };
+
+        // Add
+        let b = root.join("b.md");
+        std::fs::write(&b, "---\ntitle: B\ntags: rust\n---\n# B").unwrap();
+        repo.reload_file(&b);
+        assert_eq!(repo.markdown_files.len(), 2);
+        assert_eq!(tag_urls("rust"), ["/a/", "/b/"]);
+
+        // Modify: old tags are dropped, new ones added
+        let a = root.join("a.md");
+        std::fs::write(&a, "---\ntitle: A2\ntags: [go]\n---\n").unwrap();
+        repo.reload_file(&a);
+        assert_eq!(tag_urls("rust"), ["/b/"]);
+        assert_eq!(tag_urls("go"), ["/a/"]);
+        assert!(tag_urls("cli").is_empty());
+        assert_eq!(repo.pages_with_tag("tags", "go")[0].title, "A2");
+        let tags: Vec<_> = repo
+            .all_tags("tags")
+            .into_iter()
+            .map(|t| t.normalized)
+            .collect();
+        assert_eq!(tags, ["go", "rust"]);
+
+        // Remove, either directly or by reloading a deleted file
+        repo.remove_file(&b);
+        assert!(tag_urls("rust").is_empty());
+        std::fs::remove_file(&a).unwrap();
+        repo.reload_file(&a);
+        assert!(repo.markdown_files.is_empty());
+        assert!(repo.all_tags("tags").is_empty());
+
+        // Other files are tracked too
+        let image = root.join("pic.png");
+        std::fs::write(&image, b"png").unwrap();
+        repo.reload_file(&image);
+        assert!(repo.other_files.pin().contains_key(&image));
+        repo.remove_file(&image);
+        assert!(repo.other_files.is_empty());
+    }
+
+    #[test]
+    fn test_url_path_collisions() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("guide")).unwrap();
+        std::fs::write(root.join("guide.md"), "# Guide").unwrap();
+        std::fs::write(root.join("guide/index.md"), "# Guide index").unwrap();
+        std::fs::write(root.join("notes.md"), "# Notes").unwrap();
+        std::fs::write(root.join("notes.markdown"), "# Notes again").unwrap();
+        std::fs::write(root.join("unique.md"), "# Unique").unwrap();
+
+        let config = Config {
+            root_dir: root.clone(),
+            markdown_extensions: vec!["md".to_string(), "markdown".to_string()],
+            ..Default::default()
+        };
+        let repo = Repo::init_from_config(&config);
+        repo.scan_all().unwrap();
+
+        assert_eq!(
+            repo.url_path_collisions(),
+            vec![
+                UrlPathCollision {
+                    url_path: "/guide/".to_string(),
+                    sources: vec![PathBuf::from("guide/index.md"), PathBuf::from("guide.md")],
+                },
+                UrlPathCollision {
+                    url_path: "/notes/".to_string(),
+                    sources: vec![PathBuf::from("notes.markdown"), PathBuf::from("notes.md")],
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_is_markdown_extension_true() {
         let extensions = vec!["md".to_string(), "markdown".to_string()];
@@ -1660,6 +2098,61 @@ mod tests {
         let tags = extract_tag_values(&val);
         assert!(tags.is_empty());
     }
+
+    #[test]
+    fn test_resolve_cover_image() {
+        let dir = Path::new("blog/posts");
+        assert_eq!(
+            resolve_cover_image("cover.jpg", dir).as_deref(),
+            Some("/blog/posts/cover.jpg")
+        );
+        assert_eq!(
+            resolve_cover_image("../images/./hero.png", dir).as_deref(),
+            Some("/blog/images/hero.png")
+        );
+        assert_eq!(
+            resolve_cover_image("/static/hero.png", dir).as_deref(),
+            Some("/static/hero.png")
+        );
+        assert_eq!(
+            resolve_cover_image("https://example.com/a.jpg", dir).as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+        assert_eq!(resolve_cover_image("  ", dir), None);
+    }
+
+    #[test]
+    fn test_with_cover_image_prefers_frontmatter() {
+        let root = Path::new("/repo");
+        let mut frontmatter = crate::markdown::SimpleMetadata::new();
+        frontmatter.insert("image".to_string(), serde_json::json!("hero.png"));
+        let info = MarkdownInfo {
+            raw_path: PathBuf::from("/repo/notes/post.md"),
+            url_path: "/notes/post/".to_string(),
+            url: "/notes/post/".to_string(),
+            created: 0,
+            modified: 0,
+            frontmatter: Some(frontmatter),
+            word_count: 0,
+            date: None,
+            created_date: None,
+            updated_date: None,
+            relationships: Vec::new(),
+            cover_image: None,
+        };
+
+        let with_fm = info
+            .clone()
+            .with_cover_image(Some("inline.jpg".to_string()), root);
+        assert_eq!(with_fm.cover_image.as_deref(), Some("/notes/hero.png"));
+
+        let without_fm = MarkdownInfo {
+            frontmatter: None,
+            ..info
+        }
+        .with_cover_image(Some("inline.jpg".to_string()), root);
+        assert_eq!(without_fm.cover_image.as_deref(), Some("/notes/inline.jpg"));
+    }
 }
 
 #[cfg(test)]
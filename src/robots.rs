@@ -0,0 +1,202 @@
+//! Search engine directives: per-page `<meta name="robots">` and the site's
+//! `robots.txt`.
+//!
+//! A page's directives come from its frontmatter, falling back to the
+//! site-wide `robots` setting:
+//!
+//! ```yaml
+//! robots: noindex, nofollow   # replaces the site default
+//! noindex: true               # or toggle single directives
+//! nofollow: false
+//! ```
+//!
+//! `robots.txt` is the `robots_txt` setting verbatim, or an allow-all default
+//! that points crawlers at `sitemap.xml` when the site has one.
+
+use serde_json::Value;
+
+use crate::markdown::SimpleMetadata;
+
+/// File name of the robots exclusion file, at the site root.
+pub const ROBOTS_TXT: &str = "robots.txt";
+
+/// File name of the sitemap a generated `robots.txt` refers to.
+pub const SITEMAP_FILE: &str = "sitemap.xml";
+
+/// Contents of `robots.txt`: `custom` as-is, else a default allowing all
+/// crawlers. The default names the sitemap when the site has one and
+/// `base_url` is set, since the `Sitemap:` line needs an absolute URL.
+pub fn robots_txt(custom: Option<&str>, base_url: Option<&str>, has_sitemap: bool) -> String {
+    if let Some(custom) = custom {
+        return custom.to_string();
+    }
+    let mut txt = String::from("User-agent: *\nAllow: /\n");
+    if has_sitemap && let Some(base_url) = base_url {
+        txt.push_str(&format!(
+            "\nSitemap: {}/{SITEMAP_FILE}\n",
+            base_url.trim_end_matches('/')
+        ));
+    }
+    txt
+}
+
+/// Parsed `robots` directives, in the order written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Robots {
+    directives: Vec<String>,
+}
+
+impl Robots {
+    /// Parses a comma-separated directive list such as `noindex, nofollow`.
+    pub fn parse(content: &str) -> Self {
+        let mut robots = Self::default();
+        for directive in content.split(',') {
+            robots.add(directive);
+        }
+        robots
+    }
+
+    /// Directives for a page: frontmatter `robots` (a string or list) replaces
+    /// `default`, then boolean `noindex`/`nofollow` fields add or remove
+    /// their directive.
+    pub fn for_page(frontmatter: &SimpleMetadata, default: Option<&str>) -> Self {
+        let mut robots = match frontmatter.get("robots") {
+            Some(Value::String(content)) => Self::parse(content),
+            Some(Value::Array(items)) => Self::parse(
+                &items
+                    .iter()
+                    .filter_map(|item| item.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => default.map(Self::parse).unwrap_or_default(),
+        };
+        for directive in ["noindex", "nofollow"] {
+            match frontmatter.get(directive).and_then(frontmatter_flag) {
+                Some(true) => robots.add(directive),
+                Some(false) => robots.remove(directive),
+                None => {}
+            }
+        }
+        robots
+    }
+
+    fn add(&mut self, directive: &str) {
+        let directive = directive.trim().to_ascii_lowercase();
+        if !directive.is_empty() && !self.directives.contains(&directive) {
+            self.directives.push(directive);
+        }
+    }
+
+    fn remove(&mut self, directive: &str) {
+        self.directives.retain(|d| d != directive);
+    }
+
+    /// True when search engines are asked not to index the page.
+    pub fn noindex(&self) -> bool {
+        self.has("noindex") || self.has("none")
+    }
+
+    /// True when search engines are asked not to follow the page's links.
+    pub fn nofollow(&self) -> bool {
+        self.has("nofollow") || self.has("none")
+    }
+
+    fn has(&self, directive: &str) -> bool {
+        self.directives.iter().any(|d| d == directive)
+    }
+
+    /// The `<meta name="robots">` content, or `None` when there are no
+    /// directives (so the tag is omitted).
+    pub fn content(&self) -> Option<String> {
+        (!self.directives.is_empty()).then(|| self.directives.join(", "))
+    }
+}
+
+/// Reads a boolean frontmatter field, accepting `true`/`false` and their
+/// quoted or `yes`/`no` spellings.
+fn frontmatter_flag(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(flag) => Some(*flag),
+        Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" => Some(true),
+            "false" | "no" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn frontmatter(fields: &[(&str, Value)]) -> SimpleMetadata {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_robots_txt_default_and_custom() {
+        assert_eq!(robots_txt(None, None, true), "User-agent: *\nAllow: /\n");
+        assert_eq!(
+            robots_txt(None, Some("https://example.com/notes/"), true),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/notes/sitemap.xml\n"
+        );
+        // No sitemap to point at
+        assert_eq!(
+            robots_txt(None, Some("https://example.com"), false),
+            "User-agent: *\nAllow: /\n"
+        );
+        assert_eq!(
+            robots_txt(Some("User-agent: *\nDisallow: /drafts/\n"), None, false),
+            "User-agent: *\nDisallow: /drafts/\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_normalizes_directives() {
+        let robots = Robots::parse(" NoIndex ,nofollow,, noindex");
+        assert_eq!(robots.content().as_deref(), Some("noindex, nofollow"));
+        assert!(robots.noindex());
+        assert!(robots.nofollow());
+        assert_eq!(Robots::parse("").content(), None);
+    }
+
+    #[test]
+    fn test_frontmatter_replaces_default() {
+        let fm = frontmatter(&[("robots", json!("noindex"))]);
+        let robots = Robots::for_page(&fm, Some("nofollow"));
+        assert_eq!(robots.content().as_deref(), Some("noindex"));
+
+        let fm = frontmatter(&[("robots", json!(["noindex", "noarchive"]))]);
+        let robots = Robots::for_page(&fm, None);
+        assert_eq!(robots.content().as_deref(), Some("noindex, noarchive"));
+    }
+
+    #[test]
+    fn test_boolean_fields_toggle_directives() {
+        let fm = frontmatter(&[("noindex", json!(true)), ("nofollow", json!("yes"))]);
+        let robots = Robots::for_page(&fm, None);
+        assert_eq!(robots.content().as_deref(), Some("noindex, nofollow"));
+
+        // A page can opt back in to indexing on a noindex site
+        let fm = frontmatter(&[("noindex", json!(false))]);
+        let robots = Robots::for_page(&fm, Some("noindex, nofollow"));
+        assert_eq!(robots.content().as_deref(), Some("nofollow"));
+        assert!(!robots.noindex());
+    }
+
+    #[test]
+    fn test_default_applies_without_frontmatter() {
+        let robots = Robots::for_page(&SimpleMetadata::new(), Some("none"));
+        assert!(robots.noindex() && robots.nofollow());
+        assert_eq!(
+            Robots::for_page(&SimpleMetadata::new(), None).content(),
+            None
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Allowlist sanitizing of rendered page HTML (`sanitize_html`).
+//!
+//! Markdown passes raw HTML through, so a page from an untrusted author can
+//! carry `<script>` tags, `onclick` handlers or `javascript:` links. With
+//! `sanitize_html` on, every rendered page body goes through
+//! [`HtmlSanitizer`], which keeps only allowlisted tags and attributes and
+//! drops URLs with unsafe schemes.
+//!
+//! The allowlist covers the markup mbr's own rendering emits: mermaid and
+//! code blocks, video and audio players with their tracks, YouTube and PDF
+//! embeds, figures, galleries, link cards, task lists and `data-*`
+//! attributes such as pagefind's. `sanitize_allow_tags` and
+//! `sanitize_allow_attributes` extend it for a site's own raw HTML.
+//!
+//! In server mode and static builds the sanitizer runs as the first
+//! post-render hook, ahead of [`crate::post_render::ExternalLinks`] and any
+//! library hooks, so markup added after rendering is never stripped. The
+//! `-o` stdout render and QuickLook previews have no hooks and call
+//! [`sanitize_for_config`] on the body instead. GitHub gist embeds load
+//! through a `<script>` and are removed.
+
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+use crate::config::Config;
+use crate::post_render::{PostRenderHook, PostRenderPage};
+
+/// Tags kept on top of ammonia's defaults (which cover text, lists, tables,
+/// `figure`, `details` and the like).
+const EXTRA_TAGS: &[&str] = &[
+    "article",
+    "audio",
+    "button",
+    "iframe",
+    "input",
+    "mbr-video-extras",
+    "object",
+    "picture",
+    "section",
+    "source",
+    "track",
+    "video",
+    // Link card icons
+    "svg",
+    "path",
+];
+
+/// Attributes kept on every tag. `data-*` and `aria-*` are kept by prefix.
+const GENERIC_ATTRIBUTES: &[&str] = &[
+    "class", "dir", "hidden", "id", "lang", "role", "style", "title",
+];
+
+/// Attributes kept on particular tags, beyond ammonia's defaults.
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["download", "rel", "target"]),
+    ("img", &["decoding", "loading", "sizes", "srcset"]),
+    ("ol", &["reversed"]),
+    ("input", &["checked", "disabled", "type"]),
+    ("button", &["type"]),
+    ("source", &["media", "sizes", "src", "srcset", "type"]),
+    (
+        "video",
+        &[
+            "autoplay",
+            "controls",
+            "height",
+            "loop",
+            "muted",
+            "playsinline",
+            "poster",
+            "preload",
+            "src",
+            "width",
+        ],
+    ),
+    ("audio", &["controls", "loop", "muted", "preload", "src"]),
+    (
+        "track",
+        &[
+            "default", "kind", "label", "language", "src", "srclang", "type",
+        ],
+    ),
+    (
+        "iframe",
+        &[
+            "allow",
+            "allowfullscreen",
+            "frameborder",
+            "height",
+            "loading",
+            "referrerpolicy",
+            "sandbox",
+            "src",
+            "width",
+        ],
+    ),
+    ("object", &["data", "height", "type", "width"]),
+    ("mbr-video-extras", &["end", "src", "start"]),
+    (
+        "svg",
+        &[
+            "fill",
+            "height",
+            "stroke",
+            "stroke-linecap",
+            "stroke-linejoin",
+            "stroke-width",
+            "viewBox",
+            "width",
+        ],
+    ),
+    ("path", &["d", "fill", "stroke"]),
+];
+
+/// CSS properties kept in `style` attributes (table column alignment and
+/// media sizing); other declarations are dropped.
+const STYLE_PROPERTIES: &[&str] = &["aspect-ratio", "height", "max-width", "text-align", "width"];
+
+/// Tags removed along with their content. Never allowed, even if listed in
+/// `sanitize_allow_tags`.
+pub const FORBIDDEN_TAGS: &[&str] = &["script", "style"];
+
+/// Strips everything but allowlisted markup from rendered page HTML.
+///
+/// The ammonia builder is configured once and shared by clones.
+#[derive(Debug, Clone)]
+pub struct HtmlSanitizer {
+    /// Tags allowed on top of the built-in list (`sanitize_allow_tags`).
+    extra_tags: Vec<String>,
+    /// Attributes allowed on every tag on top of the built-in list
+    /// (`sanitize_allow_attributes`).
+    extra_attributes: Vec<String>,
+    builder: Arc<ammonia::Builder<'static>>,
+}
+
+impl HtmlSanitizer {
+    /// A sanitizer that also allows `extra_tags` (except [`FORBIDDEN_TAGS`])
+    /// and, on every tag, `extra_attributes`.
+    pub fn new(extra_tags: Vec<String>, extra_attributes: Vec<String>) -> Self {
+        let mut builder = ammonia::Builder::default();
+        builder
+            // Leave links as written; `ExternalLinks` adds `rel` afterwards
+            .link_rel(None)
+            .add_tags(EXTRA_TAGS)
+            .add_tags(
+                extra_tags
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|tag| !FORBIDDEN_TAGS.contains(tag))
+                    .map(static_name),
+            )
+            .add_generic_attributes(GENERIC_ATTRIBUTES)
+            .add_generic_attributes(extra_attributes.iter().map(|name| static_name(name)))
+            .generic_attribute_prefixes(HashSet::from(["data-", "aria-"]))
+            .filter_style_properties(STYLE_PROPERTIES.iter().copied().collect());
+        for (tag, attributes) in TAG_ATTRIBUTES {
+            builder.add_tag_attributes(tag, attributes.iter());
+        }
+        Self {
+            extra_tags,
+            extra_attributes,
+            builder: Arc::new(builder),
+        }
+    }
+
+    /// The sanitizer `config` asks for, or `None` when `sanitize_html` is off.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.sanitize_html.then(|| {
+            Self::new(
+                config.sanitize_allow_tags.clone(),
+                config.sanitize_allow_attributes.clone(),
+            )
+        })
+    }
+
+    /// Returns `html` with disallowed tags, attributes and URLs removed.
+    pub fn clean(&self, html: &str) -> String {
+        self.builder.clean(html).to_string()
+    }
+}
+
+impl Default for HtmlSanitizer {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
+impl PartialEq for HtmlSanitizer {
+    fn eq(&self, other: &Self) -> bool {
+        self.extra_tags == other.extra_tags && self.extra_attributes == other.extra_attributes
+    }
+}
+
+impl Eq for HtmlSanitizer {}
+
+/// `name` as a `&'static str`, which a stored `ammonia::Builder` needs.
+/// Each distinct name from the config is leaked once.
+fn static_name(name: &str) -> &'static str {
+    static NAMES: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Mutex::default);
+    let mut names = NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.into());
+    names.insert(name);
+    name
+}
+
+impl PostRenderHook for HtmlSanitizer {
+    fn apply(&self, html: String, _page: &PostRenderPage<'_>) -> String {
+        self.clean(&html)
+    }
+}
+
+/// Cleans a rendered body when `config` turns `sanitize_html` on, for the
+/// render paths that don't run post-render hooks.
+pub fn sanitize_for_config(config: &Config, html: String) -> String {
+    match HtmlSanitizer::from_config(config) {
+        Some(sanitizer) => sanitizer.clean(&html),
+        None => html,
+    }
+}
+
+/// True if `name` can be listed in `sanitize_allow_tags` or
+/// `sanitize_allow_attributes`: letters, digits and `-` (plus `:` and `_`
+/// for attributes), starting with a letter.
+pub fn is_valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(html: &str) -> String {
+        HtmlSanitizer::default().clean(html)
+    }
+
+    #[test]
+    fn test_strips_scripts_and_handlers() {
+        assert_eq!(
+            clean(r#"<p onclick="steal()">Hi<script>steal()</script></p>"#),
+            "<p>Hi</p>"
+        );
+        assert_eq!(
+            clean(r#"<a href="javascript:steal()">x</a><img src="x.png" onerror="steal()">"#),
+            r#"<a>x</a><img src="x.png">"#
+        );
+        assert_eq!(
+            clean(r#"<iframe src="javascript:steal()"></iframe>"#),
+            "<iframe></iframe>"
+        );
+        assert_eq!(
+            clean(
+                r#"<table><tbody><tr><td style="text-align: center; background: url(x)">1</td></tr></tbody></table>"#
+            ),
+            r#"<table><tbody><tr><td style="text-align:center">1</td></tr></tbody></table>"#
+        );
+    }
+
+    #[test]
+    fn test_allowlist_extension() {
+        let html = r#"<kbd>K</kbd><marquee behavior="alternate">Hi</marquee>"#;
+        assert_eq!(clean(html), "<kbd>K</kbd>Hi");
+
+        let sanitizer = HtmlSanitizer::new(
+            vec!["marquee".to_string(), "script".to_string()],
+            vec!["behavior".to_string()],
+        );
+        assert_eq!(
+            sanitizer.clean(&format!("{html}<script>x()</script>")),
+            r#"<kbd>K</kbd><marquee behavior="alternate">Hi</marquee>"#
+        );
+    }
+
+    #[test]
+    fn test_sanitize_for_config() {
+        let html = r#"<p>Hi<script>steal()</script></p>"#.to_string();
+        assert_eq!(sanitize_for_config(&Config::default(), html.clone()), html);
+        let config = Config {
+            sanitize_html: true,
+            ..Default::default()
+        };
+        assert_eq!(sanitize_for_config(&config, html), "<p>Hi</p>");
+    }
+
+    #[test]
+    fn test_is_valid_name() {
+        for name in ["marquee", "my-widget", "xlink:href", "data_x"] {
+            assert!(is_valid_name(name), "{name}");
+        }
+        for name in ["", "1x", "on click", "<b>"] {
+            assert!(!is_valid_name(name), "{name}");
+        }
+    }
+}
@@ -346,6 +346,8 @@ impl SearchEngine {
                 .markdown_files
                 .pin()
                 .iter()
+                // Redirect pages only point elsewhere
+                .filter(|(_, info)| !info.is_redirect())
                 .filter(|(_, info)| {
                     self.matches_folder_filter(info, &query.folder, &query.folder_scope)
                 })
@@ -445,7 +447,7 @@ impl SearchEngine {
         // For facet-only queries, check if any facet matches this tag source+value
         if pattern.is_none() {
             let matches_facet = parsed.facets.iter().any(|(field, value)| {
-                field.to_lowercase() == source.field.to_lowercase()
+                source.all_fields().any(|f| f.eq_ignore_ascii_case(field))
                     && tag_info
                         .display
                         .to_lowercase()
@@ -616,6 +618,8 @@ impl SearchEngine {
                 .markdown_files
                 .pin()
                 .iter()
+                // Redirect pages only point elsewhere
+                .filter(|(_, info)| !info.is_redirect())
                 .filter(|(_, info)| {
                     self.matches_folder_filter(info, &query.folder, &query.folder_scope)
                 })
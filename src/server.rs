@@ -1,6 +1,6 @@
 use axum::{
     Router,
-    body::Body,
+    body::{Body, Bytes},
     extract::{self, ConnectInfo, DefaultBodyLimit, OriginalUri, State, ws::WebSocketUpgrade},
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Json, Response},
@@ -11,17 +11,26 @@ use percent_encoding::percent_decode_str;
 use std::{net::SocketAddr, path::Path, path::PathBuf, sync::Arc};
 use tokio::sync::broadcast;
 
+use crate::access_log::{AccessLog, AccessLogFormat};
 use crate::config::{RelationType, SortField, TagSource};
+use crate::content_type::MimeOverrides;
+use crate::csp::ContentSecurityPolicy;
 use crate::embedded_katex;
 use crate::embedded_pico;
 use crate::errors::{MbrError, ServerError};
+use crate::favicon::{self, SiteBranding};
+use crate::image_cache::{ImageCache, ImageCacheKey};
 use crate::link_grep::InboundLinkCache;
 use crate::link_index::{LinkCache, resolve_outbound_links};
 use crate::link_transform::LinkTransformConfig;
 use crate::oembed_cache::OembedCache;
-use crate::page_context::{self, ModeFlags, PageChrome, UrlMode};
+use crate::page_assets::PageAssets;
+use crate::page_context::{self, ModeFlags, PageChrome, SearchUi, SiteLanguage, UrlMode};
 use crate::path_resolver::{PathResolverConfig, ResolvedPath, resolve_request_path};
+use crate::post_render::{PostRenderHook, PostRenderPage};
+use crate::redirect;
 use crate::repo::MarkdownInfo;
+use crate::robots::{self, Robots};
 use crate::search::{SearchEngine, SearchQuery, search_other_files};
 use crate::sorting::sort_files;
 use crate::templates;
@@ -30,10 +39,20 @@ use crate::video_metadata_cache::VideoMetadataCache;
 #[cfg(feature = "media-metadata")]
 use crate::video_transcode_cache::HlsCache;
 use crate::{markdown, repo::Repo};
+// Moved to `page_nav`; re-exported so existing `server::` paths keep working.
+pub use crate::page_nav::{
+    Breadcrumb, generate_breadcrumbs, get_current_dir_name, get_parent_path, markdown_file_to_json,
+};
 use tower::ServiceExt;
-use tower_http::{compression::CompressionLayer, services::ServeFile, trace::TraceLayer};
+use tower_http::{
+    compression::CompressionLayer, services::ServeFile, set_header::SetResponseHeaderLayer,
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Default cache size for generated thumbnails and icons: 64 MB.
+const DEFAULT_IMAGE_CACHE_SIZE: usize = 64 * 1024 * 1024;
+
 /// Default HLS cache size: 200 MB.
 #[cfg(feature = "media-metadata")]
 const DEFAULT_HLS_CACHE_SIZE: usize = 200 * 1024 * 1024;
@@ -78,12 +97,6 @@ fn claim_inflight(
 /// Default outbound link cache size: 2 MB.
 const DEFAULT_LINK_CACHE_SIZE: usize = 2 * 1024 * 1024;
 
-/// Default inbound link cache size: 1 MB.
-const DEFAULT_INBOUND_LINK_CACHE_SIZE: usize = 1024 * 1024;
-
-/// TTL for inbound link cache entries in seconds.
-const INBOUND_LINK_CACHE_TTL_SECS: u64 = 60;
-
 /// Type of media for the viewer page.
 ///
 /// Used to route requests to the appropriate media viewer template
@@ -368,6 +381,33 @@ fn resolve_media_source_file(
     })
 }
 
+/// Parse a single `bytes=` range against a body of `total` bytes.
+///
+/// Returns inclusive `(start, end)` offsets, or `None` for a missing,
+/// multi-part or unsatisfiable range (callers then serve the full body).
+#[cfg(feature = "media-metadata")]
+fn parse_single_byte_range(value: &str, total: usize) -> Option<(usize, usize)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || total == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = if start.is_empty() {
+        // Suffix range: last N bytes
+        let len: usize = end.parse().ok()?;
+        (total.checked_sub(len.min(total))?, total - 1)
+    } else {
+        let start: usize = start.parse().ok()?;
+        let end = if end.is_empty() {
+            total - 1
+        } else {
+            end.parse::<usize>().ok()?.min(total - 1)
+        };
+        (start, end)
+    };
+    (start <= end && start < total).then_some((start, end))
+}
+
 pub struct Server {
     pub router: Router,
     pub port: u16,
@@ -401,13 +441,33 @@ pub struct ServerConfig {
     pub base_dir: std::path::PathBuf,
     pub static_folder: String,
     pub markdown_extensions: Vec<String>,
+    /// Renderers for non-markdown documents, keyed by extension.
+    pub renderers: std::collections::HashMap<String, markdown::RendererKind>,
     pub ignore_dirs: Vec<String>,
     pub ignore_globs: Vec<String>,
+    /// Canonical site host for matching full-URL backlinks.
+    pub site_host: Option<String>,
+    /// Skip files matched by the root `.gitignore` when scanning.
+    pub respect_gitignore: bool,
+    /// Scan folders reached through symlinks.
+    pub follow_symlinks: bool,
     pub watcher_ignore_dirs: Vec<String>,
     pub index_file: String,
     pub oembed_timeout_ms: u64,
+    /// How bare URLs whose oembed fetch failed are rendered.
+    pub oembed_fallback: crate::oembed::OembedFallback,
     pub oembed_cache_size: usize,
+    /// Maximum oembed fetches in flight at once.
+    pub oembed_concurrency: usize,
+    /// Seconds an oembed result stays cached (0 = until evicted).
+    pub oembed_cache_ttl_secs: u64,
+    /// Maximum size in bytes of the backlink scan cache (0 = disabled).
+    pub inbound_cache_size: usize,
+    /// Seconds cached backlinks stay fresh.
+    pub inbound_cache_ttl_secs: u64,
     pub template_folder: Option<std::path::PathBuf>,
+    /// Folder of data files for templates and the `data` shortcode.
+    pub data_folder: Option<String>,
     pub sort: Vec<SortField>,
     pub gui_mode: bool,
     pub theme: String,
@@ -420,20 +480,104 @@ pub struct ServerConfig {
     pub tag_sources: Vec<TagSource>,
     pub sidebar_style: String,
     pub sidebar_max_items: usize,
+    /// Table of contents heading levels and numbering.
+    pub toc: crate::markdown::TocOptions,
+    /// Markdown syntax extensions enabled in the parser.
+    pub markdown_options: crate::markdown::MarkdownOptions,
+    /// Sticky header height (CSS length) used to offset anchor jumps.
+    pub header_height: Option<String>,
+    /// Animate jumps to in-page anchors.
+    pub smooth_scroll: bool,
+    /// Directory listing layout: "list", "cards", or "grid".
+    pub listing_layout: String,
+    /// Show card/grid cover images as thumbnails.
+    pub listing_thumbnails: bool,
+    /// Placeholder text for the search box.
+    pub search_placeholder: String,
+    /// Maximum number of search results shown.
+    pub search_result_limit: usize,
+    /// Default `<html lang>`; pages override it with frontmatter `lang`.
+    pub site_lang: String,
+    /// Default `<html dir>`; pages override it with frontmatter `dir`.
+    pub site_dir: String,
+    /// Site-wide prev/next sequence: "nav" or a markdown file such as "SUMMARY.md".
+    pub reading_order: Option<String>,
     pub title_prefix: String,
     pub title_suffix: String,
+    /// Remove a first H1 that repeats the frontmatter title.
+    pub dedupe_title_h1: bool,
+    /// Public URL of the site; links to its host aren't treated as external.
+    pub base_url: Option<String>,
+    /// Open external links in a new tab.
+    pub external_links_new_tab: bool,
+    /// Sanitizer run over each rendered page body, when `sanitize_html` is on.
+    pub sanitize_html: Option<crate::sanitize::HtmlSanitizer>,
+    /// Transforms run over each rendered page body, after the built-in ones
+    /// (see [`crate::post_render`]).
+    pub post_render_hooks: Vec<Arc<dyn PostRenderHook>>,
+    /// Lazy-load content images after the first.
+    pub lazy_images: bool,
+    /// Wrap YouTube/Vimeo players in privacy-friendly click-to-play facades.
+    pub privacy_embeds: bool,
+    /// `mermaid.initialize()` options from `mermaid_theme` and
+    /// `mermaid_config`, as a JSON object.
+    pub mermaid_config: String,
+    /// Largest width (CSS length) for content images, videos and iframes.
+    pub media_max_width: Option<String>,
+    /// Markdown files at least this many bytes are streamed (0 = never).
+    pub stream_render_threshold: u64,
+    /// Maximum number of pages rendered at once.
+    pub server_render_concurrency: usize,
+    /// Server-cache build to serve unchanged pages from.
+    pub server_cache_dir: Option<PathBuf>,
+    /// Markdown files larger than this aren't rendered (0 = no limit).
+    pub max_render_bytes: u64,
+    /// Custom favicon, web app manifest and theme color.
+    pub branding: SiteBranding,
+    /// Default `<meta name="robots">` content.
+    pub robots: Option<String>,
+    /// Custom `robots.txt` contents.
+    pub robots_txt: Option<String>,
+    /// Content-Security-Policy header and nonce, when configured.
+    pub csp: Option<ContentSecurityPolicy>,
+    /// Default format for the `format_date` template filter.
+    pub date_format: String,
     /// Highlight blocks beginning with an incomplete marker (TK/TODO/FIXME/XXX).
     pub mark_incomplete: bool,
     /// Marker strings used by the incomplete-block highlighter.
     pub incomplete_markers: Vec<String>,
+    /// Enable task list checkboxes with localStorage-backed state.
+    pub interactive_tasklists: bool,
+    /// Highlight the TOC entry for the section in view.
+    pub scroll_spy: bool,
+    /// Template for each page's "edit this page" URL (`{path}` placeholder).
+    pub edit_url_template: Option<String>,
     /// Enable the in-browser markdown editing endpoints.
     pub edit_enabled: bool,
     /// Require the editing token even for loopback callers.
     pub edit_require_token_on_loopback: bool,
     /// Argon2 PHC hash of the shared editing token (server-side only).
     pub edit_token_hash: Option<String>,
+    /// Content-Type overrides for static files, keyed by extension.
+    pub mime_overrides: std::collections::HashMap<String, String>,
+    /// File to append access log lines to (None = log via tracing).
+    pub access_log: Option<PathBuf>,
+    /// Line format for the access log.
+    pub access_log_format: AccessLogFormat,
     #[cfg(feature = "media-metadata")]
     pub transcode_enabled: bool,
+    /// Forced H.264 encoder for video transcoding (None = auto-detect).
+    #[cfg(feature = "media-metadata")]
+    pub video_encoder: Option<String>,
+    /// Directory for intermediate transcode output (None = system temp dir).
+    #[cfg(feature = "media-metadata")]
+    pub transcode_tmp_dir: Option<PathBuf>,
+    /// Codec for on-demand lossless audio transcoding (None = disabled).
+    #[cfg(feature = "media-metadata")]
+    pub audio_transcode: Option<crate::audio::AudioTranscodeFormat>,
+    /// Target bitrate in kbps for audio transcoding.
+    #[cfg(feature = "media-metadata")]
+    pub audio_transcode_bitrate_kbps: u32,
 }
 
 impl ServerConfig {
@@ -450,6 +594,14 @@ impl ServerConfig {
         self.log_filter = filter.map(|s| s.to_string());
         self
     }
+
+    /// Add a transform run over each rendered page body, after the built-in
+    /// ones and any added earlier.
+    #[must_use]
+    pub fn with_post_render_hook(mut self, hook: Arc<dyn PostRenderHook>) -> Self {
+        self.post_render_hooks.push(hook);
+        self
+    }
 }
 
 impl From<&crate::config::Config> for ServerConfig {
@@ -460,13 +612,23 @@ impl From<&crate::config::Config> for ServerConfig {
             base_dir: config.root_dir.clone(),
             static_folder: config.static_folder.clone(),
             markdown_extensions: config.markdown_extensions.clone(),
+            renderers: config.renderers.clone(),
             ignore_dirs: config.ignore_dirs.clone(),
             ignore_globs: config.ignore_globs.clone(),
+            site_host: config.site_host.clone(),
+            respect_gitignore: config.respect_gitignore,
+            follow_symlinks: config.follow_symlinks,
             watcher_ignore_dirs: config.watcher_ignore_dirs.clone(),
             index_file: config.index_file.clone(),
             oembed_timeout_ms: config.oembed_timeout_ms,
+            oembed_fallback: config.oembed_fallback,
             oembed_cache_size: config.oembed_cache_size,
+            oembed_concurrency: config.oembed_concurrency,
+            oembed_cache_ttl_secs: config.oembed_cache_ttl_secs,
+            inbound_cache_size: config.inbound_cache_size,
+            inbound_cache_ttl_secs: config.inbound_cache_ttl_secs,
             template_folder: config.template_folder.clone(),
+            data_folder: config.data_folder.clone(),
             sort: config.sort.clone(),
             gui_mode: false, // Default to server mode
             theme: config.theme.clone(),
@@ -477,20 +639,76 @@ impl From<&crate::config::Config> for ServerConfig {
             tag_sources: config.tag_sources.clone(),
             sidebar_style: config.sidebar_style.clone(),
             sidebar_max_items: config.sidebar_max_items,
+            toc: config.toc_options(),
+            markdown_options: config.markdown_options,
+            header_height: config.header_height.clone(),
+            smooth_scroll: config.smooth_scroll,
+            listing_layout: config.listing_layout.clone(),
+            listing_thumbnails: config.listing_thumbnails,
+            search_placeholder: config.search_placeholder.clone(),
+            search_result_limit: config.search_result_limit,
+            site_lang: config.site_lang.clone(),
+            site_dir: config.site_dir.clone(),
+            reading_order: config.reading_order.clone(),
             title_prefix: config.title_prefix.clone(),
             title_suffix: config.title_suffix.clone(),
+            dedupe_title_h1: config.dedupe_title_h1,
+            base_url: config.base_url.clone(),
+            external_links_new_tab: config.external_links_new_tab,
+            sanitize_html: crate::sanitize::HtmlSanitizer::from_config(config),
+            post_render_hooks: Vec::new(),
+            lazy_images: config.lazy_images,
+            privacy_embeds: config.privacy_embeds,
+            mermaid_config: config.mermaid_init_json(),
+            media_max_width: config.media_max_width.clone(),
+            stream_render_threshold: config.stream_render_threshold,
+            server_render_concurrency: config.server_render_concurrency,
+            server_cache_dir: config
+                .server_cache_dir
+                .as_ref()
+                .map(|dir| config.root_dir.join(dir)),
+            max_render_bytes: config.max_render_bytes,
+            branding: SiteBranding::from_config(config),
+            robots: config.robots.clone(),
+            robots_txt: config.robots_txt.clone(),
+            csp: ContentSecurityPolicy::from_config(config),
+            date_format: config.date_format.clone(),
             // Server/GUI default: on unless config overrides.
             mark_incomplete: config.mark_incomplete.unwrap_or(true),
             incomplete_markers: config.incomplete_markers.clone(),
+            interactive_tasklists: config.interactive_tasklists.unwrap_or(true),
+            scroll_spy: config.scroll_spy,
+            edit_url_template: config.edit_url_template.clone(),
             edit_enabled: config.edit_enabled,
             edit_require_token_on_loopback: config.edit_require_token_on_loopback,
             edit_token_hash: config.edit_token_hash.clone(),
+            mime_overrides: config.mime_overrides.clone(),
+            access_log: config.access_log.clone(),
+            access_log_format: AccessLogFormat::from_name(&config.access_log_format)
+                .unwrap_or_default(),
             #[cfg(feature = "media-metadata")]
             transcode_enabled: config.transcode,
+            #[cfg(feature = "media-metadata")]
+            video_encoder: config.video_encoder.clone(),
+            #[cfg(feature = "media-metadata")]
+            transcode_tmp_dir: config.transcode_tmp_dir.clone(),
+            #[cfg(feature = "media-metadata")]
+            audio_transcode: config
+                .audio_transcode
+                .as_deref()
+                .and_then(crate::audio::AudioTranscodeFormat::from_name),
+            #[cfg(feature = "media-metadata")]
+            audio_transcode_bitrate_kbps: config.audio_transcode_bitrate_kbps,
         }
     }
 }
 
+/// State for [`Server::init_static`].
+struct BuiltSite {
+    root_dir: PathBuf,
+    mime_overrides: MimeOverrides,
+}
+
 #[derive(Clone)]
 pub struct ServerState {
     pub base_dir: std::path::PathBuf,
@@ -498,12 +716,18 @@ pub struct ServerState {
     pub canonical_base_dir: Option<std::path::PathBuf>,
     pub static_folder: String,
     pub markdown_extensions: Vec<String>,
+    /// Renderers for non-markdown documents, keyed by extension.
+    pub renderers: std::collections::HashMap<String, markdown::RendererKind>,
     pub ignore_dirs: Vec<String>,
     pub ignore_globs: Vec<String>,
+    /// Canonical site host for matching full-URL backlinks.
+    pub site_host: Option<String>,
     pub index_file: String,
     pub templates: crate::templates::Templates,
     pub repo: Arc<Repo>,
     pub oembed_timeout_ms: u64,
+    /// How bare URLs whose oembed fetch failed are rendered
+    pub oembed_fallback: crate::oembed::OembedFallback,
     pub file_change_tx: Option<broadcast::Sender<crate::watcher::FileChangeEvent>>,
     /// Optional template folder that overrides default .mbr/ and compiled defaults
     pub template_folder: Option<std::path::PathBuf>,
@@ -515,13 +739,27 @@ pub struct ServerState {
     pub theme: String,
     /// Cache for OEmbed page metadata to avoid redundant network requests
     pub oembed_cache: Arc<OembedCache>,
+    /// Cache for generated gallery thumbnails and site icons
+    pub image_cache: Arc<ImageCache>,
     /// Cache for dynamically generated video metadata (covers, chapters, captions)
     #[cfg(feature = "media-metadata")]
     pub video_metadata_cache: Arc<VideoMetadataCache>,
     /// Whether video transcoding is enabled
     #[cfg(feature = "media-metadata")]
     pub transcode_enabled: bool,
-    /// Cache for HLS playlists and transcoded segments
+    /// Forced H.264 encoder for video transcoding (None = auto-detect)
+    #[cfg(feature = "media-metadata")]
+    pub video_encoder: Option<String>,
+    /// Directory for intermediate transcode output (None = system temp dir)
+    #[cfg(feature = "media-metadata")]
+    pub transcode_tmp_dir: Option<PathBuf>,
+    /// Codec for on-demand lossless audio transcoding (None = disabled)
+    #[cfg(feature = "media-metadata")]
+    pub audio_transcode: Option<crate::audio::AudioTranscodeFormat>,
+    /// Target bitrate in kbps for audio transcoding
+    #[cfg(feature = "media-metadata")]
+    pub audio_transcode_bitrate_kbps: u32,
+    /// Cache for HLS playlists, transcoded segments and transcoded audio
     #[cfg(feature = "media-metadata")]
     pub hls_cache: Arc<HlsCache>,
     /// Single-flight guard for video metadata extraction: maps a metadata cache
@@ -553,20 +791,91 @@ pub struct ServerState {
     pub sidebar_style: String,
     /// Maximum items per section in sidebar navigation
     pub sidebar_max_items: usize,
+    /// Table of contents heading levels and numbering.
+    pub toc: crate::markdown::TocOptions,
+    /// Markdown syntax extensions enabled in the parser.
+    pub markdown_options: crate::markdown::MarkdownOptions,
+    /// Sticky header height (CSS length) used to offset anchor jumps.
+    pub header_height: Option<String>,
+    /// Animate jumps to in-page anchors.
+    pub smooth_scroll: bool,
+    /// Directory listing layout: "list", "cards", or "grid".
+    pub listing_layout: String,
+    /// Show card/grid cover images as thumbnails.
+    pub listing_thumbnails: bool,
+    /// Placeholder text for the search box.
+    pub search_placeholder: String,
+    /// Maximum number of search results shown.
+    pub search_result_limit: usize,
+    /// Default `<html lang>`; pages override it with frontmatter `lang`.
+    pub site_lang: String,
+    /// Default `<html dir>`; pages override it with frontmatter `dir`.
+    pub site_dir: String,
     /// Text to prepend to all page titles
     pub title_prefix: String,
     /// Text to append to all page titles
     pub title_suffix: String,
+    /// Remove a first H1 that repeats the frontmatter title.
+    pub dedupe_title_h1: bool,
+    /// Public URL of the site; links to its host aren't treated as external.
+    pub base_url: Option<String>,
+    /// Transforms run over each rendered page body: the built-in ones from
+    /// config, then any added through [`ServerConfig::post_render_hooks`].
+    pub post_render_hooks: Vec<Arc<dyn PostRenderHook>>,
+    /// True when `sanitize_html` is on; frontmatter scripts and inline CSS
+    /// are dropped from the page head.
+    pub sanitize_html: bool,
+    /// Lazy-load content images after the first.
+    pub lazy_images: bool,
+    /// Wrap YouTube/Vimeo players in privacy-friendly click-to-play facades.
+    pub privacy_embeds: bool,
+    /// `mermaid.initialize()` options from `mermaid_theme` and
+    /// `mermaid_config`, as a JSON object.
+    pub mermaid_config: String,
+    /// Largest width (CSS length) for content images, videos and iframes.
+    pub media_max_width: Option<String>,
+    /// Markdown files at least this many bytes are streamed (0 = never).
+    pub stream_render_threshold: u64,
+    /// Render slots; markdown pages hold one while they render.
+    pub render_permits: Arc<tokio::sync::Semaphore>,
+    /// Server-cache build to serve unchanged pages from.
+    pub server_cache_dir: Option<PathBuf>,
+    /// Markdown files larger than this aren't rendered (0 = no limit).
+    pub max_render_bytes: u64,
+    /// Custom favicon, web app manifest and theme color.
+    pub branding: Arc<SiteBranding>,
+    /// Default `<meta name="robots">` content.
+    pub robots: Option<String>,
+    /// Custom `robots.txt` contents.
+    pub robots_txt: Option<String>,
+    /// Content-Security-Policy header and nonce, when configured.
+    pub csp: Option<Arc<ContentSecurityPolicy>>,
+    /// Curated sidebar navigation from `.mbr/nav.yml`, if present.
+    pub site_nav: Option<Vec<crate::nav::NavItem>>,
+    /// Site-wide prev/next sequence, when `reading_order` is configured.
+    pub reading_order: Option<crate::reading_order::ReadingOrder>,
+    /// Data files from `data_folder`, for the `data` shortcode.
+    pub data_files: Option<Arc<crate::data::DataFiles>>,
     /// Highlight blocks beginning with TK/TODO/FIXME/XXX (default on in server/GUI).
     pub mark_incomplete: bool,
     /// Marker strings used by the incomplete-block highlighter.
     pub incomplete_markers: Vec<String>,
+    /// Enable task list checkboxes with localStorage-backed state.
+    pub interactive_tasklists: bool,
+    /// Highlight the TOC entry for the section in view.
+    pub scroll_spy: bool,
+    /// Template for each page's "edit this page" URL (`{path}` placeholder).
+    pub edit_url_template: Option<String>,
     /// Whether the in-browser markdown editing endpoints are enabled.
     pub edit_enabled: bool,
     /// Require the editing token even for loopback callers.
     pub edit_require_token_on_loopback: bool,
     /// Argon2 PHC hash of the shared editing token (never sent to the frontend).
     pub edit_token_hash: Option<String>,
+    /// Content-Type overrides applied when serving static files.
+    pub mime_overrides: Arc<MimeOverrides>,
+    /// When the server was initialized, for the uptime reported by `/healthz`.
+    pub started_at: std::time::Instant,
 }
 
 /// JSON body for `POST /.mbr/edit/{*path}`.
@@ -587,13 +896,23 @@ impl Server {
             base_dir,
             static_folder,
             markdown_extensions,
+            renderers,
             ignore_dirs,
             ignore_globs,
+            site_host,
+            respect_gitignore,
+            follow_symlinks,
             watcher_ignore_dirs,
             index_file,
             oembed_timeout_ms,
+            oembed_fallback,
             oembed_cache_size,
+            oembed_concurrency,
+            oembed_cache_ttl_secs,
+            inbound_cache_size,
+            inbound_cache_ttl_secs,
             template_folder,
+            data_folder,
             sort,
             gui_mode,
             theme,
@@ -604,18 +923,71 @@ impl Server {
             tag_sources,
             sidebar_style,
             sidebar_max_items,
+            toc,
+            markdown_options,
+            header_height,
+            smooth_scroll,
+            listing_layout,
+            listing_thumbnails,
+            search_placeholder,
+            search_result_limit,
+            site_lang,
+            site_dir,
+            reading_order,
             title_prefix,
             title_suffix,
+            dedupe_title_h1,
+            base_url,
+            external_links_new_tab,
+            sanitize_html,
+            post_render_hooks,
+            lazy_images,
+            privacy_embeds,
+            mermaid_config,
+            media_max_width,
+            stream_render_threshold,
+            server_render_concurrency,
+            server_cache_dir,
+            max_render_bytes,
+            branding,
+            robots,
+            robots_txt,
+            csp,
+            date_format,
             mark_incomplete,
             incomplete_markers,
+            interactive_tasklists,
+            scroll_spy,
+            edit_url_template,
             edit_enabled,
             edit_require_token_on_loopback,
             edit_token_hash,
+            mime_overrides,
+            access_log,
+            access_log_format,
             #[cfg(feature = "media-metadata")]
             transcode_enabled,
+            #[cfg(feature = "media-metadata")]
+            video_encoder,
+            #[cfg(feature = "media-metadata")]
+            transcode_tmp_dir,
+            #[cfg(feature = "media-metadata")]
+            audio_transcode,
+            #[cfg(feature = "media-metadata")]
+            audio_transcode_bitrate_kbps,
         } = config;
 
-        let oembed_cache = Arc::new(OembedCache::new(oembed_cache_size));
+        let access_log = match access_log {
+            Some(path) => AccessLog::to_file(access_log_format, &path)
+                .map_err(|source| ServerError::AccessLogOpen { path, source })?,
+            None => AccessLog::new(access_log_format),
+        };
+
+        let oembed_cache = Arc::new(
+            OembedCache::new(oembed_cache_size)
+                .with_concurrency(oembed_concurrency)
+                .with_ttl_secs(oembed_cache_ttl_secs),
+        );
 
         // Initialize video metadata cache with same size as oembed cache
         #[cfg(feature = "media-metadata")]
@@ -633,6 +1005,19 @@ impl Server {
             papaya::HashMap<String, crate::video_transcode::VideoResolution>,
         > = Arc::new(papaya::HashMap::new());
 
+        // Remove temp files orphaned by transcodes of a previous run that was
+        // killed mid-transcode (normally they're removed when the handle drops).
+        #[cfg(feature = "media-metadata")]
+        if transcode_enabled || audio_transcode.is_some() {
+            let tmp_dir = transcode_tmp_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::video_transcode::cleanup_stale_temp_files(
+                    tmp_dir.as_deref(),
+                    crate::video_transcode::STALE_TEMP_FILE_AGE,
+                )
+            });
+        }
+
         // Per-directory sibling navigation cache. Created before the file-change
         // invalidation task so that task can clear it when files change.
         let sibling_nav_cache: Arc<papaya::HashMap<PathBuf, Arc<Vec<serde_json::Value>>>> =
@@ -649,23 +1034,89 @@ impl Server {
             .with(tracing_subscriber::fmt::layer())
             .try_init();
 
+        // Data files for templates and the `data` shortcode. A malformed file
+        // is reported rather than fatal; the site then runs without data.
+        let data_files = data_folder.and_then(|folder| {
+            match crate::data::DataFiles::load(&base_dir.join(folder)) {
+                Ok(data) => Some(Arc::new(data)),
+                Err(e) => {
+                    tracing::warn!("Ignoring data files: {e}");
+                    None
+                }
+            }
+        });
+
         let templates = templates::Templates::new(base_dir.as_path(), template_folder.as_deref())
-            .map_err(ServerError::TemplateInit)?;
-
-        let repo = Arc::new(Repo::init(
-            &base_dir,
-            &static_folder,
-            &markdown_extensions,
-            &ignore_dirs,
-            &ignore_globs,
-            &index_file,
-            &tag_sources,
-            &relationship_types,
-        ));
+            .map_err(ServerError::TemplateInit)?
+            .with_date_format(&date_format)
+            .with_data(data_files.clone().unwrap_or_default());
+
+        // Curated sidebar navigation. A broken manifest is reported rather than
+        // fatal; entries pointing at missing pages are shown unlinked.
+        let tag_url_sources = crate::config::tag_sources_to_url_sources(&tag_sources);
+        let nav_resolver = PathResolverConfig {
+            base_dir: base_dir.as_path(),
+            canonical_base_dir: None,
+            static_folder: &static_folder,
+            markdown_extensions: &markdown_extensions,
+            index_file: &index_file,
+            tag_sources: &tag_url_sources,
+        };
+        let site_nav = match crate::nav::load_checked(&nav_resolver) {
+            Ok(Some(nav)) => {
+                for url in nav.dangling {
+                    tracing::warn!("Navigation entry {url} does not resolve to a page");
+                }
+                Some(nav.items)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!("Ignoring sidebar navigation: {e}");
+                None
+            }
+        };
+
+        let reading_order = reading_order.and_then(|source| {
+            match crate::reading_order::load(
+                &source,
+                &base_dir,
+                site_nav.as_deref(),
+                &markdown_extensions,
+                &index_file,
+            ) {
+                Ok(order) => order,
+                Err(e) => {
+                    tracing::warn!("Ignoring reading order: {e}");
+                    None
+                }
+            }
+        });
+
+        let gitignore = if respect_gitignore {
+            crate::gitignore::GitignoreFilter::load(&base_dir, &static_folder).map(Arc::new)
+        } else {
+            None
+        };
+        let repo = Arc::new(
+            Repo::init(
+                &base_dir,
+                &static_folder,
+                &markdown_extensions,
+                &ignore_dirs,
+                &ignore_globs,
+                &index_file,
+                &tag_sources,
+                &relationship_types,
+            )
+            .with_gitignore(gitignore)
+            .with_follow_symlinks(follow_symlinks),
+        );
 
         // Spawn background repo scan so site.json is ready before first request.
         // Phase 1: basic scan (file listing + frontmatter). Phase 2: media metadata (ffmpeg/lopdf).
         let repo_for_scan = Arc::clone(&repo);
+        let tag_sources_for_scan = tag_sources.clone();
+        let data_for_scan = data_files.clone();
         tokio::task::spawn_blocking(move || {
             if let Err(e) = repo_for_scan.scan_all() {
                 tracing::error!("Background scan failed: {e}");
@@ -675,6 +1126,19 @@ impl Server {
             // Build the global wikilink name index (always on) so body
             // `[[Name]]` links resolve globally on first render.
             repo_for_scan.build_wikilink_index();
+            for warning in repo_for_scan
+                .tag_index
+                .missing_metadata(&tag_sources_for_scan, data_for_scan.as_deref())
+            {
+                tracing::warn!("{warning}");
+            }
+            for collision in repo_for_scan.url_path_collisions() {
+                tracing::warn!(
+                    "Several files map to {}, only one is served: {:?}",
+                    collision.url_path,
+                    collision.sources
+                );
+            }
             repo_for_scan.mark_scan_complete();
 
             // Phase 1.5: scan static folder (deferred from scan_all for faster search)
@@ -846,13 +1310,6 @@ impl Server {
                         "Surgical invalidation for {} file(s)",
                         relevant_events.len()
                     );
-                    let has_tag_changes = relevant_events.iter().any(|e| {
-                        matches!(
-                            e.event,
-                            crate::watcher::ChangeEventType::Deleted
-                                | crate::watcher::ChangeEventType::Modified
-                        )
-                    });
 
                     tokio::task::spawn_blocking(move || {
                         for event in &relevant_events {
@@ -861,12 +1318,16 @@ impl Server {
                             } else {
                                 PathBuf::from(&event.path)
                             };
-                            repo.invalidate_file(&abs_path, &event.event);
-                        }
-                        // Rebuild tag index if any files were deleted or modified
-                        // (created files add tags inline in invalidate_file)
-                        if has_tag_changes {
-                            repo.rebuild_tag_index();
+                            // Both keep the tag index current for the file
+                            match event.event {
+                                crate::watcher::ChangeEventType::Deleted => {
+                                    repo.remove_file(&abs_path)
+                                }
+                                crate::watcher::ChangeEventType::Created
+                                | crate::watcher::ChangeEventType::Modified => {
+                                    repo.reload_file(&abs_path)
+                                }
+                            }
                         }
                         // Relationships may have changed on any create/modify/delete;
                         // rebuild the index so endpoint resolution stays consistent.
@@ -912,33 +1373,55 @@ impl Server {
 
         let link_cache = Arc::new(LinkCache::new(DEFAULT_LINK_CACHE_SIZE));
         let inbound_link_cache = Arc::new(InboundLinkCache::new(
-            DEFAULT_INBOUND_LINK_CACHE_SIZE,
-            INBOUND_LINK_CACHE_TTL_SECS,
+            inbound_cache_size,
+            inbound_cache_ttl_secs,
         ));
 
+        let sanitize_html_enabled = sanitize_html.is_some();
+        let post_render_hooks = crate::post_render::builtin_hooks(
+            sanitize_html,
+            external_links_new_tab,
+            base_url.as_deref(),
+        )
+        .into_iter()
+        .chain(post_render_hooks)
+        .collect();
+
         let canonical_base_dir = base_dir.canonicalize().ok();
         let state = ServerState {
             base_dir,
             canonical_base_dir,
             static_folder,
             markdown_extensions,
+            renderers,
             ignore_dirs,
             ignore_globs,
+            site_host,
             index_file,
             templates,
             repo,
             oembed_timeout_ms,
+            oembed_fallback,
             file_change_tx: Some(file_change_tx),
             template_folder,
             sort,
             gui_mode,
             theme,
             oembed_cache,
+            image_cache: Arc::new(ImageCache::new(DEFAULT_IMAGE_CACHE_SIZE)),
             #[cfg(feature = "media-metadata")]
             video_metadata_cache,
             #[cfg(feature = "media-metadata")]
             transcode_enabled,
             #[cfg(feature = "media-metadata")]
+            video_encoder,
+            #[cfg(feature = "media-metadata")]
+            transcode_tmp_dir,
+            #[cfg(feature = "media-metadata")]
+            audio_transcode,
+            #[cfg(feature = "media-metadata")]
+            audio_transcode_bitrate_kbps,
+            #[cfg(feature = "media-metadata")]
             hls_cache,
             #[cfg(feature = "media-metadata")]
             metadata_inflight,
@@ -953,19 +1436,59 @@ impl Server {
             tag_sources,
             sidebar_style,
             sidebar_max_items,
+            toc,
+            markdown_options,
+            header_height,
+            smooth_scroll,
+            listing_layout,
+            listing_thumbnails,
+            search_placeholder,
+            search_result_limit,
+            site_lang,
+            site_dir,
             title_prefix,
             title_suffix,
+            dedupe_title_h1,
+            base_url,
+            post_render_hooks,
+            sanitize_html: sanitize_html_enabled,
+            lazy_images,
+            privacy_embeds,
+            mermaid_config,
+            media_max_width,
+            stream_render_threshold,
+            render_permits: Arc::new(tokio::sync::Semaphore::new(server_render_concurrency)),
+            server_cache_dir,
+            max_render_bytes,
+            branding: Arc::new(branding),
+            robots,
+            robots_txt,
+            csp: csp.clone().map(Arc::new),
+            site_nav,
+            reading_order,
+            data_files,
             mark_incomplete,
             incomplete_markers,
+            interactive_tasklists,
+            scroll_spy,
+            edit_url_template,
             edit_enabled,
             edit_require_token_on_loopback,
             edit_token_hash,
+            mime_overrides: Arc::new(MimeOverrides::new(&mime_overrides)),
+            started_at: std::time::Instant::now(),
         };
 
-        let router = Router::new()
+        let router = Router::new();
+        #[cfg(feature = "media-metadata")]
+        let router = router.route("/.mbr/transcode-status", get(Self::get_transcode_status));
+        let router = router
             .route("/", get(Self::home_page))
+            // Liveness/readiness probe; answers without touching the repo scan
+            .route("/healthz", get(Self::health_check))
             .route("/.mbr/site.json", get(Self::get_site_info))
             .route("/.mbr/media.json", get(Self::get_media_info))
+            .route("/.mbr/chapters.json", get(Self::get_chapters))
             .route("/.mbr/search", post(Self::search_handler))
             // Editing endpoints: raw source fetch and save (gated by edit_enabled + auth)
             .route("/.mbr/raw/{*path}", get(Self::raw_markdown_handler))
@@ -981,10 +1504,23 @@ impl Server {
             .route("/.mbr/pdfs/", get(Self::serve_media_viewer))
             .route("/.mbr/audio/", get(Self::serve_media_viewer))
             .route("/.mbr/images/", get(Self::serve_media_viewer))
+            .route("/.mbr/thumbs/{*path}", get(Self::serve_gallery_thumbnail))
             .route("/.mbr/{*path}", get(Self::serve_mbr_assets))
-            .route("/{*path}", get(Self::handle))
+            .route("/{*path}", get(Self::handle));
+        let router = match csp.and_then(|csp| HeaderValue::from_str(&csp.policy).ok()) {
+            Some(policy) => router.layer(SetResponseHeaderLayer::if_not_present(
+                header::CONTENT_SECURITY_POLICY,
+                policy,
+            )),
+            None => router,
+        };
+        let router = router
             .layer(CompressionLayer::new())
             .layer(TraceLayer::new_for_http())
+            .layer(axum::middleware::from_fn_with_state(
+                access_log,
+                crate::access_log::log_requests,
+            ))
             .with_state(state);
 
         Ok(Server {
@@ -995,15 +1531,46 @@ impl Server {
         })
     }
 
+    /// Creates a plain static file server over an already-built site
+    /// (the output directory of `mbr --build`).
+    ///
+    /// Nothing is rendered: files are served as-is with MIME types and range
+    /// support, `dir/` resolves to `dir/index.html`, and misses get the
+    /// site's own `404.html` when present. `mime_overrides` is the configured
+    /// extension → Content-Type map, as for the regular server.
+    pub fn init_static(
+        ip: [u8; 4],
+        port: u16,
+        root_dir: PathBuf,
+        mime_overrides: &std::collections::HashMap<String, String>,
+    ) -> Self {
+        let router = Router::new()
+            .fallback(Self::serve_built_site)
+            .layer(CompressionLayer::new())
+            .layer(TraceLayer::new_for_http())
+            .with_state(Arc::new(BuiltSite {
+                root_dir,
+                mime_overrides: MimeOverrides::new(mime_overrides),
+            }));
+
+        Server {
+            router,
+            ip,
+            port,
+            _watcher_handle: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
     pub async fn start(&self) -> Result<(), ServerError> {
         self.start_with_ready_signal(None).await
     }
 
     /// Starts the server and optionally signals when ready to accept connections.
-    /// If a sender is provided, it will receive `()` once the server is bound and listening.
+    /// If a sender is provided, it will receive the bound address once the
+    /// server is listening (the actual port when configured with port 0).
     pub async fn start_with_ready_signal(
         &self,
-        ready_tx: Option<tokio::sync::oneshot::Sender<()>>,
+        ready_tx: Option<tokio::sync::oneshot::Sender<SocketAddr>>,
     ) -> Result<(), ServerError> {
         let addr = SocketAddr::from((self.ip, self.port));
         let listener =
@@ -1021,7 +1588,7 @@ impl Server {
 
         // Signal that server is ready before starting to serve
         if let Some(tx) = ready_tx
-            && tx.send(()).is_err()
+            && tx.send(local_addr).is_err()
         {
             tracing::debug!("Ready signal receiver dropped (shutdown in progress)");
         }
@@ -1062,9 +1629,10 @@ impl Server {
                     tracing::debug!("listening on {}", local_addr);
                     println!("Server running at http://{}/", local_addr);
 
-                    // Signal that server is ready with the actual port
+                    // Signal that server is ready with the actual port (which
+                    // differs from `self.port` when that is 0, "auto")
                     if let Some(tx) = ready_tx
-                        && tx.send(self.port).is_err()
+                        && tx.send(local_addr.port()).is_err()
                     {
                         tracing::debug!("Port signal receiver dropped (shutdown in progress)");
                     }
@@ -1206,6 +1774,22 @@ impl Server {
         }
     }
 
+    /// Handles `GET /healthz` for container orchestrators and other probes.
+    ///
+    /// Always answers `200` with `{status, version, uptime}` (uptime in whole
+    /// seconds) and never waits on the background scan, so it reports the
+    /// process as live as soon as the listener is up.
+    pub async fn health_check(State(config): State<ServerState>) -> impl IntoResponse {
+        (
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({
+                "status": "ok",
+                "version": env!("CARGO_PKG_VERSION"),
+                "uptime": config.started_at.elapsed().as_secs(),
+            })),
+        )
+    }
+
     pub async fn get_site_info(
         State(config): State<ServerState>,
     ) -> Result<impl IntoResponse, StatusCode> {
@@ -1226,6 +1810,10 @@ impl Server {
             // Remove other_files from site.json - media data is served separately via media.json
             obj.remove("other_files");
 
+            obj.insert(
+                "schema_version".to_string(),
+                serde_json::json!(crate::repo::SITE_JSON_SCHEMA_VERSION),
+            );
             obj.insert(
                 "sort".to_string(),
                 serde_json::to_value(&config.sort).unwrap_or(serde_json::Value::Array(vec![])),
@@ -1699,6 +2287,7 @@ impl Server {
                     config.gui_mode,
                     &config.sidebar_style,
                     config.sidebar_max_items,
+                    config.csp.as_deref(),
                 );
             }
         };
@@ -1717,6 +2306,7 @@ impl Server {
                     config.gui_mode,
                     &config.sidebar_style,
                     config.sidebar_max_items,
+                    config.csp.as_deref(),
                 );
             }
         };
@@ -1736,6 +2326,7 @@ impl Server {
                         config.gui_mode,
                         &config.sidebar_style,
                         config.sidebar_max_items,
+                        config.csp.as_deref(),
                     );
                 }
                 Err(MbrError::InvalidMediaPath(msg)) => {
@@ -1749,6 +2340,7 @@ impl Server {
                         config.gui_mode,
                         &config.sidebar_style,
                         config.sidebar_max_items,
+                        config.csp.as_deref(),
                     );
                 }
                 Err(e) => {
@@ -1762,6 +2354,7 @@ impl Server {
                         config.gui_mode,
                         &config.sidebar_style,
                         config.sidebar_max_items,
+                        config.csp.as_deref(),
                     );
                 }
             };
@@ -1800,6 +2393,21 @@ impl Server {
         if let Some(parent) = parent_path {
             context.insert("parent_path".to_string(), json!(parent));
         }
+        // Chapter markers from a sidecar, keyed by the path the viewer reads
+        // from its query string (same shape as the static build's map).
+        if matches!(media_type, MediaViewerType::Video | MediaViewerType::Audio) {
+            match crate::chapters::load_sidecar(&validated_path) {
+                Ok(Some(chapters)) => {
+                    let key = format!("/{}", media_path.trim_start_matches('/'));
+                    context.insert(
+                        "chapters_json".to_string(),
+                        json!(crate::chapters::to_script_json(&json!({ key: chapters }))),
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Ignoring chapters for {}: {}", media_path, e),
+            }
+        }
         page_context::insert_page_chrome(
             &mut context,
             &PageChrome {
@@ -1809,7 +2417,21 @@ impl Server {
                 },
                 sidebar_style: &config.sidebar_style,
                 sidebar_max_items: config.sidebar_max_items,
+                header_height: config.header_height.as_deref(),
+                smooth_scroll: config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &config.search_placeholder,
+                    result_limit: config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &config.site_lang,
+                    dir: &config.site_dir,
+                }),
+                branding: Some(&config.branding),
+                robots: config.robots.as_deref(),
+                csp: config.csp.as_deref(),
                 title_affixes: Some((&config.title_prefix, &config.title_suffix)),
+                site_nav: config.site_nav.as_deref(),
             },
         );
 
@@ -1837,11 +2459,151 @@ impl Server {
                     config.gui_mode,
                     &config.sidebar_style,
                     config.sidebar_max_items,
+                    config.csp.as_deref(),
                 )
             }
         }
     }
 
+    /// Chapter markers for a media file, read from its `.chapters.json` sidecar.
+    ///
+    /// GET /.mbr/chapters.json?path=<encoded_path>
+    ///
+    /// Returns the validated chapter list sorted by start time (seconds), 404
+    /// when the file or its sidecar doesn't exist, and 422 with a description
+    /// of the first malformed entry otherwise.
+    pub async fn get_chapters(
+        State(config): State<ServerState>,
+        extract::Query(query): extract::Query<MediaViewerQuery>,
+    ) -> Response<Body> {
+        let Some(media_path) = query.path.filter(|p| !p.is_empty()) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Missing required 'path' query parameter",
+            )
+                .into_response();
+        };
+        let media_file =
+            match validate_media_path(&media_path, &config.base_dir, &config.static_folder) {
+                Ok(p) => p,
+                Err(MbrError::DirectoryTraversal) => {
+                    return (StatusCode::FORBIDDEN, "Access denied: Invalid path").into_response();
+                }
+                Err(_) => return (StatusCode::NOT_FOUND, "Media file not found").into_response(),
+            };
+
+        match crate::chapters::load_sidecar(&media_file) {
+            Ok(Some(chapters)) => build_response_or_500(
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+                    .body(Body::from(
+                        serde_json::to_string(&chapters).unwrap_or_else(|_| "[]".to_string()),
+                    )),
+            ),
+            Ok(None) => (StatusCode::NOT_FOUND, "No chapters for this file").into_response(),
+            Err(e) => {
+                tracing::warn!("Invalid chapters for {}: {}", media_path, e);
+                (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()
+            }
+        }
+    }
+
+    /// Reports whether a video can be transcoded and which HLS variants apply.
+    ///
+    /// `GET /.mbr/transcode-status?path=/videos/demo.mp4` returns the probed
+    /// resolution, the applicable targets with their playlist URLs, and the
+    /// probe error (as `{error, message}`) when the file can't be read as video.
+    #[cfg(feature = "media-metadata")]
+    pub async fn get_transcode_status(
+        State(config): State<ServerState>,
+        extract::Query(query): extract::Query<MediaViewerQuery>,
+    ) -> Response<Body> {
+        use crate::video_transcode::{
+            HlsRequest, TranscodeErrorInfo, TranscodeTarget, calculate_output_dimensions,
+            parse_hls_request, should_transcode,
+        };
+
+        let Some(media_path) = query.path.filter(|p| !p.is_empty()) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Missing required 'path' query parameter",
+            )
+                .into_response();
+        };
+        let media_file =
+            match validate_media_path(&media_path, &config.base_dir, &config.static_folder) {
+                Ok(p) => p,
+                Err(MbrError::DirectoryTraversal) => {
+                    return (StatusCode::FORBIDDEN, "Access denied: Invalid path").into_response();
+                }
+                Err(_) => return (StatusCode::NOT_FOUND, "Media file not found").into_response(),
+            };
+
+        let path = if media_path.starts_with('/') {
+            media_path
+        } else {
+            format!("/{media_path}")
+        };
+        let url_base = path
+            .rsplit_once('.')
+            .map_or(path.as_str(), |(base, _)| base);
+        let master_playlist = format!("{url_base}.m3u8");
+        // HLS URLs carry no extension, so only sources they map back to are reachable
+        let hls_reachable = matches!(
+            parse_hls_request(&master_playlist),
+            Some(HlsRequest::Master { video_path }) if video_path == path
+        );
+
+        let (resolution, error) = match Self::probe_resolution_cached(&media_file, &config).await {
+            Ok(resolution) => (Some(resolution), None),
+            Err(e) => (None, Some(TranscodeErrorInfo::from(&e))),
+        };
+
+        let targets: Vec<TranscodeTargetStatus> = resolution
+            .iter()
+            .flat_map(|res| {
+                TranscodeTarget::ALL
+                    .into_iter()
+                    .filter(move |target| should_transcode(res.height, *target))
+                    .map(move |target| {
+                        let (width, height) =
+                            calculate_output_dimensions(res.width, res.height, target);
+                        let suffix = target.url_suffix();
+                        TranscodeTargetStatus {
+                            name: suffix.trim_start_matches('-'),
+                            width,
+                            height,
+                            bandwidth: target.bandwidth_bps(),
+                            playlist: format!("{url_base}{suffix}.m3u8"),
+                        }
+                    })
+            })
+            .collect();
+
+        let transcodable = config.transcode_enabled && hls_reachable && !targets.is_empty();
+        let status = TranscodeStatus {
+            transcode_enabled: config.transcode_enabled,
+            transcodable,
+            master_playlist: transcodable.then_some(master_playlist),
+            resolution,
+            targets,
+            error,
+            path,
+        };
+
+        build_response_or_500(
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+                .body(Body::from(
+                    serde_json::to_string(&status).unwrap_or_else(|_| "{}".to_string()),
+                )),
+        )
+    }
+
     /// Serves assets from /.mbr/* path.
     ///
     /// Priority:
@@ -1855,6 +2617,7 @@ impl Server {
     /// that resolved paths remain within the intended directory.
     pub async fn serve_mbr_assets(
         extract::Path(path): extract::Path<String>,
+        headers: HeaderMap,
         State(config): State<ServerState>,
     ) -> Result<impl IntoResponse, StatusCode> {
         tracing::debug!("serve_mbr_assets: {}", path);
@@ -1866,6 +2629,10 @@ impl Server {
             format!("/{}", path)
         };
 
+        if let Some(response) = Self::try_serve_site_icon(&asset_path, &headers, &config).await {
+            return Ok(response);
+        }
+
         // Try template_folder first if set (with path traversal protection)
         if let Some(ref template_folder) = config.template_folder {
             // Map components/* -> components-js/* in template folder
@@ -1921,10 +2688,15 @@ impl Server {
             .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
             .and_then(|d| generate_last_modified(d.as_secs()));
 
+        let cache_control = if is_hashed_search_asset(path) {
+            CACHE_CONTROL_IMMUTABLE
+        } else {
+            CACHE_CONTROL_NO_CACHE
+        };
         let mut builder = Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, mime)
-            .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+            .header(header::CACHE_CONTROL, cache_control)
             .header(header::ETAG, etag);
 
         if let Some(lm) = last_modified {
@@ -1936,6 +2708,148 @@ impl Server {
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
     }
 
+    /// GET /.mbr/thumbs/{*path} — a gallery thumbnail of the JPEG/PNG image at
+    /// `/{path}`, generated once per version of the image.
+    ///
+    /// The image must resolve to a file inside the repo, the same check the
+    /// editing endpoints use for markdown.
+    pub async fn serve_gallery_thumbnail(
+        extract::Path(path): extract::Path<String>,
+        headers: HeaderMap,
+        State(config): State<ServerState>,
+    ) -> Result<Response<Body>, StatusCode> {
+        if !crate::media::is_transcodable_image(&path) {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        let tag_url_sources = crate::config::tag_sources_to_url_sources(&config.tag_sources);
+        let resolver_config = PathResolverConfig {
+            base_dir: config.base_dir.as_path(),
+            canonical_base_dir: config.canonical_base_dir.as_deref(),
+            static_folder: &config.static_folder,
+            markdown_extensions: &config.markdown_extensions,
+            index_file: &config.index_file,
+            tag_sources: &tag_url_sources,
+        };
+        let ResolvedPath::StaticFile(image_path) = resolve_request_path(&resolver_config, &path)
+        else {
+            return Err(StatusCode::NOT_FOUND);
+        };
+        let base = config
+            .canonical_base_dir
+            .clone()
+            .or_else(|| config.base_dir.canonicalize().ok());
+        let source = match (image_path.canonicalize().ok(), base) {
+            (Some(c), Some(b)) if c.starts_with(&b) && c.is_file() => c,
+            _ => return Err(StatusCode::NOT_FOUND),
+        };
+
+        let mime = Self::guess_mime_type(&source);
+        let key = ImageCacheKey::for_file(&source, "thumbnail").ok_or(StatusCode::NOT_FOUND)?;
+        let bytes = match config.image_cache.get(&key) {
+            Some(bytes) => bytes,
+            None => {
+                let bytes = tokio::task::spawn_blocking(move || {
+                    crate::media::thumbnail_bytes(&source, crate::constants::GALLERY_THUMBNAIL_SIZE)
+                })
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .map_err(|e| {
+                    tracing::warn!("Failed to generate thumbnail for {}: {}", path, e);
+                    StatusCode::UNPROCESSABLE_ENTITY
+                })?;
+                let bytes = Bytes::from(bytes);
+                config.image_cache.insert(key.clone(), bytes.clone());
+                bytes
+            }
+        };
+        Ok(generated_image_response(bytes, mime, &key, &headers))
+    }
+
+    /// Serves the configured favicon (as `/favicon.<ext>` and `/favicon.ico`)
+    /// and the generated `/manifest.webmanifest`. Returns `None` for other
+    /// paths, or when nothing is configured, so repository files are served.
+    async fn try_serve_site_branding(path: &str, config: &ServerState) -> Option<Response<Body>> {
+        if path == favicon::MANIFEST_FILE {
+            let manifest = config.branding.manifest.as_ref()?;
+            return Some(build_response_or_500(
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, "application/manifest+json")
+                    .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+                    .header(header::ETAG, generate_etag(manifest.as_bytes()))
+                    .body(Body::from(manifest.clone())),
+            ));
+        }
+
+        let icon = config.branding.icon.as_ref()?;
+        if path != "favicon.ico" && path != icon.file_name {
+            return None;
+        }
+        Some(match Self::serve_file_from_path(&icon.source).await {
+            Ok(response) => response,
+            Err(status) => status.into_response(),
+        })
+    }
+
+    /// Serves the generated `/robots.txt`, naming `sitemap.xml` when the
+    /// repository provides one.
+    fn serve_robots_txt(
+        resolver_config: &PathResolverConfig<'_>,
+        config: &ServerState,
+    ) -> Response {
+        let has_sitemap = matches!(
+            resolve_request_path(resolver_config, robots::SITEMAP_FILE),
+            ResolvedPath::StaticFile(_)
+        );
+        let txt = robots::robots_txt(
+            config.robots_txt.as_deref(),
+            config.base_url.as_deref(),
+            has_sitemap,
+        );
+        build_response_or_500(
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+                .header(header::ETAG, generate_etag(txt.as_bytes()))
+                .body(Body::from(txt)),
+        )
+    }
+
+    /// Serves a PNG icon generated from the favicon at `/.mbr/icons/icon-<size>.png`,
+    /// generated once per version of the favicon.
+    async fn try_serve_site_icon(
+        asset_path: &str,
+        headers: &HeaderMap,
+        config: &ServerState,
+    ) -> Option<Response<Body>> {
+        let icon = config.branding.icon.clone()?;
+        let size = icon
+            .sizes
+            .iter()
+            .copied()
+            .find(|&size| asset_path == format!("/icons/{}", favicon::icon_file_name(size)))?;
+        let Some(key) = ImageCacheKey::for_file(&icon.source, format!("icon-{size}")) else {
+            return Some(StatusCode::NOT_FOUND.into_response());
+        };
+        let png = match config.image_cache.get(&key) {
+            Some(png) => png,
+            None => match tokio::task::spawn_blocking(move || icon.icon_png(size)).await {
+                Ok(Ok(png)) => {
+                    let png = Bytes::from(png);
+                    config.image_cache.insert(key.clone(), png.clone());
+                    png
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!("Failed to generate {size}px icon: {e}");
+                    return Some(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+                }
+                Err(_) => return Some(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+            },
+        };
+        Some(generated_image_response(png, "image/png", &key, headers))
+    }
+
     /// Serve themed Pico CSS based on the configured theme.
     ///
     /// Returns the appropriate Pico CSS variant based on theme config:
@@ -1998,6 +2912,7 @@ impl Server {
         gui_mode: bool,
         sidebar_style: &str,
         sidebar_max_items: usize,
+        csp: Option<&ContentSecurityPolicy>,
     ) -> Response<Body> {
         use std::collections::HashMap;
 
@@ -2012,7 +2927,8 @@ impl Server {
             "requested_url".to_string(),
             serde_json::Value::String(requested_url.to_string()),
         );
-        // Server mode uses absolute paths; error pages omit title affixes
+        // Server mode uses absolute paths; error pages omit title affixes and
+        // have no anchors to scroll to
         page_context::insert_page_chrome(
             &mut context,
             &PageChrome {
@@ -2022,7 +2938,15 @@ impl Server {
                 },
                 sidebar_style,
                 sidebar_max_items,
+                header_height: None,
+                smooth_scroll: false,
+                search: None,
+                language: None,
+                branding: None,
+                robots: None,
+                csp,
                 title_affixes: None,
+                site_nav: None,
             },
         );
 
@@ -2089,6 +3013,10 @@ impl Server {
     ) -> Result<impl IntoResponse, StatusCode> {
         tracing::debug!("handle: {}", &path);
 
+        if let Some(response) = Self::try_serve_site_branding(&path, &config).await {
+            return Ok(response);
+        }
+
         let tag_url_sources = crate::config::tag_sources_to_url_sources(&config.tag_sources);
         let resolver_config = PathResolverConfig {
             base_dir: config.base_dir.as_path(),
@@ -2109,7 +3037,7 @@ impl Server {
                     return Ok(response);
                 }
                 tracing::debug!("serving static file: {:?}", &file_path);
-                Self::serve_static_file(file_path, req).await
+                Self::serve_static_file(file_path, req, &config.mime_overrides).await
             }
             ResolvedPath::MarkdownFile(md_path) => {
                 tracing::debug!("rendering markdown: {:?}", &md_path);
@@ -2170,6 +3098,15 @@ impl Server {
                     return Ok(response);
                 }
 
+                // Try to serve an on-demand transcode of a lossless audio file
+                #[cfg(feature = "media-metadata")]
+                if let Some(format) = config.audio_transcode
+                    && let Some(response) =
+                        Self::try_serve_audio_transcode(&path, format, req.headers(), &config).await
+                {
+                    return Ok(response);
+                }
+
                 // Try to serve dynamically generated video metadata (server mode only)
                 #[cfg(feature = "media-metadata")]
                 if let Some(response) = Self::try_serve_video_metadata(&path, &config).await {
@@ -2193,6 +3130,11 @@ impl Server {
                     return Ok(response);
                 }
 
+                // Generated robots.txt, when the repository doesn't have one
+                if path == robots::ROBOTS_TXT {
+                    return Ok(Self::serve_robots_txt(&resolver_config, &config));
+                }
+
                 tracing::debug!("resource not found: {}", &path);
                 let requested_url = format!("/{}", path);
                 Ok(Self::render_error_page(
@@ -2204,19 +3146,33 @@ impl Server {
                     config.gui_mode,
                     &config.sidebar_style,
                     config.sidebar_max_items,
+                    config.csp.as_deref(),
                 ))
             }
         }
     }
 
     /// Serves a static file using tower's ServeFile service with cache headers.
-    /// ServeFile already provides Last-Modified and ETag headers.
+    /// ServeFile already provides Last-Modified and ETag headers and answers
+    /// conditional requests; its Content-Type is replaced when `mime_overrides`
+    /// has an entry for the file. Content-hashed Pagefind search assets are
+    /// marked immutable instead of revalidated.
     async fn serve_static_file(
         file_path: std::path::PathBuf,
         req: extract::Request<Body>,
+        mime_overrides: &MimeOverrides,
     ) -> Result<Response, StatusCode> {
-        let static_service = ServeFile::new(file_path);
-        let mut response = static_service
+        let content_type = mime_overrides
+            .lookup(&file_path)
+            .and_then(|mime| HeaderValue::from_str(mime).ok());
+        let cache_control = if is_hashed_search_asset(&file_path) {
+            CACHE_CONTROL_IMMUTABLE
+        } else {
+            CACHE_CONTROL_NO_CACHE
+        };
+
+        let static_service = ServeFile::new(file_path);
+        let mut response = static_service
             .oneshot(req)
             .await
             .map(|r| r.into_response())
@@ -2225,15 +3181,74 @@ impl Server {
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
-        // Add Cache-Control header for browser revalidation
+        // Add Cache-Control header for browser revalidation (or long-term caching)
         response.headers_mut().insert(
             header::CACHE_CONTROL,
-            HeaderValue::from_static(CACHE_CONTROL_NO_CACHE),
+            HeaderValue::from_static(cache_control),
         );
 
+        if let Some(content_type) = content_type
+            && response.headers().contains_key(header::CONTENT_TYPE)
+        {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type);
+        }
+
         Ok(response)
     }
 
+    /// Handler for [`Server::init_static`]: serves files from a built site.
+    async fn serve_built_site(
+        State(site): State<Arc<BuiltSite>>,
+        req: extract::Request<Body>,
+    ) -> Result<Response, StatusCode> {
+        let root_dir = &site.root_dir;
+        let decoded = percent_decode_str(req.uri().path())
+            .decode_utf8_lossy()
+            .into_owned();
+        let relative = decoded.trim_start_matches('/');
+
+        let candidate = if relative.is_empty() || relative.ends_with('/') {
+            format!("{relative}index.html")
+        } else {
+            relative.to_string()
+        };
+        if let Some(file_path) = safe_join_asset(root_dir, &candidate) {
+            return Self::serve_static_file(file_path, req, &site.mime_overrides).await;
+        }
+
+        // A directory requested without its trailing slash: redirect so that
+        // relative links inside the page resolve the same way as in deploys.
+        if !relative.is_empty()
+            && !relative.ends_with('/')
+            && safe_join_asset(root_dir, &format!("{relative}/index.html")).is_some()
+        {
+            return Ok(build_response_or_500(
+                Response::builder()
+                    .status(StatusCode::MOVED_PERMANENTLY)
+                    .header(header::LOCATION, format!("{}/", req.uri().path()))
+                    .body(Body::empty()),
+            ));
+        }
+
+        tracing::debug!("built file not found: {}", &decoded);
+        let body = match safe_join_asset(root_dir, "404.html") {
+            Some(not_found) => tokio::fs::read(&not_found).await.ok(),
+            None => None,
+        };
+        Ok(build_response_or_500(match body {
+            Some(bytes) => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                .body(Body::from(bytes)),
+            None => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(Body::from("Not Found")),
+        }))
+    }
+
     /// Builds a response from a cached video-metadata entry, or `None` for a
     /// negative (`NotAvailable`) marker so the caller falls through to a 404.
     #[cfg(feature = "media-metadata")]
@@ -2658,7 +3673,7 @@ impl Server {
     /// Returns Some(Response) if the request was for links.json and we successfully
     /// generated it, None otherwise (fall through to 404).
     async fn try_serve_links_json(path: &str, config: &ServerState) -> Option<Response<Body>> {
-        use crate::link_grep::find_inbound_links;
+        use crate::link_grep::{InboundLinkSearch, find_inbound_links};
         use crate::link_index::PageLinks;
 
         // Check if this is a links.json request
@@ -2723,13 +3738,16 @@ impl Server {
                         is_index_file,
                         url_depth: None,
                         current_page_url: page_url_path.clone(),
+                        clean_urls: true,
                     };
 
                     let valid_tag_sources = crate::config::tag_sources_to_set(&config.tag_sources);
+                    let renderer = markdown::RendererKind::for_path(&md_path, &config.renderers);
                     match markdown::render_with_cache(
                         md_path,
                         &config.base_dir,
                         config.oembed_timeout_ms,
+                        config.oembed_fallback,
                         link_transform_config,
                         Some(config.oembed_cache.clone()),
                         true,  // server_mode
@@ -2738,6 +3756,9 @@ impl Server {
                         false, // mark_incomplete: not needed for link extraction
                         &config.incomplete_markers,
                         Some(config.repo.wikilink_index.clone()),
+                        config.data_files.clone(),
+                        renderer,
+                        &config.markdown_options,
                     )
                     .await
                     {
@@ -2797,13 +3818,23 @@ impl Server {
             let markdown_extensions = config.markdown_extensions.clone();
             let ignore_dirs = config.ignore_dirs.clone();
             let ignore_globs = config.ignore_globs.clone();
+            let gitignore = config.repo.gitignore().cloned();
+            let follow_symlinks = config.repo.follow_symlinks();
+            let site_host = config.site_host.clone();
+            let heading_ids = config.markdown_options.heading_ids;
             let links = tokio::task::spawn_blocking(move || {
                 find_inbound_links(
                     &target,
-                    &base_dir,
-                    &markdown_extensions,
-                    &ignore_dirs,
-                    &ignore_globs,
+                    &InboundLinkSearch {
+                        root_dir: &base_dir,
+                        markdown_extensions: &markdown_extensions,
+                        ignore_dirs: &ignore_dirs,
+                        ignore_globs: &ignore_globs,
+                        gitignore: gitignore.as_deref(),
+                        site_host: site_host.as_deref(),
+                        heading_ids,
+                        follow_symlinks,
+                    },
                 )
             })
             .await
@@ -2929,14 +3960,18 @@ impl Server {
                     is_index_file,
                     url_depth: None,
                     current_page_url: page_url_path.clone(),
+                    clean_urls: true,
                 };
 
                 let valid_tag_sources = crate::config::tag_sources_to_set(&config.tag_sources);
 
+                let renderer = markdown::RendererKind::for_path(&md_path, &config.renderers);
+
                 match markdown::render_with_cache(
                     md_path,
                     &config.base_dir,
                     config.oembed_timeout_ms,
+                    config.oembed_fallback,
                     link_transform_config,
                     Some(config.oembed_cache.clone()),
                     true,  // server_mode
@@ -2945,6 +3980,9 @@ impl Server {
                     false, // mark_incomplete: not needed for error scan
                     &config.incomplete_markers,
                     Some(config.repo.wikilink_index.clone()),
+                    config.data_files.clone(),
+                    renderer,
+                    &config.markdown_options,
                 )
                 .await
                 {
@@ -3077,6 +4115,47 @@ impl Server {
         )
     }
 
+    /// Build a JSON error response for a failed HLS request.
+    ///
+    /// Problems with the source (too small, unreadable, no video) are 422, an
+    /// out-of-range segment is 404, and anything else is 500.
+    #[cfg(feature = "media-metadata")]
+    fn build_transcode_error_response(
+        error: &crate::video_transcode::TranscodeError,
+    ) -> Response<Body> {
+        use crate::video_transcode::TranscodeError;
+
+        let status = match error {
+            TranscodeError::SegmentOutOfRange { .. } => StatusCode::NOT_FOUND,
+            TranscodeError::SourceTooSmall { .. }
+            | TranscodeError::OpenFailed { .. }
+            | TranscodeError::NoVideoStream { .. }
+            | TranscodeError::UnsupportedFormat => StatusCode::UNPROCESSABLE_ENTITY,
+            TranscodeError::NoAudioStream { .. }
+            | TranscodeError::TranscodeFailed(_)
+            | TranscodeError::EncoderNotAvailable(_)
+            | TranscodeError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::build_transcode_failure_response(status, &error.into())
+    }
+
+    /// Build a JSON error response (`{"error": kind, "message": ...}`).
+    #[cfg(feature = "media-metadata")]
+    fn build_transcode_failure_response(
+        status: StatusCode,
+        info: &crate::video_transcode::TranscodeErrorInfo,
+    ) -> Response<Body> {
+        build_response_or_500(
+            Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+                .body(Body::from(
+                    serde_json::to_string(info).unwrap_or_else(|_| "{}".to_string()),
+                )),
+        )
+    }
+
     /// Probes a video's resolution, memoized by path+mtime.
     ///
     /// The underlying `probe_video_resolution` demuxes the file, which is
@@ -3087,26 +4166,28 @@ impl Server {
     async fn probe_resolution_cached(
         video_file: &std::path::Path,
         config: &ServerState,
-    ) -> Option<crate::video_transcode::VideoResolution> {
+    ) -> Result<crate::video_transcode::VideoResolution, crate::video_transcode::TranscodeError>
+    {
         use crate::video_metadata_cache::cache_key_with_mtime;
-        use crate::video_transcode::probe_video_resolution;
+        use crate::video_transcode::{TranscodeError, probe_video_resolution};
 
         let key = cache_key_with_mtime(video_file, "resolution");
         if let Some(res) = config.video_resolution_cache.pin().get(&key).cloned() {
-            return Some(res);
+            return Ok(res);
         }
 
         let path = video_file.to_path_buf();
         let probed = tokio::task::spawn_blocking(move || probe_video_resolution(&path))
             .await
-            .inspect_err(|e| tracing::warn!("resolution probe task failed: {e}"))
-            .ok()?
-            .ok()?;
+            .map_err(|e| {
+                tracing::warn!("resolution probe task failed: {e}");
+                TranscodeError::TranscodeFailed(format!("resolution probe task failed: {e}"))
+            })??;
         config
             .video_resolution_cache
             .pin()
             .insert(key, probed.clone());
-        Some(probed)
+        Ok(probed)
     }
 
     /// Try to serve HLS content (playlist or segment) for transcoded video variants.
@@ -3116,58 +4197,48 @@ impl Server {
     #[cfg(feature = "media-metadata")]
     async fn try_serve_hls_content(path: &str, config: &ServerState) -> Option<Response<Body>> {
         use crate::video_transcode::{
-            HlsRequest, TranscodeError, generate_hls_playlist, parse_hls_request, should_transcode,
-            transcode_segment,
+            HlsRequest, TranscodeError, build_master_playlist, generate_hls_playlist,
+            parse_hls_request, should_transcode, transcode_segment,
         };
         use crate::video_transcode_cache::{
             HLS_WAIT_TIMEOUT, HlsCacheKey, HlsCacheStartResult, HlsCacheState,
         };
 
-        // Helper to build error response for transcode errors
-        fn build_transcode_error_response(error: &TranscodeError) -> Option<Response<Body>> {
-            match error {
-                TranscodeError::SourceTooSmall {
-                    source_height,
-                    target_height,
-                } => Some(build_response_or_500(
-                    Response::builder()
-                        .status(StatusCode::UNPROCESSABLE_ENTITY)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(Body::from(format!(
-                            "Cannot transcode: source ({}p) not larger than target ({}p)",
-                            source_height, target_height
-                        ))),
-                )),
-                TranscodeError::SegmentOutOfRange {
-                    segment_index,
-                    video_duration,
-                } => Some(build_response_or_500(
-                    Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .header(header::CONTENT_TYPE, "text/plain")
-                        .body(Body::from(format!(
-                            "Segment {} is out of range (video duration: {:.1}s)",
-                            segment_index, video_duration
-                        ))),
-                )),
-                // For other errors, fall through to 404 (return None)
-                _ => None,
-            }
-        }
-
         // Check if this is an HLS request
         let hls_request = parse_hls_request(path)?;
 
+        tracing::debug!("HLS request: {:?}", hls_request);
+
+        // Master playlist: only needs the (cached) probe, so it's built inline
+        // rather than going through the generation cache.
+        if let HlsRequest::Master { video_path } = &hls_request {
+            let video_file =
+                resolve_media_source_file(video_path, &config.base_dir, &config.static_folder)?;
+            let resolution = Self::probe_resolution_cached(&video_file, config)
+                .await
+                .ok()?;
+            let base_name = std::path::Path::new(video_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("video");
+            return match build_master_playlist(&resolution, base_name) {
+                Ok(playlist) => Some(Self::build_hls_playlist_response(Arc::new(
+                    playlist.into_bytes(),
+                ))),
+                Err(e) => Some(Self::build_transcode_error_response(&e)),
+            };
+        }
+
         // Extract the video path and target from the request
         let (video_path, target) = match &hls_request {
+            // Served above
+            HlsRequest::Master { .. } => return None,
             HlsRequest::Playlist { video_path, target } => (video_path.clone(), *target),
             HlsRequest::Segment {
                 video_path, target, ..
             } => (video_path.clone(), *target),
         };
 
-        tracing::debug!("HLS request: {:?}", hls_request);
-
         // Resolve the original video file path with path traversal protection
         let Some(video_file) =
             resolve_media_source_file(&video_path, &config.base_dir, &config.static_folder)
@@ -3180,6 +4251,7 @@ impl Server {
         // cached, serve it without probing the source. The blocking ffmpeg
         // demux in `probe_video_resolution` must not run on cache hits.
         let content_key = match &hls_request {
+            HlsRequest::Master { .. } => return None,
             HlsRequest::Playlist { .. } => HlsCacheKey::playlist(video_file.clone(), target),
             HlsRequest::Segment { segment_index, .. } => {
                 HlsCacheKey::segment(video_file.clone(), target, *segment_index)
@@ -3188,7 +4260,9 @@ impl Server {
         if let Some(HlsCacheState::Complete(data)) = config.hls_cache.get_state(&content_key) {
             tracing::debug!("Serving cached HLS content (pre-probe fast path)");
             return Some(match hls_request {
-                HlsRequest::Playlist { .. } => Self::build_hls_playlist_response(data),
+                HlsRequest::Master { .. } | HlsRequest::Playlist { .. } => {
+                    Self::build_hls_playlist_response(data)
+                }
                 HlsRequest::Segment { .. } => Self::build_hls_segment_response(data),
             });
         }
@@ -3197,7 +4271,9 @@ impl Server {
         // The probe demuxes the file (blocking), so it runs off the async worker
         // and its result is cached by path+mtime so repeated misses for an
         // unchanged file never re-demux (finding #17 + #16).
-        let resolution = Self::probe_resolution_cached(&video_file, config).await?;
+        let resolution = Self::probe_resolution_cached(&video_file, config)
+            .await
+            .ok()?;
         if !should_transcode(resolution.height, target) {
             tracing::debug!(
                 "Video already at or below target resolution: {}x{} <= {}",
@@ -3206,19 +4282,16 @@ impl Server {
                 target.height()
             );
             // Return 422 instead of None (404) with helpful message
-            return Some(build_response_or_500(
-                Response::builder()
-                    .status(StatusCode::UNPROCESSABLE_ENTITY)
-                    .header(header::CONTENT_TYPE, "text/plain")
-                    .body(Body::from(format!(
-                        "Cannot transcode: source ({}p) not larger than target ({}p)",
-                        resolution.height,
-                        target.height()
-                    ))),
+            return Some(Self::build_transcode_error_response(
+                &TranscodeError::SourceTooSmall {
+                    source_height: resolution.height,
+                    target_height: target.height(),
+                },
             ));
         }
 
         match hls_request {
+            HlsRequest::Master { .. } => return None,
             HlsRequest::Playlist { .. } => {
                 // Generate or serve cached playlist
                 let cache_key = HlsCacheKey::playlist(video_file.clone(), target);
@@ -3257,11 +4330,7 @@ impl Server {
                                 tracing::warn!("Playlist generation failed: {}", e);
                                 config.hls_cache.fail_generation(cache_key, &e);
                                 notify.notify_waiters();
-                                // Return meaningful error response for known error types
-                                if let Some(response) = build_transcode_error_response(&e) {
-                                    return Some(response);
-                                }
-                                return None;
+                                return Some(Self::build_transcode_error_response(&e));
                             }
                             Err(e) => {
                                 tracing::warn!("Playlist generation task panicked: {}", e);
@@ -3284,14 +4353,12 @@ impl Server {
                         tracing::debug!("Serving cached playlist");
                         return Some(Self::build_hls_playlist_response(data));
                     }
-                    HlsCacheStartResult::PreviouslyFailed(msg) => {
-                        tracing::debug!("Previous playlist generation failed: {}", msg);
-                        // Return 422 with cached error message instead of None (404)
-                        return Some(build_response_or_500(
-                            Response::builder()
-                                .status(StatusCode::UNPROCESSABLE_ENTITY)
-                                .header(header::CONTENT_TYPE, "text/plain")
-                                .body(Body::from(format!("Transcode failed: {}", msg))),
+                    HlsCacheStartResult::PreviouslyFailed(info) => {
+                        tracing::debug!("Previous playlist generation failed: {}", info);
+                        // Return 422 with the cached error instead of None (404)
+                        return Some(Self::build_transcode_failure_response(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            &info,
                         ));
                     }
                     HlsCacheStartResult::CacheDisabled => {
@@ -3311,11 +4378,7 @@ impl Server {
                             }
                             Ok(Err(e)) => {
                                 tracing::warn!("Playlist generation failed: {}", e);
-                                // Return meaningful error response for known error types
-                                if let Some(response) = build_transcode_error_response(&e) {
-                                    return Some(response);
-                                }
-                                return None;
+                                return Some(Self::build_transcode_error_response(&e));
                             }
                             Err(e) => {
                                 tracing::warn!("Playlist generation task panicked: {}", e);
@@ -3339,8 +4402,16 @@ impl Server {
                         );
 
                         let video_file_clone = video_file.clone();
+                        let encoder = config.video_encoder.clone();
+                        let tmp_dir = config.transcode_tmp_dir.clone();
                         let result = tokio::task::spawn_blocking(move || {
-                            transcode_segment(&video_file_clone, target, segment_index)
+                            transcode_segment(
+                                &video_file_clone,
+                                target,
+                                segment_index,
+                                encoder.as_deref(),
+                                tmp_dir.as_deref(),
+                            )
                         })
                         .await;
 
@@ -3361,11 +4432,7 @@ impl Server {
                                 tracing::warn!("Segment transcode failed: {}", e);
                                 config.hls_cache.fail_generation(cache_key, &e);
                                 notify.notify_waiters();
-                                // Return meaningful error response for known error types
-                                if let Some(response) = build_transcode_error_response(&e) {
-                                    return Some(response);
-                                }
-                                return None;
+                                return Some(Self::build_transcode_error_response(&e));
                             }
                             Err(e) => {
                                 tracing::warn!("Segment transcode task panicked: {}", e);
@@ -3388,21 +4455,27 @@ impl Server {
                         tracing::debug!("Serving cached segment");
                         return Some(Self::build_hls_segment_response(data));
                     }
-                    HlsCacheStartResult::PreviouslyFailed(msg) => {
-                        tracing::debug!("Previous segment transcode failed: {}", msg);
-                        // Return 422 with cached error message instead of None (404)
-                        return Some(build_response_or_500(
-                            Response::builder()
-                                .status(StatusCode::UNPROCESSABLE_ENTITY)
-                                .header(header::CONTENT_TYPE, "text/plain")
-                                .body(Body::from(format!("Transcode failed: {}", msg))),
+                    HlsCacheStartResult::PreviouslyFailed(info) => {
+                        tracing::debug!("Previous segment transcode failed: {}", info);
+                        // Return 422 with the cached error instead of None (404)
+                        return Some(Self::build_transcode_failure_response(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            &info,
                         ));
                     }
                     HlsCacheStartResult::CacheDisabled => {
                         // Transcode without caching (not recommended for segments)
                         let video_file_clone = video_file.clone();
+                        let encoder = config.video_encoder.clone();
+                        let tmp_dir = config.transcode_tmp_dir.clone();
                         let result = tokio::task::spawn_blocking(move || {
-                            transcode_segment(&video_file_clone, target, segment_index)
+                            transcode_segment(
+                                &video_file_clone,
+                                target,
+                                segment_index,
+                                encoder.as_deref(),
+                                tmp_dir.as_deref(),
+                            )
                         })
                         .await;
 
@@ -3412,11 +4485,7 @@ impl Server {
                             }
                             Ok(Err(e)) => {
                                 tracing::warn!("Segment transcode failed: {}", e);
-                                // Return meaningful error response for known error types
-                                if let Some(response) = build_transcode_error_response(&e) {
-                                    return Some(response);
-                                }
-                                return None;
+                                return Some(Self::build_transcode_error_response(&e));
                             }
                             Err(e) => {
                                 tracing::warn!("Segment transcode task panicked: {}", e);
@@ -3431,11 +4500,286 @@ impl Server {
         None
     }
 
+    /// Build a response for transcoded audio, honoring a single `Range` header.
+    ///
+    /// Safari refuses to play audio unless the server answers range requests
+    /// with `206 Partial Content`, so the in-memory transcode supports them too.
+    #[cfg(feature = "media-metadata")]
+    fn build_audio_transcode_response(
+        data: Arc<Vec<u8>>,
+        format: crate::audio::AudioTranscodeFormat,
+        headers: &HeaderMap,
+    ) -> Response<Body> {
+        let total = data.len();
+        let range = headers
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_single_byte_range(v, total));
+
+        let builder = Response::builder()
+            .header(header::CONTENT_TYPE, format.mime_type())
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE);
+
+        match range {
+            Some((start, end)) => build_response_or_500(
+                builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(
+                        header::CONTENT_RANGE,
+                        format!("bytes {start}-{end}/{total}"),
+                    )
+                    .header(header::CONTENT_LENGTH, end - start + 1)
+                    .body(Body::from(data[start..=end].to_vec())),
+            ),
+            None => build_response_or_500(
+                builder
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_LENGTH, total)
+                    .body(Body::from(data.as_ref().clone())),
+            ),
+        }
+    }
+
+    /// Try to serve an on-demand transcode of a lossless audio file.
+    ///
+    /// Handles `{original}.{ext}` requests (e.g. `song.flac.opus`) for the
+    /// configured codec. The whole file is transcoded once and cached in the
+    /// shared HLS cache; concurrent requests wait for the single transcode.
+    /// Returns None (fall through to 404) if the original doesn't exist or the
+    /// transcode fails.
+    #[cfg(feature = "media-metadata")]
+    async fn try_serve_audio_transcode(
+        path: &str,
+        format: crate::audio::AudioTranscodeFormat,
+        headers: &HeaderMap,
+        config: &ServerState,
+    ) -> Option<Response<Body>> {
+        use crate::audio::parse_audio_transcode_request;
+        use crate::audio_transcode::transcode_audio;
+        use crate::video_transcode_cache::{HLS_WAIT_TIMEOUT, HlsCacheKey, HlsCacheStartResult};
+
+        let audio_path = parse_audio_transcode_request(path, format)?;
+        let Some(audio_file) =
+            resolve_media_source_file(audio_path, &config.base_dir, &config.static_folder)
+        else {
+            tracing::debug!(
+                "Original audio file not found for transcode: {}",
+                audio_path
+            );
+            return None;
+        };
+
+        let bitrate_kbps = config.audio_transcode_bitrate_kbps;
+        let cache_key = HlsCacheKey::audio(audio_file.clone(), format, bitrate_kbps);
+
+        let transcode = || {
+            let audio_file = audio_file.clone();
+            let tmp_dir = config.transcode_tmp_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                transcode_audio(&audio_file, format, bitrate_kbps, tmp_dir.as_deref())
+            })
+        };
+
+        match config.hls_cache.start_generation(cache_key.clone()) {
+            HlsCacheStartResult::Started(notify) => match transcode().await {
+                Ok(Ok(data)) => {
+                    let data = Arc::new(data);
+                    config
+                        .hls_cache
+                        .complete_generation(cache_key, data.as_ref().clone());
+                    notify.notify_waiters();
+                    Some(Self::build_audio_transcode_response(data, format, headers))
+                }
+                Ok(Err(e)) => {
+                    config.hls_cache.fail_generation(cache_key, &e);
+                    notify.notify_waiters();
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Audio transcode task panicked: {}", e);
+                    None
+                }
+            },
+            HlsCacheStartResult::AlreadyInProgress(notify) => {
+                tracing::debug!("Waiting for in-progress audio transcode");
+                let data = config
+                    .hls_cache
+                    .wait_for_completion(&cache_key, notify, HLS_WAIT_TIMEOUT)
+                    .await?;
+                Some(Self::build_audio_transcode_response(data, format, headers))
+            }
+            HlsCacheStartResult::AlreadyComplete(data) => {
+                Some(Self::build_audio_transcode_response(data, format, headers))
+            }
+            HlsCacheStartResult::PreviouslyFailed(msg) => {
+                tracing::debug!("Previous audio transcode failed: {}", msg);
+                None
+            }
+            HlsCacheStartResult::CacheDisabled => match transcode().await {
+                Ok(Ok(data)) => Some(Self::build_audio_transcode_response(
+                    Arc::new(data),
+                    format,
+                    headers,
+                )),
+                Ok(Err(e)) => {
+                    tracing::warn!("Audio transcode failed: {}", e);
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Audio transcode task panicked: {}", e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// A `301` to the target of a page whose frontmatter `redirect_to` sends
+    /// it elsewhere (see [`crate::redirect`]). Answered from the scanned
+    /// frontmatter; the file is only read for a page the scan hasn't seen.
+    async fn redirect_response(md_path: &Path, config: &ServerState) -> Option<Response<Body>> {
+        let scanned = config.repo.markdown_files.pin().get(md_path).map(|info| {
+            info.frontmatter
+                .as_ref()
+                .and_then(|frontmatter| redirect_location(frontmatter, md_path))
+        });
+        let target = match scanned {
+            Some(target) => target?,
+            // Not scanned yet: read the frontmatter from the file
+            None => {
+                let path = md_path.to_path_buf();
+                let normalize_line_endings = config.markdown_options.normalize_line_endings;
+                let metadata = tokio::task::spawn_blocking(move || {
+                    markdown::extract_metadata_from_file(path, normalize_line_endings)
+                })
+                .await
+                .ok()?
+                .ok()?;
+                redirect_location(&metadata.metadata, md_path)?
+            }
+        };
+        tracing::debug!("redirecting {} to {}", md_path.display(), &target);
+        Some(build_response_or_500(
+            Response::builder()
+                .status(StatusCode::MOVED_PERMANENTLY)
+                .header(header::LOCATION, &target)
+                .body(Body::empty()),
+        ))
+    }
+
+    /// The page's HTML from `server_cache_dir`, if a server-cache build wrote
+    /// it after the markdown and the settings were last modified (see
+    /// [`crate::server_cache`]). Anything else returns `None` and renders live.
+    async fn cached_page(md_path: &Path, config: &ServerState) -> Option<Response<Body>> {
+        use crate::server_cache::{CacheMarker, settings_mtime};
+
+        let cache_dir = config.server_cache_dir.clone()?;
+        // Cached pages are rendered for the browser, not the GUI window
+        if config.gui_mode {
+            return None;
+        }
+        let md_path = md_path.to_path_buf();
+        let root = config.base_dir.clone();
+        let template_folder = config.template_folder.clone();
+        let edit_enabled = config.edit_enabled;
+        let (cached_path, marker, source_mtime) = tokio::task::spawn_blocking(move || {
+            let marker = CacheMarker::read(&cache_dir)?;
+            if marker.edit_enabled != edit_enabled {
+                return None;
+            }
+            let page_url =
+                crate::repo::build_markdown_url_path(&md_path, &root, &marker.index_file);
+            let is_index_file = md_path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .is_some_and(|f| f == marker.index_file);
+            let (cached_path, _) = crate::build::page_output_path(
+                &cache_dir,
+                &page_url,
+                is_index_file,
+                marker.clean_urls,
+            );
+            let cached_mtime = std::fs::metadata(&cached_path).ok()?.modified().ok()?;
+            let source_mtime = std::fs::metadata(&md_path).ok()?.modified().ok()?;
+            let settings_mtime = settings_mtime(&root, template_folder.as_deref());
+            if source_mtime > cached_mtime || settings_mtime.is_some_and(|t| t > cached_mtime) {
+                tracing::debug!(
+                    "Cached page is stale (source or settings modified after build), rendering: {}",
+                    md_path.display()
+                );
+                return None;
+            }
+            Some((cached_path, marker, source_mtime))
+        })
+        .await
+        .ok()??;
+
+        let mut html = tokio::fs::read_to_string(&cached_path).await.ok()?;
+        match (&marker.csp_nonce, config.csp.as_deref()) {
+            (Some(nonce), Some(csp)) => html = html.replace(nonce.as_str(), &csp.nonce),
+            (None, None) => {}
+            // Pages built with a different policy setting would be blocked
+            _ => return None,
+        }
+        tracing::debug!("Serving cached page: {}", cached_path.display());
+        let mut builder = Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+            .header(header::ETAG, generate_etag(html.as_bytes()));
+        if let Some(lm) = source_mtime
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .and_then(|d| generate_last_modified(d.as_secs()))
+        {
+            builder = builder.header(header::LAST_MODIFIED, lm);
+        }
+        builder.body(Body::from(html)).ok()
+    }
+
     async fn markdown_to_html(
         md_path: &Path,
         config: &ServerState,
     ) -> Result<Response<Body>, MbrError> {
         let root_path = config.base_dir.as_path();
+        if let Some(response) = Self::redirect_response(md_path, config).await {
+            return Ok(response);
+        }
+        if let Some(response) = Self::cached_page(md_path, config).await {
+            return Ok(response);
+        }
+        let file_size = tokio::fs::metadata(md_path).await.map(|m| m.len()).ok();
+
+        if config.max_render_bytes > 0
+            && let Some(size) = file_size.filter(|&size| size > config.max_render_bytes)
+        {
+            tracing::warn!(
+                "Not rendering {}: {size} bytes exceeds max_render_bytes ({})",
+                md_path.display(),
+                config.max_render_bytes
+            );
+            let message = format!(
+                "This file is {}, which is over the {} preview limit (max_render_bytes).",
+                format_megabytes(size),
+                format_megabytes(config.max_render_bytes)
+            );
+            return Ok(Self::render_error_page(
+                &config.templates,
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "File Too Large to Preview",
+                Some(&message),
+                &crate::repo::build_markdown_url_path(md_path, root_path, &config.index_file),
+                config.gui_mode,
+                &config.sidebar_style,
+                config.sidebar_max_items,
+                config.csp.as_deref(),
+            ));
+        }
+
+        // Wait for a render slot. Streamed pages keep theirs until the
+        // renderer has produced the whole body. The semaphore is never
+        // closed, so acquiring can't fail.
+        let render_permit = config.render_permits.clone().acquire_owned().await.ok();
 
         // Determine if this is an index file (which doesn't need ../ prefix for links)
         let is_index_file = md_path
@@ -3443,16 +4787,14 @@ impl Server {
             .and_then(|f| f.to_str())
             .is_some_and(|f| f == config.index_file);
 
+        let page_url = crate::repo::build_markdown_url_path(md_path, root_path, &config.index_file);
         let link_transform_config = LinkTransformConfig {
             markdown_extensions: config.markdown_extensions.clone(),
             index_file: config.index_file.clone(),
             is_index_file,
             url_depth: None,
-            current_page_url: crate::repo::build_markdown_url_path(
-                md_path,
-                root_path,
-                &config.index_file,
-            ),
+            current_page_url: page_url.clone(),
+            clean_urls: true,
         };
 
         // Transcoding is only available with media-metadata feature
@@ -3462,26 +4804,70 @@ impl Server {
         let transcode_enabled = false;
 
         let valid_tag_sources = crate::config::tag_sources_to_set(&config.tag_sources);
-        let render_result = markdown::render_with_cache(
-            md_path.to_path_buf(),
-            root_path,
-            config.oembed_timeout_ms,
-            link_transform_config,
-            Some(config.oembed_cache.clone()),
-            true, // server_mode is always true in server
-            transcode_enabled,
-            valid_tag_sources,
-            config.mark_incomplete,
-            &config.incomplete_markers,
-            Some(config.repo.wikilink_index.clone()),
-        )
-        .await
-        .inspect_err(|e| tracing::error!("Error rendering markdown: {e}"))?;
+        let renderer = markdown::RendererKind::for_path(md_path, &config.renderers);
+
+        // Very large files are streamed: the page shell goes out as soon as
+        // the document is processed and the body follows as it renders.
+        let stream = config.stream_render_threshold > 0
+            && file_size.is_some_and(|size| size >= config.stream_render_threshold);
+        let (mut render_result, body_rx) = if stream {
+            let streaming = markdown::render_streaming(
+                md_path.to_path_buf(),
+                root_path,
+                config.oembed_timeout_ms,
+                config.oembed_fallback,
+                link_transform_config,
+                Some(config.oembed_cache.clone()),
+                true, // server_mode is always true in server
+                transcode_enabled,
+                valid_tag_sources,
+                config.mark_incomplete,
+                &config.incomplete_markers,
+                Some(config.repo.wikilink_index.clone()),
+                config.data_files.clone(),
+                renderer,
+                &config.markdown_options,
+                crate::constants::STREAM_RENDER_CHUNK_BYTES,
+            )
+            .await
+            .inspect_err(|e| tracing::error!("Error rendering markdown: {e}"))?;
+            let mut body_rx = streaming.body;
+            let mut result = streaming.result;
+            // The title H1 is near the top, so only the first chunk is deduped
+            result.html = body_rx.recv().await.unwrap_or_default();
+            (result, Some(body_rx))
+        } else {
+            let result = markdown::render_with_cache(
+                md_path.to_path_buf(),
+                root_path,
+                config.oembed_timeout_ms,
+                config.oembed_fallback,
+                link_transform_config,
+                Some(config.oembed_cache.clone()),
+                true, // server_mode is always true in server
+                transcode_enabled,
+                valid_tag_sources,
+                config.mark_incomplete,
+                &config.incomplete_markers,
+                Some(config.repo.wikilink_index.clone()),
+                config.data_files.clone(),
+                renderer,
+                &config.markdown_options,
+            )
+            .await
+            .inspect_err(|e| tracing::error!("Error rendering markdown: {e}"))?;
+            (result, None)
+        };
+        if config.dedupe_title_h1 {
+            render_result.dedupe_title_h1();
+        }
         let mut frontmatter = render_result.frontmatter;
-        let headings = render_result.headings;
-        let inner_html_output = render_result.html;
+        let headings = config.toc.apply(render_result.headings);
+        let mut post_process = BodyPostProcess::new(config, page_url);
+        let mut inner_html_output = post_process.apply(render_result.html);
         let outbound_links = render_result.outbound_links;
         let has_h1 = render_result.has_h1;
+        let title_source = render_result.title_source;
         let word_count = render_result.word_count;
         let readability_counts = crate::readability::ReadabilityCounts {
             words: render_result.word_count,
@@ -3588,23 +4974,77 @@ impl Server {
                 breadcrumb_path: &url_path_buf,
                 headings: &headings,
                 has_h1,
+                title_source,
                 word_count,
                 readability: &readability_scores,
                 file_path: &relative_md_path.to_string_lossy(),
                 modified_secs,
+                last_commit: None,
+                dates: crate::dates::PageDates::new(&frontmatter, modified_secs),
+                robots: Robots::for_page(&frontmatter, config.robots.as_deref()),
+                assets: PageAssets::for_page_sanitized(&frontmatter, config.sanitize_html),
                 current_url: &current_url,
-                siblings: &siblings,
+                siblings: crate::reading_order::ReadingOrder::sequence_for(
+                    config.reading_order.as_ref(),
+                    &current_url,
+                    &siblings,
+                ),
             },
             &page_context::MarkdownContextOptions {
                 tag_sources: &config.tag_sources,
                 sidebar_style: &config.sidebar_style,
                 sidebar_max_items: config.sidebar_max_items,
+                header_height: config.header_height.as_deref(),
+                smooth_scroll: config.smooth_scroll,
+                search: SearchUi {
+                    placeholder: &config.search_placeholder,
+                    result_limit: config.search_result_limit,
+                },
+                language: SiteLanguage {
+                    lang: &config.site_lang,
+                    dir: &config.site_dir,
+                },
+                branding: &config.branding,
                 title_prefix: &config.title_prefix,
                 title_suffix: &config.title_suffix,
+                site_nav: config.site_nav.as_deref(),
+                interactive_tasklists: config.interactive_tasklists,
+                scroll_spy: config.scroll_spy,
+                edit_url_template: config.edit_url_template.as_deref(),
+                privacy_embeds: config.privacy_embeds,
+                csp: config.csp.as_deref(),
+                mermaid_config: &config.mermaid_config,
             },
             &page_context::UrlMode::Absolute,
         );
 
+        if let Some(mut body_rx) = body_rx {
+            let shell = config
+                .templates
+                .render_markdown(
+                    STREAM_BODY_MARKER,
+                    frontmatter.clone(),
+                    extra_context.clone(),
+                )
+                .inspect_err(|e| tracing::error!("Error rendering template: {e}"))?;
+            if let Some((head, tail)) = shell.split_once(STREAM_BODY_MARKER) {
+                return streamed_markdown_response(
+                    head.to_string() + &inner_html_output,
+                    body_rx,
+                    post_process,
+                    tail.to_string(),
+                    md_path,
+                    render_permit,
+                )
+                .await;
+            }
+            // The template doesn't place the body anywhere we can find it;
+            // wait for the rest and render the page normally.
+            while let Some(chunk) = body_rx.recv().await {
+                inner_html_output.push_str(&post_process.apply(chunk));
+            }
+        }
+
         let full_html_output = config
             .templates
             .render_markdown(&inner_html_output, frontmatter, extra_context)
@@ -3664,6 +5104,8 @@ impl Server {
             let tag_sources = config.tag_sources.clone();
             let relationship_types = config.relationship_types.clone();
             let sort = config.sort.clone();
+            let gitignore = config.repo.gitignore().cloned();
+            let follow_symlinks = config.repo.follow_symlinks();
 
             let scan_result = tokio::task::spawn_blocking(move || {
                 // Create a temporary repo instance to scan this directory
@@ -3676,7 +5118,9 @@ impl Server {
                     &index_file,
                     &tag_sources,
                     &relationship_types,
-                );
+                )
+                .with_gitignore(gitignore)
+                .with_follow_symlinks(follow_symlinks);
 
                 // Scan this directory only (non-recursive)
                 temp_repo.scan_folder(&relative_path).inspect_err(|e| {
@@ -3713,6 +5157,7 @@ impl Server {
                             }
                             Some(json!({
                                 "name": name,
+                                "url": crate::link_transform::encode_url_path(&url_path),
                                 "url_path": url_path,
                             }))
                         } else {
@@ -3783,7 +5228,21 @@ impl Server {
                 },
                 sidebar_style: &config.sidebar_style,
                 sidebar_max_items: config.sidebar_max_items,
+                header_height: config.header_height.as_deref(),
+                smooth_scroll: config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &config.search_placeholder,
+                    result_limit: config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &config.site_lang,
+                    dir: &config.site_dir,
+                }),
+                branding: Some(&config.branding),
+                robots: config.robots.as_deref(),
+                csp: config.csp.as_deref(),
                 title_affixes: Some((&config.title_prefix, &config.title_suffix)),
+                site_nav: config.site_nav.as_deref(),
             },
         );
 
@@ -3794,6 +5253,12 @@ impl Server {
         // Add is_home to context for template conditional rendering
         context.insert("is_home".to_string(), json!(is_root));
 
+        page_context::insert_listing_layout(
+            &mut context,
+            &config.listing_layout,
+            config.listing_thumbnails,
+        );
+
         let full_html_output = if is_root {
             templates
                 .render_home(context)
@@ -3850,6 +5315,17 @@ impl Server {
             &pages,
             &UrlMode::Absolute,
         );
+        let data_name = page_context::tag_data_name(&config.tag_sources, source);
+        if let Some(name) = data_name {
+            page_context::insert_author_keys(
+                &mut context,
+                config
+                    .data_files
+                    .as_deref()
+                    .and_then(|data| data.tag_entry(name, value)),
+                &UrlMode::Absolute,
+            );
+        }
         page_context::insert_page_chrome(
             &mut context,
             &PageChrome {
@@ -3859,11 +5335,28 @@ impl Server {
                 },
                 sidebar_style: &config.sidebar_style,
                 sidebar_max_items: config.sidebar_max_items,
+                header_height: config.header_height.as_deref(),
+                smooth_scroll: config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &config.search_placeholder,
+                    result_limit: config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &config.site_lang,
+                    dir: &config.site_dir,
+                }),
+                branding: Some(&config.branding),
+                robots: config.robots.as_deref(),
+                csp: config.csp.as_deref(),
                 title_affixes: Some((&config.title_prefix, &config.title_suffix)),
+                site_nav: config.site_nav.as_deref(),
             },
         );
 
-        let html_output = config.templates.render_tag(context)?;
+        let html_output = match data_name {
+            Some(_) => config.templates.render_author(context)?,
+            None => config.templates.render_tag(context)?,
+        };
 
         let etag = generate_etag(html_output.as_bytes());
 
@@ -3900,7 +5393,21 @@ impl Server {
                 },
                 sidebar_style: &config.sidebar_style,
                 sidebar_max_items: config.sidebar_max_items,
+                header_height: config.header_height.as_deref(),
+                smooth_scroll: config.smooth_scroll,
+                search: Some(SearchUi {
+                    placeholder: &config.search_placeholder,
+                    result_limit: config.search_result_limit,
+                }),
+                language: Some(SiteLanguage {
+                    lang: &config.site_lang,
+                    dir: &config.site_dir,
+                }),
+                branding: Some(&config.branding),
+                robots: config.robots.as_deref(),
+                csp: config.csp.as_deref(),
                 title_affixes: Some((&config.title_prefix, &config.title_suffix)),
+                site_nav: config.site_nav.as_deref(),
             },
         );
 
@@ -3979,95 +5486,30 @@ impl Server {
 // Pure helper functions for directory listing (extracted for testability)
 // ============================================================================
 
-/// A breadcrumb entry for navigation.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
-pub struct Breadcrumb {
-    pub name: String,
-    pub url: String,
-}
-
-impl Breadcrumb {
-    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
-        Self {
-            name: name.into(),
-            url: url.into(),
-        }
-    }
-}
-
-/// Generates breadcrumb navigation from a relative path.
-///
-/// Always starts with "Home" → "/" and includes all path components.
-/// The last component is not included in the returned breadcrumbs (it's the current page).
-pub fn generate_breadcrumbs(relative_path: &Path) -> Vec<Breadcrumb> {
-    let path_components: Vec<_> = relative_path
-        .components()
-        .filter_map(|c| {
-            if let std::path::Component::Normal(s) = c {
-                s.to_str()
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // For root (no path components), return empty breadcrumbs
-    // The current page name will be shown separately, avoiding "Home > Home"
-    if path_components.is_empty() {
-        return vec![];
-    }
-
-    // Start with Home
-    let mut breadcrumbs = vec![Breadcrumb::new("Home", "/")];
-
-    // Add all but the last component (last is current page/directory)
-    for (idx, _) in path_components
-        .iter()
-        .enumerate()
-        .take(path_components.len().saturating_sub(1))
-    {
-        let partial_path: std::path::PathBuf = path_components.iter().take(idx + 1).collect();
-        let url = format!("/{}/", partial_path.to_string_lossy());
-        let name = path_components[idx].to_string();
-        breadcrumbs.push(Breadcrumb::new(name, url));
-    }
-
-    breadcrumbs
-}
-
-/// Gets the current directory name from a relative path.
-pub fn get_current_dir_name(relative_path: &Path) -> String {
-    relative_path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .map(String::from)
-        .unwrap_or_else(|| "Home".to_string())
+/// Response body of `/.mbr/transcode-status`.
+#[cfg(feature = "media-metadata")]
+#[derive(Debug, serde::Serialize)]
+struct TranscodeStatus {
+    path: String,
+    transcode_enabled: bool,
+    /// Whether HLS variants are actually served for this source
+    transcodable: bool,
+    master_playlist: Option<String>,
+    resolution: Option<crate::video_transcode::VideoResolution>,
+    targets: Vec<TranscodeTargetStatus>,
+    /// Why the source couldn't be probed, if it couldn't
+    error: Option<crate::video_transcode::TranscodeErrorInfo>,
 }
 
-/// Gets the parent path URL for "up" navigation.
-pub fn get_parent_path(relative_path: &Path) -> Option<String> {
-    let path_components: Vec<_> = relative_path
-        .components()
-        .filter_map(|c| {
-            if let std::path::Component::Normal(s) = c {
-                s.to_str()
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    if path_components.len() > 1 {
-        let parent: std::path::PathBuf = path_components
-            .iter()
-            .take(path_components.len() - 1)
-            .collect();
-        Some(format!("/{}/", parent.to_string_lossy()))
-    } else if !path_components.is_empty() {
-        Some("/".to_string())
-    } else {
-        None
-    }
+/// A transcode target that applies to a source.
+#[cfg(feature = "media-metadata")]
+#[derive(Debug, serde::Serialize)]
+struct TranscodeTargetStatus {
+    name: &'static str,
+    width: u32,
+    height: u32,
+    bandwidth: usize,
+    playlist: String,
 }
 
 /// Builds the sorted list of sibling markdown files that share `parent_dir`.
@@ -4092,60 +5534,6 @@ fn compute_sibling_files<'a>(
     siblings
 }
 
-/// Transforms markdown file info into a JSON value for template rendering.
-pub fn markdown_file_to_json(file_info: &MarkdownInfo) -> serde_json::Value {
-    use serde_json::json;
-
-    let title = file_info
-        .frontmatter
-        .as_ref()
-        .and_then(|fm| fm.get("title"))
-        .cloned()
-        .unwrap_or_else(|| {
-            serde_json::Value::String(
-                file_info
-                    .raw_path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Untitled")
-                    .to_string(),
-            )
-        });
-
-    let description = file_info
-        .frontmatter
-        .as_ref()
-        .and_then(|fm| fm.get("description"))
-        .cloned();
-
-    let tags = file_info
-        .frontmatter
-        .as_ref()
-        .and_then(|fm| fm.get("tags"))
-        .cloned();
-
-    let note_type = file_info
-        .frontmatter
-        .as_ref()
-        .and_then(|fm| fm.get("type"))
-        .cloned();
-
-    let modified_date = chrono::DateTime::from_timestamp(file_info.modified as i64, 0)
-        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    json!({
-        "title": title,
-        "url_path": file_info.url_path,
-        "description": description,
-        "tags": tags,
-        "type": note_type,
-        "modified_date": modified_date,
-        "modified": file_info.modified,
-        "name": file_info.raw_path.file_name().and_then(|s| s.to_str()).unwrap_or(""),
-    })
-}
-
 // ============================================================================
 // Cache header helpers (extracted for testability and reuse)
 // ============================================================================
@@ -4162,6 +5550,24 @@ fn capitalize_first(s: &str) -> String {
     }
 }
 
+/// Formats a byte count as megabytes for messages ("64.0 MB").
+fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// The `redirect_to` target in a page's frontmatter, if it has a valid one.
+/// An invalid target is logged and ignored.
+fn redirect_location(frontmatter: &markdown::SimpleMetadata, md_path: &Path) -> Option<String> {
+    if redirect::has_invalid_target(frontmatter) {
+        tracing::warn!(
+            "Ignoring redirect_to in {}: expected a root-relative path or http(s) URL",
+            md_path.display()
+        );
+        return None;
+    }
+    redirect::redirect_target(frontmatter)
+}
+
 /// Finalizes an HTTP response builder, falling back to a plain 500 response
 /// if the builder was misconfigured (e.g. an invalid header value).
 ///
@@ -4189,6 +5595,51 @@ fn generate_etag(content: &[u8]) -> String {
     format!("W/\"{:x}\"", hash)
 }
 
+/// True when the request's `If-None-Match` lists `etag`, so the client's
+/// copy is current.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag == etag
+            })
+        })
+}
+
+/// Responds with an image from the [`ImageCache`], dated by its source file,
+/// or with 304 Not Modified when the client already has it.
+fn generated_image_response(
+    bytes: Bytes,
+    mime: &str,
+    key: &ImageCacheKey,
+    headers: &HeaderMap,
+) -> Response<Body> {
+    let etag = generate_etag(&bytes);
+    let last_modified = key
+        .modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .and_then(|d| generate_last_modified(d.as_secs()));
+    let mut builder = Response::builder()
+        .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE)
+        .header(header::ETAG, &etag);
+    if let Some(lm) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, lm);
+    }
+    if etag_matches(headers, &etag) {
+        return build_response_or_500(builder.status(StatusCode::NOT_MODIFIED).body(Body::empty()));
+    }
+    build_response_or_500(
+        builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, mime)
+            .body(Body::from(bytes)),
+    )
+}
+
 /// Generates a Last-Modified header value from a Unix timestamp.
 fn generate_last_modified(timestamp: u64) -> Option<String> {
     chrono::DateTime::from_timestamp(timestamp as i64, 0)
@@ -4202,6 +5653,165 @@ const CACHE_CONTROL_NO_CACHE: &str = "no-cache";
 /// Standard cache control header for truly dynamic content that shouldn't be cached.
 const CACHE_CONTROL_NO_STORE: &str = "no-store";
 
+/// Cache control header for content-hashed files, whose contents never change
+/// under the same name.
+const CACHE_CONTROL_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Extensions of the content-hashed files in a Pagefind bundle (the names
+/// embed a hash of their contents, so a new index gets new names).
+const HASHED_SEARCH_EXTENSIONS: &[&str] = &["pf_fragment", "pf_index", "pf_filter", "pf_meta"];
+
+/// Whether `path` is inside a built site's Pagefind bundle (`.mbr/pagefind/`).
+fn is_search_asset(path: &Path) -> bool {
+    let components: Vec<_> = path.components().map(|c| c.as_os_str()).collect();
+    components
+        .windows(2)
+        .any(|pair| pair[0] == ".mbr" && pair[1] == "pagefind")
+}
+
+/// Whether `path` is a content-hashed Pagefind file that can be cached forever.
+fn is_hashed_search_asset(path: &Path) -> bool {
+    is_search_asset(path)
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| HASHED_SEARCH_EXTENSIONS.contains(&ext))
+}
+
+/// Stands in for the body when rendering the page shell of a streamed page,
+/// marking where the template places it.
+const STREAM_BODY_MARKER: &str = "<!--mbr-stream-body-->";
+
+/// The server's rewrites of rendered body HTML, applied to the whole body or,
+/// for a streamed page, to each chunk in order.
+struct BodyPostProcess {
+    #[cfg(feature = "media-metadata")]
+    audio_transcode: Option<crate::audio::AudioTranscodeFormat>,
+    /// Where transcoded audio sources are looked up.
+    #[cfg(feature = "media-metadata")]
+    base_dir: PathBuf,
+    #[cfg(feature = "media-metadata")]
+    static_folder: String,
+    post_render_hooks: Vec<Arc<dyn PostRenderHook>>,
+    /// URL of the page being rendered, for the hooks.
+    url_path: String,
+    lazy_images: bool,
+    seen_first_image: bool,
+    privacy_embeds: bool,
+    media_max_width: Option<String>,
+}
+
+impl BodyPostProcess {
+    fn new(config: &ServerState, url_path: String) -> Self {
+        Self {
+            #[cfg(feature = "media-metadata")]
+            audio_transcode: config.audio_transcode,
+            #[cfg(feature = "media-metadata")]
+            base_dir: config.base_dir.clone(),
+            #[cfg(feature = "media-metadata")]
+            static_folder: config.static_folder.clone(),
+            post_render_hooks: config.post_render_hooks.clone(),
+            url_path,
+            lazy_images: config.lazy_images,
+            seen_first_image: false,
+            privacy_embeds: config.privacy_embeds,
+            media_max_width: config.media_max_width.clone(),
+        }
+    }
+
+    fn apply(&mut self, html: String) -> String {
+        // Offer the transcoded variant ahead of lossless audio sources the
+        // server can find, so remote and missing files aren't offered
+        #[cfg(feature = "media-metadata")]
+        let html = match self.audio_transcode {
+            Some(format) => crate::audio::add_transcoded_sources(&html, |src| {
+                let path = crate::audio::local_source_path(src, &self.url_path)?;
+                resolve_media_source_file(&path, &self.base_dir, &self.static_folder)
+                    .map(|_| format)
+            }),
+            None => html,
+        };
+        let html = crate::post_render::apply_hooks(
+            &self.post_render_hooks,
+            html,
+            &PostRenderPage {
+                url_path: &self.url_path,
+                server_mode: true,
+            },
+        );
+        let html = if self.privacy_embeds {
+            crate::media::privacy_embeds(&html)
+        } else {
+            html
+        };
+        let html = match &self.media_max_width {
+            Some(max_width) => crate::media::constrain_media_width(&html, max_width),
+            None => html,
+        };
+        if self.lazy_images {
+            crate::media::add_lazy_loading_after(&html, &mut self.seen_first_image)
+        } else {
+            html
+        }
+    }
+}
+
+/// Responds with `head`, then each body chunk from `body_rx` as it arrives,
+/// then `tail`.
+///
+/// There's no ETag since the page isn't known up front; Last-Modified still
+/// lets the browser revalidate.
+async fn streamed_markdown_response(
+    head: String,
+    body_rx: tokio::sync::mpsc::Receiver<String>,
+    post_process: BodyPostProcess,
+    tail: String,
+    md_path: &Path,
+    render_permit: Option<tokio::sync::OwnedSemaphorePermit>,
+) -> Result<Response<Body>, MbrError> {
+    use futures::StreamExt;
+
+    // Drain the renderer as fast as it produces, whatever the client's pace,
+    // and release the render slot as soon as it has sent its last chunk.
+    // Buffering is bounded by `max_render_bytes`.
+    let (page_tx, page_rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut body_rx = body_rx;
+        let mut post_process = post_process;
+        while let Some(chunk) = body_rx.recv().await {
+            // A gone client doesn't stop the render; it ends on its own
+            let _ = page_tx.send(post_process.apply(chunk));
+        }
+        drop(render_permit);
+    });
+    let body = futures::stream::unfold(page_rx, |mut page_rx| async move {
+        let chunk = page_rx.recv().await?;
+        Some((chunk, page_rx))
+    });
+    let page = futures::stream::once(async { head })
+        .chain(body)
+        .chain(futures::stream::once(async { tail }))
+        .map(Ok::<_, std::convert::Infallible>);
+
+    let last_modified = tokio::fs::metadata(md_path)
+        .await
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .and_then(|d| generate_last_modified(d.as_secs()));
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, CACHE_CONTROL_NO_CACHE);
+    if let Some(lm) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, lm);
+    }
+    builder
+        .body(Body::from_stream(page))
+        .map_err(MbrError::from)
+}
+
 pub const DEFAULT_FILES: &[(&str, &[u8], &str)] = &[
     (
         "/favicon.png",
@@ -4401,6 +6011,7 @@ fn build_tag_page_outbound_links(
             text: page.title,
             anchor: None,
             internal: true,
+            context: None,
         });
     }
 
@@ -4416,6 +6027,7 @@ fn build_tag_page_outbound_links(
         text: label,
         anchor: None,
         internal: true,
+        context: None,
     });
 
     outbound
@@ -4438,6 +6050,7 @@ fn build_tag_index_outbound_links(
             text: tag.display,
             anchor: None,
             internal: true,
+            context: None,
         })
         .collect()
 }
@@ -4448,6 +6061,33 @@ mod tests {
     use std::collections::HashMap;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_cache_settings_propagate_from_config() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".mbr")).unwrap();
+        std::fs::write(
+            repo.path().join(".mbr/config.toml"),
+            "oembed_cache_ttl_secs = 3600\ninbound_cache_size = 4096\ninbound_cache_ttl_secs = 5\n",
+        )
+        .unwrap();
+        let config = crate::config::Config::read(repo.path()).unwrap();
+        let server_config = ServerConfig::from(&config);
+        assert_eq!(server_config.oembed_cache_ttl_secs, 3600);
+        assert_eq!(server_config.inbound_cache_size, 4096);
+        assert_eq!(server_config.inbound_cache_ttl_secs, 5);
+
+        let defaults = ServerConfig::from(&crate::config::Config::default());
+        assert_eq!(defaults.oembed_cache_ttl_secs, 0);
+        assert_eq!(
+            defaults.inbound_cache_size,
+            crate::link_grep::DEFAULT_INBOUND_CACHE_SIZE
+        );
+        assert_eq!(
+            defaults.inbound_cache_ttl_secs,
+            crate::link_grep::DEFAULT_INBOUND_CACHE_TTL_SECS
+        );
+    }
+
     #[test]
     fn test_capitalize_first_ascii() {
         assert_eq!(capitalize_first("tags"), "Tags");
@@ -4469,177 +6109,6 @@ mod tests {
         assert_eq!(capitalize_first(""), "");
     }
 
-    #[test]
-    fn test_generate_breadcrumbs_root() {
-        let path = Path::new("");
-        let breadcrumbs = generate_breadcrumbs(path);
-
-        // Root returns empty breadcrumbs to avoid "Home > Home" duplication
-        // The template handles showing just "Home" as the current page
-        assert_eq!(breadcrumbs.len(), 0);
-    }
-
-    #[test]
-    fn test_generate_breadcrumbs_single_level() {
-        let path = Path::new("docs");
-        let breadcrumbs = generate_breadcrumbs(path);
-
-        // Home only - "docs" is the current directory, not shown in breadcrumbs
-        assert_eq!(breadcrumbs.len(), 1);
-        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
-    }
-
-    #[test]
-    fn test_generate_breadcrumbs_two_levels() {
-        let path = Path::new("docs/api");
-        let breadcrumbs = generate_breadcrumbs(path);
-
-        assert_eq!(breadcrumbs.len(), 2);
-        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
-        assert_eq!(breadcrumbs[1], Breadcrumb::new("docs", "/docs/"));
-    }
-
-    #[test]
-    fn test_generate_breadcrumbs_deep_nesting() {
-        let path = Path::new("/a/b/c/d");
-        let breadcrumbs = generate_breadcrumbs(path);
-
-        assert_eq!(breadcrumbs.len(), 4);
-        assert_eq!(breadcrumbs[0], Breadcrumb::new("Home", "/"));
-        assert_eq!(breadcrumbs[1], Breadcrumb::new("a", "/a/"));
-        assert_eq!(breadcrumbs[2], Breadcrumb::new("b", "/a/b/"));
-        assert_eq!(breadcrumbs[3], Breadcrumb::new("c", "/a/b/c/"));
-    }
-
-    #[test]
-    fn test_get_current_dir_name_root() {
-        let path = Path::new("");
-        assert_eq!(get_current_dir_name(path), "Home");
-    }
-
-    #[test]
-    fn test_get_current_dir_name_single_level() {
-        let path = Path::new("docs");
-        assert_eq!(get_current_dir_name(path), "docs");
-    }
-
-    #[test]
-    fn test_get_current_dir_name_nested() {
-        let path = Path::new("a/b/c");
-        assert_eq!(get_current_dir_name(path), "c");
-    }
-
-    #[test]
-    fn test_get_parent_path_root() {
-        let path = Path::new("");
-        assert_eq!(get_parent_path(path), None);
-    }
-
-    #[test]
-    fn test_get_parent_path_single_level() {
-        let path = Path::new("docs");
-        assert_eq!(get_parent_path(path), Some("/".to_string()));
-    }
-
-    #[test]
-    fn test_get_parent_path_two_levels() {
-        let path = Path::new("docs/api");
-        assert_eq!(get_parent_path(path), Some("/docs/".to_string()));
-    }
-
-    #[test]
-    fn test_get_parent_path_deep() {
-        let path = Path::new("a/b/c/d");
-        assert_eq!(get_parent_path(path), Some("/a/b/c/".to_string()));
-    }
-
-    #[test]
-    fn test_markdown_file_to_json_with_frontmatter() {
-        let mut frontmatter = HashMap::new();
-        frontmatter.insert(
-            "title".to_string(),
-            serde_json::Value::String("My Title".to_string()),
-        );
-        frontmatter.insert(
-            "description".to_string(),
-            serde_json::Value::String("My description".to_string()),
-        );
-        frontmatter.insert("tags".to_string(), serde_json::json!(["rust", "testing"]));
-
-        let file_info = MarkdownInfo {
-            raw_path: PathBuf::from("/root/test.md"),
-            url_path: "/test/".to_string(),
-            frontmatter: Some(frontmatter),
-            created: 1699000000,
-            modified: 1700000000,
-            relationships: Vec::new(),
-        };
-
-        let json = markdown_file_to_json(&file_info);
-
-        assert_eq!(json["title"], "My Title");
-        assert_eq!(json["url_path"], "/test/");
-        assert_eq!(json["description"], "My description");
-        assert_eq!(json["tags"], serde_json::json!(["rust", "testing"]));
-        assert_eq!(json["modified"], 1700000000);
-        assert_eq!(json["name"], "test.md");
-    }
-
-    #[test]
-    fn test_markdown_file_to_json_without_frontmatter() {
-        let file_info = MarkdownInfo {
-            raw_path: PathBuf::from("/root/my-document.md"),
-            url_path: "/my-document/".to_string(),
-            frontmatter: None,
-            created: 1699000000,
-            modified: 1700000000,
-            relationships: Vec::new(),
-        };
-
-        let json = markdown_file_to_json(&file_info);
-
-        // Should use file stem as title when no frontmatter
-        assert_eq!(json["title"], "my-document");
-        assert_eq!(json["url_path"], "/my-document/");
-        assert!(json["description"].is_null());
-        assert!(json["tags"].is_null());
-    }
-
-    #[test]
-    fn test_markdown_file_to_json_partial_frontmatter() {
-        let mut frontmatter = HashMap::new();
-        frontmatter.insert(
-            "title".to_string(),
-            serde_json::Value::String("Only Title".to_string()),
-        );
-        // No description or tags
-
-        let file_info = MarkdownInfo {
-            raw_path: PathBuf::from("/root/partial.md"),
-            url_path: "/partial/".to_string(),
-            frontmatter: Some(frontmatter),
-            created: 1699000000,
-            modified: 1700000000,
-            relationships: Vec::new(),
-        };
-
-        let json = markdown_file_to_json(&file_info);
-
-        assert_eq!(json["title"], "Only Title");
-        assert!(json["description"].is_null());
-        assert!(json["tags"].is_null());
-    }
-
-    #[test]
-    fn test_breadcrumb_equality() {
-        let b1 = Breadcrumb::new("Home", "/");
-        let b2 = Breadcrumb::new("Home", "/");
-        let b3 = Breadcrumb::new("Docs", "/docs/");
-
-        assert_eq!(b1, b2);
-        assert_ne!(b1, b3);
-    }
-
     // ==================== MediaViewerType Tests ====================
 
     #[test]
@@ -5143,10 +6612,16 @@ mod tests {
         MarkdownInfo {
             raw_path: PathBuf::from(raw),
             url_path: url.to_string(),
+            url: url.to_string(),
             frontmatter: Some(frontmatter),
             created: 0,
             modified: 0,
+            word_count: 0,
+            date: None,
+            created_date: None,
+            updated_date: None,
             relationships: Vec::new(),
+            cover_image: None,
         }
     }
 
@@ -5288,6 +6763,73 @@ mod tests {
 
         assert!(Server::metadata_response_from_cache(CachedMetadata::NotAvailable).is_none());
     }
+
+    #[cfg(feature = "media-metadata")]
+    #[test]
+    fn test_parse_single_byte_range() {
+        assert_eq!(parse_single_byte_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(
+            parse_single_byte_range("bytes=500-", 1000),
+            Some((500, 999))
+        );
+        assert_eq!(
+            parse_single_byte_range("bytes=-100", 1000),
+            Some((900, 999))
+        );
+        // End is clamped to the body length
+        assert_eq!(
+            parse_single_byte_range("bytes=900-5000", 1000),
+            Some((900, 999))
+        );
+        assert_eq!(parse_single_byte_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_single_byte_range("bytes=0-1,5-9", 1000), None);
+        assert_eq!(parse_single_byte_range("items=0-1", 1000), None);
+    }
+
+    #[test]
+    fn test_search_asset_detection() {
+        let site = Path::new("/srv/site");
+        assert!(is_search_asset(&site.join(".mbr/pagefind/pagefind.js")));
+        assert!(!is_hashed_search_asset(
+            &site.join(".mbr/pagefind/pagefind.js")
+        ));
+        assert!(is_hashed_search_asset(
+            &site.join(".mbr/pagefind/index/en_9f8e7d.pf_index")
+        ));
+        assert!(is_hashed_search_asset(
+            &site.join(".mbr/pagefind/pagefind.en_9f8e7d.pf_meta")
+        ));
+        assert!(!is_search_asset(&site.join("pagefind/pagefind.js")));
+        assert!(!is_hashed_search_asset(&site.join("docs/notes.pf_index")));
+    }
+
+    #[cfg(feature = "media-metadata")]
+    #[test]
+    fn test_audio_transcode_response_honors_range() {
+        use crate::audio::AudioTranscodeFormat;
+
+        let data = Arc::new((0u8..100).collect::<Vec<_>>());
+        let mut headers = HeaderMap::new();
+        let full = Server::build_audio_transcode_response(
+            data.clone(),
+            AudioTranscodeFormat::Opus,
+            &headers,
+        );
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(
+            full.headers().get(header::CONTENT_TYPE).unwrap(),
+            "audio/ogg; codecs=opus"
+        );
+
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=10-19"));
+        let partial =
+            Server::build_audio_transcode_response(data, AudioTranscodeFormat::Opus, &headers);
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            partial.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 10-19/100"
+        );
+    }
 }
 
 #[cfg(test)]
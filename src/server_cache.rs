@@ -0,0 +1,96 @@
+//! Pages pre-rendered for the server (`server_cache_dir`).
+//!
+//! A static build whose output folder is the configured `server_cache_dir`
+//! renders its pages the way the server would: absolute links, live reload,
+//! page errors and the server's search. It then writes [`CacheMarker`] to
+//! the folder. The server only serves cached pages from a folder carrying
+//! the marker, so an ordinary static build pointed at by mistake is ignored.
+//!
+//! A cached page is served while it's newer than its markdown and than
+//! every file in the settings folder (`.mbr/`, which holds the config,
+//! templates and theme) and `template_folder`. Anything else renders live.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Marker file, relative to the cache folder.
+pub const MARKER_FILE: &str = ".mbr/server-cache.json";
+
+/// What a server-cache build recorded about its pages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheMarker {
+    /// The build's `clean_urls`, which decides where each page was written.
+    pub clean_urls: bool,
+    /// The build's `index_file`.
+    pub index_file: String,
+    /// The build's `edit_enabled`; pages bake in the edit button.
+    pub edit_enabled: bool,
+    /// CSP nonce in the pages' inline tags, replaced with the server's own.
+    pub csp_nonce: Option<String>,
+}
+
+impl CacheMarker {
+    /// Reads the marker in `cache_dir`, or `None` if there isn't a valid one.
+    pub fn read(cache_dir: &Path) -> Option<Self> {
+        let json = std::fs::read(cache_dir.join(MARKER_FILE)).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Newest modification time of any file in the settings folder (`.mbr/`
+/// under `root`) and `template_folder`, two levels deep.
+pub fn settings_mtime(root: &Path, template_folder: Option<&Path>) -> Option<SystemTime> {
+    std::iter::once(root.join(".mbr"))
+        .chain(template_folder.map(|folder| root.join(folder)))
+        .flat_map(|dir| walkdir::WalkDir::new(dir).max_depth(2).into_iter())
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(CacheMarker::read(dir.path()), None);
+
+        let marker = CacheMarker {
+            clean_urls: false,
+            index_file: "index.md".to_string(),
+            edit_enabled: true,
+            csp_nonce: Some("abc".to_string()),
+        };
+        std::fs::create_dir_all(dir.path().join(".mbr")).unwrap();
+        std::fs::write(
+            dir.path().join(MARKER_FILE),
+            serde_json::to_vec(&marker).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(CacheMarker::read(dir.path()), Some(marker));
+    }
+
+    #[test]
+    fn test_settings_mtime() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(settings_mtime(root.path(), None), None);
+        std::fs::create_dir_all(root.path().join(".mbr/partials")).unwrap();
+        std::fs::write(root.path().join(".mbr/config.toml"), "").unwrap();
+        let old = filetime::FileTime::from_unix_time(1_000_000, 0);
+        filetime::set_file_mtime(root.path().join(".mbr/config.toml"), old).unwrap();
+        std::fs::write(root.path().join(".mbr/partials/_nav.html"), "").unwrap();
+        let new = filetime::FileTime::from_unix_time(2_000_000, 0);
+        filetime::set_file_mtime(root.path().join(".mbr/partials/_nav.html"), new).unwrap();
+
+        let mtime = settings_mtime(root.path(), None).unwrap();
+        assert_eq!(
+            filetime::FileTime::from_system_time(mtime),
+            new,
+            "the newest file wins"
+        );
+    }
+}
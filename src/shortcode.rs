@@ -0,0 +1,68 @@
+//! Parsing for `{{ name(key="value", ...) }}` shortcodes.
+//!
+//! The `data`, `gallery`, `code` and `figure` shortcodes share this syntax:
+//! a name, then `key="value"` attributes in parentheses, all inside `{{ }}`
+//! on a paragraph of its own. Values may use single or double quotes.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static KV_RE: LazyLock<Regex> = LazyLock::new(|| {
+    // Straight or curly quotes: pulldown-cmark's smart punctuation may have
+    // rewritten them before the shortcode is seen
+    Regex::new(r#"\b(?P<key>\w+)\s*=\s*["'\u{201C}\u{201D}\u{2018}\u{2019}](?P<val>[^"'\u{201C}\u{201D}\u{2018}\u{2019}]*)["'\u{201C}\u{201D}\u{2018}\u{2019}]"#)
+        .expect("Invalid KV_RE regex pattern")
+});
+
+/// Parses `text` as the shortcode `name` and returns its attributes, or
+/// `None` if `text` is some other shortcode or not one at all.
+pub fn parse(text: &str, name: &str) -> Option<HashMap<String, String>> {
+    let call = text.trim().strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    let params = call
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(parse_attrs(params))
+}
+
+/// Parses the `key="value"` attributes between a shortcode's parentheses.
+/// Values are returned as written; a repeated key keeps its last value.
+pub fn parse_attrs(params: &str) -> HashMap<String, String> {
+    KV_RE
+        .captures_iter(params)
+        .map(|kv| (kv["key"].to_string(), kv["val"].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let attrs = parse(
+            r#"  {{ figure ( src="a.png", caption='Setup' ) }} "#,
+            "figure",
+        )
+        .unwrap();
+        assert_eq!(attrs["src"], "a.png");
+        assert_eq!(attrs["caption"], "Setup");
+
+        assert!(parse("{{ gallery() }}", "gallery").unwrap().is_empty());
+        assert_eq!(parse(r#"{{ code(path="x") }}"#, "figure"), None);
+        assert_eq!(parse(r#"{{ codes(path="x") }}"#, "code"), None);
+        assert_eq!(parse(r#"code(path="x")"#, "code"), None);
+        assert_eq!(parse(r#"{{ code(path="x") }} trailing"#, "code"), None);
+    }
+
+    #[test]
+    fn test_parse_attrs_smart_quotes() {
+        let attrs =
+            parse_attrs("dir=\u{201C}photos\u{201D}, sort=\u{2018}date\u{2019}, dir=\"trip\"");
+        assert_eq!(attrs["sort"], "date");
+        assert_eq!(attrs["dir"], "trip");
+        assert_eq!(attrs.len(), 2);
+    }
+}
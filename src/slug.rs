@@ -0,0 +1,189 @@
+//! Heading anchor IDs.
+//!
+//! Every heading gets an `id` derived from its text by [`slugify`], in the
+//! style chosen by `markdown_options.heading_ids`. [`HeadingIds`] makes them
+//! unique within a page the way GitHub does: the second `## Setup` becomes
+//! `setup-1`, the third `setup-2`, skipping any suffix already taken.
+//!
+//! Body wikilink anchors (`[[Page#Getting Started]]`) go through the same
+//! function, so they land on the heading they name.
+
+use std::collections::HashMap;
+
+/// ID used when a heading's text slugifies to nothing (e.g. only emoji).
+const EMPTY_SLUG: &str = "heading";
+
+/// How heading text is turned into an anchor ID.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlugStyle {
+    /// GitHub's rules: lowercase, drop punctuation and symbols, each space
+    /// becomes `-`. Letters and digits from any script are kept.
+    #[default]
+    Github,
+    /// Transliterated to ASCII (`Café Ünïcode` → `cafe-unicode`), with runs of
+    /// anything else collapsed to a single `-`.
+    Ascii,
+    /// Lowercase with whitespace runs collapsed to `-`. Only ASCII punctuation
+    /// is dropped; emoji and other non-ASCII characters are kept.
+    PreserveUnicode,
+}
+
+/// Converts heading text to an anchor ID in the given style.
+///
+/// Never returns an empty string; text with nothing usable becomes `heading`.
+pub fn slugify(text: &str, style: SlugStyle) -> String {
+    let slug = match style {
+        SlugStyle::Github => text
+            .trim()
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| {
+                if c.is_whitespace() {
+                    Some('-')
+                } else if c.is_alphanumeric() || c == '-' || c == '_' {
+                    Some(c)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        SlugStyle::Ascii => collapse(&deunicode::deunicode(text).to_lowercase(), |c| {
+            c.is_ascii_alphanumeric()
+        }),
+        SlugStyle::PreserveUnicode => {
+            let kept: String = text
+                .to_lowercase()
+                .chars()
+                .filter(|&c| !c.is_ascii_punctuation() || c == '-' || c == '_')
+                .collect();
+            collapse(&kept, |c| !c.is_whitespace())
+        }
+    };
+    if slug.is_empty() {
+        EMPTY_SLUG.to_string()
+    } else {
+        slug
+    }
+}
+
+/// Keeps the characters `keep` accepts, joining each run of the others into a
+/// single `-` with none at either end.
+fn collapse(text: &str, keep: impl Fn(char) -> bool) -> String {
+    text.split(|c: char| !keep(c) && c != '-')
+        .flat_map(|word| word.split('-'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Hands out unique heading IDs for one page.
+#[derive(Debug, Default)]
+pub struct HeadingIds {
+    style: SlugStyle,
+    /// Each ID handed out, with the last suffix tried for it as a base.
+    seen: HashMap<String, usize>,
+}
+
+impl HeadingIds {
+    pub fn new(style: SlugStyle) -> Self {
+        Self {
+            style,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Returns a unique ID for a heading with `text`.
+    pub fn next_id(&mut self, text: &str) -> String {
+        let base = slugify(text, self.style);
+        let mut id = base.clone();
+        while self.seen.contains_key(&id) {
+            let count = self.seen.entry(base.clone()).or_insert(0);
+            *count += 1;
+            id = format!("{base}-{count}");
+        }
+        self.seen.insert(id.clone(), 0);
+        id
+    }
+
+    /// Records an explicit `{#id}` so generated IDs don't reuse it.
+    pub fn claim(&mut self, id: String) -> String {
+        self.seen.entry(id.clone()).or_insert(0);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_github() {
+        let github = |text| slugify(text, SlugStyle::Github);
+        assert_eq!(github("Getting Started"), "getting-started");
+        assert_eq!(github("Hello, World!"), "hello-world");
+        assert_eq!(github("What's new in v2.0?"), "whats-new-in-v20");
+        assert_eq!(github("A & B"), "a--b");
+        assert_eq!(github("snake_case and-dash"), "snake_case-and-dash");
+        assert_eq!(github("Café Ünïcode"), "café-ünïcode");
+        assert_eq!(github("日本語 見出し"), "日本語-見出し");
+        assert_eq!(github("🚀 Launch"), "-launch");
+        assert_eq!(github("🚀"), "heading");
+    }
+
+    #[test]
+    fn test_slugify_ascii() {
+        let ascii = |text| slugify(text, SlugStyle::Ascii);
+        assert_eq!(ascii("Café Ünïcode"), "cafe-unicode");
+        assert_eq!(ascii("A & B"), "a-b");
+        assert_eq!(ascii("  --Hello,   World!--  "), "hello-world");
+        assert_eq!(ascii("snake_case"), "snake-case");
+        assert_eq!(ascii("!!!"), "heading");
+    }
+
+    #[test]
+    fn test_slugify_preserve_unicode() {
+        let preserve = |text| slugify(text, SlugStyle::PreserveUnicode);
+        assert_eq!(preserve("Café Ünïcode"), "café-ünïcode");
+        assert_eq!(preserve("🚀 Launch!"), "🚀-launch");
+        assert_eq!(preserve("A & B"), "a-b");
+        assert_eq!(preserve("¿Qué pasó?"), "¿qué-pasó");
+    }
+
+    #[test]
+    fn test_heading_ids_are_unique() {
+        let mut ids = HeadingIds::new(SlugStyle::Github);
+        assert_eq!(ids.next_id("Setup"), "setup");
+        assert_eq!(ids.next_id("Setup"), "setup-1");
+        assert_eq!(ids.next_id("setup"), "setup-2");
+        // A heading that literally reads "Setup 3" takes that ID first...
+        assert_eq!(ids.next_id("Setup 3"), "setup-3");
+        // ...so the next duplicate skips it
+        assert_eq!(ids.next_id("Setup"), "setup-4");
+        assert_eq!(ids.next_id("Setup 1"), "setup-1-1");
+    }
+
+    #[test]
+    fn test_heading_ids_skip_claimed() {
+        let mut ids = HeadingIds::new(SlugStyle::Github);
+        assert_eq!(ids.claim("intro".to_string()), "intro");
+        assert_eq!(ids.next_id("Intro"), "intro-1");
+    }
+
+    #[test]
+    fn test_heading_ids_empty_text() {
+        let mut ids = HeadingIds::new(SlugStyle::Ascii);
+        assert_eq!(ids.next_id(""), "heading");
+        assert_eq!(ids.next_id("!!!"), "heading-1");
+    }
+
+    #[test]
+    fn test_slug_style_deserializes_kebab_case() {
+        let style: SlugStyle = serde_json::from_str("\"preserve-unicode\"").unwrap();
+        assert_eq!(style, SlugStyle::PreserveUnicode);
+        assert_eq!(
+            serde_json::to_string(&SlugStyle::Github).unwrap(),
+            "\"github\""
+        );
+    }
+}
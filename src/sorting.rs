@@ -74,6 +74,8 @@ enum SortCompare {
 /// - `"filename"` - Uses raw filename
 /// - `"created"` - Uses created timestamp
 /// - `"modified"` - Uses modified timestamp
+/// - `"date"` - Uses the normalized frontmatter date, compared chronologically
+/// - `"created_date"` / `"updated_date"` - Publish / last-updated date, likewise
 /// - Any other string - Looks up frontmatter field
 ///
 /// # Missing value behavior
@@ -155,8 +157,26 @@ fn apply_permutation(data: &mut [Value], perm: &mut [usize]) {
     }
 }
 
+/// Sort fields holding normalized timestamps, with the frontmatter field each
+/// falls back to when the file JSON doesn't carry the timestamp.
+const DATE_SORT_FIELDS: &[(&str, &str)] = &[
+    ("date", "date"),
+    ("created_date", "created"),
+    ("updated_date", "updated"),
+];
+
 /// Extracts a sort key from a file JSON object for a given field.
 fn extract_sort_key(file: &Value, field: &str, compare: SortCompare) -> SortKey {
+    // Dates compare chronologically whatever their written form
+    if let Some(&(_, fm_field)) = DATE_SORT_FIELDS.iter().find(|(name, _)| *name == field) {
+        let timestamp = file.get(field).and_then(|v| v.as_i64()).or_else(|| {
+            get_frontmatter_field(file, fm_field).and_then(|s| crate::dates::parse_date(&s))
+        });
+        return match timestamp {
+            Some(ts) => SortKey::Numeric(ts as f64),
+            None => SortKey::Missing,
+        };
+    }
     match get_field_value(file, field) {
         None => SortKey::Missing,
         Some(s) => match compare {
@@ -519,6 +539,50 @@ mod tests {
         assert_eq!(files[2]["title"], "Old");
     }
 
+    #[test]
+    fn test_sort_by_date_is_chronological() {
+        let mut files = vec![
+            json!({"name": "b.md", "title": "Mixed", "frontmatter": {"date": "Mar 2, 2023"}}),
+            json!({"name": "c.md", "title": "Undated", "frontmatter": {}}),
+            json!({"name": "a.md", "title": "Normalized", "date": 1704412800}),
+            json!({"name": "d.md", "title": "Iso", "frontmatter": {"date": "2023-12-31"}}),
+        ];
+
+        let config = vec![SortField {
+            field: "date".to_string(),
+            order: "desc".to_string(),
+            compare: "string".to_string(),
+        }];
+
+        sort_files(&mut files, &config);
+
+        assert_eq!(files[0]["title"], "Normalized");
+        assert_eq!(files[1]["title"], "Iso");
+        assert_eq!(files[2]["title"], "Mixed");
+        assert_eq!(files[3]["title"], "Undated");
+    }
+
+    #[test]
+    fn test_sort_by_updated_date_falls_back_to_frontmatter() {
+        let mut files = vec![
+            json!({"name": "a.md", "title": "Stale", "updated_date": 1600000000}),
+            json!({"name": "b.md", "title": "Fresh", "frontmatter": {"updated": "2024-02-01"}}),
+            json!({"name": "c.md", "title": "Unknown", "frontmatter": {}}),
+        ];
+
+        let config = vec![SortField {
+            field: "updated_date".to_string(),
+            order: "desc".to_string(),
+            compare: "string".to_string(),
+        }];
+
+        sort_files(&mut files, &config);
+
+        assert_eq!(files[0]["title"], "Fresh");
+        assert_eq!(files[1]["title"], "Stale");
+        assert_eq!(files[2]["title"], "Unknown");
+    }
+
     #[test]
     fn test_single_element_preserves() {
         let mut files = vec![make_file("a.md", Some("A"), None)];
@@ -0,0 +1,357 @@
+//! Repository statistics for `mbr --stats`.
+//!
+//! Everything comes from the repository scan plus a parse of each markdown
+//! page for its links. No HTML is rendered and nothing is written, so it's
+//! quick enough to run in CI, e.g. to fail when the orphan count grows.
+
+use crate::Config;
+use crate::errors::RepoError;
+use crate::link_index::{is_internal_link, resolve_relative_url, split_url_anchor};
+use crate::link_transform::{LinkTransformConfig, transform_link};
+use crate::markdown::RendererKind;
+use crate::repo::Repo;
+use pulldown_cmark::{Event, LinkType, Parser, Tag};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+/// Counts describing a repository.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct SiteStats {
+    /// Markdown pages (including other text formats rendered as pages).
+    pub pages: usize,
+    /// Words across all pages, as estimated by the scan.
+    pub words: usize,
+    /// Links between pages and to files in the repository.
+    pub internal_links: usize,
+    /// Links to other sites.
+    pub external_links: usize,
+    /// Number of entries in `orphans`.
+    pub orphan_count: usize,
+    /// URLs of pages no other page links to, sorted. The home page is never
+    /// counted.
+    pub orphans: Vec<String>,
+    /// Non-markdown files by kind (`image`, `video`, `audio`, `pdf`, ...).
+    pub media: BTreeMap<String, usize>,
+    /// Pages per tag, grouped by tag source (e.g. `tags`).
+    pub tags: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl SiteStats {
+    /// Scans the repository at `config.root_dir` and collects its stats.
+    pub fn scan(config: &Config) -> Result<Self, RepoError> {
+        let repo = Repo::init_from_config(config);
+        repo.scan_all()?;
+        repo.scan_static_folder()?;
+        repo.build_wikilink_index();
+        Ok(Self::from_repo(&repo, config))
+    }
+
+    /// Collects stats from an already scanned repository.
+    pub fn from_repo(repo: &Repo, config: &Config) -> Self {
+        use rayon::prelude::*;
+
+        let pages: Vec<(std::path::PathBuf, String, usize)> = repo
+            .markdown_files
+            .pin()
+            .iter()
+            .map(|(path, info)| (path.clone(), info.url_path.clone(), info.word_count))
+            .collect();
+        let page_urls: HashSet<&str> = pages.iter().map(|(_, url, _)| url.as_str()).collect();
+
+        let links: Vec<PageLinks> = pages
+            .par_iter()
+            .map(|(path, url, _)| page_links(repo, config, path, url))
+            .collect();
+
+        let mut linked: HashSet<String> = HashSet::new();
+        let mut stats = SiteStats {
+            pages: pages.len(),
+            words: pages.iter().map(|(_, _, words)| words).sum(),
+            ..Default::default()
+        };
+        for (page, (_, source_url, _)) in links.into_iter().zip(&pages) {
+            stats.internal_links += page.internal.len();
+            stats.external_links += page.external;
+            linked.extend(
+                page.internal
+                    .into_iter()
+                    .filter(|target| target != source_url && page_urls.contains(target.as_str())),
+            );
+        }
+
+        stats.orphans = pages
+            .iter()
+            .map(|(_, url, _)| url)
+            .filter(|url| url.as_str() != "/" && !linked.contains(*url))
+            .cloned()
+            .collect();
+        stats.orphans.sort();
+        stats.orphan_count = stats.orphans.len();
+
+        for (_, info) in repo.other_files.pin().iter() {
+            *stats.media.entry(info.filetype().to_string()).or_default() += 1;
+        }
+
+        for source in repo.tag_index.get_all_sources() {
+            let tags = repo
+                .tag_index
+                .get_all_tags(&source)
+                .into_iter()
+                .map(|tag| (tag.display, tag.count))
+                .collect();
+            stats.tags.insert(source, tags);
+        }
+
+        stats
+    }
+
+    /// Human-readable summary, one figure per line.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Pages:          {}", self.pages);
+        let _ = writeln!(out, "Words:          {}", self.words);
+        let _ = writeln!(out, "Internal links: {}", self.internal_links);
+        let _ = writeln!(out, "External links: {}", self.external_links);
+        let _ = writeln!(out, "Orphan pages:   {}", self.orphan_count);
+        let media: Vec<String> = self
+            .media
+            .iter()
+            .map(|(kind, count)| format!("{kind} {count}"))
+            .collect();
+        let _ = writeln!(
+            out,
+            "Other files:    {}",
+            if media.is_empty() {
+                "none".to_string()
+            } else {
+                media.join(", ")
+            }
+        );
+        for (source, tags) in &self.tags {
+            let mut by_count: Vec<(&String, &usize)> = tags.iter().collect();
+            by_count.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let listed: Vec<String> = by_count
+                .iter()
+                .map(|(tag, count)| format!("{tag} {count}"))
+                .collect();
+            let _ = writeln!(
+                out,
+                "Tags ({source}, {}): {}",
+                tags.len(),
+                listed.join(", ")
+            );
+        }
+        out
+    }
+}
+
+/// Links found on one page.
+#[derive(Default)]
+struct PageLinks {
+    /// Resolved root-relative targets of internal links.
+    internal: Vec<String>,
+    external: usize,
+}
+
+/// Parses the page at `path` and resolves its links the way rendering does,
+/// without generating HTML.
+fn page_links(repo: &Repo, config: &Config, path: &std::path::Path, url: &str) -> PageLinks {
+    let mut links = PageLinks::default();
+    let renderer = RendererKind::for_path(path, &config.renderers);
+    if !renderer.is_markdown() {
+        return links;
+    }
+    let Ok(input) = std::fs::read_to_string(path) else {
+        return links;
+    };
+    let input = config.markdown_options.prepare_source(input, renderer);
+    let input = crate::wikilink::transform_wikilinks(
+        &input,
+        &crate::config::tag_sources_to_set(&config.tag_sources),
+    );
+
+    let is_index_file = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .is_some_and(|f| f == config.index_file);
+    let transform = LinkTransformConfig {
+        markdown_extensions: config.markdown_extensions.clone(),
+        index_file: config.index_file.clone(),
+        is_index_file,
+        url_depth: None,
+        current_page_url: url.to_string(),
+        clean_urls: true,
+    };
+
+    for event in Parser::new_ext(&input, config.markdown_options.parser_options()) {
+        let Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+        if dest_url.starts_with('#') {
+            continue;
+        }
+        if !is_internal_link(&dest_url) {
+            links.external += 1;
+            continue;
+        }
+        let global = if matches!(link_type, LinkType::WikiLink { .. }) && !dest_url.contains('/') {
+            repo.wikilink_index
+                .resolve_wikilink(&dest_url, url, is_index_file)
+        } else {
+            None
+        };
+        let href = global.unwrap_or_else(|| transform_link(&dest_url, &transform));
+        let (href, _anchor) = split_url_anchor(&href);
+        // Transformed links are relative to the page's own URL
+        let target = resolve_relative_url(url, &href, true);
+        links.internal.push(page_url(&target, config));
+    }
+    links
+}
+
+/// Maps a resolved link target to the URL of the page it names, so links
+/// written as `/notes/page.md` or `%20`-encoded match the page's URL.
+fn page_url(target: &str, config: &Config) -> String {
+    let decoded = percent_encoding::percent_decode_str(target).decode_utf8_lossy();
+    let mut path = decoded.trim_end_matches('/').to_string();
+    if let Some((stem, ext)) = path.rsplit_once('.')
+        && config.markdown_extensions.iter().any(|e| e == ext)
+    {
+        path = stem.to_string();
+    }
+    let index_stem = config
+        .index_file
+        .rsplit_once('.')
+        .map_or(config.index_file.as_str(), |(stem, _)| stem);
+    if let Some(dir) = path.strip_suffix(index_stem)
+        && dir.ends_with('/')
+    {
+        path = dir.trim_end_matches('/').to_string();
+    }
+    format!("{path}/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &std::path::Path, name: &str, content: &str) {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn stats(root: &std::path::Path) -> SiteStats {
+        let config = Config {
+            root_dir: root.to_path_buf(),
+            ..Default::default()
+        };
+        SiteStats::scan(&config).unwrap()
+    }
+
+    #[test]
+    fn test_counts_links_and_orphans() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "index.md",
+            "# Home\n\n[Guide](docs/guide.md) and [[Notes]] and <https://example.com>\n",
+        );
+        write(
+            root,
+            "docs/guide.md",
+            "# Guide\n\n[Home](../index.md), [self](#top), [Photo](/photo.png), [ext](https://rust-lang.org)\n",
+        );
+        write(root, "notes.md", "---\ntags: [rust, cli]\n---\n# Notes\n");
+        write(
+            root,
+            "lonely.md",
+            "---\ntags: [rust]\n---\n# Lonely\n\n[Notes](notes.md)\n",
+        );
+        write(root, "photo.png", "");
+        write(root, "clip.mp4", "");
+
+        let stats = stats(root);
+        assert_eq!(stats.pages, 4);
+        assert!(stats.words > 0);
+        assert_eq!(stats.internal_links, 5);
+        assert_eq!(stats.external_links, 2);
+        assert_eq!(stats.orphans, ["/lonely/"]);
+        assert_eq!(stats.orphan_count, 1);
+        assert_eq!(stats.media.get("image"), Some(&1));
+        assert_eq!(stats.media.get("video"), Some(&1));
+        let tags = &stats.tags["tags"];
+        assert_eq!(tags.get("rust"), Some(&2));
+        assert_eq!(tags.get("cli"), Some(&1));
+    }
+
+    #[test]
+    fn test_self_links_do_not_adopt_orphans() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "index.md", "# Home\n");
+        write(dir.path(), "solo.md", "# Solo\n\n[Me](solo.md)\n");
+
+        let stats = stats(dir.path());
+        assert_eq!(stats.orphans, ["/solo/"]);
+    }
+
+    #[test]
+    fn test_counts_links_in_dialect_pages() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "index.md", "# Home\n\n[Guide](guide.mdx)\n");
+        write(
+            dir.path(),
+            "guide.mdx",
+            "# Guide\n\n<Callout>\n  [Notes](notes.md)\n</Callout>\n",
+        );
+        write(dir.path(), "notes.md", "# Notes\n");
+
+        let config = Config {
+            root_dir: dir.path().to_path_buf(),
+            markdown_extensions: vec!["md".to_string(), "mdx".to_string()],
+            ..Default::default()
+        };
+        let stats = SiteStats::scan(&config).unwrap();
+        assert_eq!(stats.internal_links, 2);
+        assert!(stats.orphans.is_empty(), "{:?}", stats.orphans);
+    }
+
+    #[test]
+    fn test_page_url_normalizes_targets() {
+        let config = Config::default();
+        assert_eq!(page_url("/docs/guide.md/", &config), "/docs/guide/");
+        assert_eq!(page_url("/docs/index.md/", &config), "/docs/");
+        assert_eq!(page_url("/my%20notes/", &config), "/my notes/");
+        assert_eq!(page_url("/", &config), "/");
+    }
+
+    #[test]
+    fn test_summary_lists_figures() {
+        let stats = SiteStats {
+            pages: 3,
+            orphan_count: 1,
+            media: BTreeMap::from([("image".to_string(), 2)]),
+            tags: BTreeMap::from([(
+                "tags".to_string(),
+                BTreeMap::from([("cli".to_string(), 1), ("rust".to_string(), 2)]),
+            )]),
+            ..Default::default()
+        };
+        let summary = stats.summary();
+        assert!(summary.contains("Pages:          3"), "{summary}");
+        assert!(summary.contains("Orphan pages:   1"), "{summary}");
+        assert!(summary.contains("Other files:    image 2"), "{summary}");
+        assert!(
+            summary.contains("Tags (tags, 2): rust 2, cli 1"),
+            "{summary}"
+        );
+    }
+}
@@ -8,6 +8,8 @@ use papaya::HashMap;
 use serde::Serialize;
 use std::collections::HashSet;
 
+use crate::config::TagSource;
+use crate::data::DataFiles;
 use crate::wikilink::normalize_tag_value;
 
 /// Information about a page tagged with a specific tag.
@@ -22,6 +24,8 @@ pub struct TaggedPage {
     pub description: Option<String>,
     /// The original tag value as it appears on this page (preserves case/spacing)
     pub original_tag_value: String,
+    /// Estimated word count from the repository scan
+    pub word_count: usize,
 }
 
 impl TaggedPage {
@@ -36,6 +40,7 @@ impl TaggedPage {
             title: title.into(),
             description: None,
             original_tag_value: original_tag_value.into(),
+            word_count: 0,
         }
     }
 
@@ -51,8 +56,15 @@ impl TaggedPage {
             title: title.into(),
             description: Some(description.into()),
             original_tag_value: original_tag_value.into(),
+            word_count: 0,
         }
     }
+
+    /// Sets the page's estimated word count.
+    pub fn with_word_count(mut self, word_count: usize) -> Self {
+        self.word_count = word_count;
+        self
+    }
 }
 
 /// A single tag with its normalized key and display value.
@@ -155,6 +167,36 @@ impl TagIndex {
         );
     }
 
+    /// Removes the page at `url_path` from every tag. Tags left without
+    /// pages are dropped, and so are sources left without tags.
+    pub fn remove_page(&self, url_path: &str) {
+        let index = self.index.pin();
+        let keys: Vec<String> = index
+            .iter()
+            .filter(|(_, pages)| pages.iter().any(|p| p.url_path == url_path))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in keys {
+            index.update(key.clone(), |pages| {
+                pages
+                    .iter()
+                    .filter(|p| p.url_path != url_path)
+                    .cloned()
+                    .collect()
+            });
+            let emptied = index
+                .remove_if(&key, |_, pages| pages.is_empty())
+                .is_ok_and(|removed| removed.is_some());
+            if emptied {
+                self.display_values.pin().remove(&key);
+            }
+        }
+        self.sources.pin().retain(|source, _| {
+            let prefix = format!("{source}:");
+            index.keys().any(|key| key.starts_with(&prefix))
+        });
+    }
+
     /// Gets all pages tagged with the given source and value.
     ///
     /// Returns an empty vector if no pages have this tag.
@@ -254,6 +296,38 @@ impl TagIndex {
         self.display_values.pin().clear();
         self.sources.pin().clear();
     }
+
+    /// Warnings for tag sources with a `data` file: values used in
+    /// frontmatter that have no entry in the file (or a missing file).
+    pub fn missing_metadata(
+        &self,
+        tag_sources: &[TagSource],
+        data: Option<&DataFiles>,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for tag_source in tag_sources {
+            let Some(name) = tag_source.data.as_deref() else {
+                continue;
+            };
+            let Some(data) = data.filter(|d| d.get(name).is_some()) else {
+                warnings.push(format!(
+                    "data file `{name}` for tag source `{}` not found",
+                    tag_source.field
+                ));
+                continue;
+            };
+            let label = tag_source.singular_label();
+            for tag in self.get_all_tags(&tag_source.url_source()) {
+                if data.tag_entry(name, &tag.normalized).is_none() {
+                    warnings.push(format!(
+                        "{label} \"{}\" has no entry in data file `{name}`",
+                        tag.display
+                    ));
+                }
+            }
+        }
+        warnings
+    }
 }
 
 #[cfg(test)]
@@ -352,6 +426,30 @@ mod tests {
         assert_eq!(pages.len(), 1);
     }
 
+    #[test]
+    fn test_remove_page() {
+        let index = TagIndex::new();
+        index.add_page("tags", "rust", TaggedPage::new("/a/", "A", "rust"));
+        index.add_page("tags", "rust", TaggedPage::new("/b/", "B", "rust"));
+        index.add_page("tags", "cli", TaggedPage::new("/a/", "A", "cli"));
+        index.add_page(
+            "performers",
+            "Joshua Jay",
+            TaggedPage::new("/a/", "A", "Joshua Jay"),
+        );
+
+        index.remove_page("/a/");
+
+        let pages = index.get_pages("tags", "rust");
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].url_path, "/b/");
+        // Emptied tags and sources go away
+        assert!(!index.has_tag("tags", "cli"));
+        assert!(index.get_tag_display("tags", "cli").is_none());
+        assert!(!index.has_source("performers"));
+        assert!(index.has_source("tags"));
+    }
+
     #[test]
     fn test_get_all_tags() {
         let index = TagIndex::new();
@@ -472,4 +570,35 @@ mod tests {
         assert_eq!(page.title, "Page Title");
         assert_eq!(page.description, Some("This is a description".to_string()));
     }
+
+    #[test]
+    fn test_missing_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("authors.yml"), "Jane Doe:\n  bio: Hi\n").unwrap();
+        let data = DataFiles::load(dir.path()).unwrap();
+
+        let index = TagIndex::new();
+        index.add_page(
+            "authors",
+            "Jane Doe",
+            TaggedPage::new("/a/", "A", "Jane Doe"),
+        );
+        index.add_page("authors", "Carol", TaggedPage::new("/b/", "B", "Carol"));
+        let sources = vec![TagSource {
+            field: "authors".to_string(),
+            label: None,
+            label_plural: None,
+            fields: vec![],
+            data: Some("authors".to_string()),
+        }];
+
+        assert_eq!(
+            index.missing_metadata(&sources, Some(&data)),
+            vec!["Author \"Carol\" has no entry in data file `authors`".to_string()]
+        );
+        assert_eq!(
+            index.missing_metadata(&sources, None),
+            vec!["data file `authors` for tag source `authors` not found".to_string()]
+        );
+    }
 }
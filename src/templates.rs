@@ -4,6 +4,7 @@ use std::{
     sync::Arc,
 };
 
+use crate::data::DataFiles;
 use crate::errors::TemplateError;
 use parking_lot::RwLock;
 use tera::{Context, Tera};
@@ -13,6 +14,10 @@ pub struct Templates {
     tera: Arc<RwLock<Tera>>,
     /// Path used for template loading (for hot reload)
     template_path: PathBuf,
+    /// Default format for the `format_date` filter
+    date_format: String,
+    /// Data files served by the `data()` function
+    data: Arc<DataFiles>,
 }
 
 impl Templates {
@@ -29,16 +34,50 @@ impl Templates {
             root_path.join(".mbr")
         };
 
-        let tera = Self::load_tera(&template_path)?;
+        let date_format = crate::dates::DEFAULT_DATE_FORMAT.to_string();
+        let data = Arc::new(DataFiles::default());
+        let tera = Self::load_tera(&template_path, &date_format, &data)?;
 
         Ok(Templates {
             tera: Arc::new(RwLock::new(tera)),
             template_path,
+            date_format,
+            data,
         })
     }
 
+    /// Sets the default format used by the `format_date` filter (the
+    /// `date_format` config option).
+    pub fn with_date_format(mut self, date_format: &str) -> Self {
+        self.date_format = date_format.to_string();
+        self.tera.write().register_filter(
+            "format_date",
+            FormatDateFilter {
+                default_format: self.date_format.clone(),
+            },
+        );
+        self
+    }
+
+    /// Sets the data files returned by the `data()` function (the
+    /// `data_folder` config option).
+    pub fn with_data(mut self, data: Arc<DataFiles>) -> Self {
+        self.data = data;
+        self.tera.write().register_function(
+            "data",
+            DataFunction {
+                data: self.data.clone(),
+            },
+        );
+        self
+    }
+
     /// Load Tera templates from the given path, with fallback to compiled defaults.
-    fn load_tera(template_path: &Path) -> Result<Tera, TemplateError> {
+    fn load_tera(
+        template_path: &Path,
+        date_format: &str,
+        data: &Arc<DataFiles>,
+    ) -> Result<Tera, TemplateError> {
         let globs = template_path.join("**/*.html");
         let source_desc = format!("{}", template_path.display());
 
@@ -56,6 +95,13 @@ impl Templates {
         // `humanize_date`); registered here so it survives `reload()` and the
         // `Tera::default()` fallback above.
         tera.register_filter("humandate", humandate_filter);
+        tera.register_filter(
+            "format_date",
+            FormatDateFilter {
+                default_format: date_format.to_string(),
+            },
+        );
+        tera.register_function("data", DataFunction { data: data.clone() });
 
         for (name, tpl) in DEFAULT_TEMPLATES.iter() {
             if tera.get_template(name).is_err() {
@@ -74,7 +120,7 @@ impl Templates {
     /// Reload all templates from disk. Call this when template files change.
     pub fn reload(&self) -> Result<(), TemplateError> {
         tracing::info!("Reloading templates from {:?}", self.template_path);
-        let new_tera = Self::load_tera(&self.template_path)?;
+        let new_tera = Self::load_tera(&self.template_path, &self.date_format, &self.data)?;
         *self.tera.write() = new_tera;
         tracing::debug!("Templates reloaded successfully");
         Ok(())
@@ -111,9 +157,10 @@ impl Templates {
         tracing::debug!("frontmatter: {:?}", &frontmatter);
 
         // Create JSON from frontmatter BEFORE adding markdown to context
-        // This avoids including the large markdown HTML in the frontmatter JSON
-        let frontmatter_json =
-            serde_json::to_string(&frontmatter).unwrap_or_else(|_| "{}".to_string());
+        // This avoids including the large markdown HTML in the frontmatter JSON.
+        // Keys are sorted so identical pages render identically between builds.
+        let sorted: std::collections::BTreeMap<_, _> = frontmatter.iter().collect();
+        let frontmatter_json = serde_json::to_string(&sorted).unwrap_or_else(|_| "{}".to_string());
 
         let mut context = Context::new();
         frontmatter.iter().for_each(|(k, v)| {
@@ -217,6 +264,20 @@ impl Templates {
         Self::render_template_with_tera(&tera, "tag.html", context_data)
     }
 
+    /// Renders a tag page for a source with per-value metadata (its `data`
+    /// setting), such as an author page with a bio.
+    ///
+    /// Context variables: those of [`Templates::render_tag`], plus
+    /// - `author`: The value's entry from the data file (e.g., name, bio,
+    ///   avatar), or an empty object when it has none
+    pub fn render_author(
+        &self,
+        context_data: HashMap<String, serde_json::Value>,
+    ) -> Result<String, TemplateError> {
+        let tera = self.tera.read();
+        Self::render_template_with_tera(&tera, "author.html", context_data)
+    }
+
     /// Renders a tag source index showing all tags from a source.
     ///
     /// Context variables:
@@ -287,6 +348,60 @@ fn humandate_filter(
     }
 }
 
+/// Tera `format_date` filter: formats a date with the configured
+/// `date_format`, or a `format` argument when given.
+///
+/// Accepts a Unix timestamp (the normalized `date` in listings) or any date
+/// string [`crate::dates::parse_date`] understands. Anything else passes
+/// through unchanged so the filter never errors.
+struct FormatDateFilter {
+    default_format: String,
+}
+
+impl tera::Filter for FormatDateFilter {
+    fn filter(
+        &self,
+        value: &serde_json::Value,
+        args: &HashMap<String, serde_json::Value>,
+    ) -> tera::Result<serde_json::Value> {
+        let timestamp = match value {
+            serde_json::Value::Number(n) => n.as_i64(),
+            serde_json::Value::String(s) => crate::dates::parse_date(s),
+            _ => None,
+        };
+        let format = args
+            .get("format")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.default_format);
+        Ok(timestamp
+            .and_then(|ts| crate::dates::format_timestamp(ts, format))
+            .map(serde_json::Value::String)
+            .unwrap_or_else(|| value.clone()))
+    }
+}
+
+/// Tera function `data(file="people")`: returns the contents of a data file,
+/// or all data files keyed by name when called without `file`.
+struct DataFunction {
+    data: Arc<DataFiles>,
+}
+
+impl tera::Function for DataFunction {
+    fn call(&self, args: &HashMap<String, serde_json::Value>) -> tera::Result<serde_json::Value> {
+        match args.get("file") {
+            None => Ok(self.data.to_value()),
+            Some(serde_json::Value::String(name)) => self
+                .data
+                .get(name)
+                .cloned()
+                .ok_or_else(|| tera::Error::msg(format!("data(): unknown data file `{name}`"))),
+            Some(other) => Err(tera::Error::msg(format!(
+                "data(): `file` must be a string, got {other}"
+            ))),
+        }
+    }
+}
+
 /// Returns `true` when `s` is a non-empty run of ASCII digits.
 fn is_all_ascii_digits(s: &str) -> bool {
     !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
@@ -381,6 +496,7 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
         include_str!("../templates/_head_markdown.html"),
     ),
     ("_nav.html", include_str!("../templates/_nav.html")),
+    ("_search.html", include_str!("../templates/_search.html")),
     (
         "_breadcrumbs.html",
         include_str!("../templates/_breadcrumbs.html"),
@@ -395,10 +511,34 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
         "_display_enhancements.html",
         include_str!("../templates/_display_enhancements.html"),
     ),
+    (
+        "_tasklists.html",
+        include_str!("../templates/_tasklists.html"),
+    ),
+    (
+        "_scroll_spy.html",
+        include_str!("../templates/_scroll_spy.html"),
+    ),
+    (
+        "_embed_facades.html",
+        include_str!("../templates/_embed_facades.html"),
+    ),
     (
         "_person_infobox.html",
         include_str!("../templates/_person_infobox.html"),
     ),
+    (
+        "_site_nav_macros.html",
+        include_str!("../templates/_site_nav_macros.html"),
+    ),
+    (
+        "_site_nav.html",
+        include_str!("../templates/_site_nav.html"),
+    ),
+    (
+        "_pagination.html",
+        include_str!("../templates/_pagination.html"),
+    ),
     // Main templates
     ("index.html", include_str!("../templates/index.html")),
     ("section.html", include_str!("../templates/section.html")),
@@ -406,10 +546,13 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
     ("error.html", include_str!("../templates/error.html")),
     // Tag templates
     ("tag.html", include_str!("../templates/tag.html")),
+    ("author.html", include_str!("../templates/author.html")),
     (
         "tag_index.html",
         include_str!("../templates/tag_index.html"),
     ),
+    // Archive template
+    ("archive.html", include_str!("../templates/archive.html")),
     // Media viewer template
     (
         "media_viewer.html",
@@ -513,6 +656,55 @@ mod tests {
         assert_eq!(humandate_filter(&json!(null), &args).unwrap(), json!(null));
     }
 
+    #[test]
+    fn test_data_function() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("people.csv"), "name\nAda\nGrace\n").unwrap();
+        let data = Arc::new(DataFiles::load(dir.path()).unwrap());
+
+        let mut tera = Tera::default();
+        tera.register_function("data", DataFunction { data });
+        tera.add_raw_template(
+            "t",
+            "{% for p in data(file='people') %}{{ p.name }};{% endfor %}{% set all = data() %}{{ all.people | length }}",
+        )
+        .unwrap();
+        tera.add_raw_template("missing", "{{ data(file='nope') }}")
+            .unwrap();
+        assert_eq!(tera.render("t", &Context::new()).unwrap(), "Ada;Grace;2");
+        assert!(tera.render("missing", &Context::new()).is_err());
+    }
+
+    #[test]
+    fn test_format_date_filter() {
+        use tera::Filter;
+        let filter = FormatDateFilter {
+            default_format: "%d/%m/%Y".to_string(),
+        };
+        let no_args = HashMap::new();
+        assert_eq!(
+            filter.filter(&json!(1704412800), &no_args).unwrap(),
+            json!("05/01/2024")
+        );
+        assert_eq!(
+            filter.filter(&json!("Jan 5, 2024"), &no_args).unwrap(),
+            json!("05/01/2024")
+        );
+        let args = HashMap::from([("format".to_string(), json!("%Y"))]);
+        assert_eq!(
+            filter
+                .filter(&json!("2024-01-05T10:00:00Z"), &args)
+                .unwrap(),
+            json!("2024")
+        );
+        // Unrecognized values pass through unchanged.
+        assert_eq!(
+            filter.filter(&json!("someday"), &no_args).unwrap(),
+            json!("someday")
+        );
+        assert_eq!(filter.filter(&json!(null), &no_args).unwrap(), json!(null));
+    }
+
     #[test]
     fn test_humandate_filter_registered_in_tera() {
         let mut tera = Tera::default();
@@ -22,6 +22,21 @@ static TIME_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"#t=([0-9]+(:[0-9]+)*)(,([0-9]+(:[0-9]+)*))?$")
         .expect("Invalid TIME_RE regex pattern")
 });
+static LANG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").expect("Invalid LANG_RE regex pattern")
+});
+
+const CUSTOM_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'.').remove(b'/').remove(b'?');
+
+/// Language assumed for subtitle tracks whose language can't be inferred.
+const DEFAULT_SUBTITLE_LANG: &str = "en";
+
+/// An explicit subtitle track from the `captions` shortcode parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleTrack {
+    pub src: String,
+    pub srclang: String,
+}
 
 #[derive(Debug, PartialEq, Default)]
 pub struct Vid {
@@ -30,6 +45,7 @@ pub struct Vid {
     pub start: Option<String>,
     pub end: Option<String>,
     pub caption: Option<String>,
+    pub subtitles: Vec<SubtitleTrack>,
 }
 
 impl Vid {
@@ -44,6 +60,7 @@ impl Vid {
                 start,
                 end,
                 caption: Some(title.to_string()),
+                subtitles: Vec::new(),
             }),
             _ => None,
         }
@@ -58,6 +75,7 @@ impl Vid {
 
         let mut vid: Vid = Default::default();
         let mut path: Option<String> = None;
+        let mut captions: Option<String> = None;
 
         for kv in KV_RE.captures_iter(params_str) {
             let key = &kv["key"];
@@ -67,25 +85,77 @@ impl Vid {
                 "start" => vid.start = Some(val.to_string()),
                 "end" => vid.end = Some(val.to_string()),
                 "caption" => vid.caption = Some(val.to_string()),
+                "captions" => captions = Some(val.to_string()),
                 _ => { /* ignore unknown keys */ }
             }
         }
 
-        const CUSTOM_ENCODE_SET: &AsciiSet =
-            &NON_ALPHANUMERIC.remove(b'.').remove(b'/').remove(b'?');
-
         match path {
             Some(p) => {
                 let p = Self::normalize_smart_quotes(&p);
                 vid.url = utf8_percent_encode(format!("/videos/{p}").as_str(), CUSTOM_ENCODE_SET)
                     .to_string();
                 vid.ext = Self::extension_from_url(&vid.url);
+                if let Some(captions) = captions {
+                    let video_dir = match p.rsplit_once('/') {
+                        Some((dir, _)) => format!("/videos/{dir}/"),
+                        None => "/videos/".to_string(),
+                    };
+                    vid.subtitles = Self::parse_captions(&captions, &video_dir);
+                }
                 Some(vid)
             }
             None => None,
         }
     }
 
+    /// Parse the `captions` shortcode parameter into subtitle tracks.
+    ///
+    /// The value is a comma-separated list of `.vtt` paths, each optionally
+    /// prefixed with a language (`fr:subs/talk.vtt`). Without a prefix the
+    /// language is taken from the file name (`talk.de.vtt`, `subs/en.vtt`),
+    /// falling back to English. Relative paths resolve against the video's
+    /// directory, root-relative paths (`/subs/en.vtt`) against the site root.
+    fn parse_captions(value: &str, video_dir: &str) -> Vec<SubtitleTrack> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (lang, path) = match entry.split_once(':') {
+                    // `ftp://...` is a URL, not an `ftp` language prefix
+                    Some((lang, path)) if LANG_RE.is_match(lang) && !path.starts_with("//") => {
+                        (Some(lang), path.trim())
+                    }
+                    _ => (None, entry),
+                };
+                let path = Self::normalize_smart_quotes(path);
+                let srclang = lang
+                    .map(str::to_string)
+                    .or_else(|| Self::lang_from_filename(&path))
+                    .unwrap_or_else(|| DEFAULT_SUBTITLE_LANG.to_string());
+                let src = if path.contains("://") {
+                    path
+                } else if path.starts_with('/') {
+                    utf8_percent_encode(&path, CUSTOM_ENCODE_SET).to_string()
+                } else {
+                    utf8_percent_encode(&format!("{video_dir}{path}"), CUSTOM_ENCODE_SET)
+                        .to_string()
+                };
+                SubtitleTrack { src, srclang }
+            })
+            .collect()
+    }
+
+    /// Infer a track language from the segment before `.vtt`
+    /// (`en.vtt` → `en`, `talk.pt-BR.vtt` → `pt-BR`).
+    fn lang_from_filename(path: &str) -> Option<String> {
+        let filename = path.rsplit('/').next()?;
+        let stem = filename.strip_suffix(".vtt")?;
+        let candidate = stem.rsplit('.').next()?;
+        LANG_RE.is_match(candidate).then(|| candidate.to_string())
+    }
+
     pub fn to_mime_type(&self) -> String {
         match self.ext.as_deref() {
             Some("m4v") => "video/mpeg".to_string(),
@@ -106,8 +176,8 @@ impl Vid {
     /// When both `server_mode` and `transcode_enabled` are true, generates multiple
     /// `<source>` tags with media queries for responsive video loading using HLS:
     /// - Original MP4 for wide screens (>= 1280px) - all browsers
-    /// - HLS 720p for medium screens (>= 640px) - Safari only (native HLS)
-    /// - HLS 480p as fallback for small screens - Safari only
+    /// - HLS master playlist for smaller screens - Safari only (native HLS),
+    ///   which picks the 720p/480p variant by bandwidth
     /// - Original MP4 as final fallback (no media query) - Chrome/Firefox/Edge on mobile
     ///
     /// Note: Time fragments (#t=start,end) only apply to MP4 sources, not HLS.
@@ -138,8 +208,7 @@ impl Vid {
 
             format!(
                 r#"<source src='{base_url}{time}' media="(min-width: 1280px)" type="{mime}">
-                    <source src='{url_base}-720p.m3u8' media="(min-width: 640px)" type="{hls_mime}">
-                    <source src='{url_base}-480p.m3u8' type="{hls_mime}">
+                    <source src='{url_base}.m3u8' type="{hls_mime}">
                     <source src='{base_url}{time}' type="{mime}">"#,
             )
         } else {
@@ -157,12 +226,35 @@ impl Vid {
             .clone()
             .unwrap_or_else(|| Self::fallback_caption(&self.url));
 
+        // Explicit subtitle tracks replace the auto-detected captions sidecar;
+        // the first one is enabled by default.
+        let text_tracks = if self.subtitles.is_empty() {
+            format!(
+                r#"<track kind="captions" label="English captions" src="{url}.captions.en.vtt" srclang="en" language="en-US" default type="vtt" data-type="vtt" />"#,
+                url = self.url
+            )
+        } else {
+            self.subtitles
+                .iter()
+                .enumerate()
+                .map(|(i, track)| {
+                    format!(
+                        r#"<track kind="subtitles" label="{lang}" src="{src}" srclang="{lang}"{default} />"#,
+                        lang = track.srclang,
+                        src = track.src,
+                        default = if i == 0 { " default" } else { "" }
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n                    ")
+        };
+
         format!(
             r#"
             <figure>
                 <video controls preload="none" playsinline poster="{url}.cover.jpg">
                     {sources}
-                    <track kind="captions" label="English captions" src="{url}.captions.en.vtt" srclang="en" language="en-US" default type="vtt" data-type="vtt" />
+                    {text_tracks}
                     <track kind="chapters" language="en-US" label="Chapters" src="{url}.chapters.en.vtt" srclang="en" default type="vtt" data-type="vtt" />
                 </video>
                 <figcaption>
@@ -334,6 +426,71 @@ mod tests {
         assert!(!vid.url.contains("%E2%80%99"));
     }
 
+    #[test]
+    fn test_from_vid_captions() {
+        let input = r#"{{ vid(path="talks/demo.mp4", captions="subs/en.vtt, fr:/shared/french subs.vtt, demo.pt-BR.vtt") }}"#;
+        let vid = Vid::from_vid(input).unwrap();
+        assert_eq!(
+            vid.subtitles,
+            vec![
+                SubtitleTrack {
+                    src: "/videos/talks/subs/en.vtt".to_string(),
+                    srclang: "en".to_string(),
+                },
+                SubtitleTrack {
+                    src: "/shared/french%20subs.vtt".to_string(),
+                    srclang: "fr".to_string(),
+                },
+                SubtitleTrack {
+                    src: "/videos/talks/demo.pt%2DBR.vtt".to_string(),
+                    srclang: "pt-BR".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_vid_captions_language_fallback() {
+        let input =
+            r#"{{ vid(path="demo.mp4", captions="subtitles.vtt, https://example.com/de.vtt") }}"#;
+        let vid = Vid::from_vid(input).unwrap();
+        assert_eq!(vid.subtitles[0].src, "/videos/subtitles.vtt");
+        assert_eq!(vid.subtitles[0].srclang, DEFAULT_SUBTITLE_LANG);
+        assert_eq!(vid.subtitles[1].src, "https://example.com/de.vtt");
+        assert_eq!(vid.subtitles[1].srclang, "de");
+    }
+
+    #[test]
+    fn test_to_html_with_subtitles() {
+        let vid = Vid {
+            url: "/videos/foo.mp4".to_string(),
+            ext: Some("mp4".to_string()),
+            start: None,
+            end: None,
+            caption: None,
+            subtitles: vec![
+                SubtitleTrack {
+                    src: "/videos/en.vtt".to_string(),
+                    srclang: "en".to_string(),
+                },
+                SubtitleTrack {
+                    src: "/videos/fr.vtt".to_string(),
+                    srclang: "fr".to_string(),
+                },
+            ],
+        };
+        let html = vid.to_html(false, false, false);
+        assert!(html.contains(
+            r#"<track kind="subtitles" label="en" src="/videos/en.vtt" srclang="en" default />"#
+        ));
+        assert!(html.contains(
+            r#"<track kind="subtitles" label="fr" src="/videos/fr.vtt" srclang="fr" />"#
+        ));
+        // Explicit tracks replace the auto-detected captions sidecar
+        assert!(!html.contains(".captions.en.vtt"));
+        assert!(html.contains(".chapters.en.vtt"));
+    }
+
     #[test]
     fn test_from_vid_invalid() {
         let input = r#"{{ notvid(path="foo.mp4") }}"#;
@@ -354,6 +511,7 @@ mod tests {
             start: Some("10".to_string()),
             end: Some("20".to_string()),
             caption: Some("Caption".to_string()),
+            subtitles: Vec::new(),
         };
         let html = vid.to_html(false, false, false);
         assert!(html.contains("<video"));
@@ -380,17 +538,18 @@ mod tests {
             start: Some("10".to_string()),
             end: Some("20".to_string()),
             caption: Some("Caption".to_string()),
+            subtitles: Vec::new(),
         };
         // With server_mode=true and transcode_enabled=true, should generate HLS sources
         let html = vid.to_html(false, true, true);
         assert!(html.contains("<video"));
         // Original MP4 source with media query for wide screens
         assert!(html.contains(r#"src='/videos/foo.mp4#t=10,20' media="(min-width: 1280px)""#));
-        // HLS 720p variant (no time fragment - HLS doesn't support it)
-        assert!(html.contains(r#"src='/videos/foo-720p.m3u8' media="(min-width: 640px)""#));
-        assert!(html.contains(r#"type="application/vnd.apple.mpegurl""#));
-        // HLS 480p variant (no media query - smallest HLS)
-        assert!(html.contains("src='/videos/foo-480p.m3u8'"));
+        // HLS master playlist (no time fragment - HLS doesn't support it)
+        assert!(html.contains(r#"src='/videos/foo.m3u8' type="application/vnd.apple.mpegurl""#));
+        // Variants are listed in the master playlist, not as separate sources
+        assert!(!html.contains("-720p.m3u8"));
+        assert!(!html.contains("-480p.m3u8"));
         // MP4 fallback (no media query) for non-Safari browsers
         // Count the number of times the original MP4 appears (should be twice)
         assert_eq!(
@@ -409,12 +568,12 @@ mod tests {
             start: None,
             end: None,
             caption: None,
+            subtitles: Vec::new(),
         };
         // transcode_enabled=true but server_mode=false should NOT generate multiple sources
         let html = vid.to_html(false, false, true);
         // Should only have single source (original behavior)
-        assert!(!html.contains("-720p.m3u8"));
-        assert!(!html.contains("-480p.m3u8"));
+        assert!(!html.contains(".m3u8"));
         assert!(html.contains("src='/videos/foo.mp4'"));
         // No caption provided, so fallback to filename
         assert!(html.contains("foo"));
@@ -428,6 +587,7 @@ mod tests {
             start: None,
             end: None,
             caption: None,
+            subtitles: Vec::new(),
         };
         let html = vid.to_html(false, false, false);
         assert!(
@@ -549,6 +709,7 @@ mod tests {
             start: Some("0:30".to_string()),
             end: Some("3:20".to_string()),
             caption: Some("Caption".to_string()),
+            subtitles: Vec::new(),
         };
         let html = vid.to_html(false, false, false);
         // Source tag should have seconds
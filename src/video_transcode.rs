@@ -13,11 +13,20 @@
 //! ## URL Patterns
 //!
 //! - `/videos/demo.mp4` - Original video (served directly)
+//! - `/videos/demo.m3u8` - HLS master playlist listing the applicable variants
 //! - `/videos/demo-720p.m3u8` - HLS playlist for 720p variant
 //! - `/videos/demo-720p-005.ts` - HLS segment 5 for 720p variant
+//!
+//! ## Audio
+//!
+//! Audio is optional. Video-only sources (screen recordings, GIF conversions)
+//! and sources whose audio track can't be decoded produce single-stream
+//! segments; `NoAudioStream` is never returned from this module.
 
 use ffmpeg_next as ffmpeg;
+use serde::Serialize;
 use std::path::Path;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Segment duration in seconds for HLS output.
@@ -40,6 +49,12 @@ pub enum TranscodeTarget {
 }
 
 impl TranscodeTarget {
+    /// All targets, highest resolution first.
+    pub const ALL: [TranscodeTarget; 2] = [
+        TranscodeTarget::Resolution720p,
+        TranscodeTarget::Resolution480p,
+    ];
+
     /// Get the target height for this resolution.
     pub fn height(&self) -> u32 {
         match self {
@@ -79,6 +94,12 @@ impl TranscodeTarget {
             TranscodeTarget::Resolution480p => "-480p",
         }
     }
+
+    /// Get the combined video+audio bandwidth in bits per second, as
+    /// advertised in the master playlist.
+    pub fn bandwidth_bps(&self) -> usize {
+        (self.video_bitrate_kbps() + self.audio_bitrate_kbps()) as usize * KBPS_TO_BPS
+    }
 }
 
 /// Errors that can occur during transcoding.
@@ -94,6 +115,8 @@ pub enum TranscodeError {
     #[error("No video stream found in file: {}", path.display())]
     NoVideoStream { path: std::path::PathBuf },
 
+    /// Only returned where audio is required (audio transcoding). Video
+    /// transcoding treats audio as optional and never returns this.
     #[error("No audio stream found in file: {}", path.display())]
     NoAudioStream { path: std::path::PathBuf },
 
@@ -122,8 +145,52 @@ pub enum TranscodeError {
     UnsupportedFormat,
 }
 
+impl TranscodeError {
+    /// Name of the error variant (e.g. `"SourceTooSmall"`), for structured
+    /// error responses.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TranscodeError::OpenFailed { .. } => "OpenFailed",
+            TranscodeError::NoVideoStream { .. } => "NoVideoStream",
+            TranscodeError::NoAudioStream { .. } => "NoAudioStream",
+            TranscodeError::SourceTooSmall { .. } => "SourceTooSmall",
+            TranscodeError::SegmentOutOfRange { .. } => "SegmentOutOfRange",
+            TranscodeError::TranscodeFailed(_) => "TranscodeFailed",
+            TranscodeError::EncoderNotAvailable(_) => "EncoderNotAvailable",
+            TranscodeError::Io(_) => "Io",
+            TranscodeError::UnsupportedFormat => "UnsupportedFormat",
+        }
+    }
+}
+
+/// Serializable summary of a [`TranscodeError`].
+///
+/// Sent as the JSON body of failed HLS requests and kept in the HLS cache so a
+/// remembered failure reports the same details as the original one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TranscodeErrorInfo {
+    /// Variant name, see [`TranscodeError::kind`]
+    pub error: &'static str,
+    pub message: String,
+}
+
+impl From<&TranscodeError> for TranscodeErrorInfo {
+    fn from(error: &TranscodeError) -> Self {
+        Self {
+            error: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for TranscodeErrorInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Information about a video file's resolution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct VideoResolution {
     pub width: u32,
     pub height: u32,
@@ -133,6 +200,8 @@ pub struct VideoResolution {
 /// Parsed HLS request type.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HlsRequest {
+    /// Request for the HLS master playlist listing all variants (.m3u8)
+    Master { video_path: String },
     /// Request for an HLS playlist (.m3u8)
     Playlist {
         video_path: String,
@@ -152,6 +221,7 @@ pub enum HlsRequest {
 ///
 /// # URL Patterns
 ///
+/// - `{base}.m3u8` → Master playlist referencing the variants
 /// - `{base}-720p.m3u8` → Playlist for 720p variant
 /// - `{base}-480p.m3u8` → Playlist for 480p variant
 /// - `{base}-720p-{NNN}.ts` → Segment NNN for 720p variant
@@ -208,6 +278,12 @@ pub fn parse_hls_request(path: &str) -> Option<HlsRequest> {
         }
     }
 
+    // Anything else ending in .m3u8 is the master playlist: {base}.m3u8
+    if let Some(base) = path.strip_suffix(".m3u8") {
+        let video_path = find_original_video_path(base);
+        return Some(HlsRequest::Master { video_path });
+    }
+
     None
 }
 
@@ -321,10 +397,20 @@ pub fn calculate_output_dimensions(
     (output_width, output_height)
 }
 
+/// Software H.264 encoder used when no hardware encoder is available.
+const SOFTWARE_H264_ENCODER: &str = "libx264";
+
+/// `video_encoder` value that skips hardware detection and uses libx264.
+pub const SOFTWARE_ENCODER_ALIAS: &str = "software";
+
 /// Try to find an available hardware encoder, falling back to software.
 ///
 /// Returns the encoder name to use.
 pub fn find_h264_encoder() -> &'static str {
+    pick_h264_encoder(|name| ffmpeg::encoder::find_by_name(name).is_some())
+}
+
+fn pick_h264_encoder(is_available: impl Fn(&str) -> bool) -> &'static str {
     // Try hardware encoders in order of preference
     let hw_encoders = [
         "h264_videotoolbox", // macOS
@@ -335,7 +421,7 @@ pub fn find_h264_encoder() -> &'static str {
     ];
 
     for encoder_name in hw_encoders {
-        if ffmpeg::encoder::find_by_name(encoder_name).is_some() {
+        if is_available(encoder_name) {
             tracing::debug!("Found hardware encoder: {}", encoder_name);
             return encoder_name;
         }
@@ -343,7 +429,79 @@ pub fn find_h264_encoder() -> &'static str {
 
     // Fall back to software encoder
     tracing::debug!("No hardware encoder found, using libx264");
-    "libx264"
+    SOFTWARE_H264_ENCODER
+}
+
+/// Resolve the H.264 encoder to use, honoring the `video_encoder` override.
+///
+/// `None` auto-detects (see [`find_h264_encoder`]), `"software"` forces
+/// libx264, and any other name is used as-is. A forced encoder that ffmpeg
+/// doesn't provide is an error rather than a silent fallback.
+pub fn select_h264_encoder(encoder_override: Option<&str>) -> Result<String, TranscodeError> {
+    resolve_h264_encoder(encoder_override, |name| {
+        ffmpeg::encoder::find_by_name(name).is_some()
+    })
+}
+
+fn resolve_h264_encoder(
+    encoder_override: Option<&str>,
+    is_available: impl Fn(&str) -> bool,
+) -> Result<String, TranscodeError> {
+    let forced = match encoder_override {
+        None => return Ok(pick_h264_encoder(is_available).to_string()),
+        Some(SOFTWARE_ENCODER_ALIAS) => SOFTWARE_H264_ENCODER,
+        Some(name) => name,
+    };
+
+    if is_available(forced) {
+        tracing::debug!("Using configured encoder: {}", forced);
+        Ok(forced.to_string())
+    } else {
+        Err(TranscodeError::EncoderNotAvailable(forced.to_string()))
+    }
+}
+
+/// Build an HLS master playlist for a source of the given resolution.
+///
+/// Lists one `#EXT-X-STREAM-INF` entry per target that applies (see
+/// [`should_transcode`]), highest first, with the combined video+audio
+/// bandwidth and the actual output dimensions. The original file is not
+/// listed: a progressive MP4 isn't a valid HLS rendition, so the `vid`
+/// shortcode offers it as a separate `<source>` instead.
+///
+/// Returns `SourceTooSmall` when the source is too small for every target.
+pub fn build_master_playlist(
+    resolution: &VideoResolution,
+    base_name: &str,
+) -> Result<String, TranscodeError> {
+    let mut playlist = String::with_capacity(256);
+    playlist.push_str("#EXTM3U\n");
+    playlist.push_str("#EXT-X-VERSION:3\n");
+
+    let mut variants = 0;
+    for target in TranscodeTarget::ALL {
+        if !should_transcode(resolution.height, target) {
+            continue;
+        }
+        let (width, height) =
+            calculate_output_dimensions(resolution.width, resolution.height, target);
+        let bandwidth = target.bandwidth_bps();
+        playlist.push_str(&format!(
+            "#EXT-X-STREAM-INF:BANDWIDTH={bandwidth},RESOLUTION={width}x{height}\n"
+        ));
+        playlist.push_str(&format!("{base_name}{}.m3u8\n", target.url_suffix()));
+        variants += 1;
+    }
+
+    if variants == 0 {
+        let smallest = TranscodeTarget::ALL[TranscodeTarget::ALL.len() - 1];
+        return Err(TranscodeError::SourceTooSmall {
+            source_height: resolution.height,
+            target_height: smallest.height(),
+        });
+    }
+
+    Ok(playlist)
 }
 
 /// Generate an HLS playlist for the given video and target resolution.
@@ -402,34 +560,163 @@ pub fn generate_hls_playlist(
     Ok(playlist)
 }
 
+/// File name prefix of HLS segment temp files.
+const SEGMENT_TEMP_PREFIX: &str = "mbr_segment_";
+
+/// File name prefix of audio transcode temp files.
+pub(crate) const AUDIO_TEMP_PREFIX: &str = "mbr_audio_";
+
+/// Temp files older than this are considered orphaned by a killed or crashed
+/// transcode. Far longer than any single transcode takes, so files that belong
+/// to another running instance are left alone.
+pub const STALE_TEMP_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Remove transcode temp files in `tmp_dir` (or the system temp directory)
+/// that were last modified more than `max_age` ago.
+///
+/// Temp files are normally removed when their handle drops, but a killed
+/// process leaves them behind. Returns the number of files removed; a missing
+/// directory counts as nothing to clean.
+pub fn cleanup_stale_temp_files(tmp_dir: Option<&Path>, max_age: Duration) -> usize {
+    let dir = tmp_dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !name.starts_with(SEGMENT_TEMP_PREFIX) && !name.starts_with(AUDIO_TEMP_PREFIX) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let is_stale = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if metadata.is_file() && is_stale {
+            match std::fs::remove_file(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => tracing::debug!("Could not remove {}: {}", entry.path().display(), e),
+            }
+        }
+    }
+
+    if removed > 0 {
+        tracing::info!(
+            "Removed {} stale transcode temp file(s) from {}",
+            removed,
+            dir.display()
+        );
+    }
+    removed
+}
+
+/// Create a temp file from `builder` in `tmp_dir` (the configured
+/// `transcode_tmp_dir`), or the system temp directory when `None`.
+///
+/// The directory is created if it doesn't exist yet.
+pub(crate) fn create_transcode_temp_file(
+    builder: &tempfile::Builder,
+    tmp_dir: Option<&Path>,
+) -> Result<tempfile::NamedTempFile, TranscodeError> {
+    match tmp_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            builder.tempfile_in(dir)
+        }
+        None => builder.tempfile(),
+    }
+    .map_err(TranscodeError::Io)
+}
+
 /// Create a uniquely-named temporary file for a transcoded MPEG-TS segment.
 ///
 /// Uses [`tempfile::NamedTempFile`] so concurrent transcodes of the same
 /// segment index for different videos or resolutions never collide on a shared
 /// path, and the file is removed automatically when the returned handle is
-/// dropped (including on an early error return).
-fn create_segment_temp_file() -> Result<tempfile::NamedTempFile, TranscodeError> {
-    tempfile::Builder::new()
-        .prefix("mbr_segment_")
-        .suffix(".ts")
-        .tempfile()
-        .map_err(TranscodeError::Io)
+/// dropped (including on an early error return). The name carries a random
+/// component rather than the pid or segment index, so retries never collide
+/// either.
+fn create_segment_temp_file(
+    tmp_dir: Option<&Path>,
+) -> Result<tempfile::NamedTempFile, TranscodeError> {
+    create_transcode_temp_file(
+        tempfile::Builder::new()
+            .prefix(SEGMENT_TEMP_PREFIX)
+            .suffix(".ts"),
+        tmp_dir,
+    )
+}
+
+/// Open a decoder for a video's optional audio track.
+///
+/// Returns the decoder, the stream's time base and the channel layout to
+/// encode with (derived from the channel count when the stream has none).
+fn open_audio_decoder(
+    input_ctx: &ffmpeg::format::context::Input,
+    audio_stream_index: usize,
+) -> Result<
+    (
+        ffmpeg::decoder::Audio,
+        ffmpeg::Rational,
+        ffmpeg::ChannelLayout,
+    ),
+    TranscodeError,
+> {
+    let audio_stream = input_ctx.stream(audio_stream_index).ok_or_else(|| {
+        TranscodeError::TranscodeFailed(format!("audio stream {audio_stream_index} not found"))
+    })?;
+    let audio_decoder_ctx = ffmpeg::codec::context::Context::from_parameters(
+        audio_stream.parameters(),
+    )
+    .map_err(|e| {
+        TranscodeError::TranscodeFailed(format!("Failed to create audio decoder context: {e}"))
+    })?;
+    let audio_decoder = audio_decoder_ctx.decoder().audio().map_err(|e| {
+        TranscodeError::TranscodeFailed(format!("Failed to create audio decoder: {e}"))
+    })?;
+
+    if audio_decoder.channels() == 0 {
+        return Err(TranscodeError::TranscodeFailed(
+            "audio stream has no channels".to_string(),
+        ));
+    }
+
+    let layout = if audio_decoder.channel_layout().is_empty() {
+        ffmpeg::ChannelLayout::default(i32::from(audio_decoder.channels()))
+    } else {
+        audio_decoder.channel_layout()
+    };
+
+    Ok((audio_decoder, audio_stream.time_base(), layout))
 }
 
 /// Transcode a single HLS segment (.ts) for the given video.
 ///
 /// Seeks to the segment start position and transcodes approximately
-/// `HLS_SEGMENT_DURATION` seconds of video to MPEG-TS format.
+/// `HLS_SEGMENT_DURATION` seconds of video to MPEG-TS format. Sources without
+/// a usable audio track produce a video-only segment.
 ///
 /// # Arguments
 ///
 /// * `source_path` - Path to the source video file
 /// * `target` - Target resolution for transcoding
 /// * `segment_index` - Zero-based segment index (segment 0 is 0-10s, segment 1 is 10-20s, etc.)
+/// * `encoder_override` - Configured `video_encoder`; `None` auto-detects
+/// * `tmp_dir` - Configured `transcode_tmp_dir`; `None` uses the system temp directory
 pub fn transcode_segment(
     source_path: &Path,
     target: TranscodeTarget,
     segment_index: u32,
+    encoder_override: Option<&str>,
+    tmp_dir: Option<&Path>,
 ) -> Result<Vec<u8>, TranscodeError> {
     // Calculate segment time range
     let start_time = segment_index as f64 * HLS_SEGMENT_DURATION;
@@ -471,7 +758,7 @@ pub fn transcode_segment(
     // Create a uniquely-named temp file for MPEG-TS output. The handle owns the
     // file and removes it on drop, so concurrent transcodes never collide and a
     // failed transcode does not leak the temp file.
-    let temp_file = create_segment_temp_file()?;
+    let temp_file = create_segment_temp_file(tmp_dir)?;
     let temp_path = temp_file.path();
 
     // Open input
@@ -524,26 +811,26 @@ pub fn transcode_segment(
         TranscodeError::TranscodeFailed(format!("Failed to create video decoder: {e}"))
     })?;
 
-    // Create audio decoder if audio stream exists
-    let mut audio_decoder = if let Some(audio_idx) = audio_stream_index {
-        let audio_stream =
-            input_ctx
-                .stream(audio_idx)
-                .ok_or_else(|| TranscodeError::NoAudioStream {
-                    path: source_path.to_path_buf(),
-                })?;
-        let audio_codec_params = audio_stream.parameters();
-        let audio_decoder_ctx =
-            ffmpeg::codec::context::Context::from_parameters(audio_codec_params).map_err(|e| {
-                TranscodeError::TranscodeFailed(format!(
-                    "Failed to create audio decoder context: {e}"
-                ))
-            })?;
-        Some(audio_decoder_ctx.decoder().audio().map_err(|e| {
-            TranscodeError::TranscodeFailed(format!("Failed to create audio decoder: {e}"))
-        })?)
-    } else {
-        None
+    // Create audio decoder if audio stream exists. Audio is optional: a track
+    // we can't decode is dropped so the segment still transcodes video-only.
+    let audio_input =
+        audio_stream_index.and_then(
+            |audio_idx| match open_audio_decoder(&input_ctx, audio_idx) {
+                Ok(opened) => Some(opened),
+                Err(e) => {
+                    tracing::warn!(
+                        "Ignoring audio in {} (transcoding video only): {}",
+                        source_path.display(),
+                        e
+                    );
+                    None
+                }
+            },
+        );
+    let audio_stream_index = audio_stream_index.filter(|_| audio_input.is_some());
+    let (mut audio_decoder, audio_input_params) = match audio_input {
+        Some((decoder, time_base, layout)) => (Some(decoder), Some((time_base, layout))),
+        None => (None, None),
     };
 
     // Create output context for MPEG-TS
@@ -552,9 +839,9 @@ pub fn transcode_segment(
     })?;
 
     // Find encoder
-    let encoder_name = find_h264_encoder();
-    let video_encoder_codec = ffmpeg::encoder::find_by_name(encoder_name)
-        .ok_or_else(|| TranscodeError::EncoderNotAvailable(encoder_name.to_string()))?;
+    let encoder_name = select_h264_encoder(encoder_override)?;
+    let video_encoder_codec = ffmpeg::encoder::find_by_name(&encoder_name)
+        .ok_or_else(|| TranscodeError::EncoderNotAvailable(encoder_name.clone()))?;
 
     // Configure video encoder
     let video_encoder_ctx = ffmpeg::codec::context::Context::new_with_codec(video_encoder_codec);
@@ -592,15 +879,7 @@ pub fn transcode_segment(
 
     // Add audio stream if present
     let mut audio_encoder_opt: Option<(usize, ffmpeg::encoder::Audio)> =
-        if let Some(audio_idx) = audio_stream_index {
-            let audio_stream =
-                input_ctx
-                    .stream(audio_idx)
-                    .ok_or_else(|| TranscodeError::NoAudioStream {
-                        path: source_path.to_path_buf(),
-                    })?;
-            let audio_time_base = audio_stream.time_base();
-
+        if let Some((audio_time_base, audio_layout)) = audio_input_params {
             let aac_encoder = ffmpeg::encoder::find_by_name("aac")
                 .ok_or_else(|| TranscodeError::EncoderNotAvailable("aac".to_string()))?;
 
@@ -615,7 +894,7 @@ pub fn transcode_segment(
                 )
             })?;
             audio_enc_setup.set_rate(audio_dec.rate() as i32);
-            audio_enc_setup.set_channel_layout(audio_dec.channel_layout());
+            audio_enc_setup.set_channel_layout(audio_layout);
             audio_enc_setup.set_format(encoder_audio_format);
             audio_enc_setup.set_time_base(audio_time_base);
             audio_enc_setup.set_bit_rate(target.audio_bitrate_kbps() as usize * KBPS_TO_BPS);
@@ -640,14 +919,14 @@ pub fn transcode_segment(
     // Create audio resampler if audio stream exists
     // Converts from decoder output format to encoder input format (FLTP for AAC)
     let mut audio_resampler: Option<ffmpeg::software::resampling::Context> =
-        if let Some(audio_dec) = &audio_decoder {
+        if let (Some(audio_dec), Some((_, audio_layout))) = (&audio_decoder, audio_input_params) {
             Some(
                 ffmpeg::software::resampling::Context::get(
                     audio_dec.format(),
-                    audio_dec.channel_layout(),
+                    audio_layout,
                     audio_dec.rate(),
                     encoder_audio_format,
-                    audio_dec.channel_layout(),
+                    audio_layout,
                     audio_dec.rate(),
                 )
                 .map_err(|e| {
@@ -682,10 +961,8 @@ pub fn transcode_segment(
         / f64::from(video_time_base.numerator())) as i64;
 
     // Calculate audio segment start PTS if audio stream exists
-    let audio_segment_start_pts = audio_stream_index
-        .and_then(|audio_idx| input_ctx.stream(audio_idx))
-        .map(|audio_stream| {
-            let audio_time_base = audio_stream.time_base();
+    let audio_segment_start_pts = audio_input_params
+        .map(|(audio_time_base, _)| {
             (start_time * f64::from(audio_time_base.denominator())
                 / f64::from(audio_time_base.numerator())) as i64
         })
@@ -700,7 +977,8 @@ pub fn transcode_segment(
     for (stream, packet) in input_ctx.packets() {
         // Calculate packet time in seconds (use stream from iterator to avoid borrow conflict)
         let stream_time_base = stream.time_base();
-        let pkt_pts = packet.pts().unwrap_or(0);
+        // Some muxers (notably screen recorders) only stamp DTS on packets
+        let pkt_pts = packet.pts().or(packet.dts()).unwrap_or(0);
         let pkt_time = pkt_pts as f64 * f64::from(stream_time_base.numerator())
             / f64::from(stream_time_base.denominator());
 
@@ -860,9 +1138,8 @@ pub fn transcode_segment(
         &mut audio_resampler,
     ) {
         // Get audio time base for PTS calculation
-        let audio_time_base = audio_stream_index
-            .and_then(|audio_idx| input_ctx.stream(audio_idx))
-            .map(|s| s.time_base())
+        let audio_time_base = audio_input_params
+            .map(|(time_base, _)| time_base)
             .unwrap_or_else(|| ffmpeg::Rational::new(1, MPEG_TS_TIME_BASE as i32));
 
         // Flush decoder
@@ -937,6 +1214,124 @@ pub fn transcode_segment(
 mod tests {
     use super::*;
 
+    // Encoder selection tests
+
+    #[test]
+    fn test_encoder_override_is_respected() {
+        let available = |name: &str| matches!(name, "h264_nvenc" | "libx264");
+
+        assert_eq!(
+            resolve_h264_encoder(None, available).unwrap(),
+            "h264_nvenc",
+            "auto-detection prefers hardware"
+        );
+        assert_eq!(
+            resolve_h264_encoder(Some("libx264"), available).unwrap(),
+            "libx264"
+        );
+        assert_eq!(
+            resolve_h264_encoder(Some(SOFTWARE_ENCODER_ALIAS), available).unwrap(),
+            "libx264"
+        );
+        assert!(matches!(
+            resolve_h264_encoder(Some("h264_qsv"), available),
+            Err(TranscodeError::EncoderNotAvailable(name)) if name == "h264_qsv"
+        ));
+    }
+
+    // Video-only sources
+
+    /// Encode a short clip with no audio stream (like a screen recording)
+    /// using ffmpeg's built-in MPEG-4 encoder.
+    fn write_silent_video(path: &Path, width: u32, height: u32, seconds: i64) {
+        const FPS: i32 = 5;
+
+        let codec = ffmpeg::encoder::find(ffmpeg::codec::Id::MPEG4).expect("mpeg4 encoder");
+        let mut output = ffmpeg::format::output(path).unwrap();
+        let global_header = output
+            .format()
+            .flags()
+            .contains(ffmpeg::format::flag::Flags::GLOBAL_HEADER);
+
+        let mut setup = ffmpeg::codec::context::Context::new_with_codec(codec)
+            .encoder()
+            .video()
+            .unwrap();
+        setup.set_width(width);
+        setup.set_height(height);
+        setup.set_format(ffmpeg::format::Pixel::YUV420P);
+        setup.set_time_base((1, FPS));
+        setup.set_frame_rate(Some((FPS, 1)));
+        if global_header {
+            setup.set_flags(ffmpeg::codec::flag::Flags::GLOBAL_HEADER);
+        }
+        let mut encoder = setup.open_as(codec).unwrap();
+        {
+            let mut stream = output.add_stream(codec).unwrap();
+            stream.set_time_base((1, FPS));
+            stream.set_parameters(&encoder);
+        }
+        output.write_header().unwrap();
+        let stream_time_base = output.stream(0).unwrap().time_base();
+
+        let mut packet = ffmpeg::Packet::empty();
+        let mut write_packets =
+            |encoder: &mut ffmpeg::encoder::Video, output: &mut ffmpeg::format::context::Output| {
+                while encoder.receive_packet(&mut packet).is_ok() {
+                    packet.set_stream(0);
+                    packet.rescale_ts((1, FPS), stream_time_base);
+                    packet.write_interleaved(output).unwrap();
+                }
+            };
+
+        let mut frame = ffmpeg::frame::Video::new(ffmpeg::format::Pixel::YUV420P, width, height);
+        for i in 0..seconds * i64::from(FPS) {
+            // Vary brightness so frames aren't identical
+            frame.data_mut(0).fill((i * 4 % 256) as u8);
+            frame.data_mut(1).fill(128);
+            frame.data_mut(2).fill(128);
+            frame.set_pts(Some(i));
+            encoder.send_frame(&frame).unwrap();
+            write_packets(&mut encoder, &mut output);
+        }
+        encoder.send_eof().unwrap();
+        write_packets(&mut encoder, &mut output);
+        output.write_trailer().unwrap();
+    }
+
+    #[test]
+    fn test_video_only_source_produces_single_stream_segments() {
+        ffmpeg::init().unwrap();
+        if ffmpeg::encoder::find_by_name(SOFTWARE_H264_ENCODER).is_none() {
+            eprintln!("skipping: {SOFTWARE_H264_ENCODER} not available");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("screen-recording.mkv");
+        write_silent_video(&source, 960, 540, 12);
+
+        let target = TranscodeTarget::Resolution480p;
+        let playlist = generate_hls_playlist(&source, target, "screen-recording").unwrap();
+        let segment_count = playlist.lines().filter(|l| l.ends_with(".ts")).count() as u32;
+        assert_eq!(segment_count, 2, "12s source should give 2 segments");
+
+        for index in 0..segment_count {
+            let data =
+                transcode_segment(&source, target, index, Some(SOFTWARE_ENCODER_ALIAS), None)
+                    .unwrap_or_else(|e| panic!("segment {index} failed: {e}"));
+
+            let segment_path = dir.path().join(format!("segment-{index}.ts"));
+            std::fs::write(&segment_path, &data).unwrap();
+            let segment = ffmpeg::format::input(&segment_path).unwrap();
+            let streams: Vec<_> = segment.streams().collect();
+            assert_eq!(streams.len(), 1, "segment {index} should be video-only");
+            let params = streams[0].parameters();
+            assert_eq!(params.medium(), ffmpeg::media::Type::Video);
+            assert_eq!(params.id(), ffmpeg::codec::Id::H264);
+        }
+    }
+
     // HLS URL parsing tests
 
     #[test]
@@ -1002,6 +1397,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_hls_master() {
+        assert_eq!(
+            parse_hls_request("videos/demo.m3u8"),
+            Some(HlsRequest::Master {
+                video_path: "videos/demo.mp4".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_master_playlist_lists_applicable_variants() {
+        let resolution = VideoResolution {
+            width: 1920,
+            height: 1080,
+            duration_secs: 60.0,
+        };
+        let playlist = build_master_playlist(&resolution, "demo").unwrap();
+        assert_eq!(
+            playlist,
+            "#EXTM3U\n#EXT-X-VERSION:3\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=2628000,RESOLUTION=1280x720\ndemo-720p.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=1096000,RESOLUTION=854x480\ndemo-480p.m3u8\n"
+        );
+
+        // A 720p source only gets the 480p variant
+        let resolution = VideoResolution {
+            width: 1280,
+            height: 720,
+            duration_secs: 60.0,
+        };
+        let playlist = build_master_playlist(&resolution, "demo").unwrap();
+        assert!(!playlist.contains("demo-720p.m3u8"));
+        assert!(playlist.contains("demo-480p.m3u8"));
+
+        let resolution = VideoResolution {
+            width: 640,
+            height: 360,
+            duration_secs: 60.0,
+        };
+        assert!(matches!(
+            build_master_playlist(&resolution, "demo"),
+            Err(TranscodeError::SourceTooSmall {
+                source_height: 360,
+                target_height: 480,
+            })
+        ));
+    }
+
     #[test]
     fn test_parse_original_mp4_not_matched() {
         assert!(parse_hls_request("videos/demo.mp4").is_none());
@@ -1080,6 +1524,23 @@ mod tests {
         assert_eq!(w % 2, 0); // Width should be even
     }
 
+    #[test]
+    fn test_transcode_error_info() {
+        let info = TranscodeErrorInfo::from(&TranscodeError::SourceTooSmall {
+            source_height: 360,
+            target_height: 480,
+        });
+        assert_eq!(info.error, "SourceTooSmall");
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::json!({
+                "error": "SourceTooSmall",
+                "message": "Source video (360p) is not larger than target (480p)",
+            })
+        );
+        assert_eq!(info.to_string(), info.message);
+    }
+
     #[test]
     fn test_transcode_target_properties() {
         assert_eq!(TranscodeTarget::Resolution720p.height(), 720);
@@ -1095,8 +1556,8 @@ mod tests {
     fn test_segment_temp_files_are_unique_and_cleaned_up() {
         // Two temp files created for the "same" segment must not collide, and
         // each must be removed when its handle is dropped.
-        let a = create_segment_temp_file().expect("temp file a");
-        let b = create_segment_temp_file().expect("temp file b");
+        let a = create_segment_temp_file(None).expect("temp file a");
+        let b = create_segment_temp_file(None).expect("temp file b");
 
         let path_a = a.path().to_path_buf();
         let path_b = b.path().to_path_buf();
@@ -1116,6 +1577,64 @@ mod tests {
         assert!(!path_b.exists());
     }
 
+    #[test]
+    fn test_cleanup_stale_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = std::time::SystemTime::now() - 2 * STALE_TEMP_FILE_AGE;
+        let write = |name: &str, modified: Option<std::time::SystemTime>| {
+            let path = dir.path().join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            if let Some(modified) = modified {
+                file.set_modified(modified).unwrap();
+            }
+            path
+        };
+
+        let stale_segment = write("mbr_segment_abc123.ts", Some(old));
+        let stale_audio = write("mbr_audio_def456.opus", Some(old));
+        let fresh_segment = write("mbr_segment_ghi789.ts", None);
+        let unrelated = write("other_segment.ts", Some(old));
+
+        assert_eq!(
+            cleanup_stale_temp_files(Some(dir.path()), STALE_TEMP_FILE_AGE),
+            2
+        );
+        assert!(!stale_segment.exists());
+        assert!(!stale_audio.exists());
+        assert!(
+            fresh_segment.exists(),
+            "in-flight transcodes are left alone"
+        );
+        assert!(unrelated.exists(), "only mbr temp files are touched");
+
+        assert_eq!(
+            cleanup_stale_temp_files(Some(&dir.path().join("missing")), STALE_TEMP_FILE_AGE),
+            0
+        );
+    }
+
+    #[test]
+    fn test_segment_temp_files_in_configured_dir_are_unique_under_concurrency() {
+        let root = tempfile::tempdir().unwrap();
+        // Doesn't exist yet; the first transcode creates it
+        let tmp_dir = root.path().join("transcode").join("tmp");
+
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let tmp_dir = tmp_dir.clone();
+                std::thread::spawn(move || {
+                    create_segment_temp_file(Some(&tmp_dir)).expect("temp file")
+                })
+            })
+            .collect();
+        let files: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        let paths: std::collections::HashSet<_> =
+            files.iter().map(|f| f.path().to_path_buf()).collect();
+        assert_eq!(paths.len(), files.len(), "temp file names must be unique");
+        assert!(paths.iter().all(|p| p.parent() == Some(tmp_dir.as_path())));
+    }
+
     #[test]
     fn test_is_supported_video() {
         assert!(is_supported_video("video.mp4"));
@@ -2,10 +2,12 @@
 //!
 //! Provides a thread-safe, size-bounded cache for HLS playlists and transcoded
 //! video segments to avoid redundant transcoding operations. Handles concurrent
-//! requests by ensuring only one transcode runs per segment.
+//! requests by ensuring only one transcode runs per segment. Whole-file audio
+//! transcodes share the same cache and single-flight machinery.
 
+use crate::audio::AudioTranscodeFormat;
 use crate::cache::SizeBoundedMap;
-use crate::video_transcode::{TranscodeError, TranscodeTarget};
+use crate::video_transcode::{TranscodeError, TranscodeErrorInfo, TranscodeTarget};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,6 +40,12 @@ pub enum HlsCacheKey {
         target: TranscodeTarget,
         segment_index: u32,
     },
+    /// Key for a whole-file audio transcode
+    Audio {
+        path: PathBuf,
+        format: AudioTranscodeFormat,
+        bitrate_kbps: u32,
+    },
 }
 
 impl HlsCacheKey {
@@ -54,6 +62,15 @@ impl HlsCacheKey {
             segment_index,
         }
     }
+
+    /// Creates a new audio transcode cache key.
+    pub fn audio(path: PathBuf, format: AudioTranscodeFormat, bitrate_kbps: u32) -> Self {
+        Self::Audio {
+            path,
+            format,
+            bitrate_kbps,
+        }
+    }
 }
 
 /// State of a cache entry.
@@ -63,8 +80,8 @@ pub enum HlsCacheState {
     InProgress(Arc<Notify>),
     /// Content is ready (playlist text or segment binary data)
     Complete(Arc<Vec<u8>>),
-    /// Content generation failed with an error message
-    Failed(String),
+    /// Content generation failed with the given error
+    Failed(TranscodeErrorInfo),
 }
 
 /// Thread-safe cache for HLS playlists and segments.
@@ -116,12 +133,12 @@ impl HlsCache {
                 HlsCacheState::Complete(data) => {
                     Some(HlsCacheStartResult::AlreadyComplete(data.clone()))
                 }
-                HlsCacheState::Failed(msg) => {
+                HlsCacheState::Failed(info) => {
                     // Only honor the failure while it is still fresh. Once the
                     // TTL elapses, fall through to start a new generation so a
                     // transient error can be retried.
                     if entry.inserted_at.elapsed() < FAILED_ENTRY_TTL {
-                        Some(HlsCacheStartResult::PreviouslyFailed(msg.clone()))
+                        Some(HlsCacheStartResult::PreviouslyFailed(info.clone()))
                     } else {
                         tracing::debug!("Failed entry for {:?} expired; allowing retry", key);
                         None
@@ -188,7 +205,7 @@ impl HlsCache {
 
         let (replaced, _) =
             self.cache
-                .insert_weighted(key.clone(), HlsCacheState::Failed(error.to_string()), 0);
+                .insert_weighted(key.clone(), HlsCacheState::Failed(error.into()), 0);
         tracing::warn!("Generation failed for {:?}: {}", key, error);
 
         // Notify waiters (so they know to check the state)
@@ -283,7 +300,10 @@ impl HlsCache {
     pub fn insert_failed_for_test(&self, key: HlsCacheKey, message: &str, created_at: Instant) {
         self.cache.insert_weighted_at(
             key,
-            HlsCacheState::Failed(message.to_string()),
+            HlsCacheState::Failed(TranscodeErrorInfo {
+                error: "TranscodeFailed",
+                message: message.to_string(),
+            }),
             0,
             created_at,
         );
@@ -299,7 +319,7 @@ pub enum HlsCacheStartResult {
     /// Content already generated - use the cached data
     AlreadyComplete(Arc<Vec<u8>>),
     /// A previous generation failed - caller can retry or serve original
-    PreviouslyFailed(String),
+    PreviouslyFailed(TranscodeErrorInfo),
     /// Cache is disabled (max_size = 0)
     CacheDisabled,
 }
@@ -491,6 +511,28 @@ mod tests {
         assert!(matches!(result, HlsCacheStartResult::Started(_)));
     }
 
+    #[test]
+    fn test_audio_keys_separate_by_format_and_bitrate() {
+        let cache = HlsCache::new(1024 * 1024);
+        let path = PathBuf::from("/music/song.flac");
+
+        let opus_128 = HlsCacheKey::audio(path.clone(), AudioTranscodeFormat::Opus, 128);
+        cache.start_generation(opus_128.clone());
+        cache.complete_generation(opus_128.clone(), vec![0u8; 100]);
+
+        for key in [
+            HlsCacheKey::audio(path.clone(), AudioTranscodeFormat::Opus, 96),
+            HlsCacheKey::audio(path, AudioTranscodeFormat::Aac, 128),
+        ] {
+            let result = cache.start_generation(key);
+            assert!(matches!(result, HlsCacheStartResult::Started(_)));
+        }
+        assert!(matches!(
+            cache.start_generation(opus_128),
+            HlsCacheStartResult::AlreadyComplete(_)
+        ));
+    }
+
     #[test]
     fn test_playlist_and_segment_are_separate() {
         let cache = HlsCache::new(1024 * 1024);
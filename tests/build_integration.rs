@@ -118,6 +118,67 @@ async fn test_build_embeds_bare_giphy_url_without_network() {
     );
 }
 
+#[tokio::test]
+async fn test_build_site_nav_uses_relative_urls() {
+    let repo = TestRepo::new();
+    repo.create_markdown("index.md", "# Home");
+    repo.create_markdown("docs/guide.md", "# Guide");
+    repo.create_static_file(
+        ".mbr/nav.toml",
+        b"[[nav]]\ntitle = \"Guide\"\nurl = \"/docs/guide/\"\n\n[[nav]]\ntitle = \"Upstream\"\nurl = \"https://example.com/\"\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        sidebar_style: "single".to_string(),
+        ..Default::default()
+    };
+    let output_dir = repo.path().join("build");
+    let builder =
+        mbr::build::Builder::new(config, output_dir.clone()).expect("Failed to create builder");
+    builder.build().await.expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.join("docs/guide/index.html")).unwrap();
+    // Tera escapes `/` in attribute values
+    assert!(html.contains(r#"<a href="..&#x2F;..&#x2F;docs&#x2F;guide&#x2F;">Guide</a>"#));
+    assert!(html.contains(r#"<a href="https:&#x2F;&#x2F;example.com&#x2F;">Upstream</a>"#));
+}
+
+#[tokio::test]
+async fn test_build_reading_order_crosses_directories() {
+    let repo = TestRepo::new();
+    repo.create_markdown("intro.md", "# Intro");
+    repo.create_markdown("guide/setup.md", "# Setup");
+    repo.create_markdown("guide/advanced/tuning.md", "# Tuning");
+    repo.create_markdown("guide/aside.md", "# Aside");
+    repo.create_markdown(
+        "SUMMARY.md",
+        "# Summary\n\n- [Intro](intro.md)\n- [Setup](guide/setup.md)\n  - [Tuning](guide/advanced/tuning.md)\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        reading_order: Some("SUMMARY.md".to_string()),
+        ..Default::default()
+    };
+    let output_dir = repo.path().join("build");
+    let builder =
+        mbr::build::Builder::new(config, output_dir.clone()).expect("Failed to create builder");
+    builder.build().await.expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.join("guide/setup/index.html")).unwrap();
+    assert!(html.contains(r#"prevPage: { url: "../../intro/", title: "Intro" }"#));
+    assert!(html.contains(r#"nextPage: { url: "../../guide/advanced/tuning/", title: "Tuning" }"#));
+
+    let html = fs::read_to_string(output_dir.join("guide/advanced/tuning/index.html")).unwrap();
+    assert!(html.contains(r#"prevPage: { url: "../../../guide/setup/", title: "Setup" }"#));
+    assert!(!html.contains("nextPage:"));
+
+    // Pages outside the order keep sibling navigation
+    let html = fs::read_to_string(output_dir.join("guide/aside/index.html")).unwrap();
+    assert!(html.contains(r#"nextPage: { url: "../../guide/setup/", title: "Setup" }"#));
+}
+
 #[tokio::test]
 async fn test_build_creates_section_pages() {
     let repo = TestRepo::new();
@@ -356,6 +417,423 @@ async fn test_build_excludes_hidden_directories() {
     }
 }
 
+#[tokio::test]
+async fn test_build_respects_gitignore() {
+    let repo = TestRepo::new();
+    repo.create_static_file(".gitignore", b"drafts/\n*.scratch.md\n");
+    repo.create_markdown("drafts/wip.md", "# WIP");
+    repo.create_markdown("notes.scratch.md", "# Scratch");
+    repo.create_markdown("readme.md", "# README");
+
+    let output = build_site(&repo).await;
+
+    assert!(!output.join("drafts").exists(), "gitignored dir was built");
+    assert!(
+        !output.join("notes.scratch").exists(),
+        "gitignored file was built"
+    );
+    assert!(output.join("readme").join("index.html").exists());
+}
+
+#[tokio::test]
+async fn test_build_gitignore_disabled_includes_ignored_files() {
+    let repo = TestRepo::new();
+    repo.create_static_file(".gitignore", b"drafts/\n");
+    repo.create_markdown("drafts/wip.md", "# WIP");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        respect_gitignore: false,
+        ..Default::default()
+    };
+    let output = repo.path().join("build");
+    mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert!(
+        output
+            .join("drafts")
+            .join("wip")
+            .join("index.html")
+            .exists()
+    );
+}
+
+// ============================================================================
+// Image Variant Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_build_generates_webp_variants_with_picture_markup() {
+    let repo = TestRepo::new();
+    repo.create_dir("images");
+    image::RgbImage::from_pixel(256, 256, image::Rgb([20, 120, 200]))
+        .save(repo.path().join("images").join("flat.png"))
+        .unwrap();
+    repo.create_static_file("images/broken.png", b"not really a png");
+    repo.create_markdown(
+        "docs/page.md",
+        "# Page\n\n![Flat](../images/flat.png)\n\n![Broken](../images/broken.png)\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        image_formats: vec!["webp".to_string()],
+        ..Default::default()
+    };
+    let output = repo.path().join("build");
+    let stats = mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert_eq!(stats.image_variants, 1);
+    assert!(output.join("images").join("flat.png.webp").exists());
+    assert!(!output.join("images").join("broken.png.webp").exists());
+
+    let html = fs::read_to_string(output.join("docs").join("page").join("index.html")).unwrap();
+    assert!(
+        html.contains(r#"<picture><source type="image/webp" srcset="../../images/flat.png.webp">"#),
+        "expected <picture> markup for the transcoded image"
+    );
+    assert!(
+        !html.contains("broken.png.webp"),
+        "images without variants must not reference one"
+    );
+}
+
+#[tokio::test]
+async fn test_build_without_image_formats_emits_plain_img() {
+    let repo = TestRepo::new();
+    repo.create_dir("images");
+    image::RgbImage::from_pixel(64, 64, image::Rgb([0, 0, 0]))
+        .save(repo.path().join("images").join("dot.png"))
+        .unwrap();
+    repo.create_markdown("page.md", "![Dot](images/dot.png)");
+
+    let output = build_site(&repo).await;
+
+    assert!(!output.join("images").join("dot.png.webp").exists());
+    let html = fs::read_to_string(output.join("page").join("index.html")).unwrap();
+    assert!(!html.contains("<picture>"));
+}
+
+#[tokio::test]
+async fn test_build_writes_gallery_thumbnails() {
+    let repo = TestRepo::new();
+    repo.create_dir("photos");
+    image::RgbImage::from_pixel(1000, 500, image::Rgb([20, 120, 200]))
+        .save(repo.path().join("photos").join("wide.jpg"))
+        .unwrap();
+    repo.create_markdown("docs/trip.md", "# Trip\n\n{{ gallery(dir=\"photos\") }}\n");
+
+    let output = repo.path().join("build");
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        ..Default::default()
+    };
+    let stats = mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert_eq!(stats.gallery_thumbnails, 1);
+    assert_eq!(stats.broken_links, 0);
+    let thumb = image::open(output.join(".mbr/thumbs/photos/wide.jpg")).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (480, 240));
+
+    let html = fs::read_to_string(output.join("docs").join("trip").join("index.html")).unwrap();
+    assert!(
+        html.contains(r#"<img src="../../.mbr/thumbs/photos/wide.jpg""#),
+        "gallery thumbnails should use relative links: {html}"
+    );
+    assert!(html.contains(r#"href="../../.mbr/images/?path=/photos/wide.jpg""#));
+}
+
+#[tokio::test]
+async fn test_build_flat_html_output() {
+    let repo = TestRepo::new();
+    repo.create_markdown("index.md", "# Home\n\nSee [the guide](docs/guide.md).\n");
+    repo.create_markdown("docs/index.md", "# Docs\n\n[Guide](guide.md)\n");
+    repo.create_markdown(
+        "docs/guide.md",
+        "---\ntags: [howto]\n---\n\n# Guide\n\n![Diagram](images/diagram.png)\n\n[Home](../index.md) and [notes](../notes/)\n",
+    );
+    repo.create_markdown("notes/todo.md", "# Todo\n");
+    repo.create_static_file("docs/images/diagram.png", b"png");
+
+    let output = repo.path().join("build");
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        clean_urls: false,
+        ..Default::default()
+    };
+    let stats = mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(stats.broken_links, 0);
+
+    // Non-index pages sit beside their source; index pages keep index.html
+    assert!(output.join("docs/guide.html").is_file());
+    assert!(!output.join("docs/guide/index.html").exists());
+    assert!(output.join("docs/index.html").is_file());
+    assert!(output.join("notes/todo.html").is_file());
+
+    let guide = fs::read_to_string(output.join("docs/guide.html")).unwrap();
+    assert!(guide.contains(r#"src="images/diagram.png""#), "{guide}");
+    assert!(guide.contains(r#"href="../index.html""#), "{guide}");
+    assert!(guide.contains(r#"href="../notes/index.html""#), "{guide}");
+    // Assets resolve from the page's real depth
+    assert!(
+        guide.contains(r#"href="..&#x2F;.mbr&#x2F;theme.css""#),
+        "{guide}"
+    );
+
+    let home = fs::read_to_string(output.join("index.html")).unwrap();
+    assert!(home.contains(r#"href="docs/guide.html""#), "{home}");
+
+    // Listings and tag pages link to the .html files too
+    let notes = fs::read_to_string(output.join("notes/index.html")).unwrap();
+    assert!(notes.contains(r#"href="../notes/todo.html""#), "{notes}");
+    let tag = fs::read_to_string(output.join("tags/howto/index.html")).unwrap();
+    assert!(tag.contains("docs/guide.html"), "{tag}");
+}
+
+#[tokio::test]
+async fn test_build_git_metadata_shows_last_commit() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/tracked.md", "# Tracked\n");
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo.path())
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Ada Lovelace")
+            .env("GIT_AUTHOR_EMAIL", "ada@example.com")
+            .env("GIT_COMMITTER_NAME", "Ada Lovelace")
+            .env("GIT_COMMITTER_EMAIL", "ada@example.com")
+            .env("GIT_COMMITTER_DATE", "2024-01-05T12:00:00Z")
+            .status()
+            .expect("git runs");
+        assert!(status.success(), "git {args:?} failed");
+    };
+    git(&["init", "-q"]);
+    git(&["add", "docs/tracked.md"]);
+    git(&["commit", "-q", "-m", "Add tracked page"]);
+    repo.create_markdown("docs/untracked.md", "# Untracked\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        git_metadata: true,
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = repo.path().join("build");
+    mbr::build::Builder::new(config, output_dir.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.join("docs/tracked/index.html")).unwrap();
+    assert!(html.contains("by Ada Lovelace"), "missing last commit line");
+    // The commit date replaces the file modification time
+    assert!(html.contains("modifiedTimestamp: 1704456000"));
+
+    let html = fs::read_to_string(output_dir.join("docs/untracked/index.html")).unwrap();
+    assert!(!html.contains("class=\"last-commit\""));
+}
+
+#[tokio::test]
+async fn test_partial_build_only_updates_prefix() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/a.md", "# Docs A\n\nFirst draft.");
+    repo.create_markdown("notes/b.md", "# Notes B\n\nFirst draft.");
+    let output = build_site(&repo).await;
+
+    repo.create_markdown("docs/a.md", "# Docs A\n\nSecond draft.");
+    repo.create_markdown("docs/c.md", "# Docs C\n");
+    repo.create_markdown("notes/b.md", "# Notes B\n\nSecond draft.");
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        build_only: Some("docs/".to_string()),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let stats = mbr::build::Builder::new(config.clone(), output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(stats.markdown_pages, 2);
+
+    let docs_a = fs::read_to_string(output.join("docs/a/index.html")).unwrap();
+    assert!(docs_a.contains("Second draft."));
+    // The section page is regenerated even though the old one exists
+    let docs_section = fs::read_to_string(output.join("docs/index.html")).unwrap();
+    assert!(docs_section.contains("Docs C"));
+    // Everything outside the prefix is left from the first build
+    let notes_b = fs::read_to_string(output.join("notes/b/index.html")).unwrap();
+    assert!(notes_b.contains("First draft."));
+    assert!(output.join(".mbr/pagefind").exists());
+
+    // There's nothing to update without a previous build
+    let result = mbr::build::Builder::new(config, repo.path().join("missing"))
+        .expect("Failed to create builder")
+        .build()
+        .await;
+    assert!(matches!(
+        result,
+        Err(mbr::BuildError::PartialBuildWithoutOutput { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_build_records_stage_durations() {
+    let repo = TestRepo::new();
+    repo.create_markdown("notes/a.md", "# A\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    for stage in [
+        "scan", "clean", "render", "sections", "tags", "assets", "static", "mbr", "links",
+    ] {
+        assert!(
+            stats.stage_durations.contains_key(stage),
+            "missing {stage}: {:?}",
+            stats.stage_durations
+        );
+    }
+    // Skipped or disabled stages aren't recorded
+    assert!(!stats.stage_durations.contains_key("search"));
+    assert!(!stats.stage_durations.contains_key("archive"));
+    let staged: std::time::Duration = stats.stage_durations.values().sum();
+    assert!(staged <= stats.duration);
+}
+
+#[tokio::test]
+async fn test_build_to_memory_sink_leaves_disk_untouched() {
+    use mbr::build_sink::{BuildSink, MemoryEntry, MemorySink};
+    use std::sync::Arc;
+
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/guide.md", "# Guide\n\nSee ![pic](pic.png).");
+    repo.create_static_file("docs/pic.png", b"not really a png");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        ..Default::default()
+    };
+    let output_dir = repo.path().join("build");
+    let sink = Arc::new(MemorySink::new());
+    mbr::build::Builder::new(config, output_dir.clone())
+        .expect("Failed to create builder")
+        .build_to(sink.clone())
+        .await
+        .expect("Build failed");
+
+    assert!(!output_dir.exists(), "nothing should be written to disk");
+    let html = sink
+        .read_to_string(&output_dir.join("docs/guide/index.html"))
+        .expect("page written to the sink");
+    assert!(html.contains("Guide</h1>"));
+    assert!(sink.exists(&output_dir.join(".mbr/site.json")));
+    assert!(matches!(
+        sink.entry(&output_dir.join("docs/pic.png")),
+        Some(MemoryEntry::Symlink(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_build_card_listing_with_cover_thumbnails() {
+    let repo = TestRepo::new();
+    repo.create_dir("posts/images");
+    image::RgbImage::from_pixel(1000, 500, image::Rgb([20, 120, 200]))
+        .save(repo.path().join("posts").join("images").join("hero.jpg"))
+        .unwrap();
+    repo.create_markdown(
+        "posts/first.md",
+        "---\ntitle: First\nimage: images/hero.jpg\n---\n\nBody\n",
+    );
+    repo.create_markdown(
+        "posts/second.md",
+        "# Second\n\n![diagram](https://example.com/diagram.png)\n",
+    );
+    repo.create_markdown("posts/plain.md", "# Plain\n\nNo images.\n");
+
+    let output = repo.path().join("build");
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        listing_layout: "cards".to_string(),
+        listing_thumbnails: true,
+        ..Default::default()
+    };
+    let stats = mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert_eq!(stats.gallery_thumbnails, 1);
+    assert_eq!(stats.broken_links, 0);
+    assert!(output.join(".mbr/thumbs/posts/images/hero.jpg").is_file());
+
+    let html = fs::read_to_string(output.join("posts").join("index.html")).unwrap();
+    assert!(html.contains(r#"class="mbr-listing mbr-listing-cards""#));
+    assert!(
+        html.contains(r#"src="..&#x2F;.mbr&#x2F;thumbs&#x2F;posts&#x2F;images&#x2F;hero.jpg""#),
+        "local covers use relative thumbnail links: {html}"
+    );
+    assert!(html.contains("https:&#x2F;&#x2F;example.com&#x2F;diagram.png"));
+    assert_eq!(html.matches("mbr-listing-cover").count(), 2);
+}
+
+#[tokio::test]
+async fn test_build_list_layout_has_no_covers() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/first.md",
+        "---\ntitle: First\nimage: hero.jpg\n---\n\nBody\n",
+    );
+
+    let output = repo.path().join("build");
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        listing_thumbnails: true,
+        ..Default::default()
+    };
+    let stats = mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert_eq!(stats.gallery_thumbnails, 0);
+    let html = fs::read_to_string(output.join("posts").join("index.html")).unwrap();
+    assert!(!html.contains("mbr-listing-cover"));
+}
+
 // ============================================================================
 // Static Mode Configuration Tests
 // ============================================================================
@@ -396,6 +874,8 @@ async fn test_build_creates_site_json() {
     let content = fs::read_to_string(&site_json_path).unwrap();
     let body: serde_json::Value = serde_json::from_str(&content).unwrap();
 
+    assert_eq!(body["schema_version"], mbr::repo::SITE_JSON_SCHEMA_VERSION);
+
     // Should have markdown_files array
     assert!(
         body["markdown_files"].is_array(),
@@ -409,6 +889,51 @@ async fn test_build_creates_site_json() {
     );
 }
 
+#[tokio::test]
+async fn test_build_pretty_json() {
+    let repo = TestRepo::new();
+    repo.create_markdown("one.md", "# One\n\n[Two](../two/)\n");
+    repo.create_markdown("two.md", "# Two\n");
+
+    let build = |pretty_json| {
+        let config = mbr::Config {
+            root_dir: repo.path().to_path_buf(),
+            skip_search_index: true,
+            pretty_json,
+            ..Default::default()
+        };
+        async move {
+            let output_dir = tempfile::tempdir().unwrap();
+            mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+                .expect("Failed to create builder")
+                .build()
+                .await
+                .expect("Build failed");
+            let read = |path: &str| fs::read_to_string(output_dir.path().join(path)).unwrap();
+            (read(".mbr/site.json"), read("two/links.json"))
+        }
+    };
+
+    let (site_json, links_json) = build(false).await;
+    assert!(!site_json.contains('\n'));
+    assert!(!links_json.contains('\n'));
+
+    let (pretty_site, pretty_links) = build(true).await;
+    assert!(
+        pretty_site.contains("\n  \"markdown_files\": ["),
+        "{pretty_site}"
+    );
+    assert!(
+        pretty_links.contains("\n  \"inbound\": ["),
+        "{pretty_links}"
+    );
+    // Same data either way
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&pretty_links).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&links_json).unwrap()
+    );
+}
+
 #[tokio::test]
 async fn test_build_site_json_includes_frontmatter() {
     let repo = TestRepo::new();
@@ -746,6 +1271,39 @@ async fn test_build_links_json_contains_inbound_links() {
     );
 }
 
+#[tokio::test]
+async fn test_build_consolidated_links_json() {
+    let repo = TestRepo::new();
+    repo.create_markdown("source.md", "# Source\n\n[Go to Target](target/)");
+    repo.create_markdown("target.md", "# Target Page");
+
+    for (mode, per_page) in [
+        (mbr::link_index::LinkIndexMode::Consolidated, false),
+        (mbr::link_index::LinkIndexMode::Both, true),
+    ] {
+        let config = mbr::Config {
+            root_dir: repo.path().to_path_buf(),
+            link_index_mode: mode,
+            ..Default::default()
+        };
+        let output_dir = tempfile::tempdir().unwrap();
+        let output = output_dir.path().to_path_buf();
+        let builder =
+            mbr::build::Builder::new(config, output.clone()).expect("Failed to create builder");
+        builder.build().await.expect("Build failed");
+
+        let content = fs::read_to_string(output.join(".mbr").join("links.json")).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["/source/"]["outbound"][0]["to"], "target/");
+        assert_eq!(json["/target/"]["inbound"][0]["from"], "/source/");
+        assert_eq!(
+            output.join("target").join("links.json").exists(),
+            per_page,
+            "{mode:?}"
+        );
+    }
+}
+
 #[tokio::test]
 async fn test_build_links_json_bidirectional() {
     let repo = TestRepo::new();
@@ -779,6 +1337,33 @@ async fn test_build_links_json_bidirectional() {
     );
 }
 
+#[tokio::test]
+async fn test_build_links_json_inbound_context() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "source.md",
+        "# Source\n\nBefore you deploy, read [the target](target/) carefully.",
+    );
+    repo.create_markdown("target.md", "# Target");
+
+    let output = build_site(&repo).await;
+
+    let json: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output.join("target").join("links.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json["inbound"][0]["context"],
+        "Before you deploy, read the target carefully."
+    );
+    // Outbound entries don't repeat the context of the page's own links
+    let source: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output.join("source").join("links.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(source["outbound"][0].get("context").is_none());
+}
+
 #[tokio::test]
 async fn test_build_links_json_includes_anchors() {
     let repo = TestRepo::new();
@@ -883,6 +1468,33 @@ async fn test_build_detects_broken_internal_links() {
     );
 }
 
+#[tokio::test]
+async fn test_build_copies_and_validates_vid_subtitle_tracks() {
+    let repo = TestRepo::new();
+    repo.create_static_file("videos/talk.mp4", b"fake video");
+    repo.create_static_file("videos/subs/en.vtt", b"WEBVTT\n");
+    repo.create_markdown(
+        "page.md",
+        "# Page\n\n{{ vid(path=\"talk.mp4\", captions=\"subs/en.vtt, subs/missing.fr.vtt\") }}\n",
+    );
+
+    let (output, stats) = build_site_with_stats(&repo).await;
+
+    assert!(
+        output.join("videos/subs/en.vtt").exists(),
+        "Caption files should be included with the static assets"
+    );
+    let html = fs::read_to_string(output.join("page/index.html")).unwrap();
+    assert!(
+        html.contains(r#"<track kind="subtitles" label="en" src="../videos/subs/en.vtt" srclang="en" default />"#),
+        "Subtitle track should use a relative src. Got: {html}"
+    );
+    assert_eq!(
+        stats.broken_links, 1,
+        "The missing caption file should be reported"
+    );
+}
+
 #[tokio::test]
 async fn test_build_no_false_positives_for_valid_links() {
     let repo = TestRepo::new();
@@ -972,15 +1584,953 @@ async fn test_build_skip_link_checks() {
 }
 
 #[tokio::test]
-async fn test_build_validates_symlinked_assets() {
+async fn test_build_search_partial_points_at_pagefind_bundle() {
     let repo = TestRepo::new();
+    repo.create_markdown("docs/guide.md", "# Guide\n\nSearchable text.");
 
-    // Create a static folder with an asset
-    repo.create_dir("static/images");
-    repo.create_static_file("static/images/logo.png", b"fake image data");
+    let output = build_site(&repo).await;
 
-    // Create a page linking to the symlinked asset
-    repo.create_markdown("page.md", "# Page\n\n![Logo](/images/logo.png)");
+    let html = fs::read_to_string(output.join("docs/guide/index.html")).unwrap();
+    assert!(
+        html.contains(r#"pagefind-path="../../.mbr/pagefind/""#),
+        "search element should point at the Pagefind bundle: {html}"
+    );
+    assert!(output.join(".mbr/pagefind/pagefind.js").exists());
+    assert!(output.join(".mbr/pagefind/pagefind-ui.js").exists());
+}
+
+#[tokio::test]
+async fn test_build_uses_custom_search_partial() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page");
+    repo.create_static_file(
+        ".mbr/_search.html",
+        br#"<div id="my-search" data-bundle="{{ relative_base | safe }}pagefind/"></div>"#,
+    );
+
+    let output = build_site(&repo).await;
+
+    let html = fs::read_to_string(output.join("page/index.html")).unwrap();
+    assert!(html.contains(r#"<div id="my-search" data-bundle="../.mbr/pagefind/"></div>"#));
+    assert!(!html.contains("<mbr-search"));
+}
+
+#[tokio::test]
+async fn test_build_html_lang_and_dir() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/page.md", "# Page");
+    repo.create_markdown(
+        "docs/english.md",
+        "---\nlang: en-GB\ndir: ltr\n---\n# Colour",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        site_lang: "fa".to_string(),
+        site_dir: "rtl".to_string(),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().to_path_buf();
+    let builder =
+        mbr::build::Builder::new(config, output.clone()).expect("Failed to create builder");
+    builder.build().await.expect("Build failed");
+
+    for page in ["docs/page/index.html", "docs/index.html", "index.html"] {
+        let html = fs::read_to_string(output.join(page)).unwrap();
+        assert!(
+            html.contains(r#"<html lang="fa" dir="rtl">"#),
+            "{page} should use the site language"
+        );
+    }
+    let html = fs::read_to_string(output.join("docs/english/index.html")).unwrap();
+    assert!(html.contains(r#"<html lang="en-GB" dir="ltr">"#));
+}
+
+#[tokio::test]
+async fn test_build_interactive_tasklists_opt_in() {
+    let repo = TestRepo::new();
+    repo.create_markdown("todo.md", "# Todo\n\n- [ ] milk\n- [x] eggs");
+
+    for (setting, expected) in [(None, false), (Some(true), true)] {
+        let config = mbr::Config {
+            root_dir: repo.path().to_path_buf(),
+            skip_search_index: true,
+            interactive_tasklists: setting,
+            ..Default::default()
+        };
+        let output_dir = tempfile::tempdir().unwrap();
+        let output = output_dir.path().to_path_buf();
+        let builder =
+            mbr::build::Builder::new(config, output.clone()).expect("Failed to create builder");
+        builder.build().await.expect("Build failed");
+
+        let html = fs::read_to_string(output.join("todo/index.html")).unwrap();
+        assert_eq!(
+            html.contains("mbr-tasklist:"),
+            expected,
+            "interactive_tasklists = {setting:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_build_runs_post_build_command() {
+    let repo = TestRepo::new();
+    repo.create_markdown("one.md", "# One");
+    repo.create_markdown("two.md", "# Two");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        post_build_command: Some(
+            r#"echo "$MBR_MARKDOWN_PAGES $(basename "$PWD")" > "$MBR_OUTPUT_DIR/hook.txt""#
+                .to_string(),
+        ),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().to_path_buf();
+    let builder =
+        mbr::build::Builder::new(config, output.clone()).expect("Failed to create builder");
+    let stats = builder.build().await.expect("Build failed");
+
+    assert_eq!(stats.post_build_exit_code, Some(0));
+    let root_name = repo.path().file_name().unwrap().to_string_lossy();
+    assert_eq!(
+        fs::read_to_string(output.join("hook.txt")).unwrap().trim(),
+        format!("2 {root_name}")
+    );
+}
+
+#[tokio::test]
+async fn test_build_fails_when_post_build_command_fails() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        post_build_command: Some("echo deploying; exit 3".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let builder = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder");
+
+    let err = builder
+        .build()
+        .await
+        .expect_err("hook failure should fail the build");
+    assert!(matches!(
+        err,
+        mbr::errors::BuildError::PostBuildFailed { .. }
+    ));
+    assert!(err.to_string().contains("exit status: 3"), "{err}");
+}
+
+#[tokio::test]
+async fn test_build_skip_search_index() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n\nSome searchable content.");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().to_path_buf();
+
+    let builder =
+        mbr::build::Builder::new(config, output.clone()).expect("Failed to create builder");
+    let stats = builder.build().await.expect("Build failed");
+
+    assert_eq!(stats.pagefind_indexed, None);
+    assert!(!output.join(".mbr").join("pagefind").exists());
+    assert!(output.join("page").join("index.html").exists());
+}
+
+#[tokio::test]
+async fn test_build_reports_url_path_collisions() {
+    let repo = TestRepo::new();
+    repo.create_markdown("guide.md", "# Guide file\n");
+    repo.create_markdown("guide/index.md", "# Guide folder\n");
+    repo.create_markdown("other.md", "# Other\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config.clone(), output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Collisions only warn by default");
+    assert_eq!(stats.url_collisions, 1);
+
+    let strict = mbr::Config {
+        strict: true,
+        ..config
+    };
+    let err = mbr::build::Builder::new(strict, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect_err("strict builds fail on collisions");
+    assert!(matches!(
+        err,
+        mbr::errors::BuildError::UrlPathCollisions { count: 1 }
+    ));
+}
+
+#[tokio::test]
+async fn test_build_constrains_media_width() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "page.md",
+        "# Page\n\n![Photo](photo.png)\n\n<img src=\"wide.png\" data-max-width=\"none\">\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        media_max_width: Some("30rem".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("page/index.html")).unwrap();
+    assert!(
+        html.contains(r#"alt="Photo" style="max-width: 30rem; height: auto;""#),
+        "{html}"
+    );
+    assert!(
+        html.contains(
+            r#"<img src="../wide.png" data-max-width="none" loading="lazy" decoding="async">"#
+        ),
+        "data-max-width=\"none\" opts out: {html}"
+    );
+}
+
+#[tokio::test]
+async fn test_build_resolves_sibling_assets_from_page_url() {
+    let repo = TestRepo::new();
+    let body = "![X](images/x.png)\n\n<img src=\"images/y.png\" alt=\"Y\">\n";
+    repo.create_markdown("docs/guide.md", &format!("# Guide\n\n{body}"));
+    repo.create_markdown("docs/index.md", &format!("# Docs\n\n{body}"));
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    // docs/guide.md lives at /docs/guide/, one level below docs/images/
+    let guide = fs::read_to_string(output_dir.path().join("docs/guide/index.html")).unwrap();
+    assert!(guide.contains(r#"src="../images/x.png""#), "{guide}");
+    assert!(guide.contains(r#"src="../images/y.png""#), "{guide}");
+    // docs/index.md lives at /docs/, beside docs/images/
+    let index = fs::read_to_string(output_dir.path().join("docs/index.html")).unwrap();
+    assert!(index.contains(r#"src="images/x.png""#), "{index}");
+    assert!(index.contains(r#"src="images/y.png""#), "{index}");
+}
+
+#[tokio::test]
+async fn test_build_runs_post_render_hooks() {
+    use mbr::post_render::{HeadingAnchors, PostRenderHook, PostRenderPage};
+    use std::sync::Arc;
+
+    struct StripedTables;
+    impl PostRenderHook for StripedTables {
+        fn apply(&self, html: String, page: &PostRenderPage<'_>) -> String {
+            assert!(!page.server_mode);
+            html.replace(
+                "<table>",
+                &format!(r#"<table class="striped" data-page="{}">"#, page.url_path),
+            )
+        }
+    }
+
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "page.md",
+        "# Page\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n[Out](https://example.com/)\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        external_links_new_tab: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .with_post_render_hook(Arc::new(HeadingAnchors))
+        .with_post_render_hook(Arc::new(StripedTables))
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("page/index.html")).unwrap();
+    assert!(
+        html.contains(r#"<table class="striped" data-page="/page/">"#),
+        "{html}"
+    );
+    assert!(
+        html.contains(
+            r##"<a class="mbr-heading-anchor" href="#page" aria-label="Permalink">#</a></h1>"##
+        ),
+        "{html}"
+    );
+    // The built-in external-link hook still runs
+    assert!(html.contains(r#"class="mbr-external-link""#), "{html}");
+}
+
+#[tokio::test]
+async fn test_build_archive_pages() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/first.md",
+        "---\ntitle: First Post\ndate: 2024-01-10\n---\n\n# First\n",
+    );
+    repo.create_markdown(
+        "posts/second.md",
+        "---\ntitle: Second Post\ndate: 2024-03-05\n---\n\n# Second\n",
+    );
+    repo.create_markdown(
+        "posts/old.md",
+        "---\ntitle: Old Post\ncreated: 2023-06-01\n---\n\n# Old\n",
+    );
+    repo.create_markdown(
+        "index.md",
+        "# Home\n\n[2024 posts](/archive/2024/) and [March](/archive/2024/03/)\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        build_archive_pages: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    // Index + 2 years + 3 months
+    assert_eq!(stats.archive_pages, 6);
+    assert_eq!(stats.broken_links, 0, "archive URLs are valid link targets");
+
+    let index = fs::read_to_string(output_dir.path().join("archive/index.html")).unwrap();
+    // Tera escapes `/` in attributes as `&#x2F;`
+    assert!(
+        index.contains(r#"<a href="..&#x2F;archive&#x2F;2024&#x2F;">2024</a>"#),
+        "{index}"
+    );
+    let first_2024 = index.find("Second Post").unwrap();
+    assert!(first_2024 < index.find("First Post").unwrap());
+    assert!(index.find("First Post").unwrap() < index.find("Old Post").unwrap());
+
+    let year = fs::read_to_string(output_dir.path().join("archive/2024/index.html")).unwrap();
+    assert!(year.contains(">March 2024</a>"), "{year}");
+    assert!(year.contains(r#"<a href="..&#x2F;..&#x2F;posts&#x2F;first&#x2F;">First Post</a>"#));
+    assert!(!year.contains("Old Post"));
+
+    let month = fs::read_to_string(output_dir.path().join("archive/2024/03/index.html")).unwrap();
+    assert!(month.contains("<h1 data-pagefind-meta=\"title\">March 2024</h1>"));
+    assert!(month.contains("Second Post"));
+    assert!(!month.contains("First Post"));
+}
+
+#[tokio::test]
+async fn test_build_json_feed() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/first.md",
+        "---\ntitle: First Post\ndate: 2024-01-10\ndescription: The first one\n---\n\nHello **feed**. See [about](../about.md).\n",
+    );
+    repo.create_markdown(
+        "posts/second.md",
+        "---\ntitle: Second Post\ndate: 2024-03-05\n---\n\nSecond body.\n",
+    );
+    repo.create_markdown(
+        "posts/hidden.md",
+        "---\ntitle: Hidden Post\ndate: 2024-02-01\nrobots: noindex\n---\n\nHidden body.\n",
+    );
+    repo.create_markdown("about.md", "# About\n\nNot dated.\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        feed_formats: vec!["json".to_string()],
+        base_url: Some("https://notes.example.com/".to_string()),
+        site_name: Some("Field Notes".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(stats.feeds, 1);
+
+    let feed: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.path().join("feed.json")).unwrap())
+            .unwrap();
+    assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(feed["title"], "Field Notes");
+    assert_eq!(feed["feed_url"], "https://notes.example.com/feed.json");
+    let items = feed["items"].as_array().unwrap();
+    let urls: Vec<&str> = items.iter().map(|i| i["url"].as_str().unwrap()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://notes.example.com/posts/second/",
+            "https://notes.example.com/posts/first/"
+        ]
+    );
+    assert_eq!(items[1]["summary"], "The first one");
+    assert_eq!(items[1]["date_published"], "2024-01-10T00:00:00Z");
+    let content = items[1]["content_html"].as_str().unwrap();
+    assert!(content.contains("<strong>feed</strong>"), "{content}");
+    assert!(
+        content.contains(r#"href="https://notes.example.com/about/""#),
+        "links are absolute: {content}"
+    );
+}
+
+#[tokio::test]
+async fn test_build_json_feed_needs_base_url() {
+    let repo = TestRepo::new();
+    repo.create_markdown("post.md", "---\ndate: 2024-01-10\n---\n\n# Post\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        feed_formats: vec!["json".to_string()],
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(stats.feeds, 0);
+    assert!(!output_dir.path().join("feed.json").exists());
+}
+
+#[tokio::test]
+async fn test_build_redirect_to_writes_stand_in_page() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "guides/setup.md",
+        "---\ntitle: Setup\ndate: 2024-03-05\n---\n\n# Setup\n\nThe new setup guide.\n",
+    );
+    repo.create_markdown(
+        "old/install.md",
+        "---\ntitle: Install\ndate: 2024-01-10\nredirect_to: /guides/setup/\n---\n\n# Install\n\nSecretword lives here.\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        feed_formats: vec!["json".to_string()],
+        base_url: Some("https://notes.example.com/".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("old/install/index.html")).unwrap();
+    assert!(
+        html.contains(r#"<meta http-equiv="refresh" content="0; url=../../guides/setup/">"#),
+        "{html}"
+    );
+    assert!(
+        html.contains(r#"<link rel="canonical" href="https://notes.example.com/guides/setup/">"#),
+        "{html}"
+    );
+    assert!(!html.contains("Secretword"), "{html}");
+
+    // Left out of the search index and the feed
+    let indexed = get_indexed_urls(&output_dir.path().join(".mbr/pagefind"));
+    assert!(indexed.iter().any(|url| url.contains("guides/setup")));
+    assert!(
+        !indexed.iter().any(|url| url.contains("old/install")),
+        "{indexed:?}"
+    );
+    let feed = fs::read_to_string(output_dir.path().join("feed.json")).unwrap();
+    assert!(feed.contains("/guides/setup/"));
+    assert!(!feed.contains("/old/install/"), "{feed}");
+}
+
+#[tokio::test]
+async fn test_build_section_feeds() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "blog/first.md",
+        "---\ntitle: First Post\ndate: 2024-01-10\n---\n\nFirst body.\n",
+    );
+    repo.create_markdown(
+        "blog/second.md",
+        "---\ntitle: Second Post\ndate: 2024-03-05\n---\n\nSecond body.\n",
+    );
+    repo.create_markdown("journal/index.md", "# Journal\n\nEntries below.\n");
+    repo.create_markdown(
+        "journal/day.md",
+        "---\ntitle: A Day\ndate: 2024-02-01\n---\n\nDay body.\n",
+    );
+    repo.create_markdown("notes/undated.md", "# Undated\n");
+    repo.create_markdown(
+        "elsewhere.md",
+        "---\ntitle: Elsewhere\ndate: 2024-04-01\n---\n\nSubscribe to [the blog](/blog/feed.json).\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        section_feeds: vec![
+            "blog/".to_string(),
+            "journal".to_string(),
+            "notes/".to_string(),
+        ],
+        base_url: Some("https://notes.example.com/".to_string()),
+        site_name: Some("Field Notes".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    // blog and journal; notes has no dated pages and no site feed was asked for
+    assert_eq!(stats.feeds, 2);
+    assert_eq!(stats.broken_links, 0, "feed files are valid link targets");
+    assert!(!output_dir.path().join("feed.json").exists());
+    assert!(!output_dir.path().join("notes/feed.json").exists());
+
+    let feed: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output_dir.path().join("blog/feed.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(feed["title"], "Field Notes: blog");
+    assert_eq!(feed["home_page_url"], "https://notes.example.com/blog/");
+    assert_eq!(feed["feed_url"], "https://notes.example.com/blog/feed.json");
+    let titles: Vec<&str> = feed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Second Post", "First Post"]);
+
+    // The generated section page and a markdown section page both link theirs
+    let link = r#"<link rel="alternate" type="application/feed+json" title="Field Notes: blog" href="../blog/feed.json" />"#;
+    let section = fs::read_to_string(output_dir.path().join("blog/index.html")).unwrap();
+    assert!(section.contains(link), "{section}");
+    let journal = fs::read_to_string(output_dir.path().join("journal/index.html")).unwrap();
+    assert!(
+        journal.contains(r#"href="../journal/feed.json""#),
+        "{journal}"
+    );
+    let notes = fs::read_to_string(output_dir.path().join("notes/index.html")).unwrap();
+    assert!(!notes.contains(r#"rel="alternate""#));
+    let post = fs::read_to_string(output_dir.path().join("blog/first/index.html")).unwrap();
+    assert!(!post.contains(r#"rel="alternate""#));
+}
+
+#[tokio::test]
+async fn test_build_archive_pages_disabled_by_default() {
+    let repo = TestRepo::new();
+    repo.create_markdown("post.md", "---\ndate: 2024-01-10\n---\n\n# Post\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    assert_eq!(stats.archive_pages, 0);
+    assert!(!output_dir.path().join("archive").exists());
+}
+
+#[tokio::test]
+async fn test_build_inlines_small_assets() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "docs/page.md",
+        "# Page\n\n![Logo](logo.svg)\n\n![Chart](chart.svg)\n\n<img src=\"keep.svg\" alt=\"Keep\" data-no-inline>\n",
+    );
+    let small_svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><rect id="tiny"/></svg>"#;
+    repo.create_static_file("docs/logo.svg", small_svg.as_bytes());
+    repo.create_static_file("docs/keep.svg", small_svg.as_bytes());
+    repo.create_static_file(
+        "docs/chart.svg",
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><desc>{}</desc></svg>"#,
+            "x".repeat(500)
+        )
+        .as_bytes(),
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        inline_assets_under_bytes: 200,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert!(stats.assets_inlined >= 1);
+
+    let html = fs::read_to_string(output_dir.path().join("docs/page/index.html")).unwrap();
+    assert!(html.contains(r#"<rect id="tiny"/>"#), "{html}");
+    assert!(html.contains(r#"aria-label="Logo""#), "{html}");
+    assert!(!html.contains("logo.svg"), "{html}");
+    assert!(html.contains("chart.svg"), "large files stay external");
+    assert!(html.contains("keep.svg"), "data-no-inline is respected");
+}
+
+#[tokio::test]
+async fn test_build_robots_meta() {
+    let repo = TestRepo::new();
+    repo.create_markdown("draft.md", "# Draft\n");
+    repo.create_markdown("public.md", "---\nnoindex: false\n---\n# Public\n");
+    repo.create_markdown(
+        "thanks.md",
+        "---\nrobots: [noindex, noarchive]\n---\n# Thanks\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        robots: Some("noindex, nofollow".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let page =
+        |url: &str| fs::read_to_string(output_dir.path().join(url).join("index.html")).unwrap();
+    assert!(page("draft").contains(r#"<meta name="robots" content="noindex, nofollow" />"#));
+    assert!(page("public").contains(r#"<meta name="robots" content="nofollow" />"#));
+    assert!(page("thanks").contains(r#"<meta name="robots" content="noindex, noarchive" />"#));
+    // Generated section pages get the site default
+    assert!(page("").contains(r#"<meta name="robots" content="noindex, nofollow" />"#));
+}
+
+#[tokio::test]
+async fn test_build_page_assets_from_frontmatter() {
+    let repo = TestRepo::new();
+    repo.create_static_file("static/styles/demo.css", b"main { color: red; }");
+    repo.create_markdown(
+        "docs/demo.md",
+        "---\nhead_css: styles/demo.css\nhead_js: \"window.demo = '</script>';\"\n---\n# Demo\n",
+    );
+    repo.create_markdown("docs/plain.md", "# Plain\n");
+    repo.create_markdown(
+        "docs/typo.md",
+        "---\nhead_css: styles/dmeo.css\n---\n# Typo\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    // Only the misspelled stylesheet is reported
+    assert_eq!(stats.broken_links, 1);
+
+    let html = fs::read_to_string(output_dir.path().join("docs/demo/index.html")).unwrap();
+    assert!(
+        html.contains(r#"<link rel="stylesheet" href="..&#x2F;..&#x2F;styles&#x2F;demo.css" />"#),
+        "{html}"
+    );
+    assert!(html.contains("<script>window.demo = '<\\/script>';</script>"));
+    assert!(output_dir.path().join("styles/demo.css").exists());
+
+    let plain = fs::read_to_string(output_dir.path().join("docs/plain/index.html")).unwrap();
+    assert!(!plain.contains("demo.css"));
+}
+
+#[tokio::test]
+async fn test_build_sanitize_html_drops_frontmatter_scripts() {
+    let repo = TestRepo::new();
+    repo.create_static_file("static/styles/demo.css", b"main { color: red; }");
+    repo.create_static_file("static/scripts/steal.js", b"steal();");
+    repo.create_markdown(
+        "demo.md",
+        "---\nhead_css: [styles/demo.css, \"body { display: none; }\"]\nhead_js: [scripts/steal.js, \"alert('x');\"]\n---\n# Demo\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        sanitize_html: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("demo/index.html")).unwrap();
+    assert!(!html.contains(">alert('x');</script>"), "{html}");
+    assert!(!html.contains(r#"steal.js"></script>"#), "{html}");
+    assert!(!html.contains("display: none; }</style>"), "{html}");
+    assert!(html.contains("demo.css"), "{html}");
+}
+
+#[tokio::test]
+async fn test_build_writes_robots_txt() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        robots_txt: Some("User-agent: *\nDisallow: /drafts/\n".to_string()),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config.clone(), output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(
+        fs::read_to_string(output_dir.path().join("robots.txt")).unwrap(),
+        "User-agent: *\nDisallow: /drafts/\n"
+    );
+
+    // A robots.txt in the static folder wins over the generated one
+    repo.create_static_file("static/robots.txt", b"User-agent: *\nDisallow: /\n");
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(
+        fs::read_to_string(output_dir.path().join("robots.txt")).unwrap(),
+        "User-agent: *\nDisallow: /\n"
+    );
+}
+
+#[tokio::test]
+async fn test_build_embeds_content_security_policy() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        content_security_policy: Some(
+            "script-src 'self' 'nonce-{nonce}'; frame-ancestors 'none'".to_string(),
+        ),
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("page/index.html")).unwrap();
+    let nonce = html
+        .split(r#"<script nonce=""#)
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("inline scripts carry a nonce");
+    assert_eq!(nonce.len(), 32);
+    // Tera escapes the quotes; frame-ancestors is dropped from the meta tag
+    assert!(
+        html.contains(&format!(
+            r#"<meta http-equiv="Content-Security-Policy" content="script-src &#x27;self&#x27; &#x27;nonce-{nonce}&#x27;" />"#
+        )),
+        "{html}"
+    );
+    let not_found = fs::read_to_string(output_dir.path().join("404.html")).unwrap();
+    assert!(not_found.contains(&format!(r#"<script nonce="{nonce}">"#)));
+}
+
+#[tokio::test]
+async fn test_build_custom_favicon_and_manifest() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/page.md", "# Page\n");
+    let logo = repo.path().join("favicon.png");
+    image::RgbaImage::from_pixel(512, 512, image::Rgba([20, 80, 160, 255]))
+        .save(&logo)
+        .unwrap();
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        favicon: Some("favicon.png".to_string()),
+        site_name: Some("Field Notes".to_string()),
+        theme_color: Some("#1450a0".to_string()),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().to_path_buf();
+    mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    // Copied over the linked asset of the same name, leaving the source intact
+    let png = std::fs::read(&logo).unwrap();
+    assert!(!png.is_empty());
+    assert_eq!(std::fs::read(output.join("favicon.png")).unwrap(), png);
+    for size in [180, 192, 512] {
+        assert!(
+            output
+                .join(".mbr/icons")
+                .join(format!("icon-{size}.png"))
+                .exists()
+        );
+    }
+    let manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output.join("manifest.webmanifest")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(manifest["name"], "Field Notes");
+    assert_eq!(manifest["icons"][0]["src"], ".mbr/icons/icon-180.png");
+
+    let html = std::fs::read_to_string(output.join("docs/page/index.html")).unwrap();
+    assert!(
+        html.contains(r#"<link rel="icon" type="image/png" href="../../favicon.png" />"#),
+        "{html}"
+    );
+    assert!(html.contains(r#"<link rel="manifest" href="../../manifest.webmanifest" />"#));
+    assert!(html.contains(r##"<meta name="theme-color" content="#1450a0" />"##));
+    // An explicit theme_color replaces the theme's light/dark pair
+    assert!(!html.contains("prefers-color-scheme: dark"));
+}
+
+#[tokio::test]
+async fn test_build_theme_color_follows_pico_theme() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        theme: "amber".to_string(),
+        skip_search_index: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().to_path_buf();
+    mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = std::fs::read_to_string(output.join("page/index.html")).unwrap();
+    assert!(html.contains(r#"<meta name="color-scheme" content="light dark" />"#));
+    assert!(html.contains(
+        r##"<meta name="theme-color" media="(prefers-color-scheme: light)" content="#ffbf00" />"##
+    ));
+    assert!(html.contains(
+        r##"<meta name="theme-color" media="(prefers-color-scheme: dark)" content="#13171f" />"##
+    ));
+}
+
+#[tokio::test]
+async fn test_incremental_build_reuses_unchanged_search_index() {
+    use mbr::build::SearchIndexStatus;
+
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n\nSome searchable content.");
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        incremental: true,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = output_dir.path().join("site");
+    let build = || async {
+        mbr::build::Builder::new(config.clone(), output.clone())
+            .expect("Failed to create builder")
+            .build()
+            .await
+            .expect("Build failed")
+    };
+    let pagefind_dir = output.join(".mbr").join("pagefind");
+
+    let stats = build().await;
+    assert_eq!(stats.pagefind_indexed, Some(SearchIndexStatus::Indexed));
+    assert!(pagefind_dir.join("pagefind-entry.json").exists());
+
+    let stats = build().await;
+    assert_eq!(stats.pagefind_indexed, Some(SearchIndexStatus::Unchanged));
+    assert!(pagefind_dir.join("pagefind-entry.json").exists());
+
+    repo.create_markdown("page.md", "# Page\n\nDifferent searchable content.");
+    let stats = build().await;
+    assert_eq!(stats.pagefind_indexed, Some(SearchIndexStatus::Indexed));
+    assert!(pagefind_dir.join("pagefind-entry.json").exists());
+}
+
+#[tokio::test]
+async fn test_build_validates_symlinked_assets() {
+    let repo = TestRepo::new();
+
+    // Create a static folder with an asset
+    repo.create_dir("static/images");
+    repo.create_static_file("static/images/logo.png", b"fake image data");
+
+    // Create a page linking to the symlinked asset
+    repo.create_markdown("page.md", "# Page\n\n![Logo](/images/logo.png)");
 
     let (_, stats) = build_site_with_stats(&repo).await;
 
@@ -1101,6 +2651,148 @@ async fn test_build_media_viewer_pages_include_navigation() {
     );
 }
 
+#[tokio::test]
+async fn test_build_media_viewer_pages_embed_sidecar_chapters() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello World");
+    repo.create_static_file("podcast/ep1.mp3", b"fake audio");
+    repo.create_static_file(
+        "podcast/ep1.mp3.chapters.json",
+        br#"[{"start": "1:30", "title": "News"}, {"start": 0, "title": "Intro"}]"#,
+    );
+    repo.create_static_file("podcast/ep2.mp3", b"fake audio");
+    repo.create_static_file("podcast/ep2.mp3.chapters.json", b"not json");
+
+    let output = build_site(&repo).await;
+
+    let audio_html = fs::read_to_string(output.join(".mbr/audio/index.html")).unwrap();
+    assert!(
+        audio_html.contains(
+            r#"chapters: {"/podcast/ep1.mp3":[{"start":0.0,"title":"Intro"},{"start":90.0,"title":"News"}]}"#
+        ),
+        "Audio viewer should embed sorted chapters keyed by URL path"
+    );
+    assert!(
+        !audio_html.contains("ep2.mp3"),
+        "Malformed sidecars should be skipped"
+    );
+
+    let videos_html = fs::read_to_string(output.join(".mbr/videos/index.html")).unwrap();
+    assert!(
+        videos_html.contains("chapters: {}"),
+        "Video viewer should not embed audio chapters"
+    );
+}
+
+#[tokio::test]
+async fn test_build_paginates_directory_and_tag_listings() {
+    let repo = TestRepo::new();
+    for n in 1..=5 {
+        repo.create_markdown(
+            &format!("notes/note{n}.md"),
+            &format!("---\ntitle: Note {n}\ntags: [big]\n---\n\n# Note {n}\n"),
+        );
+    }
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        page_size: 2,
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    let stats = mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+    assert_eq!(stats.broken_links, 0, "pagination links resolve");
+
+    let read = |path: &str| fs::read_to_string(output_dir.path().join(path)).unwrap();
+
+    let first = read("notes/index.html");
+    assert!(first.contains("Note 1") && first.contains("Note 2"));
+    assert!(!first.contains("Note 3"));
+    assert!(first.contains("Page 1 of 3"));
+    assert!(
+        first.contains(r#"href="..&#x2F;notes&#x2F;page&#x2F;2&#x2F;" rel="next""#),
+        "{first}"
+    );
+
+    let second = read("notes/page/2/index.html");
+    assert!(second.contains("Note 3") && second.contains("Note 4"));
+    assert!(
+        second.contains(r#"href="..&#x2F;..&#x2F;..&#x2F;notes&#x2F;" rel="prev""#),
+        "{second}"
+    );
+    assert!(
+        second
+            .contains(r#"href="..&#x2F;..&#x2F;..&#x2F;notes&#x2F;page&#x2F;3&#x2F;" rel="next""#),
+        "{second}"
+    );
+    // Assets resolve from the deeper page
+    assert!(second.contains("../../../.mbr/"), "{second}");
+
+    let last = read("notes/page/3/index.html");
+    assert!(last.contains("Note 5"));
+    assert!(!last.contains(r#"rel="next""#));
+
+    let tag_page = read("tags/big/page/3/index.html");
+    assert!(tag_page.contains("Note 5"));
+    assert!(tag_page.contains("5 pages tagged"), "{tag_page}");
+    assert!(!output_dir.path().join("tags/big/page/4").exists());
+}
+
+#[tokio::test]
+async fn test_build_author_page_with_bio() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/hello.md",
+        "---\ntitle: Hello\nauthor: Jane Doe\n---\n\n# Hello\n",
+    );
+    repo.create_markdown(
+        "posts/other.md",
+        "---\ntitle: Other\nauthor: Unknown Person\n---\n\n# Other\n",
+    );
+    repo.create_static_file(
+        "data/authors.yml",
+        b"Jane Doe:\n  name: Jane Q. Doe\n  bio: Writes about typography.\n  avatar: /images/jane.jpg\n",
+    );
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        skip_search_index: true,
+        data_folder: Some("data".to_string()),
+        tag_sources: vec![mbr::config::TagSource {
+            field: "authors".to_string(),
+            label: Some("Author".to_string()),
+            label_plural: None,
+            fields: vec!["author".to_string()],
+            data: Some("authors".to_string()),
+        }],
+        ..Default::default()
+    };
+    let output_dir = tempfile::tempdir().unwrap();
+    mbr::build::Builder::new(config, output_dir.path().to_path_buf())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output_dir.path().join("authors/jane_doe/index.html")).unwrap();
+    assert!(html.contains("<title>Jane Q. Doe</title>"), "{html}");
+    assert!(html.contains("Writes about typography."));
+    // Site-absolute avatar is made relative to the page
+    assert!(html.contains(r#"src="..&#x2F;..&#x2F;images&#x2F;jane.jpg""#));
+    assert!(html.contains("Hello"));
+
+    // Authors missing from the data file still get a page, without a bio
+    let html =
+        fs::read_to_string(output_dir.path().join("authors/unknown_person/index.html")).unwrap();
+    assert!(html.contains("Unknown Person"));
+    assert!(!html.contains("mbr-author-bio"));
+}
+
 // ============================================================================
 // Tag page path traversal tests
 // ============================================================================
@@ -1114,6 +2806,8 @@ async fn build_site_with_tags(repo: &TestRepo) -> std::path::PathBuf {
             field: "tags".to_string(),
             label: None,
             label_plural: None,
+            fields: vec![],
+            data: None,
         }],
         ..Default::default()
     };
@@ -1629,3 +3323,117 @@ async fn test_build_body_wikilink_resolves_globally() {
         stats.broken_links
     );
 }
+
+#[tokio::test]
+async fn test_build_renders_txt_with_plaintext_renderer() {
+    let repo = TestRepo::new();
+    repo.create_markdown("notes.txt", "<b>not bold</b>\n");
+
+    let mut config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        renderers: std::collections::HashMap::from([(
+            "txt".to_string(),
+            mbr::markdown::RendererKind::Plaintext,
+        )]),
+        ..Default::default()
+    };
+    config.register_renderer_extensions();
+    let output = repo.path().join("build");
+    mbr::build::Builder::new(config, output.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    let html = fs::read_to_string(output.join("notes").join("index.html")).expect("notes page");
+    assert!(
+        html.contains("<pre class=\"mbr-plaintext\">&lt;b&gt;not bold&lt;/b&gt;\n</pre>"),
+        "{html}"
+    );
+}
+
+#[tokio::test]
+async fn test_build_encodes_urls_for_unusual_filenames() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "index.md",
+        "# Home\n\n[Spaced](<my notes/my note.md>) [Accent](caf%C3%A9.md) [Sharp](c%23.md)\n",
+    );
+    repo.create_markdown("my notes/my note.md", "# Spaced\n\n[Back](../café.md)\n");
+    repo.create_markdown("café.md", "# Café\n");
+    repo.create_markdown("c#.md", "# Sharp\n");
+
+    let (output, stats) = build_site_with_stats(&repo).await;
+
+    // Output files use the readable names
+    for page in ["my notes/my note", "café", "c#"] {
+        assert!(output.join(page).join("index.html").is_file(), "{page}");
+    }
+    // Links are encoded the same way however the author wrote them
+    let home = fs::read_to_string(output.join("index.html")).unwrap();
+    for href in ["my%20notes/my%20note/", "caf%C3%A9/", "c%23/"] {
+        assert!(home.contains(&format!("href=\"{href}\"")), "{href}: {home}");
+    }
+    let spaced = fs::read_to_string(output.join("my notes/my note/index.html")).unwrap();
+    assert!(spaced.contains("href=\"../../caf%C3%A9/\""), "{spaced}");
+    // Section listing links to its page (templates escape `/`)
+    let section = fs::read_to_string(output.join("my notes/index.html"))
+        .unwrap()
+        .replace("&#x2F;", "/");
+    assert!(
+        section.contains("href=\"../my%20notes/my%20note/\""),
+        "{section}"
+    );
+    assert_eq!(stats.broken_links, 0);
+
+    // site.json carries both forms
+    let site: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output.join(".mbr/site.json")).unwrap()).unwrap();
+    let cafe = site["markdown_files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["url_path"] == "/café/")
+        .expect("café page in site.json");
+    assert_eq!(cafe["url"], "/caf%C3%A9/");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_build_handles_symlinked_folders() {
+    let repo = TestRepo::new();
+    repo.create_markdown("notes/a.md", "# A\n");
+    // A cycle back to the root, and a folder linked in from outside
+    std::os::unix::fs::symlink("..", repo.path().join("notes/up")).unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    fs::write(outside.path().join("b.md"), "# B\n").unwrap();
+    std::os::unix::fs::symlink(outside.path(), repo.path().join("linked")).unwrap();
+
+    let build = |follow_symlinks| {
+        let config = mbr::Config {
+            root_dir: repo.path().to_path_buf(),
+            skip_search_index: true,
+            follow_symlinks,
+            ..Default::default()
+        };
+        async move {
+            let output = tempfile::tempdir().unwrap();
+            let stats = mbr::build::Builder::new(config, output.path().to_path_buf())
+                .expect("Failed to create builder")
+                .build()
+                .await
+                .expect("Build failed");
+            (output, stats)
+        }
+    };
+
+    let (output, stats) = build(true).await;
+    assert_eq!(stats.markdown_pages, 2);
+    assert!(output.path().join("notes/a/index.html").is_file());
+    assert!(output.path().join("linked/b/index.html").is_file());
+
+    let (output, stats) = build(false).await;
+    assert_eq!(stats.markdown_pages, 1);
+    assert!(output.path().join("notes/a/index.html").is_file());
+    assert!(!output.path().join("linked").exists());
+}
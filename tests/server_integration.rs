@@ -15,8 +15,12 @@ fn test_server_config(port: u16, root_dir: PathBuf) -> mbr::server::ServerConfig
         base_dir: root_dir,
         static_folder: "static".to_string(),
         markdown_extensions: vec!["md".to_string()],
+        renderers: std::collections::HashMap::new(),
         ignore_dirs: vec!["target".to_string(), "node_modules".to_string()],
         ignore_globs: vec!["*.log".to_string()],
+        site_host: None,
+        respect_gitignore: true,
+        follow_symlinks: true,
         watcher_ignore_dirs: vec![
             ".direnv".to_string(),
             ".git".to_string(),
@@ -26,8 +30,14 @@ fn test_server_config(port: u16, root_dir: PathBuf) -> mbr::server::ServerConfig
         ],
         index_file: "index.md".to_string(),
         oembed_timeout_ms: 100,
+        oembed_fallback: Default::default(),
         oembed_cache_size: 2 * 1024 * 1024,
+        oembed_concurrency: 8,
+        oembed_cache_ttl_secs: 0,
+        inbound_cache_size: 1024 * 1024,
+        inbound_cache_ttl_secs: 60,
         template_folder: None,
+        data_folder: None,
         sort: mbr::config::default_sort_config(),
         gui_mode: false,
         theme: "default".to_string(),
@@ -38,15 +48,58 @@ fn test_server_config(port: u16, root_dir: PathBuf) -> mbr::server::ServerConfig
         tag_sources: mbr::config::default_tag_sources(),
         sidebar_style: "panel".to_string(),
         sidebar_max_items: 100,
+        toc: mbr::markdown::TocOptions::default(),
+        markdown_options: mbr::markdown::MarkdownOptions::default(),
+        header_height: None,
+        smooth_scroll: false,
+        listing_layout: "list".to_string(),
+        listing_thumbnails: false,
+        search_placeholder: "Search files...".to_string(),
+        search_result_limit: 20,
+        site_lang: "en".to_string(),
+        site_dir: "ltr".to_string(),
+        reading_order: None,
         title_prefix: String::new(),
         title_suffix: String::new(),
+        dedupe_title_h1: false,
+        base_url: None,
+        external_links_new_tab: false,
+        sanitize_html: None,
+        post_render_hooks: Vec::new(),
+        lazy_images: true,
+        privacy_embeds: false,
+        mermaid_config: "{}".to_string(),
+        media_max_width: None,
+        stream_render_threshold: 1024 * 1024,
+        server_render_concurrency: 8,
+        server_cache_dir: None,
+        max_render_bytes: 64 * 1024 * 1024,
+        branding: Default::default(),
+        robots: None,
+        robots_txt: None,
+        csp: None,
+        date_format: mbr::dates::DEFAULT_DATE_FORMAT.to_string(),
         mark_incomplete: true,
         incomplete_markers: mbr::config::default_incomplete_markers(),
+        interactive_tasklists: true,
+        scroll_spy: true,
+        edit_url_template: None,
         edit_enabled: false,
         edit_require_token_on_loopback: false,
         edit_token_hash: None,
+        mime_overrides: std::collections::HashMap::new(),
+        access_log: None,
+        access_log_format: mbr::access_log::AccessLogFormat::Human,
         #[cfg(feature = "media-metadata")]
         transcode_enabled: false,
+        #[cfg(feature = "media-metadata")]
+        video_encoder: None,
+        #[cfg(feature = "media-metadata")]
+        transcode_tmp_dir: None,
+        #[cfg(feature = "media-metadata")]
+        audio_transcode: None,
+        #[cfg(feature = "media-metadata")]
+        audio_transcode_bitrate_kbps: 128,
     }
 }
 
@@ -174,6 +227,30 @@ async fn test_serve_markdown_file() {
     assert_html_contains(&html, "This is a test.");
 }
 
+#[tokio::test]
+async fn test_page_title_precedence() {
+    let repo = TestRepo::new();
+    repo.create_markdown("fm.md", "---\ntitle: From Frontmatter\n---\n\nNo heading.");
+    repo.create_markdown("heading.md", "## Intro\n\n# From Heading\n\nBody.");
+    repo.create_markdown("plain-notes.md", "Just text.");
+
+    let server = TestServer::start(&repo).await;
+
+    // A frontmatter title with no H1 gets a visible heading
+    let html = server.get_text("/fm/").await;
+    assert_html_contains(&html, "<title>From Frontmatter</title>");
+    assert_html_contains(&html, "<h1>From Frontmatter</h1>");
+
+    // H1- and filename-derived titles don't duplicate the page's headings
+    let html = server.get_text("/heading/").await;
+    assert_html_contains(&html, "<title>From Heading</title>");
+    assert!(!html.contains("<h1>From Heading</h1>"));
+
+    let html = server.get_text("/plain-notes/").await;
+    assert_html_contains(&html, "<title>plain-notes</title>");
+    assert!(!html.contains("<h1>plain-notes</h1>"));
+}
+
 #[tokio::test]
 async fn test_serve_markdown_file_with_dotted_name() {
     // Regression: a markdown file whose name contains a period is served at a
@@ -196,6 +273,28 @@ async fn test_serve_markdown_file_with_dotted_name() {
     assert_html_contains(&html, "Born 2010.");
 }
 
+#[tokio::test]
+async fn test_serve_pages_with_unusual_filenames() {
+    let repo = TestRepo::new();
+    repo.create_markdown("my notes/my note.md", "# Spaced\n\n[Café](../café.md)\n");
+    repo.create_markdown("café.md", "# Café\n");
+    repo.create_markdown("c#.md", "# Sharp\n");
+
+    let server = TestServer::start(&repo).await;
+    server.wait_for_scan().await;
+
+    // Pages are served at their percent-encoded URLs
+    for path in ["/my%20notes/my%20note/", "/caf%C3%A9/", "/c%23/"] {
+        assert_eq!(server.get(path).await.status(), 200, "{path}");
+    }
+    let html = server.get_text("/my%20notes/my%20note/").await;
+    assert_html_contains(&html, "href=\"../../caf%C3%A9/\"");
+
+    // Listings link with the encoded URL
+    let html = server.get_text("/my%20notes/").await.replace("&#x2F;", "/");
+    assert_html_contains(&html, "href=\"/my%20notes/my%20note/\"");
+}
+
 #[tokio::test]
 async fn test_server_marks_incomplete_blocks_by_default() {
     // Server/GUI default for mark_incomplete is true; rendered HTML should
@@ -278,6 +377,221 @@ async fn test_directory_listing() {
     assert_html_contains(&html, "two");
 }
 
+#[tokio::test]
+async fn test_listings_show_reading_time() {
+    let repo = TestRepo::new();
+    let body = "lorem ipsum ".repeat(225);
+    repo.create_markdown(
+        "articles/long.md",
+        &format!("---\ntitle: Long Read\ntags: [essays]\n---\n# Long Read\n\n{body}\n"),
+    );
+    repo.create_markdown("articles/short.md", "# Short");
+
+    let server = TestServer::start(&repo).await;
+
+    // 452 estimated words at 200 wpm
+    let html = server.get_text("/articles/").await;
+    assert_html_contains(&html, "3 min read");
+    assert_html_contains(&html, "1 min read");
+
+    let html = server.get_text("/tags/essays/").await;
+    assert_html_contains(&html, "<small>3 min read</small>");
+}
+
+#[tokio::test]
+async fn test_toc_levels_and_numbering() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "guide.md",
+        "# Guide\n\n## Setup\n\n#### Linux\n\n## Usage\n\n##### Flags\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.toc = mbr::markdown::TocOptions {
+            min_level: 2,
+            max_level: 4,
+            numbered: true,
+        };
+    })
+    .await;
+
+    let html = server.get_text("/guide/").await;
+    assert_html_contains(&html, r#"id: "setup", text: "Setup", number: "1.""#);
+    assert_html_contains(&html, r#"id: "linux", text: "Linux", number: "1.1""#);
+    assert_html_contains(&html, r#"id: "usage", text: "Usage", number: "2.""#);
+    assert!(!html.contains(r#"id: "guide", text: "Guide""#));
+    assert!(!html.contains(r#"id: "flags""#));
+}
+
+#[tokio::test]
+async fn test_data_shortcode_renders_csv_table() {
+    let repo = TestRepo::new();
+    repo.create_static_file(
+        "data/people.csv",
+        b"name,role\nAda,Engineer\nGrace,Admiral\n",
+    );
+    repo.create_markdown("team.md", "# Team\n\n{{ data(file=\"people\") }}\n");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.data_folder = Some("data".to_string());
+    })
+    .await;
+
+    let html = server.get_text("/team/").await;
+    assert_html_contains(&html, "<table class=\"mbr-data\">");
+    assert_html_contains(&html, "<th>name</th><th>role</th>");
+    assert_html_contains(&html, "<td>Grace</td><td>Admiral</td>");
+}
+
+#[tokio::test]
+async fn test_gallery_shortcode_serves_thumbnails() {
+    let repo = TestRepo::new();
+    repo.create_dir("photos");
+    image::RgbImage::from_pixel(1200, 600, image::Rgb([20, 120, 200]))
+        .save(repo.path().join("photos").join("wide.jpg"))
+        .unwrap();
+    repo.create_static_file("photos/broken.png", b"not really a png");
+    repo.create_markdown("trip.md", "# Trip\n\n{{ gallery(dir=\"photos\") }}\n");
+
+    let server = TestServer::start(&repo).await;
+
+    let html = server.get_text("/trip/").await;
+    assert_html_contains(&html, r#"<div class="mbr-gallery">"#);
+    assert_html_contains(
+        &html,
+        r#"<a class="mbr-gallery-item" href="/.mbr/images/?path=/photos/wide.jpg"><img src="/.mbr/thumbs/photos/wide.jpg""#,
+    );
+
+    let resp = server.get("/.mbr/thumbs/photos/wide.jpg").await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "image/jpeg");
+    assert!(resp.headers().contains_key("last-modified"));
+    let etag = resp.headers()["etag"].clone();
+    let thumb = image::load_from_memory(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (480, 240));
+
+    // Revalidation gets a 304 until the image changes
+    let revalidate = || {
+        server
+            .client
+            .get(server.url("/.mbr/thumbs/photos/wide.jpg"))
+            .header("if-none-match", etag.clone())
+            .send()
+    };
+    assert_eq!(revalidate().await.unwrap().status(), 304);
+    image::RgbImage::from_pixel(600, 1200, image::Rgb([200, 20, 20]))
+        .save(repo.path().join("photos").join("wide.jpg"))
+        .unwrap();
+    filetime::set_file_mtime(
+        repo.path().join("photos").join("wide.jpg"),
+        filetime::FileTime::from_unix_time(2_000_000_000, 0),
+    )
+    .unwrap();
+    let resp = revalidate().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let thumb = image::load_from_memory(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (240, 480));
+
+    assert_eq!(
+        server.get("/.mbr/thumbs/photos/broken.png").await.status(),
+        422
+    );
+    assert_eq!(
+        server.get("/.mbr/thumbs/photos/nope.jpg").await.status(),
+        404
+    );
+    assert_eq!(server.get("/.mbr/thumbs/trip.md").await.status(), 404);
+}
+
+#[tokio::test]
+async fn test_listing_dates_are_normalized_and_formatted() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/older.md",
+        "---\ntitle: Older\ndate: Jan 5, 2024\n---\n# Older\n",
+    );
+    repo.create_markdown(
+        "posts/newer.md",
+        "---\ntitle: Newer\ndate: 2024-02-01T09:30:00Z\n---\n# Newer\n",
+    );
+    repo.create_markdown(
+        "posts/vague.md",
+        "---\ntitle: Vague\ndate: someday\n---\n# Vague\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.date_format = "%Y-%m-%d".to_string();
+        config.sort = vec![mbr::config::SortField {
+            field: "date".to_string(),
+            order: "desc".to_string(),
+            compare: "string".to_string(),
+        }];
+    })
+    .await;
+
+    let html = server.get_text("/posts/").await;
+    assert_html_contains(&html, "Date: 2024-01-05");
+    assert_html_contains(&html, "Date: 2024-02-01");
+    let newer = html.find("Newer").unwrap();
+    let older = html.find("Older").unwrap();
+    let vague = html.find("Vague").unwrap();
+    assert!(newer < older && older < vague, "expected newest first");
+
+    // Unparseable dates are reported with the page's other errors
+    let json: serde_json::Value = server
+        .get("/posts/vague/errors.json")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        json["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["type"] == "frontmatter_parse_error"
+                && e["message"] == r#"date: unrecognized date "someday""#),
+        "{json}"
+    );
+}
+
+#[tokio::test]
+async fn test_listing_sorts_by_updated_date() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/first.md",
+        "---\ntitle: First Post\ndate: 2020-01-01\nupdated: 2024-06-01\n---\n# First Post\n",
+    );
+    repo.create_markdown(
+        "posts/second.md",
+        "---\ntitle: Second Post\ndate: 2023-01-01\nupdated: 2023-02-01\n---\n# Second Post\n",
+    );
+
+    let sort_by = |field: &str| {
+        vec![mbr::config::SortField {
+            field: field.to_string(),
+            order: "desc".to_string(),
+            compare: "string".to_string(),
+        }]
+    };
+
+    let by_created = sort_by("created_date");
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.sort = by_created;
+    })
+    .await;
+    let html = server.get_text("/posts/").await;
+    assert!(html.find("Second Post").unwrap() < html.find("First Post").unwrap());
+
+    let by_updated = sort_by("updated_date");
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.sort = by_updated;
+    })
+    .await;
+    let html = server.get_text("/posts/").await;
+    assert!(html.find("First Post").unwrap() < html.find("Second Post").unwrap());
+}
+
 #[tokio::test]
 async fn test_static_file_serving() {
     let repo = TestRepo::new();
@@ -483,6 +797,55 @@ async fn test_root_index_url_redirects() {
     assert_eq!(response.headers().get("location").unwrap(), "/");
 }
 
+#[tokio::test]
+async fn test_redirect_to_frontmatter_returns_301() {
+    let repo = TestRepo::new();
+    repo.create_markdown("guides/setup.md", "# Setup\n\nThe new guide.\n");
+    repo.create_markdown(
+        "old/install.md",
+        "---\ntitle: Zanzibar Install\nredirect_to: /guides/setup/#linux\n---\n\nZanzibar notes.\n",
+    );
+    repo.create_markdown(
+        "old/broken.md",
+        "---\nredirect_to: not/a/path\n---\n\n# Still Here\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let client = mbr::http_client_builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    let response = client
+        .get(server.url("/old/install/"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 301);
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "/guides/setup/#linux"
+    );
+
+    // An unusable target is ignored and the page renders as usual
+    let response = server.get("/old/broken/").await;
+    assert_eq!(response.status(), 200);
+    assert!(response.text().await.unwrap().contains("Still Here"));
+
+    // Redirect pages stay out of search
+    server.wait_for_scan().await;
+    for scope in ["metadata", "content"] {
+        let response = server
+            .post_json(
+                "/.mbr/search",
+                &format!(r#"{{"q": "Zanzibar", "scope": "{scope}"}}"#),
+            )
+            .await;
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["results"], serde_json::json!([]), "{scope}: {body}");
+    }
+}
+
 #[tokio::test]
 async fn test_markdown_with_frontmatter() {
     let repo = TestRepo::new();
@@ -512,6 +875,100 @@ async fn test_site_json_endpoint() {
     assert!(content_type.to_str().unwrap().contains("application/json"));
 }
 
+#[tokio::test]
+async fn test_healthz_endpoint() {
+    let repo = TestRepo::new();
+    repo.create_markdown("test.md", "# Test");
+
+    let server = TestServer::start(&repo).await;
+    let response = server.get("/healthz").await;
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["uptime"].is_u64());
+}
+
+#[tokio::test]
+async fn test_page_assets_from_frontmatter() {
+    let repo = TestRepo::new();
+    repo.create_static_file("static/styles/demo.css", b"main { color: red; }");
+    repo.create_markdown(
+        "demo.md",
+        "---\nhead_css: [/styles/demo.css, \"h1 { color: blue; }\"]\nhead_js: https://cdn.example.com/lib.js\n---\n# Demo\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/demo/").await;
+    assert_html_contains(
+        &html,
+        r#"<link rel="stylesheet" href="&#x2F;styles&#x2F;demo.css" />"#,
+    );
+    assert_html_contains(&html, "<style>h1 { color: blue; }</style>");
+    assert_html_contains(
+        &html,
+        r#"<script src="https:&#x2F;&#x2F;cdn.example.com&#x2F;lib.js"></script>"#,
+    );
+    assert_eq!(server.get("/styles/demo.css").await.status(), 200);
+}
+
+#[tokio::test]
+async fn test_access_log_written_to_file() {
+    let repo = TestRepo::new();
+    repo.create_markdown("test.md", "# Test");
+    let log_dir = tempfile::TempDir::new().unwrap();
+    let log_path = log_dir.path().join("access.log");
+
+    let mut big = String::from("# Big\n\n");
+    for i in 0..500 {
+        big.push_str(&format!("Paragraph {i} of the big page.\n\n"));
+    }
+    repo.create_markdown("big.md", &big);
+
+    let server = TestServer::start_with_config_fn(&repo, {
+        let log_path = log_path.clone();
+        move |config| {
+            config.access_log = Some(log_path);
+            config.access_log_format = mbr::access_log::AccessLogFormat::Combined;
+            config.stream_render_threshold = 1024;
+        }
+    })
+    .await;
+    let health = server.get("/healthz").await;
+    assert_eq!(health.status(), 200);
+    let health_len = health.bytes().await.unwrap().len();
+    let missing = server.get("/missing-page/").await;
+    assert_eq!(missing.status(), 404);
+    missing.bytes().await.unwrap();
+    // Streamed, so there's no Content-Length to go by
+    let page_len = server.get_text("/big/").await.len();
+
+    // Lines are appended by a writer thread once each body is sent
+    let mut log = String::new();
+    for _ in 0..100 {
+        log = std::fs::read_to_string(&log_path).unwrap_or_default();
+        if log.lines().count() >= 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(
+        log.contains(&format!("\"GET /healthz HTTP/1.1\" 200 {health_len} ")),
+        "{log}"
+    );
+    assert!(
+        log.contains("\"GET /missing-page/ HTTP/1.1\" 404 "),
+        "{log}"
+    );
+    assert!(
+        log.contains(&format!("\"GET /big/ HTTP/1.1\" 200 {page_len} ")),
+        "{log}"
+    );
+    assert!(log.lines().all(|line| line.starts_with("127.0.0.1 - - [")));
+}
+
 #[tokio::test]
 async fn test_default_css_served() {
     let repo = TestRepo::new();
@@ -1182,6 +1639,13 @@ async fn test_site_json_returns_valid_structure() {
 
     let body: serde_json::Value = response.json().await.unwrap();
 
+    assert_eq!(body["schema_version"], 3);
+    assert_eq!(
+        body["schema_version"],
+        mbr::repo::SITE_JSON_SCHEMA_VERSION,
+        "schema_version should match the library constant"
+    );
+
     // Should have markdown_files array
     assert!(
         body["markdown_files"].is_array(),
@@ -2720,80 +3184,215 @@ async fn test_media_viewer_video_valid_path_returns_200() {
 }
 
 #[tokio::test]
-async fn test_media_viewer_video_directory_traversal_blocked() {
+async fn test_vtt_files_served_as_text_vtt() {
     let repo = TestRepo::new();
-    repo.create_markdown("readme.md", "# Hello");
+    repo.create_static_file(
+        "videos/subs/en.vtt",
+        b"WEBVTT\n\n00:00.000 --> 00:01.000\nHi\n",
+    );
 
     let server = TestServer::start(&repo).await;
 
-    // Attempt directory traversal via path parameter
-    let response = server.get("/.mbr/videos/?path=/../etc/passwd").await;
-
-    assert_eq!(
-        response.status(),
-        403,
-        "Directory traversal should return 403 Forbidden"
-    );
-
-    let html = response.text().await.unwrap();
+    let response = server.get("/videos/subs/en.vtt").await;
+    assert_eq!(response.status(), 200);
+    let content_type = response.headers()[reqwest::header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
     assert!(
-        html.contains("Forbidden") || html.contains("Access denied"),
-        "Error page should indicate access denied. Got: {}",
-        &html[..std::cmp::min(500, html.len())]
+        content_type.starts_with("text/vtt"),
+        "Caption files need the text/vtt MIME type, got {content_type}"
     );
 }
 
 #[tokio::test]
-async fn test_media_viewer_video_url_encoded_traversal_blocked() {
+async fn test_hls_files_served_with_builtin_mime_types() {
     let repo = TestRepo::new();
-    repo.create_markdown("readme.md", "# Hello");
+    repo.create_static_file("videos/clip.m3u8", b"#EXTM3U\n");
+    repo.create_static_file("videos/clip-000.ts", b"fake segment");
 
     let server = TestServer::start(&repo).await;
 
-    // URL-encoded ".." = "%2e%2e"
-    let response = server
-        .get("/.mbr/videos/?path=%2f%2e%2e%2fetc%2fpasswd")
-        .await;
-
-    assert_eq!(
-        response.status(),
-        403,
-        "URL-encoded directory traversal should return 403 Forbidden"
-    );
+    for (path, expected) in [
+        ("/videos/clip.m3u8", "application/vnd.apple.mpegurl"),
+        ("/videos/clip-000.ts", "video/mp2t"),
+    ] {
+        let response = server.get(path).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()[reqwest::header::CONTENT_TYPE], expected);
+    }
 }
 
 #[tokio::test]
-async fn test_media_viewer_video_nonexistent_file_returns_404() {
+async fn test_mime_override_wins_over_default() {
     let repo = TestRepo::new();
-    repo.create_markdown("readme.md", "# Hello");
-
-    let server = TestServer::start(&repo).await;
+    repo.create_static_file("music/song.opus", b"fake opus");
+    repo.create_static_file("src/app.ts", b"export {}");
 
-    // Request a video file that doesn't exist
-    let response = server
-        .get("/.mbr/videos/?path=/videos/nonexistent.mp4")
-        .await;
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.mime_overrides = std::collections::HashMap::from([
+            ("opus".to_string(), "audio/ogg; codecs=opus".to_string()),
+            (".TS".to_string(), "text/x-typescript".to_string()),
+        ]);
+    })
+    .await;
 
+    let response = server.get("/music/song.opus").await;
     assert_eq!(
-        response.status(),
-        404,
-        "Nonexistent video file should return 404 Not Found"
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        "audio/ogg; codecs=opus"
+    );
+    // Configured overrides also replace the built-in ones
+    let response = server.get("/src/app.ts").await;
+    assert_eq!(
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        "text/x-typescript"
     );
 }
 
 #[tokio::test]
-async fn test_media_viewer_video_nested_path() {
+async fn test_media_viewer_embeds_sidecar_chapters() {
     let repo = TestRepo::new();
-
-    // Create a nested video structure
-    repo.create_dir("videos/2024/january");
-    repo.create_static_file("videos/2024/january/event.mp4", b"fake video");
+    repo.create_static_file("videos/talk.mp4", b"fake video content");
+    repo.create_static_file(
+        "videos/talk.mp4.chapters.json",
+        br#"[{"start": "0:45", "title": "Demo"}]"#,
+    );
 
     let server = TestServer::start(&repo).await;
 
-    let response = server
-        .get("/.mbr/videos/?path=/videos/2024/january/event.mp4")
-        .await;
+    let html = server
+        .get("/.mbr/videos/?path=/videos/talk.mp4")
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        html.contains(r#"chapters: {"/videos/talk.mp4":[{"start":45.0,"title":"Demo"}]}"#),
+        "Viewer should embed the sidecar chapters"
+    );
+}
+
+#[tokio::test]
+async fn test_chapters_json_endpoint() {
+    let repo = TestRepo::new();
+    repo.create_static_file("audio/ep1.mp3", b"fake audio");
+    repo.create_static_file(
+        "audio/ep1.mp3.chapters.json",
+        br#"[{"start": "2:00", "title": "Outro"}, {"start": 0, "title": "Intro"}]"#,
+    );
+    repo.create_static_file("audio/ep2.mp3", b"fake audio");
+    repo.create_static_file(
+        "audio/ep2.mp3.chapters.json",
+        br#"[{"start": "1:75", "title": "Bad"}]"#,
+    );
+    repo.create_static_file("audio/ep3.mp3", b"fake audio");
+
+    let server = TestServer::start(&repo).await;
+
+    let response = server.get("/.mbr/chapters.json?path=/audio/ep1.mp3").await;
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!([
+            {"start": 0.0, "title": "Intro"},
+            {"start": 120.0, "title": "Outro"},
+        ])
+    );
+
+    let response = server.get("/.mbr/chapters.json?path=/audio/ep2.mp3").await;
+    assert_eq!(response.status(), 422);
+    let message = response.text().await.unwrap();
+    assert!(
+        message.contains("chapter 1: invalid timestamp '1:75'"),
+        "Error should name the bad entry, got: {message}"
+    );
+
+    let response = server.get("/.mbr/chapters.json?path=/audio/ep3.mp3").await;
+    assert_eq!(response.status(), 404, "No sidecar should be 404");
+
+    let response = server
+        .get("/.mbr/chapters.json?path=/../../etc/passwd")
+        .await;
+    assert_ne!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_media_viewer_video_directory_traversal_blocked() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello");
+
+    let server = TestServer::start(&repo).await;
+
+    // Attempt directory traversal via path parameter
+    let response = server.get("/.mbr/videos/?path=/../etc/passwd").await;
+
+    assert_eq!(
+        response.status(),
+        403,
+        "Directory traversal should return 403 Forbidden"
+    );
+
+    let html = response.text().await.unwrap();
+    assert!(
+        html.contains("Forbidden") || html.contains("Access denied"),
+        "Error page should indicate access denied. Got: {}",
+        &html[..std::cmp::min(500, html.len())]
+    );
+}
+
+#[tokio::test]
+async fn test_media_viewer_video_url_encoded_traversal_blocked() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello");
+
+    let server = TestServer::start(&repo).await;
+
+    // URL-encoded ".." = "%2e%2e"
+    let response = server
+        .get("/.mbr/videos/?path=%2f%2e%2e%2fetc%2fpasswd")
+        .await;
+
+    assert_eq!(
+        response.status(),
+        403,
+        "URL-encoded directory traversal should return 403 Forbidden"
+    );
+}
+
+#[tokio::test]
+async fn test_media_viewer_video_nonexistent_file_returns_404() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello");
+
+    let server = TestServer::start(&repo).await;
+
+    // Request a video file that doesn't exist
+    let response = server
+        .get("/.mbr/videos/?path=/videos/nonexistent.mp4")
+        .await;
+
+    assert_eq!(
+        response.status(),
+        404,
+        "Nonexistent video file should return 404 Not Found"
+    );
+}
+
+#[tokio::test]
+async fn test_media_viewer_video_nested_path() {
+    let repo = TestRepo::new();
+
+    // Create a nested video structure
+    repo.create_dir("videos/2024/january");
+    repo.create_static_file("videos/2024/january/event.mp4", b"fake video");
+
+    let server = TestServer::start(&repo).await;
+
+    let response = server
+        .get("/.mbr/videos/?path=/videos/2024/january/event.mp4")
+        .await;
 
     assert_eq!(
         response.status(),
@@ -3029,100 +3628,982 @@ async fn test_mbr_assets_cannot_access_files_outside_mbr_dir() {
 }
 
 #[tokio::test]
-async fn test_mbr_assets_valid_file_still_works() {
+async fn test_mbr_assets_valid_file_still_works() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello");
+
+    // Create a custom CSS file in .mbr directory
+    repo.create_dir(".mbr");
+    repo.create_static_file(".mbr/user.css", b"body { color: red; }");
+
+    let server = TestServer::start(&repo).await;
+
+    // Valid .mbr asset request should still work
+    let response = server.get("/.mbr/user.css").await;
+    assert_eq!(
+        response.status(),
+        200,
+        "Valid .mbr asset should return 200 OK"
+    );
+
+    let content = response.text().await.unwrap();
+    assert!(
+        content.contains("body { color: red; }"),
+        "Should return the correct CSS content"
+    );
+}
+
+#[tokio::test]
+async fn test_mbr_assets_nested_path_works() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Hello");
+
+    // Create nested directory structure in .mbr
+    repo.create_dir(".mbr/custom/styles");
+    repo.create_static_file(
+        ".mbr/custom/styles/theme.css",
+        b".theme { display: block; }",
+    );
+
+    let server = TestServer::start(&repo).await;
+
+    // Nested path should work
+    let response = server.get("/.mbr/custom/styles/theme.css").await;
+    assert_eq!(
+        response.status(),
+        200,
+        "Nested .mbr asset path should return 200 OK"
+    );
+}
+
+#[tokio::test]
+async fn test_code_blocks_with_unsupported_language_render_correctly() {
+    let repo = TestRepo::new();
+    let markdown = concat!(
+        "# Code Examples\n\n",
+        "```rust\nfn hello() { println!(\"hi\"); }\n```\n\n",
+        "```totally_bogus_lang\nsome code here\n```\n\n",
+        "```python\ndef hello(): pass\n```",
+    );
+    repo.create_markdown("code.md", markdown);
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/code/").await;
+
+    // Page renders successfully with all code blocks
+    assert_html_contains(&html, "Code Examples");
+    assert_html_contains(&html, "language-rust");
+    assert_html_contains(&html, "language-totally_bogus_lang");
+    assert_html_contains(&html, "language-python");
+
+    // Code content is preserved
+    assert_html_contains(&html, "fn hello");
+    assert_html_contains(&html, "some code here");
+    assert_html_contains(&html, "def hello");
+
+    // hljs component is present so client-side highlighting can proceed
+    assert_html_contains(&html, "mbr-hljs");
+}
+
+#[tokio::test]
+async fn test_site_nav_manifest_in_single_sidebar() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/start.md", "# Start");
+    repo.create_markdown("docs/install.md", "# Install");
+    repo.create_static_file(
+        ".mbr/nav.yml",
+        b"nav:\n  - title: Start Here\n    url: /docs/start/\n  - title: Guides\n    children:\n      - title: Install\n        url: /docs/install/\n      - title: Removed\n        url: /docs/removed/\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.sidebar_style = "single".to_string();
+    })
+    .await;
+
+    let html = server.get_text("/docs/start/").await;
+    assert_html_contains(&html, r#"<nav slot="site-nav" class="mbr-site-nav""#);
+    // Tera escapes `/` in attribute values
+    assert_html_contains(
+        &html,
+        r#"<a href="&#x2F;docs&#x2F;start&#x2F;">Start Here</a>"#,
+    );
+    assert_html_contains(
+        &html,
+        r#"<span>Guides</span><ul><li><a href="&#x2F;docs&#x2F;install&#x2F;">"#,
+    );
+    // Dangling entries keep their title but lose the link
+    assert_html_contains(&html, "<span>Removed</span>");
+    assert!(!html.contains("removed&#x2F;"));
+}
+
+#[tokio::test]
+async fn test_reading_order_follows_nav_manifest() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/start.md", "# Start");
+    repo.create_markdown("guides/install.md", "# Install");
+    repo.create_markdown("guides/zzz.md", "# Zzz");
+    repo.create_static_file(
+        ".mbr/nav.yml",
+        b"nav:\n  - title: Start Here\n    url: /docs/start/\n  - title: Install\n    url: /guides/install/\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.reading_order = Some("nav".to_string());
+    })
+    .await;
+
+    let html = server.get_text("/guides/install/").await;
+    assert_html_contains(
+        &html,
+        r#"prevPage: { url: "/docs/start/", title: "Start Here" }"#,
+    );
+    // The last page in the order has no next, even with a later sibling
+    assert!(!html.contains("nextPage:"));
+
+    let html = server.get_text("/docs/start/").await;
+    assert_html_contains(
+        &html,
+        r#"nextPage: { url: "/guides/install/", title: "Install" }"#,
+    );
+}
+
+// ==================== Title Prefix/Suffix Tests ====================
+
+#[tokio::test]
+async fn test_title_prefix_in_markdown_page() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "readme.md",
+        "---\ntitle: My Page\n---\n\n# My Page\n\nContent.",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.title_prefix = "My Site: ".to_string();
+    })
+    .await;
+
+    let html = server.get_text("/readme/").await;
+    assert_html_contains(&html, "<title>My Site: My Page</title>");
+}
+
+#[tokio::test]
+async fn test_dedupe_title_h1() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "same.md",
+        "---\ntitle: My Page\n---\n\n# My Page\n\nContent.",
+    );
+    repo.create_markdown(
+        "different.md",
+        "---\ntitle: My Page\n---\n\n# Another Heading\n\nContent.",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.dedupe_title_h1 = true;
+    })
+    .await;
+
+    let html = server.get_text("/same/").await;
+    assert!(!html.contains(r#"<h1 id="my-page">"#));
+    assert_eq!(html.matches("<h1>My Page</h1>").count(), 1);
+
+    let html = server.get_text("/different/").await;
+    assert_html_contains(&html, r#"<h1 id="another-heading">Another Heading</h1>"#);
+}
+
+#[tokio::test]
+async fn test_external_links_open_in_new_tab() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "links.md",
+        "# Links\n\n[Out](https://example.com/) [Home](https://notes.example.com/a/) [Sibling](other.md) [Top](#links)\n\nBare: https://example.net/page, in a sentence.\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.external_links_new_tab = true;
+        config.base_url = Some("https://notes.example.com/".to_string());
+    })
+    .await;
+
+    let html = server.get_text("/links/").await;
+    assert_html_contains(
+        &html,
+        r#"<a href="https://example.com/" target="_blank" rel="noopener noreferrer" class="mbr-external-link">Out</a>"#,
+    );
+    assert_html_contains(&html, r#"<a href="https://notes.example.com/a/">Home</a>"#);
+    assert_html_contains(&html, r#"<a href="../other/">Sibling</a>"#);
+    assert_html_contains(&html, r##"<a href="#links">Top</a>"##);
+    // Bare URLs are autolinked and decorated like written links
+    assert_html_contains(
+        &html,
+        r#"Bare: <a href="https://example.net/page" target="_blank" rel="noopener noreferrer" class="mbr-external-link">https://example.net/page</a>, in a sentence."#,
+    );
+}
+
+#[tokio::test]
+async fn test_header_height_and_smooth_scroll() {
+    let repo = TestRepo::new();
+    repo.create_markdown("doc.md", "# Doc\n\n## Section\n\n[Jump](#section)\n");
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/doc/").await;
+    assert!(!html.contains("--mbr-header-height:"));
+    assert!(!html.contains("scroll-behavior"));
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.header_height = Some("4rem".to_string());
+        config.smooth_scroll = true;
+    })
+    .await;
+    let html = server.get_text("/doc/").await;
+    assert_html_contains(&html, ":root { --mbr-header-height: 4rem; }");
+    assert_html_contains(&html, "html { scroll-behavior: smooth; }");
+
+    // Directory pages share the same head
+    let html = server.get_text("/").await;
+    assert_html_contains(&html, ":root { --mbr-header-height: 4rem; }");
+}
+
+#[tokio::test]
+async fn test_html_lang_and_dir() {
+    let repo = TestRepo::new();
+    repo.create_markdown("doc.md", "# Doc\n");
+    repo.create_markdown("arabic.md", "---\nlang: ar\ndir: rtl\n---\n# مرحبا\n");
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/doc/").await;
+    assert_html_contains(&html, r#"<html lang="en" dir="ltr">"#);
+    let html = server.get_text("/arabic/").await;
+    assert_html_contains(&html, r#"<html lang="ar" dir="rtl">"#);
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.site_lang = "he".to_string();
+        config.site_dir = "rtl".to_string();
+    })
+    .await;
+    for path in ["/doc/", "/"] {
+        let html = server.get_text(path).await;
+        assert_html_contains(&html, r#"<html lang="he" dir="rtl">"#);
+    }
+    // Frontmatter still wins over the site default
+    let html = server.get_text("/arabic/").await;
+    assert_html_contains(&html, r#"<html lang="ar" dir="rtl">"#);
+}
+
+#[tokio::test]
+async fn test_interactive_tasklists_script() {
+    let repo = TestRepo::new();
+    repo.create_markdown("todo.md", "# Todo\n\n- [ ] milk\n- [x] eggs\n");
+
+    // Server/GUI default is on: the checkboxes are enabled client-side
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/todo/").await;
+    assert_html_contains(&html, "mbr-tasklist:");
+    assert_html_contains(&html, r#"<input disabled="" type="checkbox"/>"#);
+
+    let server = TestServer::start_with_config_fn(&repo, |c| c.interactive_tasklists = false).await;
+    let html = server.get_text("/todo/").await;
+    assert!(!html.contains("mbr-tasklist:"));
+}
+
+#[tokio::test]
+async fn test_scroll_spy_script() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "long.md",
+        "# Long\n\n## First\n\nText\n\n## Second\n\nText\n",
+    );
+    repo.create_markdown("plain.md", "Just a paragraph.\n");
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/long/").await;
+    assert_html_contains(&html, "mbr-active-heading");
+    // Pages without headings have nothing to track
+    let html = server.get_text("/plain/").await;
+    assert!(!html.contains("mbr-active-heading"));
+
+    let server = TestServer::start_with_config_fn(&repo, |c| c.scroll_spy = false).await;
+    let html = server.get_text("/long/").await;
+    assert!(!html.contains("mbr-active-heading"));
+}
+
+#[tokio::test]
+async fn test_edit_url_template_links_page_source() {
+    let repo = TestRepo::new();
+    repo.create_markdown("docs/guide.md", "# Guide\n");
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/docs/guide/").await;
+    assert!(!html.contains("Edit this page"));
+
+    let server = TestServer::start_with_config_fn(&repo, |c| {
+        c.edit_url_template = Some("https://github.com/me/notes/edit/main/{path}".to_string())
+    })
+    .await;
+    let html = server.get_text("/docs/guide/").await;
+    // Tera escapes `/` in attribute values
+    assert_html_contains(
+        &html,
+        "edit&#x2F;main&#x2F;docs&#x2F;guide.md\" rel=\"noopener\">Edit this page</a>",
+    );
+}
+
+#[tokio::test]
+async fn test_search_placeholder_and_result_limit() {
+    let repo = TestRepo::new();
+    repo.create_markdown("doc.md", "# Doc\n");
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/doc/").await;
+    assert_html_contains(
+        &html,
+        r#"<mbr-search placeholder="Search files..." result-limit="20"></mbr-search>"#,
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.search_placeholder = "Find a note".to_string();
+        config.search_result_limit = 5;
+    })
+    .await;
+    for path in ["/doc/", "/"] {
+        let html = server.get_text(path).await;
+        assert_html_contains(
+            &html,
+            r#"<mbr-search placeholder="Find a note" result-limit="5"></mbr-search>"#,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_plaintext_renderer_for_txt_files() {
+    let repo = TestRepo::new();
+    repo.create_markdown("readme.md", "# Readme\n");
+    repo.create_markdown(
+        "notes.txt",
+        "if a < b && c:\n    print(\"# not a heading\")\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.markdown_extensions.push("txt".to_string());
+        config
+            .renderers
+            .insert("txt".to_string(), mbr::markdown::RendererKind::Plaintext);
+    })
+    .await;
+
+    let html = server.get_text("/notes/").await;
+    assert_html_contains(
+        &html,
+        "<pre class=\"mbr-plaintext\">if a &lt; b &amp;&amp; c:\n    print(\"# not a heading\")\n</pre>",
+    );
+    assert_html_contains(&html, "<title>notes</title>");
+
+    // Markdown files still go through the markdown pipeline
+    let html = server.get_text("/readme/").await;
+    assert_html_contains(&html, "<h1 id=\"readme\">Readme</h1>");
+}
+
+#[tokio::test]
+async fn test_mdx_and_quarto_render_as_markdown() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "guide.mdx",
+        "---\ntitle: Guide\n---\nimport { Callout } from './Callout'\n\n## Setup\n\n<Callout type=\"tip\">\n  Run **mbr** first.\n</Callout>\n\n<Chart data={[1, 2]} />\n",
+    );
+    repo.create_markdown(
+        "report.qmd",
+        "# Report\n\n::: {.callout-note}\nA note.\n:::\n\n```{python}\nprint(1)\n```\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config
+            .markdown_extensions
+            .extend(["mdx".to_string(), "qmd".to_string()]);
+    })
+    .await;
+
+    let html = server.get_text("/guide/").await;
+    assert_html_contains(&html, "<h2 id=\"setup\">Setup</h2>");
+    assert_html_contains(&html, "<p>Run <strong>mbr</strong> first.</p>");
+    for jsx in ["import", "Callout", "Chart"] {
+        assert!(!html.contains(jsx), "{jsx} left in page: {html}");
+    }
+
+    let html = server.get_text("/report/").await;
+    assert_html_contains(&html, "<p>A note.</p>");
+    assert_html_contains(&html, "language-python");
+    assert!(!html.contains(":::"), "{html}");
+}
+
+#[tokio::test]
+async fn test_lazy_images_skip_first_image() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "gallery.md",
+        "# Gallery\n\n![One](one.png)\n\n![Two](two.png)\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/gallery/").await;
+    assert_html_contains(&html, r#"<img src="../one.png" alt="One" />"#);
+    assert_html_contains(
+        &html,
+        r#"<img src="../two.png" alt="Two" loading="lazy" decoding="async" />"#,
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.lazy_images = false;
+    })
+    .await;
+    let html = server.get_text("/gallery/").await;
+    assert!(!html.contains(r#"loading="lazy""#));
+}
+
+#[tokio::test]
+async fn test_media_max_width_constrains_content_media() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "media.md",
+        "# Media\n\n![Photo](photo.png)\n\n![Talk](https://youtu.be/dQw4w9WgXcQ)\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/media/").await;
+    assert!(!html.contains("max-width: 50%"));
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.media_max_width = Some("50%".to_string());
+    })
+    .await;
+    let html = server.get_text("/media/").await;
+    assert_html_contains(
+        &html,
+        r#"<img src="../photo.png" alt="Photo" style="max-width: 50%; height: auto;" />"#,
+    );
+    assert_html_contains(
+        &html,
+        r#"style="max-width: 50%; height: auto; aspect-ratio: 560 / 315;""#,
+    );
+}
+
+#[tokio::test]
+async fn test_privacy_embeds_wrap_video_players() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "video.md",
+        "# Video\n\n![Talk](https://www.youtube.com/watch?v=dQw4w9WgXcQ)\n",
+    );
+
+    // Off by default: the player is embedded directly
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/video/").await;
+    assert!(!html.contains("mbr-embed-facade"));
+    assert_html_contains(
+        &html,
+        r#"src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ""#,
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.privacy_embeds = true;
+    })
+    .await;
+    let html = server.get_text("/video/").await;
+    assert_html_contains(
+        &html,
+        r#"<div class="mbr-embed-facade" data-provider="youtube">"#,
+    );
+    assert_html_contains(&html, "https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg");
+    assert_html_contains(&html, ".mbr-embed-facade-play");
+    let (_, player) = html.split_once("<template>").unwrap();
+    assert!(player.contains("youtube-nocookie.com/embed/dQw4w9WgXcQ"));
+}
+
+#[tokio::test]
+async fn test_sanitize_html_strips_scripts() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "page.md",
+        "# Page\n\n<div class=\"note\" onmouseover=\"steal()\">Hi</div>\n\n<script>steal()</script>\n\n- [x] done\n",
+    );
+
+    // Off by default: raw HTML passes through
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/page/").await;
+    assert!(html.contains("<script>steal()</script>"));
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.sanitize_html = Some(mbr::sanitize::HtmlSanitizer::default());
+    })
+    .await;
+    let html = server.get_text("/page/").await;
+    assert!(!html.contains("steal()"), "{html}");
+    assert_html_contains(&html, r#"<div class="note">Hi</div>"#);
+    assert!(html.contains(r#"type="checkbox""#));
+}
+
+#[tokio::test]
+async fn test_sanitize_html_drops_frontmatter_scripts() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "page.md",
+        "---\nhead_css: [/styles/demo.css, \"body { display: none; }\"]\nhead_js: [/scripts/steal.js, \"alert('x');\"]\n---\n# Page\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/page/").await;
+    assert!(html.contains(">alert('x');</script>"), "{html}");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.sanitize_html = Some(mbr::sanitize::HtmlSanitizer::default());
+    })
+    .await;
+    let html = server.get_text("/page/").await;
+    assert!(!html.contains(">alert('x');</script>"), "{html}");
+    assert!(!html.contains(r#"steal.js"></script>"#), "{html}");
+    assert!(!html.contains("display: none; }</style>"), "{html}");
+    assert!(html.contains("/styles/demo.css"), "{html}");
+}
+
+#[tokio::test]
+async fn test_mermaid_config_reaches_page() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "diagram.md",
+        "# Diagram\n\n```mermaid\ngraph TD; A-->B\n```\n",
+    );
+
+    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/diagram/").await;
+    assert!(html.contains("mermaid: {}"), "{html}");
+
+    let mermaid = mbr::config::Config {
+        mermaid_theme: Some("forest".to_string()),
+        mermaid_config: Some(r#"{"fontFamily": "Inter"}"#.to_string()),
+        ..Default::default()
+    }
+    .mermaid_init_json();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.mermaid_config = mermaid;
+    })
+    .await;
+    let html = server.get_text("/diagram/").await;
+    assert!(
+        html.contains(r#"mermaid: {"fontFamily":"Inter","theme":"forest"}"#),
+        "{html}"
+    );
+}
+
+#[tokio::test]
+async fn test_markdown_over_max_render_bytes_is_refused() {
+    let repo = TestRepo::new();
+    repo.create_markdown("huge.md", &"# Huge\n\n".repeat(1000));
+    repo.create_markdown("small.md", "# Small\n");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.max_render_bytes = 4096;
+    })
+    .await;
+
+    let response = server.get("/huge/").await;
+    assert_eq!(response.status(), 413);
+    let html = response.text().await.unwrap();
+    assert_html_contains(&html, "File Too Large to Preview");
+    assert!(!html.contains(r#"<h1 id="huge">"#));
+
+    let response = server.get("/small/").await;
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_large_markdown_page_is_streamed() {
+    let repo = TestRepo::new();
+    let mut md = String::from("---\ntitle: Big Page\n---\n\n");
+    for i in 0..2000 {
+        md.push_str(&format!("## Part {i}\n\n![Pic {i}](pic{i}.png)\n\n"));
+    }
+    md.push_str("The very end.\n");
+    repo.create_markdown("big.md", &md);
+    repo.create_markdown("small.md", "# Small\n\nShort page.\n");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.stream_render_threshold = 64 * 1024;
+    })
+    .await;
+
+    let response = server.get("/big/").await;
+    assert_eq!(response.status(), 200);
+    // Streamed pages can't be hashed up front
+    assert!(response.headers().get("etag").is_none());
+    assert!(response.headers().get("last-modified").is_some());
+    let html = response.text().await.unwrap();
+    assert_html_contains(&html, "<title>Big Page</title>");
+    assert_html_contains(&html, r#"<h2 id="part-1999">Part 1999</h2>"#);
+    assert_html_contains(&html, "The very end.");
+    assert!(html.trim_end().ends_with("</html>"));
+    assert!(!html.contains("mbr-stream-body"));
+    // Only the first image on the whole page stays eager
+    assert_html_contains(&html, r#"<img src="../pic0.png" alt="Pic 0" />"#);
+    assert_eq!(html.matches(r#"loading="lazy""#).count(), 1999);
+
+    // Pages under the threshold keep the buffered path
+    let response = server.get("/small/").await;
+    assert!(response.headers().get("etag").is_some());
+}
+
+#[tokio::test]
+async fn test_renders_beyond_concurrency_limit_wait_their_turn() {
+    let repo = TestRepo::new();
+    let mut big = String::from("# Big\n\n");
+    for i in 0..2000 {
+        big.push_str(&format!("Paragraph {i} of the big page.\n\n"));
+    }
+    repo.create_markdown("big.md", &big);
+    for i in 0..6 {
+        repo.create_markdown(&format!("page{i}.md"), &format!("# Page {i}\n"));
+    }
+    repo.create_static_file("static/notes.txt", b"plain text");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.server_render_concurrency = 1;
+        config.stream_render_threshold = 16 * 1024;
+    })
+    .await;
+
+    let mut paths: Vec<String> = (0..6).map(|i| format!("/page{i}/")).collect();
+    paths.push("/big/".to_string());
+    paths.push("/big/".to_string());
+    let responses = futures::future::join_all(paths.iter().map(|path| server.get_text(path))).await;
+    for (i, html) in responses[..6].iter().enumerate() {
+        assert_html_contains(html, &format!(r#"<h1 id="page-{i}">Page {i}</h1>"#));
+    }
+    for html in &responses[6..] {
+        assert_html_contains(html, "Paragraph 1999 of the big page.");
+    }
+
+    // Static files don't need a render slot
+    let response = server.get("/notes.txt").await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "plain text");
+}
+
+/// Post-render hook that records how many renders ran at once.
+#[derive(Default)]
+struct ConcurrencyProbe {
+    in_flight: std::sync::atomic::AtomicUsize,
+    max_in_flight: std::sync::atomic::AtomicUsize,
+}
+
+impl mbr::post_render::PostRenderHook for ConcurrencyProbe {
+    fn apply(&self, html: String, _page: &mbr::post_render::PostRenderPage<'_>) -> String {
+        use std::sync::atomic::Ordering;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(100));
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        html
+    }
+}
+
+/// Requests four pages at once with `server_render_concurrency` set to
+/// `limit` and returns the most renders seen running together.
+async fn max_concurrent_renders(limit: usize) -> usize {
+    let repo = TestRepo::new();
+    for i in 0..4 {
+        repo.create_markdown(&format!("page{i}.md"), &format!("# Page {i}\n"));
+    }
+    let probe = std::sync::Arc::new(ConcurrencyProbe::default());
+    let hook = probe.clone();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.server_render_concurrency = limit;
+        config.post_render_hooks.push(hook);
+    })
+    .await;
+
+    let paths: Vec<String> = (0..4).map(|i| format!("/page{i}/")).collect();
+    let responses = futures::future::join_all(paths.iter().map(|path| server.get(path))).await;
+    for response in responses {
+        assert_eq!(response.status(), 200);
+    }
+    probe
+        .max_in_flight
+        .load(std::sync::atomic::Ordering::SeqCst)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_render_concurrency_limit_serializes_renders() {
+    assert_eq!(max_concurrent_renders(1).await, 1);
+    assert!(
+        max_concurrent_renders(4).await > 1,
+        "renders overlap when the limit allows it"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_stalled_stream_releases_render_slot() {
+    let repo = TestRepo::new();
+    let mut big = String::from("# Big\n\n");
+    for i in 0..200_000 {
+        big.push_str(&format!("Paragraph {i} of the big page.\n\n"));
+    }
+    repo.create_markdown("big.md", &big);
+    repo.create_markdown("small.md", "# Small\n");
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.server_render_concurrency = 1;
+        config.stream_render_threshold = 16 * 1024;
+    })
+    .await;
+
+    // Take the headers but never read the body
+    let stalled = server.get("/big/").await;
+    assert_eq!(stalled.status(), 200);
+
+    let html = tokio::time::timeout(Duration::from_secs(30), server.get_text("/small/"))
+        .await
+        .expect("the render slot is freed once the big page has rendered");
+    assert_html_contains(&html, r#"<h1 id="small">Small</h1>"#);
+    drop(stalled);
+}
+
+/// Builds `repo` into `prebuilt/` as a server cache, then rewrites each of
+/// `pages` with `Live copy.` while keeping its pre-build mtime, so a response
+/// saying `Built copy.` can only have come from the cache.
+async fn build_server_cache(repo: &TestRepo, pages: &[&str], clean_urls: bool) -> PathBuf {
+    let cache_dir = repo.path().join("prebuilt");
+    let before_build = filetime::FileTime::from_system_time(
+        std::time::SystemTime::now() - Duration::from_secs(7200),
+    );
+    for page in pages {
+        let path = repo.create_markdown(page, "# Page\n\nBuilt copy.\n");
+        filetime::set_file_mtime(path, before_build).unwrap();
+    }
+
+    let config = mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        server_cache_dir: Some(PathBuf::from("prebuilt")),
+        clean_urls,
+        skip_search_index: true,
+        ..Default::default()
+    };
+    mbr::build::Builder::new(config, cache_dir.clone())
+        .expect("Failed to create builder")
+        .build()
+        .await
+        .expect("Build failed");
+
+    for page in pages {
+        let path = repo.create_markdown(page, "# Page\n\nLive copy.\n");
+        filetime::set_file_mtime(path, before_build).unwrap();
+    }
+    cache_dir
+}
+
+#[tokio::test]
+async fn test_server_cache_dir_serves_unchanged_pages() {
+    let repo = TestRepo::new();
+    let cache_dir = build_server_cache(&repo, &["docs/unchanged.md", "docs/edited.md"], true).await;
+    assert!(cache_dir.join(".mbr/server-cache.json").exists());
+    // Saved since the build
+    repo.create_markdown("docs/edited.md", "# Edited\n\nLive copy.\n");
+    repo.create_markdown("new.md", "# New\n\nLive copy.\n");
+
+    let cache_path = cache_dir.clone();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.server_cache_dir = Some(cache_path);
+    })
+    .await;
+
+    let response = server.get("/docs/unchanged/").await;
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("etag").is_some());
+    let html = response.text().await.unwrap();
+    assert_html_contains(&html, "Built copy.");
+    // Rendered for the server: live reload, server search, absolute assets
+    assert!(html.contains("<mbr-live-reload>"), "{html}");
+    assert!(html.contains(r#""server_mode":"true""#), "{html}");
+    assert!(!html.contains("../../.mbr/"), "{html}");
+
+    // Newer sources and pages missing from the build render live
+    assert_html_contains(&server.get_text("/docs/edited/").await, "Live copy.");
+    assert_html_contains(&server.get_text("/new/").await, "Live copy.");
+
+    // A settings change makes every cached page stale
+    std::fs::write(repo.path().join(".mbr/theme.css"), "body {}").unwrap();
+    let future = filetime::FileTime::from_system_time(
+        std::time::SystemTime::now() + Duration::from_secs(60),
+    );
+    filetime::set_file_mtime(repo.path().join(".mbr/theme.css"), future).unwrap();
+    assert_html_contains(&server.get_text("/docs/unchanged/").await, "Live copy.");
+}
+
+#[tokio::test]
+async fn test_server_cache_dir_without_clean_urls() {
+    let repo = TestRepo::new();
+    let cache_dir = build_server_cache(&repo, &["docs/page.md"], false).await;
+    assert!(cache_dir.join("docs/page.html").exists());
+
+    let cache_path = cache_dir.clone();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.server_cache_dir = Some(cache_path);
+    })
+    .await;
+    assert_html_contains(&server.get_text("/docs/page/").await, "Built copy.");
+}
+
+#[tokio::test]
+async fn test_server_cache_dir_ignores_static_builds() {
     let repo = TestRepo::new();
-    repo.create_markdown("readme.md", "# Hello");
-
-    // Create a custom CSS file in .mbr directory
-    repo.create_dir(".mbr");
-    repo.create_static_file(".mbr/user.css", b"body { color: red; }");
-
-    let server = TestServer::start(&repo).await;
-
-    // Valid .mbr asset request should still work
-    let response = server.get("/.mbr/user.css").await;
-    assert_eq!(
-        response.status(),
-        200,
-        "Valid .mbr asset should return 200 OK"
-    );
+    let cache_dir = build_server_cache(&repo, &["docs/page.md"], true).await;
+    // Without the marker the folder is just a static site
+    std::fs::remove_file(cache_dir.join(".mbr/server-cache.json")).unwrap();
 
-    let content = response.text().await.unwrap();
-    assert!(
-        content.contains("body { color: red; }"),
-        "Should return the correct CSS content"
-    );
+    let cache_path = cache_dir.clone();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.server_cache_dir = Some(cache_path);
+    })
+    .await;
+    assert_html_contains(&server.get_text("/docs/page/").await, "Live copy.");
 }
 
 #[tokio::test]
-async fn test_mbr_assets_nested_path_works() {
+async fn test_custom_favicon_and_manifest() {
     let repo = TestRepo::new();
-    repo.create_markdown("readme.md", "# Hello");
+    repo.create_markdown("docs/page.md", "# Page\n");
+    let logo = repo.path().join("logo.png");
+    image::RgbaImage::from_pixel(256, 256, image::Rgba([20, 80, 160, 255]))
+        .save(&logo)
+        .unwrap();
+    let branding = mbr::favicon::SiteBranding::from_config(&mbr::Config {
+        root_dir: repo.path().to_path_buf(),
+        favicon: Some("logo.png".to_string()),
+        site_name: Some("Field Notes".to_string()),
+        theme_color: Some("#1450a0".to_string()),
+        ..Default::default()
+    });
 
-    // Create nested directory structure in .mbr
-    repo.create_dir(".mbr/custom/styles");
-    repo.create_static_file(
-        ".mbr/custom/styles/theme.css",
-        b".theme { display: block; }",
-    );
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.branding = branding;
+    })
+    .await;
 
-    let server = TestServer::start(&repo).await;
+    let html = server.get_text("/docs/page/").await;
+    assert_html_contains(
+        &html,
+        r#"<link rel="icon" type="image/png" href="/favicon.png" />"#,
+    );
+    assert_html_contains(
+        &html,
+        r#"<link rel="apple-touch-icon" href="/.mbr/icons/icon-180.png" />"#,
+    );
+    assert_html_contains(
+        &html,
+        r#"<link rel="manifest" href="/manifest.webmanifest" />"#,
+    );
+    assert_html_contains(&html, r##"<meta name="theme-color" content="#1450a0" />"##);
+    assert!(!html.contains(".mbr/favicon.png"));
+
+    let png = std::fs::read(&logo).unwrap();
+    for path in ["/favicon.ico", "/favicon.png"] {
+        let response = server.get(path).await;
+        assert_eq!(response.status(), 200, "{path}");
+        assert_eq!(response.bytes().await.unwrap().as_ref(), png.as_slice());
+    }
 
-    // Nested path should work
-    let response = server.get("/.mbr/custom/styles/theme.css").await;
+    let response = server.get("/manifest.webmanifest").await;
     assert_eq!(
-        response.status(),
-        200,
-        "Nested .mbr asset path should return 200 OK"
+        response.headers().get("content-type").unwrap(),
+        "application/manifest+json"
     );
+    let manifest: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(manifest["name"], "Field Notes");
+
+    let response = server.get("/.mbr/icons/icon-192.png").await;
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().contains_key("last-modified"));
+    let etag = response.headers()["etag"].clone();
+    let icon = image::load_from_memory(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!((icon.width(), icon.height()), (192, 192));
+    let response = server
+        .client
+        .get(server.url("/.mbr/icons/icon-192.png"))
+        .header("if-none-match", etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 304);
+    // Larger than the source image, so never generated
+    assert_eq!(server.get("/.mbr/icons/icon-512.png").await.status(), 404);
 }
 
 #[tokio::test]
-async fn test_code_blocks_with_unsupported_language_render_correctly() {
+async fn test_default_favicon_without_branding() {
     let repo = TestRepo::new();
-    let markdown = concat!(
-        "# Code Examples\n\n",
-        "```rust\nfn hello() { println!(\"hi\"); }\n```\n\n",
-        "```totally_bogus_lang\nsome code here\n```\n\n",
-        "```python\ndef hello(): pass\n```",
-    );
-    repo.create_markdown("code.md", markdown);
-
+    repo.create_markdown("page.md", "# Page\n");
     let server = TestServer::start(&repo).await;
-    let html = server.get_text("/code/").await;
 
-    // Page renders successfully with all code blocks
-    assert_html_contains(&html, "Code Examples");
-    assert_html_contains(&html, "language-rust");
-    assert_html_contains(&html, "language-totally_bogus_lang");
-    assert_html_contains(&html, "language-python");
+    let html = server.get_text("/page/").await;
+    assert_html_contains(&html, r#"href="&#x2F;.mbr&#x2F;favicon.png""#);
+    assert!(!html.contains("rel=\"manifest\""));
+    assert_eq!(server.get("/manifest.webmanifest").await.status(), 404);
+}
 
-    // Code content is preserved
-    assert_html_contains(&html, "fn hello");
-    assert_html_contains(&html, "some code here");
-    assert_html_contains(&html, "def hello");
+#[tokio::test]
+async fn test_robots_txt_generated_unless_provided() {
+    let repo = TestRepo::new();
+    repo.create_markdown("page.md", "# Page\n");
+    repo.create_static_file("sitemap.xml", b"<urlset/>");
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.base_url = Some("https://notes.example.com".to_string());
+    })
+    .await;
 
-    // hljs component is present so client-side highlighting can proceed
-    assert_html_contains(&html, "mbr-hljs");
-}
+    let response = server.get("/robots.txt").await;
+    assert_eq!(response.status(), 200);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain")
+    );
+    assert_eq!(
+        response.text().await.unwrap(),
+        "User-agent: *\nAllow: /\n\nSitemap: https://notes.example.com/sitemap.xml\n"
+    );
 
-// ==================== Title Prefix/Suffix Tests ====================
+    repo.create_static_file("robots.txt", b"User-agent: *\nDisallow: /\n");
+    assert_eq!(
+        server.get_text("/robots.txt").await,
+        "User-agent: *\nDisallow: /\n"
+    );
+}
 
 #[tokio::test]
-async fn test_title_prefix_in_markdown_page() {
+async fn test_content_security_policy_header_and_nonce() {
     let repo = TestRepo::new();
-    repo.create_markdown(
-        "readme.md",
-        "---\ntitle: My Page\n---\n\n# My Page\n\nContent.",
+    repo.create_markdown("page.md", "# Page\n");
+    let csp = mbr::csp::ContentSecurityPolicy::new(
+        "default-src 'self'; script-src 'self' 'nonce-{nonce}'",
+        "f00d".to_string(),
     );
-
-    let server = TestServer::start_with_config_fn(&repo, |config| {
-        config.title_prefix = "My Site: ".to_string();
+    let server = TestServer::start_with_config_fn(&repo, move |config| {
+        config.csp = Some(csp);
     })
     .await;
 
-    let html = server.get_text("/readme/").await;
-    assert_html_contains(&html, "<title>My Site: My Page</title>");
+    let response = server.get("/page/").await;
+    assert_eq!(
+        response.headers()["content-security-policy"],
+        "default-src 'self'; script-src 'self' 'nonce-f00d'"
+    );
+    let html = response.text().await.unwrap();
+    assert_html_contains(&html, r#"<script nonce="f00d">"#);
+    // The server sends the policy as a header only
+    assert!(!html.contains("http-equiv=\"Content-Security-Policy\""));
+
+    // Error pages carry the nonce too
+    let missing = server.get_text("/missing/").await;
+    assert_html_contains(&missing, r#"<script nonce="f00d">"#);
+    assert!(!missing.contains("onclick="));
 }
 
 #[tokio::test]
@@ -3247,6 +4728,8 @@ async fn test_search_tag_pages_with_custom_source() {
             field: "performer".to_string(),
             label: Some("Performer".to_string()),
             label_plural: Some("Performers".to_string()),
+            fields: vec![],
+            data: None,
         }];
     })
     .await;
@@ -3283,6 +4766,91 @@ async fn test_search_tag_pages_with_custom_source() {
     );
 }
 
+#[tokio::test]
+async fn test_tag_source_merges_multiple_fields() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "keyworded.md",
+        "---\ntitle: Keyworded Page\nkeywords: [x]\n---\n\nContent.",
+    );
+    repo.create_markdown(
+        "tagged.md",
+        "---\ntitle: Tagged Page\ntags: [x]\n---\n\nContent.",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.tag_sources = vec![mbr::config::TagSource {
+            field: "tags".to_string(),
+            label: None,
+            label_plural: None,
+            fields: vec!["keywords".to_string()],
+            data: None,
+        }];
+    })
+    .await;
+    server.wait_for_scan().await;
+
+    let html = server.get_text("/tags/x/").await;
+    assert_html_contains(&html, "Keyworded Page");
+    assert_html_contains(&html, "Tagged Page");
+
+    let response = server.get("/keywords/x/").await;
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_author_page_shows_metadata() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "post.md",
+        "---\ntitle: A Post\nauthor: Jane Doe\n---\n\nContent.",
+    );
+    repo.create_static_file(
+        "data/authors.yml",
+        b"Jane Doe:\n  bio: Writes about typography.\n  avatar: /images/jane.jpg\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.data_folder = Some("data".to_string());
+        config.tag_sources = vec![mbr::config::TagSource {
+            field: "authors".to_string(),
+            label: Some("Author".to_string()),
+            label_plural: None,
+            fields: vec!["author".to_string()],
+            data: Some("authors".to_string()),
+        }];
+    })
+    .await;
+    server.wait_for_scan().await;
+
+    let html = server.get_text("/authors/jane_doe/").await;
+    assert_html_contains(&html, "Writes about typography.");
+    assert_html_contains(&html, r#"src="&#x2F;images&#x2F;jane.jpg""#);
+    assert_html_contains(&html, "A Post");
+    assert_html_contains(&html, "1 page by Jane Doe");
+}
+
+#[tokio::test]
+async fn test_grid_listing_shows_cover_images() {
+    let repo = TestRepo::new();
+    repo.create_markdown(
+        "posts/first.md",
+        "# First\n\nIntro.\n\n![Hero](../images/hero.png)\n",
+    );
+
+    let server = TestServer::start_with_config_fn(&repo, |config| {
+        config.listing_layout = "grid".to_string();
+    })
+    .await;
+
+    let html = server.get_text("/posts/").await;
+    assert_html_contains(&html, r#"class="mbr-listing mbr-listing-grid""#);
+    assert_html_contains(
+        &html,
+        r#"class="mbr-listing-cover" src="&#x2F;images&#x2F;hero.png""#,
+    );
+}
+
 #[tokio::test]
 async fn test_search_tag_pages_skipped_for_folder_scope() {
     let repo = TestRepo::new();
@@ -3598,6 +5166,44 @@ async fn test_hls_traversal_blocked() {
     }
 }
 
+/// The transcode status endpoint reports probe failures as structured errors
+/// rather than failing the request.
+#[cfg(feature = "media-metadata")]
+#[tokio::test]
+async fn test_transcode_status_reports_probe_errors() {
+    let repo = TestRepo::new();
+    repo.create_static_file("videos/broken.mp4", b"not really a video");
+
+    let server = TestServer::start(&repo).await;
+
+    let response = server
+        .get("/.mbr/transcode-status?path=/videos/broken.mp4")
+        .await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["path"], "/videos/broken.mp4");
+    assert_eq!(body["transcode_enabled"], false);
+    assert_eq!(body["transcodable"], false);
+    assert!(body["resolution"].is_null());
+    assert_eq!(body["targets"], serde_json::json!([]));
+    assert!(
+        body["error"]["error"].is_string() && body["error"]["message"].is_string(),
+        "probe failure should be reported as {{error, message}}, got: {body}"
+    );
+
+    let response = server.get("/.mbr/transcode-status").await;
+    assert_eq!(response.status(), 400);
+
+    let response = server
+        .get("/.mbr/transcode-status?path=/videos/missing.mp4")
+        .await;
+    assert_eq!(response.status(), 404);
+}
+
 // ==================== Editing endpoint tests ====================
 
 /// Enables editing on a loopback test server.
@@ -4055,3 +5661,156 @@ async fn test_body_wikilink_resolves_globally_across_folders() {
         "unresolved [[Totally Missing]] must be reported broken: {errors:?}"
     );
 }
+
+// ============================================================================
+// Serve-build (static) mode tests
+// ============================================================================
+
+impl TestServer {
+    /// Starts a plain static server over an already-built site directory.
+    async fn start_static(root_dir: PathBuf) -> Self {
+        let port = find_available_port();
+
+        let handle = tokio::spawn(async move {
+            let server = mbr::server::Server::init_static(
+                [127, 0, 0, 1],
+                port,
+                root_dir,
+                &std::collections::HashMap::new(),
+            );
+            server.start().await.expect("test server failed to start");
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = mbr::http_client(Duration::from_secs(5));
+
+        Self {
+            port,
+            client,
+            _handle: handle,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_serve_build_serves_index_and_files() {
+    let site = TestRepo::new();
+    site.create_static_file("index.html", b"<h1>Home</h1>");
+    site.create_static_file("docs/guide/index.html", b"<h1>Guide</h1>");
+    site.create_static_file("style.css", b"body {}");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+
+    let home = server.get("/").await;
+    assert_eq!(home.status(), 200);
+    assert!(home.text().await.unwrap().contains("Home"));
+
+    let guide = server.get("/docs/guide/").await;
+    assert_eq!(guide.status(), 200);
+    assert!(guide.text().await.unwrap().contains("Guide"));
+
+    let css = server.get("/style.css").await;
+    assert_eq!(css.status(), 200);
+    assert!(
+        css.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/css")
+    );
+}
+
+#[tokio::test]
+async fn test_serve_build_redirects_directory_without_slash() {
+    let site = TestRepo::new();
+    site.create_static_file("docs/index.html", b"<h1>Docs</h1>");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    let response = client.get(server.url("/docs")).send().await.unwrap();
+    assert_eq!(response.status(), 301);
+    assert_eq!(response.headers()["location"], "/docs/");
+}
+
+#[tokio::test]
+async fn test_serve_build_range_request() {
+    let site = TestRepo::new();
+    site.create_static_file("media/clip.bin", b"0123456789ABCDEFGHIJ");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+    let response = server
+        .client
+        .get(server.url("/media/clip.bin"))
+        .header("Range", "bytes=5-9")
+        .send()
+        .await
+        .expect("Request failed");
+
+    assert_eq!(response.status(), 206);
+    assert_eq!(response.bytes().await.unwrap().as_ref(), b"56789");
+}
+
+#[tokio::test]
+async fn test_serve_build_caches_search_assets() {
+    let site = TestRepo::new();
+    site.create_static_file(".mbr/pagefind/pagefind.js", b"export {}");
+    site.create_static_file(".mbr/pagefind/fragment/en_1a2b3c.pf_fragment", b"frag");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+
+    let hashed = server
+        .get("/.mbr/pagefind/fragment/en_1a2b3c.pf_fragment")
+        .await;
+    assert_eq!(hashed.status(), 200);
+    assert_eq!(
+        hashed.headers()["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+
+    let entry = server.get("/.mbr/pagefind/pagefind.js").await;
+    assert_eq!(entry.status(), 200);
+    assert_eq!(entry.headers()["cache-control"], "no-cache");
+    let etag = entry.headers()["etag"].clone();
+
+    let revalidated = server
+        .client
+        .get(server.url("/.mbr/pagefind/pagefind.js"))
+        .header("If-None-Match", etag.clone())
+        .send()
+        .await
+        .expect("Request failed");
+    assert_eq!(revalidated.status(), 304);
+    assert_eq!(revalidated.headers()["etag"], etag);
+}
+
+#[tokio::test]
+async fn test_serve_build_uses_custom_404_page() {
+    let site = TestRepo::new();
+    site.create_static_file("404.html", b"<h1>Custom missing page</h1>");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+    let response = server.get("/nope/").await;
+
+    assert_eq!(response.status(), 404);
+    assert!(
+        response
+            .text()
+            .await
+            .unwrap()
+            .contains("Custom missing page")
+    );
+}
+
+#[tokio::test]
+async fn test_serve_build_plain_404_without_custom_page() {
+    let site = TestRepo::new();
+    site.create_static_file("index.html", b"<h1>Home</h1>");
+
+    let server = TestServer::start_static(site.path().to_path_buf()).await;
+    assert_eq!(server.get("/missing.txt").await.status(), 404);
+    assert_eq!(server.get("/../etc/passwd").await.status(), 404);
+}